@@ -0,0 +1,14 @@
+//! Feeds arbitrary model output to `ResponseParser::parse`.
+//!
+//! Run with `cargo +nightly fuzz run parse_response`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use nexus::executor::ResponseParser;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(response) = std::str::from_utf8(data) {
+        let _ = ResponseParser::new().parse(response, "run_fuzz");
+    }
+});
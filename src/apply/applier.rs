@@ -0,0 +1,1338 @@
+//! Writing approved actions to disk.
+//!
+//! An [`Applier`] edits the working tree by default. With a staging
+//! directory it copies each affected file there on first touch and edits the
+//! copy instead, so results can be inspected (or tested) before an in-place
+//! apply. Every file of an action is computed before any is written, so a
+//! failing hunk never leaves an action half-applied.
+//!
+//! A patch that records `base_file_sha256` is refused if one of those files
+//! changed since the proposal, unless the change was made by an earlier
+//! action of the same applier.
+//!
+//! A hunk or search/replace block that does not apply is settled by the
+//! patch's `on_conflict` strategy: `fail` refuses the action, `ours` leaves
+//! that part of the file alone, `theirs` writes the proposed lines over the
+//! most similar ones, and `marker` writes both between conflict markers and
+//! reports the file as [`conflicted`](AppliedChanges::conflicted).
+//!
+//! With `fallback_strategy: fuzzy`, a hunk or block is first tried against
+//! the most similar lines, and applied there if they are at least
+//! `fuzzy_threshold` similar; the lowest similarity accepted is reported as
+//! the [`match_confidence`](AppliedChanges::match_confidence).
+//!
+//! [`Applier::check`] runs the same matching without writing anything, so
+//! patches can be checked before anyone approves them.
+//!
+//! Besides patches, the applier handles file create, rename, and delete
+//! actions, honoring their `overwrite`, `ignore_if_exists`, `recursive`, and
+//! `ignore_if_missing` flags. Agenda patches are unified diffs that may only
+//! edit their `target_path`, and plan patches rewrite a stored plan; see
+//! [`crate::plan::patch`].
+//!
+//! A file whose current content looks binary is never written, unless the
+//! applier's [`BinaryGuard`] allows its path.
+//!
+//! Each action's changes come with the prior contents of the files it
+//! touched, so the action can be undone later. With [`RunBackups`], those
+//! contents are also copied aside before the files are touched.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::binary::BinaryGuard;
+use crate::error::NexusError;
+use crate::event_log::payload::sha256_hex;
+use crate::paths::{is_absolute_any_platform, normalize_separators};
+use crate::plan::patch::{patch_plan, patch_ref_path};
+use crate::plan::plan_path;
+use crate::types::{
+    ActionDetails, AgendaPatchDetails, FallbackStrategy, FileCreateDetails, FileDeleteDetails,
+    FileRenameDetails, OnConflict, PatchDetails, PatchFormat, PlanPatchDetails, ProposedAction,
+    SearchReplaceBlock,
+};
+
+use super::backup::RunBackups;
+use super::collect_files;
+use super::conflict::{Conflict, DEFAULT_FUZZY_THRESHOLD};
+use super::matcher::{MatchOptions, NormalizationNote};
+use super::search_replace::resolve_block;
+use super::unified::{Patched, agenda_diff, first_conflict, parse_unified, resolve_hunks};
+
+/// New content per path, in order; `None` deletes the file.
+type Updates = Vec<(String, Option<String>)>;
+
+/// The part of a patch action the applier works from.
+enum PatchSource<'a> {
+    Diff(&'a str),
+    Blocks(&'a [SearchReplaceBlock]),
+    WholeFile(&'a HashMap<String, String>),
+}
+
+fn patch_source(details: &PatchDetails) -> Option<PatchSource<'_>> {
+    let diff = details.diff.as_deref().map(PatchSource::Diff);
+    let blocks = details
+        .search_replace_blocks
+        .as_deref()
+        .filter(|blocks| !blocks.is_empty())
+        .map(PatchSource::Blocks);
+    let whole_file = details
+        .whole_file_content
+        .as_ref()
+        .filter(|contents| !contents.is_empty())
+        .map(PatchSource::WholeFile);
+    match details.format {
+        PatchFormat::Unified => diff.or(blocks),
+        PatchFormat::SearchReplace => blocks.or(diff),
+        PatchFormat::WholeFile => whole_file,
+    }
+}
+
+/// Files an applied action wrote or removed, relative to the project root.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AppliedChanges {
+    pub written: Vec<String>,
+    pub deleted: Vec<String>,
+    /// Content of each changed path before the action, `None` if it did
+    /// not exist.
+    pub before: Vec<(String, Option<Vec<u8>>)>,
+    /// Written files left with conflict markers to resolve by hand.
+    pub conflicted: Vec<String>,
+    /// Lowest similarity of the lines a hunk or block was fuzzily applied
+    /// to; `None` if everything matched exactly.
+    pub match_confidence: Option<f64>,
+    /// Files whose search text was found only after Unicode normalization,
+    /// with what was normalized.
+    pub normalized: Vec<(String, NormalizationNote)>,
+}
+
+/// Whether a patch applies to the current files as written.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PatchCheck {
+    pub applies_cleanly: bool,
+    /// The first hunk or block that does not apply, and why.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failing_hunk: Option<String>,
+}
+
+impl AppliedChanges {
+    /// Notes how patching `path` went.
+    fn note(&mut self, path: &str, patched: &Patched) {
+        if patched.marked && !self.conflicted.iter().any(|seen| seen == path) {
+            self.conflicted.push(path.to_string());
+        }
+        if let Some(confidence) = patched.confidence {
+            self.match_confidence = Some(
+                self.match_confidence
+                    .map_or(confidence, |lowest| lowest.min(confidence)),
+            );
+        }
+        if let Some(note) = &patched.normalization {
+            self.normalized.push((path.to_string(), note.clone()));
+        }
+    }
+}
+
+/// Applies actions to the working tree or to a staging directory.
+#[derive(Debug)]
+pub struct Applier {
+    root: PathBuf,
+    stage_dir: Option<PathBuf>,
+    match_options: MatchOptions,
+    backups: Option<RunBackups>,
+    binary_guard: BinaryGuard,
+    /// Staged paths removed by an earlier action; reads must not fall back
+    /// to the working tree for them.
+    staged_deletions: HashSet<String>,
+    /// Paths written or deleted by earlier actions; their base hashes are
+    /// expected to differ.
+    changed: HashSet<String>,
+}
+
+impl Applier {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            stage_dir: None,
+            match_options: MatchOptions::default(),
+            backups: None,
+            binary_guard: BinaryGuard::default(),
+            staged_deletions: HashSet::new(),
+            changed: HashSet::new(),
+        }
+    }
+
+    /// Writes results under `dir` instead of the working tree.
+    pub fn with_stage_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.stage_dir = Some(dir.into());
+        self
+    }
+
+    /// Options for locating search/replace blocks.
+    pub fn with_match_options(mut self, options: MatchOptions) -> Self {
+        self.match_options = options;
+        self
+    }
+
+    /// Lets binary files whose paths `guard` allows be written.
+    pub fn with_binary_guard(mut self, guard: BinaryGuard) -> Self {
+        self.binary_guard = guard;
+        self
+    }
+
+    /// Backs up every existing file before changing it.
+    pub fn with_backups(mut self, backups: RunBackups) -> Self {
+        self.backups = Some(backups);
+        self
+    }
+
+    /// Directory results are written to.
+    pub fn output_root(&self) -> &Path {
+        self.stage_dir.as_deref().unwrap_or(&self.root)
+    }
+
+    /// True if this applier knows how to apply `action`.
+    pub fn supports(action: &ProposedAction) -> bool {
+        match &action.details {
+            ActionDetails::Patch(details) => patch_source(details).is_some(),
+            ActionDetails::AgendaPatch(_)
+            | ActionDetails::FileCreate(_)
+            | ActionDetails::FileRename(_)
+            | ActionDetails::FileDelete(_)
+            | ActionDetails::PlanPatch(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Applies `action`, returning the files it changed.
+    pub fn apply(&mut self, action: &ProposedAction) -> Result<AppliedChanges, NexusError> {
+        self.apply_with(action, None)
+    }
+
+    /// Like [`apply`](Self::apply), but a hunk that does not match exactly
+    /// is applied to the most similar lines if they are at least
+    /// `threshold` similar.
+    pub fn apply_fuzzy(
+        &mut self,
+        action: &ProposedAction,
+        threshold: f64,
+    ) -> Result<AppliedChanges, NexusError> {
+        self.apply_with(action, Some(threshold))
+    }
+
+    /// Hunks of `action` that do not apply to the current files.
+    pub fn conflicts(&self, action: &ProposedAction) -> Result<Vec<Conflict>, NexusError> {
+        let ActionDetails::Patch(details) = &action.details else {
+            return Ok(Vec::new());
+        };
+        let Some(PatchSource::Diff(diff)) = patch_source(details) else {
+            return Ok(Vec::new());
+        };
+        let Ok(patches) = parse_unified(diff) else {
+            return Ok(Vec::new());
+        };
+        let mut conflicts = Vec::new();
+        for patch in &patches {
+            let Some(old) = &patch.old_path else {
+                continue;
+            };
+            let Some(content) = self.read(old)? else {
+                continue;
+            };
+            if let Some(hunk) = first_conflict(&content, &patch.hunks) {
+                conflicts.push(Conflict {
+                    path: patch.path().to_string(),
+                    hunk,
+                });
+            }
+        }
+        Ok(conflicts)
+    }
+
+    /// Checks, without writing anything, whether each patch of `actions`
+    /// applies exactly to the current files once the patches before it
+    /// have. Other actions are not checked and get `None`.
+    pub fn check(&self, actions: &[ProposedAction]) -> Vec<Option<PatchCheck>> {
+        let mut updates = Updates::new();
+        actions
+            .iter()
+            .map(|action| {
+                let ActionDetails::Patch(details) = &action.details else {
+                    return None;
+                };
+                let before = updates.len();
+                let planned =
+                    self.plan_patch(action, details, None, &OnConflict::Fail, &mut updates);
+                Some(match planned {
+                    Ok(_) => PatchCheck {
+                        applies_cleanly: true,
+                        failing_hunk: None,
+                    },
+                    Err(err) => {
+                        updates.truncate(before);
+                        PatchCheck {
+                            applies_cleanly: false,
+                            failing_hunk: Some(match err {
+                                NexusError::PatchFailed { path, reason, .. }
+                                    if !path.as_os_str().is_empty() =>
+                                {
+                                    format!("{}: {reason}", path.display())
+                                }
+                                err => err.to_string(),
+                            }),
+                        }
+                    }
+                })
+            })
+            .collect()
+    }
+
+    fn apply_with(
+        &mut self,
+        action: &ProposedAction,
+        threshold: Option<f64>,
+    ) -> Result<AppliedChanges, NexusError> {
+        let details = match &action.details {
+            ActionDetails::Patch(details) => details,
+            ActionDetails::AgendaPatch(details) => return self.patch_agenda(details, threshold),
+            ActionDetails::FileCreate(details) => return self.create(details),
+            ActionDetails::FileRename(details) => return self.rename(details),
+            ActionDetails::FileDelete(details) => return self.remove(details),
+            ActionDetails::PlanPatch(details) => return self.patch_stored_plan(details),
+            _ => return Err(unsupported(action)),
+        };
+        let threshold = threshold.or_else(|| {
+            (details.fallback_strategy == FallbackStrategy::Fuzzy)
+                .then(|| details.fuzzy_threshold.unwrap_or(DEFAULT_FUZZY_THRESHOLD))
+        });
+        let mut updates = Updates::new();
+        let notes = self.plan_patch(
+            action,
+            details,
+            threshold,
+            &details.on_conflict,
+            &mut updates,
+        )?;
+        self.commit_noted(updates, notes)
+    }
+
+    /// Adds the file contents the patch `details` leads to onto `updates`,
+    /// returning its conflicts and match confidence.
+    fn plan_patch(
+        &self,
+        action: &ProposedAction,
+        details: &PatchDetails,
+        threshold: Option<f64>,
+        on_conflict: &OnConflict,
+        updates: &mut Updates,
+    ) -> Result<AppliedChanges, NexusError> {
+        if let Some(base) = &details.base_file_sha256 {
+            self.verify_base(base, updates)?;
+        }
+        match patch_source(details) {
+            Some(PatchSource::Diff(diff)) => {
+                self.plan_unified(diff, threshold, on_conflict, updates)
+            }
+            Some(PatchSource::Blocks(blocks)) => {
+                self.plan_search_replace(blocks, threshold, on_conflict, updates)
+            }
+            Some(PatchSource::WholeFile(contents)) => {
+                let mut paths: Vec<_> = contents.keys().collect();
+                paths.sort();
+                updates.extend(
+                    paths
+                        .into_iter()
+                        .map(|path| (normalize_separators(path), Some(contents[path].clone()))),
+                );
+                Ok(AppliedChanges::default())
+            }
+            None => Err(unsupported(action)),
+        }
+    }
+
+    fn plan_unified(
+        &self,
+        diff: &str,
+        threshold: Option<f64>,
+        on_conflict: &OnConflict,
+        updates: &mut Updates,
+    ) -> Result<AppliedChanges, NexusError> {
+        let patches = parse_unified(diff).map_err(|reason| NexusError::PatchFailed {
+            path: PathBuf::new(),
+            reason,
+            source: None,
+        })?;
+
+        let mut notes = AppliedChanges::default();
+        for patch in &patches {
+            let failed = |reason: String| NexusError::PatchFailed {
+                path: PathBuf::from(patch.path()),
+                reason,
+                source: None,
+            };
+            let current = match &patch.old_path {
+                Some(old) => self
+                    .current(updates, old)?
+                    .ok_or_else(|| failed("file does not exist".to_string()))?,
+                None => String::new(),
+            };
+            let patched =
+                resolve_hunks(&current, &patch.hunks, threshold, on_conflict).map_err(failed)?;
+            notes.note(patch.path(), &patched);
+            let updated = patched.content;
+            match (&patch.old_path, &patch.new_path) {
+                (_, None) => updates.push((patch.path().to_string(), None)),
+                (Some(old), Some(new)) if old != new => {
+                    updates.push((old.clone(), None));
+                    updates.push((new.clone(), Some(updated)));
+                }
+                (_, Some(new)) => updates.push((new.clone(), Some(updated))),
+            }
+        }
+        Ok(notes)
+    }
+
+    fn plan_search_replace(
+        &self,
+        blocks: &[SearchReplaceBlock],
+        threshold: Option<f64>,
+        on_conflict: &OnConflict,
+        updates: &mut Updates,
+    ) -> Result<AppliedChanges, NexusError> {
+        let mut notes = AppliedChanges::default();
+        for (index, block) in blocks.iter().enumerate() {
+            let path = normalize_separators(&block.file);
+            let failed = |reason: String| NexusError::PatchFailed {
+                path: PathBuf::from(&path),
+                reason: format!("block {}: {reason}", index + 1),
+                source: None,
+            };
+            let current = self
+                .current(updates, &path)?
+                .ok_or_else(|| failed("file does not exist".to_string()))?;
+            let patched =
+                resolve_block(&current, block, self.match_options, threshold, on_conflict)
+                    .map_err(failed)?;
+            notes.note(&path, &patched);
+            updates.push((path, Some(patched.content)));
+        }
+        Ok(notes)
+    }
+
+    /// Fails if a file in `base` no longer hashes to its recorded SHA-256.
+    /// Files changed by earlier actions, or pending in `updates`, are not
+    /// checked.
+    fn verify_base(
+        &self,
+        base: &HashMap<String, String>,
+        updates: &Updates,
+    ) -> Result<(), NexusError> {
+        let mut entries: Vec<_> = base.iter().collect();
+        entries.sort();
+        for (path, expected) in entries {
+            let path = normalize_separators(path);
+            if self.changed.contains(&path) || updates.iter().any(|(pending, _)| *pending == path) {
+                continue;
+            }
+            let actual = self
+                .read(&path)?
+                .map(|current| sha256_hex(current.as_bytes()));
+            if actual.as_deref() != Some(expected.as_str()) {
+                let reason = match actual {
+                    Some(actual) => format!(
+                        "file changed since proposal (expected sha256 {expected}, found {actual})"
+                    ),
+                    None => "file changed since proposal (it no longer exists)".to_string(),
+                };
+                return Err(file_failed(&path, &reason));
+            }
+        }
+        Ok(())
+    }
+
+    fn create(&mut self, details: &FileCreateDetails) -> Result<AppliedChanges, NexusError> {
+        let path = normalize_separators(&details.path);
+        if self.exists(&path)? {
+            if details.ignore_if_exists {
+                return Ok(AppliedChanges::default());
+            }
+            if !details.overwrite || self.is_dir(&path)? {
+                return Err(file_failed(&path, "file already exists"));
+            }
+        }
+        self.commit(vec![(path, Some(details.content.clone()))])
+    }
+
+    fn rename(&mut self, details: &FileRenameDetails) -> Result<AppliedChanges, NexusError> {
+        let old_path = normalize_separators(&details.old_path);
+        let new_path = normalize_separators(&details.new_path);
+        if self.is_dir(&old_path)? {
+            return Err(file_failed(
+                &old_path,
+                "renaming directories is not supported",
+            ));
+        }
+        let content = self
+            .read(&old_path)?
+            .ok_or_else(|| file_failed(&old_path, "file does not exist"))?;
+        if old_path == new_path {
+            return Ok(AppliedChanges::default());
+        }
+        if self.exists(&new_path)? && (!details.overwrite || self.is_dir(&new_path)?) {
+            return Err(file_failed(&new_path, "rename target already exists"));
+        }
+        self.commit(vec![(old_path, None), (new_path, Some(content))])
+    }
+
+    fn remove(&mut self, details: &FileDeleteDetails) -> Result<AppliedChanges, NexusError> {
+        let path = normalize_separators(&details.path);
+        if !self.exists(&path)? {
+            if details.ignore_if_missing {
+                return Ok(AppliedChanges::default());
+            }
+            return Err(file_failed(&path, "file does not exist"));
+        }
+        if !self.is_dir(&path)? {
+            return self.commit(vec![(path, None)]);
+        }
+        if !details.recursive {
+            return Err(file_failed(
+                &path,
+                "is a directory; set recursive to delete it",
+            ));
+        }
+
+        let updates = self
+            .files_under(&path)?
+            .into_iter()
+            .map(|file| (file, None))
+            .collect();
+        let changes = self.commit(updates)?;
+        let target = resolve(self.output_root(), &path)?;
+        match std::fs::remove_dir_all(&target) {
+            Ok(()) => Ok(changes),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(changes),
+            Err(err) => Err(NexusError::IoError {
+                operation: "delete directory".to_string(),
+                path: target,
+                source: err,
+            }),
+        }
+    }
+
+    fn patch_agenda(
+        &mut self,
+        details: &AgendaPatchDetails,
+        threshold: Option<f64>,
+    ) -> Result<AppliedChanges, NexusError> {
+        let target = normalize_separators(&details.target_path);
+        let mut updates = Updates::new();
+        let notes = self.plan_unified(
+            &agenda_diff(details),
+            threshold,
+            &OnConflict::Fail,
+            &mut updates,
+        )?;
+        if let Some((path, _)) = updates
+            .iter()
+            .find(|(path, content)| *path != target || content.is_none())
+        {
+            return Err(file_failed(
+                path,
+                &format!("an agenda patch may only edit {target}"),
+            ));
+        }
+        self.commit_noted(updates, notes)
+    }
+
+    fn patch_stored_plan(
+        &mut self,
+        details: &PlanPatchDetails,
+    ) -> Result<AppliedChanges, NexusError> {
+        let path = plan_path(&details.plan_id)?;
+        let plan = self
+            .read(&path)?
+            .ok_or_else(|| file_failed(&path, "plan does not exist"))?;
+        let patch_path = patch_ref_path(&details.patch_ref);
+        let patch = self
+            .read(&patch_path)?
+            .ok_or_else(|| file_failed(&patch_path, "patch document does not exist"))?;
+        let patched = patch_plan(&plan, &patch, &details.patch_mode)
+            .map_err(|reason| file_failed(&path, &reason))?;
+        self.commit(vec![(path, Some(serde_json::to_string_pretty(&patched)?))])
+    }
+
+    /// True if `path` exists as a file or directory, preferring the staged
+    /// copy.
+    fn exists(&self, path: &str) -> Result<bool, NexusError> {
+        Ok(self.is_dir(path)? || self.read(path)?.is_some())
+    }
+
+    fn is_dir(&self, path: &str) -> Result<bool, NexusError> {
+        let mut candidates = Vec::new();
+        if let Some(stage_dir) = &self.stage_dir {
+            candidates.push(resolve(stage_dir, path)?);
+        }
+        candidates.push(resolve(&self.root, path)?);
+        Ok(candidates.iter().any(|candidate| candidate.is_dir()))
+    }
+
+    /// Files under the directory `path`, in the working tree and the stage
+    /// directory, minus those already deleted from the stage.
+    fn files_under(&self, path: &str) -> Result<Vec<String>, NexusError> {
+        let mut files = Vec::new();
+        let bases = std::iter::once(&self.root).chain(self.stage_dir.as_ref());
+        for base in bases {
+            collect_files(
+                base,
+                &resolve(base, path)?,
+                "read directory to delete",
+                &mut files,
+            )?;
+        }
+        files.retain(|file| !self.staged_deletions.contains(file));
+        files.sort();
+        files.dedup();
+        Ok(files)
+    }
+
+    /// Content of `path` after the not yet written `updates`.
+    fn current(&self, updates: &Updates, path: &str) -> Result<Option<String>, NexusError> {
+        match updates.iter().rev().find(|(pending, _)| pending == path) {
+            Some((_, content)) => Ok(content.clone()),
+            None => self.read(path),
+        }
+    }
+
+    /// Writes `updates` once every path is known to be valid.
+    fn commit(&mut self, updates: Updates) -> Result<AppliedChanges, NexusError> {
+        for (path, content) in &updates {
+            resolve(self.output_root(), path)?;
+            if content.is_some() {
+                self.check_not_binary(path)?;
+            }
+        }
+        let mut changes = AppliedChanges::default();
+        for (path, content) in updates {
+            if !changes.before.iter().any(|(seen, _)| *seen == path) {
+                let before = self.read_bytes(&path)?;
+                if let (Some(backups), Some(bytes)) = (&self.backups, &before) {
+                    backups.save(&path, bytes)?;
+                }
+                changes.before.push((path.clone(), before));
+            }
+            self.changed.insert(path.clone());
+            match content {
+                Some(content) => {
+                    self.write(&path, &content)?;
+                    changes.deleted.retain(|deleted| *deleted != path);
+                    if !changes.written.contains(&path) {
+                        changes.written.push(path);
+                    }
+                }
+                None => {
+                    self.delete(&path)?;
+                    changes.written.retain(|written| *written != path);
+                    changes.deleted.push(path);
+                }
+            }
+        }
+        Ok(changes)
+    }
+
+    /// Like [`commit`](Self::commit), carrying over the conflicts and
+    /// match confidence in `notes`.
+    fn commit_noted(
+        &mut self,
+        updates: Updates,
+        notes: AppliedChanges,
+    ) -> Result<AppliedChanges, NexusError> {
+        let mut changes = self.commit(updates)?;
+        changes.conflicted = notes.conflicted;
+        changes
+            .conflicted
+            .retain(|path| changes.written.contains(path));
+        changes.match_confidence = notes.match_confidence;
+        changes.normalized = notes.normalized;
+        Ok(changes)
+    }
+
+    /// Fails with `NexusError::BinaryFile` if the current `path`, staged or
+    /// in the working tree, looks binary and is not allowed.
+    fn check_not_binary(&self, path: &str) -> Result<(), NexusError> {
+        if let Some(stage_dir) = &self.stage_dir {
+            self.binary_guard.check_file(stage_dir, path)?;
+        }
+        self.binary_guard.check_file(&self.root, path)
+    }
+
+    /// Current content of `path`, preferring the staged copy.
+    fn read(&self, path: &str) -> Result<Option<String>, NexusError> {
+        let Some(bytes) = self.read_bytes(path)? else {
+            return Ok(None);
+        };
+        String::from_utf8(bytes)
+            .map(Some)
+            .map_err(|err| NexusError::IoError {
+                operation: "read file to patch".to_string(),
+                path: PathBuf::from(path),
+                source: std::io::Error::new(std::io::ErrorKind::InvalidData, err),
+            })
+    }
+
+    /// Current bytes of `path`, preferring the staged copy.
+    fn read_bytes(&self, path: &str) -> Result<Option<Vec<u8>>, NexusError> {
+        let mut candidates = Vec::new();
+        if let Some(stage_dir) = &self.stage_dir {
+            if self.staged_deletions.contains(path) {
+                return Ok(None);
+            }
+            candidates.push(resolve(stage_dir, path)?);
+        }
+        candidates.push(resolve(&self.root, path)?);
+
+        for candidate in candidates {
+            match std::fs::read(&candidate) {
+                Ok(content) => return Ok(Some(content)),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(err) => {
+                    return Err(NexusError::IoError {
+                        operation: "read file to patch".to_string(),
+                        path: candidate,
+                        source: err,
+                    });
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    fn write(&mut self, path: &str, content: &str) -> Result<(), NexusError> {
+        let target = resolve(self.output_root(), path)?;
+        let io_error = |err| NexusError::IoError {
+            operation: "write patched file".to_string(),
+            path: target.clone(),
+            source: err,
+        };
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent).map_err(io_error)?;
+        }
+        std::fs::write(&target, content).map_err(io_error)?;
+        self.staged_deletions.remove(path);
+        Ok(())
+    }
+
+    fn delete(&mut self, path: &str) -> Result<(), NexusError> {
+        let target = resolve(self.output_root(), path)?;
+        match std::fs::remove_file(&target) {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound && self.stage_dir.is_some() => {}
+            Err(err) => {
+                return Err(NexusError::IoError {
+                    operation: "delete file".to_string(),
+                    path: target,
+                    source: err,
+                });
+            }
+        }
+        if self.stage_dir.is_some() {
+            self.staged_deletions.insert(path.to_string());
+        }
+        Ok(())
+    }
+}
+
+fn unsupported(action: &ProposedAction) -> NexusError {
+    NexusError::PatchFailed {
+        path: PathBuf::new(),
+        reason: format!(
+            "{} has an action kind or patch format that cannot be applied",
+            action.id
+        ),
+        source: None,
+    }
+}
+
+fn file_failed(path: &str, reason: &str) -> NexusError {
+    NexusError::PatchFailed {
+        path: PathBuf::from(path),
+        reason: reason.to_string(),
+        source: None,
+    }
+}
+
+/// Joins a repository-relative `path` onto `base`, refusing paths that
+/// would land outside it.
+pub(super) fn resolve(base: &Path, path: &str) -> Result<PathBuf, NexusError> {
+    let normalized = normalize_separators(path);
+    let escapes = is_absolute_any_platform(&normalized)
+        || Path::new(&normalized)
+            .components()
+            .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir));
+    if normalized.is_empty() || escapes {
+        return Err(NexusError::PatchFailed {
+            path: PathBuf::from(path),
+            reason: "path must be relative to the project root".to_string(),
+            source: None,
+        });
+    }
+    Ok(base.join(normalized))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::MatchMode;
+
+    fn patch(diff: &str) -> ProposedAction {
+        ProposedAction::test_diff("act_1", diff)
+    }
+
+    #[test]
+    fn test_applies_in_place() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "one\ntwo\n").unwrap();
+
+        let changes = Applier::new(dir.path())
+            .apply(&patch(
+                "--- a/a.txt\n+++ b/a.txt\n@@ -1,2 +1,2 @@\n one\n-two\n+three\n",
+            ))
+            .unwrap();
+
+        assert_eq!(changes.written, ["a.txt"]);
+        assert_eq!(
+            changes.before,
+            [("a.txt".to_string(), Some(b"one\ntwo\n".to_vec()))]
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "one\nthree\n"
+        );
+    }
+
+    #[test]
+    fn test_refuses_to_write_binary_targets() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.dat"), "one\0\ntwo\n").unwrap();
+        let action = patch("--- a/a.dat\n+++ b/a.dat\n@@ -1,2 +1,2 @@\n one\0\n-two\n+three\n");
+
+        let err = Applier::new(dir.path()).apply(&action).unwrap_err();
+        assert!(matches!(err, NexusError::BinaryFile { ref path, .. } if path == "a.dat"));
+        assert_eq!(
+            std::fs::read(dir.path().join("a.dat")).unwrap(),
+            b"one\0\ntwo\n"
+        );
+
+        let guard = BinaryGuard::new(&["*.dat".to_string()]).unwrap();
+        Applier::new(dir.path())
+            .with_binary_guard(guard)
+            .apply(&action)
+            .unwrap();
+        assert_eq!(
+            std::fs::read(dir.path().join("a.dat")).unwrap(),
+            b"one\0\nthree\n"
+        );
+    }
+
+    #[test]
+    fn test_stage_dir_leaves_working_tree_untouched() {
+        let root = tempfile::tempdir().unwrap();
+        let stage = tempfile::tempdir().unwrap();
+        std::fs::create_dir(root.path().join("src")).unwrap();
+        std::fs::write(root.path().join("src/lib.rs"), "a\nb\n").unwrap();
+        std::fs::write(root.path().join("old.txt"), "gone\n").unwrap();
+
+        let mut applier = Applier::new(root.path()).with_stage_dir(stage.path());
+        applier
+            .apply(&patch(
+                "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1,2 +1,2 @@\n a\n-b\n+c\n",
+            ))
+            .unwrap();
+        // A second action builds on the staged copy, not the original.
+        applier
+            .apply(&patch(
+                "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1,2 +1,3 @@\n a\n c\n+d\n",
+            ))
+            .unwrap();
+        let changes = applier
+            .apply(&patch(
+                "--- a/old.txt\n+++ /dev/null\n@@ -1 +0,0 @@\n-gone\n",
+            ))
+            .unwrap();
+
+        assert_eq!(changes.deleted, ["old.txt"]);
+        assert_eq!(
+            std::fs::read_to_string(stage.path().join("src/lib.rs")).unwrap(),
+            "a\nc\nd\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.path().join("src/lib.rs")).unwrap(),
+            "a\nb\n"
+        );
+        assert!(root.path().join("old.txt").exists());
+        assert!(!stage.path().join("old.txt").exists());
+    }
+
+    #[test]
+    fn test_failed_hunk_writes_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "one\n").unwrap();
+        std::fs::write(dir.path().join("b.txt"), "two\n").unwrap();
+
+        let err = Applier::new(dir.path())
+            .apply(&patch(
+                "--- a/a.txt\n+++ b/a.txt\n@@ -1 +1 @@\n-one\n+uno\n\
+                 --- a/b.txt\n+++ b/b.txt\n@@ -1 +1 @@\n-zwei\n+dos\n",
+            ))
+            .unwrap_err();
+
+        assert!(
+            matches!(err, NexusError::PatchFailed { ref path, .. } if path == Path::new("b.txt"))
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "one\n"
+        );
+    }
+
+    #[test]
+    fn test_conflicts_and_fuzzy_apply() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "one\ntwo!\nthree\n").unwrap();
+        let action = patch("--- a/a.txt\n+++ b/a.txt\n@@ -1,3 +1,3 @@\n one\n-two\n+2\n three\n");
+        let mut applier = Applier::new(dir.path());
+
+        assert!(applier.apply(&action).is_err());
+        let conflicts = applier.conflicts(&action).unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].path, "a.txt");
+        assert_eq!(conflicts[0].hunk.actual, ["one", "two!", "three"]);
+
+        let changes = applier.apply_fuzzy(&action, 0.6).unwrap();
+        assert!(changes.match_confidence.is_some_and(|c| c >= 0.6));
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "one\n2\nthree\n"
+        );
+    }
+
+    #[test]
+    fn test_check_follows_earlier_patches_without_writing() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "one\n").unwrap();
+        let actions = [
+            patch("--- a/a.txt\n+++ b/a.txt\n@@ -1 +1 @@\n-one\n+two\n"),
+            patch("--- a/a.txt\n+++ b/a.txt\n@@ -1 +1 @@\n-two\n+three\n"),
+            patch("--- a/a.txt\n+++ b/a.txt\n@@ -1 +1 @@\n-one\n+uno\n"),
+        ];
+
+        let checks = Applier::new(dir.path()).check(&actions);
+        assert_eq!(
+            checks,
+            [
+                Some(PatchCheck {
+                    applies_cleanly: true,
+                    failing_hunk: None
+                }),
+                Some(PatchCheck {
+                    applies_cleanly: true,
+                    failing_hunk: None
+                }),
+                Some(PatchCheck {
+                    applies_cleanly: false,
+                    failing_hunk: Some("a.txt: hunk 1 does not match the file".to_string())
+                }),
+            ]
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "one\n"
+        );
+    }
+
+    #[test]
+    fn test_fuzzy_fallback_strategy_honors_threshold() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "a\nb\nc\ntwo!\nd\ne\n").unwrap();
+        let mut action =
+            patch("--- a/a.txt\n+++ b/a.txt\n@@ -1,6 +1,6 @@\n a\n b\n c\n-two\n+2\n d\n e\n");
+        let ActionDetails::Patch(details) = &mut action.details else {
+            unreachable!()
+        };
+        details.fallback_strategy = FallbackStrategy::Fuzzy;
+        details.fuzzy_threshold = Some(0.99);
+        let mut applier = Applier::new(dir.path());
+        assert!(applier.apply(&action).is_err());
+
+        let ActionDetails::Patch(details) = &mut action.details else {
+            unreachable!()
+        };
+        details.fuzzy_threshold = None;
+        let changes = applier.apply(&action).unwrap();
+        assert!(
+            changes
+                .match_confidence
+                .is_some_and(|c| c >= DEFAULT_FUZZY_THRESHOLD)
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "a\nb\nc\n2\nd\ne\n"
+        );
+    }
+
+    #[test]
+    fn test_on_conflict_marker_flags_file() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "one\ntwo!\nthree\n").unwrap();
+        let mut action =
+            patch("--- a/a.txt\n+++ b/a.txt\n@@ -1,3 +1,3 @@\n one\n-two\n+2\n three\n");
+        let ActionDetails::Patch(details) = &mut action.details else {
+            unreachable!()
+        };
+        details.on_conflict = OnConflict::Ours;
+        let mut applier = Applier::new(dir.path());
+
+        let changes = applier.apply(&action).unwrap();
+        assert!(changes.conflicted.is_empty());
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "one\ntwo!\nthree\n"
+        );
+
+        let ActionDetails::Patch(details) = &mut action.details else {
+            unreachable!()
+        };
+        details.on_conflict = OnConflict::Marker;
+        let changes = applier.apply(&action).unwrap();
+        assert_eq!(changes.conflicted, ["a.txt"]);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "<<<<<<< ours\none\ntwo!\nthree\n=======\none\n2\nthree\n>>>>>>> theirs\n"
+        );
+    }
+
+    #[test]
+    fn test_applies_search_replace_blocks() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "one\n  two\nthree\n").unwrap();
+        let block = |search: &str, replace: &str, match_mode| SearchReplaceBlock {
+            file: "a.txt".to_string(),
+            search: search.to_string(),
+            replace: replace.to_string(),
+            match_mode,
+        };
+        let mut action = patch("");
+        action.details = ActionDetails::Patch(PatchDetails {
+            format: PatchFormat::SearchReplace,
+            search_replace_blocks: Some(vec![
+                block("one\n", "1\n", MatchMode::Exact),
+                block("two three", "2 3", MatchMode::WhitespaceInsensitive),
+            ]),
+            ..Default::default()
+        });
+
+        let changes = Applier::new(dir.path()).apply(&action).unwrap();
+        assert_eq!(changes.written, ["a.txt"]);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "1\n  2 3\n"
+        );
+
+        let err = Applier::new(dir.path()).apply(&action).unwrap_err();
+        match err {
+            NexusError::PatchFailed { path, reason, .. } => {
+                assert_eq!(path, Path::new("a.txt"));
+                assert!(
+                    reason.starts_with("block 1: search text not found"),
+                    "{reason}"
+                );
+            }
+            other => panic!("expected PatchFailed, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_reports_search_text_found_after_normalization() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "let caf\u{e9} = 1;\n").unwrap();
+        let mut action = patch("");
+        action.details = ActionDetails::Patch(PatchDetails {
+            format: PatchFormat::SearchReplace,
+            search_replace_blocks: Some(vec![SearchReplaceBlock {
+                file: "a.txt".to_string(),
+                search: "cafe\u{301}\u{200B} = 1".to_string(),
+                replace: "cafe = 2".to_string(),
+                match_mode: MatchMode::Exact,
+            }]),
+            ..Default::default()
+        });
+
+        let strict = MatchOptions {
+            normalize_unicode: false,
+        };
+        assert!(
+            Applier::new(dir.path())
+                .with_match_options(strict)
+                .apply(&action)
+                .is_err()
+        );
+
+        let changes = Applier::new(dir.path()).apply(&action).unwrap();
+        let [(path, note)] = changes.normalized.as_slice() else {
+            panic!("expected one note, got {:?}", changes.normalized);
+        };
+        assert_eq!(path, "a.txt");
+        assert!(note.nfc);
+        assert_eq!(note.invisible_chars_removed, 1);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "let cafe = 2;\n"
+        );
+    }
+
+    #[test]
+    fn test_applies_whole_file_with_base_guard() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "old\n").unwrap();
+        let whole_file = |base: &str| {
+            let mut action = patch("");
+            action.details = ActionDetails::Patch(PatchDetails {
+                format: PatchFormat::WholeFile,
+                whole_file_content: Some(HashMap::from([
+                    ("a.txt".to_string(), "new\n".to_string()),
+                    ("src/b.txt".to_string(), "created\n".to_string()),
+                ])),
+                base_file_sha256: Some(HashMap::from([("a.txt".to_string(), base.to_string())])),
+                ..Default::default()
+            });
+            action
+        };
+
+        let err = Applier::new(dir.path())
+            .apply(&whole_file(&sha256_hex(b"other\n")))
+            .unwrap_err();
+        match err {
+            NexusError::PatchFailed { path, reason, .. } => {
+                assert_eq!(path, Path::new("a.txt"));
+                assert!(
+                    reason.starts_with("file changed since proposal"),
+                    "{reason}"
+                );
+            }
+            other => panic!("expected PatchFailed, got {other:?}"),
+        }
+        assert!(!dir.path().join("src/b.txt").exists());
+
+        let changes = Applier::new(dir.path())
+            .apply(&whole_file(&sha256_hex(b"old\n")))
+            .unwrap();
+        assert_eq!(changes.written, ["a.txt", "src/b.txt"]);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "new\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("src/b.txt")).unwrap(),
+            "created\n"
+        );
+    }
+
+    #[test]
+    fn test_refuses_patch_when_base_file_changed() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "one\ntwo\n").unwrap();
+        let with_base = |diff: &str| {
+            let mut action = patch(diff);
+            if let ActionDetails::Patch(details) = &mut action.details {
+                details.base_file_sha256 = Some(HashMap::from([(
+                    "a.txt".to_string(),
+                    sha256_hex(b"one\ntwo\n"),
+                )]));
+            }
+            action
+        };
+        let first = with_base("--- a/a.txt\n+++ b/a.txt\n@@ -1,2 +1,2 @@\n one\n-two\n+2\n");
+        let second = with_base("--- a/a.txt\n+++ b/a.txt\n@@ -1,2 +1,2 @@\n-one\n+1\n 2\n");
+
+        // Changes made by earlier actions of the same applier are expected.
+        let mut applier = Applier::new(dir.path());
+        applier.apply(&first).unwrap();
+        applier.apply(&second).unwrap();
+
+        std::fs::write(dir.path().join("a.txt"), "one\ntwo\n\n").unwrap();
+        let (path, reason) = failure(Applier::new(dir.path()).apply(&first).unwrap_err());
+        assert_eq!(path, Path::new("a.txt"));
+        assert!(
+            reason.starts_with("file changed since proposal"),
+            "{reason}"
+        );
+    }
+
+    fn file_action(details: ActionDetails) -> ProposedAction {
+        ProposedAction::test("act_1", details)
+    }
+
+    fn failure(err: NexusError) -> (PathBuf, String) {
+        match err {
+            NexusError::PatchFailed { path, reason, .. } => (path, reason),
+            other => panic!("expected PatchFailed, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_file_create_honors_flags() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "old\n").unwrap();
+        let create = |path: &str, overwrite, ignore_if_exists| {
+            file_action(ActionDetails::FileCreate(FileCreateDetails {
+                path: path.to_string(),
+                content: "new\n".to_string(),
+                overwrite,
+                ignore_if_exists,
+            }))
+        };
+        let mut applier = Applier::new(dir.path());
+
+        let changes = applier.apply(&create("src/b.txt", false, false)).unwrap();
+        assert_eq!(changes.written, ["src/b.txt"]);
+
+        let (path, reason) = failure(applier.apply(&create("a.txt", false, false)).unwrap_err());
+        assert_eq!(
+            (path.to_str(), reason.as_str()),
+            (Some("a.txt"), "file already exists")
+        );
+
+        let changes = applier.apply(&create("a.txt", false, true)).unwrap();
+        assert!(changes.written.is_empty());
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "old\n"
+        );
+
+        applier.apply(&create("a.txt", true, false)).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "new\n"
+        );
+    }
+
+    #[test]
+    fn test_file_rename_honors_overwrite() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "a\n").unwrap();
+        std::fs::write(dir.path().join("b.txt"), "b\n").unwrap();
+        let rename = |old_path: &str, new_path: &str, overwrite| {
+            file_action(ActionDetails::FileRename(FileRenameDetails {
+                old_path: old_path.to_string(),
+                new_path: new_path.to_string(),
+                overwrite,
+            }))
+        };
+        let mut applier = Applier::new(dir.path());
+
+        let (path, reason) = failure(applier.apply(&rename("a.txt", "b.txt", false)).unwrap_err());
+        assert_eq!(path, Path::new("b.txt"));
+        assert_eq!(reason, "rename target already exists");
+
+        let (path, reason) = failure(applier.apply(&rename("x.txt", "y.txt", false)).unwrap_err());
+        assert_eq!(path, Path::new("x.txt"));
+        assert_eq!(reason, "file does not exist");
+
+        let changes = applier.apply(&rename("a.txt", "b.txt", true)).unwrap();
+        assert_eq!(changes.deleted, ["a.txt"]);
+        assert_eq!(changes.written, ["b.txt"]);
+        assert!(!dir.path().join("a.txt").exists());
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("b.txt")).unwrap(),
+            "a\n"
+        );
+    }
+
+    #[test]
+    fn test_file_delete_honors_flags() {
+        let root = tempfile::tempdir().unwrap();
+        let stage = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(root.path().join("gen/nested")).unwrap();
+        std::fs::write(root.path().join("gen/a.txt"), "a\n").unwrap();
+        std::fs::write(root.path().join("gen/nested/b.txt"), "b\n").unwrap();
+        let delete = |path: &str, recursive, ignore_if_missing| {
+            file_action(ActionDetails::FileDelete(FileDeleteDetails {
+                path: path.to_string(),
+                recursive,
+                ignore_if_missing,
+            }))
+        };
+
+        let mut staged = Applier::new(root.path()).with_stage_dir(stage.path());
+        let (path, reason) = failure(staged.apply(&delete("gen", false, false)).unwrap_err());
+        assert_eq!(path, Path::new("gen"));
+        assert_eq!(reason, "is a directory; set recursive to delete it");
+        let changes = staged.apply(&delete("gen", true, false)).unwrap();
+        assert_eq!(changes.deleted, ["gen/a.txt", "gen/nested/b.txt"]);
+        assert!(root.path().join("gen/nested/b.txt").exists());
+
+        let mut applier = Applier::new(root.path());
+        let (_, reason) = failure(
+            applier
+                .apply(&delete("missing.txt", false, false))
+                .unwrap_err(),
+        );
+        assert_eq!(reason, "file does not exist");
+        assert_eq!(
+            applier.apply(&delete("missing.txt", false, true)).unwrap(),
+            AppliedChanges::default()
+        );
+        applier.apply(&delete("gen", true, false)).unwrap();
+        assert!(!root.path().join("gen").exists());
+    }
+
+    #[test]
+    fn test_agenda_patch_edits_only_its_target() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("docs")).unwrap();
+        std::fs::write(dir.path().join("docs/agenda.md"), "# Agenda\n- [ ] plan\n").unwrap();
+        std::fs::write(dir.path().join("a.txt"), "one\n").unwrap();
+        let agenda = |diff: &str| {
+            file_action(ActionDetails::AgendaPatch(AgendaPatchDetails {
+                target_path: "docs/agenda.md".to_string(),
+                diff: diff.to_string(),
+            }))
+        };
+        let mut applier = Applier::new(dir.path());
+
+        let changes = applier
+            .apply(&agenda(
+                "@@ -1,2 +1,3 @@\n # Agenda\n-- [ ] plan\n+- [x] plan\n+- [ ] build\n",
+            ))
+            .unwrap();
+        assert_eq!(changes.written, ["docs/agenda.md"]);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("docs/agenda.md")).unwrap(),
+            "# Agenda\n- [x] plan\n- [ ] build\n"
+        );
+
+        let (path, reason) = failure(
+            applier
+                .apply(&agenda("--- a/a.txt\n+++ b/a.txt\n@@ -1 +1 @@\n-one\n+1\n"))
+                .unwrap_err(),
+        );
+        assert_eq!(path, Path::new("a.txt"));
+        assert_eq!(reason, "an agenda patch may only edit docs/agenda.md");
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "one\n"
+        );
+    }
+
+    #[test]
+    fn test_rejects_paths_outside_root() {
+        let dir = tempfile::tempdir().unwrap();
+        for path in ["../escape.txt", "/etc/passwd", "C:\\x.txt"] {
+            let diff = format!("--- /dev/null\n+++ {path}\n@@ -0,0 +1 @@\n+x\n");
+            let err = Applier::new(dir.path()).apply(&patch(&diff)).unwrap_err();
+            assert!(matches!(err, NexusError::PatchFailed { .. }), "{path}");
+        }
+    }
+}
@@ -0,0 +1,150 @@
+//! Copies of files as they were before a run changed them.
+//!
+//! Before an action modifies or removes a file, the applier copies it to
+//! `.nexus/runs/<run_id>/backups/`, keeping its path relative to the project
+//! root. Only the first copy of a path is kept, so a backup holds the file
+//! as it was before the run. [`RunBackups::restore`] copies backups back
+//! into the working tree.
+
+use std::path::{Path, PathBuf};
+
+use crate::error::NexusError;
+use crate::event_log::EventLogPath;
+use crate::paths::normalize_separators;
+
+use super::applier::resolve;
+use super::collect_files;
+
+const BACKUPS_DIR: &str = "backups";
+
+/// Backups of one run.
+#[derive(Debug, Clone)]
+pub struct RunBackups {
+    root: PathBuf,
+    run_id: String,
+    dir: PathBuf,
+}
+
+impl RunBackups {
+    /// Backups of `run_id` in the project at `root`.
+    pub fn new(root: impl Into<PathBuf>, run_id: &str) -> Result<Self, NexusError> {
+        let root = root.into();
+        let log_path = EventLogPath::new(&root).for_run(run_id)?;
+        let runs_dir = log_path.parent().unwrap_or_else(|| Path::new("."));
+        Ok(Self {
+            dir: runs_dir.join(run_id).join(BACKUPS_DIR),
+            run_id: run_id.to_string(),
+            root,
+        })
+    }
+
+    /// Directory the backups are kept in.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Backs up `bytes` as the content of `path`, unless it already has a
+    /// backup.
+    pub(crate) fn save(&self, path: &str, bytes: &[u8]) -> Result<(), NexusError> {
+        let target = resolve(&self.dir, path)?;
+        if target.exists() {
+            return Ok(());
+        }
+        let io_error = |err| NexusError::IoError {
+            operation: "back up file".to_string(),
+            path: target.clone(),
+            source: err,
+        };
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent).map_err(io_error)?;
+        }
+        std::fs::write(&target, bytes).map_err(io_error)
+    }
+
+    /// Paths with a backup, relative to the project root and sorted.
+    pub fn files(&self) -> Result<Vec<String>, NexusError> {
+        let mut files = Vec::new();
+        collect_files(&self.dir, &self.dir, "read backups", &mut files)?;
+        files.sort();
+        Ok(files)
+    }
+
+    /// Copies the backup of `path` back into the working tree, or every
+    /// backup if `path` is `None`, and returns the restored paths.
+    ///
+    /// # Errors
+    /// - `NexusError::BackupNotFound` if `path` has no backup
+    pub fn restore(&self, path: Option<&str>) -> Result<Vec<String>, NexusError> {
+        let files = match path {
+            Some(path) => {
+                let path = normalize_separators(path);
+                if !resolve(&self.dir, &path)?.is_file() {
+                    return Err(NexusError::BackupNotFound {
+                        run_id: self.run_id.clone(),
+                        path,
+                    });
+                }
+                vec![path]
+            }
+            None => self.files()?,
+        };
+        for file in &files {
+            let source = resolve(&self.dir, file)?;
+            let target = resolve(&self.root, file)?;
+            let io_error = |err| NexusError::IoError {
+                operation: "restore file".to_string(),
+                path: target.clone(),
+                source: err,
+            };
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent).map_err(io_error)?;
+            }
+            std::fs::copy(&source, &target).map_err(io_error)?;
+        }
+        Ok(files)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_backup_of_a_path_is_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let backups = RunBackups::new(dir.path(), "run_1").unwrap();
+        backups.save("src/a.txt", b"original\n").unwrap();
+        backups.save("src/a.txt", b"edited\n").unwrap();
+        backups.save("b.txt", b"b\n").unwrap();
+
+        assert_eq!(backups.files().unwrap(), ["b.txt", "src/a.txt"]);
+        assert_eq!(
+            std::fs::read(backups.dir().join("src/a.txt")).unwrap(),
+            b"original\n"
+        );
+    }
+
+    #[test]
+    fn test_restore_one_or_all_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let backups = RunBackups::new(dir.path(), "run_1").unwrap();
+        backups.save("src/a.txt", b"a\n").unwrap();
+        backups.save("b.txt", b"b\n").unwrap();
+
+        assert_eq!(backups.restore(Some("src/a.txt")).unwrap(), ["src/a.txt"]);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("src/a.txt")).unwrap(),
+            "a\n"
+        );
+        assert!(!dir.path().join("b.txt").exists());
+
+        assert_eq!(backups.restore(None).unwrap(), ["b.txt", "src/a.txt"]);
+        assert!(dir.path().join("b.txt").exists());
+
+        let err = backups.restore(Some("missing.txt")).unwrap_err();
+        assert!(
+            matches!(err, NexusError::BackupNotFound { ref path, .. } if path == "missing.txt")
+        );
+        assert!(backups.restore(Some("../escape.txt")).is_err());
+    }
+}
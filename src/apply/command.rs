@@ -0,0 +1,276 @@
+//! Running approved command actions.
+//!
+//! The argv is spawned directly, never through a shell, in the declared
+//! working directory under the project root. The child sees only a small
+//! base environment plus the variables named in `env_allow`, so secrets in
+//! the parent environment (such as `OPENAI_API_KEY`) do not leak into it.
+//! A command still running at its timeout is killed.
+
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use serde_json::{Value, json};
+
+use crate::error::NexusError;
+use crate::paths::{is_absolute_any_platform, normalize_separators};
+use crate::types::CommandDetails;
+
+/// Variables every command inherits, whatever its `env_allow`.
+const BASE_ENV: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USER",
+    "LANG",
+    "LC_ALL",
+    "TERM",
+    "TMPDIR",
+    "SYSTEMROOT",
+    "USERPROFILE",
+];
+
+/// How often a running command is checked for exit or timeout.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// What a finished (or killed) command produced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandOutput {
+    /// Exit code; `None` if the command was killed or ended by a signal.
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    pub timed_out: bool,
+    pub duration_ms: u128,
+}
+
+impl CommandOutput {
+    pub fn success(&self) -> bool {
+        !self.timed_out && self.exit_code == Some(0)
+    }
+
+    /// Why the command failed, or `None` if it succeeded.
+    pub fn failure(&self, timeout_s: u32) -> Option<String> {
+        if self.timed_out {
+            return Some(format!("timed out after {timeout_s}s"));
+        }
+        match self.exit_code {
+            Some(0) => None,
+            Some(code) => Some(format!("exited with status {code}")),
+            None => Some("terminated by a signal".to_string()),
+        }
+    }
+
+    /// The output as JSON, for `--output-format json`.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "exit_code": self.exit_code,
+            "success": self.success(),
+            "timed_out": self.timed_out,
+            "duration_ms": self.duration_ms,
+            "stdout": self.stdout,
+            "stderr": self.stderr,
+        })
+    }
+}
+
+/// Spawns command actions under the project root.
+#[derive(Debug, Clone)]
+pub struct CommandRunner {
+    root: PathBuf,
+}
+
+impl CommandRunner {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Runs `details.argv` to completion or timeout.
+    ///
+    /// A non-zero exit or timeout is reported in the output, not as an
+    /// error; errors mean the command could not be started.
+    pub fn run(&self, details: &CommandDetails) -> Result<CommandOutput, NexusError> {
+        let (program, args) =
+            details
+                .argv
+                .split_first()
+                .ok_or_else(|| NexusError::ValidationError {
+                    message: "command argv is empty".to_string(),
+                    field: Some("argv".to_string()),
+                })?;
+        let cwd = self.working_dir(details.cwd.as_deref())?;
+
+        let mut command = Command::new(program);
+        command
+            .args(args)
+            .current_dir(&cwd)
+            .env_clear()
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        for name in BASE_ENV
+            .iter()
+            .copied()
+            .chain(details.env_allow.iter().map(String::as_str))
+        {
+            if let Some(value) = std::env::var_os(name) {
+                command.env(name, value);
+            }
+        }
+
+        let started = Instant::now();
+        let mut child = command.spawn().map_err(|err| NexusError::IoError {
+            operation: format!("run command `{}`", details.argv.join(" ")),
+            path: cwd.clone(),
+            source: err,
+        })?;
+        let stdout = capture(child.stdout.take());
+        let stderr = capture(child.stderr.take());
+
+        let timeout = Duration::from_secs(u64::from(details.timeout_s));
+        let (status, timed_out) =
+            wait_with_timeout(&mut child, timeout).map_err(|err| NexusError::IoError {
+                operation: format!("wait for command `{}`", details.argv.join(" ")),
+                path: cwd,
+                source: err,
+            })?;
+
+        Ok(CommandOutput {
+            exit_code: status.code(),
+            stdout: join_capture(stdout),
+            stderr: join_capture(stderr),
+            timed_out,
+            duration_ms: started.elapsed().as_millis(),
+        })
+    }
+
+    /// Resolves the declared `cwd` against the project root.
+    fn working_dir(&self, cwd: Option<&str>) -> Result<PathBuf, NexusError> {
+        let Some(cwd) = cwd else {
+            return Ok(self.root.clone());
+        };
+        let normalized = normalize_separators(cwd);
+        let escapes = is_absolute_any_platform(&normalized)
+            || Path::new(&normalized)
+                .components()
+                .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir));
+        if escapes {
+            return Err(NexusError::PathRejected {
+                path: cwd.to_string(),
+                reason: "command cwd must be relative to the project root".to_string(),
+            });
+        }
+        Ok(self.root.join(normalized))
+    }
+}
+
+/// Waits for `child`, killing it once `timeout` has passed.
+fn wait_with_timeout(child: &mut Child, timeout: Duration) -> std::io::Result<(ExitStatus, bool)> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok((status, false));
+        }
+        if Instant::now() >= deadline {
+            // The child may exit between the check and the kill.
+            let _ = child.kill();
+            return Ok((child.wait()?, true));
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Reads a pipe to the end on its own thread so a chatty command cannot
+/// fill the pipe and block.
+fn capture<R: Read + Send + 'static>(pipe: Option<R>) -> Option<JoinHandle<Vec<u8>>> {
+    pipe.map(|mut pipe| {
+        std::thread::spawn(move || {
+            let mut bytes = Vec::new();
+            let _ = pipe.read_to_end(&mut bytes);
+            bytes
+        })
+    })
+}
+
+fn join_capture(handle: Option<JoinHandle<Vec<u8>>>) -> String {
+    handle
+        .and_then(|handle| handle.join().ok())
+        .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+        .unwrap_or_default()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn command(argv: &[&str]) -> CommandDetails {
+        CommandDetails {
+            argv: argv.iter().map(|arg| arg.to_string()).collect(),
+            cwd: None,
+            timeout_s: 10,
+            env_allow: Vec::new(),
+            requires_network: false,
+            purpose: None,
+        }
+    }
+
+    #[test]
+    fn test_captures_output_and_exit_status() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        let mut details = command(&["sh", "-c", "pwd; echo oops >&2; exit 3"]);
+        details.cwd = Some("sub".to_string());
+
+        let output = CommandRunner::new(dir.path()).run(&details).unwrap();
+        assert_eq!(output.exit_code, Some(3));
+        assert!(output.stdout.trim_end().ends_with("/sub"), "{output:?}");
+        assert_eq!(output.stderr, "oops\n");
+        assert_eq!(
+            output.failure(details.timeout_s).as_deref(),
+            Some("exited with status 3")
+        );
+    }
+
+    #[test]
+    fn test_env_is_limited_to_allow_list() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut details = command(&["sh", "-c", "echo \"${CARGO_PKG_NAME:-unset}\""]);
+
+        let output = CommandRunner::new(dir.path()).run(&details).unwrap();
+        assert_eq!(output.stdout, "unset\n");
+
+        details.env_allow.push("CARGO_PKG_NAME".to_string());
+        let output = CommandRunner::new(dir.path()).run(&details).unwrap();
+        assert_eq!(output.stdout, "nexus\n");
+        assert!(output.success());
+    }
+
+    #[test]
+    fn test_timeout_kills_command() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut details = command(&["sleep", "5"]);
+        details.timeout_s = 0;
+
+        let output = CommandRunner::new(dir.path()).run(&details).unwrap();
+        assert!(output.timed_out);
+        assert!(output.duration_ms < 5000);
+        assert_eq!(output.failure(0).as_deref(), Some("timed out after 0s"));
+    }
+
+    #[test]
+    fn test_rejects_empty_argv_and_escaping_cwd() {
+        let runner = CommandRunner::new(tempfile::tempdir().unwrap().path());
+        assert!(matches!(
+            runner.run(&command(&[])),
+            Err(NexusError::ValidationError { .. })
+        ));
+
+        let mut details = command(&["true"]);
+        details.cwd = Some("../elsewhere".to_string());
+        assert!(matches!(
+            runner.run(&details),
+            Err(NexusError::PathRejected { .. })
+        ));
+    }
+}
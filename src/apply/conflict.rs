@@ -0,0 +1,288 @@
+//! Resolving patches that no longer match the files they target.
+//!
+//! When a hunk's context is not found, the user is shown the expected lines
+//! next to the most similar lines in the file and picks a [`Resolution`].
+//! Prompting goes through [`ConflictPrompt`] so the flow can be scripted.
+//!
+//! An action can instead settle its conflicts itself through its
+//! `on_conflict` strategy; with `marker`, both sides are written into the
+//! file between the markers below.
+
+use std::fmt;
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::error::NexusError;
+use crate::types::ProposedAction;
+
+use super::unified::HunkConflict;
+
+/// Similarity a fuzzy apply requires when the action does not set
+/// `fuzzy_threshold`.
+pub const DEFAULT_FUZZY_THRESHOLD: f64 = 0.8;
+
+/// Opens a conflict; the file's lines follow.
+pub const MARKER_OURS: &str = "<<<<<<< ours";
+/// Separates the file's lines from the proposed ones.
+pub const MARKER_DIVIDER: &str = "=======";
+/// Closes a conflict.
+pub const MARKER_THEIRS: &str = ">>>>>>> theirs";
+
+/// `ours` and `theirs` between conflict markers.
+pub(crate) fn conflict_markers<'a>(ours: &[&'a str], theirs: &[&'a str]) -> Vec<&'a str> {
+    let mut lines = Vec::with_capacity(ours.len() + theirs.len() + 3);
+    lines.push(MARKER_OURS);
+    lines.extend_from_slice(ours);
+    lines.push(MARKER_DIVIDER);
+    lines.extend_from_slice(theirs);
+    lines.push(MARKER_THEIRS);
+    lines
+}
+
+/// A hunk of an action that does not apply to `path`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub path: String,
+    pub hunk: HunkConflict,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}: hunk {} does not apply", self.path, self.hunk.hunk)?;
+        writeln!(f, "  expected:")?;
+        for line in &self.hunk.expected {
+            writeln!(f, "    | {line}")?;
+        }
+        match self.hunk.actual_start {
+            Some(start) => writeln!(
+                f,
+                "  closest match at line {start} ({:.0}% similar):",
+                self.hunk.similarity * 100.0
+            )?,
+            None => writeln!(f, "  the file is empty")?,
+        }
+        for line in &self.hunk.actual {
+            writeln!(f, "    | {line}")?;
+        }
+        Ok(())
+    }
+}
+
+/// What to do with an action whose patch conflicts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    /// Apply hunks to the most similar lines.
+    Fuzzy,
+    /// Fix the diff in an editor and try again.
+    Edit,
+    /// Leave the action pending.
+    Skip,
+    /// Leave the action pending and ask the model for a new patch.
+    Reask,
+    /// Stop applying the run.
+    Abort,
+}
+
+impl Resolution {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Resolution::Fuzzy => "fuzzy",
+            Resolution::Edit => "edit",
+            Resolution::Skip => "skip",
+            Resolution::Reask => "reask",
+            Resolution::Abort => "abort",
+        }
+    }
+
+    /// Parses a menu choice: the first letter or the full name.
+    pub fn from_choice(choice: &str) -> Option<Self> {
+        match choice.trim().to_ascii_lowercase().as_str() {
+            "f" | "fuzzy" => Some(Resolution::Fuzzy),
+            "e" | "edit" => Some(Resolution::Edit),
+            "s" | "skip" => Some(Resolution::Skip),
+            "r" | "reask" | "re-ask" => Some(Resolution::Reask),
+            "q" | "quit" | "abort" => Some(Resolution::Abort),
+            _ => None,
+        }
+    }
+}
+
+/// Asks how to resolve conflicts.
+pub trait ConflictPrompt {
+    /// Picks a resolution for `action`, given its conflicts.
+    fn choose(
+        &mut self,
+        action: &ProposedAction,
+        conflicts: &[Conflict],
+    ) -> Result<Resolution, NexusError>;
+
+    /// Returns an edited copy of `diff`.
+    fn edit(&mut self, action: &ProposedAction, diff: &str) -> Result<String, NexusError>;
+
+    /// Called when a chosen resolution did not apply either.
+    fn failed(&mut self, _error: &NexusError) {}
+}
+
+/// Prompts on a line-oriented terminal and edits diffs in `$VISUAL` or
+/// `$EDITOR`.
+pub struct LinePrompt<R, W> {
+    input: R,
+    output: W,
+}
+
+impl LinePrompt<std::io::StdinLock<'static>, std::io::Stderr> {
+    /// Reads choices from stdin and writes prompts to stderr.
+    pub fn stdio() -> Self {
+        Self::new(std::io::stdin().lock(), std::io::stderr())
+    }
+}
+
+impl<R: BufRead, W: Write> LinePrompt<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        Self { input, output }
+    }
+
+    /// Writes `question` and reads one answer line; `None` at end of input.
+    pub(crate) fn ask(&mut self, question: &str) -> std::io::Result<Option<String>> {
+        write!(self.output, "{question}")?;
+        self.output.flush()?;
+        let mut line = String::new();
+        if self.input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        Ok(Some(line))
+    }
+
+    /// Writes one line of prompt text.
+    pub(crate) fn say(&mut self, text: &str) -> std::io::Result<()> {
+        writeln!(self.output, "{text}")
+    }
+}
+
+impl<R: BufRead, W: Write> ConflictPrompt for LinePrompt<R, W> {
+    fn choose(
+        &mut self,
+        action: &ProposedAction,
+        conflicts: &[Conflict],
+    ) -> Result<Resolution, NexusError> {
+        let io_error = |err| NexusError::IoError {
+            operation: "prompt for conflict resolution".to_string(),
+            path: PathBuf::from("<terminal>"),
+            source: err,
+        };
+        writeln!(
+            self.output,
+            "\nConflict applying {}: {}",
+            action.id, action.summary
+        )
+        .map_err(io_error)?;
+        for conflict in conflicts {
+            write!(self.output, "{conflict}").map_err(io_error)?;
+        }
+        loop {
+            let Some(line) = self
+                .ask("[f]uzzy apply, [e]dit diff, [s]kip, [r]e-ask model, [q]uit? ")
+                .map_err(io_error)?
+            else {
+                return Ok(Resolution::Abort);
+            };
+            match Resolution::from_choice(&line) {
+                Some(resolution) => return Ok(resolution),
+                None => {
+                    writeln!(self.output, "Unknown choice `{}`", line.trim()).map_err(io_error)?
+                }
+            }
+        }
+    }
+
+    fn edit(&mut self, action: &ProposedAction, diff: &str) -> Result<String, NexusError> {
+        let path = std::env::temp_dir().join(format!("nexus-{}.diff", action.id));
+        let io_error = |operation: &str, err| NexusError::IoError {
+            operation: operation.to_string(),
+            path: path.clone(),
+            source: err,
+        };
+        std::fs::write(&path, diff).map_err(|err| io_error("write diff for editing", err))?;
+
+        let editor = std::env::var("VISUAL")
+            .or_else(|_| std::env::var("EDITOR"))
+            .unwrap_or_else(|_| "vi".to_string());
+        let mut argv = editor.split_whitespace();
+        let program = argv.next().unwrap_or("vi");
+        let status = Command::new(program)
+            .args(argv)
+            .arg(&path)
+            .status()
+            .map_err(|err| io_error("run editor", err))?;
+        let edited = std::fs::read_to_string(&path);
+        let _ = std::fs::remove_file(&path);
+        if !status.success() {
+            return Err(NexusError::PatchFailed {
+                path: path.clone(),
+                reason: format!("editor `{editor}` exited with {status}"),
+                source: None,
+            });
+        }
+        edited.map_err(|err| io_error("read edited diff", err))
+    }
+
+    fn failed(&mut self, error: &NexusError) {
+        let _ = writeln!(self.output, "Still does not apply: {error}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ActionDetails, PatchDetails};
+
+    fn conflict() -> Conflict {
+        Conflict {
+            path: "src/lib.rs".to_string(),
+            hunk: HunkConflict {
+                hunk: 2,
+                expected: vec!["fn b() {}".to_string()],
+                actual_start: Some(7),
+                actual: vec!["fn bb() {}".to_string()],
+                similarity: 0.5,
+            },
+        }
+    }
+
+    #[test]
+    fn test_display_shows_expected_and_actual() {
+        assert_eq!(
+            conflict().to_string(),
+            "src/lib.rs: hunk 2 does not apply\n  expected:\n    | fn b() {}\n  \
+             closest match at line 7 (50% similar):\n    | fn bb() {}\n"
+        );
+    }
+
+    #[test]
+    fn test_from_choice() {
+        assert_eq!(Resolution::from_choice("f\n"), Some(Resolution::Fuzzy));
+        assert_eq!(Resolution::from_choice(" Re-ask "), Some(Resolution::Reask));
+        assert_eq!(Resolution::from_choice("x"), None);
+    }
+
+    #[test]
+    fn test_line_prompt_repeats_until_valid_choice() {
+        let action = ProposedAction::test("act_1", ActionDetails::Patch(PatchDetails::default()))
+            .with_summary("Rename b");
+        let mut output = Vec::new();
+        let mut prompt = LinePrompt::new(&b"maybe\ns\n"[..], &mut output);
+        let resolution = prompt.choose(&action, &[conflict()]).unwrap();
+        assert_eq!(resolution, Resolution::Skip);
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("Conflict applying act_1: Rename b"));
+        assert!(output.contains("Unknown choice `maybe`"));
+
+        let mut prompt = LinePrompt::new(&b""[..], Vec::new());
+        assert_eq!(
+            prompt.choose(&action, &[conflict()]).unwrap(),
+            Resolution::Abort
+        );
+    }
+}
@@ -0,0 +1,253 @@
+//! Locating search text inside file contents.
+//!
+//! Matching is exact first. When that fails and Unicode normalization is
+//! enabled, both sides are compared in NFC form with invisible characters
+//! removed, and the match is mapped back to byte offsets in the original
+//! content so the replacement lands on the real text.
+
+use std::ops::Range;
+
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::canonical_combining_class;
+
+use crate::types::NexusSettings;
+
+/// Characters that render as nothing but break byte-for-byte matching.
+const INVISIBLE_CHARS: &[char] = &[
+    '\u{00AD}', // soft hyphen
+    '\u{200B}', // zero width space
+    '\u{200C}', // zero width non-joiner
+    '\u{200D}', // zero width joiner
+    '\u{2060}', // word joiner
+    '\u{FEFF}', // zero width no-break space / BOM
+];
+
+/// Options controlling how search text is located.
+#[derive(Debug, Clone, Copy)]
+pub struct MatchOptions {
+    /// Retry failed exact matches after NFC normalization and invisible-character removal.
+    pub normalize_unicode: bool,
+}
+
+impl Default for MatchOptions {
+    fn default() -> Self {
+        Self {
+            normalize_unicode: true,
+        }
+    }
+}
+
+impl MatchOptions {
+    /// Options from the `normalize_unicode` setting.
+    pub fn from_settings(settings: &NexusSettings) -> Self {
+        Self {
+            normalize_unicode: settings.normalize_unicode,
+        }
+    }
+}
+
+/// Normalization that had to be applied for a match to succeed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NormalizationNote {
+    /// Content and search text differed in Unicode normalization form.
+    pub nfc: bool,
+    /// Number of invisible characters ignored in the file and search text.
+    pub invisible_chars_removed: usize,
+}
+
+impl std::fmt::Display for NormalizationNote {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = Vec::new();
+        if self.nfc {
+            parts.push("Unicode NFC normalization".to_string());
+        }
+        if self.invisible_chars_removed > 0 {
+            parts.push(format!(
+                "ignoring {} invisible character(s)",
+                self.invisible_chars_removed
+            ));
+        }
+        write!(f, "matched only after {}", parts.join(" and "))
+    }
+}
+
+/// A located match in the original content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextMatch {
+    /// Byte range of the matched text in the original content.
+    pub range: Range<usize>,
+    /// Present when the match required normalization.
+    pub normalization: Option<NormalizationNote>,
+}
+
+/// Finds the first occurrence of `search` in `content`.
+///
+/// # Examples
+///
+/// ```
+/// use nexus::apply::matcher::{MatchOptions, find_match};
+///
+/// // "é" precomposed in the file, decomposed in the search text.
+/// let content = "let caf\u{e9} = 1;";
+/// let found = find_match(content, "cafe\u{301}", MatchOptions::default()).unwrap();
+/// assert_eq!(&content[found.range.clone()], "caf\u{e9}");
+/// assert!(found.normalization.is_some());
+/// ```
+pub fn find_match(content: &str, search: &str, options: MatchOptions) -> Option<TextMatch> {
+    if search.is_empty() {
+        return None;
+    }
+
+    if let Some(start) = content.find(search) {
+        return Some(TextMatch {
+            range: start..start + search.len(),
+            normalization: None,
+        });
+    }
+
+    if !options.normalize_unicode {
+        return None;
+    }
+
+    find_normalized_match(content, search)
+}
+
+/// Text in NFC form with invisible characters removed, plus the original
+/// byte range that produced each output byte.
+struct NormalizedText {
+    text: String,
+    origins: Vec<Range<usize>>,
+    invisible_removed: usize,
+}
+
+fn find_normalized_match(content: &str, search: &str) -> Option<TextMatch> {
+    let haystack = normalize_with_offsets(content);
+    let needle = normalize_with_offsets(search);
+    if needle.text.is_empty() {
+        return None;
+    }
+
+    let start = haystack.text.find(&needle.text)?;
+    let end = start + needle.text.len();
+    let range = haystack.origins[start].start..haystack.origins[end - 1].end;
+
+    let matched = &content[range.clone()];
+    let invisible_in_match = matched.chars().filter(|ch| is_invisible(*ch)).count();
+    let visible_original: String = matched.chars().filter(|ch| !is_invisible(*ch)).collect();
+    let visible_search: String = search.chars().filter(|ch| !is_invisible(*ch)).collect();
+
+    Some(TextMatch {
+        range,
+        normalization: Some(NormalizationNote {
+            nfc: visible_original != visible_search,
+            invisible_chars_removed: invisible_in_match + needle.invisible_removed,
+        }),
+    })
+}
+
+/// Normalizes `input` cluster by cluster so each output byte can be traced
+/// back to the original text. A cluster is a starter followed by its
+/// combining marks, which is the unit NFC composes within.
+fn normalize_with_offsets(input: &str) -> NormalizedText {
+    let mut result = NormalizedText {
+        text: String::with_capacity(input.len()),
+        origins: Vec::with_capacity(input.len()),
+        invisible_removed: 0,
+    };
+
+    let mut cluster_start: Option<usize> = None;
+    for (index, ch) in input.char_indices() {
+        if is_invisible(ch) {
+            if let Some(start) = cluster_start.take() {
+                push_cluster(&mut result, input, start..index);
+            }
+            result.invisible_removed += 1;
+            continue;
+        }
+
+        if canonical_combining_class(ch) == 0 {
+            if let Some(start) = cluster_start.take() {
+                push_cluster(&mut result, input, start..index);
+            }
+            cluster_start = Some(index);
+        } else if cluster_start.is_none() {
+            cluster_start = Some(index);
+        }
+    }
+    if let Some(start) = cluster_start {
+        push_cluster(&mut result, input, start..input.len());
+    }
+
+    result
+}
+
+fn push_cluster(result: &mut NormalizedText, input: &str, range: Range<usize>) {
+    let normalized: String = input[range.clone()].nfc().collect();
+    result.text.push_str(&normalized);
+    result
+        .origins
+        .extend(std::iter::repeat_n(range, normalized.len()));
+}
+
+fn is_invisible(ch: char) -> bool {
+    INVISIBLE_CHARS.contains(&ch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exact_match_has_no_note() {
+        let found = find_match("fn main() {}", "main", MatchOptions::default()).unwrap();
+        assert_eq!(found.range, 3..7);
+        assert!(found.normalization.is_none());
+    }
+
+    #[test]
+    fn test_nfd_search_matches_nfc_content() {
+        let content = "const NAME: &str = \"Jos\u{e9}\";\n";
+        let found = find_match(content, "\"Jose\u{301}\"", MatchOptions::default()).unwrap();
+
+        assert_eq!(&content[found.range.clone()], "\"Jos\u{e9}\"");
+        let note = found.normalization.unwrap();
+        assert!(note.nfc);
+        assert_eq!(note.invisible_chars_removed, 0);
+        assert!(note.to_string().contains("NFC"));
+    }
+
+    #[test]
+    fn test_invisible_characters_in_content_are_ignored() {
+        let content = "let total\u{200B}_count = 0;";
+        let found = find_match(content, "total_count", MatchOptions::default()).unwrap();
+
+        assert_eq!(&content[found.range.clone()], "total\u{200B}_count");
+        let note = found.normalization.unwrap();
+        assert!(!note.nfc);
+        assert_eq!(note.invisible_chars_removed, 1);
+        assert!(note.to_string().contains("1 invisible"));
+    }
+
+    #[test]
+    fn test_invisible_characters_in_search_are_ignored() {
+        let content = "use crate::types;";
+        let found = find_match(content, "\u{FEFF}crate::types", MatchOptions::default()).unwrap();
+
+        assert_eq!(&content[found.range.clone()], "crate::types");
+        assert_eq!(found.normalization.unwrap().invisible_chars_removed, 1);
+    }
+
+    #[test]
+    fn test_normalization_can_be_disabled() {
+        let options = MatchOptions {
+            normalize_unicode: false,
+        };
+        assert!(find_match("caf\u{e9}", "cafe\u{301}", options).is_none());
+    }
+
+    #[test]
+    fn test_no_match_returns_none() {
+        assert!(find_match("alpha", "beta", MatchOptions::default()).is_none());
+        assert!(find_match("alpha", "", MatchOptions::default()).is_none());
+    }
+}
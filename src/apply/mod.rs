@@ -0,0 +1,53 @@
+//! Tool Gateway: deterministic application of approved actions.
+
+pub mod applier;
+pub mod backup;
+pub mod command;
+pub mod conflict;
+pub mod matcher;
+pub mod run;
+pub mod search_replace;
+pub mod undo;
+pub mod unified;
+
+pub use applier::{AppliedChanges, Applier, PatchCheck};
+pub use backup::RunBackups;
+pub use command::{CommandOutput, CommandRunner};
+pub use conflict::{ConflictPrompt, LinePrompt, Resolution};
+pub use matcher::{MatchOptions, NormalizationNote, TextMatch, find_match};
+pub use run::{ApplyReport, RunApply};
+pub use undo::{RunUndo, UndoReport};
+
+use std::path::Path;
+
+use crate::error::NexusError;
+use crate::paths::to_repo_relative;
+
+/// Appends the files under `dir` to `files`, relative to `base`. A missing
+/// `dir` holds no files; other read errors name `operation`.
+pub(super) fn collect_files(
+    base: &Path,
+    dir: &Path,
+    operation: &str,
+    files: &mut Vec<String>,
+) -> Result<(), NexusError> {
+    let io_error = |err| NexusError::IoError {
+        operation: operation.to_string(),
+        path: dir.to_path_buf(),
+        source: err,
+    };
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(io_error(err)),
+    };
+    for entry in entries {
+        let path = entry.map_err(io_error)?.path();
+        if path.is_dir() {
+            collect_files(base, &path, operation, files)?;
+        } else if let Some(relative) = to_repo_relative(base, &path) {
+            files.push(relative);
+        }
+    }
+    Ok(())
+}
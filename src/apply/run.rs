@@ -0,0 +1,1132 @@
+//! Applying the pending actions of a run to the working tree.
+//!
+//! Each applied action is recorded as `tool.executed`, so it is no longer
+//! pending, with the prior contents of its files so it can be undone; see
+//! [`RunUndo`](super::RunUndo). Command actions run only once approved or allowed by the
+//! [`CommandPolicy`]; their output is stored
+//! as a payload and a failing command is recorded as `tool.failed`. Denied actions and actions the applier cannot handle yet are
+//! left pending. With a [`PathPolicy`], an action touching a denied path is
+//! recorded as `permission.denied`, and one writing outside
+//! `allow_paths_write` is only applied once it was approved. With a [`ConflictPrompt`], a patch that no longer matches
+//! its files is resolved interactively instead of failing the whole apply;
+//! every choice is recorded as `conflict.resolved`. With [`GitSettings`],
+//! the actions are applied on the run's own branch and, if asked, each one
+//! is committed and recorded as `git.committed`; see [`crate::git`].
+//! With [`VerifySettings`], the `verify` commands run once anything was
+//! applied, each recorded as `verification.completed` with its output, and
+//! a failing check can roll the run back. An applied plan patch is also
+//! recorded as `plan.patched`.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use serde_json::{Value, json};
+
+use crate::binary::BinaryGuard;
+use crate::error::NexusError;
+use crate::event_log::{
+    EventLogPath, EventLogReader, EventLogWriter, PayloadStore, RunTrace, command_executed,
+    command_failed, conflict_resolved, git_branch_created, git_committed, permission_denied,
+    plan_patched, tool_executed, tool_failed, verification_completed,
+};
+use crate::git::{GitRepo, RunBranch, action_commit_message};
+use crate::handoff::role_name;
+use crate::plan::PlanStore;
+use crate::policy::{CommandPolicy, PathPolicy};
+use crate::preview::pending_actions;
+use crate::redact::Redactor;
+use crate::types::{
+    ActionDetails, CommandDetails, Decision, GitSettings, NexusSettings, ProposedAction,
+    VerifySettings,
+};
+
+use super::applier::{AppliedChanges, Applier};
+use super::backup::RunBackups;
+use super::command::{CommandOutput, CommandRunner};
+use super::conflict::{Conflict, ConflictPrompt, DEFAULT_FUZZY_THRESHOLD, Resolution};
+use super::matcher::{MatchOptions, NormalizationNote};
+use super::undo::{BEFORE_IMAGES_KEY, RunUndo, UndoReport, store_before_images};
+
+/// What applying a run did.
+#[derive(Debug, Default)]
+pub struct ApplyReport {
+    pub applied: Vec<(String, AppliedChanges)>,
+    /// Command actions that ran, successful or not.
+    pub commands: Vec<(String, CommandOutput)>,
+    /// Actions that ran but failed, with the reason; they stay pending.
+    pub failed: Vec<(String, String)>,
+    /// Actions left pending, with the reason.
+    pub skipped: Vec<(String, String)>,
+    /// Conflicting actions the user wants the model to redo.
+    pub reask: Vec<(ProposedAction, Vec<Conflict>)>,
+    /// Branch the actions were applied on, when applied through git.
+    pub branch: Option<RunBranch>,
+    /// Commit of each applied action, when committed per action.
+    pub commits: Vec<(String, String)>,
+    /// Verify commands that ran after applying, up to the first failure.
+    pub verification: Vec<(Vec<String>, CommandOutput)>,
+    /// What was undone after a verify command failed, when rolling back.
+    pub rolled_back: Option<UndoReport>,
+}
+
+impl ApplyReport {
+    /// Whether a verify command failed after applying.
+    pub fn verification_failed(&self) -> bool {
+        self.verification
+            .iter()
+            .any(|(_, output)| !output.success())
+    }
+
+    /// Extra instruction for a retry that regenerates the re-asked actions.
+    pub fn reask_instruction(&self) -> Option<String> {
+        if self.reask.is_empty() {
+            return None;
+        }
+        let mut instruction = String::from(
+            "These changes from the previous attempt no longer apply to the current files. \
+             Regenerate them against the file contents below.\n",
+        );
+        for (action, conflicts) in &self.reask {
+            instruction.push_str(&format!("\n{} ({})\n", action.summary, action.id));
+            for conflict in conflicts {
+                instruction.push_str(&conflict.to_string());
+            }
+        }
+        Some(instruction)
+    }
+
+    /// The report as JSON, for `--output-format json`. Command output is
+    /// included in full rather than printed.
+    pub fn to_json(&self) -> Value {
+        let applied: Vec<Value> = self
+            .applied
+            .iter()
+            .map(|(action_id, changes)| {
+                json!({
+                    "action_id": action_id,
+                    "written": changes.written,
+                    "deleted": changes.deleted,
+                    "conflicted": changes.conflicted,
+                    "match_confidence": changes.match_confidence,
+                    "normalized": normalized_json(&changes.normalized),
+                })
+            })
+            .collect();
+        let commands: Vec<Value> = self
+            .commands
+            .iter()
+            .map(
+                |(action_id, output)| json!({ "action_id": action_id, "output": output.to_json() }),
+            )
+            .collect();
+        let commits: Vec<Value> = self
+            .commits
+            .iter()
+            .map(|(action_id, commit)| json!({ "action_id": action_id, "commit": commit }))
+            .collect();
+        let reask: Vec<Value> = self
+            .reask
+            .iter()
+            .map(|(action, conflicts)| {
+                let conflicts: Vec<String> = conflicts.iter().map(ToString::to_string).collect();
+                json!({ "action_id": action.id, "conflicts": conflicts })
+            })
+            .collect();
+        let verification: Vec<Value> = self
+            .verification
+            .iter()
+            .map(|(argv, output)| json!({ "command": argv, "output": output.to_json() }))
+            .collect();
+        json!({
+            "branch": self.branch.as_ref().map(RunBranch::branch),
+            "applied": applied,
+            "commands": commands,
+            "commits": commits,
+            "failed": reasons_json(&self.failed),
+            "skipped": reasons_json(&self.skipped),
+            "reask": reask,
+            "verification": verification,
+            "rolled_back": self.rolled_back.as_ref().map(UndoReport::to_json),
+        })
+    }
+}
+
+/// `(action ID, reason)` pairs as JSON objects.
+pub(crate) fn reasons_json(reasons: &[(String, String)]) -> Value {
+    reasons
+        .iter()
+        .map(|(action_id, reason)| json!({ "action_id": action_id, "reason": reason }))
+        .collect()
+}
+
+/// `(path, normalization)` pairs as JSON objects.
+fn normalized_json(normalized: &[(String, NormalizationNote)]) -> Value {
+    normalized
+        .iter()
+        .map(|(path, note)| json!({ "path": path, "note": note.to_string() }))
+        .collect()
+}
+
+/// Applies the pending actions of one run.
+pub struct RunApply<'a> {
+    root: PathBuf,
+    run_id: String,
+    prompt: Option<&'a mut dyn ConflictPrompt>,
+    path_policy: Option<PathPolicy>,
+    command_policy: Option<CommandPolicy>,
+    /// Actions to leave pending, with the reason.
+    held: Vec<(String, String)>,
+    sync_interval: Option<u64>,
+    git: Option<GitSettings>,
+    verify: Option<VerifySettings>,
+    redactor: Redactor,
+    binary_guard: BinaryGuard,
+    match_options: MatchOptions,
+}
+
+enum Resolved {
+    Applied(AppliedChanges),
+    Skipped(String),
+}
+
+impl<'a> RunApply<'a> {
+    pub fn new(root: impl Into<PathBuf>, run_id: impl Into<String>) -> Self {
+        Self {
+            root: root.into(),
+            run_id: run_id.into(),
+            prompt: None,
+            path_policy: None,
+            command_policy: None,
+            held: Vec::new(),
+            sync_interval: None,
+            git: None,
+            verify: None,
+            redactor: Redactor::new(),
+            binary_guard: BinaryGuard::default(),
+            match_options: MatchOptions::default(),
+        }
+    }
+
+    /// Applies with the path and command policies, log sync interval, git
+    /// integration, verify checks, redaction patterns, binary allow list and
+    /// search text matching from `settings`.
+    pub fn from_settings(
+        root: impl Into<PathBuf>,
+        run_id: impl Into<String>,
+        settings: &NexusSettings,
+    ) -> Result<Self, NexusError> {
+        let mut apply = Self::new(root, run_id)
+            .with_path_policy(PathPolicy::from_settings(settings)?)
+            .with_command_policy(CommandPolicy::from_settings(settings))
+            .with_sync_interval(settings.event_sync_interval())
+            .with_git(settings.git.clone().unwrap_or_default())
+            .with_redactor(Redactor::from_settings(settings)?)
+            .with_binary_guard(BinaryGuard::from_settings(settings)?)
+            .with_match_options(MatchOptions::from_settings(settings));
+        if let Some(verify) = &settings.verify {
+            apply = apply.with_verify(verify.clone());
+        }
+        Ok(apply)
+    }
+
+    /// Leaves `action_id` pending without applying it, e.g. while it
+    /// awaits an approval nobody was there to give.
+    pub fn with_held(mut self, action_id: impl Into<String>, reason: impl Into<String>) -> Self {
+        self.held.push((action_id.into(), reason.into()));
+        self
+    }
+
+    /// Lets `allow_commands` run command actions without approval and
+    /// `deny_commands` reject them.
+    pub fn with_command_policy(mut self, policy: CommandPolicy) -> Self {
+        self.command_policy = Some(policy);
+        self
+    }
+
+    /// Checks the files each action touches before applying it.
+    pub fn with_path_policy(mut self, policy: PathPolicy) -> Self {
+        self.path_policy = Some(policy);
+        self
+    }
+
+    /// Syncs the log to disk every `events` events; see
+    /// [`EventLogWriter::with_sync_interval`].
+    pub fn with_sync_interval(mut self, events: Option<u64>) -> Self {
+        self.sync_interval = events;
+        self
+    }
+
+    /// Applies the actions on the run's `nexus/<run_id>` branch when the
+    /// project is a git work tree, as `settings` allow.
+    pub fn with_git(mut self, settings: GitSettings) -> Self {
+        self.git = Some(settings);
+        self
+    }
+
+    /// Runs the `settings` commands once actions were applied.
+    pub fn with_verify(mut self, settings: VerifySettings) -> Self {
+        self.verify = Some(settings);
+        self
+    }
+
+    /// Binary targets `guard` allows may be written; others are refused.
+    pub fn with_binary_guard(mut self, guard: BinaryGuard) -> Self {
+        self.binary_guard = guard;
+        self
+    }
+
+    /// Options for locating search/replace blocks.
+    pub fn with_match_options(mut self, options: MatchOptions) -> Self {
+        self.match_options = options;
+        self
+    }
+
+    /// Masks secrets in the events this writes, e.g. the API key and
+    /// `redact_patterns`.
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    /// Resolves conflicting patches through `prompt` instead of failing.
+    pub fn with_prompt(mut self, prompt: &'a mut dyn ConflictPrompt) -> Self {
+        self.prompt = Some(prompt);
+        self
+    }
+
+    pub fn run(mut self) -> Result<ApplyReport, NexusError> {
+        let log_path = EventLogPath::new(&self.root).for_run(&self.run_id)?;
+        let decisions = permission_decisions(&log_path)?;
+        let actions = pending_actions(&log_path)?;
+        let trace = RunTrace::for_log(&log_path)?.unwrap_or_else(RunTrace::from_env);
+        let mut writer = EventLogWriter::open(&log_path)?
+            .with_redactor(self.redactor.clone())
+            .with_sync_interval(self.sync_interval)
+            .with_trace(trace.span());
+        let mut applier = Applier::new(&self.root)
+            .with_backups(RunBackups::new(&self.root, &self.run_id)?)
+            .with_binary_guard(self.binary_guard.clone())
+            .with_match_options(self.match_options);
+        let mut report = ApplyReport::default();
+        let git = match (self.git.clone(), actions.is_empty()) {
+            (Some(settings), false) => GitRepo::discover(&self.root).map(|repo| (repo, settings)),
+            _ => None,
+        };
+        if let Some((repo, settings)) = &git {
+            report.branch = repo.prepare_for_run(&self.run_id, settings)?;
+            if let Some(RunBranch::Created { branch, base }) = &report.branch {
+                writer.append(&git_branch_created(&self.run_id, branch, base.as_deref()))?;
+            }
+        }
+
+        for action in actions {
+            if let Some((_, reason)) = self.held.iter().find(|(id, _)| *id == action.id) {
+                report.skipped.push((action.id.clone(), reason.clone()));
+                continue;
+            }
+            if decisions.denied.contains(&action.id) {
+                report
+                    .skipped
+                    .push((action.id.clone(), "permission denied".to_string()));
+                continue;
+            }
+            if let Some(policy) = &self.path_policy {
+                let check = policy.check(&action);
+                match check.decision {
+                    Decision::Deny => {
+                        writer.append(&permission_denied(
+                            &self.run_id,
+                            &action.id,
+                            &check.reason,
+                        ))?;
+                        report.skipped.push((action.id.clone(), check.reason));
+                        continue;
+                    }
+                    Decision::Ask if !decisions.granted.contains(&action.id) => {
+                        report.skipped.push((
+                            action.id.clone(),
+                            format!("requires approval: {}", check.reason),
+                        ));
+                        continue;
+                    }
+                    _ => {}
+                }
+            }
+            if let ActionDetails::Command(details) = &action.details {
+                let check = self
+                    .command_policy
+                    .as_ref()
+                    .and_then(|policy| policy.check(details));
+                let decision = check.as_ref().map(|check| check.decision);
+                if let Some(check) = check.filter(|check| check.decision == Decision::Deny) {
+                    writer.append(&permission_denied(&self.run_id, &action.id, &check.reason))?;
+                    report.skipped.push((action.id.clone(), check.reason));
+                    continue;
+                }
+                if decision != Some(Decision::Allow) && !decisions.granted.contains(&action.id) {
+                    report
+                        .skipped
+                        .push((action.id.clone(), "command requires approval".to_string()));
+                    continue;
+                }
+                self.run_command(&log_path, &mut writer, &action.id, details, &mut report)?;
+                continue;
+            }
+            if let ActionDetails::Handoff(details) = &action.details {
+                report.skipped.push((
+                    action.id.clone(),
+                    format!("handoff to {} was not accepted", role_name(&details.to)),
+                ));
+                continue;
+            }
+            if !Applier::supports(&action) {
+                report
+                    .skipped
+                    .push((action.id.clone(), "not applicable yet".to_string()));
+                continue;
+            }
+
+            let result = match applier.apply(&action) {
+                Ok(changes) => Ok(Resolved::Applied(changes)),
+                Err(err @ NexusError::PatchFailed { .. }) if self.prompt.is_some() => {
+                    self.resolve(&mut applier, &mut writer, &action, err, &mut report)
+                }
+                Err(err) => Err(err),
+            };
+            match result {
+                Ok(Resolved::Applied(changes)) => {
+                    let files: Vec<String> = changes
+                        .written
+                        .iter()
+                        .chain(&changes.deleted)
+                        .cloned()
+                        .collect();
+                    let store = PayloadStore::for_log(&log_path, &self.run_id)?;
+                    let images =
+                        store_before_images(&store, &self.root, &action.id, &changes.before)?;
+                    let mut event = tool_executed(&self.run_id, &action.id, files.clone());
+                    if let Some(serde_json::Value::Object(payload)) = event.payload.as_mut() {
+                        payload
+                            .insert(BEFORE_IMAGES_KEY.to_string(), serde_json::to_value(images)?);
+                        if let Some(confidence) = changes.match_confidence {
+                            payload.insert("match_confidence".to_string(), confidence.into());
+                        }
+                        if !changes.conflicted.is_empty() {
+                            payload.insert(
+                                "files_conflicted".to_string(),
+                                serde_json::to_value(&changes.conflicted)?,
+                            );
+                        }
+                        if !changes.normalized.is_empty() {
+                            payload.insert(
+                                "files_normalized".to_string(),
+                                normalized_json(&changes.normalized),
+                            );
+                        }
+                    }
+                    writer.append(&event)?;
+                    if let ActionDetails::PlanPatch(details) = &action.details {
+                        let steps = PlanStore::new(&self.root)
+                            .load(&details.plan_id)?
+                            .map_or(0, |plan| plan.steps.len());
+                        writer.append(&plan_patched(
+                            &self.run_id,
+                            &action.id,
+                            &details.plan_id,
+                            &details.patch_mode,
+                            steps,
+                        ))?;
+                    }
+                    report.applied.push((action.id.clone(), changes));
+                    if let Some((repo, _)) = git.as_ref().filter(|(_, git)| git.commit_per_action) {
+                        self.commit_action(repo, &mut writer, &action, &files, &mut report)?;
+                    }
+                }
+                Ok(Resolved::Skipped(reason)) => report.skipped.push((action.id.clone(), reason)),
+                Err(err) => {
+                    writer.append(&tool_failed(&self.run_id, &action.id, &err.to_string()))?;
+                    writer.sync()?;
+                    return Err(err);
+                }
+            }
+        }
+        writer.sync()?;
+        drop(writer);
+        if let Some(settings) = self.verify.as_ref().filter(|_| !report.applied.is_empty()) {
+            self.verify(&log_path, &trace, settings, &mut report)?;
+        }
+        Ok(report)
+    }
+
+    /// Runs the verify commands until one fails, recording each as
+    /// `verification.completed` with its output as the payload, then undoes
+    /// the run if the failure calls for it.
+    fn verify(
+        &self,
+        log_path: &Path,
+        trace: &RunTrace,
+        settings: &VerifySettings,
+        report: &mut ApplyReport,
+    ) -> Result<(), NexusError> {
+        let mut writer = EventLogWriter::open(log_path)?
+            .with_redactor(self.redactor.clone())
+            .with_sync_interval(self.sync_interval)
+            .with_trace(trace.span());
+        let runner = CommandRunner::new(&self.root);
+        for (index, argv) in settings.commands.iter().enumerate() {
+            let details = CommandDetails {
+                argv: argv.clone(),
+                cwd: None,
+                timeout_s: settings.timeout_s,
+                env_allow: Vec::new(),
+                requires_network: false,
+                purpose: None,
+            };
+            // A check that cannot start fails like one that exits non-zero.
+            let output = runner.run(&details).unwrap_or_else(|err| CommandOutput {
+                exit_code: None,
+                stdout: String::new(),
+                stderr: err.to_string(),
+                timed_out: false,
+                duration_ms: 0,
+            });
+            let captured = serde_json::json!({
+                "argv": argv,
+                "stdout": output.stdout,
+                "stderr": output.stderr,
+            });
+            let payload_ref = PayloadStore::for_log(log_path, &self.run_id)?.write(
+                &format!("verify_{index}.json"),
+                captured.to_string().as_bytes(),
+                "application/json",
+                "verify output",
+            )?;
+            let success = output.success();
+            writer.append(
+                &verification_completed(&self.run_id, "verify", argv, success, output.exit_code)
+                    .with_payload_ref(payload_ref),
+            )?;
+            report.verification.push((argv.clone(), output));
+            if !success {
+                break;
+            }
+        }
+        writer.sync()?;
+        drop(writer);
+        if report.verification_failed() && settings.rollback_on_failure {
+            report.rolled_back = Some(
+                RunUndo::new(&self.root, &self.run_id)
+                    .with_redactor(self.redactor.clone())
+                    .with_sync_interval(self.sync_interval)
+                    .run()?,
+            );
+        }
+        Ok(())
+    }
+
+    /// Commits the `files` an applied action changed and records the commit.
+    fn commit_action(
+        &self,
+        repo: &GitRepo,
+        writer: &mut EventLogWriter,
+        action: &ProposedAction,
+        files: &[String],
+        report: &mut ApplyReport,
+    ) -> Result<(), NexusError> {
+        let paths: Vec<PathBuf> = files.iter().map(|file| self.root.join(file)).collect();
+        let message = action_commit_message(&self.run_id, &action.id, &action.summary);
+        let commit = match repo.commit_paths(&paths, &message) {
+            Ok(commit) => commit,
+            Err(err) => {
+                writer.sync()?;
+                return Err(err);
+            }
+        };
+        if let Some(commit) = commit {
+            writer.append(&git_committed(&self.run_id, &action.id, &commit))?;
+            report.commits.push((action.id.clone(), commit));
+        }
+        Ok(())
+    }
+
+    /// Runs an approved command action and records its exit status, with
+    /// its output as the event's payload.
+    fn run_command(
+        &self,
+        log_path: &Path,
+        writer: &mut EventLogWriter,
+        action_id: &str,
+        details: &CommandDetails,
+        report: &mut ApplyReport,
+    ) -> Result<(), NexusError> {
+        let output = match CommandRunner::new(&self.root).run(details) {
+            Ok(output) => output,
+            Err(err) => {
+                writer.append(&tool_failed(&self.run_id, action_id, &err.to_string()))?;
+                writer.sync()?;
+                return Err(err);
+            }
+        };
+        let captured = serde_json::json!({
+            "argv": details.argv,
+            "stdout": output.stdout,
+            "stderr": output.stderr,
+        });
+        let payload_ref = PayloadStore::for_log(log_path, &self.run_id)?.write(
+            &format!("command_{action_id}.json"),
+            captured.to_string().as_bytes(),
+            "application/json",
+            "command output",
+        )?;
+        let event = match output.failure(details.timeout_s) {
+            None => command_executed(
+                &self.run_id,
+                action_id,
+                output.exit_code.unwrap_or_default(),
+                output.duration_ms,
+            ),
+            Some(reason) => {
+                report.failed.push((action_id.to_string(), reason.clone()));
+                command_failed(&self.run_id, action_id, output.exit_code, &reason)
+            }
+        };
+        writer.append(&event.with_payload_ref(payload_ref))?;
+        report.commands.push((action_id.to_string(), output));
+        Ok(())
+    }
+
+    /// Prompts until the conflicting `action` is applied, skipped, or the
+    /// user gives up.
+    fn resolve(
+        &mut self,
+        applier: &mut Applier,
+        writer: &mut EventLogWriter,
+        action: &ProposedAction,
+        mut error: NexusError,
+        report: &mut ApplyReport,
+    ) -> Result<Resolved, NexusError> {
+        let Some(prompt) = self.prompt.as_deref_mut() else {
+            return Err(error);
+        };
+        let mut action = action.clone();
+        let run_id = self.run_id.as_str();
+        loop {
+            let conflicts = applier.conflicts(&action)?;
+            if conflicts.is_empty() {
+                // Not a context mismatch (e.g. a missing file); nothing to resolve.
+                return Err(error);
+            }
+            let resolution = prompt.choose(&action, &conflicts)?;
+            let action_id = action.id.clone();
+            let record = |writer: &mut EventLogWriter, applied: bool| {
+                writer.append(&conflict_resolved(
+                    run_id,
+                    &action_id,
+                    resolution.as_str(),
+                    applied,
+                ))
+            };
+            let attempt = match resolution {
+                Resolution::Fuzzy => applier.apply_fuzzy(&action, fuzzy_threshold(&action)),
+                Resolution::Edit => {
+                    let diff = match &action.details {
+                        ActionDetails::Patch(details) => details.diff.clone().unwrap_or_default(),
+                        _ => String::new(),
+                    };
+                    let edited = prompt.edit(&action, &diff)?;
+                    if let ActionDetails::Patch(details) = &mut action.details {
+                        details.diff = Some(edited);
+                    }
+                    applier.apply(&action)
+                }
+                Resolution::Skip => {
+                    record(writer, false)?;
+                    return Ok(Resolved::Skipped("skipped on conflict".to_string()));
+                }
+                Resolution::Reask => {
+                    record(writer, false)?;
+                    report.reask.push((action, conflicts));
+                    return Ok(Resolved::Skipped("re-asked on conflict".to_string()));
+                }
+                Resolution::Abort => {
+                    record(writer, false)?;
+                    return Err(error);
+                }
+            };
+            match attempt {
+                Ok(changes) => {
+                    record(writer, true)?;
+                    return Ok(Resolved::Applied(changes));
+                }
+                Err(err) => {
+                    record(writer, false)?;
+                    prompt.failed(&err);
+                    error = err;
+                }
+            }
+        }
+    }
+}
+
+fn fuzzy_threshold(action: &ProposedAction) -> f64 {
+    match &action.details {
+        ActionDetails::Patch(details) => details.fuzzy_threshold.unwrap_or(DEFAULT_FUZZY_THRESHOLD),
+        _ => DEFAULT_FUZZY_THRESHOLD,
+    }
+}
+
+/// Actions the run's log records as granted or denied, alone or as part of
+/// an approval group.
+#[derive(Default)]
+pub(crate) struct PermissionDecisions {
+    pub(crate) granted: HashSet<String>,
+    pub(crate) denied: HashSet<String>,
+}
+
+pub(crate) fn permission_decisions(log_path: &Path) -> Result<PermissionDecisions, NexusError> {
+    let events = EventLogReader::open(log_path)?.load_all()?;
+    let mut decisions = PermissionDecisions::default();
+    for event in &events {
+        let set = match event.event_type.as_str() {
+            "permission.granted" => &mut decisions.granted,
+            "permission.denied" => &mut decisions.denied,
+            _ => continue,
+        };
+        set.extend(event.action_ids().into_iter().map(str::to_string));
+    }
+    Ok(decisions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_log::{action_proposed, permission_granted};
+    use crate::plan::Plan;
+    use crate::types::{NexusSettings, PatchMode, PlanPatchDetails};
+
+    const DIFF: &str = "--- a/a.txt\n+++ b/a.txt\n@@ -1,3 +1,3 @@\n one\n-two\n+2\n three\n";
+
+    /// Answers with fixed resolutions and replaces edited diffs with `edited`.
+    struct Scripted {
+        choices: Vec<Resolution>,
+        edited: String,
+    }
+
+    impl ConflictPrompt for Scripted {
+        fn choose(
+            &mut self,
+            _action: &ProposedAction,
+            _conflicts: &[Conflict],
+        ) -> Result<Resolution, NexusError> {
+            Ok(self.choices.remove(0))
+        }
+
+        fn edit(&mut self, _action: &ProposedAction, _diff: &str) -> Result<String, NexusError> {
+            Ok(self.edited.clone())
+        }
+    }
+
+    fn propose(root: &Path, ids: &[&str]) {
+        for id in ids {
+            propose_action(
+                root,
+                ProposedAction::test_diff(id, DIFF).with_summary("Use digits"),
+            );
+        }
+    }
+
+    fn propose_action(root: &Path, action: ProposedAction) {
+        let log_path = EventLogPath::new(root).for_run("run_1").unwrap();
+        let mut writer = EventLogWriter::open(&log_path).unwrap();
+        let payload_ref = PayloadStore::for_log(&log_path, "run_1")
+            .unwrap()
+            .write(
+                &format!("action_{}.json", action.id),
+                &serde_json::to_vec(&action).unwrap(),
+                "application/json",
+                "action",
+            )
+            .unwrap();
+        writer
+            .append(
+                &action_proposed("run_1", &action.id, "patch", &action.summary, None)
+                    .with_payload_ref(payload_ref),
+            )
+            .unwrap();
+    }
+
+    fn events(root: &Path, event_type: &str) -> Vec<serde_json::Value> {
+        let log_path = EventLogPath::new(root).for_run("run_1").unwrap();
+        EventLogReader::open(&log_path)
+            .unwrap()
+            .load_all()
+            .unwrap()
+            .into_iter()
+            .filter(|event| event.event_type == event_type)
+            .filter_map(|event| event.payload)
+            .collect()
+    }
+
+    #[test]
+    fn test_applies_pending_actions_once() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "one\ntwo\nthree\n").unwrap();
+        propose(dir.path(), &["act_1"]);
+
+        let report = RunApply::new(dir.path(), "run_1").run().unwrap();
+        assert_eq!(report.applied.len(), 1);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "one\n2\nthree\n"
+        );
+        assert_eq!(
+            events(dir.path(), "tool.executed")[0]["files_modified"][0],
+            "a.txt"
+        );
+        let backups = RunBackups::new(dir.path(), "run_1").unwrap();
+        assert_eq!(
+            std::fs::read_to_string(backups.dir().join("a.txt")).unwrap(),
+            "one\ntwo\nthree\n"
+        );
+
+        let report = RunApply::new(dir.path(), "run_1").run().unwrap();
+        assert!(report.applied.is_empty());
+    }
+
+    #[test]
+    fn test_conflict_fails_without_prompt() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "one\ntwo!\nthree\n").unwrap();
+        propose(dir.path(), &["act_1"]);
+
+        let err = RunApply::new(dir.path(), "run_1").run().unwrap_err();
+        assert!(matches!(err, NexusError::PatchFailed { .. }));
+        assert_eq!(events(dir.path(), "tool.failed").len(), 1);
+    }
+
+    #[test]
+    fn test_conflict_resolutions_are_recorded() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "one\ntwo!\nthree\n").unwrap();
+        propose(dir.path(), &["act_1", "act_2", "act_3"]);
+        {
+            let log_path = EventLogPath::new(dir.path()).for_run("run_1").unwrap();
+            let mut writer = EventLogWriter::open(&log_path).unwrap();
+            writer
+                .append(&permission_denied("run_1", "act_3", "policy"))
+                .unwrap();
+        }
+
+        // act_1: fuzzy apply is not similar enough, editing the diff fixes
+        // it. act_2 then conflicts with act_1's change and is re-asked.
+        let mut prompt = Scripted {
+            choices: vec![Resolution::Fuzzy, Resolution::Edit, Resolution::Reask],
+            edited: "--- a/a.txt\n+++ b/a.txt\n@@ -2 +2 @@\n-two!\n+2\n".to_string(),
+        };
+        let report = RunApply::new(dir.path(), "run_1")
+            .with_prompt(&mut prompt)
+            .run()
+            .unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "one\n2\nthree\n"
+        );
+        assert_eq!(report.applied.len(), 1);
+        assert_eq!(report.reask.len(), 1);
+        assert!(report.reask_instruction().unwrap().contains("act_2"));
+        assert_eq!(
+            report.skipped,
+            [
+                ("act_2".to_string(), "re-asked on conflict".to_string()),
+                ("act_3".to_string(), "permission denied".to_string())
+            ]
+        );
+
+        let resolved = events(dir.path(), "conflict.resolved");
+        let choices: Vec<_> = resolved
+            .iter()
+            .map(|payload| {
+                (
+                    payload["action_id"].clone(),
+                    payload["resolution"].clone(),
+                    payload["applied"].clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            choices,
+            [
+                ("act_1".into(), "fuzzy".into(), false.into()),
+                ("act_1".into(), "edit".into(), true.into()),
+                ("act_2".into(), "reask".into(), false.into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_path_policy_denies_or_requires_approval() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "one\ntwo\nthree\n").unwrap();
+        propose(dir.path(), &["act_1"]);
+        let settings = |deny: &str, allow: &str| NexusSettings {
+            deny_paths: vec![deny.to_string()],
+            allow_paths_write: vec![allow.to_string()],
+            ..NexusSettings::default()
+        };
+        let policy = |deny, allow| PathPolicy::from_settings(&settings(deny, allow)).unwrap();
+
+        let report = RunApply::new(dir.path(), "run_1")
+            .with_path_policy(policy("*.md", "src/**"))
+            .run()
+            .unwrap();
+        assert!(report.applied.is_empty());
+        assert_eq!(
+            report.skipped,
+            [(
+                "act_1".to_string(),
+                "requires approval: a.txt is outside allow_paths_write".to_string()
+            )]
+        );
+
+        let log_path = EventLogPath::new(dir.path()).for_run("run_1").unwrap();
+        EventLogWriter::open(&log_path)
+            .unwrap()
+            .append(&permission_granted("run_1", "act_1", "once"))
+            .unwrap();
+        let report = RunApply::new(dir.path(), "run_1")
+            .with_path_policy(policy("*.txt", "src/**"))
+            .run()
+            .unwrap();
+        assert_eq!(report.skipped[0].1, "a.txt matches deny_paths");
+        assert_eq!(events(dir.path(), "permission.denied").len(), 1);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "one\ntwo\nthree\n"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_failed_verification_is_recorded_and_rolled_back() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "one\ntwo\nthree\n").unwrap();
+        propose(dir.path(), &["act_1"]);
+        let sh = |script: &str| vec!["sh".to_string(), "-c".to_string(), script.to_string()];
+
+        let report = RunApply::new(dir.path(), "run_1")
+            .with_verify(VerifySettings {
+                commands: vec![sh("true"), sh("echo broken >&2; exit 1"), sh("true")],
+                rollback_on_failure: true,
+                ..VerifySettings::default()
+            })
+            .run()
+            .unwrap();
+        assert_eq!(report.verification.len(), 2);
+        assert!(report.verification_failed());
+        let document = report.to_json();
+        assert_eq!(document["applied"][0]["written"], json!(["a.txt"]));
+        assert_eq!(document["verification"][1]["output"]["stderr"], "broken\n");
+        assert_eq!(document["rolled_back"]["reverted"][0]["action_id"], "act_1");
+        assert_eq!(report.rolled_back.unwrap().reverted.len(), 1);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "one\ntwo\nthree\n"
+        );
+
+        let checks = events(dir.path(), "verification.completed");
+        assert_eq!(checks[0]["success"], true);
+        assert_eq!(checks[1]["success"], false);
+        assert_eq!(checks[1]["exit_code"], 1);
+        assert_eq!(events(dir.path(), "run.reverted").len(), 1);
+        let log_path = EventLogPath::new(dir.path()).for_run("run_1").unwrap();
+        let artifacts = PayloadStore::for_log(&log_path, "run_1")
+            .unwrap()
+            .artifacts_dir();
+        let output = std::fs::read_to_string(artifacts.join("verify_1.json"));
+        assert!(output.is_ok_and(|output| output.contains("broken")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_runs_approved_commands_and_records_exit_status() {
+        let dir = tempfile::tempdir().unwrap();
+        for (id, script) in [
+            ("act_1", "echo built"),
+            ("act_2", "exit 2"),
+            ("act_3", "true"),
+        ] {
+            propose_action(
+                dir.path(),
+                ProposedAction::test(
+                    id,
+                    ActionDetails::Command(CommandDetails {
+                        argv: vec!["sh".to_string(), "-c".to_string(), script.to_string()],
+                        cwd: None,
+                        timeout_s: 10,
+                        env_allow: Vec::new(),
+                        requires_network: false,
+                        purpose: None,
+                    }),
+                )
+                .with_summary("Build"),
+            );
+        }
+        let log_path = EventLogPath::new(dir.path()).for_run("run_1").unwrap();
+        let mut writer = EventLogWriter::open(&log_path).unwrap();
+        for id in ["act_1", "act_2"] {
+            writer
+                .append(&permission_granted("run_1", id, "once"))
+                .unwrap();
+        }
+        drop(writer);
+
+        let report = RunApply::new(dir.path(), "run_1").run().unwrap();
+        assert_eq!(report.commands.len(), 2);
+        assert_eq!(report.commands[0].1.stdout, "built\n");
+        assert_eq!(
+            report.failed,
+            [("act_2".to_string(), "exited with status 2".to_string())]
+        );
+        assert_eq!(
+            report.skipped,
+            [("act_3".to_string(), "command requires approval".to_string())]
+        );
+
+        let executed = events(dir.path(), "tool.executed");
+        assert_eq!(executed[0]["action_id"], "act_1");
+        assert_eq!(executed[0]["exit_code"], 0);
+        let failed = events(dir.path(), "tool.failed");
+        assert_eq!(failed[0]["action_id"], "act_2");
+        assert_eq!(failed[0]["exit_code"], 2);
+
+        // The failed command stays pending; the successful one does not.
+        // allow_commands lets act_3 run without approval.
+        let policy = CommandPolicy::from_settings(&NexusSettings {
+            allow_commands: vec![vec!["sh".to_string(), "-c".to_string(), "true".to_string()]],
+            ..NexusSettings::default()
+        });
+        let report = RunApply::new(dir.path(), "run_1")
+            .with_command_policy(policy)
+            .run()
+            .unwrap();
+        let ran: Vec<_> = report.commands.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ran, ["act_2", "act_3"]);
+    }
+
+    #[test]
+    fn test_git_applies_on_run_branch() {
+        let dir = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            let output = std::process::Command::new("git")
+                .args(args)
+                .current_dir(dir.path())
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "git {args:?} failed");
+            String::from_utf8(output.stdout).unwrap()
+        };
+        git(&["init", "--quiet", "--initial-branch=main"]);
+        std::fs::write(dir.path().join("a.txt"), "one\ntwo\nthree\n").unwrap();
+        git(&["add", "a.txt"]);
+        git(&[
+            "-c",
+            "user.name=Nexus Test",
+            "-c",
+            "user.email=nexus@example.com",
+            "commit",
+            "--quiet",
+            "-m",
+            "initial",
+        ]);
+        propose(dir.path(), &["act_1"]);
+
+        let report = RunApply::new(dir.path(), "run_1")
+            .with_git(GitSettings::default())
+            .run()
+            .unwrap();
+        assert_eq!(report.applied.len(), 1);
+        assert_eq!(report.branch.unwrap().branch(), "nexus/run_1");
+        assert!(report.commits.is_empty());
+        assert_eq!(git(&["branch", "--show-current"]).trim(), "nexus/run_1");
+        let created = events(dir.path(), "git.branch_created");
+        assert_eq!(created[0]["branch"], "nexus/run_1");
+        assert_eq!(created[0]["base"], "main");
+    }
+
+    #[test]
+    fn test_git_commits_each_applied_action() {
+        let dir = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            let output = std::process::Command::new("git")
+                .args(args)
+                .current_dir(dir.path())
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "git {args:?} failed");
+            String::from_utf8(output.stdout).unwrap()
+        };
+        git(&["init", "--quiet", "--initial-branch=main"]);
+        git(&["config", "user.name", "Nexus Test"]);
+        git(&["config", "user.email", "nexus@example.com"]);
+        std::fs::write(dir.path().join("a.txt"), "one\ntwo\nthree\n").unwrap();
+        git(&["add", "a.txt"]);
+        git(&["commit", "--quiet", "-m", "initial"]);
+        propose(dir.path(), &["act_1"]);
+
+        let settings = GitSettings {
+            commit_per_action: true,
+            ..GitSettings::default()
+        };
+        let report = RunApply::new(dir.path(), "run_1")
+            .with_git(settings)
+            .run()
+            .unwrap();
+
+        let (action_id, commit) = &report.commits[0];
+        assert_eq!(action_id, "act_1");
+        assert_eq!(git(&["rev-parse", "HEAD"]).trim(), commit);
+        let message = git(&["log", "-1", "--format=%B"]);
+        assert!(message.starts_with("Use digits\n"));
+        assert!(message.contains("Nexus-Run-Id: run_1\nNexus-Action-Id: act_1"));
+        assert_eq!(events(dir.path(), "git.committed")[0]["commit"], *commit);
+    }
+
+    #[test]
+    fn test_plan_patch_rewrites_the_stored_plan() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = PlanStore::new(dir.path());
+        let plan = Plan::parse("tidy up", r#"{"steps": [{"title": "Add the helper"}]}"#).unwrap();
+        store.save("run_0", &plan).unwrap();
+        std::fs::write(
+            dir.path().join("patch.json"),
+            r#"[{"op": "add", "path": "/steps/-", "value": {"title": "Call it"}}]"#,
+        )
+        .unwrap();
+        propose_action(
+            dir.path(),
+            ProposedAction::test(
+                "act_1",
+                ActionDetails::PlanPatch(PlanPatchDetails {
+                    plan_id: "run_0".to_string(),
+                    patch_ref: "patch.json".to_string(),
+                    patch_mode: PatchMode::JsonPatch,
+                    summary: None,
+                }),
+            )
+            .with_summary("Split the plan"),
+        );
+
+        let report = RunApply::new(dir.path(), "run_1").run().unwrap();
+        assert_eq!(report.applied[0].1.written, [".nexus/runs/run_0/plan.json"]);
+        let patched = store.load("run_0").unwrap().unwrap();
+        assert_eq!(patched.steps[1].title, "Call it");
+        let events = events(dir.path(), "plan.patched");
+        assert_eq!(events[0]["plan_id"], "run_0");
+        assert_eq!(events[0]["patch_mode"], "json_patch");
+        assert_eq!(events[0]["steps"], 2);
+
+        RunUndo::new(dir.path(), "run_1").run().unwrap();
+        assert_eq!(store.load("run_0").unwrap(), Some(plan));
+    }
+}
@@ -0,0 +1,483 @@
+//! Applying search/replace blocks.
+//!
+//! [`MatchMode::Exact`] finds the search text as written (with the Unicode
+//! fallback of [`find_match`]). [`MatchMode::WhitespaceInsensitive`] treats
+//! every run of whitespace as equal and ignores leading and trailing
+//! whitespace, so re-indented or re-wrapped search text still matches.
+//! Search text found nowhere may be fuzzily matched to the most similar
+//! lines, by normalized Levenshtein similarity, and is otherwise settled by
+//! the action's [`OnConflict`] strategy.
+
+use std::ops::Range;
+
+use crate::types::{MatchMode, OnConflict, SearchReplaceBlock};
+
+use super::conflict::conflict_markers;
+use super::matcher::{MatchOptions, NormalizationNote, TextMatch, find_match};
+use super::unified::{Patched, line_ending};
+
+/// Least similarity of the lines [`OnConflict::Theirs`] writes the
+/// replacement over; below it the replacement is appended to the file.
+const THEIRS_MIN_SIMILARITY: f64 = 0.5;
+
+/// Where `block.search` is in `content` under the block's match mode, and
+/// the normalization it needed to be found there.
+pub fn locate(
+    content: &str,
+    block: &SearchReplaceBlock,
+    options: MatchOptions,
+) -> Option<TextMatch> {
+    match block.match_mode {
+        MatchMode::Exact => find_match(content, &block.search, options),
+        MatchMode::WhitespaceInsensitive => {
+            find_ignoring_whitespace(content, &block.search).map(|range| TextMatch {
+                range,
+                normalization: None,
+            })
+        }
+    }
+}
+
+/// `block` with the line endings of its search and replacement text
+/// converted to those of `content`, so a multi-line block matches a CRLF
+/// file and keeps it CRLF.
+pub(crate) fn with_line_ending(content: &str, block: &SearchReplaceBlock) -> SearchReplaceBlock {
+    let newline = line_ending(content);
+    let convert = |text: &str| text.replace("\r\n", "\n").replace('\n', newline);
+    SearchReplaceBlock {
+        search: convert(&block.search),
+        replace: convert(&block.replace),
+        ..block.clone()
+    }
+}
+
+/// Replaces the located search text of `block` in `content`.
+///
+/// On failure, the message names the most similar lines of the file.
+pub fn apply_block(
+    content: &str,
+    block: &SearchReplaceBlock,
+    options: MatchOptions,
+) -> Result<String, String> {
+    replace_located(content, block, options).map(|(updated, _)| updated)
+}
+
+/// Like [`apply_block`], also returning the normalization the search text
+/// was found after.
+fn replace_located(
+    content: &str,
+    block: &SearchReplaceBlock,
+    options: MatchOptions,
+) -> Result<(String, Option<NormalizationNote>), String> {
+    if block.search.trim().is_empty() {
+        return Err("search text is empty".to_string());
+    }
+    let block = &with_line_ending(content, block);
+    let found = locate(content, block, options).ok_or_else(|| not_found(content, &block.search))?;
+    let mut updated = content.to_string();
+    updated.replace_range(found.range, &block.replace);
+    Ok((updated, found.normalization))
+}
+
+/// Like [`apply_block`], but search text that is not found replaces the
+/// most similar lines if they are at least `threshold` similar, and is
+/// otherwise settled by `on_conflict`: the file is kept as is, the
+/// replacement is written over the most similar lines (or appended when
+/// none are similar enough), or both are written between conflict markers.
+///
+/// A replacement written over lines takes their indentation, and the
+/// file keeps its line endings.
+pub fn resolve_block(
+    content: &str,
+    block: &SearchReplaceBlock,
+    options: MatchOptions,
+    threshold: Option<f64>,
+    on_conflict: &OnConflict,
+) -> Result<Patched, String> {
+    let error = match replace_located(content, block, options) {
+        Ok((updated, normalization)) => {
+            return Ok(Patched {
+                content: updated,
+                marked: false,
+                confidence: None,
+                normalization,
+            });
+        }
+        Err(error) => error,
+    };
+    if block.search.trim().is_empty() {
+        return Err(error);
+    }
+    let mut lines: Vec<&str> = content.lines().collect();
+    let needle: Vec<&str> = block.search.lines().collect();
+    let nearest = nearest_window(&lines, &needle);
+    let (mut start, mut end) = match nearest {
+        Some((start, _)) => (start, (start + needle.len()).min(lines.len())),
+        None => (lines.len(), lines.len()),
+    };
+    let replace: Vec<&str> = block.replace.lines().collect();
+    let reindented = reindent(&replace, &needle, &lines[start..end]);
+    let reindented: Vec<&str> = reindented.iter().map(String::as_str).collect();
+    let mut confidence = None;
+    let mut marked = false;
+    let new = match (nearest, threshold) {
+        (Some((_, similarity)), Some(threshold)) if similarity >= threshold => {
+            confidence = Some(similarity);
+            reindented
+        }
+        _ => match on_conflict {
+            OnConflict::Fail => {
+                return Err(match threshold {
+                    Some(threshold) => {
+                        format!("{error}\n({:.0}% similarity required)", threshold * 100.0)
+                    }
+                    None => error,
+                });
+            }
+            OnConflict::Ours => {
+                return Ok(Patched {
+                    content: content.to_string(),
+                    marked: false,
+                    confidence: None,
+                    normalization: None,
+                });
+            }
+            OnConflict::Theirs => match nearest {
+                Some((_, similarity)) if similarity >= THEIRS_MIN_SIMILARITY => reindented,
+                _ => {
+                    (start, end) = (lines.len(), lines.len());
+                    replace
+                }
+            },
+            OnConflict::Marker => {
+                marked = true;
+                conflict_markers(&lines[start..end], &replace)
+            }
+        },
+    };
+    lines.splice(start..end, new);
+    let newline = line_ending(content);
+    let mut output = lines.join(newline);
+    if (content.is_empty() || content.ends_with('\n')) && !output.is_empty() {
+        output.push_str(newline);
+    }
+    Ok(Patched {
+        content: output,
+        marked,
+        confidence,
+        normalization: None,
+    })
+}
+
+/// `replace` moved from the indentation of `search` to that of `target`,
+/// the lines it is written over. Lines that do not start with the search
+/// text's indentation are kept as they are.
+fn reindent(replace: &[&str], search: &[&str], target: &[&str]) -> Vec<String> {
+    let indent = |lines: &[&str]| {
+        lines
+            .iter()
+            .find(|line| !line.trim().is_empty())
+            .map(|line| line[..line.len() - line.trim_start().len()].to_string())
+            .unwrap_or_default()
+    };
+    let (from, to) = (indent(search), indent(target));
+    replace
+        .iter()
+        .map(|line| match line.strip_prefix(from.as_str()) {
+            Some(rest) if !line.trim().is_empty() => format!("{to}{rest}"),
+            _ => line.to_string(),
+        })
+        .collect()
+}
+
+fn not_found(content: &str, search: &str) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let needle: Vec<&str> = search.lines().collect();
+    let Some((start, similarity)) = nearest_window(&lines, &needle) else {
+        return "search text not found; the file is empty".to_string();
+    };
+    let end = (start + needle.len()).min(lines.len());
+    let mut message = format!(
+        "search text not found; closest match at line {} ({:.0}% similar):",
+        start + 1,
+        similarity * 100.0
+    );
+    for line in &lines[start..end] {
+        message.push_str("\n    | ");
+        message.push_str(line);
+    }
+    message
+}
+
+/// Start and similarity of the lines most like `needle`, compared by
+/// characters so a single mistyped line still finds its counterpart.
+fn nearest_window(lines: &[&str], needle: &[&str]) -> Option<(usize, f64)> {
+    if lines.is_empty() || needle.is_empty() {
+        return None;
+    }
+    let wanted = needle.join("\n");
+    let window = needle.len().min(lines.len());
+    let mut best: Option<(usize, f64)> = None;
+    for start in 0..=lines.len() - window {
+        let similarity =
+            strsim::normalized_levenshtein(&wanted, &lines[start..start + window].join("\n"));
+        if best.is_none_or(|(_, best_similarity)| similarity > best_similarity) {
+            best = Some((start, similarity));
+        }
+    }
+    best
+}
+
+/// Finds `search` treating whitespace runs as equal.
+///
+/// When the search text starts with whitespace the match extends back to the
+/// start of its line, and when it ends with whitespace the match takes the
+/// rest of the line, so the replacement's own indentation and line ending
+/// are not doubled.
+fn find_ignoring_whitespace(content: &str, search: &str) -> Option<Range<usize>> {
+    let needle = collapse(search.trim()).text;
+    if needle.is_empty() {
+        return None;
+    }
+    let haystack = collapse(content);
+    let start = haystack.text.find(&needle)?;
+    let end = start + needle.len();
+    let mut range = haystack.origins[start].start..haystack.origins[end - 1].end;
+
+    if search.starts_with(char::is_whitespace) {
+        let line_start = content[..range.start].rfind('\n').map_or(0, |i| i + 1);
+        if content[line_start..range.start].trim().is_empty() {
+            range.start = line_start;
+        }
+    }
+    if search.ends_with(char::is_whitespace) {
+        let rest = &content[range.end..];
+        let line_end = rest.find('\n').map_or(rest.len(), |i| i + 1);
+        if rest[..line_end].trim().is_empty() {
+            range.end += line_end;
+        }
+    }
+    Some(range)
+}
+
+/// Text with whitespace runs collapsed to one space, plus the original byte
+/// range behind each output byte.
+struct Collapsed {
+    text: String,
+    origins: Vec<Range<usize>>,
+}
+
+fn collapse(input: &str) -> Collapsed {
+    let mut text = String::with_capacity(input.len());
+    let mut origins = Vec::with_capacity(input.len());
+    let mut chars = input.char_indices().peekable();
+    while let Some((index, ch)) = chars.next() {
+        if ch.is_whitespace() {
+            let mut end = index + ch.len_utf8();
+            while let Some((next, next_ch)) = chars.peek().copied() {
+                if !next_ch.is_whitespace() {
+                    break;
+                }
+                end = next + next_ch.len_utf8();
+                chars.next();
+            }
+            text.push(' ');
+            origins.push(index..end);
+        } else {
+            text.push(ch);
+            origins.extend(std::iter::repeat_n(
+                index..index + ch.len_utf8(),
+                ch.len_utf8(),
+            ));
+        }
+    }
+    Collapsed { text, origins }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(search: &str, replace: &str, match_mode: MatchMode) -> SearchReplaceBlock {
+        SearchReplaceBlock {
+            file: "src/lib.rs".to_string(),
+            search: search.to_string(),
+            replace: replace.to_string(),
+            match_mode,
+        }
+    }
+
+    const CONTENT: &str = "fn main() {\n    let x = 1;\n    println!(\"{x}\");\n}\n";
+
+    #[test]
+    fn test_exact_match() {
+        let updated = apply_block(
+            CONTENT,
+            &block("let x = 1;", "let x = 2;", MatchMode::Exact),
+            MatchOptions::default(),
+        )
+        .unwrap();
+        assert!(updated.contains("    let x = 2;\n"));
+
+        let err = apply_block(
+            CONTENT,
+            &block("let  x = 1;", "let x = 2;", MatchMode::Exact),
+            MatchOptions::default(),
+        )
+        .unwrap_err();
+        assert!(
+            err.starts_with("search text not found; closest match at line 2"),
+            "{err}"
+        );
+        assert!(err.ends_with("\n    |     let x = 1;"), "{err}");
+    }
+
+    #[test]
+    fn test_whitespace_insensitive_match() {
+        let search = "  let x  =  1;\n        println!(\"{x}\");\n";
+        let replace = "    let y = 1;\n    println!(\"{y}\");\n";
+        let updated = apply_block(
+            CONTENT,
+            &block(search, replace, MatchMode::WhitespaceInsensitive),
+            MatchOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            updated,
+            "fn main() {\n    let y = 1;\n    println!(\"{y}\");\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_whitespace_insensitive_inline_match() {
+        let updated = apply_block(
+            CONTENT,
+            &block("x =\n1", "x = 3", MatchMode::WhitespaceInsensitive),
+            MatchOptions::default(),
+        )
+        .unwrap();
+        assert!(updated.contains("    let x = 3;\n"));
+    }
+
+    #[test]
+    fn test_resolve_block_by_strategy() {
+        let missing = block("let z = 1;\n", "let z = 2;\n", MatchMode::Exact);
+        let resolve = |on_conflict| {
+            resolve_block(
+                CONTENT,
+                &missing,
+                MatchOptions::default(),
+                None,
+                &on_conflict,
+            )
+        };
+
+        assert!(resolve(OnConflict::Fail).is_err());
+        assert_eq!(resolve(OnConflict::Ours).unwrap().content, CONTENT);
+        assert_eq!(
+            resolve(OnConflict::Theirs).unwrap().content,
+            "fn main() {\n    let z = 2;\n    println!(\"{x}\");\n}\n"
+        );
+        let marked = resolve(OnConflict::Marker).unwrap();
+        assert!(marked.marked);
+        assert_eq!(
+            marked.content,
+            "fn main() {\n<<<<<<< ours\n    let x = 1;\n=======\nlet z = 2;\n>>>>>>> theirs\n\
+             \x20   println!(\"{x}\");\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_theirs_appends_when_nothing_is_similar() {
+        let unrelated = block("struct Config;\n", "struct Settings;\n", MatchMode::Exact);
+        let patched = resolve_block(
+            CONTENT,
+            &unrelated,
+            MatchOptions::default(),
+            None,
+            &OnConflict::Theirs,
+        )
+        .unwrap();
+        assert_eq!(patched.content, format!("{CONTENT}struct Settings;\n"));
+    }
+
+    #[test]
+    fn test_resolve_block_keeps_crlf_line_endings() {
+        let content = CONTENT.replace('\n', "\r\n");
+        let typo = block("    let x = 1:\n", "    let x = 2;\n", MatchMode::Exact);
+        let patched = resolve_block(
+            &content,
+            &typo,
+            MatchOptions::default(),
+            Some(0.8),
+            &OnConflict::Fail,
+        )
+        .unwrap();
+        assert_eq!(
+            patched.content,
+            "fn main() {\r\n    let x = 2;\r\n    println!(\"{x}\");\r\n}\r\n"
+        );
+    }
+
+    #[test]
+    fn test_exact_block_keeps_crlf_line_endings() {
+        let content = CONTENT.replace('\n', "\r\n");
+        let multi_line = block(
+            "    let x = 1;\n    println!(\"{x}\");\n",
+            "    let x = 2;\n    println!(\"{x}!\");\n",
+            MatchMode::Exact,
+        );
+        let patched = resolve_block(
+            &content,
+            &multi_line,
+            MatchOptions::default(),
+            None,
+            &OnConflict::Fail,
+        )
+        .unwrap();
+        assert_eq!(patched.confidence, None);
+        assert_eq!(
+            patched.content,
+            "fn main() {\r\n    let x = 2;\r\n    println!(\"{x}!\");\r\n}\r\n"
+        );
+    }
+
+    #[test]
+    fn test_fuzzy_block_records_confidence() {
+        let typo = block("    let x = 1:\n", "    let x = 2;\n", MatchMode::Exact);
+        let patched = resolve_block(
+            CONTENT,
+            &typo,
+            MatchOptions::default(),
+            Some(0.8),
+            &OnConflict::Fail,
+        )
+        .unwrap();
+        assert_eq!(
+            patched.content,
+            "fn main() {\n    let x = 2;\n    println!(\"{x}\");\n}\n"
+        );
+        assert!(patched.confidence.is_some_and(|c| (0.8..1.0).contains(&c)));
+
+        let err = resolve_block(
+            CONTENT,
+            &typo,
+            MatchOptions::default(),
+            Some(0.99),
+            &OnConflict::Fail,
+        )
+        .unwrap_err();
+        assert!(err.ends_with("(99% similarity required)"), "{err}");
+    }
+
+    #[test]
+    fn test_empty_search_is_rejected() {
+        let err = apply_block(
+            CONTENT,
+            &block(" \n", "x", MatchMode::Exact),
+            MatchOptions::default(),
+        )
+        .unwrap_err();
+        assert_eq!(err, "search text is empty");
+    }
+}
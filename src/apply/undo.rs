@@ -0,0 +1,364 @@
+//! Undoing the applied actions of a run.
+//!
+//! When an action is applied, the prior content of every file it touched is
+//! stored as a run artifact and listed in the `before_images` of its
+//! `tool.executed` event, along with a digest of what the action left
+//! behind. [`RunUndo`] restores those files, latest action first, and
+//! records each reverted action as `run.reverted`. A file changed again
+//! since the action was applied is not overwritten unless forced.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::error::NexusError;
+use crate::event_log::payload::sha256_hex;
+use crate::event_log::{
+    EventLogPath, EventLogReader, EventLogWriter, PayloadStore, RunTrace, run_reverted,
+};
+use crate::redact::Redactor;
+use crate::types::{PayloadRef, RunEvent, RunEventKind};
+
+use super::run::reasons_json;
+
+/// Key of the before-images in a `tool.executed` payload.
+pub const BEFORE_IMAGES_KEY: &str = "before_images";
+
+/// A file as it was before an action, and as the action left it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BeforeImage {
+    /// Path relative to the project root.
+    pub path: String,
+    /// Artifact holding the prior content; `None` if the file did not exist.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<PayloadRef>,
+    /// SHA-256 of the file after the action; `None` if it removed the file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after_sha256: Option<String>,
+}
+
+/// Stores the prior contents `before` of the files `action_id` changed
+/// under `root`, and describes them for the action's `tool.executed` event.
+pub(crate) fn store_before_images(
+    store: &PayloadStore,
+    root: &Path,
+    action_id: &str,
+    before: &[(String, Option<Vec<u8>>)],
+) -> Result<Vec<BeforeImage>, NexusError> {
+    let mut images = Vec::with_capacity(before.len());
+    for (index, (path, content)) in before.iter().enumerate() {
+        let content = content
+            .as_ref()
+            .map(|bytes| {
+                store.write(
+                    &format!("before_{action_id}_{index}"),
+                    bytes,
+                    "application/octet-stream",
+                    &format!("{path} before {action_id}"),
+                )
+            })
+            .transpose()?;
+        images.push(BeforeImage {
+            path: path.clone(),
+            content,
+            after_sha256: read_optional(&root.join(path))?.map(|bytes| sha256_hex(&bytes)),
+        });
+    }
+    Ok(images)
+}
+
+/// What undoing a run did.
+#[derive(Debug, Default)]
+pub struct UndoReport {
+    /// Reverted actions, latest first, with the files each restored.
+    pub reverted: Vec<(String, Vec<String>)>,
+    /// Applied actions that could not be reverted, with the reason.
+    pub skipped: Vec<(String, String)>,
+}
+
+impl UndoReport {
+    /// The report as JSON, for `--output-format json`.
+    pub fn to_json(&self) -> Value {
+        let reverted: Vec<Value> = self
+            .reverted
+            .iter()
+            .map(|(action_id, files)| json!({ "action_id": action_id, "files": files }))
+            .collect();
+        json!({ "reverted": reverted, "skipped": reasons_json(&self.skipped) })
+    }
+}
+
+/// Reverts the applied actions of one run.
+pub struct RunUndo {
+    root: PathBuf,
+    run_id: String,
+    force: bool,
+    redactor: Redactor,
+    sync_interval: Option<u64>,
+}
+
+impl RunUndo {
+    pub fn new(root: impl Into<PathBuf>, run_id: impl Into<String>) -> Self {
+        Self {
+            root: root.into(),
+            run_id: run_id.into(),
+            force: false,
+            redactor: Redactor::new(),
+            sync_interval: None,
+        }
+    }
+
+    /// Restores files even if they changed since the action was applied.
+    pub fn with_force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    /// Masks secrets in the events this writes.
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    /// Syncs the log to disk every `events` events; see
+    /// [`EventLogWriter::with_sync_interval`].
+    pub fn with_sync_interval(mut self, events: Option<u64>) -> Self {
+        self.sync_interval = events;
+        self
+    }
+
+    /// Restores the files of every applied action not reverted yet.
+    ///
+    /// # Errors
+    /// - `NexusError::FileChangedSinceApply` if a file was changed after
+    ///   its action was applied and `force` is off; actions reverted until
+    ///   then stay reverted
+    pub fn run(self) -> Result<UndoReport, NexusError> {
+        let log_path = EventLogPath::new(&self.root).for_run(&self.run_id)?;
+        let runs_dir = log_path.parent().unwrap_or_else(|| Path::new("."));
+        let events = EventLogReader::open(&log_path)?.load_all()?;
+        let trace = RunTrace::for_log(&log_path)?.unwrap_or_else(RunTrace::from_env);
+        let mut writer = EventLogWriter::open(&log_path)?
+            .with_redactor(self.redactor.clone())
+            .with_sync_interval(self.sync_interval)
+            .with_trace(trace.span());
+        let mut report = UndoReport::default();
+
+        for (action_id, images) in applied_actions(&events) {
+            let Some(images) = images else {
+                report
+                    .skipped
+                    .push((action_id, "no before-images recorded".to_string()));
+                continue;
+            };
+            if !self.force {
+                if let Some(image) = self.changed_since_apply(&images)? {
+                    writer.sync()?;
+                    return Err(NexusError::FileChangedSinceApply { path: image.path });
+                }
+            }
+            // Undo in reverse, so a path an action touched twice ends up
+            // as it was first.
+            for image in images.iter().rev() {
+                self.restore(runs_dir, image)?;
+            }
+            let files: Vec<String> = images.into_iter().map(|image| image.path).collect();
+            writer.append(&run_reverted(&self.run_id, &action_id, files.clone()))?;
+            report.reverted.push((action_id, files));
+        }
+        writer.sync()?;
+        Ok(report)
+    }
+
+    /// The first of `images` whose file no longer is as the action left it.
+    fn changed_since_apply(
+        &self,
+        images: &[BeforeImage],
+    ) -> Result<Option<BeforeImage>, NexusError> {
+        for image in images {
+            let current = read_optional(&self.root.join(&image.path))?;
+            if current.map(|bytes| sha256_hex(&bytes)) != image.after_sha256 {
+                return Ok(Some(image.clone()));
+            }
+        }
+        Ok(None)
+    }
+
+    fn restore(&self, runs_dir: &Path, image: &BeforeImage) -> Result<(), NexusError> {
+        let target = self.root.join(&image.path);
+        let io_error = |operation: &str, err| NexusError::IoError {
+            operation: operation.to_string(),
+            path: target.clone(),
+            source: err,
+        };
+        match &image.content {
+            Some(content) => {
+                let bytes = PayloadStore::read(runs_dir, content)?;
+                if let Some(parent) = target.parent() {
+                    std::fs::create_dir_all(parent)
+                        .map_err(|err| io_error("create directory", err))?;
+                }
+                std::fs::write(&target, bytes).map_err(|err| io_error("restore file", err))
+            }
+            None => match std::fs::remove_file(&target) {
+                Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                    Err(io_error("remove created file", err))
+                }
+                _ => Ok(()),
+            },
+        }
+    }
+}
+
+/// Applied actions not reverted yet, latest first, with their before-images
+/// if the log has them.
+pub(crate) fn applied_actions(events: &[RunEvent]) -> Vec<(String, Option<Vec<BeforeImage>>)> {
+    let action_id = |event: &RunEvent| {
+        event
+            .payload
+            .as_ref()
+            .and_then(|payload| payload.get("action_id")?.as_str())
+            .map(str::to_string)
+    };
+    let mut applied: Vec<(String, Option<Vec<BeforeImage>>)> = Vec::new();
+    for event in events {
+        let Some(id) = action_id(event) else {
+            continue;
+        };
+        match event.event_type {
+            RunEventKind::ToolExecuted => {
+                let images = event
+                    .payload
+                    .as_ref()
+                    .and_then(|payload| payload.get(BEFORE_IMAGES_KEY))
+                    .and_then(|images| serde_json::from_value(images.clone()).ok());
+                applied.push((id, images));
+            }
+            RunEventKind::RunReverted => applied.retain(|(applied_id, _)| *applied_id != id),
+            _ => {}
+        }
+    }
+    applied.reverse();
+    applied
+}
+
+fn read_optional(path: &Path) -> Result<Option<Vec<u8>>, NexusError> {
+    match std::fs::read(path) {
+        Ok(bytes) => Ok(Some(bytes)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(NexusError::IoError {
+            operation: "read file".to_string(),
+            path: path.to_path_buf(),
+            source: err,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apply::RunApply;
+    use crate::event_log::action_proposed;
+    use crate::types::{ActionDetails, FileCreateDetails, PatchDetails, ProposedAction};
+
+    fn propose(root: &Path, id: &str, details: ActionDetails) {
+        let action = ProposedAction::test(id, details);
+        let log_path = EventLogPath::new(root).for_run("run_1").unwrap();
+        let mut writer = EventLogWriter::open(&log_path).unwrap();
+        let payload_ref = PayloadStore::for_log(&log_path, "run_1")
+            .unwrap()
+            .write(
+                &format!("action_{id}.json"),
+                &serde_json::to_vec(&action).unwrap(),
+                "application/json",
+                "action",
+            )
+            .unwrap();
+        writer
+            .append(
+                &action_proposed("run_1", id, "patch", &action.summary, None)
+                    .with_payload_ref(payload_ref),
+            )
+            .unwrap();
+    }
+
+    fn applied_run(root: &Path) {
+        std::fs::write(root.join("a.txt"), "one\ntwo\n").unwrap();
+        propose(
+            root,
+            "act_1",
+            ActionDetails::Patch(PatchDetails {
+                diff: Some(
+                    "--- a/a.txt\n+++ b/a.txt\n@@ -1,2 +1,2 @@\n one\n-two\n+2\n".to_string(),
+                ),
+                ..Default::default()
+            }),
+        );
+        propose(
+            root,
+            "act_2",
+            ActionDetails::FileCreate(FileCreateDetails {
+                path: "new/b.txt".to_string(),
+                content: "b\n".to_string(),
+                overwrite: false,
+                ignore_if_exists: false,
+            }),
+        );
+        let report = RunApply::new(root, "run_1").run().unwrap();
+        assert_eq!(report.applied.len(), 2);
+    }
+
+    #[test]
+    fn test_undo_restores_files_latest_first() {
+        let dir = tempfile::tempdir().unwrap();
+        applied_run(dir.path());
+
+        let report = RunUndo::new(dir.path(), "run_1").run().unwrap();
+        let reverted: Vec<_> = report.reverted.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(reverted, ["act_2", "act_1"]);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "one\ntwo\n"
+        );
+        assert!(!dir.path().join("new/b.txt").exists());
+
+        // Undo events continue the trace the apply started.
+        let log_path = EventLogPath::new(dir.path()).for_run("run_1").unwrap();
+        let events = EventLogReader::open(&log_path).unwrap().load_all().unwrap();
+        let correlation = |event: &RunEvent| event.trace.clone().unwrap().correlation_id;
+        let applied = events
+            .iter()
+            .find(|event| event.event_type == "tool.executed");
+        assert_eq!(
+            correlation(events.last().unwrap()),
+            correlation(applied.unwrap())
+        );
+
+        // Everything is reverted; undoing again does nothing.
+        let again = RunUndo::new(dir.path(), "run_1").run().unwrap();
+        assert!(again.reverted.is_empty());
+    }
+
+    #[test]
+    fn test_undo_refuses_files_changed_since_apply() {
+        let dir = tempfile::tempdir().unwrap();
+        applied_run(dir.path());
+        std::fs::write(dir.path().join("a.txt"), "edited by hand\n").unwrap();
+
+        let err = RunUndo::new(dir.path(), "run_1").run().unwrap_err();
+        assert!(matches!(err, NexusError::FileChangedSinceApply { ref path } if path == "a.txt"));
+        // act_2 was reverted before act_1 was refused.
+        assert!(!dir.path().join("new/b.txt").exists());
+
+        let report = RunUndo::new(dir.path(), "run_1")
+            .with_force(true)
+            .run()
+            .unwrap();
+        assert_eq!(report.reverted.len(), 1);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "one\ntwo\n"
+        );
+    }
+}
@@ -0,0 +1,536 @@
+//! Parsing and applying unified diffs.
+//!
+//! Hunks must match their context exactly. A hunk is tried at its recorded
+//! line first, then at the nearest position where its old lines match, so
+//! diffs made against a slightly shifted file still apply. Fuzzy
+//! application additionally accepts the most similar block of lines, and an
+//! [`OnConflict`] strategy settles hunks that match nowhere.
+
+use similar::TextDiff;
+
+use crate::paths::normalize_separators;
+use crate::types::{AgendaPatchDetails, OnConflict};
+
+use super::conflict::conflict_markers;
+use super::matcher::NormalizationNote;
+
+const DEV_NULL: &str = "/dev/null";
+
+/// Changes a unified diff makes to one file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilePatch {
+    /// `None` when the file is created.
+    pub old_path: Option<String>,
+    /// `None` when the file is deleted.
+    pub new_path: Option<String>,
+    pub hunks: Vec<Hunk>,
+}
+
+impl FilePatch {
+    /// The path the patch applies to.
+    pub fn path(&self) -> &str {
+        self.new_path
+            .as_deref()
+            .or(self.old_path.as_deref())
+            .unwrap_or_default()
+    }
+}
+
+/// One `@@` section of a file patch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    /// 1-based line the hunk starts at in the old file (0 for empty files).
+    pub old_start: usize,
+    pub lines: Vec<HunkLine>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HunkLine {
+    Context(String),
+    Remove(String),
+    Add(String),
+}
+
+impl Hunk {
+    /// Lines the hunk expects in the old file.
+    pub fn old_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                HunkLine::Context(text) | HunkLine::Remove(text) => Some(text.as_str()),
+                HunkLine::Add(_) => None,
+            })
+            .collect()
+    }
+
+    /// Lines the hunk leaves in the new file.
+    pub fn new_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                HunkLine::Context(text) | HunkLine::Add(text) => Some(text.as_str()),
+                HunkLine::Remove(_) => None,
+            })
+            .collect()
+    }
+}
+
+/// The diff of an agenda patch, with file headers for its `target_path`
+/// added if it has none.
+pub fn agenda_diff(details: &AgendaPatchDetails) -> String {
+    let mut diff = if details.diff.lines().any(|line| line.starts_with("--- ")) {
+        String::new()
+    } else {
+        let target = normalize_separators(&details.target_path);
+        format!("--- a/{target}\n+++ b/{target}\n")
+    };
+    diff.push_str(&details.diff);
+    if !diff.ends_with('\n') {
+        diff.push('\n');
+    }
+    diff
+}
+
+/// Splits a (possibly multi-file) unified diff into per-file patches.
+pub fn parse_unified(diff: &str) -> Result<Vec<FilePatch>, String> {
+    let mut patches: Vec<FilePatch> = Vec::new();
+    let mut old_path: Option<Option<String>> = None;
+    let mut lines = diff.lines().peekable();
+
+    while let Some(line) = lines.next() {
+        if let Some(path) = line.strip_prefix("--- ") {
+            old_path = Some(header_path(path, "a/"));
+        } else if let Some(path) = line.strip_prefix("+++ ") {
+            let old = old_path.take().ok_or("`+++` header without `---` header")?;
+            patches.push(FilePatch {
+                old_path: old,
+                new_path: header_path(path, "b/"),
+                hunks: Vec::new(),
+            });
+        } else if let Some(range) = line.strip_prefix("@@ ") {
+            let patch = patches.last_mut().ok_or("hunk before any file header")?;
+            let (old_start, old_count, new_count) = parse_range(range)?;
+            let mut hunk = Hunk {
+                old_start,
+                lines: Vec::new(),
+            };
+            while let Some(next) = lines.peek() {
+                let old_left = hunk.old_lines().len() < old_count;
+                let new_left = hunk.new_lines().len() < new_count;
+                // Once the header's counts are used up, `---`/`+++` start the
+                // next file; before that they are removed `--` or added `++`
+                // lines.
+                let header = !old_left
+                    && !new_left
+                    && (next.starts_with("--- ") || next.starts_with("+++ "));
+                let hunk_line = if let Some(text) = next.strip_prefix(' ') {
+                    HunkLine::Context(text.to_string())
+                } else if next.is_empty() && old_left && new_left {
+                    // Editors and models often strip the space of blank context lines.
+                    HunkLine::Context(String::new())
+                } else if let Some(text) = next.strip_prefix('+').filter(|_| !header) {
+                    HunkLine::Add(text.to_string())
+                } else if let Some(text) = next.strip_prefix('-').filter(|_| !header) {
+                    HunkLine::Remove(text.to_string())
+                } else if next.starts_with('\\') {
+                    lines.next();
+                    continue;
+                } else {
+                    break;
+                };
+                hunk.lines.push(hunk_line);
+                lines.next();
+            }
+            patch.hunks.push(hunk);
+        }
+    }
+
+    if patches.is_empty() {
+        return Err("no file headers found".to_string());
+    }
+    Ok(patches)
+}
+
+fn header_path(rest: &str, prefix: &str) -> Option<String> {
+    let path = rest.split('\t').next().unwrap_or(rest).trim_end();
+    if path == DEV_NULL {
+        return None;
+    }
+    let path = normalize_separators(path);
+    Some(path.strip_prefix(prefix).unwrap_or(&path).to_string())
+}
+
+/// Reads `-<start>[,<count>] +<start>[,<count>]` from a hunk header as
+/// `(old_start, old_count, new_count)`.
+fn parse_range(range: &str) -> Result<(usize, usize, usize), String> {
+    let malformed = || format!("malformed hunk header `@@ {range}`");
+    let mut parts = range.split_whitespace();
+    let old = parts.next().and_then(|part| part.strip_prefix('-'));
+    let new = parts.next().and_then(|part| part.strip_prefix('+'));
+    let (Some(old), Some(new)) = (old, new) else {
+        return Err(malformed());
+    };
+    let parse = |part: &str| -> Option<(usize, usize)> {
+        match part.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((part.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_count) = parse(old).ok_or_else(malformed)?;
+    let (_, new_count) = parse(new).ok_or_else(malformed)?;
+    Ok((old_start, old_count, new_count))
+}
+
+/// Content after changes that did not all match exactly.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Patched {
+    pub content: String,
+    /// True if conflict markers were written.
+    pub marked: bool,
+    /// Lowest similarity of the lines a change was fuzzily applied to;
+    /// `None` if every change matched exactly.
+    pub confidence: Option<f64>,
+    /// Normalization the search text matched only after, if any.
+    pub normalization: Option<NormalizationNote>,
+}
+
+/// A hunk whose old lines are not in the file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HunkConflict {
+    /// 1-based index of the hunk.
+    pub hunk: usize,
+    pub expected: Vec<String>,
+    /// 1-based line of the most similar block, if the file has any lines.
+    pub actual_start: Option<usize>,
+    /// The most similar block of lines found in the file.
+    pub actual: Vec<String>,
+    /// Similarity of `actual` to `expected`, from 0.0 to 1.0.
+    pub similarity: f64,
+}
+
+/// Applies `hunks` to `content`, returning the new content.
+///
+/// The line endings and trailing newline of `content` are preserved; new
+/// files get a trailing newline.
+pub fn apply_hunks(content: &str, hunks: &[Hunk]) -> Result<String, String> {
+    apply_hunks_with(content, hunks, None, &OnConflict::Fail)
+        .map(|patched| patched.content)
+        .map_err(|conflict| conflict_message(&conflict))
+}
+
+/// Like [`apply_hunks`], but a hunk that does not match exactly is applied
+/// to the most similar block of lines if its similarity reaches `threshold`.
+pub fn fuzzy_apply_hunks(content: &str, hunks: &[Hunk], threshold: f64) -> Result<String, String> {
+    apply_hunks_with(content, hunks, Some(threshold), &OnConflict::Fail)
+        .map(|patched| patched.content)
+        .map_err(|conflict| fuzzy_message(&conflict, threshold))
+}
+
+/// Like [`fuzzy_apply_hunks`] (or [`apply_hunks`] without a `threshold`),
+/// but a hunk that still does not match is settled by `on_conflict`: left
+/// out, applied over the most similar lines, or written next to them
+/// between conflict markers.
+pub fn resolve_hunks(
+    content: &str,
+    hunks: &[Hunk],
+    threshold: Option<f64>,
+    on_conflict: &OnConflict,
+) -> Result<Patched, String> {
+    apply_hunks_with(content, hunks, threshold, on_conflict).map_err(|conflict| match threshold {
+        Some(threshold) => fuzzy_message(&conflict, threshold),
+        None => conflict_message(&conflict),
+    })
+}
+
+/// The first hunk that does not apply exactly, with the closest lines the
+/// file has instead.
+pub fn first_conflict(content: &str, hunks: &[Hunk]) -> Option<HunkConflict> {
+    apply_hunks_with(content, hunks, None, &OnConflict::Fail).err()
+}
+
+fn conflict_message(conflict: &HunkConflict) -> String {
+    format!("hunk {} does not match the file", conflict.hunk)
+}
+
+fn fuzzy_message(conflict: &HunkConflict, threshold: f64) -> String {
+    format!(
+        "{} (best match {:.0}% similar, {:.0}% required)",
+        conflict_message(conflict),
+        conflict.similarity * 100.0,
+        threshold * 100.0
+    )
+}
+
+fn apply_hunks_with(
+    content: &str,
+    hunks: &[Hunk],
+    threshold: Option<f64>,
+    on_conflict: &OnConflict,
+) -> Result<Patched, HunkConflict> {
+    let trailing_newline = content.is_empty() || content.ends_with('\n');
+    let newline = line_ending(content);
+    let mut lines: Vec<&str> = content.lines().collect();
+    let mut offset: isize = 0;
+    let mut marked = false;
+    let mut confidence: Option<f64> = None;
+
+    for (index, hunk) in hunks.iter().enumerate() {
+        let old = hunk.old_lines();
+        let expected = (hunk.old_start.saturating_sub(1) as isize + offset).max(0) as usize;
+        let (start, new) = match find_lines(&lines, &old, expected) {
+            Some(start) => (start, hunk.new_lines()),
+            None => {
+                let (nearest, similarity) = nearest_lines(&lines, &old, expected);
+                let start = nearest.unwrap_or_else(|| expected.min(lines.len()));
+                let end = (start + old.len()).min(lines.len());
+                match (nearest, threshold, on_conflict) {
+                    (Some(_), Some(threshold), _) if similarity >= threshold => {
+                        confidence = Some(confidence.map_or(similarity, |c| c.min(similarity)));
+                        (start, hunk.new_lines())
+                    }
+                    (_, _, OnConflict::Ours) => continue,
+                    (_, _, OnConflict::Theirs) => (start, hunk.new_lines()),
+                    (_, _, OnConflict::Marker) => {
+                        marked = true;
+                        (
+                            start,
+                            conflict_markers(&lines[start..end], &hunk.new_lines()),
+                        )
+                    }
+                    (_, _, OnConflict::Fail) => {
+                        return Err(HunkConflict {
+                            hunk: index + 1,
+                            expected: old.iter().map(|line| line.to_string()).collect(),
+                            actual_start: nearest.map(|start| start + 1),
+                            actual: lines[start..end]
+                                .iter()
+                                .map(|line| line.to_string())
+                                .collect(),
+                            similarity,
+                        });
+                    }
+                }
+            }
+        };
+        let end = (start + old.len()).min(lines.len());
+        offset += new.len() as isize - (end - start) as isize;
+        lines.splice(start..end, new);
+    }
+
+    let mut output = lines.join(newline);
+    if trailing_newline && !output.is_empty() {
+        output.push_str(newline);
+    }
+    Ok(Patched {
+        content: output,
+        marked,
+        confidence,
+        normalization: None,
+    })
+}
+
+/// Line ending of `content`, taken from its first line: `\r\n` for
+/// Windows-style files, `\n` otherwise.
+pub(crate) fn line_ending(content: &str) -> &'static str {
+    match content.split_once('\n') {
+        Some((first, _)) if first.ends_with('\r') => "\r\n",
+        _ => "\n",
+    }
+}
+
+/// Position of `needle` in `haystack` closest to `expected`.
+fn find_lines(haystack: &[&str], needle: &[&str], expected: usize) -> Option<usize> {
+    if needle.is_empty() {
+        return Some(expected.min(haystack.len()));
+    }
+    let last_start = haystack.len().checked_sub(needle.len())?;
+    let matches_at = |start: usize| haystack[start..start + needle.len()] == *needle;
+    let expected = expected.min(last_start);
+    (0..=last_start.max(expected)).find_map(|distance| {
+        [
+            expected.checked_sub(distance),
+            expected.checked_add(distance),
+        ]
+        .into_iter()
+        .flatten()
+        .filter(|start| *start <= last_start)
+        .find(|start| matches_at(*start))
+    })
+}
+
+/// Start and similarity of the block of lines most like `needle`; ties go
+/// to the block closest to `expected`.
+pub(crate) fn nearest_lines(
+    haystack: &[&str],
+    needle: &[&str],
+    expected: usize,
+) -> (Option<usize>, f64) {
+    if haystack.is_empty() || needle.is_empty() {
+        return (None, 0.0);
+    }
+    let window = needle.len().min(haystack.len());
+    let mut best: Option<(usize, f64)> = None;
+    for start in 0..=haystack.len() - window {
+        let ratio =
+            f64::from(TextDiff::from_slices(needle, &haystack[start..start + window]).ratio());
+        let closer = |current: usize| start.abs_diff(expected) < current.abs_diff(expected);
+        match best {
+            Some((current, best_ratio))
+                if ratio < best_ratio || (ratio == best_ratio && !closer(current)) => {}
+            _ => best = Some((start, ratio)),
+        }
+    }
+    match best {
+        Some((start, ratio)) => (Some(start), ratio),
+        None => (None, 0.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1,3 +1,3 @@\n fn a() {}\n-fn b() {}\n+fn c() {}\n fn d() {}\n";
+
+    #[test]
+    fn test_parse_unified() {
+        let patches = parse_unified(DIFF).unwrap();
+        assert_eq!(patches.len(), 1);
+        assert_eq!(patches[0].path(), "src/lib.rs");
+        assert_eq!(patches[0].hunks[0].old_start, 1);
+        assert_eq!(
+            patches[0].hunks[0].old_lines(),
+            ["fn a() {}", "fn b() {}", "fn d() {}"]
+        );
+
+        let created =
+            parse_unified("--- /dev/null\n+++ b/new.txt\n@@ -0,0 +1 @@\n+hello\n").unwrap();
+        assert_eq!(created[0].old_path, None);
+        assert_eq!(created[0].path(), "new.txt");
+
+        assert!(parse_unified("not a diff").is_err());
+    }
+
+    #[test]
+    fn test_apply_hunks() {
+        let patch = &parse_unified(DIFF).unwrap()[0];
+        let updated = apply_hunks("fn a() {}\nfn b() {}\nfn d() {}\n", &patch.hunks).unwrap();
+        assert_eq!(updated, "fn a() {}\nfn c() {}\nfn d() {}\n");
+    }
+
+    #[test]
+    fn test_apply_hunks_keeps_crlf_line_endings() {
+        let content = "fn a() {}\r\nfn b() {}\r\nfn d() {}\r\n";
+        for diff in [DIFF.to_string(), DIFF.replace('\n', "\r\n")] {
+            let patch = &parse_unified(&diff).unwrap()[0];
+            let updated = apply_hunks(content, &patch.hunks).unwrap();
+            assert_eq!(updated, "fn a() {}\r\nfn c() {}\r\nfn d() {}\r\n");
+        }
+    }
+
+    #[test]
+    fn test_apply_hunks_with_shifted_lines() {
+        let patch = &parse_unified(DIFF).unwrap()[0];
+        let content = "// header\n\nfn a() {}\nfn b() {}\nfn d() {}";
+        let updated = apply_hunks(content, &patch.hunks).unwrap();
+        assert_eq!(updated, "// header\n\nfn a() {}\nfn c() {}\nfn d() {}");
+    }
+
+    #[test]
+    fn test_apply_hunks_rejects_mismatched_context() {
+        let patch = &parse_unified(DIFF).unwrap()[0];
+        let err = apply_hunks("fn a() {}\nfn x() {}\nfn d() {}\n", &patch.hunks).unwrap_err();
+        assert_eq!(err, "hunk 1 does not match the file");
+    }
+
+    #[test]
+    fn test_first_conflict_reports_nearest_lines() {
+        let patch = &parse_unified(DIFF).unwrap()[0];
+        let content = "// header\nfn a() {}\nfn bb() {}\nfn d() {}\n";
+        let conflict = first_conflict(content, &patch.hunks).unwrap();
+        assert_eq!(conflict.hunk, 1);
+        assert_eq!(conflict.actual_start, Some(2));
+        assert_eq!(conflict.actual, ["fn a() {}", "fn bb() {}", "fn d() {}"]);
+        assert!(conflict.similarity > 0.6 && conflict.similarity < 1.0);
+
+        assert!(first_conflict("fn a() {}\nfn b() {}\nfn d() {}\n", &patch.hunks).is_none());
+    }
+
+    #[test]
+    fn test_fuzzy_apply_hunks() {
+        let patch = &parse_unified(DIFF).unwrap()[0];
+        let content = "fn a() {}\nfn bb() {}\nfn d() {}\n";
+        assert_eq!(
+            fuzzy_apply_hunks(content, &patch.hunks, 0.6).unwrap(),
+            "fn a() {}\nfn c() {}\nfn d() {}\n"
+        );
+        let err = fuzzy_apply_hunks(content, &patch.hunks, 0.99).unwrap_err();
+        assert!(err.starts_with("hunk 1 does not match the file (best match"));
+
+        let patched = resolve_hunks(content, &patch.hunks, Some(0.6), &OnConflict::Fail).unwrap();
+        assert!(patched.confidence.is_some_and(|c| (0.6..1.0).contains(&c)));
+        let exact = resolve_hunks(
+            "fn a() {}\nfn b() {}\nfn d() {}\n",
+            &patch.hunks,
+            Some(0.6),
+            &OnConflict::Fail,
+        )
+        .unwrap();
+        assert_eq!(exact.confidence, None);
+    }
+
+    #[test]
+    fn test_resolve_hunks_by_strategy() {
+        let patch = &parse_unified(DIFF).unwrap()[0];
+        let content = "fn a() {}\nfn x() {}\nfn d() {}\n";
+        let resolve = |on_conflict| resolve_hunks(content, &patch.hunks, None, &on_conflict);
+
+        assert!(resolve(OnConflict::Fail).is_err());
+        assert_eq!(resolve(OnConflict::Ours).unwrap().content, content);
+        assert_eq!(
+            resolve(OnConflict::Theirs).unwrap().content,
+            "fn a() {}\nfn c() {}\nfn d() {}\n"
+        );
+        let marked = resolve(OnConflict::Marker).unwrap();
+        assert!(marked.marked);
+        assert_eq!(
+            marked.content,
+            "<<<<<<< ours\nfn a() {}\nfn x() {}\nfn d() {}\n=======\n\
+             fn a() {}\nfn c() {}\nfn d() {}\n>>>>>>> theirs\n"
+        );
+    }
+
+    #[test]
+    fn test_blank_context_lines_without_space() {
+        let diff = "--- a/x\n+++ b/x\n@@ -1,3 +1,3 @@\n a\n\n-b\n+c\n\n--- a/y\n+++ b/y\n@@ -1 +1 @@\n-y\n+z\n";
+        let patches = parse_unified(diff).unwrap();
+        assert_eq!(patches.len(), 2);
+        assert_eq!(patches[0].hunks[0].old_lines(), ["a", "", "b"]);
+        assert_eq!(
+            apply_hunks("a\n\nb\n", &patches[0].hunks).unwrap(),
+            "a\n\nc\n"
+        );
+    }
+
+    #[test]
+    fn test_removed_and_added_lines_that_look_like_headers() {
+        let diff = "--- a/q.sql\n+++ b/q.sql\n@@ -1,2 +1,2 @@\n--- comment\n+++ counter\n select 1;\n\
+                    --- a/y\n+++ b/y\n@@ -1 +1 @@\n-y\n+z\n";
+        let patches = parse_unified(diff).unwrap();
+        assert_eq!(patches.len(), 2);
+        assert_eq!(patches[0].hunks[0].old_lines(), ["-- comment", "select 1;"]);
+        assert_eq!(patches[0].hunks[0].new_lines(), ["++ counter", "select 1;"]);
+        assert_eq!(
+            apply_hunks("-- comment\nselect 1;\n", &patches[0].hunks).unwrap(),
+            "++ counter\nselect 1;\n"
+        );
+        assert_eq!(patches[1].path(), "y");
+    }
+
+    #[test]
+    fn test_apply_hunks_to_new_file() {
+        let patch = &parse_unified("--- /dev/null\n+++ b/new.txt\n@@ -0,0 +1,2 @@\n+one\n+two\n")
+            .unwrap()[0];
+        assert_eq!(apply_hunks("", &patch.hunks).unwrap(), "one\ntwo\n");
+    }
+}
@@ -0,0 +1,491 @@
+//! Autopilot: applying a run's actions in bounded batches without asking
+//! about each one.
+//!
+//! In autopilot mode the permission gate approves the actions the
+//! `autopilot` settings allow ([`auto_approval`]): file changes with
+//! `auto_approve_patches`, test commands with `auto_approve_tests`, handoffs
+//! with `auto_handoffs`. Deny rules, `deny_paths`, writes outside
+//! `allow_paths_write` and flagged patches still stop an action.
+//! [`RunAutopilot`] then packs the approved actions into batches of at most
+//! `max_batch_steps` actions and `max_batch_cu` complexity units
+//! ([`crate::cost`]), applies one batch at a time, and asks before going on
+//! with the next. A failed action or verify check ends the run's autopilot,
+//! as does an action too large for any batch.
+
+use std::collections::HashSet;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use serde_json::{Value, json};
+
+use crate::apply::run::permission_decisions;
+use crate::apply::{ApplyReport, LinePrompt, RunApply};
+use crate::cost::action_cu;
+use crate::error::NexusError;
+use crate::event_log::EventLogPath;
+use crate::policy::PermissionGate;
+use crate::preview::pending_actions;
+use crate::redact::Redactor;
+use crate::resume::{ResumeReport, RunResume};
+use crate::types::{ActionDetails, AutopilotConfig, NexusSettings, PermissionMode, ProposedAction};
+
+/// Test runners recognized by their program name alone.
+const TEST_RUNNERS: &[&str] = &["pytest", "jest", "vitest", "ctest", "rspec", "phpunit"];
+
+/// Reason actions outside the current batch are held back.
+const LATER_BATCH: &str = "left for a later autopilot batch";
+
+/// The `autopilot` setting that approves `action` without asking, if any.
+pub fn auto_approval(config: &AutopilotConfig, action: &ProposedAction) -> Option<&'static str> {
+    match &action.details {
+        ActionDetails::Patch(_)
+        | ActionDetails::FileCreate(_)
+        | ActionDetails::FileRename(_)
+        | ActionDetails::FileDelete(_)
+            if config.auto_approve_patches =>
+        {
+            Some("auto_approve_patches")
+        }
+        ActionDetails::Command(details)
+            if config.auto_approve_tests && is_test_command(&details.argv) =>
+        {
+            Some("auto_approve_tests")
+        }
+        ActionDetails::Handoff(_) if config.auto_handoffs => Some("auto_handoffs"),
+        _ => None,
+    }
+}
+
+/// True if `argv` runs a test suite, e.g. `cargo test` or `pytest`.
+pub fn is_test_command(argv: &[String]) -> bool {
+    let Some((program, args)) = argv.split_first() else {
+        return false;
+    };
+    let name = Path::new(program)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(program);
+    TEST_RUNNERS.contains(&name) || args.iter().any(|arg| arg == "test")
+}
+
+/// Actions applied together, and their total complexity units.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AutopilotBatch {
+    pub action_ids: Vec<String>,
+    pub cu: u32,
+}
+
+/// Packs `actions` in order into batches within `config`'s limits.
+///
+/// Packing stops at an action whose units alone exceed `max_batch_cu`;
+/// the reason is returned with the batches planned before it.
+pub fn plan_batches(
+    config: &AutopilotConfig,
+    actions: &[ProposedAction],
+) -> (Vec<AutopilotBatch>, Option<String>) {
+    let max_steps = usize::try_from(config.max_batch_steps.max(1)).unwrap_or(usize::MAX);
+    let mut batches = Vec::new();
+    let mut current = AutopilotBatch::default();
+    for action in actions {
+        let cu = action_cu(action);
+        if cu > config.max_batch_cu {
+            if !current.action_ids.is_empty() {
+                batches.push(current);
+            }
+            let reason = format!(
+                "{} needs {cu} CU, more than max_batch_cu ({})",
+                action.id, config.max_batch_cu
+            );
+            return (batches, Some(reason));
+        }
+        if current.action_ids.len() == max_steps
+            || current.cu.saturating_add(cu) > config.max_batch_cu
+        {
+            batches.push(std::mem::take(&mut current));
+        }
+        current.action_ids.push(action.id.clone());
+        current.cu = current.cu.saturating_add(cu);
+    }
+    if !current.action_ids.is_empty() {
+        batches.push(current);
+    }
+    (batches, None)
+}
+
+/// Asks whether autopilot goes on with the next batch.
+pub trait BatchPrompt {
+    /// Whether to apply `next` now that `applied` batches were applied.
+    fn continue_batch(&mut self, applied: usize, next: &AutopilotBatch)
+    -> Result<bool, NexusError>;
+}
+
+impl<R: BufRead, W: Write> BatchPrompt for LinePrompt<R, W> {
+    fn continue_batch(
+        &mut self,
+        applied: usize,
+        next: &AutopilotBatch,
+    ) -> Result<bool, NexusError> {
+        let io_error = |err| NexusError::IoError {
+            operation: "prompt for next batch".to_string(),
+            path: PathBuf::from("<terminal>"),
+            source: err,
+        };
+        self.say(&format!(
+            "\nApplied {applied} batch(es). Next: {} ({} CU)",
+            next.action_ids.join(", "),
+            next.cu
+        ))
+        .map_err(io_error)?;
+        loop {
+            let Some(line) = self.ask("Continue? [y/n] ").map_err(io_error)? else {
+                return Ok(false);
+            };
+            match line.trim().to_ascii_lowercase().as_str() {
+                "y" | "yes" => return Ok(true),
+                "n" | "no" => return Ok(false),
+                other => self
+                    .say(&format!("Unknown choice `{other}`"))
+                    .map_err(io_error)?,
+            }
+        }
+    }
+}
+
+/// What autopilot did with a run.
+#[derive(Debug, Default)]
+pub struct AutopilotReport {
+    /// Decisions recorded for actions that had none.
+    pub decided: ResumeReport,
+    /// Applied batches, in order.
+    pub batches: Vec<(AutopilotBatch, ApplyReport)>,
+    /// Why autopilot stopped with actions left pending.
+    pub stopped: Option<String>,
+}
+
+impl AutopilotReport {
+    /// The report as JSON, for `--output-format json`.
+    pub fn to_json(&self) -> Value {
+        let batches: Vec<Value> = self
+            .batches
+            .iter()
+            .map(|(batch, applied)| {
+                json!({
+                    "action_ids": batch.action_ids,
+                    "cu": batch.cu,
+                    "apply": applied.to_json(),
+                })
+            })
+            .collect();
+        json!({
+            "decided": self.decided.to_json(),
+            "batches": batches,
+            "stopped": self.stopped,
+        })
+    }
+}
+
+/// Decides and applies the pending actions of one run in batches.
+pub struct RunAutopilot<'a> {
+    root: PathBuf,
+    run_id: String,
+    settings: NexusSettings,
+    prompt: Option<&'a mut dyn BatchPrompt>,
+    redactor: Option<Redactor>,
+}
+
+impl<'a> RunAutopilot<'a> {
+    /// Autopilot for `run_id` with the policies and limits of `settings`,
+    /// whatever their `permission_mode`.
+    pub fn new(
+        root: impl Into<PathBuf>,
+        run_id: impl Into<String>,
+        settings: NexusSettings,
+    ) -> Self {
+        Self {
+            root: root.into(),
+            run_id: run_id.into(),
+            settings: NexusSettings {
+                permission_mode: PermissionMode::Autopilot,
+                ..settings
+            },
+            prompt: None,
+            redactor: None,
+        }
+    }
+
+    /// Asks `prompt` before each batch after the first. Without a prompt
+    /// only the first batch is applied.
+    pub fn with_prompt(mut self, prompt: &'a mut dyn BatchPrompt) -> Self {
+        self.prompt = Some(prompt);
+        self
+    }
+
+    /// Masks secrets in the events this writes; without one, only the
+    /// `redact_patterns` of its settings and the built-in patterns are.
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = Some(redactor);
+        self
+    }
+
+    /// Records a decision for each undecided action, then applies the
+    /// approved ones batch by batch.
+    pub fn run(mut self) -> Result<AutopilotReport, NexusError> {
+        let config = self.settings.autopilot.clone().unwrap_or_default();
+        let redactor = match self.redactor.take() {
+            Some(redactor) => redactor,
+            None => Redactor::from_settings(&self.settings)?,
+        };
+        let decided = RunResume::new(&self.root, &self.run_id)
+            .with_permission_gate(PermissionGate::from_settings(&self.settings)?)
+            .with_redactor(redactor.clone())
+            .with_sync_interval(self.settings.event_sync_interval())
+            .run()?;
+        let log_path = EventLogPath::new(&self.root).for_run(&self.run_id)?;
+        let denied = permission_decisions(&log_path)?.denied;
+        let awaiting: HashSet<&str> = decided.awaiting.iter().map(|(id, _)| id.as_str()).collect();
+        let approved: Vec<ProposedAction> = pending_actions(&log_path)?
+            .into_iter()
+            .filter(|action| !denied.contains(&action.id) && !awaiting.contains(action.id.as_str()))
+            .collect();
+
+        let (batches, mut stopped) = plan_batches(&config, &approved);
+        let mut report = AutopilotReport::default();
+        for (index, batch) in batches.into_iter().enumerate() {
+            if index > 0 {
+                let go_on = match self.prompt.as_deref_mut() {
+                    Some(prompt) => prompt.continue_batch(index, &batch)?,
+                    None => false,
+                };
+                if !go_on {
+                    stopped = Some(format!("stopped before batch {}", index + 1));
+                    break;
+                }
+            }
+            let mut apply = RunApply::from_settings(&self.root, &self.run_id, &self.settings)?
+                .with_redactor(redactor.clone());
+            for (action_id, reason) in &decided.awaiting {
+                apply = apply.with_held(action_id, reason);
+            }
+            for action in approved
+                .iter()
+                .filter(|action| !batch.action_ids.contains(&action.id))
+            {
+                apply = apply.with_held(&action.id, LATER_BATCH);
+            }
+            let applied = apply.run()?;
+            let failed = !applied.failed.is_empty() || applied.verification_failed();
+            report.batches.push((batch, applied));
+            if failed {
+                stopped = Some(format!("batch {} failed", index + 1));
+                break;
+            }
+        }
+        report.decided = decided;
+        report.stopped = stopped;
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_log::{EventLogWriter, PayloadStore, action_proposed};
+    use crate::types::{CommandDetails, PatchDetails};
+
+    fn patch(id: &str, files: &[&str]) -> ProposedAction {
+        ProposedAction::test_patch(id, files)
+    }
+
+    fn command(argv: &[&str]) -> ProposedAction {
+        ProposedAction::test(
+            "act_cmd",
+            ActionDetails::Command(CommandDetails {
+                argv: argv.iter().map(|arg| arg.to_string()).collect(),
+                cwd: None,
+                timeout_s: 60,
+                env_allow: Vec::new(),
+                requires_network: false,
+                purpose: None,
+            }),
+        )
+    }
+
+    /// Answers each batch prompt with the next of `answers`.
+    struct Scripted {
+        answers: Vec<bool>,
+        asked: usize,
+    }
+
+    impl BatchPrompt for Scripted {
+        fn continue_batch(
+            &mut self,
+            applied: usize,
+            _next: &AutopilotBatch,
+        ) -> Result<bool, NexusError> {
+            assert_eq!(applied, self.asked + 1);
+            self.asked += 1;
+            Ok(self.answers.remove(0))
+        }
+    }
+
+    #[test]
+    fn test_auto_approval_follows_settings() {
+        let config = AutopilotConfig {
+            auto_approve_tests: true,
+            ..AutopilotConfig::default()
+        };
+        assert_eq!(
+            auto_approval(&config, &command(&["cargo", "test"])),
+            Some("auto_approve_tests")
+        );
+        assert_eq!(
+            auto_approval(&config, &command(&["/usr/bin/pytest", "-q"])),
+            Some("auto_approve_tests")
+        );
+        assert_eq!(auto_approval(&config, &command(&["cargo", "build"])), None);
+        assert_eq!(auto_approval(&config, &patch("act_1", &["a.rs"])), None);
+    }
+
+    #[test]
+    fn test_batches_respect_steps_and_cu() {
+        // One file at the root, risk 1: 2 + 3 + 5 = 10 CU each.
+        let actions: Vec<_> = (1..=5)
+            .map(|n| patch(&format!("act_{n}"), &["a.rs"]))
+            .collect();
+        let config = AutopilotConfig {
+            max_batch_cu: 30,
+            max_batch_steps: 2,
+            ..AutopilotConfig::default()
+        };
+        let (batches, stopped) = plan_batches(&config, &actions);
+        let sizes: Vec<_> = batches.iter().map(|batch| batch.action_ids.len()).collect();
+        assert_eq!(sizes, [2, 2, 1]);
+        assert!(stopped.is_none());
+
+        let config = AutopilotConfig {
+            max_batch_cu: 25,
+            max_batch_steps: 8,
+            ..AutopilotConfig::default()
+        };
+        let (batches, _) = plan_batches(&config, &actions);
+        assert_eq!(batches[0].cu, 20);
+        assert_eq!(batches.len(), 3);
+
+        let mut large = actions.clone();
+        large.insert(1, patch("act_big", &["a/1.rs", "b/2.rs", "c/3.rs"]));
+        large[1].risk = 3;
+        let (batches, stopped) = plan_batches(&config, &large);
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].action_ids, ["act_1"]);
+        assert_eq!(
+            stopped.as_deref(),
+            Some("act_big needs 30 CU, more than max_batch_cu (25)")
+        );
+    }
+
+    fn propose(root: &Path, count: usize) {
+        let log_path = EventLogPath::new(root).for_run("run_1").unwrap();
+        let mut writer = EventLogWriter::open(&log_path).unwrap();
+        for n in 1..=count {
+            let path = format!("f{n}.txt");
+            std::fs::write(root.join(&path), "one\n").unwrap();
+            let mut action = patch(&format!("act_{n}"), &[]);
+            action.details = ActionDetails::Patch(PatchDetails {
+                diff: Some(format!(
+                    "--- a/{path}\n+++ b/{path}\n@@ -1 +1 @@\n-one\n+1\n"
+                )),
+                ..Default::default()
+            });
+            let payload_ref = PayloadStore::for_log(&log_path, "run_1")
+                .unwrap()
+                .write(
+                    &format!("action_{}.json", action.id),
+                    &serde_json::to_vec(&action).unwrap(),
+                    "application/json",
+                    "action",
+                )
+                .unwrap();
+            writer
+                .append(
+                    &action_proposed("run_1", &action.id, "patch", &action.summary, None)
+                        .with_payload_ref(payload_ref),
+                )
+                .unwrap();
+        }
+        writer.sync().unwrap();
+    }
+
+    fn settings() -> NexusSettings {
+        NexusSettings {
+            autopilot: Some(AutopilotConfig {
+                max_batch_steps: 2,
+                auto_approve_patches: true,
+                ..AutopilotConfig::default()
+            }),
+            ..NexusSettings::default()
+        }
+    }
+
+    #[test]
+    fn test_applies_first_batch_then_asks() {
+        let dir = tempfile::tempdir().unwrap();
+        propose(dir.path(), 5);
+        let mut prompt = Scripted {
+            answers: vec![true, false],
+            asked: 0,
+        };
+
+        let report = RunAutopilot::new(dir.path(), "run_1", settings())
+            .with_prompt(&mut prompt)
+            .run()
+            .unwrap();
+        assert_eq!(report.decided.granted.len(), 5);
+        assert_eq!(report.batches.len(), 2);
+        assert_eq!(report.stopped.as_deref(), Some("stopped before batch 3"));
+        let applied: usize = report
+            .batches
+            .iter()
+            .map(|(_, applied)| applied.applied.len())
+            .sum();
+        assert_eq!(applied, 4);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("f4.txt")).unwrap(),
+            "1\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("f5.txt")).unwrap(),
+            "one\n"
+        );
+
+        // Without a prompt, the next run applies one batch and stops.
+        let report = RunAutopilot::new(dir.path(), "run_1", settings())
+            .run()
+            .unwrap();
+        assert_eq!(report.batches.len(), 1);
+        assert!(report.stopped.is_none());
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("f5.txt")).unwrap(),
+            "1\n"
+        );
+    }
+
+    #[test]
+    fn test_unapproved_actions_stay_pending() {
+        let dir = tempfile::tempdir().unwrap();
+        propose(dir.path(), 1);
+        let settings = NexusSettings {
+            autopilot: None,
+            ..settings()
+        };
+
+        let report = RunAutopilot::new(dir.path(), "run_1", settings)
+            .run()
+            .unwrap();
+        assert_eq!(report.decided.awaiting.len(), 1);
+        assert!(report.batches.is_empty());
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("f1.txt")).unwrap(),
+            "one\n"
+        );
+    }
+}
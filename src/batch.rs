@@ -0,0 +1,899 @@
+//! Batch mode: run several tasks from one YAML file.
+//!
+//! Each entry becomes its own run with its own run ID and event log under
+//! `.nexus/runs/`. Runs execute sequentially unless more jobs are allowed.
+//! Each finished run gets its `<run_id>.summary.md` next to its log, and a
+//! consolidated markdown summary is written once all have finished.
+//!
+//! ```yaml
+//! tasks:
+//!   - task: rename getUserData to fetchUserProfile
+//!     files: ["src/**/*.ts"]
+//!   - task: extract validation logic
+//!     files: ["src/forms/*.rs"]
+//!     options:
+//!       format: search_replace
+//!       max_tokens: 4000
+//!       output: json_schema
+//!       plan: true
+//! ```
+//!
+//! With `plan: true` and a planner registered through
+//! [`BatchRunner::with_role_executor`], the task is broken down into steps
+//! first and executed one step at a time; see [`crate::plan`].
+
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use futures::StreamExt;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::apply::Applier;
+use crate::binary::BinaryGuard;
+use crate::context::collect_files;
+use crate::error::NexusError;
+use crate::event_log::{AsyncEventLogWriter, EventLogPath, EventLogWriter, RunTrace, helpers};
+use crate::executor::{CodexAdapter, ExecuteOptions, FileContext, OutputFormat, RunPhase};
+use crate::export::write_summary;
+use crate::handoff::{MAX_HANDOFFS, handoff_task, handoffs};
+use crate::plan::PlanStore;
+use crate::policy::deps::{AuditOutcome, run_audit};
+use crate::policy::{PermissionGate, PolicyDecision};
+use crate::preview::render_dry_run;
+use crate::redact::Redactor;
+use crate::review::review_request_body;
+use crate::types::{
+    ActionDetails, AgentRole, Decision, PatchFormat, ProposedAction, RunEvent, RunId, RunIdScheme,
+    RunStatus,
+};
+
+/// Scope recorded on permissions granted without a prompt.
+pub(crate) const AUTOPILOT_SCOPE: &str = "autopilot";
+/// Verification name recorded for the dependency audit.
+const DEPS_AUDIT_NAME: &str = "deps audit";
+
+/// Attempts made to find a run ID not already used by this batch.
+const MAX_RUN_ID_ATTEMPTS: usize = 8;
+
+/// Tasks read from a batch file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BatchFile {
+    pub tasks: Vec<BatchTask>,
+}
+
+/// One batch entry: a task, the files it may see, and per-task options.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BatchTask {
+    pub task: String,
+
+    /// Glob patterns, relative to the project root, selecting context files.
+    #[serde(default)]
+    pub files: Vec<String>,
+
+    #[serde(default)]
+    pub options: BatchTaskOptions,
+
+    /// Run this task follows up on, e.g. the run that handed it off.
+    #[serde(default)]
+    pub parent_run_id: Option<RunId>,
+}
+
+/// Per-task overrides of the execute options.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BatchTaskOptions {
+    #[serde(default)]
+    pub dry_run: bool,
+
+    #[serde(default)]
+    pub max_tokens: Option<u32>,
+
+    #[serde(default)]
+    pub temperature: Option<f32>,
+
+    #[serde(default)]
+    pub format: PatchFormat,
+
+    #[serde(default)]
+    pub output: OutputFormat,
+
+    /// Has the planner break the task down into steps executed one by one.
+    #[serde(default)]
+    pub plan: bool,
+
+    /// Task template from `.nexus/prompts/tasks/` for the prompt.
+    #[serde(default)]
+    pub template: Option<String>,
+}
+
+impl BatchTaskOptions {
+    fn to_execute_options(&self) -> ExecuteOptions {
+        ExecuteOptions {
+            dry_run: self.dry_run,
+            max_tokens: self.max_tokens,
+            temperature: self.temperature,
+            preferred_format: self.format.clone(),
+            output: self.output,
+            template: self.template.clone(),
+        }
+    }
+}
+
+impl BatchTask {
+    /// Reads a single task file (one batch entry, without the `tasks` list).
+    pub fn load(path: &Path) -> Result<Self, NexusError> {
+        let content = std::fs::read_to_string(path).map_err(|err| NexusError::IoError {
+            operation: "read task file".to_string(),
+            path: path.to_path_buf(),
+            source: err,
+        })?;
+        let task: Self = serde_yaml::from_str(&content).map_err(|err| NexusError::ConfigParse {
+            path: path.to_path_buf(),
+            message: err.to_string(),
+        })?;
+        if task.task.trim().is_empty() {
+            return Err(NexusError::ValidationError {
+                message: "task description is empty".to_string(),
+                field: Some("task".to_string()),
+            });
+        }
+        Ok(task)
+    }
+}
+
+impl BatchFile {
+    /// Reads and validates a batch file.
+    pub fn load(path: &Path) -> Result<Self, NexusError> {
+        let content = std::fs::read_to_string(path).map_err(|err| NexusError::IoError {
+            operation: "read batch file".to_string(),
+            path: path.to_path_buf(),
+            source: err,
+        })?;
+        let batch: Self =
+            serde_yaml::from_str(&content).map_err(|err| NexusError::ConfigParse {
+                path: path.to_path_buf(),
+                message: err.to_string(),
+            })?;
+        batch.validate()?;
+        Ok(batch)
+    }
+
+    fn validate(&self) -> Result<(), NexusError> {
+        if self.tasks.is_empty() {
+            return Err(NexusError::ValidationError {
+                message: "batch file contains no tasks".to_string(),
+                field: Some("tasks".to_string()),
+            });
+        }
+        if let Some(index) = self
+            .tasks
+            .iter()
+            .position(|entry| entry.task.trim().is_empty())
+        {
+            return Err(NexusError::ValidationError {
+                message: format!("task description of entry {} is empty", index + 1),
+                field: Some("tasks".to_string()),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Result of one batch entry.
+#[derive(Debug, Clone)]
+pub struct BatchOutcome {
+    pub task: String,
+    pub run_id: String,
+    pub log_path: PathBuf,
+    pub status: RunStatus,
+    pub action_count: usize,
+    /// Actions the permission gate allowed; zero when no gate is configured.
+    pub approved_count: usize,
+    pub error: Option<String>,
+}
+
+impl BatchOutcome {
+    /// True if the run failed or was cancelled.
+    pub fn is_failure(&self) -> bool {
+        matches!(self.status, RunStatus::Failed | RunStatus::Cancelled)
+    }
+
+    /// The outcome as JSON, as reported by `--ci` and `--output-format json`.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "run_id": self.run_id,
+            "task": self.task,
+            "status": self.status.as_str(),
+            "exit_code": self.status.exit_code(),
+            "action_count": self.action_count,
+            "approved_count": self.approved_count,
+            "log_path": self.log_path,
+            "error": self.error,
+        })
+    }
+}
+
+/// How a run started from an earlier one is linked to it in `run.started`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FollowUp<'a> {
+    /// Runs the earlier run's task again (`retry_of`).
+    Retry(&'a str),
+    /// Continues the earlier run's conversation (`refine_of`); see
+    /// [`crate::session`].
+    Refine(&'a str),
+}
+
+/// Runs batch entries against one adapter.
+pub struct BatchRunner<'a> {
+    adapter: &'a CodexAdapter,
+    root: PathBuf,
+    jobs: usize,
+    guard: BinaryGuard,
+    run_id_scheme: RunIdScheme,
+    gate: Option<PermissionGate>,
+    sync_interval: Option<u64>,
+    role_executors: Vec<(AgentRole, &'a CodexAdapter)>,
+    review_actions: bool,
+    redactor: Redactor,
+}
+
+impl<'a> BatchRunner<'a> {
+    pub fn new(adapter: &'a CodexAdapter, root: impl Into<PathBuf>) -> Self {
+        Self {
+            adapter,
+            root: root.into(),
+            jobs: 1,
+            guard: BinaryGuard::default(),
+            run_id_scheme: RunIdScheme::default(),
+            gate: None,
+            sync_interval: None,
+            role_executors: Vec::new(),
+            review_actions: false,
+            redactor: Redactor::new(),
+        }
+    }
+
+    /// The project the runs are in.
+    pub(crate) fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Runs up to `jobs` tasks at once; 0 is treated as 1.
+    pub fn with_jobs(mut self, jobs: usize) -> Self {
+        self.jobs = jobs.max(1);
+        self
+    }
+
+    pub fn with_binary_guard(mut self, guard: BinaryGuard) -> Self {
+        self.guard = guard;
+        self
+    }
+
+    pub fn with_run_id_scheme(mut self, scheme: RunIdScheme) -> Self {
+        self.run_id_scheme = scheme;
+        self
+    }
+
+    /// Syncs each run's log to disk every `events` events; see
+    /// [`EventLogWriter::with_sync_interval`].
+    pub fn with_sync_interval(mut self, events: Option<u64>) -> Self {
+        self.sync_interval = events;
+        self
+    }
+
+    /// Masks secrets, e.g. the API key and `redact_patterns`, in the events
+    /// of each run.
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    /// Decides each proposed action with `gate` without prompting.
+    ///
+    /// Allowed actions get `permission.granted` (scope `autopilot`); denied
+    /// ones and those needing a human get `permission.denied`.
+    pub fn with_permission_gate(mut self, gate: PermissionGate) -> Self {
+        self.gate = Some(gate);
+        self
+    }
+
+    /// Hands runs over to `adapter` when an agent proposes a handoff to
+    /// `role`; see [`crate::handoff`]. Without one, handoffs to `role` are
+    /// logged but not accepted. The [`AgentRole::Planner`] adapter also
+    /// plans tasks with `plan: true`.
+    pub fn with_role_executor(mut self, role: AgentRole, adapter: &'a CodexAdapter) -> Self {
+        self.role_executors
+            .retain(|(existing, _)| *existing != role);
+        self.role_executors.push((role, adapter));
+        self
+    }
+
+    /// Has the [`AgentRole::Reviewer`] adapter review each proposed action
+    /// before it is decided; see [`crate::review`].
+    pub fn with_action_review(mut self, enabled: bool) -> Self {
+        self.review_actions = enabled;
+        self
+    }
+
+    /// Runs every task and returns their outcomes in batch-file order.
+    pub async fn run(&self, batch: &BatchFile) -> Result<Vec<BatchOutcome>, NexusError> {
+        let log_paths = EventLogPath::new(&self.root);
+        log_paths.ensure_dir().map_err(|err| NexusError::IoError {
+            operation: "create runs directory".to_string(),
+            path: self.root.join(".nexus").join("runs"),
+            source: err,
+        })?;
+
+        let mut run_ids = HashSet::new();
+        let mut planned = Vec::with_capacity(batch.tasks.len());
+        for entry in &batch.tasks {
+            let run_id = self.allocate_run_id(&log_paths, &mut run_ids)?;
+            let log_path = log_paths.for_run(&run_id)?;
+            planned.push((entry, run_id, log_path));
+        }
+
+        let outcomes = futures::stream::iter(planned)
+            .map(|(entry, run_id, log_path)| self.run_task(entry, run_id, log_path, None))
+            .buffered(self.jobs)
+            .collect()
+            .await;
+        Ok(outcomes)
+    }
+
+    fn allocate_run_id(
+        &self,
+        log_paths: &EventLogPath,
+        taken: &mut HashSet<String>,
+    ) -> Result<String, NexusError> {
+        for _ in 0..MAX_RUN_ID_ATTEMPTS {
+            let run_id = log_paths.allocate_run_id(self.run_id_scheme)?;
+            if taken.insert(run_id.clone()) {
+                return Ok(run_id);
+            }
+        }
+        Err(NexusError::InvalidRunId(format!(
+            "could not allocate a distinct run_id after {MAX_RUN_ID_ATTEMPTS} attempts"
+        )))
+    }
+
+    /// Runs `entry` as a new run linked to `retry_of` through `run.started`.
+    pub async fn run_retry(
+        &self,
+        entry: &BatchTask,
+        retry_of: &str,
+    ) -> Result<BatchOutcome, NexusError> {
+        self.run_follow_up(entry, FollowUp::Retry(retry_of)).await
+    }
+
+    /// Runs `entry` as a new run linked to an earlier one as `follow_up`
+    /// says.
+    pub async fn run_follow_up(
+        &self,
+        entry: &BatchTask,
+        follow_up: FollowUp<'_>,
+    ) -> Result<BatchOutcome, NexusError> {
+        let log_paths = EventLogPath::new(&self.root);
+        let run_id = log_paths.allocate_run_id(self.run_id_scheme)?;
+        let log_path = log_paths.for_run(&run_id)?;
+        Ok(self
+            .run_task(entry, run_id, log_path, Some(follow_up))
+            .await)
+    }
+
+    async fn run_task(
+        &self,
+        entry: &BatchTask,
+        run_id: String,
+        log_path: PathBuf,
+        follow_up: Option<FollowUp<'_>>,
+    ) -> BatchOutcome {
+        let mut outcome = BatchOutcome {
+            task: entry.task.clone(),
+            run_id,
+            log_path,
+            status: RunStatus::Failed,
+            action_count: 0,
+            approved_count: 0,
+            error: None,
+        };
+        if let Err(err) = self.execute_task(entry, &mut outcome, follow_up).await {
+            outcome.status = match err {
+                NexusError::Cancelled => RunStatus::Cancelled,
+                _ => RunStatus::Failed,
+            };
+            outcome.error = Some(err.to_string());
+        }
+        // The run's writer is closed by now, so its log can be read back.
+        if let Err(err) = write_summary(&outcome.log_path) {
+            log::warn!("no summary for {}: {err}", outcome.run_id);
+        }
+        outcome
+    }
+
+    async fn execute_task(
+        &self,
+        entry: &BatchTask,
+        outcome: &mut BatchOutcome,
+        follow_up: Option<FollowUp<'_>>,
+    ) -> Result<(), NexusError> {
+        let run_id = outcome.run_id.clone();
+        let mut writer = EventLogWriter::open(&outcome.log_path)?
+            .with_redactor(self.redactor.clone())
+            .with_sync_interval(self.sync_interval)
+            .with_trace(RunTrace::from_env().span());
+        let mut started = match follow_up {
+            Some(FollowUp::Retry(original)) => {
+                helpers::run_started_retry(&run_id, &entry.task, original)
+            }
+            Some(FollowUp::Refine(original)) => {
+                helpers::run_started_refine(&run_id, &entry.task, original)
+            }
+            None => helpers::run_started(&run_id, &entry.task),
+        };
+        if let Some(parent) = &entry.parent_run_id {
+            started = started.with_parent_run(parent.as_str());
+        }
+        writer.append(&started)?;
+
+        let files = match self.collect_files(entry) {
+            Ok(files) => files,
+            Err(err) => {
+                writer.append(&helpers::executor_failed(&run_id, &err.to_string(), None))?;
+                writer.append(&helpers::run_completed(&run_id, RunStatus::Failed, 0))?;
+                writer.sync()?;
+                return Err(err);
+            }
+        };
+
+        let options = entry.options.to_execute_options();
+        let log = AsyncEventLogWriter::spawn(writer);
+        let mut result = match self.role_executor(&AgentRole::Planner) {
+            Some(planner) if entry.options.plan => {
+                self.execute_plan(planner, entry, &run_id, &files, &options, &log)
+                    .await
+            }
+            _ => {
+                self.adapter
+                    .execute_run_with_logging(&run_id, &entry.task, &files, options.clone(), &log)
+                    .await
+            }
+        };
+        let mut writer = log.into_inner().await?;
+        let mut accepted = 0;
+        loop {
+            let actions = match result {
+                Ok(actions) => actions,
+                // The adapter already wrote the terminal run.cancelled event.
+                Err(NexusError::Cancelled) => return Err(NexusError::Cancelled),
+                Err(err) => {
+                    writer.append(&helpers::run_completed(&run_id, RunStatus::Failed, 0))?;
+                    writer.sync()?;
+                    return Err(err);
+                }
+            };
+            outcome.action_count += actions.len();
+            self.record_reviews(entry, &run_id, &actions, &mut writer)
+                .await?;
+            let allowed = self.settle_actions(&run_id, &actions, &mut writer)?;
+            outcome.approved_count += allowed.len();
+
+            let Some((phase, adapter, task)) =
+                self.hand_off(entry, &run_id, &actions, &allowed, accepted, &mut writer)?
+            else {
+                break;
+            };
+            // The handoff itself is done once accepted.
+            outcome.action_count -= 1;
+            accepted += 1;
+            let log = AsyncEventLogWriter::spawn(writer);
+            result = adapter
+                .execute_phase_with_logging(&run_id, &phase, &task, &files, options.clone(), &log)
+                .await;
+            writer = log.into_inner().await?;
+        }
+
+        outcome.status = if outcome.action_count == 0 {
+            RunStatus::CompletedNoChanges
+        } else {
+            RunStatus::ProposedPendingApply
+        };
+        writer.append(&helpers::run_completed(&run_id, outcome.status, 0))?;
+        writer.sync()?;
+        Ok(())
+    }
+
+    /// Has `planner` break `entry` down into a plan, stores and logs it, and
+    /// executes its steps in order; returns the actions of all steps.
+    async fn execute_plan(
+        &self,
+        planner: &CodexAdapter,
+        entry: &BatchTask,
+        run_id: &str,
+        files: &[FileContext],
+        options: &ExecuteOptions,
+        log: &AsyncEventLogWriter,
+    ) -> Result<Vec<ProposedAction>, NexusError> {
+        let plan = planner
+            .plan_with_logging(run_id, &entry.task, files, options.clone(), log)
+            .await?;
+        let plan_ref = PlanStore::for_log(log.path()).save(run_id, &plan)?;
+        log.append(&helpers::plan_created(run_id, plan.steps.len()).with_payload_ref(plan_ref))
+            .await?;
+
+        let mut actions: Vec<ProposedAction> = Vec::new();
+        for (index, step) in plan.steps.iter().enumerate() {
+            let step_number = index + 1;
+            let task = plan.step_task(step_number, &render_dry_run(&actions, &self.root, false));
+            let proposed = self
+                .adapter
+                .execute_phase_with_logging(
+                    run_id,
+                    &RunPhase::plan_step(step_number),
+                    &task,
+                    files,
+                    options.clone(),
+                    log,
+                )
+                .await?;
+            log.append(&helpers::plan_step_completed(
+                run_id,
+                step_number,
+                &step.title,
+                proposed.len(),
+            ))
+            .await?;
+            actions.extend(proposed);
+        }
+        Ok(actions)
+    }
+
+    /// Logs the reviewer's review of each of `actions` if reviews are on. A
+    /// review that fails is logged as a warning and ends the pass; reviews
+    /// never fail the run.
+    async fn record_reviews(
+        &self,
+        entry: &BatchTask,
+        run_id: &str,
+        actions: &[ProposedAction],
+        writer: &mut EventLogWriter,
+    ) -> Result<(), NexusError> {
+        let Some(reviewer) = self
+            .role_executor(&AgentRole::Reviewer)
+            .filter(|_| self.review_actions)
+        else {
+            return Ok(());
+        };
+        for action in actions
+            .iter()
+            .filter(|action| !matches!(action.details, ActionDetails::Handoff(_)))
+        {
+            let changes = render_dry_run(std::slice::from_ref(action), &self.root, false);
+            let body = review_request_body(&entry.task, action, &changes);
+            match reviewer.review_action(&body).await {
+                Ok(review) => {
+                    writer.append(&helpers::action_reviewed(run_id, &action.id, &review))?
+                }
+                Err(err) => {
+                    log::warn!("review of {} failed: {err}", action.id);
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn role_executor(&self, role: &AgentRole) -> Option<&'a CodexAdapter> {
+        self.role_executors
+            .iter()
+            .find(|(existing, _)| existing == role)
+            .map(|(_, adapter)| *adapter)
+    }
+
+    /// Validates `actions` and, with a gate, decides them; returns the IDs
+    /// the gate allowed.
+    fn settle_actions(
+        &self,
+        run_id: &str,
+        actions: &[ProposedAction],
+        writer: &mut EventLogWriter,
+    ) -> Result<HashSet<String>, NexusError> {
+        let checks = Applier::new(&self.root).check(actions);
+        for (action, check) in actions.iter().zip(checks) {
+            if let Some(check) = check {
+                writer.append(&helpers::action_validated(
+                    run_id,
+                    &action.id,
+                    check.applies_cleanly,
+                    check.failing_hunk.as_deref(),
+                ))?;
+            }
+        }
+        match &self.gate {
+            Some(gate) => record_decisions(gate, &self.root, run_id, actions, writer),
+            None => Ok(HashSet::new()),
+        }
+    }
+
+    /// Logs the handoffs among `actions` and accepts the first one an
+    /// executor is registered for and the gate (if any) allowed, unless the
+    /// run already took [`MAX_HANDOFFS`]. Returns the accepted phase, its
+    /// executor, and the task to give it.
+    fn hand_off(
+        &self,
+        entry: &BatchTask,
+        run_id: &str,
+        actions: &[ProposedAction],
+        allowed: &HashSet<String>,
+        accepted: usize,
+        writer: &mut EventLogWriter,
+    ) -> Result<Option<(RunPhase, &'a CodexAdapter, String)>, NexusError> {
+        let mut next = None;
+        for (action, details) in handoffs(actions) {
+            writer.append(&helpers::handoff_requested(
+                run_id,
+                &action.id,
+                &details.from,
+                &details.to,
+                &details.reason,
+            ))?;
+            if next.is_some() || accepted >= MAX_HANDOFFS {
+                continue;
+            }
+            if self.gate.is_some() && !allowed.contains(&action.id) {
+                continue;
+            }
+            let Some(adapter) = self.role_executor(&details.to) else {
+                continue;
+            };
+            writer.append(&helpers::handoff_accepted(run_id, &action.id, &details.to))?;
+            let proposed: Vec<ProposedAction> = actions
+                .iter()
+                .filter(|action| !matches!(action.details, ActionDetails::Handoff(_)))
+                .cloned()
+                .collect();
+            let phase = RunPhase::handoff(accepted + 1, details.to.clone());
+            let task = handoff_task(
+                &entry.task,
+                details,
+                &render_dry_run(&proposed, &self.root, false),
+            );
+            next = Some((phase, adapter, task));
+        }
+        Ok(next)
+    }
+
+    fn collect_files(&self, entry: &BatchTask) -> Result<Vec<FileContext>, NexusError> {
+        collect_files(&self.root, &entry.files, &self.guard)
+    }
+}
+
+/// Logs the gate's decision for each action; returns the IDs it allowed.
+///
+/// Runs the deps audit at most once per run, when a decision waits on it.
+fn record_decisions(
+    gate: &PermissionGate,
+    root: &Path,
+    run_id: &str,
+    actions: &[ProposedAction],
+    writer: &mut EventLogWriter,
+) -> Result<HashSet<String>, NexusError> {
+    let mut approved = HashSet::new();
+    let mut audit: Option<AuditOutcome> = None;
+    for action in actions {
+        let decision = audited_decision(gate, root, run_id, action, &mut audit, writer)?;
+        let event = match decision.decision {
+            Decision::Allow => {
+                approved.insert(action.id.clone());
+                allowed_event(run_id, action, &decision)
+            }
+            Decision::Deny => helpers::permission_denied(run_id, &action.id, &decision.reason),
+            Decision::Ask => helpers::permission_denied(
+                run_id,
+                &action.id,
+                &format!("{}; unattended runs cannot ask", decision.reason),
+            ),
+        };
+        writer.append(&event)?;
+    }
+    Ok(approved)
+}
+
+/// `permission.granted` (scope `autopilot`) for an action the gate
+/// allowed. If a `notify` tag rule asked for it, the event is marked and
+/// the user warned.
+pub(crate) fn allowed_event(
+    run_id: &str,
+    action: &ProposedAction,
+    decision: &PolicyDecision,
+) -> RunEvent {
+    let event = helpers::permission_granted(run_id, &action.id, AUTOPILOT_SCOPE);
+    if !decision.notify {
+        return event;
+    }
+    log::warn!(
+        "{} was allowed without asking ({}): {}",
+        action.id,
+        decision.reason,
+        action.summary
+    );
+    let mut payload = event.payload.clone().unwrap_or_default();
+    payload["notify"] = true.into();
+    event.with_payload(payload)
+}
+
+/// The gate's decision for `action`, settling a pending deps audit.
+///
+/// The audit runs on first need and its outcome is cached in `audit`, so
+/// callers deciding several actions run it at most once.
+pub(crate) fn audited_decision(
+    gate: &PermissionGate,
+    root: &Path,
+    run_id: &str,
+    action: &ProposedAction,
+    audit: &mut Option<AuditOutcome>,
+    writer: &mut EventLogWriter,
+) -> Result<PolicyDecision, NexusError> {
+    let decision = gate.evaluate(action);
+    if !decision.requires_audit {
+        return Ok(decision);
+    }
+    let outcome = match audit {
+        Some(outcome) => outcome.clone(),
+        None => {
+            let command = gate.deps_audit_command();
+            let outcome = run_audit(command, root)?;
+            writer.append(&helpers::verification_completed(
+                run_id,
+                DEPS_AUDIT_NAME,
+                command,
+                outcome.success,
+                outcome.exit_code,
+            ))?;
+            audit.insert(outcome).clone()
+        }
+    };
+    Ok(if outcome.success {
+        PolicyDecision {
+            notify: decision.notify,
+            ..PolicyDecision::new(Decision::Allow, format!("{DEPS_AUDIT_NAME} passed"))
+        }
+    } else {
+        PolicyDecision::new(Decision::Ask, format!("{DEPS_AUDIT_NAME} failed"))
+    })
+}
+
+/// Renders a markdown table of batch outcomes.
+pub fn render_batch_summary(outcomes: &[BatchOutcome]) -> String {
+    let failed = outcomes
+        .iter()
+        .filter(|outcome| outcome.is_failure())
+        .count();
+    let mut out = String::from("# Batch summary\n\n");
+    let _ = writeln!(
+        out,
+        "{} task(s), {} succeeded, {} failed.\n",
+        outcomes.len(),
+        outcomes.len() - failed,
+        failed
+    );
+    out.push_str("| # | Task | Run | Status | Actions | Error |\n");
+    out.push_str("|---|------|-----|--------|---------|-------|\n");
+    for (index, outcome) in outcomes.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "| {} | {} | `{}` | {} | {} | {} |",
+            index + 1,
+            table_cell(&outcome.task),
+            outcome.run_id,
+            outcome.status.as_str(),
+            outcome.action_count,
+            table_cell(outcome.error.as_deref().unwrap_or(""))
+        );
+    }
+    out
+}
+
+/// Writes the batch summary to `.nexus/runs/batch_<timestamp>.summary.md`.
+pub fn write_batch_summary(root: &Path, outcomes: &[BatchOutcome]) -> Result<PathBuf, NexusError> {
+    let name = format!(
+        "batch_{}.summary.md",
+        chrono::Utc::now().format("%Y%m%d_%H%M%S_%3f")
+    );
+    let path = root.join(".nexus").join("runs").join(name);
+    std::fs::write(&path, render_batch_summary(outcomes)).map_err(|err| NexusError::IoError {
+        operation: "write batch summary".to_string(),
+        path: path.clone(),
+        source: err,
+    })?;
+    Ok(path)
+}
+
+fn table_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_batch_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("tasks.yaml");
+        std::fs::write(
+            &path,
+            "tasks:\n  - task: rename foo\n    files: [\"src/*.rs\"]\n    parent_run_id: run_0\n  - task: extract bar\n    options:\n      format: search_replace\n      dry_run: true\n      output: json_schema\n",
+        )
+        .unwrap();
+
+        let batch = BatchFile::load(&path).unwrap();
+        assert_eq!(batch.tasks.len(), 2);
+        assert_eq!(batch.tasks[0].files, ["src/*.rs"]);
+        assert_eq!(batch.tasks[1].options.format, PatchFormat::SearchReplace);
+        assert!(batch.tasks[1].options.dry_run);
+        assert_eq!(batch.tasks[0].options.output, OutputFormat::Text);
+        assert_eq!(batch.tasks[1].options.output, OutputFormat::JsonSchema);
+        assert_eq!(batch.tasks[0].parent_run_id.as_deref(), Some("run_0"));
+        assert_eq!(batch.tasks[1].parent_run_id, None);
+    }
+
+    #[test]
+    fn test_load_batch_file_rejects_empty_and_unknown() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("tasks.yaml");
+
+        std::fs::write(&path, "tasks: []\n").unwrap();
+        assert!(matches!(
+            BatchFile::load(&path),
+            Err(NexusError::ValidationError { .. })
+        ));
+
+        std::fs::write(&path, "tasks:\n  - task: x\n    globs: []\n").unwrap();
+        assert!(matches!(
+            BatchFile::load(&path),
+            Err(NexusError::ConfigParse { .. })
+        ));
+
+        std::fs::write(&path, "tasks:\n  - task: x\n    parent_run_id: ../run\n").unwrap();
+        assert!(matches!(
+            BatchFile::load(&path),
+            Err(NexusError::ConfigParse { .. })
+        ));
+    }
+
+    #[test]
+    fn test_render_batch_summary() {
+        let outcomes = vec![
+            BatchOutcome {
+                task: "rename | foo".to_string(),
+                run_id: "run_1".to_string(),
+                log_path: PathBuf::from("run_1.jsonl"),
+                status: RunStatus::ProposedPendingApply,
+                action_count: 2,
+                approved_count: 0,
+                error: None,
+            },
+            BatchOutcome {
+                task: "broken".to_string(),
+                run_id: "run_2".to_string(),
+                log_path: PathBuf::from("run_2.jsonl"),
+                status: RunStatus::Failed,
+                action_count: 0,
+                approved_count: 0,
+                error: Some("API error: boom".to_string()),
+            },
+        ];
+
+        let summary = render_batch_summary(&outcomes);
+        assert!(summary.contains("2 task(s), 1 succeeded, 1 failed."));
+        assert!(
+            summary.contains("| 1 | rename \\| foo | `run_1` | proposed_pending_apply | 2 |  |")
+        );
+        assert!(summary.contains("| 2 | broken | `run_2` | failed | 0 | API error: boom |"));
+    }
+}
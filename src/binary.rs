@@ -0,0 +1,206 @@
+//! Binary file detection shared by context loading, parsing, and apply.
+//!
+//! Detection follows git's heuristic: a NUL byte in the first 8000 bytes,
+//! or a high proportion of non-text control bytes, marks content as binary.
+//! Paths matching `binary_allow_paths` in settings bypass the check for
+//! formats that are intentionally edited despite looking binary.
+
+use std::path::Path;
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+
+use crate::error::NexusError;
+use crate::paths::normalize_separators;
+use crate::types::NexusSettings;
+
+const SNIFF_LEN: usize = 8000;
+const MAX_CONTROL_PERCENT: usize = 30;
+const GIT_BINARY_PATCH_MARKER: &str = "GIT binary patch";
+const BINARY_FILES_PREFIX: &str = "Binary files ";
+
+/// Returns true if `bytes` look like binary (non-text) content.
+///
+/// # Examples
+///
+/// ```
+/// use nexus::binary::looks_binary;
+///
+/// assert!(!looks_binary(b"fn main() {}\n"));
+/// assert!(looks_binary(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"));
+/// ```
+pub fn looks_binary(bytes: &[u8]) -> bool {
+    let sample = &bytes[..bytes.len().min(SNIFF_LEN)];
+    if sample.is_empty() {
+        return false;
+    }
+    if sample.contains(&0) {
+        return true;
+    }
+
+    let control = sample
+        .iter()
+        .filter(|byte| {
+            byte.is_ascii_control() && !matches!(byte, b'\t' | b'\n' | b'\r' | 0x0c | 0x1b)
+        })
+        .count();
+    control * 100 / sample.len() > MAX_CONTROL_PERCENT
+}
+
+/// Returns the target path of a diff that carries a binary patch, if any.
+///
+/// Binary diffs (`GIT binary patch` or `Binary files ... differ`) cannot be
+/// applied as text and are rejected at parse time.
+pub fn binary_diff_target(diff: &str) -> Option<String> {
+    let mut current: Option<String> = None;
+    for line in diff.lines() {
+        if let Some(rest) = line.strip_prefix("diff --git ") {
+            current = rest
+                .split_whitespace()
+                .last()
+                .map(|path| normalize_separators(path.trim_start_matches("b/")));
+        } else if let Some(rest) = line.strip_prefix("+++ ") {
+            current = Some(normalize_separators(rest.trim().trim_start_matches("b/")));
+        } else if line.starts_with(GIT_BINARY_PATCH_MARKER) {
+            return Some(current.unwrap_or_default());
+        } else if let Some(rest) = line.strip_prefix(BINARY_FILES_PREFIX) {
+            if rest.ends_with(" differ") {
+                let target = rest
+                    .trim_end_matches(" differ")
+                    .rsplit(" and ")
+                    .next()
+                    .map(|path| normalize_separators(path.trim_start_matches("b/")));
+                return Some(target.or(current).unwrap_or_default());
+            }
+        }
+    }
+    None
+}
+
+/// Rejects binary content unless the path is explicitly allowed.
+#[derive(Debug, Clone)]
+pub struct BinaryGuard {
+    allow: GlobSet,
+}
+
+impl Default for BinaryGuard {
+    fn default() -> Self {
+        Self {
+            allow: GlobSet::empty(),
+        }
+    }
+}
+
+impl BinaryGuard {
+    /// Builds a guard that exempts paths matching any of `allow_patterns`.
+    pub fn new(allow_patterns: &[String]) -> Result<Self, NexusError> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in allow_patterns {
+            let glob = Glob::new(pattern).map_err(|err| NexusError::ConfigError {
+                message: format!("invalid binary_allow_paths pattern '{pattern}': {err}"),
+                path: None,
+                source: None,
+            })?;
+            builder.add(glob);
+        }
+        let allow = builder.build().map_err(|err| NexusError::ConfigError {
+            message: format!("invalid binary_allow_paths: {err}"),
+            path: None,
+            source: None,
+        })?;
+        Ok(Self { allow })
+    }
+
+    /// Builds a guard from the `binary_allow_paths` setting.
+    pub fn from_settings(settings: &NexusSettings) -> Result<Self, NexusError> {
+        Self::new(&settings.binary_allow_paths)
+    }
+
+    /// Returns true if `path` is exempt from binary detection.
+    pub fn is_allowed(&self, path: &str) -> bool {
+        self.allow.is_match(normalize_separators(path))
+    }
+
+    /// Fails with `NexusError::BinaryFile` if `bytes` look binary and `path` is not allowed.
+    pub fn check_bytes(&self, path: &str, bytes: &[u8]) -> Result<(), NexusError> {
+        if looks_binary(bytes) && !self.is_allowed(path) {
+            return Err(NexusError::BinaryFile {
+                path: path.to_string(),
+                reason: "content looks binary".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Apply-time check of an on-disk target. Missing files pass.
+    pub fn check_file(&self, root: &Path, path: &str) -> Result<(), NexusError> {
+        let full_path = root.join(path);
+        match std::fs::read(&full_path) {
+            Ok(bytes) => self.check_bytes(path, &bytes),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(NexusError::IoError {
+                operation: "read file".to_string(),
+                path: full_path,
+                source: err,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_looks_binary() {
+        assert!(!looks_binary(b""));
+        assert!(!looks_binary("héllo wörld\n".as_bytes()));
+        assert!(!looks_binary(b"\x1b[31mred\x1b[0m\n"));
+        assert!(looks_binary(b"abc\0def"));
+        assert!(looks_binary(&[0x01, 0x02, 0x03, 0x04, b'a']));
+    }
+
+    #[test]
+    fn test_binary_diff_target_git_binary_patch() {
+        let diff =
+            "diff --git a/logo.png b/logo.png\nindex 1..2 100644\nGIT binary patch\nliteral 10\n";
+        assert_eq!(binary_diff_target(diff).as_deref(), Some("logo.png"));
+    }
+
+    #[test]
+    fn test_binary_diff_target_binary_files_differ() {
+        let diff = "Binary files a/assets/font.woff and b/assets/font.woff differ\n";
+        assert_eq!(
+            binary_diff_target(diff).as_deref(),
+            Some("assets/font.woff")
+        );
+    }
+
+    #[test]
+    fn test_binary_diff_target_text_diff() {
+        let diff = "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1 +1 @@\n-old\n+new\n";
+        assert!(binary_diff_target(diff).is_none());
+    }
+
+    #[test]
+    fn test_guard_allow_list() {
+        let guard = BinaryGuard::new(&["**/*.pbxproj".to_string()]).unwrap();
+        assert!(guard.check_bytes("app/project.pbxproj", b"\0data").is_ok());
+        assert!(matches!(
+            guard.check_bytes("app/icon.png", b"\0data"),
+            Err(NexusError::BinaryFile { .. })
+        ));
+    }
+
+    #[test]
+    fn test_guard_check_file() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("blob.bin"), [0u8, 1, 2, 3]).unwrap();
+        std::fs::write(dir.path().join("text.rs"), "fn main() {}\n").unwrap();
+        let guard = BinaryGuard::default();
+
+        assert!(guard.check_file(dir.path(), "blob.bin").is_err());
+        assert!(guard.check_file(dir.path(), "text.rs").is_ok());
+        assert!(guard.check_file(dir.path(), "missing.rs").is_ok());
+    }
+}
@@ -0,0 +1,137 @@
+//! Cooperative cancellation for in-flight runs.
+//!
+//! A `CancelToken` is shared between the Ctrl-C handler and the code doing
+//! work. Long-running operations race their futures against
+//! [`CancelToken::cancelled`]; dropping the losing future aborts any
+//! in-flight HTTP request it owned.
+//!
+//! Only the executor's side of a run is cancellable: the handler is
+//! installed while the model is asked for actions, before any file is
+//! written, so there is nothing to roll back. `nexus apply` installs no
+//! handler and is not interrupted part way through an action by it.
+
+use std::future::Future;
+use std::sync::Arc;
+
+use tokio::sync::watch;
+
+use crate::error::{NexusError, exit_codes};
+
+/// Shared flag signalling that the current run should stop.
+#[derive(Debug, Clone)]
+pub struct CancelToken {
+    sender: Arc<watch::Sender<bool>>,
+}
+
+impl Default for CancelToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        let (sender, _) = watch::channel(false);
+        Self {
+            sender: Arc::new(sender),
+        }
+    }
+
+    /// Requests cancellation. Idempotent.
+    pub fn cancel(&self) {
+        self.sender.send_replace(true);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        *self.sender.borrow()
+    }
+
+    /// Resolves once cancellation has been requested.
+    pub async fn cancelled(&self) {
+        let mut receiver = self.sender.subscribe();
+        // The sender lives as long as `self`, so this cannot observe a closed channel.
+        let _ = receiver.wait_for(|cancelled| *cancelled).await;
+    }
+
+    /// Runs `future` to completion unless cancellation is requested first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nexus::cancel::CancelToken;
+    /// use nexus::NexusError;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let token = CancelToken::new();
+    /// token.cancel();
+    /// let result = token.run(async { Ok::<_, NexusError>(1) }).await;
+    /// assert!(matches!(result, Err(NexusError::Cancelled)));
+    /// # });
+    /// ```
+    pub async fn run<T, F>(&self, future: F) -> Result<T, NexusError>
+    where
+        F: Future<Output = Result<T, NexusError>>,
+    {
+        if self.is_cancelled() {
+            return Err(NexusError::Cancelled);
+        }
+        tokio::select! {
+            biased;
+            _ = self.cancelled() => Err(NexusError::Cancelled),
+            result = future => result,
+        }
+    }
+}
+
+/// Cancels `token` when the process receives Ctrl-C (SIGINT).
+///
+/// Must be called from within a Tokio runtime. Listening replaces the
+/// default SIGINT handler for the rest of the process, so a second Ctrl-C
+/// exits at once with [`exit_codes::CANCELLED`] in case shutting down got
+/// stuck.
+pub fn install_ctrl_c_handler(token: CancelToken) {
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_err() {
+            return;
+        }
+        log::warn!("interrupt received, cancelling run");
+        token.cancel();
+        if tokio::signal::ctrl_c().await.is_ok() {
+            log::warn!("second interrupt received, exiting");
+            std::process::exit(i32::from(exit_codes::CANCELLED));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_run_completes_when_not_cancelled() {
+        let token = CancelToken::new();
+        let result = token.run(async { Ok::<_, NexusError>(7) }).await;
+        assert_eq!(result.unwrap(), 7);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_interrupts_pending_future() {
+        let token = CancelToken::new();
+        let canceller = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            canceller.cancel();
+        });
+
+        let result = token
+            .run(async {
+                tokio::time::sleep(Duration::from_secs(30)).await;
+                Ok::<_, NexusError>(())
+            })
+            .await;
+
+        assert!(matches!(result, Err(NexusError::Cancelled)));
+        assert!(token.is_cancelled());
+    }
+}
@@ -0,0 +1,274 @@
+//! CI integrations selected with `--ci`.
+//!
+//! `--ci github` reports runs as GitHub Actions workflow commands
+//! (`::notice`, `::warning`, `::error`) and writes a JSON result to the step
+//! outputs file named by `$GITHUB_OUTPUT`.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde_json::{Value, json};
+
+use crate::batch::BatchOutcome;
+use crate::error::{NexusError, exit_codes};
+use crate::event_log::EventLogReader;
+use crate::policy::touched_paths;
+use crate::preview::load_action_artifact;
+
+/// Proposed actions at or above this risk are annotated as warnings.
+const WARNING_RISK: u8 = 2;
+
+/// Exit code for a set of finished runs under the documented contract.
+///
+/// Any failed run yields its failure code; otherwise the highest status code
+/// wins, so pending approvals outrank "no changes" and plain success.
+pub fn exit_code(outcomes: &[BatchOutcome]) -> u8 {
+    if let Some(failed) = outcomes.iter().find(|outcome| outcome.is_failure()) {
+        return failed.status.exit_code();
+    }
+    outcomes
+        .iter()
+        .map(|outcome| outcome.status.exit_code())
+        .max()
+        .unwrap_or(exit_codes::OK)
+}
+
+/// Reports runs to GitHub Actions.
+pub struct GithubReporter {
+    output_path: Option<PathBuf>,
+}
+
+impl GithubReporter {
+    /// Writes step outputs to `output_path`, if set.
+    pub fn new(output_path: Option<PathBuf>) -> Self {
+        Self { output_path }
+    }
+
+    /// Uses the step outputs file from `$GITHUB_OUTPUT`.
+    pub fn from_env() -> Self {
+        Self::new(std::env::var_os("GITHUB_OUTPUT").map(PathBuf::from))
+    }
+
+    /// Prints annotations for every run to `out` and writes step outputs.
+    ///
+    /// Returns the exit code the step should finish with.
+    pub fn report(
+        &self,
+        outcomes: &[BatchOutcome],
+        out: &mut impl Write,
+    ) -> Result<u8, NexusError> {
+        for outcome in outcomes {
+            for annotation in annotations(outcome)? {
+                writeln!(out, "{annotation}").map_err(|err| NexusError::IoError {
+                    operation: "write annotation".to_string(),
+                    path: PathBuf::from("<stdout>"),
+                    source: err,
+                })?;
+            }
+        }
+        let code = exit_code(outcomes);
+        if let Some(path) = &self.output_path {
+            write_outputs(path, outcomes, code)?;
+        }
+        Ok(code)
+    }
+}
+
+/// Workflow commands for the proposed actions, denials, and failure of a run.
+pub fn annotations(outcome: &BatchOutcome) -> Result<Vec<String>, NexusError> {
+    let events = EventLogReader::open(&outcome.log_path)?.load_all()?;
+    let runs_dir = outcome.log_path.parent().unwrap_or_else(|| Path::new("."));
+
+    let mut annotations = Vec::new();
+    for event in &events {
+        let payload_str = |key: &str| {
+            event
+                .payload
+                .as_ref()
+                .and_then(|payload| payload.get(key))
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string()
+        };
+        match event.event_type.as_str() {
+            "action.proposed" => {
+                let Some(payload_ref) = &event.payload_ref else {
+                    continue;
+                };
+                let action = load_action_artifact(runs_dir, payload_ref)?;
+                let level = if action.risk >= WARNING_RISK {
+                    "warning"
+                } else {
+                    "notice"
+                };
+                let file = touched_paths(&action).into_iter().next();
+                annotations.push(workflow_command(
+                    level,
+                    file.as_deref(),
+                    &format!("Nexus proposed {} (risk {})", action.id, action.risk),
+                    &action.summary,
+                ));
+            }
+            "permission.denied" => annotations.push(workflow_command(
+                "error",
+                None,
+                &format!("Nexus denied {}", payload_str("action_id")),
+                &payload_str("reason"),
+            )),
+            _ => {}
+        }
+    }
+    if let Some(error) = &outcome.error {
+        annotations.push(workflow_command(
+            "error",
+            None,
+            &format!("Nexus run {} failed", outcome.run_id),
+            error,
+        ));
+    }
+    Ok(annotations)
+}
+
+fn workflow_command(level: &str, file: Option<&str>, title: &str, message: &str) -> String {
+    let mut properties = Vec::new();
+    if let Some(file) = file {
+        properties.push(format!("file={}", escape_property(file)));
+    }
+    properties.push(format!("title={}", escape_property(title)));
+    format!(
+        "::{level} {}::{}",
+        properties.join(","),
+        escape_data(message)
+    )
+}
+
+/// Escapes a workflow command message.
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escapes a workflow command property value.
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// Appends `result`, `status`, and `exit_code` to the step outputs file.
+///
+/// `result` is compact JSON, which never spans lines.
+fn write_outputs(path: &Path, outcomes: &[BatchOutcome], code: u8) -> Result<(), NexusError> {
+    let runs: Vec<Value> = outcomes.iter().map(BatchOutcome::to_json).collect();
+    let result = json!({ "exit_code": code, "runs": runs });
+    let status = match outcomes {
+        [single] => single.status.as_str(),
+        _ if code == exit_codes::OK => "success",
+        _ => "mixed",
+    };
+
+    let io_error = |err| NexusError::IoError {
+        operation: "write GitHub step outputs".to_string(),
+        path: path.to_path_buf(),
+        source: err,
+    };
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(io_error)?;
+    write!(
+        file,
+        "result={}\nstatus={status}\nexit_code={code}\n",
+        serde_json::to_string(&result)?
+    )
+    .map_err(io_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_log::{EventLogWriter, PayloadStore, action_proposed, permission_denied};
+    use crate::types::{ProposedAction, RunStatus};
+
+    fn outcome(log_path: PathBuf, status: RunStatus) -> BatchOutcome {
+        BatchOutcome {
+            task: "task".to_string(),
+            run_id: "run_1".to_string(),
+            log_path,
+            status,
+            action_count: 1,
+            approved_count: 0,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_escaping() {
+        assert_eq!(escape_data("50%\nnext"), "50%25%0Anext");
+        assert_eq!(escape_property("a:b,c"), "a%3Ab%2Cc");
+    }
+
+    #[test]
+    fn test_exit_code_contract() {
+        let path = PathBuf::from("run.jsonl");
+        let pending = outcome(path.clone(), RunStatus::ProposedPendingApply);
+        let no_changes = outcome(path.clone(), RunStatus::CompletedNoChanges);
+        let failed = outcome(path, RunStatus::Failed);
+
+        assert_eq!(exit_code(&[]), exit_codes::OK);
+        assert_eq!(
+            exit_code(&[no_changes.clone(), pending.clone()]),
+            exit_codes::PENDING_APPLY
+        );
+        assert_eq!(exit_code(&[pending, failed]), exit_codes::GENERAL_ERROR);
+    }
+
+    #[test]
+    fn test_report_annotates_and_writes_outputs() {
+        let dir = tempfile::tempdir().unwrap();
+        let log_path = dir.path().join("run_1.jsonl");
+        {
+            let mut writer = EventLogWriter::open(&log_path).unwrap();
+            let action = ProposedAction::test_patch("act_1", &["src/lib.rs"])
+                .with_summary("Rename foo")
+                .with_risk(2);
+            let payload_ref = PayloadStore::for_log(&log_path, "run_1")
+                .unwrap()
+                .write(
+                    "action_act_1.json",
+                    &serde_json::to_vec(&action).unwrap(),
+                    "application/json",
+                    "action",
+                )
+                .unwrap();
+            writer
+                .append(
+                    &action_proposed("run_1", "act_1", "patch", "Rename foo", None)
+                        .with_payload_ref(payload_ref),
+                )
+                .unwrap();
+            writer
+                .append(&permission_denied("run_1", "act_1", "policy"))
+                .unwrap();
+        }
+
+        let output_path = dir.path().join("github_output");
+        let mut out = Vec::new();
+        let code = GithubReporter::new(Some(output_path.clone()))
+            .report(
+                &[outcome(log_path, RunStatus::ProposedPendingApply)],
+                &mut out,
+            )
+            .unwrap();
+
+        assert_eq!(code, exit_codes::PENDING_APPLY);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "::warning file=src/lib.rs,title=Nexus proposed act_1 (risk 2)::Rename foo\n\
+             ::error title=Nexus denied act_1::policy\n"
+        );
+        let outputs = std::fs::read_to_string(output_path).unwrap();
+        assert!(outputs.starts_with("result={\"exit_code\":3,\"runs\":[{"));
+        assert!(outputs.contains("\nstatus=proposed_pending_apply\nexit_code=3\n"));
+    }
+}
@@ -1,4 +1,4 @@
-use clap::Parser;
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
 
 /// Validate and return a non-empty task description.
@@ -37,22 +37,31 @@ fn validate_config_path(s: &str) -> Result<PathBuf, String> {
 #[derive(Parser, Debug)]
 #[command(name = "nexus")]
 #[command(version, about)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 #[command(after_help = "Examples:\n  \
         nexus \"rename getUserData to fetchUserProfile\"\n  \
         nexus --dry-run \"extract validation logic\"\n  \
-        nexus -v --config custom.json \"refactor task\"")]
+        nexus --dry-run \"add doc comments\" --files 'src/**/*.rs'\n  \
+        nexus -v --config custom.json \"refactor task\"\n  \
+        nexus --provider ollama --dry-run \"simplify error handling\"\n  \
+        nexus diff run_20260101_120000_000\n  \
+        nexus batch tasks.yaml --jobs 2")]
 pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// The refactoring task to execute.
     ///
     /// Describe the refactoring in natural language. Be specific
     /// about what to rename, move, extract, or restructure.
-    #[arg(value_name = "TASK", value_parser = validate_task)]
-    pub task: String,
+    #[arg(value_name = "TASK", value_parser = validate_task, required = true)]
+    pub task: Option<String>,
 
     /// Path to configuration file.
     #[arg(
         short = 'c',
         long,
+        global = true,
         value_name = "FILE",
         env = "NEXUS_CONFIG",
         default_value = ".nexus/settings.json",
@@ -60,6 +69,18 @@ pub struct Cli {
     )]
     pub config: PathBuf,
 
+    /// Files to send to the model as context.
+    ///
+    /// Glob patterns relative to the working directory, e.g.
+    /// `--files 'src/**/*.rs' Cargo.toml`. Put the task before this flag.
+    #[arg(long, value_name = "GLOB", num_args = 1..)]
+    pub files: Vec<String>,
+
+    /// Prompt template for the task, from `.nexus/prompts/tasks/<NAME>.md`
+    /// (or `.j2`), in place of the default user message.
+    #[arg(long, value_name = "NAME")]
+    pub template: Option<String>,
+
     /// Preview changes without applying them.
     ///
     /// Shows proposed patches and what would change, but doesn't
@@ -67,11 +88,491 @@ pub struct Cli {
     #[arg(long, env = "NEXUS_DRY_RUN")]
     pub dry_run: bool,
 
+    /// Reject unknown keys in the configuration file.
+    ///
+    /// Equivalent to setting `"strict": true` in settings.json.
+    #[arg(long, global = true, env = "NEXUS_STRICT_CONFIG")]
+    pub strict_config: bool,
+
     /// Increase output verbosity.
     ///
     /// Use -v for info, -vv for debug, -vvv for trace.
-    #[arg(short, action = clap::ArgAction::Count)]
+    #[arg(short, global = true, action = clap::ArgAction::Count)]
     pub verbose: u8,
+
+    /// Report results for a CI system.
+    ///
+    /// `github` prints workflow annotations, writes the JSON result to
+    /// `$GITHUB_OUTPUT`, and exits with the run status exit code.
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "PROVIDER",
+        env = "NEXUS_CI"
+    )]
+    pub ci: Option<CiMode>,
+
+    /// Model backend to send tasks to.
+    ///
+    /// One of `openai`, `anthropic`, `ollama`, or `azure`. Overrides
+    /// `provider` in settings; defaults to `openai`.
+    #[arg(long, global = true, value_name = "NAME", env = "NEXUS_PROVIDER")]
+    pub provider: Option<String>,
+
+    /// Model to request, e.g. `gpt-5.2-codex`.
+    ///
+    /// Overrides `model` in settings; `nexus retry` otherwise reuses the
+    /// original run's model.
+    #[arg(long, global = true, value_name = "MODEL", env = "NEXUS_MODEL")]
+    pub model: Option<String>,
+
+    /// How results are printed.
+    ///
+    /// `json` prints each result (proposed actions, apply reports, runs)
+    /// as one line of JSON on stdout, and errors as
+    /// `{"error": {"message", "code"}}` with the exit code they end with.
+    /// Progress messages still go to stderr.
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "FORMAT",
+        env = "NEXUS_OUTPUT_FORMAT",
+        default_value = "text"
+    )]
+    pub output_format: OutputMode,
+}
+
+/// Output formats for `--output-format`.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputMode {
+    /// Text for people to read.
+    #[default]
+    Text,
+    /// Machine-readable JSON for scripts and editors.
+    Json,
+}
+
+/// CI systems supported by `--ci`.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CiMode {
+    /// GitHub Actions.
+    Github,
+}
+
+/// Subcommands operating on existing runs.
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Create `.nexus/settings.json` and `.nexus/runs/` in the current
+    /// directory.
+    Init(InitArgs),
+
+    /// Show proposed, not yet applied actions of a run as one diff.
+    Diff(DiffArgs),
+
+    /// Apply a run's pending actions to the working tree.
+    Apply(ApplyArgs),
+
+    /// Continue an interrupted run: decide and apply the actions it had
+    /// proposed.
+    Resume(ResumeArgs),
+
+    /// Apply a run's actions in bounded batches, approving what the
+    /// `autopilot` settings allow without asking.
+    Autopilot(AutopilotArgs),
+
+    /// Export proposed actions of a run for other tools.
+    Export(ExportArgs),
+
+    /// Write a report for a run from its event log.
+    Summary(SummaryArgs),
+
+    /// Run every task listed in a YAML file, each as its own run.
+    Batch(BatchArgs),
+
+    /// Process task files dropped into a queue directory, one at a time.
+    Daemon(DaemonArgs),
+
+    /// Re-run a previous run's task with modified parameters.
+    Retry(RetryArgs),
+
+    /// Ask for revised proposals, continuing a run's conversation with the
+    /// model.
+    Refine(RefineArgs),
+
+    /// Restore the files a run's applied actions changed.
+    Undo(UndoArgs),
+
+    /// Copy files back from the backups a run took before changing them.
+    Restore(RestoreArgs),
+
+    /// Inspect and compare existing runs.
+    Runs(RunsArgs),
+
+    /// Work with raw run event logs.
+    #[command(alias = "logs")]
+    Log(LogArgs),
+
+    /// Manage the settings file.
+    Config(ConfigArgs),
+}
+
+/// Arguments for `nexus config`.
+#[derive(Args, Debug)]
+pub struct ConfigArgs {
+    #[command(subcommand)]
+    pub command: ConfigCommand,
+}
+
+/// Subcommands of `nexus config`.
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Print the effective settings and the layers each value came from:
+    /// default, user (the global file), project (--config), env
+    /// (`NEXUS_*` variables), or cli.
+    Show {
+        /// Print JSON instead of one line per key.
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Upgrade the settings file (--config) to the current schema version
+    /// and write it back.
+    Migrate {
+        /// Print what would change without writing the file.
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+/// Arguments for `nexus log`.
+#[derive(Args, Debug)]
+pub struct LogArgs {
+    #[command(subcommand)]
+    pub command: LogCommand,
+}
+
+/// Subcommands of `nexus log`.
+#[derive(Subcommand, Debug)]
+pub enum LogCommand {
+    /// Export run event logs, optionally anonymized for bug reports, as
+    /// JSONL or as tables for audit queries.
+    Export {
+        /// Run whose log to export.
+        #[arg(value_name = "RUN_ID", required_unless_present = "all")]
+        run_id: Option<String>,
+
+        /// Export every recorded run.
+        #[arg(long, conflicts_with = "run_id")]
+        all: bool,
+
+        /// Output format.
+        #[arg(long, value_enum, default_value = "jsonl")]
+        format: LogFormat,
+
+        /// Replace paths, payload text, and actor/model details per the
+        /// `anonymize` settings.
+        #[arg(long)]
+        anonymize: bool,
+
+        /// Write to FILE instead of stdout. Required for `sqlite` (the
+        /// database) and `csv` (the directory the tables are written to).
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+
+    /// Check that a run's event log has not been edited since it was
+    /// written, using its hash chain.
+    Verify {
+        /// Run whose log to verify.
+        #[arg(value_name = "RUN_ID")]
+        run_id: String,
+    },
+
+    /// Print the last events of a run, and with --follow, new ones as the
+    /// run appends them.
+    Tail {
+        /// Run whose log to print.
+        #[arg(value_name = "RUN_ID")]
+        run_id: String,
+
+        /// Keep printing events until the run ends.
+        #[arg(short, long)]
+        follow: bool,
+
+        /// Number of past events to print first.
+        #[arg(short = 'n', long, value_name = "N", default_value_t = 10)]
+        lines: usize,
+    },
+}
+
+/// Formats supported by `nexus log export`.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogFormat {
+    /// The event log as is, one JSON event per line.
+    Jsonl,
+    /// `events`, `actions`, and `usage` tables in a SQLite database.
+    Sqlite,
+    /// `events.csv`, `actions.csv`, and `usage.csv` in a directory.
+    Csv,
+}
+
+/// Arguments for `nexus runs`.
+#[derive(Args, Debug)]
+pub struct RunsArgs {
+    #[command(subcommand)]
+    pub command: RunsCommand,
+}
+
+/// Subcommands of `nexus runs`.
+#[derive(Subcommand, Debug)]
+pub enum RunsCommand {
+    /// List recorded runs, oldest first, from the run index.
+    List {
+        /// Rebuild the index from the logs before listing.
+        #[arg(long)]
+        rebuild: bool,
+
+        /// Print the runs as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Compare the files, proposed changes, and usage of two runs.
+    Diff {
+        /// First run to compare.
+        #[arg(value_name = "RUN_A")]
+        run_a: String,
+
+        /// Second run to compare.
+        #[arg(value_name = "RUN_B")]
+        run_b: String,
+    },
+
+    /// Replay a run's event log as a timeline, followed by the tree of runs
+    /// linked to it through `parent_run_id`.
+    Show {
+        /// Run to show.
+        #[arg(value_name = "RUN_ID")]
+        run_id: String,
+
+        /// Print the timeline as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Sum a run's token usage and estimated cost.
+    Cost {
+        /// Run to price.
+        #[arg(value_name = "RUN_ID")]
+        run_id: String,
+
+        /// Print the breakdown as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// Arguments for `nexus retry`.
+#[derive(Args, Debug)]
+pub struct RetryArgs {
+    /// Run whose task to run again.
+    #[arg(value_name = "RUN_ID")]
+    pub run_id: String,
+
+    /// Sampling temperature for the new run.
+    #[arg(long)]
+    pub temperature: Option<f32>,
+
+    /// Extra instruction appended to the original task.
+    #[arg(long, value_name = "TEXT")]
+    pub instruction: Option<String>,
+}
+
+/// Arguments for `nexus refine`.
+#[derive(Args, Debug)]
+pub struct RefineArgs {
+    /// Run whose proposals to revise.
+    #[arg(value_name = "RUN_ID")]
+    pub run_id: String,
+
+    /// What to change, e.g. "also update the tests".
+    #[arg(value_name = "INSTRUCTION")]
+    pub instruction: String,
+}
+
+/// Arguments for `nexus undo`.
+#[derive(Args, Debug)]
+pub struct UndoArgs {
+    /// Run whose applied actions to revert.
+    #[arg(value_name = "RUN_ID")]
+    pub run_id: String,
+
+    /// Restore files even if they changed since the run applied them.
+    #[arg(long)]
+    pub force: bool,
+}
+
+/// Arguments for `nexus restore`.
+#[derive(Args, Debug)]
+pub struct RestoreArgs {
+    /// Run whose backups to restore.
+    #[arg(value_name = "RUN_ID")]
+    pub run_id: String,
+
+    /// File to restore, relative to the project root; every backed-up
+    /// file if omitted.
+    #[arg(value_name = "PATH")]
+    pub path: Option<String>,
+}
+
+/// Arguments for `nexus daemon`.
+#[derive(Args, Debug)]
+pub struct DaemonArgs {
+    /// Directory watched for `*.yaml` task files.
+    #[arg(long, value_name = "DIR", default_value = ".nexus/queue")]
+    pub queue: PathBuf,
+
+    /// Seconds to wait between scans of an empty queue.
+    #[arg(long, value_name = "SECS", default_value_t = 5)]
+    pub poll_interval: u64,
+
+    /// Process the tasks currently queued, then exit. Stops at the first
+    /// task that fails with an error.
+    #[arg(long)]
+    pub once: bool,
+}
+
+/// Arguments for `nexus batch`.
+#[derive(Args, Debug)]
+pub struct BatchArgs {
+    /// YAML file listing tasks with their file globs and options.
+    #[arg(value_name = "FILE")]
+    pub file: PathBuf,
+
+    /// Number of tasks to run at once.
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u16).range(1..))]
+    pub jobs: u16,
+}
+
+/// Report formats supported by `nexus summary`.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// `<run_id>.summary.md`.
+    #[default]
+    Markdown,
+    /// Standalone `<run_id>.report.html` with diffs, timeline, and usage charts.
+    Html,
+}
+
+/// Arguments for `nexus summary`.
+#[derive(Args, Debug)]
+pub struct SummaryArgs {
+    /// Run to summarize.
+    #[arg(value_name = "RUN_ID")]
+    pub run_id: String,
+
+    /// Report format to write.
+    #[arg(long, value_enum, default_value = "markdown")]
+    pub report: ReportFormat,
+}
+
+/// Arguments for `nexus diff`.
+#[derive(Args, Debug)]
+pub struct DiffArgs {
+    /// Run whose pending actions to show.
+    #[arg(value_name = "RUN_ID")]
+    pub run_id: String,
+
+    /// Disable colored output (also honored via `NO_COLOR`).
+    #[arg(long)]
+    pub no_color: bool,
+
+    /// Apply the actions to copies of the affected files in this directory
+    /// instead of printing the diff. The working tree is left untouched.
+    #[arg(long, value_name = "DIR")]
+    pub stage_dir: Option<PathBuf>,
+}
+
+/// Arguments for `nexus apply`.
+#[derive(Args, Debug)]
+pub struct ApplyArgs {
+    /// Run whose pending actions to apply.
+    #[arg(value_name = "RUN_ID")]
+    pub run_id: String,
+
+    /// Fail on conflicting patches instead of prompting for a resolution.
+    /// Implied when stdin is not a terminal.
+    #[arg(long)]
+    pub no_interactive: bool,
+}
+
+/// Arguments for `nexus init`.
+#[derive(Args, Debug)]
+pub struct InitArgs {
+    /// Overwrite an existing settings file with the defaults.
+    #[arg(long)]
+    pub force: bool,
+
+    /// Add `.nexus/runs/` to `.gitignore`.
+    #[arg(long)]
+    pub gitignore: bool,
+}
+
+/// Arguments for `nexus resume`.
+#[derive(Args, Debug)]
+pub struct ResumeArgs {
+    /// Run to resume.
+    #[arg(value_name = "RUN_ID")]
+    pub run_id: String,
+
+    /// Leave actions that need approval pending and fail on conflicting
+    /// patches instead of prompting. Implied when stdin is not a terminal.
+    #[arg(long)]
+    pub no_interactive: bool,
+
+    /// Review the actions that need approval full screen: the actions on
+    /// the left, the selected one's diff on the right. Approve (`a`),
+    /// reject (`r`), or skip (`s`) each, then press Enter to apply.
+    #[arg(long, conflicts_with = "no_interactive")]
+    pub tui: bool,
+}
+
+/// Arguments for `nexus autopilot`.
+#[derive(Args, Debug)]
+pub struct AutopilotArgs {
+    /// Run whose pending actions to apply.
+    #[arg(value_name = "RUN_ID")]
+    pub run_id: String,
+
+    /// Apply only the first batch instead of asking before each next one.
+    /// Implied when stdin is not a terminal.
+    #[arg(long)]
+    pub no_interactive: bool,
+}
+
+/// Output formats supported by `nexus export`.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// SARIF 2.1.0, for code scanning UIs.
+    Sarif,
+}
+
+/// Arguments for `nexus export`.
+#[derive(Args, Debug)]
+pub struct ExportArgs {
+    /// Run whose proposed actions to export.
+    #[arg(value_name = "RUN_ID")]
+    pub run_id: String,
+
+    /// Output format.
+    #[arg(long, value_enum, default_value = "sarif")]
+    pub format: ExportFormat,
+
+    /// Write to FILE instead of stdout.
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
 }
 
 impl Cli {
@@ -83,10 +584,18 @@ impl Cli {
     /// use nexus::Cli;
     ///
     /// let cli = Cli {
-    ///     task: "rename foo to bar".into(),
+    ///     command: None,
+    ///     task: Some("rename foo to bar".into()),
     ///     config: std::path::PathBuf::from(".nexus/settings.json"),
+    ///     files: Vec::new(),
+    ///     template: None,
     ///     dry_run: false,
+    ///     strict_config: false,
     ///     verbose: 2,
+    ///     ci: None,
+    ///     provider: None,
+    ///     model: None,
+    ///     output_format: nexus::cli::OutputMode::Text,
     /// };
     /// assert_eq!(cli.log_level(), "debug");
     /// ```
@@ -98,6 +607,11 @@ impl Cli {
             _ => "trace",
         }
     }
+
+    /// Whether results are printed as JSON (`--output-format json`).
+    pub fn json_output(&self) -> bool {
+        self.output_format == OutputMode::Json
+    }
 }
 
 #[cfg(test)]
@@ -144,10 +658,48 @@ mod tests {
     #[test]
     fn test_basic_parse() {
         let cli = with_clean_env(|| Cli::parse_from(["nexus", "rename foo to bar"]));
-        assert_eq!(cli.task, "rename foo to bar");
+        assert_eq!(cli.task.as_deref(), Some("rename foo to bar"));
+        assert!(cli.command.is_none());
         assert!(!cli.dry_run);
         assert_eq!(cli.verbose, 0);
         assert_eq!(cli.config, PathBuf::from(".nexus/settings.json"));
+        assert!(cli.files.is_empty());
+    }
+
+    #[test]
+    fn test_files_take_several_globs() {
+        let cli = with_clean_env(|| {
+            Cli::parse_from([
+                "nexus",
+                "document the parser",
+                "--files",
+                "src/**/*.rs",
+                "Cargo.toml",
+            ])
+        });
+        assert_eq!(cli.task.as_deref(), Some("document the parser"));
+        assert_eq!(cli.files, ["src/**/*.rs", "Cargo.toml"]);
+    }
+
+    #[test]
+    fn test_model_flag_is_global() {
+        let cli = with_clean_env(|| Cli::parse_from(["nexus", "--model", "gpt-x", "rename a"]));
+        assert_eq!(cli.model.as_deref(), Some("gpt-x"));
+
+        let cli = with_clean_env(|| {
+            Cli::parse_from(["nexus", "batch", "tasks.yaml", "--model", "gpt-y"])
+        });
+        assert_eq!(cli.model.as_deref(), Some("gpt-y"));
+    }
+
+    #[test]
+    fn test_provider_flag_is_global() {
+        let cli =
+            with_clean_env(|| Cli::parse_from(["nexus", "retry", "run_1", "--provider", "ollama"]));
+        assert_eq!(cli.provider.as_deref(), Some("ollama"));
+
+        let cli = with_clean_env(|| Cli::parse_from(["nexus", "rename a"]));
+        assert_eq!(cli.provider, None);
     }
 
     #[test]
@@ -156,6 +708,7 @@ mod tests {
             Cli::parse_from([
                 "nexus",
                 "--dry-run",
+                "--strict-config",
                 "-vvv",
                 "--config",
                 "custom.json",
@@ -163,6 +716,7 @@ mod tests {
             ])
         });
         assert!(cli.dry_run);
+        assert!(cli.strict_config);
         assert_eq!(cli.verbose, 3);
         assert_eq!(cli.config, PathBuf::from("custom.json"));
     }
@@ -170,10 +724,18 @@ mod tests {
     #[test]
     fn test_log_level() {
         let cli = Cli {
-            task: "task".to_string(),
+            command: None,
+            task: Some("task".to_string()),
             config: PathBuf::from(".nexus/settings.json"),
+            files: Vec::new(),
+            template: None,
             dry_run: false,
+            strict_config: false,
             verbose: 0,
+            ci: None,
+            provider: None,
+            model: None,
+            output_format: OutputMode::Text,
         };
         assert_eq!(cli.log_level(), "warn");
 
@@ -186,4 +748,374 @@ mod tests {
         let cli = Cli { verbose: 3, ..cli };
         assert_eq!(cli.log_level(), "trace");
     }
+
+    #[test]
+    fn test_diff_subcommand() {
+        let cli =
+            with_clean_env(|| Cli::parse_from(["nexus", "diff", "run_1", "--no-color", "-v"]));
+        assert!(cli.task.is_none());
+        assert_eq!(cli.verbose, 1);
+        match cli.command {
+            Some(Command::Diff(args)) => {
+                assert_eq!(args.run_id, "run_1");
+                assert!(args.no_color);
+                assert!(args.stage_dir.is_none());
+            }
+            other => panic!("expected diff subcommand, got {other:?}"),
+        }
+
+        let cli = with_clean_env(|| {
+            Cli::parse_from(["nexus", "diff", "run_1", "--stage-dir", "/tmp/stage"])
+        });
+        match cli.command {
+            Some(Command::Diff(args)) => {
+                assert_eq!(args.stage_dir, Some(PathBuf::from("/tmp/stage")));
+            }
+            other => panic!("expected diff subcommand, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_apply_subcommand() {
+        let cli =
+            with_clean_env(|| Cli::parse_from(["nexus", "apply", "run_1", "--no-interactive"]));
+        match cli.command {
+            Some(Command::Apply(args)) => {
+                assert_eq!(args.run_id, "run_1");
+                assert!(args.no_interactive);
+            }
+            other => panic!("expected apply subcommand, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_init_subcommand() {
+        let cli = with_clean_env(|| Cli::parse_from(["nexus", "init", "--gitignore"]));
+        match cli.command {
+            Some(Command::Init(args)) => {
+                assert!(args.gitignore);
+                assert!(!args.force);
+            }
+            other => panic!("expected init subcommand, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_resume_subcommand() {
+        let cli = with_clean_env(|| Cli::parse_from(["nexus", "resume", "run_1"]));
+        match cli.command {
+            Some(Command::Resume(args)) => {
+                assert_eq!(args.run_id, "run_1");
+                assert!(!args.no_interactive);
+                assert!(!args.tui);
+            }
+            other => panic!("expected resume subcommand, got {other:?}"),
+        }
+
+        let cli = with_clean_env(|| Cli::parse_from(["nexus", "resume", "run_1", "--tui"]));
+        assert!(matches!(cli.command, Some(Command::Resume(args)) if args.tui));
+        let conflicting = with_clean_env(|| {
+            Cli::try_parse_from(["nexus", "resume", "run_1", "--tui", "--no-interactive"])
+        });
+        assert!(conflicting.is_err());
+    }
+
+    #[test]
+    fn test_task_required_without_subcommand() {
+        let result = with_clean_env(|| Cli::try_parse_from(["nexus"]));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_export_subcommand() {
+        let cli = with_clean_env(|| {
+            Cli::parse_from([
+                "nexus",
+                "export",
+                "run_1",
+                "--format",
+                "sarif",
+                "-o",
+                "out.sarif",
+            ])
+        });
+        match cli.command {
+            Some(Command::Export(args)) => {
+                assert_eq!(args.run_id, "run_1");
+                assert_eq!(args.format, ExportFormat::Sarif);
+                assert_eq!(args.output, Some(PathBuf::from("out.sarif")));
+            }
+            other => panic!("expected export subcommand, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_summary_report_format() {
+        let cli =
+            with_clean_env(|| Cli::parse_from(["nexus", "summary", "run_1", "--report", "html"]));
+        match cli.command {
+            Some(Command::Summary(args)) => assert_eq!(args.report, ReportFormat::Html),
+            other => panic!("expected summary subcommand, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_batch_subcommand() {
+        let cli = with_clean_env(|| Cli::parse_from(["nexus", "batch", "tasks.yaml", "-j", "3"]));
+        match cli.command {
+            Some(Command::Batch(args)) => {
+                assert_eq!(args.file, PathBuf::from("tasks.yaml"));
+                assert_eq!(args.jobs, 3);
+            }
+            other => panic!("expected batch subcommand, got {other:?}"),
+        }
+
+        let result =
+            with_clean_env(|| Cli::try_parse_from(["nexus", "batch", "t.yaml", "-j", "0"]));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_daemon_subcommand_defaults() {
+        let cli = with_clean_env(|| Cli::parse_from(["nexus", "daemon", "--once"]));
+        match cli.command {
+            Some(Command::Daemon(args)) => {
+                assert_eq!(args.queue, PathBuf::from(".nexus/queue"));
+                assert_eq!(args.poll_interval, 5);
+                assert!(args.once);
+            }
+            other => panic!("expected daemon subcommand, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_retry_subcommand() {
+        let cli = with_clean_env(|| {
+            Cli::parse_from([
+                "nexus",
+                "retry",
+                "run_1",
+                "--model",
+                "gpt-x",
+                "--temperature",
+                "0.3",
+                "--instruction",
+                "be brief",
+            ])
+        });
+        assert_eq!(cli.model.as_deref(), Some("gpt-x"));
+        match cli.command {
+            Some(Command::Retry(args)) => {
+                assert_eq!(args.run_id, "run_1");
+                assert_eq!(args.temperature, Some(0.3));
+                assert_eq!(args.instruction.as_deref(), Some("be brief"));
+            }
+            other => panic!("expected retry subcommand, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_refine_subcommand() {
+        let cli = with_clean_env(|| {
+            Cli::parse_from(["nexus", "refine", "run_1", "also update the tests"])
+        });
+        match cli.command {
+            Some(Command::Refine(args)) => {
+                assert_eq!(args.run_id, "run_1");
+                assert_eq!(args.instruction, "also update the tests");
+            }
+            other => panic!("expected refine subcommand, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_runs_diff_subcommand() {
+        let cli = with_clean_env(|| Cli::parse_from(["nexus", "runs", "diff", "run_a", "run_b"]));
+        match cli.command {
+            Some(Command::Runs(RunsArgs {
+                command: RunsCommand::Diff { run_a, run_b },
+            })) => {
+                assert_eq!(run_a, "run_a");
+                assert_eq!(run_b, "run_b");
+            }
+            other => panic!("expected runs diff subcommand, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_runs_list_subcommand() {
+        let cli = with_clean_env(|| Cli::parse_from(["nexus", "runs", "list", "--rebuild"]));
+        match cli.command {
+            Some(Command::Runs(RunsArgs {
+                command: RunsCommand::List { rebuild, json },
+            })) => {
+                assert!(rebuild);
+                assert!(!json);
+            }
+            other => panic!("expected runs list subcommand, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_runs_cost_subcommand() {
+        let cli = with_clean_env(|| Cli::parse_from(["nexus", "runs", "cost", "run_1"]));
+        match cli.command {
+            Some(Command::Runs(RunsArgs {
+                command: RunsCommand::Cost { run_id, json },
+            })) => {
+                assert_eq!(run_id, "run_1");
+                assert!(!json);
+            }
+            other => panic!("expected runs cost subcommand, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_runs_show_subcommand() {
+        let cli = with_clean_env(|| Cli::parse_from(["nexus", "runs", "show", "run_1", "--json"]));
+        match cli.command {
+            Some(Command::Runs(RunsArgs {
+                command: RunsCommand::Show { run_id, json },
+            })) => {
+                assert_eq!(run_id, "run_1");
+                assert!(json);
+            }
+            other => panic!("expected runs show subcommand, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_ci_flag_is_global() {
+        let cli = with_clean_env(|| Cli::parse_from(["nexus", "retry", "run_1", "--ci", "github"]));
+        assert_eq!(cli.ci, Some(CiMode::Github));
+    }
+
+    #[test]
+    fn test_output_format_flag_is_global() {
+        let cli = with_clean_env(|| Cli::parse_from(["nexus", "--dry-run", "rename a"]));
+        assert!(!cli.json_output());
+
+        let cli = with_clean_env(|| {
+            Cli::parse_from(["nexus", "runs", "list", "--output-format", "json"])
+        });
+        assert!(cli.json_output());
+        let cli = with_clean_env(|| {
+            Cli::parse_from(["nexus", "--output-format", "json", "--dry-run", "rename a"])
+        });
+        assert!(cli.json_output());
+        assert!(cli.dry_run);
+    }
+
+    #[test]
+    fn test_log_export_subcommand() {
+        let cli = with_clean_env(|| {
+            Cli::parse_from([
+                "nexus",
+                "log",
+                "export",
+                "run_1",
+                "--anonymize",
+                "-o",
+                "out.jsonl",
+            ])
+        });
+        match cli.command {
+            Some(Command::Log(LogArgs {
+                command:
+                    LogCommand::Export {
+                        run_id,
+                        all,
+                        format,
+                        anonymize,
+                        output,
+                    },
+            })) => {
+                assert_eq!(run_id.as_deref(), Some("run_1"));
+                assert!(!all);
+                assert_eq!(format, LogFormat::Jsonl);
+                assert!(anonymize);
+                assert_eq!(output, Some(PathBuf::from("out.jsonl")));
+            }
+            other => panic!("expected log export subcommand, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_log_export_all_as_sqlite() {
+        let cli = with_clean_env(|| {
+            Cli::parse_from([
+                "nexus", "logs", "export", "--all", "--format", "sqlite", "-o", "runs.db",
+            ])
+        });
+        match cli.command {
+            Some(Command::Log(LogArgs {
+                command:
+                    LogCommand::Export {
+                        run_id,
+                        all,
+                        format,
+                        ..
+                    },
+            })) => {
+                assert_eq!(run_id, None);
+                assert!(all);
+                assert_eq!(format, LogFormat::Sqlite);
+            }
+            other => panic!("expected log export subcommand, got {other:?}"),
+        }
+
+        let both =
+            with_clean_env(|| Cli::try_parse_from(["nexus", "log", "export", "run_1", "--all"]));
+        assert!(both.is_err());
+        let neither = with_clean_env(|| Cli::try_parse_from(["nexus", "log", "export"]));
+        assert!(neither.is_err());
+    }
+
+    #[test]
+    fn test_config_migrate_subcommand() {
+        let cli = with_clean_env(|| {
+            Cli::parse_from(["nexus", "config", "migrate", "--dry-run", "-c", "s.json"])
+        });
+        assert_eq!(cli.config, PathBuf::from("s.json"));
+        assert!(!cli.dry_run);
+        match cli.command {
+            Some(Command::Config(ConfigArgs {
+                command: ConfigCommand::Migrate { dry_run },
+            })) => assert!(dry_run),
+            other => panic!("expected config migrate subcommand, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_logs_verify_subcommand() {
+        let cli = with_clean_env(|| Cli::parse_from(["nexus", "logs", "verify", "run_1"]));
+        match cli.command {
+            Some(Command::Log(LogArgs {
+                command: LogCommand::Verify { run_id },
+            })) => assert_eq!(run_id, "run_1"),
+            other => panic!("expected log verify subcommand, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_logs_tail_subcommand() {
+        let cli = with_clean_env(|| {
+            Cli::parse_from(["nexus", "logs", "tail", "run_1", "--follow", "-n", "0"])
+        });
+        match cli.command {
+            Some(Command::Log(LogArgs {
+                command:
+                    LogCommand::Tail {
+                        run_id,
+                        follow,
+                        lines,
+                    },
+            })) => {
+                assert_eq!(run_id, "run_1");
+                assert!(follow);
+                assert_eq!(lines, 0);
+            }
+            other => panic!("expected log tail subcommand, got {other:?}"),
+        }
+    }
 }
@@ -0,0 +1,205 @@
+//! Gitignore-style filtering for workspace traversal.
+//!
+//! Follows the `.gitignore` rules git itself applies: blank lines and `#`
+//! comments are skipped, `!` re-includes, a trailing `/` matches only
+//! directories, and a pattern containing a `/` is anchored to the directory
+//! of its file while any other pattern matches at every depth. Deeper files
+//! override their parents and, within a file, the last matching rule wins.
+//! `.git/info/exclude` applies like a root `.gitignore`.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use globset::{GlobBuilder, GlobMatcher};
+
+use crate::error::NexusError;
+
+const IGNORE_FILE: &str = ".gitignore";
+
+/// Rules read from one ignore file.
+#[derive(Debug, Clone)]
+pub struct IgnoreFile {
+    /// Directory the file applies to, relative to the root; empty for the
+    /// root itself.
+    base: String,
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    matcher: GlobMatcher,
+    negate: bool,
+    dir_only: bool,
+}
+
+impl IgnoreFile {
+    /// Parses `content` as the ignore file of the `base` directory.
+    ///
+    /// Patterns globset cannot compile are skipped with a warning, as git
+    /// skips patterns it cannot use.
+    pub fn parse(base: &str, content: &str) -> Self {
+        let rules = content
+            .lines()
+            .filter_map(|line| {
+                let rule = parse_rule(line);
+                if let Some(Err(err)) = &rule {
+                    log::warn!("ignoring invalid pattern in {base}/{IGNORE_FILE}: {err}");
+                }
+                rule?.ok()
+            })
+            .collect();
+        Self {
+            base: base.trim_matches('/').to_string(),
+            rules,
+        }
+    }
+
+    /// `Some(true)` if the last rule matching `path` ignores it,
+    /// `Some(false)` if it re-includes it, and `None` if no rule matches.
+    pub fn matched(&self, path: &str, is_dir: bool) -> Option<bool> {
+        let relative = if self.base.is_empty() {
+            path
+        } else {
+            path.strip_prefix(self.base.as_str())?.strip_prefix('/')?
+        };
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| (is_dir || !rule.dir_only) && rule.matcher.is_match(relative))
+            .map(|rule| !rule.negate)
+    }
+}
+
+fn parse_rule(line: &str) -> Option<Result<Rule, globset::Error>> {
+    let mut pattern = trim_unescaped_trailing_spaces(line);
+    if pattern.is_empty() || pattern.starts_with('#') {
+        return None;
+    }
+    let negate = pattern.starts_with('!');
+    // `\#` and `\!` stand for a literal leading `#` or `!`.
+    if negate || pattern.starts_with("\\#") || pattern.starts_with("\\!") {
+        pattern = &pattern[1..];
+    }
+    let dir_only = pattern.ends_with('/');
+    let pattern = pattern.trim_end_matches('/');
+    if pattern.is_empty() {
+        return None;
+    }
+    let glob = match pattern.strip_prefix('/') {
+        Some(anchored) => anchored.to_string(),
+        None if pattern.contains('/') => pattern.to_string(),
+        None => format!("**/{pattern}"),
+    };
+    Some(
+        GlobBuilder::new(&glob)
+            .literal_separator(true)
+            .backslash_escape(true)
+            .build()
+            .map(|glob| Rule {
+                matcher: glob.compile_matcher(),
+                negate,
+                dir_only,
+            }),
+    )
+}
+
+fn trim_unescaped_trailing_spaces(line: &str) -> &str {
+    let trimmed = line.trim_end_matches(' ');
+    if trimmed.ends_with('\\') && trimmed.len() < line.len() {
+        // Keep the escaped space.
+        &line[..trimmed.len() + 1]
+    } else {
+        trimmed
+    }
+}
+
+/// Ignore files in effect for one directory: its own and its ancestors'.
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreStack {
+    files: Vec<Arc<IgnoreFile>>,
+}
+
+impl IgnoreStack {
+    /// Rules for the root directory: `.git/info/exclude` and `.gitignore`.
+    pub fn for_root(root: &Path) -> Result<Self, NexusError> {
+        let mut stack = Self::default();
+        if let Some(exclude) = read_optional(&root.join(".git").join("info").join("exclude"))? {
+            stack.files.push(Arc::new(IgnoreFile::parse("", &exclude)));
+        }
+        stack.enter(root, "")
+    }
+
+    /// Rules for the subdirectory `dir` (relative to `root`) of the
+    /// directory these rules belong to.
+    pub fn enter(&self, root: &Path, dir: &str) -> Result<Self, NexusError> {
+        let mut stack = self.clone();
+        if let Some(content) = read_optional(&root.join(dir).join(IGNORE_FILE))? {
+            stack.files.push(Arc::new(IgnoreFile::parse(dir, &content)));
+        }
+        Ok(stack)
+    }
+
+    /// True if `path` (relative to the root) is ignored.
+    pub fn is_ignored(&self, path: &str, is_dir: bool) -> bool {
+        self.files
+            .iter()
+            .rev()
+            .find_map(|file| file.matched(path, is_dir))
+            .unwrap_or(false)
+    }
+}
+
+fn read_optional(path: &Path) -> Result<Option<String>, NexusError> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(NexusError::IoError {
+            operation: "read ignore file".to_string(),
+            path: path.to_path_buf(),
+            source: err,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gitignore_rules() {
+        let file = IgnoreFile::parse(
+            "",
+            "# build output\n*.log\n!keep.log\nbuild/\n/dist\ndocs/*.html\n\\#notes\n",
+        );
+        let ignored = |path: &str, is_dir: bool| file.matched(path, is_dir);
+
+        assert_eq!(ignored("a.log", false), Some(true));
+        assert_eq!(ignored("src/deep/a.log", false), Some(true));
+        assert_eq!(ignored("src/keep.log", false), Some(false));
+        assert_eq!(ignored("build", true), Some(true));
+        assert_eq!(ignored("src/build", true), Some(true));
+        assert_eq!(ignored("build", false), None);
+        assert_eq!(ignored("dist", true), Some(true));
+        assert_eq!(ignored("src/dist", true), None);
+        assert_eq!(ignored("docs/a.html", false), Some(true));
+        assert_eq!(ignored("docs/api/a.html", false), None);
+        assert_eq!(ignored("#notes", false), Some(true));
+        assert_eq!(ignored("src/main.rs", false), None);
+    }
+
+    #[test]
+    fn test_nested_files_override_parents() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("web")).unwrap();
+        std::fs::write(dir.path().join(".gitignore"), "*.gen.ts\n").unwrap();
+        std::fs::write(dir.path().join("web/.gitignore"), "!api.gen.ts\n/local/\n").unwrap();
+
+        let root = IgnoreStack::for_root(dir.path()).unwrap();
+        let web = root.enter(dir.path(), "web").unwrap();
+        assert!(root.is_ignored("types.gen.ts", false));
+        assert!(web.is_ignored("web/types.gen.ts", false));
+        assert!(!web.is_ignored("web/api.gen.ts", false));
+        assert!(web.is_ignored("web/local", true));
+        assert!(!root.is_ignored("local", true));
+    }
+}
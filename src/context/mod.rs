@@ -0,0 +1,236 @@
+//! File context collection for executor prompts.
+//!
+//! Glob expansion walks the workspace the way git sees it: files and
+//! directories ignored by `.gitignore` (see [`ignore`]) are never
+//! collected, nor are build and dependency directories.
+//!
+//! Edits made to context files while the model was working are caught at
+//! apply time through the `base_file_sha256` the executor records with each
+//! patch; see [`crate::apply::Applier`].
+
+pub mod ignore;
+
+use std::path::Path;
+
+use globset::{Glob, GlobSetBuilder};
+
+use crate::binary::BinaryGuard;
+use crate::error::NexusError;
+use crate::executor::FileContext;
+use crate::executor::prompt::language_from_path;
+use crate::paths::normalize_separators;
+
+use ignore::IgnoreStack;
+
+/// Reads a repository-relative file into a `FileContext`, with its language
+/// inferred from the extension.
+///
+/// Binary files are rejected unless allowed by `guard`; allowed files that
+/// are not valid UTF-8 are decoded lossily.
+pub fn load_file(root: &Path, path: &str, guard: &BinaryGuard) -> Result<FileContext, NexusError> {
+    let path = normalize_separators(path);
+    let full_path = root.join(&path);
+    let bytes = std::fs::read(&full_path).map_err(|err| NexusError::IoError {
+        operation: "read context file".to_string(),
+        path: full_path.clone(),
+        source: err,
+    })?;
+
+    guard.check_bytes(&path, &bytes)?;
+
+    let content = match String::from_utf8(bytes) {
+        Ok(content) => content,
+        Err(err) if guard.is_allowed(&path) => String::from_utf8_lossy(err.as_bytes()).into_owned(),
+        Err(_) => {
+            return Err(NexusError::BinaryFile {
+                path,
+                reason: "content is not valid UTF-8".to_string(),
+            });
+        }
+    };
+
+    Ok(FileContext {
+        language: Some(language_from_path(&path)),
+        path,
+        content,
+    })
+}
+
+/// Directories never searched when expanding globs, ignored or not.
+const SKIPPED_DIRS: &[&str] = &[".git", ".nexus", "target", "node_modules"];
+
+/// Expands glob patterns into sorted repository-relative file paths.
+///
+/// Patterns match `/`-separated paths relative to `root`. Paths ignored by
+/// `.gitignore` files or `.git/info/exclude` are left out, and
+/// [`SKIPPED_DIRS`] are never searched.
+pub fn expand_globs(root: &Path, patterns: &[String]) -> Result<Vec<String>, NexusError> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern).map_err(|err| NexusError::ValidationError {
+            message: format!("invalid file glob '{pattern}': {err}"),
+            field: Some("files".to_string()),
+        })?;
+        builder.add(glob);
+    }
+    let globs = builder.build().map_err(|err| NexusError::ValidationError {
+        message: format!("invalid file globs: {err}"),
+        field: Some("files".to_string()),
+    })?;
+
+    let mut matches = Vec::new();
+    let mut pending = vec![(root.to_path_buf(), IgnoreStack::for_root(root)?)];
+    while let Some((dir, ignores)) = pending.pop() {
+        let entries = std::fs::read_dir(&dir).map_err(|err| NexusError::IoError {
+            operation: "read directory".to_string(),
+            path: dir.clone(),
+            source: err,
+        })?;
+        for entry in entries {
+            let entry = entry.map_err(|err| NexusError::IoError {
+                operation: "read directory".to_string(),
+                path: dir.clone(),
+                source: err,
+            })?;
+            let path = entry.path();
+            let Ok(relative) = path.strip_prefix(root) else {
+                continue;
+            };
+            let relative = normalize_separators(&relative.to_string_lossy());
+            let file_type = entry.file_type().map_err(|err| NexusError::IoError {
+                operation: "stat".to_string(),
+                path: path.clone(),
+                source: err,
+            })?;
+            if file_type.is_dir() {
+                let skipped = SKIPPED_DIRS.contains(&entry.file_name().to_string_lossy().as_ref());
+                if !skipped && !ignores.is_ignored(&relative, true) {
+                    let ignores = ignores.enter(root, &relative)?;
+                    pending.push((path, ignores));
+                }
+            } else if file_type.is_file()
+                && globs.is_match(&relative)
+                && !ignores.is_ignored(&relative, false)
+            {
+                matches.push(relative);
+            }
+        }
+    }
+
+    matches.sort();
+    Ok(matches)
+}
+
+/// Expands `patterns` and loads every matching file, in path order.
+pub fn collect_files(
+    root: &Path,
+    patterns: &[String],
+    guard: &BinaryGuard,
+) -> Result<Vec<FileContext>, NexusError> {
+    expand_globs(root, patterns)?
+        .iter()
+        .map(|path| load_file(root, path, guard))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_file_reads_text() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/lib.rs"), "pub fn a() {}\n").unwrap();
+
+        let context = load_file(dir.path(), "src\\lib.rs", &BinaryGuard::default()).unwrap();
+        assert_eq!(context.path, "src/lib.rs");
+        assert_eq!(context.content, "pub fn a() {}\n");
+        assert_eq!(context.language.as_deref(), Some("rust"));
+    }
+
+    #[test]
+    fn test_load_file_rejects_binary() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("image.png"), b"\x89PNG\0\0").unwrap();
+
+        let result = load_file(dir.path(), "image.png", &BinaryGuard::default());
+        assert!(matches!(result, Err(NexusError::BinaryFile { .. })));
+    }
+
+    #[test]
+    fn test_load_file_allows_configured_binary_paths() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("data.dat"), b"key=\xff\0value").unwrap();
+        let guard = BinaryGuard::new(&["*.dat".to_string()]).unwrap();
+
+        let context = load_file(dir.path(), "data.dat", &guard).unwrap();
+        assert!(context.content.starts_with("key="));
+    }
+
+    #[test]
+    fn test_expand_globs_sorted_and_skips_nexus() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("src/nested")).unwrap();
+        std::fs::create_dir_all(dir.path().join(".nexus/runs")).unwrap();
+        std::fs::write(dir.path().join("src/b.rs"), "").unwrap();
+        std::fs::write(dir.path().join("src/nested/a.rs"), "").unwrap();
+        std::fs::write(dir.path().join("src/readme.md"), "").unwrap();
+        std::fs::write(dir.path().join(".nexus/runs/x.rs"), "").unwrap();
+
+        let files = expand_globs(dir.path(), &["**/*.rs".to_string()]).unwrap();
+        assert_eq!(files, ["src/b.rs", "src/nested/a.rs"]);
+    }
+
+    #[test]
+    fn test_expand_globs_skips_ignored_and_build_dirs() {
+        let dir = TempDir::new().unwrap();
+        for path in [
+            "src/lib.rs",
+            "src/gen/out.rs",
+            "src/keep.generated.rs",
+            "src/other.generated.rs",
+            "target/debug/build.rs",
+            "web/node_modules/pkg/index.rs",
+            "vendor/dep.rs",
+        ] {
+            let path = dir.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+        std::fs::write(dir.path().join(".gitignore"), "gen/\n*.generated.rs\n").unwrap();
+        std::fs::write(dir.path().join("src/.gitignore"), "!keep.generated.rs\n").unwrap();
+        std::fs::create_dir_all(dir.path().join(".git/info")).unwrap();
+        std::fs::write(dir.path().join(".git/info/exclude"), "/vendor\n").unwrap();
+
+        let files = expand_globs(dir.path(), &["**/*.rs".to_string()]).unwrap();
+        assert_eq!(files, ["src/keep.generated.rs", "src/lib.rs"]);
+    }
+
+    #[test]
+    fn test_collect_files_loads_matches_with_language() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/app.ts"), "export {};\n").unwrap();
+        std::fs::write(dir.path().join("Makefile"), "all:\n").unwrap();
+
+        let files = collect_files(
+            dir.path(),
+            &["src/*.ts".to_string(), "Makefile".to_string()],
+            &BinaryGuard::default(),
+        )
+        .unwrap();
+        let loaded: Vec<(&str, Option<&str>)> = files
+            .iter()
+            .map(|file| (file.path.as_str(), file.language.as_deref()))
+            .collect();
+        assert_eq!(
+            loaded,
+            [
+                ("Makefile", Some("text")),
+                ("src/app.ts", Some("typescript"))
+            ]
+        );
+    }
+}
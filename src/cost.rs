@@ -0,0 +1,174 @@
+//! Complexity units (CU): how much reviewing and applying an action costs.
+//!
+//! Estimates follow the starter metric in `docs/architecture.md`,
+//! `CU = 2F + 3D + C + 5R`, per action: `F` files it touches, `D` their
+//! distinct directories, `C` the weight of the command it runs and `R` its
+//! risk. Patches and created files add one unit per [`LINES_PER_CU`]
+//! changed lines, and commands weigh more the longer they may run. The
+//! autopilot batch budget (`max_batch_cu`) is spent in these units.
+
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+use crate::policy::touched_paths;
+use crate::preview::diff_stat;
+use crate::types::{ActionDetails, PatchDetails, ProposedAction};
+
+/// Changed lines that add one unit to a patch.
+pub const LINES_PER_CU: usize = 20;
+
+/// An action's complexity units, by component.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CostEstimate {
+    /// Files the action touches.
+    pub files: u32,
+    /// Distinct directories of those files.
+    pub dirs: u32,
+    /// Units for the lines it changes.
+    pub size: u32,
+    /// Weight of the command it runs; see [`timeout_class`].
+    pub command: u32,
+    pub risk: u32,
+}
+
+impl CostEstimate {
+    pub fn of(action: &ProposedAction) -> Self {
+        let paths = touched_paths(action);
+        let dirs: HashSet<&str> = paths
+            .iter()
+            .map(|path| path.rsplit_once('/').map_or("", |(dir, _)| dir))
+            .collect();
+        let command = match &action.details {
+            ActionDetails::Command(details) => timeout_class(details.timeout_s),
+            _ => 0,
+        };
+        Self {
+            files: saturate(paths.len()),
+            dirs: saturate(dirs.len()),
+            size: saturate(changed_lines(action).div_ceil(LINES_PER_CU)),
+            command,
+            risk: u32::from(action.risk),
+        }
+    }
+
+    /// Total units: `2F + 3D + size + C + 5R`.
+    pub fn cu(&self) -> u32 {
+        self.files
+            .saturating_mul(2)
+            .saturating_add(self.dirs.saturating_mul(3))
+            .saturating_add(self.size)
+            .saturating_add(self.command)
+            .saturating_add(self.risk.saturating_mul(5))
+    }
+}
+
+/// Complexity units of `action`.
+pub fn action_cu(action: &ProposedAction) -> u32 {
+    CostEstimate::of(action).cu()
+}
+
+/// Weight of a command allowed to run `timeout_s` seconds: 1 up to a
+/// minute, 2 up to ten minutes, 3 beyond.
+pub fn timeout_class(timeout_s: u32) -> u32 {
+    match timeout_s {
+        0..=60 => 1,
+        61..=600 => 2,
+        _ => 3,
+    }
+}
+
+/// Lines `action` adds or removes, as far as its details tell.
+fn changed_lines(action: &ProposedAction) -> usize {
+    match &action.details {
+        ActionDetails::Patch(details) => patch_lines(details),
+        ActionDetails::AgendaPatch(details) => {
+            let stat = diff_stat(&details.diff);
+            stat.added + stat.removed
+        }
+        ActionDetails::FileCreate(details) => details.content.lines().count(),
+        _ => 0,
+    }
+}
+
+fn patch_lines(details: &PatchDetails) -> usize {
+    let diff = details.diff.as_deref().map_or(0, |diff| {
+        let stat = diff_stat(diff);
+        stat.added + stat.removed
+    });
+    let blocks: usize = details
+        .search_replace_blocks
+        .iter()
+        .flatten()
+        .map(|block| block.search.lines().count() + block.replace.lines().count())
+        .sum();
+    let whole_files: usize = details
+        .whole_file_content
+        .iter()
+        .flat_map(|contents| contents.values())
+        .map(|content| content.lines().count())
+        .sum();
+    diff + blocks + whole_files
+}
+
+fn saturate(count: usize) -> u32 {
+    u32::try_from(count).unwrap_or(u32::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::CommandDetails;
+
+    fn action(details: ActionDetails) -> ProposedAction {
+        ProposedAction::test("act_1", details)
+    }
+
+    fn patch(files: &[&str], diff: Option<&str>) -> ProposedAction {
+        action(ActionDetails::Patch(PatchDetails {
+            files: files.iter().map(|file| file.to_string()).collect(),
+            diff: diff.map(str::to_string),
+            ..Default::default()
+        }))
+    }
+
+    fn command(timeout_s: u32) -> ProposedAction {
+        action(ActionDetails::Command(CommandDetails {
+            argv: vec!["cargo".to_string(), "test".to_string()],
+            cwd: None,
+            timeout_s,
+            env_allow: Vec::new(),
+            requires_network: false,
+            purpose: None,
+        }))
+    }
+
+    #[test]
+    fn test_files_and_directories() {
+        // One file at the root, risk 1: 2 + 3 + 5 = 10 CU.
+        assert_eq!(action_cu(&patch(&["a.rs"], None)), 10);
+        assert_eq!(action_cu(&patch(&["src/a.rs", "src/b.rs"], None)), 12);
+        assert_eq!(action_cu(&patch(&["src/a.rs", "tests/b.rs"], None)), 15);
+    }
+
+    #[test]
+    fn test_patch_size_adds_units() {
+        let small = "--- a/a.rs\n+++ b/a.rs\n@@ -1 +1 @@\n-one\n+1\n";
+        assert_eq!(CostEstimate::of(&patch(&[], Some(small))).size, 1);
+
+        let mut large = String::from("--- a/a.rs\n+++ b/a.rs\n@@ -1,30 +1,30 @@\n");
+        for line in 0..30 {
+            large.push_str(&format!("-{line}\n+{line}!\n"));
+        }
+        let estimate = CostEstimate::of(&patch(&[], Some(&large)));
+        assert_eq!(estimate.size, 3);
+        assert_eq!(estimate.cu(), 2 + 3 + 3 + 5);
+    }
+
+    #[test]
+    fn test_commands_weigh_by_timeout() {
+        assert_eq!(action_cu(&command(30)), 1 + 5);
+        assert_eq!(action_cu(&command(600)), 2 + 5);
+        assert_eq!(action_cu(&command(1200)), 3 + 5);
+    }
+}
@@ -0,0 +1,233 @@
+//! Queue daemon: processes task files dropped into a directory.
+//!
+//! Each `*.yaml`/`*.yml` file in the queue directory is one batch entry
+//! (`task`, `files`, `options`). Files are claimed oldest-name-first by moving
+//! them to `processing/`, run one at a time with the autopilot permission
+//! gate, and the actions the gate approved are applied on the run's
+//! `nexus/<run_id>` branch. The file is then moved to `done/` or `failed/`
+//! next to a `<name>.result.json` describing the run.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde_json::json;
+
+use crate::apply::RunApply;
+use crate::apply::run::permission_decisions;
+use crate::batch::{BatchFile, BatchOutcome, BatchRunner, BatchTask};
+use crate::cancel::CancelToken;
+use crate::error::NexusError;
+use crate::preview::pending_actions;
+use crate::redact::Redactor;
+use crate::resume::finish_run;
+use crate::types::NexusSettings;
+
+/// Default queue directory, relative to the project root.
+pub const DEFAULT_QUEUE_DIR: &str = ".nexus/queue";
+/// Default delay between scans of an empty queue.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+const PROCESSING_DIR: &str = "processing";
+const DONE_DIR: &str = "done";
+const FAILED_DIR: &str = "failed";
+const TASK_EXTENSIONS: &[&str] = &["yaml", "yml"];
+
+/// Watches a queue directory and runs submitted tasks sequentially.
+pub struct Daemon<'a> {
+    runner: BatchRunner<'a>,
+    queue_dir: PathBuf,
+    poll_interval: Duration,
+    settings: NexusSettings,
+    redactor: Option<Redactor>,
+}
+
+impl<'a> Daemon<'a> {
+    /// `runner` should carry the autopilot permission gate.
+    pub fn new(runner: BatchRunner<'a>, queue_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            runner,
+            queue_dir: queue_dir.into(),
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            settings: NexusSettings::default(),
+            redactor: None,
+        }
+    }
+
+    /// Applies approved actions as `settings` allow, e.g. their paths, git
+    /// branch and verify commands.
+    pub fn with_settings(mut self, settings: NexusSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Masks secrets in the events applying writes; without one, only the
+    /// `redact_patterns` of its settings and the built-in patterns are.
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = Some(redactor);
+        self
+    }
+
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Processes queued tasks until `cancel` fires.
+    ///
+    /// Failing tasks are logged and the daemon keeps going.
+    pub async fn run(&self, cancel: &CancelToken) -> Result<(), NexusError> {
+        while !cancel.is_cancelled() {
+            match self.run_once().await {
+                Ok(Some(outcome)) => {
+                    log::info!("{} finished: {}", outcome.run_id, outcome.status.as_str());
+                    continue;
+                }
+                Ok(None) => {}
+                Err(NexusError::Cancelled) => break,
+                Err(err) => log::error!("queued task failed: {err}"),
+            }
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                _ = tokio::time::sleep(self.poll_interval) => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Claims and runs the next queued task, if any, and applies the actions
+    /// the permission gate approved.
+    ///
+    /// Task files that fail to parse, or whose run or apply fails with an
+    /// error, are moved to `failed/` with the error.
+    pub async fn run_once(&self) -> Result<Option<BatchOutcome>, NexusError> {
+        let Some(queued) = self.next_task()? else {
+            return Ok(None);
+        };
+        let claimed = self.move_into(&queued, PROCESSING_DIR)?;
+        log::info!("processing {}", claimed.display());
+
+        let task = match BatchTask::load(&claimed) {
+            Ok(task) => task,
+            Err(err) => {
+                let failed = self.move_into(&claimed, FAILED_DIR)?;
+                write_result(&failed, &json!({ "error": err.to_string() }))?;
+                return Err(err);
+            }
+        };
+
+        let (outcome, applied) = match self.process(task).await {
+            Ok(processed) => processed,
+            Err(err) => {
+                let failed = self.move_into(&claimed, FAILED_DIR)?;
+                write_result(&failed, &json!({ "error": err.to_string() }))?;
+                return Err(err);
+            }
+        };
+        let target = if outcome.is_failure() {
+            FAILED_DIR
+        } else {
+            DONE_DIR
+        };
+        let finished = self.move_into(&claimed, target)?;
+        write_result(
+            &finished,
+            &json!({
+                "run_id": outcome.run_id,
+                "log_path": outcome.log_path,
+                "status": outcome.status.as_str(),
+                "action_count": outcome.action_count,
+                "approved_count": outcome.approved_count,
+                "branch": applied.as_ref().and_then(|report| report["branch"].as_str()),
+                "apply": applied,
+                "error": outcome.error,
+            }),
+        )?;
+        Ok(Some(outcome))
+    }
+
+    /// Runs `task`, then applies what the gate approved; returns the outcome
+    /// with the run's final status, and the apply report as JSON when the
+    /// run proposed actions.
+    async fn process(
+        &self,
+        task: BatchTask,
+    ) -> Result<(BatchOutcome, Option<serde_json::Value>), NexusError> {
+        let batch = BatchFile { tasks: vec![task] };
+        let mut outcome = self
+            .runner
+            .run(&batch)
+            .await?
+            .pop()
+            .expect("one outcome per task");
+        if outcome.is_failure() || outcome.action_count == 0 {
+            return Ok((outcome, None));
+        }
+
+        let redactor = match &self.redactor {
+            Some(redactor) => redactor.clone(),
+            None => Redactor::from_settings(&self.settings)?,
+        };
+        let granted = permission_decisions(&outcome.log_path)?.granted;
+        let mut apply =
+            RunApply::from_settings(self.runner.root(), &outcome.run_id, &self.settings)?
+                .with_redactor(redactor.clone());
+        for action in pending_actions(&outcome.log_path)? {
+            if !granted.contains(&action.id) {
+                apply = apply.with_held(&action.id, "awaiting approval");
+            }
+        }
+        let report = apply.run()?;
+        outcome.status = finish_run(
+            self.runner.root(),
+            &outcome.run_id,
+            &redactor,
+            self.settings.event_sync_interval(),
+        )?;
+        Ok((outcome, Some(report.to_json())))
+    }
+
+    fn next_task(&self) -> Result<Option<PathBuf>, NexusError> {
+        let entries = match std::fs::read_dir(&self.queue_dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(io_error("read queue directory", &self.queue_dir, err)),
+        };
+
+        let mut tasks: Vec<PathBuf> = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && is_task_file(path))
+            .collect();
+        tasks.sort();
+        Ok(tasks.into_iter().next())
+    }
+
+    fn move_into(&self, path: &Path, dir: &str) -> Result<PathBuf, NexusError> {
+        let target_dir = self.queue_dir.join(dir);
+        std::fs::create_dir_all(&target_dir)
+            .map_err(|err| io_error("create queue directory", &target_dir, err))?;
+        let target = target_dir.join(path.file_name().unwrap_or_default());
+        std::fs::rename(path, &target).map_err(|err| io_error("move task file", path, err))?;
+        Ok(target)
+    }
+}
+
+fn is_task_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| TASK_EXTENSIONS.contains(&ext))
+}
+
+fn write_result(task_path: &Path, result: &serde_json::Value) -> Result<(), NexusError> {
+    let path = task_path.with_extension("result.json");
+    let json = serde_json::to_string_pretty(result)?;
+    std::fs::write(&path, json + "\n").map_err(|err| io_error("write task result", &path, err))
+}
+
+fn io_error(operation: &str, path: &Path, source: std::io::Error) -> NexusError {
+    NexusError::IoError {
+        operation: operation.to_string(),
+        path: path.to_path_buf(),
+        source,
+    }
+}
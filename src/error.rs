@@ -7,6 +7,9 @@ pub enum NexusError {
     #[error("invalid run_id: {0}")]
     InvalidRunId(String),
 
+    #[error("invalid action_id: {0}")]
+    InvalidActionId(String),
+
     #[error("event log is locked by another process")]
     EventLogLocked,
 
@@ -100,8 +103,12 @@ pub enum NexusError {
     #[error("rate limited (retry after {retry_after:?}s)")]
     RateLimited { retry_after: Option<u64> },
 
-    #[error("model not available: {model}")]
-    ModelNotAvailable { model: String },
+    #[error("model not available: {model}{}", did_you_mean(suggestions))]
+    ModelNotAvailable {
+        model: String,
+        /// Close matches among the provider's available models.
+        suggestions: Vec<String>,
+    },
 
     #[error("response parsing failed: {context}")]
     ResponseParseFailed {
@@ -111,11 +118,71 @@ pub enum NexusError {
 
     #[error("stream interrupted: {message}")]
     StreamInterrupted { message: String },
+
+    #[error("response exceeded {limit_bytes} byte limit ({} bytes received)", partial.len())]
+    ResponseTooLarge { limit_bytes: usize, partial: String },
+
+    #[error(
+        "prompt needs ~{estimated_tokens} tokens but {model} accepts {context_window}; \
+         largest files: {largest_files}. Narrow the file set (e.g. with --files) and retry"
+    )]
+    PromptTooLarge {
+        model: String,
+        estimated_tokens: usize,
+        context_window: usize,
+        largest_files: String,
+    },
+
+    #[error("binary file rejected: {path} ({reason})")]
+    BinaryFile { path: String, reason: String },
+
+    #[error("{failed} of {total} batch task(s) failed")]
+    BatchFailed { failed: usize, total: usize },
+
+    #[error("run cancelled")]
+    Cancelled,
+
+    #[error("run budget exceeded: {spent} spent, limit {limit}")]
+    BudgetExceeded { limit: String, spent: String },
+
+    #[error("no backup of {path} for run {run_id}")]
+    BackupNotFound { run_id: String, path: String },
+
+    #[error("{path} changed since it was applied; refusing to undo")]
+    FileChangedSinceApply { path: String },
+
+    #[error("event log tampered with at line {line}: {reason}")]
+    EventLogTampered { line: usize, reason: String },
+
+    #[error("git {command} failed: {message}")]
+    GitFailed { command: String, message: String },
+
+    #[error("working tree has uncommitted changes in {}; commit or stash them first", files.join(", "))]
+    WorkingTreeDirty { files: Vec<String> },
+
+    #[error("{format} export failed: {message}")]
+    ExportFailed {
+        format: String,
+        message: String,
+        #[source]
+        source: Option<Box<dyn std::error::Error + Send + Sync>>,
+    },
+}
+
+fn did_you_mean(suggestions: &[String]) -> String {
+    if suggestions.is_empty() {
+        String::new()
+    } else {
+        format!(" (did you mean {}?)", suggestions.join(", "))
+    }
 }
 
 #[derive(Error, Debug)]
 pub enum SettingsValidationError {
-    #[error("invalid schema version: expected '1.0', got '{0}'")]
+    #[error(
+        "invalid schema version: expected '{expected}', got '{0}'",
+        expected = crate::types::SCHEMA_VERSION
+    )]
     InvalidSchemaVersion(String),
 
     #[error("invalid permission mode: {0}")]
@@ -124,6 +191,27 @@ pub enum SettingsValidationError {
     #[error("invalid path pattern '{path}': {reason}")]
     InvalidPathPattern { path: String, reason: String },
 
+    #[error("invalid redact pattern '{pattern}': {reason}")]
+    InvalidRedactPattern { pattern: String, reason: String },
+
+    #[error("invalid prompt example '{name}': {reason}")]
+    InvalidPromptExample { name: String, reason: String },
+
+    #[error("invalid approval_rules[{index}]: {reason}")]
+    InvalidApprovalRule { index: usize, reason: String },
+
+    #[error("context_token_budget must be >= 1, got {0}")]
+    InvalidContextTokenBudget(usize),
+
+    #[error("max_run_tokens must be >= 1, got {0}")]
+    InvalidMaxRunTokens(u64),
+
+    #[error("durability_interval must be >= 1, got {0}")]
+    InvalidDurabilityInterval(u64),
+
+    #[error("max_run_cost_usd must be > 0, got {0}")]
+    InvalidMaxRunCost(f64),
+
     #[error("max_batch_cu must be >= 1, got {0}")]
     InvalidMaxBatchCu(u32),
 
@@ -136,6 +224,15 @@ pub type NexusResult<T> = Result<T, NexusError>;
 pub mod exit_codes {
     pub const OK: u8 = 0;
     pub const GENERAL_ERROR: u8 = 1;
+    // Run outcomes that are not errors but should not read as plain success.
+    pub const NO_CHANGES: u8 = 2;
+    pub const PENDING_APPLY: u8 = 3;
+    pub const PARTIALLY_APPLIED: u8 = 4;
+    pub const ROLLED_BACK: u8 = 5;
+    /// Aborted after passing `max_run_tokens` or `max_run_cost_usd`.
+    pub const BUDGET_EXCEEDED: u8 = 6;
+    /// Interrupted by SIGINT (128 + 2), matching shell convention.
+    pub const CANCELLED: u8 = 130;
     pub const USAGE: u8 = 64;
     pub const DATAERR: u8 = 65;
     pub const NOINPUT: u8 = 66;
@@ -143,6 +240,7 @@ pub mod exit_codes {
     pub const SOFTWARE: u8 = 70;
     pub const CANTCREAT: u8 = 73;
     pub const IOERR: u8 = 74;
+    pub const TEMPFAIL: u8 = 75;
     pub const NOPERM: u8 = 77;
     pub const CONFIG: u8 = 78;
 }
@@ -161,8 +259,8 @@ impl From<&NexusError> for u8 {
     /// ```
     fn from(err: &NexusError) -> u8 {
         match err {
-            NexusError::InvalidRunId(_) => exit_codes::USAGE,
-            NexusError::EventLogLocked => 75, // EX_TEMPFAIL
+            NexusError::InvalidRunId(_) | NexusError::InvalidActionId(_) => exit_codes::USAGE,
+            NexusError::EventLogLocked => exit_codes::TEMPFAIL,
             NexusError::EventLogNotFound(_) => exit_codes::NOINPUT,
             NexusError::EventLogCorrupted { .. } => exit_codes::DATAERR,
             NexusError::Serialization(_) => exit_codes::DATAERR,
@@ -189,6 +287,18 @@ impl From<&NexusError> for u8 {
             NexusError::ModelNotAvailable { .. } => exit_codes::CONFIG,
             NexusError::ResponseParseFailed { .. } => exit_codes::DATAERR,
             NexusError::StreamInterrupted { .. } => exit_codes::IOERR,
+            NexusError::ResponseTooLarge { .. } => exit_codes::DATAERR,
+            NexusError::PromptTooLarge { .. } => exit_codes::DATAERR,
+            NexusError::BinaryFile { .. } => exit_codes::DATAERR,
+            NexusError::BatchFailed { .. } => exit_codes::GENERAL_ERROR,
+            NexusError::Cancelled => exit_codes::CANCELLED,
+            NexusError::BudgetExceeded { .. } => exit_codes::BUDGET_EXCEEDED,
+            NexusError::FileChangedSinceApply { .. } => exit_codes::TEMPFAIL,
+            NexusError::BackupNotFound { .. } => exit_codes::NOINPUT,
+            NexusError::ExportFailed { .. } => exit_codes::IOERR,
+            NexusError::EventLogTampered { .. } => exit_codes::DATAERR,
+            NexusError::GitFailed { .. } => exit_codes::SOFTWARE,
+            NexusError::WorkingTreeDirty { .. } => exit_codes::TEMPFAIL,
         }
     }
 }
@@ -0,0 +1,224 @@
+//! Event log writer for async code.
+//!
+//! [`EventLogWriter`] does blocking file I/O, which stalls the runtime when
+//! called from a future. [`AsyncEventLogWriter`] moves the writer to its own
+//! thread and sends it events over a channel; each call still waits for its
+//! own write, so errors surface where they did before. Once the queue is
+//! drained the thread flushes the buffer, so events reach the file without
+//! waiting for an explicit `sync`.
+
+use std::path::{Path, PathBuf};
+
+use tokio::sync::{mpsc, oneshot};
+
+use super::EventLogWriter;
+use crate::error::NexusError;
+use crate::types::{RunEvent, TraceInfo};
+
+enum Command {
+    Append(Box<RunEvent>, oneshot::Sender<Result<(), NexusError>>),
+    AppendAll(Vec<RunEvent>, oneshot::Sender<Result<(), NexusError>>),
+    Sync(oneshot::Sender<Result<(), NexusError>>),
+}
+
+/// Handle to an [`EventLogWriter`] running on a background thread.
+///
+/// Events are written in the order they are sent. Dropping the handle
+/// lets the thread finish queued writes and close the log; use
+/// [`close`](Self::close) to wait for that, or
+/// [`into_inner`](Self::into_inner) to take the writer back.
+pub struct AsyncEventLogWriter {
+    commands: mpsc::UnboundedSender<Command>,
+    /// Receives the writer back once the thread has drained the queue.
+    finished: oneshot::Receiver<EventLogWriter>,
+    path: PathBuf,
+    trace: Option<TraceInfo>,
+}
+
+impl AsyncEventLogWriter {
+    /// Moves `writer` to a background thread.
+    pub fn spawn(writer: EventLogWriter) -> Self {
+        let path = writer.path().to_path_buf();
+        let trace = writer.trace().cloned();
+        let (commands, receiver) = mpsc::unbounded_channel();
+        let (done, finished) = oneshot::channel();
+        std::thread::spawn(move || {
+            let _ = done.send(run(writer, receiver));
+        });
+        Self {
+            commands,
+            finished,
+            path,
+            trace,
+        }
+    }
+
+    /// Opens `path` like [`EventLogWriter::open`] and moves the writer to a
+    /// background thread.
+    pub fn open(path: &Path) -> Result<Self, NexusError> {
+        Ok(Self::spawn(EventLogWriter::open(path)?))
+    }
+
+    /// Appends an event, assigning the next event_seq; see
+    /// [`EventLogWriter::append`].
+    pub async fn append(&self, event: &RunEvent) -> Result<(), NexusError> {
+        let (reply, result) = oneshot::channel();
+        self.send(Command::Append(Box::new(event.clone()), reply))?;
+        result.await.map_err(|_| self.stopped())?
+    }
+
+    /// Appends `events` with a single write; see
+    /// [`EventLogWriter::append_all`].
+    pub async fn append_all(&self, events: &[RunEvent]) -> Result<(), NexusError> {
+        let (reply, result) = oneshot::channel();
+        self.send(Command::AppendAll(events.to_vec(), reply))?;
+        result.await.map_err(|_| self.stopped())?
+    }
+
+    /// Flushes every event sent so far and syncs it to disk.
+    pub async fn sync(&self) -> Result<(), NexusError> {
+        let (reply, result) = oneshot::channel();
+        self.send(Command::Sync(reply))?;
+        result.await.map_err(|_| self.stopped())?
+    }
+
+    /// Returns the path of the log file being written.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the trace the writer gives events appended without one.
+    pub fn trace(&self) -> Option<&TraceInfo> {
+        self.trace.as_ref()
+    }
+
+    /// Waits for queued events to be written and returns the writer, still
+    /// holding the log's lock.
+    pub async fn into_inner(self) -> Result<EventLogWriter, NexusError> {
+        let stopped = self.stopped();
+        let Self {
+            commands, finished, ..
+        } = self;
+        drop(commands);
+        finished.await.map_err(|_| stopped)
+    }
+
+    /// Waits for queued events to be written, then closes the log.
+    pub async fn close(self) -> Result<(), NexusError> {
+        drop(self.into_inner().await?);
+        Ok(())
+    }
+
+    fn send(&self, command: Command) -> Result<(), NexusError> {
+        self.commands.send(command).map_err(|_| self.stopped())
+    }
+
+    fn stopped(&self) -> NexusError {
+        NexusError::IoError {
+            operation: "write event".to_string(),
+            path: self.path.clone(),
+            source: std::io::Error::new(
+                std::io::ErrorKind::BrokenPipe,
+                "event log writer thread stopped",
+            ),
+        }
+    }
+}
+
+/// Serves commands until every sender is gone, flushing whenever the queue
+/// runs dry, and hands the writer back.
+fn run(
+    mut writer: EventLogWriter,
+    mut commands: mpsc::UnboundedReceiver<Command>,
+) -> EventLogWriter {
+    while let Some(mut command) = commands.blocking_recv() {
+        loop {
+            match command {
+                Command::Append(event, reply) => {
+                    let _ = reply.send(writer.append(&event));
+                }
+                Command::AppendAll(events, reply) => {
+                    let _ = reply.send(writer.append_all(&events));
+                }
+                Command::Sync(reply) => {
+                    let _ = reply.send(writer.sync());
+                }
+            }
+            match commands.try_recv() {
+                Ok(next) => command = next,
+                Err(_) => break,
+            }
+        }
+        if let Err(err) = writer.flush() {
+            log::warn!("failed to flush event log: {err}");
+        }
+    }
+    writer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_log::EventLogReader;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_async_writer_appends_in_order() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("run_1.jsonl");
+
+        let writer = AsyncEventLogWriter::open(&path).unwrap();
+        for event_type in ["run.started", "executor.started"] {
+            writer
+                .append(&RunEvent::new("run_1", event_type))
+                .await
+                .unwrap();
+        }
+        let proposed = vec![RunEvent::new("run_1", "action.proposed"); 2];
+        writer.append_all(&proposed).await.unwrap();
+        writer
+            .append(&RunEvent::new("run_1", "run.completed"))
+            .await
+            .unwrap();
+        writer.sync().await.unwrap();
+        let writer = writer.into_inner().await.unwrap();
+        assert_eq!(writer.next_seq(), 6);
+        drop(writer);
+
+        let events = EventLogReader::open(&path).unwrap().load_all().unwrap();
+        let types: Vec<_> = events.iter().map(|e| e.event_type.as_str()).collect();
+        assert_eq!(
+            types,
+            [
+                "run.started",
+                "executor.started",
+                "action.proposed",
+                "action.proposed",
+                "run.completed"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_events_are_flushed_without_sync() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("run_1.jsonl");
+
+        let writer = AsyncEventLogWriter::open(&path).unwrap();
+        writer
+            .append(&RunEvent::new("run_1", "run.started"))
+            .await
+            .unwrap();
+
+        let mut flushed = false;
+        for _ in 0..50 {
+            if std::fs::metadata(&path).unwrap().len() > 0 {
+                flushed = true;
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert!(flushed, "event should reach the file without sync");
+        writer.close().await.unwrap();
+    }
+}
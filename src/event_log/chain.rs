@@ -0,0 +1,255 @@
+//! Hash chain making edits to an event log detectable.
+//!
+//! Every event the writer appends carries `prev_hash`, the `event_hash` of
+//! the event before it (or [`GENESIS_HASH`] for the first), and its own
+//! `event_hash`: the SHA-256 of `prev_hash` followed by the event in
+//! canonical JSON (keys sorted, no whitespace), `event_hash` left out.
+//! Changing, removing, or reordering an event breaks the chain from that
+//! point on. Logs written before chaining have no hashes; they are reported
+//! as unchained rather than tampered with.
+
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use super::{EventLogPath, EventLogReader};
+use crate::error::NexusError;
+
+/// `prev_hash` of the first event of a chain.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+pub(crate) const PREV_HASH_FIELD: &str = "prev_hash";
+pub(crate) const EVENT_HASH_FIELD: &str = "event_hash";
+
+/// Hash of `event`, which must already hold its `prev_hash`.
+pub(crate) fn event_hash(event: &serde_json::Map<String, Value>) -> String {
+    let mut canonical = String::new();
+    for (key, value) in sorted(event) {
+        if key != EVENT_HASH_FIELD {
+            push_field(&mut canonical, key, value);
+        }
+    }
+    let prev_hash = event
+        .get(PREV_HASH_FIELD)
+        .and_then(Value::as_str)
+        .unwrap_or(GENESIS_HASH);
+    let mut hasher = Sha256::new();
+    hasher.update(prev_hash.as_bytes());
+    hasher.update(format!("{{{canonical}}}").as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+fn sorted(object: &serde_json::Map<String, Value>) -> Vec<(&String, &Value)> {
+    let mut fields: Vec<_> = object.iter().collect();
+    fields.sort_by(|a, b| a.0.cmp(b.0));
+    fields
+}
+
+fn push_field(out: &mut String, key: &str, value: &Value) {
+    if !out.is_empty() {
+        out.push(',');
+    }
+    out.push_str(&Value::String(key.to_string()).to_string());
+    out.push(':');
+    push_canonical(out, value);
+}
+
+fn push_canonical(out: &mut String, value: &Value) {
+    match value {
+        Value::Object(object) => {
+            let mut fields = String::new();
+            for (key, value) in sorted(object) {
+                push_field(&mut fields, key, value);
+            }
+            out.push('{');
+            out.push_str(&fields);
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                push_canonical(out, item);
+            }
+            out.push(']');
+        }
+        scalar => out.push_str(&scalar.to_string()),
+    }
+}
+
+/// Checks the hash chain of the log of `run_id` under `project_root`.
+pub fn verify_run(project_root: &Path, run_id: &str) -> Result<ChainReport, NexusError> {
+    EventLogReader::open(&EventLogPath::new(project_root).for_run(run_id)?)?.verify_chain()
+}
+
+/// Where a chain stops holding.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChainBreak {
+    /// Line of the first event that does not fit the chain.
+    pub line: usize,
+    pub reason: String,
+}
+
+/// Result of checking a log's hash chain.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ChainReport {
+    /// Events whose hashes check out.
+    pub verified: usize,
+    /// Events at the start of the log written without hashes.
+    pub unchained: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub broken: Option<ChainBreak>,
+}
+
+impl ChainReport {
+    pub fn is_intact(&self) -> bool {
+        self.broken.is_none()
+    }
+}
+
+/// Follows a chain one event at a time.
+#[derive(Debug, Default)]
+pub(crate) struct ChainVerifier {
+    report: ChainReport,
+    last_hash: Option<String>,
+}
+
+impl ChainVerifier {
+    /// Checks the event on `line`; after the first break, later events are
+    /// not checked.
+    pub(crate) fn check(&mut self, line: usize, event: &serde_json::Map<String, Value>) {
+        if self.report.broken.is_some() {
+            return;
+        }
+        let stored = event.get(EVENT_HASH_FIELD).and_then(Value::as_str);
+        let prev = event.get(PREV_HASH_FIELD).and_then(Value::as_str);
+        let (Some(stored), Some(prev)) = (stored, prev) else {
+            if self.last_hash.is_some() {
+                self.fail(line, "event has no hash after chained events");
+            } else {
+                self.report.unchained += 1;
+            }
+            return;
+        };
+        let expected_prev = self.last_hash.as_deref().unwrap_or(GENESIS_HASH);
+        if prev != expected_prev {
+            self.fail(line, "prev_hash does not match the previous event");
+        } else if event_hash(event) != stored {
+            self.fail(line, "event_hash does not match the event's contents");
+        } else {
+            self.report.verified += 1;
+            self.last_hash = Some(stored.to_string());
+        }
+    }
+
+    pub(crate) fn malformed(&mut self, line: usize) {
+        if self.report.broken.is_none() {
+            self.fail(line, "line is not a JSON event");
+        }
+    }
+
+    fn fail(&mut self, line: usize, reason: &str) {
+        self.report.broken = Some(ChainBreak {
+            line,
+            reason: reason.to_string(),
+        });
+    }
+
+    pub(crate) fn finish(self) -> ChainReport {
+        self.report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn object(value: Value) -> serde_json::Map<String, Value> {
+        match value {
+            Value::Object(object) => object,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_hash_ignores_key_order() {
+        let a = object(json!({"type": "x", "payload": {"b": 1, "a": [1, {"d": 2, "c": 3}]}}));
+        let b = object(json!({"payload": {"a": [1, {"c": 3, "d": 2}], "b": 1}, "type": "x"}));
+        assert_eq!(event_hash(&a), event_hash(&b));
+
+        let mut hashed = a.clone();
+        hashed.insert(EVENT_HASH_FIELD.to_string(), json!("ignored"));
+        assert_eq!(event_hash(&hashed), event_hash(&a));
+    }
+
+    fn write_log(path: &Path, types: &[&str]) {
+        let mut writer = crate::event_log::EventLogWriter::open(path).unwrap();
+        for &event_type in types {
+            let event = crate::types::RunEvent::new("run_1", event_type)
+                .with_payload(json!({"n": 1.5, "text": "caf\u{e9}"}));
+            writer.append(&event).unwrap();
+        }
+        writer.sync().unwrap();
+    }
+
+    fn verify(path: &Path) -> ChainReport {
+        EventLogReader::open(path).unwrap().verify_chain().unwrap()
+    }
+
+    #[test]
+    fn test_written_log_verifies_across_reopen() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("run_1.jsonl");
+        write_log(&path, &["run.started", "executor.started"]);
+        write_log(&path, &["run.completed"]);
+
+        let report = verify(&path);
+        assert!(report.is_intact());
+        assert_eq!(report.verified, 3);
+        assert_eq!(report.unchained, 0);
+    }
+
+    #[test]
+    fn test_edits_break_the_chain() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("run_1.jsonl");
+        write_log(&path, &["run.started", "executor.started", "run.completed"]);
+        let original = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = original.lines().collect();
+
+        std::fs::write(
+            &path,
+            original.replace("executor.started", "executor.failed"),
+        )
+        .unwrap();
+        let edited = verify(&path).broken.unwrap();
+        assert_eq!(edited.line, 2);
+        assert!(edited.reason.contains("event_hash"));
+
+        std::fs::write(&path, format!("{}\n{}\n", lines[0], lines[2])).unwrap();
+        let removed = verify(&path).broken.unwrap();
+        assert_eq!(removed.line, 2);
+        assert!(removed.reason.contains("prev_hash"));
+    }
+
+    #[test]
+    fn test_verifier_accepts_unchained_prefix_only() {
+        let mut first = object(json!({"type": "a", "prev_hash": GENESIS_HASH}));
+        first.insert(EVENT_HASH_FIELD.to_string(), json!(event_hash(&first)));
+
+        let mut verifier = ChainVerifier::default();
+        verifier.check(1, &object(json!({"type": "legacy"})));
+        verifier.check(2, &first);
+        verifier.check(3, &object(json!({"type": "legacy"})));
+        let report = verifier.finish();
+
+        assert_eq!(report.unchained, 1);
+        assert_eq!(report.verified, 1);
+        assert_eq!(report.broken.unwrap().line, 3);
+    }
+}
@@ -0,0 +1,142 @@
+//! Reading a log while its run is still writing it.
+//!
+//! [`EventLogReader`](super::EventLogReader) takes a shared lock, so it
+//! waits for the run's writer to finish. A follower reads without a lock
+//! instead, and polls for lines appended since it last looked. A line the
+//! writer has not finished yet is held back until its newline arrives.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use super::EventLogPath;
+use crate::error::NexusError;
+use crate::types::RunEvent;
+
+/// Polls a log for events as they are appended.
+pub struct EventLogFollower {
+    reader: BufReader<File>,
+    path: PathBuf,
+    /// Start of a line whose newline has not been written yet, kept as
+    /// bytes since it may end mid-character.
+    partial: Vec<u8>,
+}
+
+impl EventLogFollower {
+    /// Opens `path` to read from its first event.
+    ///
+    /// # Errors
+    /// - `NexusError::EventLogNotFound` if file doesn't exist
+    pub fn open(path: &Path) -> Result<Self, NexusError> {
+        if !path.exists() {
+            return Err(NexusError::EventLogNotFound(path.to_path_buf()));
+        }
+        let file = File::open(path).map_err(|e| NexusError::IoError {
+            operation: "open log file".to_string(),
+            path: path.to_path_buf(),
+            source: e,
+        })?;
+        Ok(Self {
+            reader: BufReader::new(file),
+            path: path.to_path_buf(),
+            partial: Vec::new(),
+        })
+    }
+
+    /// Opens the log of `run_id` under `project_root`.
+    pub fn open_for_run(project_root: &Path, run_id: &str) -> Result<Self, NexusError> {
+        Self::open(&EventLogPath::new(project_root).for_run(run_id)?)
+    }
+
+    /// Opens `path` to read only events appended from now on.
+    pub fn open_at_end(path: &Path) -> Result<Self, NexusError> {
+        let mut follower = Self::open(path)?;
+        follower
+            .reader
+            .seek(SeekFrom::End(0))
+            .map_err(|e| NexusError::IoError {
+                operation: "seek log file".to_string(),
+                path: path.to_path_buf(),
+                source: e,
+            })?;
+        Ok(follower)
+    }
+
+    /// Returns the events completed since the last poll, possibly none.
+    ///
+    /// Malformed lines are skipped with a warning to stderr, as
+    /// [`EventLogReader::load_all`](super::EventLogReader::load_all) does.
+    pub fn poll(&mut self) -> Result<Vec<RunEvent>, NexusError> {
+        let mut events = Vec::new();
+        loop {
+            let read = self
+                .reader
+                .read_until(b'\n', &mut self.partial)
+                .map_err(|e| NexusError::IoError {
+                    operation: "read line".to_string(),
+                    path: self.path.clone(),
+                    source: e,
+                })?;
+            if read == 0 || self.partial.last() != Some(&b'\n') {
+                return Ok(events);
+            }
+            let line = std::mem::take(&mut self.partial);
+            if line.trim_ascii().is_empty() {
+                continue;
+            }
+            match serde_json::from_slice::<RunEvent>(&line) {
+                Ok(event) => events.push(event),
+                Err(e) => eprintln!("Warning: skipping malformed event: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    const STARTED: &str =
+        r#"{"v":"nexus/1","run_id":"run_1","type":"run.started","time":"2026-01-08T12:00:00Z"}"#;
+    const COMPLETED: &str =
+        r#"{"v":"nexus/1","run_id":"run_1","type":"run.completed","time":"2026-01-08T12:00:01Z"}"#;
+
+    #[test]
+    fn test_follower_waits_for_complete_lines() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("run_1.jsonl");
+        std::fs::write(&path, format!("{STARTED}\n")).unwrap();
+
+        let mut follower = EventLogFollower::open_at_end(&path).unwrap();
+        assert!(follower.poll().unwrap().is_empty());
+
+        let mut log = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        let (head, tail) = COMPLETED.split_at(20);
+        log.write_all(head.as_bytes()).unwrap();
+        assert!(follower.poll().unwrap().is_empty());
+
+        log.write_all(format!("{tail}\n").as_bytes()).unwrap();
+        let events = follower.poll().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_type, "run.completed");
+    }
+
+    #[test]
+    fn test_follower_reads_while_writer_holds_lock() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("run_1.jsonl");
+        let mut writer = crate::event_log::EventLogWriter::open(&path).unwrap();
+        writer
+            .append(&RunEvent::new("run_1", "run.started"))
+            .unwrap();
+        writer.sync().unwrap();
+
+        let mut follower = EventLogFollower::open(&path).unwrap();
+        assert_eq!(follower.poll().unwrap().len(), 1);
+    }
+}
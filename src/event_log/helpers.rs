@@ -3,9 +3,14 @@
 //! These helpers centralize event type strings and payload shapes so
 //! callers can emit consistent, schema-aligned `RunEvent` records.
 
+use std::path::Path;
+
 use serde_json::json;
 
-use crate::types::{Actor, AgentRole, RunEvent};
+use crate::error::exit_codes;
+use crate::executor::{OmittedFile, UsageInfo};
+use crate::review::ActionReview;
+use crate::types::{Actor, AgentRole, PatchMode, RunEvent, RunEventKind, RunStatus};
 
 fn tool_actor() -> Actor {
     Actor {
@@ -15,6 +20,14 @@ fn tool_actor() -> Actor {
     }
 }
 
+fn agent_actor(role: &AgentRole) -> Actor {
+    Actor {
+        agent: Some(role.clone()),
+        provider: None,
+        model: None,
+    }
+}
+
 fn default_executor_actor() -> Actor {
     Actor {
         agent: Some(AgentRole::Executor),
@@ -25,16 +38,136 @@ fn default_executor_actor() -> Actor {
 
 /// Creates run.started event.
 pub fn run_started(run_id: &str, task: &str) -> RunEvent {
-    RunEvent::new(run_id, "run.started")
+    RunEvent::new(run_id, RunEventKind::RunStarted)
         .with_actor(tool_actor())
         .with_payload(json!({"task": task}))
 }
 
+/// Creates run.started event for a re-run of `retry_of`.
+pub fn run_started_retry(run_id: &str, task: &str, retry_of: &str) -> RunEvent {
+    RunEvent::new(run_id, RunEventKind::RunStarted)
+        .with_actor(tool_actor())
+        .with_payload(json!({"task": task, "retry_of": retry_of}))
+}
+
+/// Creates run.started event for a run refining the proposals of
+/// `refine_of`.
+pub fn run_started_refine(run_id: &str, task: &str, refine_of: &str) -> RunEvent {
+    RunEvent::new(run_id, RunEventKind::RunStarted)
+        .with_actor(tool_actor())
+        .with_payload(json!({"task": task, "refine_of": refine_of}))
+}
+
+/// Creates run.reverted event for an applied action whose `files` were
+/// restored to their prior contents.
+pub fn run_reverted(run_id: &str, action_id: &str, files: Vec<String>) -> RunEvent {
+    RunEvent::new(run_id, RunEventKind::RunReverted)
+        .with_actor(tool_actor())
+        .with_payload(json!({"action_id": action_id, "files_restored": files}))
+}
+
+/// Creates handoff.requested event for a handoff action passing the run
+/// from the `from` agent to the `to` agent.
+pub fn handoff_requested(
+    run_id: &str,
+    action_id: &str,
+    from: &AgentRole,
+    to: &AgentRole,
+    reason: &str,
+) -> RunEvent {
+    RunEvent::new(run_id, RunEventKind::HandoffRequested)
+        .with_actor(agent_actor(from))
+        .with_payload(json!({
+            "action_id": action_id,
+            "from": from,
+            "to": to,
+            "reason": reason
+        }))
+}
+
+/// Creates handoff.accepted event once the `to` agent takes over the run.
+pub fn handoff_accepted(run_id: &str, action_id: &str, to: &AgentRole) -> RunEvent {
+    RunEvent::new(run_id, RunEventKind::HandoffAccepted)
+        .with_actor(agent_actor(to))
+        .with_payload(json!({"action_id": action_id, "to": to}))
+}
+
+/// Creates plan.created event for a plan of `steps` steps; attach the
+/// stored plan with `with_payload_ref`.
+pub fn plan_created(run_id: &str, steps: usize) -> RunEvent {
+    RunEvent::new(run_id, RunEventKind::PlanCreated)
+        .with_actor(agent_actor(&AgentRole::Planner))
+        .with_payload(json!({"steps": steps}))
+}
+
+/// Creates plan.step.completed event once the executor is done with step
+/// `step` (from 1) of the plan.
+pub fn plan_step_completed(
+    run_id: &str,
+    step: usize,
+    title: &str,
+    action_count: usize,
+) -> RunEvent {
+    RunEvent::new(run_id, RunEventKind::PlanStepCompleted)
+        .with_actor(default_executor_actor())
+        .with_payload(json!({
+            "step": step,
+            "title": title,
+            "action_count": action_count
+        }))
+}
+
+/// Creates plan.patched event once the action `action_id` replaced or
+/// patched plan `plan_id`, leaving it with `steps` steps.
+pub fn plan_patched(
+    run_id: &str,
+    action_id: &str,
+    plan_id: &str,
+    mode: &PatchMode,
+    steps: usize,
+) -> RunEvent {
+    RunEvent::new(run_id, RunEventKind::PlanPatched)
+        .with_actor(tool_actor())
+        .with_payload(json!({
+            "action_id": action_id,
+            "plan_id": plan_id,
+            "patch_mode": mode,
+            "steps": steps
+        }))
+}
+
 /// Creates run.completed event.
-pub fn run_completed(run_id: &str, status: &str, actions_applied: u32) -> RunEvent {
-    RunEvent::new(run_id, "run.completed")
+pub fn run_completed(run_id: &str, status: RunStatus, actions_applied: u32) -> RunEvent {
+    RunEvent::new(run_id, RunEventKind::RunCompleted)
+        .with_actor(tool_actor())
+        .with_payload(json!({
+            "status": status.as_str(),
+            "exit_code": status.exit_code(),
+            "actions_applied": actions_applied
+        }))
+}
+
+/// Creates run.cancelled event, the terminal event for an interrupted run.
+pub fn run_cancelled(run_id: &str, reason: &str) -> RunEvent {
+    RunEvent::new(run_id, RunEventKind::RunCancelled)
+        .with_actor(tool_actor())
+        .with_payload(json!({
+            "status": RunStatus::Cancelled.as_str(),
+            "exit_code": RunStatus::Cancelled.exit_code(),
+            "reason": reason
+        }))
+}
+
+/// Creates run.budget_exceeded event.
+pub fn run_budget_exceeded(run_id: &str, limit: &str, spent: &str) -> RunEvent {
+    RunEvent::new(run_id, RunEventKind::RunBudgetExceeded)
         .with_actor(tool_actor())
-        .with_payload(json!({"status": status, "actions_applied": actions_applied}))
+        .with_payload(json!({
+            "status": "budget_exceeded",
+            "exit_code": exit_codes::BUDGET_EXCEEDED,
+            "limit": limit,
+            "spent": spent
+        }))
 }
 
 /// Creates action.proposed event.
@@ -46,7 +179,7 @@ pub fn action_proposed(
     actor: Option<Actor>,
 ) -> RunEvent {
     let actor = actor.unwrap_or_else(default_executor_actor);
-    RunEvent::new(run_id, "action.proposed")
+    RunEvent::new(run_id, RunEventKind::ActionProposed)
         .with_actor(actor)
         .with_payload(json!({
             "action_id": action_id,
@@ -55,23 +188,78 @@ pub fn action_proposed(
         }))
 }
 
+/// Creates action.validated event recording whether the patch of
+/// `action_id` applies to the current files, and if not, where it fails.
+pub fn action_validated(
+    run_id: &str,
+    action_id: &str,
+    applies_cleanly: bool,
+    failing_hunk: Option<&str>,
+) -> RunEvent {
+    let mut payload = json!({"action_id": action_id, "applies_cleanly": applies_cleanly});
+    if let Some(failing_hunk) = failing_hunk {
+        payload["failing_hunk"] = json!(failing_hunk);
+    }
+    RunEvent::new(run_id, RunEventKind::ActionValidated)
+        .with_actor(tool_actor())
+        .with_payload(payload)
+}
+
+/// Creates action.reviewed event recording the reviewer's `review` of
+/// `action_id`.
+pub fn action_reviewed(run_id: &str, action_id: &str, review: &ActionReview) -> RunEvent {
+    RunEvent::new(run_id, RunEventKind::ActionReviewed)
+        .with_actor(agent_actor(&AgentRole::Reviewer))
+        .with_payload(json!({
+            "action_id": action_id,
+            "confidence": review.confidence,
+            "concerns": review.concerns
+        }))
+}
+
 /// Creates permission.granted event.
 pub fn permission_granted(run_id: &str, action_id: &str, scope: &str) -> RunEvent {
-    RunEvent::new(run_id, "permission.granted")
+    RunEvent::new(run_id, RunEventKind::PermissionGranted)
         .with_actor(tool_actor())
         .with_payload(json!({"action_id": action_id, "scope": scope}))
 }
 
+/// Creates permission.granted event for the actions `action_ids` of the
+/// approval group `group_id`, approved in one decision.
+pub fn permission_granted_group(
+    run_id: &str,
+    group_id: &str,
+    action_ids: &[String],
+    scope: &str,
+) -> RunEvent {
+    RunEvent::new(run_id, RunEventKind::PermissionGranted)
+        .with_actor(tool_actor())
+        .with_payload(json!({"group_id": group_id, "action_ids": action_ids, "scope": scope}))
+}
+
+/// Creates permission.denied event for the actions `action_ids` of the
+/// approval group `group_id`, rejected in one decision.
+pub fn permission_denied_group(
+    run_id: &str,
+    group_id: &str,
+    action_ids: &[String],
+    reason: &str,
+) -> RunEvent {
+    RunEvent::new(run_id, RunEventKind::PermissionDenied)
+        .with_actor(tool_actor())
+        .with_payload(json!({"group_id": group_id, "action_ids": action_ids, "reason": reason}))
+}
+
 /// Creates permission.denied event.
 pub fn permission_denied(run_id: &str, action_id: &str, reason: &str) -> RunEvent {
-    RunEvent::new(run_id, "permission.denied")
+    RunEvent::new(run_id, RunEventKind::PermissionDenied)
         .with_actor(tool_actor())
         .with_payload(json!({"action_id": action_id, "reason": reason}))
 }
 
 /// Creates tool.executed event (success).
 pub fn tool_executed(run_id: &str, action_id: &str, files_modified: Vec<String>) -> RunEvent {
-    RunEvent::new(run_id, "tool.executed")
+    RunEvent::new(run_id, RunEventKind::ToolExecuted)
         .with_actor(tool_actor())
         .with_payload(json!({
             "action_id": action_id,
@@ -82,31 +270,111 @@ pub fn tool_executed(run_id: &str, action_id: &str, files_modified: Vec<String>)
 
 /// Creates tool.failed event.
 pub fn tool_failed(run_id: &str, action_id: &str, error: &str) -> RunEvent {
-    RunEvent::new(run_id, "tool.failed")
+    RunEvent::new(run_id, RunEventKind::ToolFailed)
         .with_actor(tool_actor())
         .with_payload(json!({"action_id": action_id, "success": false, "error": error}))
 }
 
-/// Creates executor.started event.
-pub fn executor_started(run_id: &str, task: &str, file_count: usize, model: &str) -> RunEvent {
+/// Creates tool.executed event for a command action that exited successfully.
+pub fn command_executed(
+    run_id: &str,
+    action_id: &str,
+    exit_code: i32,
+    duration_ms: u128,
+) -> RunEvent {
+    RunEvent::new(run_id, RunEventKind::ToolExecuted)
+        .with_actor(tool_actor())
+        .with_payload(json!({
+            "action_id": action_id,
+            "success": true,
+            "exit_code": exit_code,
+            "duration_ms": duration_ms
+        }))
+}
+
+/// Creates tool.failed event for a command action that exited non-zero,
+/// timed out, or was killed (`exit_code` is then `None`).
+pub fn command_failed(
+    run_id: &str,
+    action_id: &str,
+    exit_code: Option<i32>,
+    error: &str,
+) -> RunEvent {
+    RunEvent::new(run_id, RunEventKind::ToolFailed)
+        .with_actor(tool_actor())
+        .with_payload(json!({
+            "action_id": action_id,
+            "success": false,
+            "exit_code": exit_code,
+            "error": error
+        }))
+}
+
+/// Creates conflict.resolved event recording how a conflicting patch was handled.
+pub fn conflict_resolved(
+    run_id: &str,
+    action_id: &str,
+    resolution: &str,
+    applied: bool,
+) -> RunEvent {
+    RunEvent::new(run_id, RunEventKind::ConflictResolved)
+        .with_actor(tool_actor())
+        .with_payload(json!({
+            "action_id": action_id,
+            "resolution": resolution,
+            "applied": applied
+        }))
+}
+
+/// Creates verification.completed event for a named check.
+pub fn verification_completed(
+    run_id: &str,
+    name: &str,
+    command: &[String],
+    success: bool,
+    exit_code: Option<i32>,
+) -> RunEvent {
+    RunEvent::new(run_id, RunEventKind::VerificationCompleted)
+        .with_actor(tool_actor())
+        .with_payload(json!({
+            "name": name,
+            "command": command.join(" "),
+            "success": success,
+            "exit_code": exit_code
+        }))
+}
+
+/// Creates executor.started event listing the context files sent.
+pub fn executor_started(run_id: &str, task: &str, files: &[String], model: &str) -> RunEvent {
     let actor = Actor {
         agent: Some(AgentRole::Executor),
         provider: Some("openai".to_string()),
         model: Some(model.to_string()),
     };
 
-    RunEvent::new(run_id, "executor.started")
+    RunEvent::new(run_id, RunEventKind::ExecutorStarted)
         .with_actor(actor)
         .with_payload(json!({
             "task": task,
-            "file_count": file_count,
+            "file_count": files.len(),
+            "files": files,
             "model": model
         }))
 }
 
+/// Creates context.truncated event for files cut to fit the context budget.
+pub fn context_truncated(run_id: &str, budget: usize, files: &[OmittedFile]) -> RunEvent {
+    RunEvent::new(run_id, RunEventKind::ContextTruncated)
+        .with_actor(default_executor_actor())
+        .with_payload(json!({
+            "budget": budget,
+            "files": files
+        }))
+}
+
 /// Creates executor.streaming event.
 pub fn executor_streaming(run_id: &str, chunk_size: usize, total_chars: usize) -> RunEvent {
-    RunEvent::new(run_id, "executor.streaming")
+    RunEvent::new(run_id, RunEventKind::ExecutorStreaming)
         .with_actor(default_executor_actor())
         .with_payload(json!({
             "chunk_size": chunk_size,
@@ -116,7 +384,7 @@ pub fn executor_streaming(run_id: &str, chunk_size: usize, total_chars: usize) -
 
 /// Creates executor.completed event.
 pub fn executor_completed(run_id: &str, action_count: usize, duration_ms: u128) -> RunEvent {
-    RunEvent::new(run_id, "executor.completed")
+    RunEvent::new(run_id, RunEventKind::ExecutorCompleted)
         .with_actor(default_executor_actor())
         .with_payload(json!({
             "action_count": action_count,
@@ -125,6 +393,23 @@ pub fn executor_completed(run_id: &str, action_count: usize, duration_ms: u128)
         }))
 }
 
+/// Adds token usage, and its estimated cost if the model is priced, to an
+/// executor.completed event.
+pub fn with_usage(mut event: RunEvent, usage: &UsageInfo, cost_usd: Option<f64>) -> RunEvent {
+    let mut usage = json!({
+        "prompt_tokens": usage.prompt_tokens,
+        "completion_tokens": usage.completion_tokens,
+        "total_tokens": usage.total_tokens,
+    });
+    if let (Some(cost_usd), Some(usage)) = (cost_usd, usage.as_object_mut()) {
+        usage.insert("cost_usd".to_string(), json!(cost_usd));
+    }
+    if let Some(payload) = event.payload.as_mut().and_then(|p| p.as_object_mut()) {
+        payload.insert("usage".to_string(), usage);
+    }
+    event
+}
+
 /// Creates executor.failed event.
 pub fn executor_failed(run_id: &str, error: &str, status_code: Option<u16>) -> RunEvent {
     let mut payload = json!({
@@ -138,11 +423,40 @@ pub fn executor_failed(run_id: &str, error: &str, status_code: Option<u16>) -> R
         }
     }
 
-    RunEvent::new(run_id, "executor.failed")
+    RunEvent::new(run_id, RunEventKind::ExecutorFailed)
         .with_actor(default_executor_actor())
         .with_payload(payload)
 }
 
+/// Creates log.recovered event for a partial last line, left by an
+/// interrupted append, that was cut from the log at `offset` and saved to
+/// `backup`.
+pub fn log_recovered(run_id: &str, offset: u64, truncated_bytes: usize, backup: &Path) -> RunEvent {
+    RunEvent::new(run_id, RunEventKind::LogRecovered)
+        .with_actor(tool_actor())
+        .with_payload(json!({
+            "offset": offset,
+            "truncated_bytes": truncated_bytes,
+            "backup": backup.display().to_string()
+        }))
+}
+
+/// Creates git.branch_created event for the branch a run's changes are
+/// applied on, created from `base` (`None` on a detached HEAD).
+pub fn git_branch_created(run_id: &str, branch: &str, base: Option<&str>) -> RunEvent {
+    RunEvent::new(run_id, RunEventKind::GitBranchCreated)
+        .with_actor(tool_actor())
+        .with_payload(json!({"branch": branch, "base": base}))
+}
+
+/// Creates git.committed event for the commit holding an applied action's
+/// changes.
+pub fn git_committed(run_id: &str, action_id: &str, commit: &str) -> RunEvent {
+    RunEvent::new(run_id, RunEventKind::GitCommitted)
+        .with_actor(tool_actor())
+        .with_payload(json!({"action_id": action_id, "commit": commit}))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -168,7 +482,7 @@ mod tests {
 
     #[test]
     fn test_helper_run_completed() {
-        let event = run_completed("run_001", "success", 2);
+        let event = run_completed("run_001", RunStatus::Success, 2);
         assert_eq!(event.event_type, "run.completed");
 
         let actor = event.actor.as_ref().expect("actor should be set");
@@ -176,7 +490,65 @@ mod tests {
 
         assert_eq!(
             event.payload,
-            Some(json!({"status": "success", "actions_applied": 2}))
+            Some(json!({"status": "success", "exit_code": 0, "actions_applied": 2}))
+        );
+    }
+
+    #[test]
+    fn test_helper_command_events_record_exit_status() {
+        let event = command_executed("run_001", "act_001", 0, 42);
+        assert_eq!(event.event_type, "tool.executed");
+        assert_eq!(
+            event.payload,
+            Some(json!({
+                "action_id": "act_001",
+                "success": true,
+                "exit_code": 0,
+                "duration_ms": 42
+            }))
+        );
+
+        let event = command_failed("run_001", "act_001", None, "timed out after 5s");
+        assert_eq!(event.event_type, "tool.failed");
+        assert_tool_actor(event.actor.as_ref().expect("actor should be set"));
+        assert_eq!(
+            event.payload,
+            Some(json!({
+                "action_id": "act_001",
+                "success": false,
+                "exit_code": null,
+                "error": "timed out after 5s"
+            }))
+        );
+    }
+
+    #[test]
+    fn test_helper_run_started_retry() {
+        let event = run_started_retry("run_002", "rename function", "run_001");
+        assert_eq!(event.event_type, "run.started");
+        assert_eq!(
+            event.payload,
+            Some(json!({"task": "rename function", "retry_of": "run_001"}))
+        );
+
+        let event = run_started_refine("run_003", "also update the tests", "run_002");
+        assert_eq!(
+            event.payload,
+            Some(json!({"task": "also update the tests", "refine_of": "run_002"}))
+        );
+    }
+
+    #[test]
+    fn test_helper_executor_started_lists_files() {
+        let event = executor_started("run_001", "task", &["src/a.rs".to_string()], "gpt-test");
+        assert_eq!(
+            event.payload,
+            Some(json!({
+                "task": "task",
+                "file_count": 1,
+                "files": ["src/a.rs"],
+                "model": "gpt-test"
+            }))
         );
     }
 
@@ -222,6 +594,20 @@ mod tests {
         assert!(actor.model.is_none());
     }
 
+    #[test]
+    fn test_helper_run_cancelled() {
+        let event = run_cancelled("run_001", "interrupted by user");
+        assert_eq!(event.event_type, "run.cancelled");
+        assert_eq!(
+            event.payload,
+            Some(json!({
+                "status": "cancelled",
+                "exit_code": 130,
+                "reason": "interrupted by user"
+            }))
+        );
+    }
+
     #[test]
     fn test_helper_permission_granted() {
         let event = permission_granted("run_001", "act_001", "once");
@@ -296,6 +682,52 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_helper_verification_completed() {
+        let command = vec!["cargo".to_string(), "deny".to_string(), "check".to_string()];
+        let event = verification_completed("run_001", "deps audit", &command, false, Some(1));
+        assert_eq!(event.event_type, "verification.completed");
+        assert_tool_actor(event.actor.as_ref().expect("actor should be set"));
+        assert_eq!(
+            event.payload,
+            Some(json!({
+                "name": "deps audit",
+                "command": "cargo deny check",
+                "success": false,
+                "exit_code": 1
+            }))
+        );
+    }
+
+    #[test]
+    fn test_helper_conflict_resolved() {
+        let event = conflict_resolved("run_001", "act_001", "fuzzy", true);
+        assert_eq!(event.event_type, "conflict.resolved");
+        assert_tool_actor(event.actor.as_ref().expect("actor should be set"));
+        assert_eq!(
+            event.payload,
+            Some(json!({"action_id": "act_001", "resolution": "fuzzy", "applied": true}))
+        );
+    }
+
+    #[test]
+    fn test_helper_context_truncated() {
+        let files = [OmittedFile {
+            path: "src/big.rs".to_string(),
+            tokens: 900,
+            kept_tokens: 0,
+        }];
+        let event = context_truncated("run_001", 500, &files);
+        assert_eq!(event.event_type, "context.truncated");
+        assert_eq!(
+            event.payload,
+            Some(json!({
+                "budget": 500,
+                "files": [{"path": "src/big.rs", "tokens": 900, "kept_tokens": 0}]
+            }))
+        );
+    }
+
     #[test]
     fn test_helper_round_trip_serialization() {
         let event = action_proposed("run_003", "act_003", "patch", "Round trip", None);
@@ -0,0 +1,464 @@
+//! Index of the runs recorded under `.nexus/runs/`.
+//!
+//! Finding a run by reading every log is O(all history). Instead,
+//! [`EventLogWriter`](super::EventLogWriter) appends a record to
+//! `index.jsonl` beside the log when a run starts and when it ends, noting
+//! the time, the final status, and the byte offset of the event. Records are
+//! only ever appended; [`EventLogIndex::load`] folds them into one entry per
+//! run. Logs written before the index existed are picked up by
+//! [`EventLogIndex::rebuild`].
+
+use std::collections::{HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+
+use super::{EventLogPath, EventLogReader};
+use crate::error::NexusError;
+use crate::types::{RunEvent, RunEventKind};
+
+/// File name of the index, in the same directory as the logs.
+pub const INDEX_FILE_NAME: &str = "index.jsonl";
+
+/// Whether an index record marks the start or the end of a run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum IndexMark {
+    Started,
+    Ended,
+}
+
+/// One line of `index.jsonl`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct IndexRecord {
+    pub run_id: String,
+    /// File name of the log, relative to the index.
+    pub log: String,
+    pub mark: IndexMark,
+    pub time: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    /// Byte offset of the event in the log.
+    pub offset: u64,
+    /// Run that spawned this one; set on start records only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_run_id: Option<String>,
+}
+
+impl IndexRecord {
+    /// The record `event`, written at `offset` of `log`, adds to the index:
+    /// a start for the first event of a run, an end for a terminal event.
+    pub(crate) fn for_event(event: &RunEvent, log: &str, offset: u64, first: bool) -> Option<Self> {
+        let (mark, status) = match end_status(event) {
+            Some(status) => (IndexMark::Ended, Some(status)),
+            None if first => (IndexMark::Started, None),
+            None => return None,
+        };
+        Some(Self {
+            run_id: event.run_id.clone(),
+            log: log.to_string(),
+            mark,
+            time: event.time,
+            status,
+            offset,
+            parent_run_id: match mark {
+                IndexMark::Started => event.parent_run_id.clone(),
+                IndexMark::Ended => None,
+            },
+        })
+    }
+}
+
+/// Final status recorded by `event`, if it ends a run.
+fn end_status(event: &RunEvent) -> Option<String> {
+    let payload_status = || {
+        event
+            .payload
+            .as_ref()
+            .and_then(|payload| payload.get("status")?.as_str())
+            .unwrap_or("unknown")
+            .to_string()
+    };
+    match event.event_type {
+        RunEventKind::RunCompleted
+        | RunEventKind::RunCancelled
+        | RunEventKind::RunBudgetExceeded => Some(payload_status()),
+        RunEventKind::ExecutorFailed => Some("failed".to_string()),
+        _ => None,
+    }
+}
+
+/// Whether `event` is the last a run records: completion, cancellation,
+/// an exceeded budget, or an executor failure.
+pub fn ends_run(event: &RunEvent) -> bool {
+    end_status(event).is_some()
+}
+
+/// Appends `record` to the index at `index_path`.
+///
+/// The lock is held only for the write, so concurrent runs can share the
+/// index.
+pub(crate) fn append_record(index_path: &Path, record: &IndexRecord) -> Result<(), NexusError> {
+    let io_error = |operation: &str, source| NexusError::IoError {
+        operation: operation.to_string(),
+        path: index_path.to_path_buf(),
+        source,
+    };
+    let mut line = serde_json::to_vec(record)?;
+    line.push(b'\n');
+    let mut file = OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(index_path)
+        .map_err(|e| io_error("open run index", e))?;
+    FileExt::lock_exclusive(&file).map_err(|e| io_error("lock run index", e))?;
+    file.write_all(&line)
+        .map_err(|e| io_error("write run index", e))
+}
+
+/// Where and when one run was recorded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RunIndexEntry {
+    pub run_id: String,
+    /// Log the run's events are in.
+    pub log: PathBuf,
+    pub started_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ended_at: Option<DateTime<Utc>>,
+    /// Final status, or `None` while the run is in progress.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    /// Byte offset of the run's first event in `log`.
+    pub start_offset: u64,
+    /// Byte offset of the event that ended the run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_offset: Option<u64>,
+    /// Run that spawned this one, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_run_id: Option<String>,
+}
+
+impl RunIndexEntry {
+    /// Reads the run's events, starting at its first one.
+    pub fn load_events(&self) -> Result<Vec<RunEvent>, NexusError> {
+        let mut reader = EventLogReader::open(&self.log)?;
+        reader.seek(self.start_offset)?;
+        Ok(reader
+            .load_all()?
+            .into_iter()
+            .filter(|event| event.run_id == self.run_id)
+            .collect())
+    }
+}
+
+/// Every run in a runs directory, in the order they started.
+#[derive(Debug, Clone, Default)]
+pub struct EventLogIndex {
+    entries: Vec<RunIndexEntry>,
+}
+
+impl EventLogIndex {
+    /// Loads the index of `.nexus/runs/` under `project_root`.
+    pub fn load_for_project(project_root: &Path) -> Result<Self, NexusError> {
+        Self::load(&EventLogPath::new(project_root).base_dir)
+    }
+
+    /// Loads the index of `runs_dir`, building it first if there is none.
+    pub fn load(runs_dir: &Path) -> Result<Self, NexusError> {
+        let index_path = runs_dir.join(INDEX_FILE_NAME);
+        if !index_path.exists() {
+            return Self::rebuild(runs_dir);
+        }
+        let file = File::open(&index_path).map_err(|e| NexusError::IoError {
+            operation: "open run index".to_string(),
+            path: index_path.clone(),
+            source: e,
+        })?;
+        FileExt::lock_shared(&file).map_err(|e| NexusError::IoError {
+            operation: "lock run index".to_string(),
+            path: index_path.clone(),
+            source: e,
+        })?;
+        let mut records = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(|e| NexusError::IoError {
+                operation: "read run index".to_string(),
+                path: index_path.clone(),
+                source: e,
+            })?;
+            // A torn last line from an interrupted write is not worth failing over.
+            if let Ok(record) = serde_json::from_str::<IndexRecord>(&line) {
+                records.push(record);
+            }
+        }
+        Ok(Self::from_records(runs_dir, records))
+    }
+
+    /// Rebuilds the index of `runs_dir` by scanning every log in it, and
+    /// replaces `index.jsonl` with the result.
+    pub fn rebuild(runs_dir: &Path) -> Result<Self, NexusError> {
+        let mut records = Vec::new();
+        let logs = match std::fs::read_dir(runs_dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => {
+                return Err(NexusError::IoError {
+                    operation: "read runs directory".to_string(),
+                    path: runs_dir.to_path_buf(),
+                    source: e,
+                });
+            }
+        };
+        let mut names: Vec<String> = logs
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .filter(|name| name.ends_with(".jsonl") && name != INDEX_FILE_NAME)
+            .collect();
+        names.sort();
+        for name in &names {
+            scan_log(&runs_dir.join(name), name, &mut records)?;
+        }
+
+        let index_path = runs_dir.join(INDEX_FILE_NAME);
+        let mut contents = Vec::new();
+        for record in &records {
+            serde_json::to_writer(&mut contents, record)?;
+            contents.push(b'\n');
+        }
+        let io_error = |operation: &str, source| NexusError::IoError {
+            operation: operation.to_string(),
+            path: index_path.clone(),
+            source,
+        };
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&index_path)
+            .map_err(|e| io_error("open run index", e))?;
+        FileExt::lock_exclusive(&file).map_err(|e| io_error("lock run index", e))?;
+        file.set_len(0)
+            .and_then(|()| file.write_all(&contents))
+            .map_err(|e| io_error("write run index", e))?;
+
+        Ok(Self::from_records(runs_dir, records))
+    }
+
+    fn from_records(runs_dir: &Path, records: Vec<IndexRecord>) -> Self {
+        let mut entries: Vec<RunIndexEntry> = Vec::new();
+        let mut positions: HashMap<String, usize> = HashMap::new();
+        for record in records {
+            let position = match positions.get(&record.run_id) {
+                Some(&position) => position,
+                None => {
+                    positions.insert(record.run_id.clone(), entries.len());
+                    entries.push(RunIndexEntry {
+                        run_id: record.run_id.clone(),
+                        log: runs_dir.join(&record.log),
+                        started_at: record.time,
+                        ended_at: None,
+                        status: None,
+                        start_offset: record.offset,
+                        end_offset: None,
+                        parent_run_id: None,
+                    });
+                    entries.len() - 1
+                }
+            };
+            let entry = &mut entries[position];
+            match record.mark {
+                IndexMark::Started => entry.parent_run_id = record.parent_run_id,
+                IndexMark::Ended => {
+                    entry.ended_at = Some(record.time);
+                    entry.status = record.status;
+                    entry.end_offset = Some(record.offset);
+                }
+            }
+        }
+        entries.sort_by_key(|entry| entry.started_at);
+        Self { entries }
+    }
+
+    pub fn get(&self, run_id: &str) -> Option<&RunIndexEntry> {
+        self.entries.iter().find(|entry| entry.run_id == run_id)
+    }
+
+    /// Runs spawned by `run_id`, in the order they started.
+    pub fn children(&self, run_id: &str) -> Vec<&RunIndexEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.parent_run_id.as_deref() == Some(run_id))
+            .collect()
+    }
+
+    pub fn entries(&self) -> &[RunIndexEntry] {
+        &self.entries
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Adds the index records of the log at `path` to `records`.
+///
+/// Reads without a lock so that a run in progress does not block the scan;
+/// a partly written last line is skipped.
+fn scan_log(path: &Path, name: &str, records: &mut Vec<IndexRecord>) -> Result<(), NexusError> {
+    let file = File::open(path).map_err(|e| NexusError::IoError {
+        operation: "open log file".to_string(),
+        path: path.to_path_buf(),
+        source: e,
+    })?;
+    let mut reader = BufReader::new(file);
+    let mut seen = HashSet::new();
+    let mut offset = 0u64;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let read = reader
+            .read_line(&mut line)
+            .map_err(|e| NexusError::IoError {
+                operation: "read line".to_string(),
+                path: path.to_path_buf(),
+                source: e,
+            })?;
+        if read == 0 {
+            return Ok(());
+        }
+        if let Ok(event) = serde_json::from_str::<RunEvent>(&line) {
+            let first = seen.insert(event.run_id.clone());
+            records.extend(IndexRecord::for_event(&event, name, offset, first));
+        }
+        offset += read as u64;
+    }
+}
+
+/// Events of `run_id` under `project_root`, and the directory of its log.
+///
+/// Found through the index when the run is in it, else read from
+/// `<run_id>.jsonl`.
+pub(crate) fn load_run_events(
+    project_root: &Path,
+    run_id: &str,
+) -> Result<(Vec<RunEvent>, PathBuf), NexusError> {
+    let paths = EventLogPath::new(project_root);
+    let log_path = paths.for_run(run_id)?;
+    let runs_dir = paths.base_dir;
+    if runs_dir.join(INDEX_FILE_NAME).exists() {
+        if let Some(entry) = EventLogIndex::load(&runs_dir)?.get(run_id) {
+            return Ok((entry.load_events()?, runs_dir));
+        }
+    }
+    let events = EventLogReader::open(&log_path)?.load_all()?;
+    Ok((events, runs_dir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_log::{EventLogWriter, helpers};
+    use crate::types::RunStatus;
+    use tempfile::TempDir;
+
+    fn write_run(runs_dir: &Path, run_id: &str, finish: bool) {
+        let mut writer = EventLogWriter::open(&runs_dir.join(format!("{run_id}.jsonl"))).unwrap();
+        writer
+            .append(&helpers::run_started(run_id, "task"))
+            .unwrap();
+        writer
+            .append(&helpers::executor_started(run_id, "task", &[], "gpt-4o"))
+            .unwrap();
+        if finish {
+            writer
+                .append(&helpers::run_completed(run_id, RunStatus::Success, 1))
+                .unwrap();
+        }
+        writer.sync().unwrap();
+    }
+
+    #[test]
+    fn test_writer_maintains_index() {
+        let dir = TempDir::new().unwrap();
+        write_run(dir.path(), "run_a", true);
+        write_run(dir.path(), "run_b", false);
+
+        let index = EventLogIndex::load(dir.path()).unwrap();
+        let ids: Vec<_> = index.entries().iter().map(|e| e.run_id.as_str()).collect();
+        assert_eq!(ids, ["run_a", "run_b"]);
+
+        let done = index.get("run_a").unwrap();
+        assert_eq!(done.status.as_deref(), Some("success"));
+        assert_eq!(done.start_offset, 0);
+        let log = std::fs::read_to_string(dir.path().join("run_a.jsonl")).unwrap();
+        let last_line = log.lines().last().unwrap();
+        assert_eq!(
+            done.end_offset,
+            Some((log.len() - last_line.len() - 1) as u64)
+        );
+
+        let running = index.get("run_b").unwrap();
+        assert_eq!(running.status, None);
+        assert_eq!(running.load_events().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_rebuild_matches_written_index() {
+        let dir = TempDir::new().unwrap();
+        write_run(dir.path(), "run_a", true);
+        write_run(dir.path(), "run_b", false);
+        let written = EventLogIndex::load(dir.path()).unwrap();
+
+        std::fs::remove_file(dir.path().join(INDEX_FILE_NAME)).unwrap();
+        let rebuilt = EventLogIndex::load(dir.path()).unwrap();
+
+        assert_eq!(rebuilt.entries(), written.entries());
+        assert!(dir.path().join(INDEX_FILE_NAME).exists());
+    }
+
+    #[test]
+    fn test_index_records_parent_run() {
+        let dir = TempDir::new().unwrap();
+        write_run(dir.path(), "run_a", true);
+        let mut writer = EventLogWriter::open(&dir.path().join("run_b.jsonl")).unwrap();
+        writer
+            .append(&helpers::run_started("run_b", "follow-up").with_parent_run("run_a"))
+            .unwrap();
+        writer.sync().unwrap();
+        drop(writer);
+
+        let index = EventLogIndex::load(dir.path()).unwrap();
+        let child = index.get("run_b").unwrap();
+        assert_eq!(child.parent_run_id.as_deref(), Some("run_a"));
+        let children: Vec<_> = index
+            .children("run_a")
+            .iter()
+            .map(|e| e.run_id.as_str())
+            .collect();
+        assert_eq!(children, ["run_b"]);
+
+        let rebuilt = EventLogIndex::rebuild(dir.path()).unwrap();
+        assert_eq!(rebuilt.entries(), index.entries());
+    }
+
+    #[test]
+    fn test_load_run_events_falls_back_to_log() {
+        let dir = TempDir::new().unwrap();
+        let runs_dir = dir.path().join(".nexus").join("runs");
+        std::fs::create_dir_all(&runs_dir).unwrap();
+        std::fs::write(
+            runs_dir.join("old_run.jsonl"),
+            "{\"v\":\"nexus/1\",\"run_id\":\"old_run\",\"type\":\"run.started\",\"time\":\"2026-01-08T12:00:00Z\"}\n",
+        )
+        .unwrap();
+        write_run(&runs_dir, "new_run", true);
+
+        let (events, _) = load_run_events(dir.path(), "old_run").unwrap();
+        assert_eq!(events.len(), 1);
+        let (events, _) = load_run_events(dir.path(), "new_run").unwrap();
+        assert_eq!(events.len(), 3);
+    }
+}
@@ -3,18 +3,35 @@
 //! Provides EventLogWriter and EventLogReader for recording
 //! and replaying run events.
 
+mod async_writer;
+pub mod chain;
+mod follow;
 pub mod helpers;
+mod index;
+pub mod payload;
 mod reader;
+pub mod trace;
 mod writer;
 
+pub use async_writer::AsyncEventLogWriter;
+pub use chain::{ChainBreak, ChainReport, verify_run};
+pub use follow::EventLogFollower;
 pub use helpers::*;
-pub use reader::EventLogReader;
+pub(crate) use index::load_run_events;
+pub use index::{EventLogIndex, INDEX_FILE_NAME, RunIndexEntry, ends_run};
+pub use payload::PayloadStore;
+pub use reader::{EventLogReader, ReverseEventIterator};
 pub use reader::{filter_by_run, filter_by_type};
-pub use writer::EventLogWriter;
+pub use trace::{RunTrace, TRACEPARENT_ENV};
+pub use writer::{DEFAULT_PAYLOAD_OFFLOAD_BYTES, EventLogWriter};
 
 use std::path::{Path, PathBuf};
 
 use crate::NexusError;
+use crate::types::{RunId, RunIdScheme};
+
+/// Attempts made to find an unused run ID before giving up.
+const MAX_RUN_ID_ATTEMPTS: usize = 8;
 
 /// Internal helper for managing event log file paths.
 /// Not exposed in public API.
@@ -33,37 +50,41 @@ impl EventLogPath {
         }
     }
 
+    /// Keeps logs directly in `runs_dir`, e.g. the directory of an open log.
+    pub fn in_dir(runs_dir: &Path) -> Self {
+        Self {
+            base_dir: runs_dir.to_path_buf(),
+        }
+    }
+
     /// Returns path to log file for given run_id.
     /// Validates run_id to prevent path traversal attacks.
     pub fn for_run(&self, run_id: &str) -> Result<PathBuf, NexusError> {
-        Self::validate_run_id(run_id)?;
+        let run_id = RunId::new(run_id)?;
         Ok(self.base_dir.join(format!("{}.jsonl", run_id)))
     }
 
+    /// Generates a run ID whose log file does not already exist.
+    ///
+    /// Regenerates on collision; fails after a bounded number of attempts
+    /// (only realistic with `RunIdScheme::Timestamp`).
+    pub fn allocate_run_id(&self, scheme: RunIdScheme) -> Result<String, NexusError> {
+        for _ in 0..MAX_RUN_ID_ATTEMPTS {
+            let run_id = scheme.generate();
+            if !self.for_run(&run_id)?.exists() {
+                return Ok(run_id);
+            }
+        }
+        Err(NexusError::InvalidRunId(format!(
+            "could not allocate an unused run_id after {} attempts",
+            MAX_RUN_ID_ATTEMPTS
+        )))
+    }
+
     /// Creates the runs directory if it doesn't exist.
     pub fn ensure_dir(&self) -> std::io::Result<()> {
         std::fs::create_dir_all(&self.base_dir)
     }
-
-    /// Validates run_id contains no path traversal characters.
-    /// SECURITY: run_id is user-controlled, must validate before path construction.
-    fn validate_run_id(run_id: &str) -> Result<(), NexusError> {
-        if run_id.trim().is_empty() {
-            return Err(NexusError::InvalidRunId("empty run_id".to_string()));
-        }
-        if run_id.contains('/') || run_id.contains('\\') || run_id.contains("..") {
-            return Err(NexusError::InvalidRunId(format!(
-                "run_id contains invalid characters: {}",
-                run_id
-            )));
-        }
-        if run_id.len() > 249 {
-            return Err(NexusError::InvalidRunId(
-                "run_id exceeds 249 characters (filename limit with .jsonl extension)".to_string(),
-            ));
-        }
-        Ok(())
-    }
 }
 
 #[cfg(test)]
@@ -90,6 +111,10 @@ mod tests {
         assert!(path.for_run("foo\\bar").is_err());
         assert!(path.for_run("..").is_err(), "double-dot should be rejected");
         assert!(path.for_run("foo..bar").is_err());
+        assert!(
+            path.for_run("index").is_err(),
+            "index.jsonl is the run index"
+        );
     }
 
     #[test]
@@ -100,6 +125,20 @@ mod tests {
         assert!(path.for_run("\t\n").is_err());
     }
 
+    #[test]
+    fn test_allocate_run_id_skips_existing_logs() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = EventLogPath::new(dir.path());
+        path.ensure_dir().unwrap();
+
+        let run_id = path.allocate_run_id(RunIdScheme::TimestampRandom).unwrap();
+        assert!(!path.for_run(&run_id).unwrap().exists());
+        std::fs::write(path.for_run(&run_id).unwrap(), "").unwrap();
+
+        let next = path.allocate_run_id(RunIdScheme::TimestampRandom).unwrap();
+        assert_ne!(run_id, next);
+    }
+
     #[test]
     fn test_event_log_path_rejects_overlong() {
         let path = EventLogPath::new(Path::new("/project"));
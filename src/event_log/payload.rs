@@ -0,0 +1,162 @@
+//! External payload storage for event log entries.
+//!
+//! Payloads too large for a JSONL line are written under
+//! `<runs_dir>/<run_id>/artifacts/` and referenced from events via
+//! `PayloadRef`, with a URI relative to the runs directory.
+
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+use crate::error::NexusError;
+use crate::types::{PayloadRef, RunId};
+
+const ARTIFACTS_DIR: &str = "artifacts";
+
+/// Writes run artifacts and produces `PayloadRef`s for them.
+pub struct PayloadStore {
+    runs_dir: PathBuf,
+    run_id: String,
+}
+
+impl PayloadStore {
+    /// Creates a store for `run_id` rooted at the directory holding the run logs.
+    pub fn new(runs_dir: &Path, run_id: &str) -> Result<Self, NexusError> {
+        Ok(Self {
+            runs_dir: runs_dir.to_path_buf(),
+            run_id: RunId::new(run_id)?.into(),
+        })
+    }
+
+    /// Creates a store next to an existing log file.
+    pub fn for_log(log_path: &Path, run_id: &str) -> Result<Self, NexusError> {
+        let runs_dir = log_path.parent().unwrap_or_else(|| Path::new("."));
+        Self::new(runs_dir, run_id)
+    }
+
+    /// Directory artifacts for this run are written to.
+    pub fn artifacts_dir(&self) -> PathBuf {
+        self.runs_dir.join(&self.run_id).join(ARTIFACTS_DIR)
+    }
+
+    /// Writes `bytes` as artifact `name` and returns a reference to it.
+    pub fn write(
+        &self,
+        name: &str,
+        bytes: &[u8],
+        mime: &str,
+        label: &str,
+    ) -> Result<PayloadRef, NexusError> {
+        if name.is_empty() || name.contains(['/', '\\']) || name.contains("..") {
+            return Err(NexusError::ValidationError {
+                message: format!("invalid artifact name: {name}"),
+                field: Some("name".to_string()),
+            });
+        }
+
+        let dir = self.artifacts_dir();
+        std::fs::create_dir_all(&dir).map_err(|e| NexusError::IoError {
+            operation: "create directory".to_string(),
+            path: dir.clone(),
+            source: e,
+        })?;
+
+        let path = dir.join(name);
+        std::fs::write(&path, bytes).map_err(|e| NexusError::IoError {
+            operation: "write artifact".to_string(),
+            path: path.clone(),
+            source: e,
+        })?;
+
+        Ok(PayloadRef {
+            uri: format!("{}/{}/{}", self.run_id, ARTIFACTS_DIR, name),
+            mime: Some(mime.to_string()),
+            sha256: Some(sha256_hex(bytes)),
+            size_bytes: Some(bytes.len() as u64),
+            label: Some(label.to_string()),
+        })
+    }
+
+    /// Reads the artifact `payload_ref` points to, checking it against the
+    /// recorded size and digest.
+    pub fn read(runs_dir: &Path, payload_ref: &PayloadRef) -> Result<Vec<u8>, NexusError> {
+        let path = Self::resolve(runs_dir, payload_ref);
+        let bytes = std::fs::read(&path).map_err(|e| NexusError::IoError {
+            operation: "read artifact".to_string(),
+            path: path.clone(),
+            source: e,
+        })?;
+        let size_matches = payload_ref
+            .size_bytes
+            .is_none_or(|size| size == bytes.len() as u64);
+        let digest_matches = payload_ref
+            .sha256
+            .as_ref()
+            .is_none_or(|digest| *digest == sha256_hex(&bytes));
+        if !size_matches || !digest_matches {
+            return Err(NexusError::ValidationError {
+                message: format!("artifact {} does not match its payload_ref", path.display()),
+                field: Some("payload_ref".to_string()),
+            });
+        }
+        Ok(bytes)
+    }
+
+    /// Resolves a `PayloadRef` URI produced by this store to a filesystem path.
+    pub fn resolve(runs_dir: &Path, payload_ref: &PayloadRef) -> PathBuf {
+        runs_dir.join(&payload_ref.uri)
+    }
+}
+
+/// Returns the lowercase hex SHA-256 digest of `bytes`.
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_artifact_returns_payload_ref() {
+        let dir = TempDir::new().unwrap();
+        let store = PayloadStore::new(dir.path(), "run_1").unwrap();
+
+        let payload_ref = store
+            .write("response.txt", b"hello", "text/plain", "raw response")
+            .unwrap();
+
+        assert_eq!(payload_ref.uri, "run_1/artifacts/response.txt");
+        assert_eq!(payload_ref.size_bytes, Some(5));
+        assert_eq!(
+            payload_ref.sha256.as_deref(),
+            Some("2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824")
+        );
+        let resolved = PayloadStore::resolve(dir.path(), &payload_ref);
+        assert_eq!(std::fs::read(resolved).unwrap(), b"hello");
+    }
+
+    #[test]
+    fn test_read_checks_digest() {
+        let dir = TempDir::new().unwrap();
+        let store = PayloadStore::new(dir.path(), "run_1").unwrap();
+        let payload_ref = store
+            .write("payload.json", b"{}", "application/json", "event payload")
+            .unwrap();
+        assert_eq!(PayloadStore::read(dir.path(), &payload_ref).unwrap(), b"{}");
+
+        std::fs::write(PayloadStore::resolve(dir.path(), &payload_ref), b"[]").unwrap();
+        assert!(PayloadStore::read(dir.path(), &payload_ref).is_err());
+    }
+
+    #[test]
+    fn test_store_rejects_invalid_names() {
+        let dir = TempDir::new().unwrap();
+        assert!(PayloadStore::new(dir.path(), "../escape").is_err());
+
+        let store = PayloadStore::new(dir.path(), "run_1").unwrap();
+        assert!(store.write("../x", b"", "text/plain", "x").is_err());
+        assert!(store.write("a/b", b"", "text/plain", "x").is_err());
+    }
+}
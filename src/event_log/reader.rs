@@ -1,13 +1,17 @@
 //! Event log reader with streaming iteration and shared locking.
 
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
 
 use fs2::FileExt;
 
+use super::chain::{ChainReport, ChainVerifier};
 use crate::error::NexusError;
-use crate::types::RunEvent;
+use crate::types::{RunEvent, RunEventKind};
+
+/// Bytes read at a time when iterating from the end of a log.
+const REVERSE_CHUNK_BYTES: u64 = 8 * 1024;
 
 /// Event log reader with shared locking for concurrent access.
 ///
@@ -78,6 +82,53 @@ impl EventLogReader {
         Ok(events)
     }
 
+    /// Returns an iterator over events from the last to the first, reading
+    /// the file backwards in chunks rather than loading it whole.
+    ///
+    /// Malformed lines are skipped with a warning to stderr, as
+    /// [`load_all`](Self::load_all) does, since their line numbers are not
+    /// known when reading backwards. [`seek`](Self::seek) before reading
+    /// forwards again.
+    pub fn iter_rev(&mut self) -> Result<ReverseEventIterator<'_>, NexusError> {
+        let end = self
+            .reader
+            .seek(SeekFrom::End(0))
+            .map_err(|e| NexusError::IoError {
+                operation: "seek log file".to_string(),
+                path: self.path.clone(),
+                source: e,
+            })?;
+        Ok(ReverseEventIterator {
+            reader: self,
+            pos: end,
+            partial: Vec::new(),
+            lines: Vec::new(),
+        })
+    }
+
+    /// Loads the last `n` events, oldest first, without reading the rest
+    /// of the log. The reader is left at the start of the file.
+    pub fn tail(&mut self, n: usize) -> Result<Vec<RunEvent>, NexusError> {
+        let mut events = self.iter_rev()?.take(n).collect::<Result<Vec<_>, _>>()?;
+        events.reverse();
+        self.seek(0)?;
+        Ok(events)
+    }
+
+    /// Reads the `len` bytes at `start`.
+    fn read_chunk(&mut self, start: u64, len: usize) -> Result<Vec<u8>, NexusError> {
+        let mut chunk = vec![0; len];
+        self.reader
+            .seek(SeekFrom::Start(start))
+            .and_then(|_| self.reader.read_exact(&mut chunk))
+            .map_err(|e| NexusError::IoError {
+                operation: "read log file".to_string(),
+                path: self.path.clone(),
+                source: e,
+            })?;
+        Ok(chunk)
+    }
+
     /// Reads next line and parses as RunEvent.
     fn read_next(&mut self) -> Option<Result<RunEvent, NexusError>> {
         loop {
@@ -113,6 +164,50 @@ impl EventLogReader {
         }
     }
 
+    /// Skips to byte `offset`, which must be the start of a line.
+    ///
+    /// Line numbers reported afterwards count from there.
+    pub fn seek(&mut self, offset: u64) -> Result<(), NexusError> {
+        self.reader
+            .seek(SeekFrom::Start(offset))
+            .map_err(|e| NexusError::IoError {
+                operation: "seek log file".to_string(),
+                path: self.path.clone(),
+                source: e,
+            })?;
+        Ok(())
+    }
+
+    /// Checks the hash chain of the events not yet read.
+    ///
+    /// A line that is not a JSON object breaks the chain like any other
+    /// edit; only I/O failures are errors.
+    pub fn verify_chain(&mut self) -> Result<ChainReport, NexusError> {
+        let mut verifier = ChainVerifier::default();
+        loop {
+            let mut line = String::new();
+            let read = self
+                .reader
+                .read_line(&mut line)
+                .map_err(|e| NexusError::IoError {
+                    operation: "read line".to_string(),
+                    path: self.path.clone(),
+                    source: e,
+                })?;
+            if read == 0 {
+                return Ok(verifier.finish());
+            }
+            self.line_number += 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<serde_json::Value>(&line) {
+                Ok(serde_json::Value::Object(event)) => verifier.check(self.line_number, &event),
+                _ => verifier.malformed(self.line_number),
+            }
+        }
+    }
+
     /// Returns the current line number (for error reporting).
     pub fn line_number(&self) -> usize {
         self.line_number
@@ -132,6 +227,65 @@ impl Iterator for EventIterator<'_> {
     }
 }
 
+/// Iterator over events from the end of the log file; see
+/// [`EventLogReader::iter_rev`].
+pub struct ReverseEventIterator<'a> {
+    reader: &'a mut EventLogReader,
+    /// Start of the bytes not read yet.
+    pos: u64,
+    /// Bytes after `pos` up to the first newline read, which may be the
+    /// end of a line starting before `pos`.
+    partial: Vec<u8>,
+    /// Complete lines read but not yielded, last one on top.
+    lines: Vec<Vec<u8>>,
+}
+
+impl ReverseEventIterator<'_> {
+    /// Reads the chunk before `pos`, splitting off the complete lines.
+    fn read_back(&mut self) -> Result<(), NexusError> {
+        let start = self.pos.saturating_sub(REVERSE_CHUNK_BYTES);
+        let mut chunk = self.reader.read_chunk(start, (self.pos - start) as usize)?;
+        chunk.append(&mut self.partial);
+        self.pos = start;
+
+        let mut segments = chunk.split(|&byte| byte == b'\n');
+        let first = segments.next().unwrap_or_default().to_vec();
+        self.lines.extend(segments.map(<[u8]>::to_vec));
+        if self.pos == 0 {
+            self.lines.insert(0, first);
+        } else {
+            self.partial = first;
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for ReverseEventIterator<'_> {
+    type Item = Result<RunEvent, NexusError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(line) = self.lines.pop() {
+                if line.trim_ascii().is_empty() {
+                    continue;
+                }
+                match serde_json::from_slice::<RunEvent>(&line) {
+                    Ok(event) => return Some(Ok(event)),
+                    Err(e) => eprintln!("Warning: skipping malformed event: {}", e),
+                }
+                continue;
+            }
+            if self.pos == 0 {
+                return None;
+            }
+            if let Err(e) = self.read_back() {
+                self.pos = 0;
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
 impl Drop for EventLogReader {
     fn drop(&mut self) {
         // Lock is released automatically when file handle is dropped
@@ -152,8 +306,9 @@ pub fn filter_by_run<'a>(
 /// Filter events by event_type.
 pub fn filter_by_type<'a>(
     events: impl Iterator<Item = Result<RunEvent, NexusError>> + 'a,
-    event_type: &'a str,
+    event_type: impl Into<RunEventKind>,
 ) -> impl Iterator<Item = Result<RunEvent, NexusError>> + 'a {
+    let event_type = event_type.into();
     events.filter(move |result| match result {
         Ok(event) => event.event_type == event_type,
         Err(_) => true,
@@ -261,6 +416,47 @@ not valid json
         assert_eq!(events.len(), 2);
     }
 
+    #[test]
+    fn test_reader_iterates_backwards_across_chunks() {
+        let dir = TempDir::new().unwrap();
+        let content: String = (0..500)
+            .map(|i| {
+                format!(
+                    "{{\"v\":\"nexus/1\",\"run_id\":\"run_123\",\"type\":\"event.{i}\",\"time\":\"2026-01-08T12:00:00Z\"}}\n"
+                )
+            })
+            .collect();
+        assert!(content.len() as u64 > 4 * REVERSE_CHUNK_BYTES);
+        let path = create_test_file(&dir, &format!("{content}not valid json\n"));
+
+        let mut reader = EventLogReader::open(&path).unwrap();
+        let types: Vec<_> = reader
+            .iter_rev()
+            .unwrap()
+            .map(|event| event.unwrap().event_type)
+            .collect();
+        assert_eq!(types.len(), 500);
+        assert_eq!(types[0], "event.499");
+        assert_eq!(types[499], "event.0");
+
+        let tail = reader.tail(3).unwrap();
+        let types: Vec<_> = tail.iter().map(|e| e.event_type.as_str()).collect();
+        assert_eq!(types, ["event.497", "event.498", "event.499"]);
+        assert_eq!(reader.iter().count(), 501);
+    }
+
+    #[test]
+    fn test_reader_tail_of_short_log() {
+        let dir = TempDir::new().unwrap();
+        let content = r#"{"v":"nexus/1","run_id":"run_123","type":"run.started","time":"2026-01-08T12:00:00Z"}
+{"v":"nexus/1","run_id":"run_123","type":"run.completed","time":"2026-01-08T12:00:01Z"}"#;
+        let path = create_test_file(&dir, content);
+
+        let mut reader = EventLogReader::open(&path).unwrap();
+        assert_eq!(reader.tail(10).unwrap().len(), 2);
+        assert!(reader.tail(0).unwrap().is_empty());
+    }
+
     #[test]
     fn test_filter_by_run() {
         let dir = TempDir::new().unwrap();
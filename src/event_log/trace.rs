@@ -0,0 +1,180 @@
+//! Correlation IDs and spans for a run's events.
+//!
+//! Every event of a run carries the run's `correlation_id`, and events of
+//! the same phase (prompt, stream, parse, apply) share a `span_id`. The
+//! correlation ID follows W3C Trace Context, so a tool that starts Nexus
+//! with a `TRACEPARENT` variable sees the run under its own trace, with the
+//! run's spans as children of the caller's.
+
+use std::path::Path;
+
+use rand::Rng;
+
+use super::EventLogReader;
+use crate::error::NexusError;
+use crate::types::TraceInfo;
+
+/// Environment variable holding an incoming W3C `traceparent`.
+pub const TRACEPARENT_ENV: &str = "TRACEPARENT";
+
+const TRACE_ID_BYTES: usize = 16;
+const SPAN_ID_BYTES: usize = 8;
+
+/// Trace a run's events belong to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunTrace {
+    correlation_id: String,
+    /// Span new spans are children of, if any.
+    parent_span_id: Option<String>,
+}
+
+impl Default for RunTrace {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RunTrace {
+    /// Starts a new trace with a random correlation ID.
+    pub fn new() -> Self {
+        Self {
+            correlation_id: random_hex::<TRACE_ID_BYTES>(),
+            parent_span_id: None,
+        }
+    }
+
+    /// Joins the trace of a W3C `traceparent` header value, e.g.
+    /// `00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01`.
+    ///
+    /// Returns `None` if `traceparent` is malformed or carries the all-zero
+    /// IDs the spec rules out.
+    pub fn from_traceparent(traceparent: &str) -> Option<Self> {
+        let mut fields = traceparent.trim().split('-');
+        let (version, trace_id, parent_id, flags) = (
+            fields.next()?,
+            fields.next()?,
+            fields.next()?,
+            fields.next()?,
+        );
+        let valid = is_hex(version, 1)
+            && version != "ff"
+            && is_hex(trace_id, TRACE_ID_BYTES)
+            && is_hex(parent_id, SPAN_ID_BYTES)
+            && is_hex(flags, 1)
+            && (version != "00" || fields.next().is_none());
+        if !valid || is_zero(trace_id) || is_zero(parent_id) {
+            return None;
+        }
+        Some(Self {
+            correlation_id: trace_id.to_ascii_lowercase(),
+            parent_span_id: Some(parent_id.to_ascii_lowercase()),
+        })
+    }
+
+    /// Joins the trace in [`TRACEPARENT_ENV`] if it holds a valid
+    /// `traceparent`, else starts a new one.
+    pub fn from_env() -> Self {
+        std::env::var(TRACEPARENT_ENV)
+            .ok()
+            .and_then(|value| Self::from_traceparent(&value))
+            .unwrap_or_default()
+    }
+
+    /// Continues the trace of `span`, with new spans as its children.
+    pub fn within(span: &TraceInfo) -> Option<Self> {
+        Some(Self {
+            correlation_id: span.correlation_id.clone()?,
+            parent_span_id: span.span_id.clone(),
+        })
+    }
+
+    /// Continues the trace of the log at `path` under its first span, or
+    /// `None` if no event of the log is traced.
+    pub fn for_log(path: &Path) -> Result<Option<Self>, NexusError> {
+        let mut reader = EventLogReader::open(path)?;
+        for event in reader.iter() {
+            let Ok(event) = event else {
+                continue;
+            };
+            if let Some(trace) = event.trace.as_ref().and_then(Self::within) {
+                return Ok(Some(trace));
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn correlation_id(&self) -> &str {
+        &self.correlation_id
+    }
+
+    /// Opens a new span in this trace.
+    pub fn span(&self) -> TraceInfo {
+        TraceInfo {
+            correlation_id: Some(self.correlation_id.clone()),
+            span_id: Some(random_hex::<SPAN_ID_BYTES>()),
+            parent_span_id: self.parent_span_id.clone(),
+        }
+    }
+}
+
+fn random_hex<const N: usize>() -> String {
+    loop {
+        let bytes: [u8; N] = rand::rng().random();
+        if bytes.iter().any(|&byte| byte != 0) {
+            return bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+        }
+    }
+}
+
+fn is_hex(field: &str, bytes: usize) -> bool {
+    field.len() == bytes * 2 && field.bytes().all(|byte| byte.is_ascii_hexdigit())
+}
+
+fn is_zero(field: &str) -> bool {
+    field.bytes().all(|byte| byte == b'0')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    #[test]
+    fn test_traceparent_sets_correlation_and_parent() {
+        let trace = RunTrace::from_traceparent(TRACEPARENT).unwrap();
+        assert_eq!(trace.correlation_id(), "4bf92f3577b34da6a3ce929d0e0e4736");
+
+        let span = trace.span();
+        assert_eq!(span.parent_span_id.as_deref(), Some("00f067aa0ba902b7"));
+        assert_eq!(span.span_id.as_ref().unwrap().len(), SPAN_ID_BYTES * 2);
+        assert_ne!(span.span_id, trace.span().span_id);
+    }
+
+    #[test]
+    fn test_malformed_traceparent_is_ignored() {
+        for invalid in [
+            "",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01",
+            "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e473-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra",
+        ] {
+            assert!(
+                RunTrace::from_traceparent(invalid).is_none(),
+                "{invalid:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_within_span_nests_new_spans() {
+        let root = RunTrace::new().span();
+        let child = RunTrace::within(&root).unwrap().span();
+        assert_eq!(child.correlation_id, root.correlation_id);
+        assert_eq!(child.parent_span_id, root.span_id);
+        assert!(RunTrace::within(&TraceInfo::default()).is_none());
+    }
+}
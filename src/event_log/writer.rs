@@ -1,23 +1,83 @@
 //! Event log writer with atomic appends and exclusive locking.
 
+use std::collections::HashSet;
 use std::fs::{File, OpenOptions};
-use std::io::{BufRead, BufReader, BufWriter, ErrorKind, Write};
+use std::io::{BufRead, BufReader, BufWriter, ErrorKind, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 
 use fs2::FileExt;
 use serde::ser::Error as SerError;
 
+use super::chain::{self, EVENT_HASH_FIELD, GENESIS_HASH, PREV_HASH_FIELD};
+use super::index::{self, INDEX_FILE_NAME, IndexRecord};
+use super::{PayloadStore, helpers};
 use crate::error::NexusError;
-use crate::types::RunEvent;
+use crate::redact::Redactor;
+use crate::types::{RunEvent, RunId, TraceInfo};
+
+/// Payloads larger than this, serialized, are moved out of the log by default.
+pub const DEFAULT_PAYLOAD_OFFLOAD_BYTES: usize = 64 * 1024;
+
+/// Payload fields up to this size, serialized, stay in the log when the
+/// payload is offloaded, so `status`, `action_id` and the like remain
+/// readable without the artifact.
+const INLINE_FIELD_MAX_BYTES: usize = 256;
+
+/// Suffix of the file beside a log that keeps lines cut from its end.
+const TORN_SUFFIX: &str = ".torn";
+
+/// Result of scanning a log on open.
+#[derive(Default)]
+struct LogScan {
+    max_seq: u64,
+    /// `event_hash` of the last event, if it has one.
+    last_hash: Option<String>,
+    /// `run_id` of the last event.
+    last_run_id: Option<String>,
+    /// Length of the log up to the end of its last complete line.
+    valid_len: u64,
+    /// Unterminated last line that is not valid JSON.
+    torn: Option<Vec<u8>>,
+    /// Whether the last line is a complete event missing its newline.
+    missing_newline: bool,
+}
 
 /// Append-only event log writer with exclusive file locking.
 ///
 /// Events are written as JSONL (one JSON object per line).
 /// Uses OS-level `O_APPEND` for atomic writes and `fs2` for exclusive locking.
+/// The start and end of each run are also recorded in the run index
+/// (`index.jsonl` beside the log), and each event is hash-chained to the
+/// one before it (see [`chain`](super::chain)).
+///
+/// A payload larger than the offload threshold is written in full to an
+/// artifact of the run and referenced by `payload_ref`; only its small
+/// fields stay inline.
+///
+/// If an append was interrupted and left a partial line at the end of the
+/// log, opening the log moves that line to `<log>.torn` and records a
+/// `log.recovered` event in its place.
+///
+/// With a sync interval set, every that many appended events are synced to
+/// disk; either way, events not yet synced are synced when the writer is
+/// dropped.
 pub struct EventLogWriter {
     writer: BufWriter<File>,
     event_seq: u64,
     path: PathBuf,
+    redactor: Redactor,
+    /// Byte offset the next event is written at.
+    offset: u64,
+    /// Runs whose start this writer has recorded in the index.
+    indexed_runs: HashSet<String>,
+    payload_offload_bytes: Option<usize>,
+    /// `event_hash` of the last event in the log, if it has one.
+    last_hash: Option<String>,
+    sync_interval: Option<u64>,
+    /// Events appended since the last sync.
+    unsynced: u64,
+    /// Trace given to events that carry none.
+    trace: Option<TraceInfo>,
 }
 
 impl EventLogWriter {
@@ -51,14 +111,80 @@ impl EventLogWriter {
             }
         })?;
 
-        let max_seq = Self::scan_max_event_seq(path)?;
-        let next_seq = if max_seq == 0 { 1 } else { max_seq + 1 };
+        // Scan through the locked handle: on Windows, locks are mandatory and a
+        // second handle could not read the range we just locked.
+        let scan = Self::scan_log(&file, path)?;
+        let next_seq = if scan.max_seq == 0 {
+            1
+        } else {
+            scan.max_seq + 1
+        };
+        let recovered = match &scan.torn {
+            Some(torn) => Some(Self::truncate_torn(&file, path, scan.valid_len, torn)?),
+            None => None,
+        };
 
-        Ok(Self {
+        let mut writer = Self {
             writer: BufWriter::new(file),
             event_seq: next_seq,
             path: path.to_path_buf(),
-        })
+            redactor: Redactor::new(),
+            offset: scan.valid_len,
+            indexed_runs: HashSet::new(),
+            payload_offload_bytes: Some(DEFAULT_PAYLOAD_OFFLOAD_BYTES),
+            last_hash: scan.last_hash,
+            sync_interval: None,
+            unsynced: 0,
+            trace: None,
+        };
+        if scan.missing_newline {
+            writer.write_bytes(b"\n")?;
+        }
+        if let Some(backup) = recovered {
+            let run_id = scan.last_run_id.unwrap_or_else(|| {
+                path.file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            });
+            let torn_bytes = scan.torn.as_ref().map_or(0, Vec::len);
+            log::warn!(
+                "recovered event log {}: moved {torn_bytes} bytes of a partial last line to {}",
+                path.display(),
+                backup.display()
+            );
+            writer.append(&helpers::log_recovered(
+                &run_id,
+                scan.valid_len,
+                torn_bytes,
+                &backup,
+            ))?;
+            writer.sync()?;
+        }
+        Ok(writer)
+    }
+
+    /// Saves the partial line `torn` to the log's `.torn` file, then cuts it
+    /// from the log at `valid_len`. Returns the backup's path.
+    fn truncate_torn(
+        file: &File,
+        path: &Path,
+        valid_len: u64,
+        torn: &[u8],
+    ) -> Result<PathBuf, NexusError> {
+        let mut backup_name = path.as_os_str().to_owned();
+        backup_name.push(TORN_SUFFIX);
+        let backup = PathBuf::from(backup_name);
+        let mut line = torn.to_vec();
+        line.push(b'\n');
+        Self::open_file(&backup)?
+            .write_all(&line)
+            .and_then(|()| file.set_len(valid_len))
+            .map_err(|e| NexusError::IoError {
+                operation: "recover log file".to_string(),
+                path: path.to_path_buf(),
+                source: e,
+            })?;
+        Ok(backup)
     }
 
     /// Opens log file with correct options.
@@ -67,6 +193,7 @@ impl EventLogWriter {
         use std::os::unix::fs::OpenOptionsExt;
 
         OpenOptions::new()
+            .read(true)
             .append(true)
             .create(true)
             .mode(0o600)
@@ -84,6 +211,7 @@ impl EventLogWriter {
         // File permissions inherit from parent directory ACLs.
         // For sensitive data, ensure parent directory has appropriate ACLs.
         OpenOptions::new()
+            .read(true)
             .append(true)
             .create(true)
             .open(path)
@@ -94,41 +222,123 @@ impl EventLogWriter {
             })
     }
 
-    /// Scans an existing JSONL file to find the maximum event_seq.
-    fn scan_max_event_seq(path: &Path) -> Result<u64, NexusError> {
-        let file = File::open(path).map_err(|e| NexusError::IoError {
-            operation: "read log file".to_string(),
-            path: path.to_path_buf(),
-            source: e,
-        })?;
-
-        let reader = BufReader::new(file);
-        let mut max_seq = 0u64;
-
-        for line in reader.lines() {
-            let line = line.map_err(|e| NexusError::IoError {
-                operation: "read line".to_string(),
+    /// Scans an existing JSONL file for the maximum event_seq, the hash
+    /// the next event chains to, and a partial last line.
+    ///
+    /// Appends always go to the end of the file, so rewinding the shared
+    /// handle for this scan does not affect where later events are written.
+    fn scan_log(mut file: &File, path: &Path) -> Result<LogScan, NexusError> {
+        file.seek(SeekFrom::Start(0))
+            .map_err(|e| NexusError::IoError {
+                operation: "read log file".to_string(),
                 path: path.to_path_buf(),
                 source: e,
             })?;
 
-            if line.trim().is_empty() {
+        let mut reader = BufReader::new(file);
+        let mut scan = LogScan::default();
+        let mut line = Vec::new();
+
+        loop {
+            line.clear();
+            let read = reader
+                .read_until(b'\n', &mut line)
+                .map_err(|e| NexusError::IoError {
+                    operation: "read line".to_string(),
+                    path: path.to_path_buf(),
+                    source: e,
+                })?;
+            if read == 0 {
+                break;
+            }
+            let terminated = line.last() == Some(&b'\n');
+
+            if line.trim_ascii().is_empty() {
+                scan.valid_len += read as u64;
                 continue;
             }
 
-            let value: serde_json::Value = serde_json::from_str(&line)?;
+            let value: serde_json::Value = match serde_json::from_slice(&line) {
+                Ok(value) => value,
+                Err(_) if !terminated => {
+                    scan.torn = Some(line);
+                    break;
+                }
+                Err(e) => return Err(e.into()),
+            };
+            scan.valid_len += read as u64;
+            scan.missing_newline = !terminated;
             if let Some(seq) = value.get("event_seq").and_then(|v| v.as_u64()) {
-                max_seq = max_seq.max(seq);
+                scan.max_seq = scan.max_seq.max(seq);
             }
+            scan.last_hash = value
+                .get(EVENT_HASH_FIELD)
+                .and_then(|v| v.as_str())
+                .map(str::to_string);
+            scan.last_run_id = value
+                .get("run_id")
+                .and_then(|v| v.as_str())
+                .map(str::to_string);
         }
 
-        Ok(max_seq)
+        Ok(scan)
     }
 
     /// Appends an event to the log, assigning the next event_seq.
     ///
-    /// Does NOT sync to disk (call `sync()` for durability).
+    /// Syncs to disk only when the sync interval is reached (see
+    /// [`with_sync_interval`](Self::with_sync_interval)); call `sync()` for
+    /// durability otherwise.
     pub fn append(&mut self, event: &RunEvent) -> Result<(), NexusError> {
+        self.append_all(std::slice::from_ref(event))
+    }
+
+    /// Appends `events` in order with a single write, assigning each the
+    /// next event_seq.
+    ///
+    /// Syncs at most once, after the whole batch, when the sync interval is
+    /// reached. If any event fails to serialize or has an invalid run_id,
+    /// none are written.
+    pub fn append_all(&mut self, events: &[RunEvent]) -> Result<(), NexusError> {
+        let mut batch = Vec::new();
+        let mut offsets = Vec::with_capacity(events.len());
+        let mut last_hash = self.last_hash.clone();
+        for (seq, event) in (self.event_seq..).zip(events) {
+            let prev_hash = last_hash.as_deref().unwrap_or(GENESIS_HASH);
+            let (line, event_hash) = self.encode(event, seq, prev_hash)?;
+            offsets.push(self.offset + batch.len() as u64);
+            batch.extend_from_slice(&line);
+            last_hash = Some(event_hash);
+        }
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        self.write_bytes(&batch)?;
+        self.event_seq += events.len() as u64;
+        self.last_hash = last_hash;
+        self.unsynced += events.len() as u64;
+        if self
+            .sync_interval
+            .is_some_and(|interval| self.unsynced >= interval)
+        {
+            self.sync()?;
+        }
+        for (event, offset) in events.iter().zip(offsets) {
+            self.index(event, offset)?;
+        }
+        Ok(())
+    }
+
+    /// Serializes `event` as log line `seq` chained to `prev_hash`, returning
+    /// the line and its `event_hash`.
+    fn encode(
+        &self,
+        event: &RunEvent,
+        seq: u64,
+        prev_hash: &str,
+    ) -> Result<(Vec<u8>, String), NexusError> {
+        RunId::new(event.run_id.as_str())?;
         let mut value = serde_json::to_value(event)?;
         let obj = match value.as_object_mut() {
             Some(obj) => obj,
@@ -140,22 +350,124 @@ impl EventLogWriter {
         };
         obj.insert(
             "event_seq".to_string(),
-            serde_json::Value::Number(self.event_seq.into()),
+            serde_json::Value::Number(seq.into()),
         );
+        if let (None, Some(trace)) = (&event.trace, &self.trace) {
+            obj.insert("trace".to_string(), serde_json::to_value(trace)?);
+        }
+        if let Some(payload) = obj.get_mut("payload") {
+            self.redactor.redact_value(payload);
+        }
+        if event.payload_ref.is_none() {
+            self.offload_payload(obj, &event.run_id, seq)?;
+        }
+        obj.insert(
+            PREV_HASH_FIELD.to_string(),
+            serde_json::Value::String(prev_hash.to_string()),
+        );
+        let event_hash = chain::event_hash(obj);
+        obj.insert(
+            EVENT_HASH_FIELD.to_string(),
+            serde_json::Value::String(event_hash.clone()),
+        );
+
+        let mut line = serde_json::to_vec(&value)?;
+        line.push(b'\n');
+        Ok((line, event_hash))
+    }
 
-        serde_json::to_writer(&mut self.writer, &value)?;
+    /// Writes `bytes` to the buffer and advances the offset past them.
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), NexusError> {
         self.writer
-            .write_all(b"\n")
+            .write_all(bytes)
             .map_err(|e| NexusError::IoError {
-                operation: "write newline".to_string(),
+                operation: "write event".to_string(),
                 path: self.path.clone(),
                 source: e,
             })?;
+        self.offset += bytes.len() as u64;
+        Ok(())
+    }
+
+    /// Moves an oversized `payload` of `event` to an artifact, leaving its
+    /// small fields inline and a `payload_ref` to the full payload.
+    ///
+    /// If the artifact cannot be written the payload stays inline, as a
+    /// large log line beats a lost event.
+    fn offload_payload(
+        &self,
+        event: &mut serde_json::Map<String, serde_json::Value>,
+        run_id: &str,
+        seq: u64,
+    ) -> Result<(), NexusError> {
+        let (Some(threshold), Some(payload)) = (self.payload_offload_bytes, event.get("payload"))
+        else {
+            return Ok(());
+        };
+        let bytes = serde_json::to_vec(payload)?;
+        if bytes.len() <= threshold {
+            return Ok(());
+        }
+        let stored = PayloadStore::for_log(&self.path, run_id).and_then(|store| {
+            store.write(
+                &format!("event_{seq:06}.json"),
+                &bytes,
+                "application/json",
+                "event payload",
+            )
+        });
+        let payload_ref = match stored {
+            Ok(payload_ref) => payload_ref,
+            Err(err) => {
+                log::warn!("failed to offload event payload: {err}");
+                return Ok(());
+            }
+        };
+
+        let inline = match event.remove("payload") {
+            Some(serde_json::Value::Object(fields)) => fields
+                .into_iter()
+                .filter(|(_, value)| {
+                    serde_json::to_vec(value).is_ok_and(|v| v.len() <= INLINE_FIELD_MAX_BYTES)
+                })
+                .collect(),
+            _ => serde_json::Map::new(),
+        };
+        if !inline.is_empty() {
+            event.insert("payload".to_string(), serde_json::Value::Object(inline));
+        }
+        event.insert(
+            "payload_ref".to_string(),
+            serde_json::to_value(payload_ref)?,
+        );
+        Ok(())
+    }
 
-        self.event_seq += 1;
+    /// Records `event`, written at `offset`, in the run index if it starts or
+    /// ends a run.
+    fn index(&mut self, event: &RunEvent, offset: u64) -> Result<(), NexusError> {
+        let first = !self.indexed_runs.contains(&event.run_id);
+        let Some(log) = self.path.file_name().and_then(|name| name.to_str()) else {
+            return Ok(());
+        };
+        let Some(record) = IndexRecord::for_event(event, log, offset, first) else {
+            return Ok(());
+        };
+        let index_path = self.path.with_file_name(INDEX_FILE_NAME);
+        index::append_record(&index_path, &record)?;
+        self.indexed_runs.insert(event.run_id.clone());
         Ok(())
     }
 
+    /// Flushes buffered events to the file without syncing to disk.
+    pub fn flush(&mut self) -> Result<(), NexusError> {
+        self.writer.flush().map_err(|e| NexusError::IoError {
+            operation: "flush buffer".to_string(),
+            path: self.path.clone(),
+            source: e,
+        })
+    }
+
     /// Flushes buffer and syncs data to disk.
     pub fn sync(&mut self) -> Result<(), NexusError> {
         self.writer.flush().map_err(|e| NexusError::IoError {
@@ -173,6 +485,7 @@ impl EventLogWriter {
                 source: e,
             })?;
 
+        self.unsynced = 0;
         Ok(())
     }
 
@@ -180,12 +493,51 @@ impl EventLogWriter {
     pub fn next_seq(&self) -> u64 {
         self.event_seq
     }
+
+    /// Replaces the redactor applied to event payloads before they are written.
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    /// Sets the serialized payload size above which payloads are offloaded
+    /// to artifacts; `None` keeps every payload inline.
+    pub fn with_payload_offload(mut self, max_inline_bytes: Option<usize>) -> Self {
+        self.payload_offload_bytes = max_inline_bytes;
+        self
+    }
+
+    /// Syncs to disk after every `events` appended events; `None` (the
+    /// default) leaves syncing to `sync()` and to closing the writer.
+    pub fn with_sync_interval(mut self, events: Option<u64>) -> Self {
+        self.sync_interval = events.map(|events| events.max(1));
+        self
+    }
+
+    /// Gives events appended without a trace `trace` instead.
+    pub fn with_trace(mut self, trace: TraceInfo) -> Self {
+        self.trace = Some(trace);
+        self
+    }
+
+    /// Returns the trace given to events appended without one.
+    pub fn trace(&self) -> Option<&TraceInfo> {
+        self.trace.as_ref()
+    }
+
+    /// Returns the path of the log file being written.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
 }
 
 impl Drop for EventLogWriter {
     fn drop(&mut self) {
         // Flush buffer (ignore errors in drop)
         let _ = self.writer.flush();
+        if self.unsynced > 0 {
+            let _ = self.writer.get_ref().sync_data();
+        }
         // Lock is released automatically when file handle is dropped
     }
 }
@@ -244,6 +596,61 @@ mod tests {
         assert!(content.ends_with('\n'));
     }
 
+    #[test]
+    fn test_writer_redacts_payload_secrets() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("test.jsonl");
+
+        {
+            let redactor = Redactor::new().with_secret("sk-live-0123456789");
+            let mut writer = EventLogWriter::open(&path).unwrap().with_redactor(redactor);
+            let event = RunEvent::new("run_123", "executor.failed")
+                .with_payload(serde_json::json!({"error": "bad key sk-live-0123456789"}));
+            writer.append(&event).unwrap();
+            writer.sync().unwrap();
+        }
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(!content.contains("sk-live-0123456789"));
+        assert!(content.contains("bad key [REDACTED]"));
+    }
+
+    #[test]
+    fn test_writer_offloads_large_payloads() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("run_1.jsonl");
+        let output = "x".repeat(2_000);
+
+        {
+            let mut writer = EventLogWriter::open(&path)
+                .unwrap()
+                .with_payload_offload(Some(1_024));
+            let small = RunEvent::new("run_1", "tool.executed")
+                .with_payload(serde_json::json!({"action_id": "a1"}));
+            let large = RunEvent::new("run_1", "tool.failed")
+                .with_payload(serde_json::json!({"action_id": "a2", "stderr": output}));
+            writer.append(&small).unwrap();
+            writer.append(&large).unwrap();
+            writer.sync().unwrap();
+        }
+
+        let events = crate::event_log::EventLogReader::open(&path)
+            .unwrap()
+            .load_all()
+            .unwrap();
+        assert!(events[0].payload_ref.is_none());
+        let offloaded = &events[1];
+        assert_eq!(
+            offloaded.payload,
+            Some(serde_json::json!({"action_id": "a2"}))
+        );
+        let payload_ref = offloaded.payload_ref.as_ref().unwrap();
+        assert_eq!(payload_ref.uri, "run_1/artifacts/event_000002.json");
+        let stored = PayloadStore::read(dir.path(), payload_ref).unwrap();
+        let full: serde_json::Value = serde_json::from_slice(&stored).unwrap();
+        assert_eq!(full["stderr"], output);
+    }
+
     #[test]
     fn test_writer_increments_event_seq() {
         let dir = TempDir::new().unwrap();
@@ -278,6 +685,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_writer_appends_at_end_after_reopen() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("test.jsonl");
+
+        {
+            let mut writer = EventLogWriter::open(&path).unwrap();
+            writer.append(&RunEvent::new("run_123", "event1")).unwrap();
+            writer.sync().unwrap();
+        }
+        {
+            let mut writer = EventLogWriter::open(&path).unwrap();
+            writer.append(&RunEvent::new("run_123", "event2")).unwrap();
+            writer.sync().unwrap();
+        }
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<_> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("\"event_seq\":1"));
+        assert!(lines[1].contains("\"event_seq\":2"));
+    }
+
     #[test]
     fn test_writer_handles_empty_file_on_reopen() {
         let dir = TempDir::new().unwrap();
@@ -304,6 +734,60 @@ mod tests {
         assert_eq!(writer.next_seq(), 1);
     }
 
+    #[test]
+    fn test_writer_recovers_torn_last_line() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("run_123.jsonl");
+        {
+            let mut writer = EventLogWriter::open(&path).unwrap();
+            writer.append(&RunEvent::new("run_123", "event1")).unwrap();
+            writer.append(&RunEvent::new("run_123", "event2")).unwrap();
+        }
+        let intact_len = std::fs::metadata(&path).unwrap().len();
+        let torn = r#"{"v":"nexus/1","run_id":"run_"#;
+        let mut log = OpenOptions::new().append(true).open(&path).unwrap();
+        log.write_all(torn.as_bytes()).unwrap();
+        drop(log);
+
+        let writer = EventLogWriter::open(&path).unwrap();
+        assert_eq!(writer.next_seq(), 4);
+        drop(writer);
+
+        let backup = dir.path().join("run_123.jsonl.torn");
+        assert_eq!(
+            std::fs::read_to_string(&backup).unwrap(),
+            format!("{torn}\n")
+        );
+        let open = || crate::event_log::EventLogReader::open(&path).unwrap();
+        assert!(open().verify_chain().unwrap().is_intact());
+        let events = open().load_all().unwrap();
+        assert_eq!(events.len(), 3);
+        let payload = events[2].payload.as_ref().unwrap();
+        assert_eq!(events[2].event_type, "log.recovered");
+        assert_eq!(events[2].run_id, "run_123");
+        assert_eq!(payload["offset"], intact_len);
+        assert_eq!(payload["truncated_bytes"], torn.len());
+    }
+
+    #[test]
+    fn test_writer_completes_last_line_missing_newline() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("test.jsonl");
+        std::fs::write(
+            &path,
+            "{\"v\":\"nexus/1\",\"run_id\":\"run_1\",\"type\":\"test\",\"event_seq\":1}",
+        )
+        .unwrap();
+
+        let mut writer = EventLogWriter::open(&path).unwrap();
+        writer.append(&RunEvent::new("run_1", "event2")).unwrap();
+        drop(writer);
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(content.lines().count(), 2);
+        assert!(!path.with_extension("jsonl.torn").exists());
+    }
+
     #[cfg(unix)]
     #[test]
     fn test_writer_file_permissions() {
@@ -320,6 +804,111 @@ mod tests {
         assert_eq!(mode, 0o600, "File should have 0600 permissions");
     }
 
+    #[cfg(windows)]
+    #[test]
+    fn test_writer_reopen_reads_through_mandatory_lock() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("test.jsonl");
+
+        {
+            let mut writer = EventLogWriter::open(&path).unwrap();
+            writer.append(&RunEvent::new("run_123", "event1")).unwrap();
+            writer.append(&RunEvent::new("run_123", "event2")).unwrap();
+            writer.sync().unwrap();
+        }
+
+        let mut writer = EventLogWriter::open(&path).unwrap();
+        assert_eq!(writer.next_seq(), 3);
+        writer.append(&RunEvent::new("run_123", "event3")).unwrap();
+        writer.sync().unwrap();
+    }
+
+    #[test]
+    fn test_writer_append_all_writes_batch_in_order() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("run_123.jsonl");
+
+        let mut writer = EventLogWriter::open(&path)
+            .unwrap()
+            .with_sync_interval(Some(1));
+        writer.append_all(&[]).unwrap();
+        writer
+            .append_all(&[
+                RunEvent::new("run_123", "run.started"),
+                RunEvent::new("run_123", "action.proposed"),
+                RunEvent::new("run_123", "run.completed"),
+            ])
+            .unwrap();
+        assert_eq!(writer.next_seq(), 4);
+        assert_eq!(writer.unsynced, 0);
+        drop(writer);
+
+        let reader = || crate::event_log::EventLogReader::open(&path).unwrap();
+        assert_eq!(reader().verify_chain().unwrap().verified, 3);
+        let content = std::fs::read_to_string(&path).unwrap();
+        for (line, seq) in content.lines().zip(1..) {
+            assert!(line.contains(&format!("\"event_seq\":{seq}")));
+        }
+
+        let index = crate::event_log::EventLogIndex::load(dir.path()).unwrap();
+        let entry = index.get("run_123").unwrap();
+        assert_eq!(entry.load_events().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_writer_traces_untraced_events() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("run_123.jsonl");
+        let trace = crate::event_log::RunTrace::new();
+        let (run_span, phase_span) = (trace.span(), trace.span());
+
+        let mut writer = EventLogWriter::open(&path)
+            .unwrap()
+            .with_trace(run_span.clone());
+        writer.append(&RunEvent::new("run_123", "event1")).unwrap();
+        writer
+            .append(&RunEvent::new("run_123", "event2").with_trace(phase_span.clone()))
+            .unwrap();
+        drop(writer);
+
+        let events = crate::event_log::EventLogReader::open(&path)
+            .unwrap()
+            .load_all()
+            .unwrap();
+        let span_ids: Vec<_> = events
+            .iter()
+            .map(|e| e.trace.as_ref().unwrap().span_id.clone())
+            .collect();
+        assert_eq!(span_ids, [run_span.span_id, phase_span.span_id]);
+        assert_eq!(
+            events[0].trace.as_ref().unwrap().correlation_id.as_deref(),
+            Some(trace.correlation_id())
+        );
+    }
+
+    #[test]
+    fn test_writer_syncs_every_interval_events() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("test.jsonl");
+
+        let mut writer = EventLogWriter::open(&path)
+            .unwrap()
+            .with_sync_interval(Some(2));
+        writer.append(&RunEvent::new("run_123", "event1")).unwrap();
+        assert_eq!(writer.unsynced, 1);
+        writer.append(&RunEvent::new("run_123", "event2")).unwrap();
+        assert_eq!(writer.unsynced, 0);
+        assert!(std::fs::metadata(&path).unwrap().len() > 0);
+
+        let mut writer = writer.with_sync_interval(None);
+        for _ in 0..3 {
+            writer.append(&RunEvent::new("run_123", "event")).unwrap();
+        }
+        assert_eq!(writer.unsynced, 3);
+        writer.sync().unwrap();
+        assert_eq!(writer.unsynced, 0);
+    }
+
     #[test]
     fn test_writer_lock_prevents_second_open() {
         let dir = TempDir::new().unwrap();
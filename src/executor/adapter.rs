@@ -1,27 +1,77 @@
 use async_trait::async_trait;
-use chrono::Utc;
+use futures::StreamExt;
 use secrecy::SecretString;
-use std::time::Instant;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::time::{Duration, Instant};
 
-use super::client::{ChatCompletionRequest, ChatMessage as ClientChatMessage, CodexClient};
+use super::budget::{BudgetMeter, RunBudget};
+use super::client::{
+    ChatCompletionRequest, ChatMessage as ClientChatMessage, CodexClient, StreamOptions, UsageInfo,
+};
+use super::network::NetworkConfig;
 use super::parser::ResponseParser;
-use super::prompt::{ChatMessage as PromptChatMessage, PromptBuilder};
-use super::streaming::StreamHandler;
-use super::{ExecuteOptions, Executor, FileContext, StreamChunk};
+use super::pricing;
+use super::prompt::{ChatMessage as PromptChatMessage, OmittedFile, PromptBuilder, fit_to_budget};
+use super::provider::DEFAULT_PROVIDER;
+use super::scheduler::RateLimitScheduler;
+use super::streaming::{DEFAULT_MAX_RESPONSE_BYTES, StreamHandler, StreamedReply, ToolCall};
+use super::tools::{
+    PROPOSE_ACTIONS_TOOL, actions_response_format, propose_actions_choice, propose_actions_tool,
+};
+use super::{ExecuteOptions, Executor, FileContext, OutputFormat, RunPhase, StreamChunk, tokens};
+use crate::cancel::CancelToken;
 use crate::error::NexusError;
-use crate::event_log::{EventLogWriter, helpers};
-use crate::types::{ActionKindTag, ProposedAction};
+use crate::event_log::payload::sha256_hex;
+use crate::event_log::{AsyncEventLogWriter, EventLogPath, PayloadStore, RunTrace, helpers};
+use crate::paths::normalize_separators;
+use crate::plan::Plan;
+use crate::policy::scan::PatchScanner;
+use crate::policy::{deps, touched_paths};
+use crate::redact::Redactor;
+use crate::review::{ActionReview, REVIEW_MAX_TOKENS, REVIEW_SYSTEM_PROMPT};
+use crate::types::{
+    ActionDetails, ActionKindTag, AgentRole, ProposedAction, RunId, RunIdScheme, TraceInfo,
+};
 
-const DEFAULT_MODEL: &str = "gpt-5.2-codex";
-const RUN_ID_PREFIX: &str = "run_";
-const RUN_ID_TIME_FORMAT: &str = "%Y%m%d_%H%M%S";
-const RUN_ID_MILLIS_WIDTH: usize = 3;
+pub(crate) const DEFAULT_MODEL: &str = "gpt-5.2-codex";
+const RATIONALE_MAX_TOKENS: u32 = 512;
+const LARGEST_FILES_LISTED: usize = 5;
+const RATIONALE_SYSTEM_PROMPT: &str = "For each proposed code change, explain in one sentence \
+why it is needed. Respond with only a JSON object mapping each action id to its sentence.";
+
+/// One provider call: the exact request sent and the raw reply received.
+#[derive(Debug, Serialize)]
+struct Exchange {
+    request: ChatCompletionRequest,
+    response: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    reasoning: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tool_calls: Vec<ToolCall>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    usage: Option<UsageInfo>,
+}
 
 pub struct CodexAdapter {
     client: CodexClient,
     parser: ResponseParser,
     prompt_builder: PromptBuilder,
     model: String,
+    provider: String,
+    run_id_scheme: RunIdScheme,
+    max_response_bytes: usize,
+    cancel: CancelToken,
+    rationale_followup: bool,
+    tool_calling: bool,
+    show_thinking: bool,
+    budget: RunBudget,
+    context_window: Option<usize>,
+    context_budget: Option<usize>,
+    scanner: PatchScanner,
+    history: Vec<ClientChatMessage>,
 }
 
 impl CodexAdapter {
@@ -31,6 +81,18 @@ impl CodexAdapter {
             parser: ResponseParser::new(),
             prompt_builder: PromptBuilder::new(),
             model: DEFAULT_MODEL.to_string(),
+            provider: DEFAULT_PROVIDER.to_string(),
+            run_id_scheme: RunIdScheme::default(),
+            max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
+            cancel: CancelToken::new(),
+            rationale_followup: false,
+            tool_calling: false,
+            show_thinking: true,
+            budget: RunBudget::default(),
+            context_window: None,
+            context_budget: None,
+            scanner: PatchScanner::default(),
+            history: Vec::new(),
         }
     }
 
@@ -44,32 +106,236 @@ impl CodexAdapter {
         self
     }
 
+    /// Names the backend in logged events; see [`super::provider`].
+    pub fn with_provider_name(mut self, name: impl Into<String>) -> Self {
+        self.provider = name.into();
+        self
+    }
+
     pub fn with_base_url(mut self, url: impl Into<String>) -> Self {
         self.client = self.client.with_base_url(url);
         self
     }
 
+    pub fn with_run_id_scheme(mut self, scheme: RunIdScheme) -> Self {
+        self.run_id_scheme = scheme;
+        self
+    }
+
+    /// Caps the accumulated response size; larger responses abort the stream.
+    pub fn with_max_response_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_response_bytes = max_bytes;
+        self
+    }
+
+    /// Adds user redaction patterns; the API key is always masked.
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.client = self.client.with_redactor(redactor);
+        self
+    }
+
+    /// Redactor masking the API key and configured patterns.
+    pub fn redactor(&self) -> &Redactor {
+        self.client.redactor()
+    }
+
+    /// Shares a cancellation token; cancelling it aborts in-flight requests.
+    pub fn with_cancel_token(mut self, token: CancelToken) -> Self {
+        self.cancel = token;
+        self
+    }
+
+    /// Issues a small follow-up request to fill in `why` for actions the
+    /// model proposed without a rationale. Failures leave `why` unset.
+    pub fn with_rationale_followup(mut self, enabled: bool) -> Self {
+        self.rationale_followup = enabled;
+        self
+    }
+
+    /// Asks for actions through the `propose_actions` tool instead of as
+    /// free text. Replies that do not call the tool are parsed as text.
+    pub fn with_tool_calling(mut self, enabled: bool) -> Self {
+        self.tool_calling = enabled;
+        self
+    }
+
+    /// Passes the model's reasoning to streaming callbacks as
+    /// [`StreamChunk::Thinking`]; disabled, only the answer is streamed.
+    pub fn with_show_thinking(mut self, enabled: bool) -> Self {
+        self.show_thinking = enabled;
+        self
+    }
+
+    /// Aborts runs whose usage passes `budget`; see [`super::budget`].
+    pub fn with_budget(mut self, budget: RunBudget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Overrides the context window used by the pre-flight prompt size check.
+    pub fn with_context_window(mut self, tokens: usize) -> Self {
+        self.context_window = Some(tokens);
+        self
+    }
+
+    /// Caps the estimated tokens of context file contents sent with each
+    /// request; files past the budget are truncated or dropped. `None` sends
+    /// every file whole.
+    pub fn with_context_budget(mut self, tokens: Option<usize>) -> Self {
+        self.context_budget = tokens;
+        self
+    }
+
+    /// Uses custom prompt templates, e.g. from `PromptBuilder::from_dir`.
+    pub fn with_prompt_builder(mut self, builder: PromptBuilder) -> Self {
+        self.prompt_builder = builder;
+        self
+    }
+
+    /// Replaces the secret and license scanner applied to proposed actions.
+    pub fn with_patch_scanner(mut self, scanner: PatchScanner) -> Self {
+        self.scanner = scanner;
+        self
+    }
+
+    /// Paces this adapter's provider calls with a scheduler shared with
+    /// other adapters, so a rate limit hit by one pauses all of them.
+    pub fn with_scheduler(mut self, scheduler: RateLimitScheduler) -> Self {
+        self.client = self.client.with_scheduler(scheduler);
+        self
+    }
+
+    /// Enables or disables the preflight check of the model against the
+    /// provider's model list.
+    pub fn with_model_validation(mut self, enabled: bool) -> Self {
+        self.client = self.client.with_model_validation(enabled);
+        self
+    }
+
+    pub fn with_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.client = self.client.with_idle_timeout(timeout);
+        self
+    }
+
+    /// Sends `headers` with every request; see
+    /// [`CodexClient::with_extra_headers`].
+    pub fn with_extra_headers(
+        mut self,
+        headers: &BTreeMap<String, String>,
+    ) -> Result<Self, NexusError> {
+        self.client = self.client.with_extra_headers(headers)?;
+        Ok(self)
+    }
+
+    /// Sends requests through `network`'s proxy, trusting its certificates.
+    pub fn with_network(mut self, network: &NetworkConfig) -> Result<Self, NexusError> {
+        self.client = self.client.with_network(network)?;
+        Ok(self)
+    }
+
+    /// Caps how long a rate-limited request waits for `Retry-After`.
+    pub fn with_max_retry_after(mut self, max: Duration) -> Self {
+        self.client = self.client.with_max_retry_after(max);
+        self
+    }
+
+    /// Sends `messages`, an earlier conversation, in place of the system
+    /// prompt and examples, so each task continues it as the next user
+    /// turn; see [`crate::session`].
+    pub fn with_history(mut self, messages: Vec<ClientChatMessage>) -> Self {
+        self.history = messages;
+        self
+    }
+
     fn build_request(
         &self,
         task: &str,
         files: &[FileContext],
         options: &ExecuteOptions,
-    ) -> ChatCompletionRequest {
-        let prompt_messages =
-            self.prompt_builder
-                .build_messages(task, files, options.preferred_format.clone());
-        let messages = to_client_messages(prompt_messages);
+    ) -> Result<ChatCompletionRequest, NexusError> {
+        let (files, _) = self.fit_files(files);
+        let prompt_messages = self.prompt_builder.build_messages_with_template(
+            options.template.as_deref(),
+            task,
+            &files,
+            options.preferred_format.clone(),
+        )?;
+        let mut messages = to_client_messages(prompt_messages);
+        if !self.history.is_empty() {
+            messages = self.history.iter().cloned().chain(messages.pop()).collect();
+        }
 
-        ChatCompletionRequest {
+        Ok(ChatCompletionRequest {
             model: self.model.clone(),
             messages,
             stream: true,
             max_tokens: options.max_tokens,
             temperature: options.temperature,
-            stream_options: None,
+            stream_options: Some(StreamOptions {
+                include_usage: true,
+            }),
+            response_format: (options.output == OutputFormat::JsonSchema)
+                .then(actions_response_format),
+            tools: self.tool_calling.then(|| vec![propose_actions_tool()]),
+            tool_choice: self.tool_calling.then(propose_actions_choice),
+        })
+    }
+
+    /// Applies the context budget, returning the files to send and what was
+    /// cut from them.
+    fn fit_files<'a>(
+        &self,
+        files: &'a [FileContext],
+    ) -> (Cow<'a, [FileContext]>, Vec<OmittedFile>) {
+        match self.context_budget {
+            Some(budget) => {
+                let (kept, omitted) = fit_to_budget(files, budget);
+                (Cow::Owned(kept), omitted)
+            }
+            None => (Cow::Borrowed(files), Vec::new()),
         }
     }
 
+    /// Fails fast when the prompt plus the reserved completion cannot fit the
+    /// model's context window, naming the files that contribute most.
+    fn check_prompt_size(
+        &self,
+        request: &ChatCompletionRequest,
+        files: &[FileContext],
+    ) -> Result<(), NexusError> {
+        let context_window = self
+            .context_window
+            .unwrap_or_else(|| tokens::context_window(&self.model));
+        let prompt_tokens = estimate_prompt_tokens(request);
+        let reserved = request.max_tokens.unwrap_or(0) as usize;
+        let estimated_tokens = prompt_tokens + reserved;
+        if estimated_tokens <= context_window {
+            return Ok(());
+        }
+
+        let mut sizes: Vec<(&str, usize)> = files
+            .iter()
+            .map(|file| (file.path.as_str(), tokens::estimate_tokens(&file.content)))
+            .collect();
+        sizes.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        let largest_files = if sizes.is_empty() {
+            "none".to_string()
+        } else {
+            sizes
+                .iter()
+                .take(LARGEST_FILES_LISTED)
+                .map(|(path, tokens)| format!("{path} (~{tokens})"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        Err(NexusError::PromptTooLarge {
+            model: self.model.clone(),
+            estimated_tokens,
+            context_window,
+            largest_files,
+        })
+    }
+
     /// Internal execution method that accepts a run_id parameter.
     ///
     /// This ensures consistent run_id across logged events and returned actions.
@@ -79,16 +345,20 @@ impl CodexAdapter {
         files: &[FileContext],
         options: &ExecuteOptions,
         run_id: &str,
+        transcript: &mut Vec<Exchange>,
     ) -> Result<Vec<ProposedAction>, NexusError> {
         if options.dry_run {
             return Ok(Vec::new());
         }
 
-        let request = self.build_request(task, files, options);
-        let stream = self.client.chat_completion_stream(request).await?;
-        let stream = Box::pin(stream);
-        let (response, _usage) = StreamHandler::accumulate(stream).await?;
-        self.parser.parse(&response, run_id)
+        let request = self.build_request(task, files, options)?;
+        self.check_prompt_size(&request, files)?;
+        let reply = self.complete(request, |_| {}, transcript).await?;
+        let mut actions = self.parse_reply(&reply, options.output, run_id)?;
+        self.fill_missing_rationale(&mut actions, transcript).await;
+        self.flag_findings(&mut actions);
+        record_base_hashes(&mut actions, files);
+        Ok(actions)
     }
 
     /// Internal streaming execution method that accepts a run_id parameter.
@@ -105,12 +375,207 @@ impl CodexAdapter {
             return Ok(Vec::new());
         }
 
-        let request = self.build_request(task, files, options);
-        let stream = self.client.chat_completion_stream(request).await?;
-        let stream = Box::pin(stream);
-        let callback = move |chunk| on_chunk(chunk);
-        let (response, _usage) = StreamHandler::with_callback(stream, callback).await?;
-        self.parser.parse(&response, run_id)
+        let mut transcript = Vec::new();
+        let request = self.build_request(task, files, options)?;
+        self.check_prompt_size(&request, files)?;
+        let show_thinking = self.show_thinking;
+        let callback = move |chunk| {
+            if show_thinking || !matches!(chunk, StreamChunk::Thinking(_)) {
+                on_chunk(chunk)
+            }
+        };
+        let reply = self.complete(request, callback, &mut transcript).await?;
+        let mut actions = self.parse_reply(&reply, options.output, run_id)?;
+        self.fill_missing_rationale(&mut actions, &mut transcript)
+            .await;
+        self.flag_findings(&mut actions);
+        record_base_hashes(&mut actions, files);
+        Ok(actions)
+    }
+
+    /// Sends one request, accumulates the streamed reply, and records both.
+    async fn complete<F>(
+        &self,
+        request: ChatCompletionRequest,
+        on_chunk: F,
+        transcript: &mut Vec<Exchange>,
+    ) -> Result<StreamedReply, NexusError>
+    where
+        F: Fn(StreamChunk) + Send,
+    {
+        let mut meter = (!self.budget.is_unlimited()).then(|| {
+            BudgetMeter::new(
+                self.budget,
+                &self.model,
+                total_usage(transcript).unwrap_or_default(),
+                estimate_prompt_tokens(&request) as u64,
+            )
+        });
+        if let Some(meter) = &meter {
+            meter.check()?;
+        }
+        let stream = self.client.chat_completion_stream(request.clone()).await?;
+        // Checked per chunk so an overrun closes the stream instead of
+        // paying for the rest of the reply.
+        let stream = Box::pin(stream).map(move |result| match (meter.as_mut(), result) {
+            (Some(meter), Ok(chunk)) => {
+                meter.record(&chunk);
+                meter.check().map(|()| chunk)
+            }
+            (_, result) => result,
+        });
+        let reply = StreamHandler::collect(stream, self.max_response_bytes, on_chunk).await?;
+        transcript.push(Exchange {
+            request,
+            response: reply.content.clone(),
+            reasoning: reply.reasoning.clone(),
+            tool_calls: reply.tool_calls.clone(),
+            usage: reply.usage.clone(),
+        });
+        Ok(reply)
+    }
+
+    /// Reads actions from the `propose_actions` call if the model made one,
+    /// else from the JSON reply `output` asked for, falling back to the text
+    /// parser when the model did not comply or its JSON does not match the
+    /// schema.
+    fn parse_reply(
+        &self,
+        reply: &StreamedReply,
+        output: OutputFormat,
+        run_id: &str,
+    ) -> Result<Vec<ProposedAction>, NexusError> {
+        let call = reply
+            .tool_calls
+            .iter()
+            .find(|call| call.name == PROPOSE_ACTIONS_TOOL);
+        let structured = match call {
+            Some(call) => Some((PROPOSE_ACTIONS_TOOL, call.arguments.as_str())),
+            None if output == OutputFormat::JsonSchema => {
+                Some(("json_schema reply", &*reply.content))
+            }
+            None => None,
+        };
+        if let Some((source_name, json)) = structured {
+            match self.parser.parse_structured_actions(json) {
+                Err(NexusError::JsonError { source, .. }) => {
+                    log::warn!("{source_name} does not match the action schema: {source}");
+                }
+                result => return result,
+            }
+        } else if self.tool_calling {
+            log::debug!("model answered without calling {PROPOSE_ACTIONS_TOOL}; parsing text");
+        }
+        self.parser.parse(&reply.content, run_id)
+    }
+
+    /// Raises the risk of actions that add secrets or disallowed licenses,
+    /// or edit dependency manifests.
+    fn flag_findings(&self, actions: &mut [ProposedAction]) {
+        for action in actions.iter_mut() {
+            for finding in self.scanner.flag(action) {
+                log::warn!("{}: {finding}", action.id);
+            }
+            let manifests = deps::flag_dependency_edits(action);
+            if !manifests.is_empty() {
+                log::info!("{}: edits dependency manifests {manifests:?}", action.id);
+            }
+        }
+    }
+
+    /// Asks the model for a one-sentence `why` for each action missing one.
+    async fn fill_missing_rationale(
+        &self,
+        actions: &mut [ProposedAction],
+        transcript: &mut Vec<Exchange>,
+    ) {
+        if !self.rationale_followup || actions.iter().all(|action| action.why.is_some()) {
+            return;
+        }
+
+        match self.request_rationale(actions, transcript).await {
+            Ok(rationale) => {
+                for action in actions.iter_mut().filter(|action| action.why.is_none()) {
+                    action.why = rationale.get(&action.id).cloned();
+                }
+            }
+            Err(err) => log::warn!("rationale follow-up failed: {err}"),
+        }
+    }
+
+    async fn request_rationale(
+        &self,
+        actions: &[ProposedAction],
+        transcript: &mut Vec<Exchange>,
+    ) -> Result<HashMap<String, String>, NexusError> {
+        let request = ChatCompletionRequest {
+            model: self.model.clone(),
+            messages: vec![
+                ClientChatMessage {
+                    role: "system".to_string(),
+                    content: RATIONALE_SYSTEM_PROMPT.to_string(),
+                },
+                ClientChatMessage {
+                    role: "user".to_string(),
+                    content: rationale_request_body(actions),
+                },
+            ],
+            stream: true,
+            max_tokens: Some(RATIONALE_MAX_TOKENS),
+            temperature: Some(0.0),
+            stream_options: Some(StreamOptions {
+                include_usage: true,
+            }),
+            response_format: None,
+            tools: None,
+            tool_choice: None,
+        };
+        let reply = self.complete(request, |_| {}, transcript).await?;
+        parse_rationale_response(&reply.content)
+    }
+
+    /// Asks the model for a review of the proposed action described by
+    /// `body` (see [`crate::review::review_request_body`]).
+    pub async fn review_action(&self, body: &str) -> Result<ActionReview, NexusError> {
+        let request = ChatCompletionRequest {
+            model: self.model.clone(),
+            messages: vec![
+                ClientChatMessage {
+                    role: "system".to_string(),
+                    content: REVIEW_SYSTEM_PROMPT.to_string(),
+                },
+                ClientChatMessage {
+                    role: "user".to_string(),
+                    content: body.to_string(),
+                },
+            ],
+            stream: true,
+            max_tokens: Some(REVIEW_MAX_TOKENS),
+            temperature: Some(0.0),
+            stream_options: Some(StreamOptions {
+                include_usage: true,
+            }),
+            response_format: None,
+            tools: None,
+            tool_choice: None,
+        };
+        let mut transcript = Vec::new();
+        self.cancel
+            .run(async {
+                let reply = self.complete(request, |_| {}, &mut transcript).await?;
+                ActionReview::parse(&reply.content)
+            })
+            .await
+    }
+
+    /// A run ID with no log in `runs_dir` yet; without one, in the
+    /// `.nexus/runs/` of the working directory.
+    fn allocate_run_id(&self, runs_dir: Option<&Path>) -> Result<String, NexusError> {
+        let paths = match runs_dir {
+            Some(dir) => EventLogPath::in_dir(dir),
+            None => EventLogPath::new(&std::env::current_dir().unwrap_or_default()),
+        };
+        paths.allocate_run_id(self.run_id_scheme)
     }
 
     pub async fn execute_with_logging(
@@ -118,43 +583,307 @@ impl CodexAdapter {
         task: &str,
         files: &[FileContext],
         options: ExecuteOptions,
-        writer: &mut EventLogWriter,
+        writer: &AsyncEventLogWriter,
     ) -> Result<Vec<ProposedAction>, NexusError> {
-        let run_id = generate_run_id();
+        let runs_dir = writer.path().parent().unwrap_or_else(|| Path::new("."));
+        let run_id = self.allocate_run_id(Some(runs_dir))?;
+        self.execute_run_with_logging(&run_id, task, files, options, writer)
+            .await
+    }
+
+    /// Like [`Self::execute_with_logging`], for a run ID the caller already
+    /// allocated (e.g. to name the log file after the run).
+    pub async fn execute_run_with_logging(
+        &self,
+        run_id: &str,
+        task: &str,
+        files: &[FileContext],
+        options: ExecuteOptions,
+        writer: &AsyncEventLogWriter,
+    ) -> Result<Vec<ProposedAction>, NexusError> {
+        self.execute_logged(run_id, None, task, files, options, writer)
+            .await
+    }
+
+    /// Like [`Self::execute_run_with_logging`], for a later `phase` of the
+    /// run, e.g. a plan step or the agent taking over through a handoff.
+    pub async fn execute_phase_with_logging(
+        &self,
+        run_id: &str,
+        phase: &RunPhase,
+        task: &str,
+        files: &[FileContext],
+        options: ExecuteOptions,
+        writer: &AsyncEventLogWriter,
+    ) -> Result<Vec<ProposedAction>, NexusError> {
+        self.execute_logged(run_id, Some(phase), task, files, options, writer)
+            .await
+    }
+
+    async fn execute_logged(
+        &self,
+        run_id: &str,
+        phase: Option<&RunPhase>,
+        task: &str,
+        files: &[FileContext],
+        options: ExecuteOptions,
+        writer: &AsyncEventLogWriter,
+    ) -> Result<Vec<ProposedAction>, NexusError> {
+        let run_id = RunId::new(run_id)?;
+        let artifact = |name: &str| match phase {
+            Some(phase) => format!("{}_{name}", phase.label),
+            None => name.to_string(),
+        };
         let started_at = Instant::now();
+        let trace = writer
+            .trace()
+            .and_then(RunTrace::within)
+            .unwrap_or_else(RunTrace::from_env);
+        let (prompt_span, stream_span, parse_span) = (trace.span(), trace.span(), trace.span());
 
-        let started = helpers::executor_started(&run_id, task, files.len(), &self.model);
-        writer.append(&started)?;
+        let paths: Vec<String> = files.iter().map(|file| file.path.clone()).collect();
+        let mut started = helpers::executor_started(&run_id, task, &paths, &self.model)
+            .with_trace(prompt_span.clone());
+        if let Some(actor) = started.actor.as_mut() {
+            actor.provider = Some(self.provider.clone());
+            if let Some(phase) = phase {
+                actor.agent = Some(phase.role.clone());
+            }
+        }
+        if !options.dry_run {
+            let stored = self
+                .build_request(task, files, &options)
+                .and_then(|request| {
+                    self.persist_json(
+                        writer,
+                        &run_id,
+                        &artifact("request.json"),
+                        &request,
+                        "request",
+                    )
+                });
+            match stored {
+                Ok(payload_ref) => started = started.with_payload_ref(payload_ref),
+                Err(err) => log::warn!("failed to store request: {err}"),
+            }
+        }
+        writer.append(&started).await?;
+        let (_, omitted) = self.fit_files(files);
+        if !options.dry_run && !omitted.is_empty() {
+            let budget = self.context_budget.unwrap_or_default();
+            log::warn!(
+                "context exceeds the {budget}-token budget; truncated or dropped {} file(s)",
+                omitted.len()
+            );
+            writer
+                .append(
+                    &helpers::context_truncated(&run_id, budget, &omitted).with_trace(prompt_span),
+                )
+                .await?;
+        }
 
         // Use the same run_id for execution to ensure event-action correlation
-        let result = self.execute_internal(task, files, &options, &run_id).await;
+        let mut transcript = Vec::new();
+        let result = self
+            .cancel
+            .run(self.execute_internal(task, files, &options, &run_id, &mut transcript))
+            .await;
         match result {
-            Ok(actions) => {
-                for action in &actions {
+            Ok(mut actions) => {
+                if let Some(phase) = phase {
+                    for action in &mut actions {
+                        action.id = format!("{}_{}", action.id, phase.label);
+                    }
+                }
+                let mut proposed = Vec::with_capacity(actions.len());
+                for (index, action) in actions.iter().enumerate() {
                     let kind = action_kind_label(&action.kind);
-                    let event =
-                        helpers::action_proposed(&run_id, &action.id, kind, &action.summary, None);
-                    writer.append(&event)?;
+                    let mut event =
+                        helpers::action_proposed(&run_id, &action.id, kind, &action.summary, None)
+                            .with_trace(parse_span.clone());
+                    let name = artifact(&format!("action_{:03}.json", index + 1));
+                    match persist_action(writer, &run_id, &name, action) {
+                        Ok(payload_ref) => event = event.with_payload_ref(payload_ref),
+                        Err(err) => log::warn!("failed to store action {}: {err}", action.id),
+                    }
+                    proposed.push(event);
                 }
+                writer.append_all(&proposed).await?;
 
                 let duration_ms = started_at.elapsed().as_millis();
-                let completed = helpers::executor_completed(&run_id, actions.len(), duration_ms);
-                writer.append(&completed)?;
-                writer.sync()?;
+                let mut completed =
+                    helpers::executor_completed(&run_id, actions.len(), duration_ms)
+                        .with_trace(parse_span);
+                if let Some(usage) = total_usage(&transcript) {
+                    let cost_usd = pricing::usage_cost_usd(&self.model, &usage);
+                    completed = helpers::with_usage(completed, &usage, cost_usd);
+                }
+                if !transcript.is_empty() {
+                    let stored = self.persist_json(
+                        writer,
+                        &run_id,
+                        &artifact("transcript.json"),
+                        &transcript,
+                        "provider transcript",
+                    );
+                    match stored {
+                        Ok(payload_ref) => completed = completed.with_payload_ref(payload_ref),
+                        Err(err) => log::warn!("failed to store transcript: {err}"),
+                    }
+                }
+                writer.append(&completed).await?;
+                writer.sync().await?;
                 Ok(actions)
             }
             Err(err) => {
-                let status_code = match &err {
+                let partial_name = artifact("response.partial.txt");
+                self.record_failure(writer, &run_id, &err, stream_span, &partial_name)
+                    .await?;
+                Err(err)
+            }
+        }
+    }
+
+    /// Asks this adapter, set up with [`crate::plan::PLANNER_SYSTEM_PROMPT`],
+    /// for a plan of `task`, logging the exchange like an executor pass
+    /// that proposes no actions.
+    pub async fn plan_with_logging(
+        &self,
+        run_id: &str,
+        task: &str,
+        files: &[FileContext],
+        options: ExecuteOptions,
+        writer: &AsyncEventLogWriter,
+    ) -> Result<Plan, NexusError> {
+        let run_id = RunId::new(run_id)?;
+        let started_at = Instant::now();
+        let trace = writer
+            .trace()
+            .and_then(RunTrace::within)
+            .unwrap_or_else(RunTrace::from_env);
+        let (prompt_span, stream_span) = (trace.span(), trace.span());
+
+        let request = self.build_request(task, files, &options)?;
+        let paths: Vec<String> = files.iter().map(|file| file.path.clone()).collect();
+        let mut started =
+            helpers::executor_started(&run_id, task, &paths, &self.model).with_trace(prompt_span);
+        if let Some(actor) = started.actor.as_mut() {
+            actor.provider = Some(self.provider.clone());
+            actor.agent = Some(AgentRole::Planner);
+        }
+        match self.persist_json(writer, &run_id, "plan_request.json", &request, "request") {
+            Ok(payload_ref) => started = started.with_payload_ref(payload_ref),
+            Err(err) => log::warn!("failed to store request: {err}"),
+        }
+        writer.append(&started).await?;
+
+        let mut transcript = Vec::new();
+        let result = self
+            .cancel
+            .run(async {
+                self.check_prompt_size(&request, files)?;
+                let reply = self
+                    .complete(request.clone(), |_| {}, &mut transcript)
+                    .await?;
+                Plan::parse(task, &reply.content)
+            })
+            .await;
+        let plan = match result {
+            Ok(plan) => plan,
+            Err(err) => {
+                self.record_failure(
+                    writer,
+                    &run_id,
+                    &err,
+                    stream_span,
+                    "plan_response.partial.txt",
+                )
+                .await?;
+                return Err(err);
+            }
+        };
+
+        let duration_ms = started_at.elapsed().as_millis();
+        let mut completed = helpers::executor_completed(&run_id, 0, duration_ms);
+        if let Some(actor) = completed.actor.as_mut() {
+            actor.agent = Some(AgentRole::Planner);
+        }
+        if let Some(usage) = total_usage(&transcript) {
+            let cost_usd = pricing::usage_cost_usd(&self.model, &usage);
+            completed = helpers::with_usage(completed, &usage, cost_usd);
+        }
+        let stored = self.persist_json(
+            writer,
+            &run_id,
+            "plan_transcript.json",
+            &transcript,
+            "provider transcript",
+        );
+        match stored {
+            Ok(payload_ref) => completed = completed.with_payload_ref(payload_ref),
+            Err(err) => log::warn!("failed to store transcript: {err}"),
+        }
+        writer.append(&completed).await?;
+        Ok(plan)
+    }
+
+    /// Logs why a pass over `run_id` failed: `run.cancelled`,
+    /// `run.budget_exceeded`, or `executor.failed` with the partial response
+    /// stored as `partial_name` if it was too large.
+    async fn record_failure(
+        &self,
+        writer: &AsyncEventLogWriter,
+        run_id: &str,
+        err: &NexusError,
+        span: TraceInfo,
+        partial_name: &str,
+    ) -> Result<(), NexusError> {
+        let event = match err {
+            NexusError::Cancelled => helpers::run_cancelled(run_id, "interrupted by user"),
+            NexusError::BudgetExceeded { limit, spent } => {
+                log::warn!("aborting run {run_id}: spent {spent}, limit {limit}");
+                helpers::run_budget_exceeded(run_id, limit, spent)
+            }
+            err => {
+                let status_code = match err {
                     NexusError::ApiError { status_code, .. } => *status_code,
                     _ => None,
                 };
-
-                let failed = helpers::executor_failed(&run_id, &err.to_string(), status_code);
-                writer.append(&failed)?;
-                writer.sync()?;
-                Err(err)
+                let message = self.client.redactor().redact(&err.to_string());
+                let mut failed = helpers::executor_failed(run_id, &message, status_code);
+                if let NexusError::ResponseTooLarge { partial, .. } = err {
+                    let partial = self.client.redactor().redact(partial);
+                    match persist_partial_response(writer, run_id, partial_name, &partial) {
+                        Ok(payload_ref) => failed = failed.with_payload_ref(payload_ref),
+                        Err(persist_err) => {
+                            log::warn!("failed to persist partial response: {persist_err}")
+                        }
+                    }
+                }
+                failed
             }
-        }
+        };
+        writer.append(&event.with_trace(span)).await?;
+        writer.sync().await
+    }
+
+    /// Stores a redacted JSON artifact of what was exchanged with the provider.
+    fn persist_json(
+        &self,
+        writer: &AsyncEventLogWriter,
+        run_id: &str,
+        name: &str,
+        value: &impl Serialize,
+        label: &str,
+    ) -> Result<crate::types::PayloadRef, NexusError> {
+        let json = serde_json::to_string_pretty(value)?;
+        let json = self.client.redactor().redact(&json);
+        PayloadStore::for_log(writer.path(), run_id)?.write(
+            name,
+            json.as_bytes(),
+            "application/json",
+            label,
+        )
     }
 }
 
@@ -166,8 +895,11 @@ impl Executor for CodexAdapter {
         files: Vec<FileContext>,
         options: ExecuteOptions,
     ) -> Result<Vec<ProposedAction>, NexusError> {
-        let run_id = generate_run_id();
-        self.execute_internal(task, &files, &options, &run_id).await
+        let run_id = self.allocate_run_id(None)?;
+        let mut transcript = Vec::new();
+        self.cancel
+            .run(self.execute_internal(task, &files, &options, &run_id, &mut transcript))
+            .await
     }
 
     async fn execute_streaming(
@@ -177,22 +909,133 @@ impl Executor for CodexAdapter {
         options: ExecuteOptions,
         on_chunk: Box<dyn Fn(StreamChunk) + Send>,
     ) -> Result<Vec<ProposedAction>, NexusError> {
-        let run_id = generate_run_id();
-        self.execute_streaming_internal(task, &files, &options, &run_id, on_chunk)
+        let run_id = self.allocate_run_id(None)?;
+        self.cancel
+            .run(self.execute_streaming_internal(task, &files, &options, &run_id, on_chunk))
             .await
     }
 }
 
-fn generate_run_id() -> String {
-    let now = Utc::now();
-    let timestamp = now.format(RUN_ID_TIME_FORMAT).to_string();
-    let millis = now.timestamp_subsec_millis();
-    format!(
-        "{RUN_ID_PREFIX}{timestamp}_{millis:0width$}",
-        width = RUN_ID_MILLIS_WIDTH
+fn estimate_prompt_tokens(request: &ChatCompletionRequest) -> usize {
+    request
+        .messages
+        .iter()
+        .map(|message| tokens::estimate_tokens(&message.content))
+        .sum()
+}
+
+/// Tokens used across every call of a run, if the provider reported any.
+fn total_usage(transcript: &[Exchange]) -> Option<UsageInfo> {
+    let mut usages = transcript
+        .iter()
+        .filter_map(|exchange| exchange.usage.as_ref())
+        .peekable();
+    usages.peek()?;
+    Some(usages.fold(UsageInfo::default(), |mut total, usage| {
+        total.prompt_tokens += usage.prompt_tokens;
+        total.completion_tokens += usage.completion_tokens;
+        total.total_tokens += usage.total_tokens;
+        total
+    }))
+}
+
+/// Records the SHA-256 of each context file a patch touches, so the applier
+/// can refuse the patch once the file has changed. Any hashes in the model's
+/// output are replaced; it cannot compute them reliably.
+fn record_base_hashes(actions: &mut [ProposedAction], files: &[FileContext]) {
+    let hashes: HashMap<String, String> = files
+        .iter()
+        .map(|file| {
+            (
+                normalize_separators(&file.path),
+                sha256_hex(file.content.as_bytes()),
+            )
+        })
+        .collect();
+    for action in actions {
+        let touched = touched_paths(action);
+        let ActionDetails::Patch(details) = &mut action.details else {
+            continue;
+        };
+        let base: HashMap<String, String> = touched
+            .into_iter()
+            .filter_map(|path| {
+                let hash = hashes.get(&path)?.clone();
+                Some((path, hash))
+            })
+            .collect();
+        details.base_file_sha256 = (!base.is_empty()).then_some(base);
+    }
+}
+
+/// Stores the full proposed action so it can be previewed or applied later.
+fn persist_action(
+    writer: &AsyncEventLogWriter,
+    run_id: &str,
+    name: &str,
+    action: &ProposedAction,
+) -> Result<crate::types::PayloadRef, NexusError> {
+    let bytes = serde_json::to_vec_pretty(action)?;
+    PayloadStore::for_log(writer.path(), run_id)?.write(
+        name,
+        &bytes,
+        "application/json",
+        "proposed action",
+    )
+}
+
+/// Saves the truncated response of an oversized generation as a run artifact.
+fn persist_partial_response(
+    writer: &AsyncEventLogWriter,
+    run_id: &str,
+    name: &str,
+    partial: &str,
+) -> Result<crate::types::PayloadRef, NexusError> {
+    PayloadStore::for_log(writer.path(), run_id)?.write(
+        name,
+        partial.as_bytes(),
+        "text/plain",
+        "partial response (size limit exceeded)",
     )
 }
 
+/// Lists actions without a rationale, with their summaries and changes.
+fn rationale_request_body(actions: &[ProposedAction]) -> String {
+    let pending: Vec<_> = actions
+        .iter()
+        .filter(|action| action.why.is_none())
+        .map(|action| {
+            serde_json::json!({
+                "action_id": action.id,
+                "summary": action.summary,
+                "details": action.details,
+            })
+        })
+        .collect();
+    serde_json::to_string_pretty(&pending).unwrap_or_default()
+}
+
+/// Extracts the `{action_id: why}` object from a rationale response.
+fn parse_rationale_response(response: &str) -> Result<HashMap<String, String>, NexusError> {
+    let start = response.find('{');
+    let end = response.rfind('}');
+    let json = match (start, end) {
+        (Some(start), Some(end)) if start < end => &response[start..=end],
+        _ => {
+            return Err(NexusError::ResponseParseFailed {
+                context: "rationale response contains no JSON object".to_string(),
+                raw_response: Some(response.to_string()),
+            });
+        }
+    };
+    let rationale: HashMap<String, String> = serde_json::from_str(json)?;
+    Ok(rationale
+        .into_iter()
+        .map(|(id, why)| (id, why.trim().to_string()))
+        .filter(|(_, why)| !why.is_empty())
+        .collect())
+}
+
 fn to_client_messages(messages: Vec<PromptChatMessage>) -> Vec<ClientChatMessage> {
     messages
         .into_iter()
@@ -0,0 +1,321 @@
+//! Where API keys come from when a provider's environment variable is
+//! unset.
+//!
+//! A [`Provider`](super::Provider) first reads its own variable, e.g.
+//! `OPENAI_API_KEY`. If that is unset, the [`AuthChain`] built from
+//! settings asks each [`AuthProvider`] in turn: `api_key_command` (a
+//! credential helper), `api_key_file`, then `api_key_keychain` (the macOS
+//! Keychain or the Linux secret service). Keys are looked up once per
+//! provider and kept for the rest of the process.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+use std::sync::Mutex;
+
+use secrecy::SecretString;
+
+use crate::error::NexusError;
+use crate::types::NexusSettings;
+
+/// Variable telling `api_key_command` which provider the key is for.
+pub const AUTH_PROVIDER_ENV: &str = "NEXUS_AUTH_PROVIDER";
+
+/// A place an API key may be stored.
+pub trait AuthProvider: Send + Sync {
+    /// The setting it comes from, as named in error messages.
+    fn describe(&self) -> String;
+
+    /// The key for the provider called `provider`, or `None` if this
+    /// source has none.
+    fn api_key(&self, provider: &str) -> Result<Option<SecretString>, NexusError>;
+}
+
+/// `api_key_command`: a credential helper printing the key on stdout.
+#[derive(Debug, Clone)]
+pub struct CredentialCommand {
+    argv: Vec<String>,
+}
+
+impl CredentialCommand {
+    pub fn new(argv: Vec<String>) -> Self {
+        Self { argv }
+    }
+}
+
+impl AuthProvider for CredentialCommand {
+    fn describe(&self) -> String {
+        "api_key_command".to_string()
+    }
+
+    fn api_key(&self, provider: &str) -> Result<Option<SecretString>, NexusError> {
+        let Some((program, args)) = self.argv.split_first() else {
+            return Ok(None);
+        };
+        let output = Command::new(program)
+            .args(args)
+            .env(AUTH_PROVIDER_ENV, provider)
+            .stdin(Stdio::null())
+            .stderr(Stdio::inherit())
+            .output()
+            .map_err(|err| {
+                auth_error(format!("failed to run api_key_command `{program}`: {err}"))
+            })?;
+        if !output.status.success() {
+            return Err(auth_error(format!(
+                "api_key_command `{program}` failed with {}",
+                output.status
+            )));
+        }
+        Ok(key_from(&output.stdout))
+    }
+}
+
+/// `api_key_file`: a file holding the key. `~/` is the home directory.
+#[derive(Debug, Clone)]
+pub struct KeyFile {
+    path: PathBuf,
+}
+
+impl KeyFile {
+    pub fn new(path: &str) -> Self {
+        let home = std::env::var_os("HOME").filter(|home| !home.is_empty());
+        let path = match (path.strip_prefix("~/"), home) {
+            (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+            _ => PathBuf::from(path),
+        };
+        Self { path }
+    }
+}
+
+impl AuthProvider for KeyFile {
+    fn describe(&self) -> String {
+        format!("api_key_file ({})", self.path.display())
+    }
+
+    fn api_key(&self, _provider: &str) -> Result<Option<SecretString>, NexusError> {
+        let bytes = std::fs::read(&self.path).map_err(|err| NexusError::IoError {
+            operation: "read api_key_file".to_string(),
+            path: self.path.clone(),
+            source: err,
+        })?;
+        warn_if_shared(&self.path);
+        Ok(key_from(&bytes))
+    }
+}
+
+#[cfg(unix)]
+fn warn_if_shared(path: &std::path::Path) {
+    use std::os::unix::fs::PermissionsExt;
+    if let Ok(metadata) = std::fs::metadata(path) {
+        if metadata.permissions().mode() & 0o077 != 0 {
+            log::warn!(
+                "api_key_file {} is readable by other users; consider `chmod 600`",
+                path.display()
+            );
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn warn_if_shared(_path: &std::path::Path) {}
+
+/// `api_key_keychain`: the system keychain entry for `service`, with the
+/// provider's name as the account. Read with `security` on macOS and
+/// `secret-tool` (libsecret) elsewhere.
+#[derive(Debug, Clone)]
+pub struct Keychain {
+    service: String,
+}
+
+impl Keychain {
+    pub fn new(service: impl Into<String>) -> Self {
+        Self {
+            service: service.into(),
+        }
+    }
+
+    fn lookup(&self, provider: &str) -> std::io::Result<Output> {
+        let mut command = if cfg!(target_os = "macos") {
+            let mut command = Command::new("security");
+            command.args([
+                "find-generic-password",
+                "-s",
+                &self.service,
+                "-a",
+                provider,
+                "-w",
+            ]);
+            command
+        } else {
+            let mut command = Command::new("secret-tool");
+            command.args(["lookup", "service", &self.service, "account", provider]);
+            command
+        };
+        command.stdin(Stdio::null()).stderr(Stdio::null()).output()
+    }
+}
+
+impl AuthProvider for Keychain {
+    fn describe(&self) -> String {
+        format!("api_key_keychain ({})", self.service)
+    }
+
+    fn api_key(&self, provider: &str) -> Result<Option<SecretString>, NexusError> {
+        let output = self
+            .lookup(provider)
+            .map_err(|err| auth_error(format!("failed to read the keychain: {err}")))?;
+        if !output.status.success() {
+            // Both tools exit non-zero when there is no such entry.
+            log::debug!(
+                "no keychain entry for service {} and account {provider}",
+                self.service
+            );
+            return Ok(None);
+        }
+        Ok(key_from(&output.stdout))
+    }
+}
+
+/// [`AuthProvider`]s asked in order, remembering what each provider's key
+/// turned out to be.
+#[derive(Default)]
+pub struct AuthChain {
+    providers: Vec<Box<dyn AuthProvider>>,
+    found: Mutex<HashMap<String, Option<SecretString>>>,
+}
+
+impl std::fmt::Debug for AuthChain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuthChain")
+            .field("providers", &self.describe())
+            .finish_non_exhaustive()
+    }
+}
+
+impl AuthChain {
+    /// `api_key_command`, `api_key_file`, and `api_key_keychain`, in that
+    /// order, for those that are set.
+    pub fn from_settings(settings: &NexusSettings) -> Self {
+        let mut chain = Self::default();
+        if !settings.api_key_command.is_empty() {
+            chain = chain.with_provider(CredentialCommand::new(settings.api_key_command.clone()));
+        }
+        if let Some(path) = &settings.api_key_file {
+            chain = chain.with_provider(KeyFile::new(path));
+        }
+        if let Some(service) = &settings.api_key_keychain {
+            chain = chain.with_provider(Keychain::new(service.clone()));
+        }
+        chain
+    }
+
+    /// Asks `provider` after those already in the chain.
+    pub fn with_provider(mut self, provider: impl AuthProvider + 'static) -> Self {
+        self.providers.push(Box::new(provider));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.providers.is_empty()
+    }
+
+    /// The sources asked, comma-separated.
+    pub fn describe(&self) -> String {
+        self.providers
+            .iter()
+            .map(|provider| provider.describe())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// The first key any source has for `provider`.
+    ///
+    /// # Errors
+    /// The error of the first source that failed to answer.
+    pub fn api_key(&self, provider: &str) -> Result<Option<SecretString>, NexusError> {
+        let mut found = self.found.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(key) = found.get(provider) {
+            return Ok(key.clone());
+        }
+        let mut key = None;
+        for source in &self.providers {
+            key = source.api_key(provider)?;
+            if key.is_some() {
+                log::debug!("API key for {provider} from {}", source.describe());
+                break;
+            }
+        }
+        found.insert(provider.to_string(), key.clone());
+        Ok(key)
+    }
+}
+
+/// The key in a source's raw output: its trimmed first line, if any.
+fn key_from(bytes: &[u8]) -> Option<SecretString> {
+    let text = String::from_utf8_lossy(bytes);
+    let key = text.lines().next()?.trim();
+    (!key.is_empty()).then(|| SecretString::from(key))
+}
+
+fn auth_error(message: String) -> NexusError {
+    NexusError::ConfigError {
+        message,
+        path: None,
+        source: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use secrecy::ExposeSecret;
+
+    fn exposed(key: Option<SecretString>) -> Option<String> {
+        key.map(|key| key.expose_secret().to_string())
+    }
+
+    #[test]
+    fn test_key_file_and_chain_order() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("key");
+        std::fs::write(&path, "sk-from-file\n").unwrap();
+
+        let settings = NexusSettings {
+            api_key_file: Some(path.display().to_string()),
+            ..NexusSettings::default()
+        };
+        let chain = AuthChain::from_settings(&settings);
+        assert_eq!(
+            exposed(chain.api_key("openai").unwrap()).as_deref(),
+            Some("sk-from-file")
+        );
+
+        // The key is remembered once found.
+        std::fs::remove_file(&path).unwrap();
+        assert!(chain.api_key("openai").unwrap().is_some());
+        assert!(chain.api_key("anthropic").is_err());
+        assert!(AuthChain::default().api_key("openai").unwrap().is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_credential_command_is_told_the_provider() {
+        let helper = CredentialCommand::new(vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            format!("echo \"sk-${AUTH_PROVIDER_ENV}\""),
+        ]);
+        assert_eq!(
+            exposed(helper.api_key("anthropic").unwrap()).as_deref(),
+            Some("sk-anthropic")
+        );
+
+        let failing = CredentialCommand::new(vec!["false".to_string()]);
+        let err = failing.api_key("openai").unwrap_err();
+        assert!(
+            err.to_string().contains("api_key_command `false` failed"),
+            "{err}"
+        );
+    }
+}
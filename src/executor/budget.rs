@@ -0,0 +1,187 @@
+//! Per-run caps on tokens and estimated cost.
+//!
+//! Usage is checked while a reply streams: until the provider reports real
+//! usage, which most send only with the final chunk, the prompt and the text
+//! received so far are estimated with [`tokens::estimate_tokens`]. A run
+//! past either cap is aborted with [`NexusError::BudgetExceeded`]. The cost
+//! cap only applies to models listed in [`pricing`].
+
+use super::{ChatChunk, UsageInfo, pricing, tokens};
+use crate::error::NexusError;
+use crate::types::NexusSettings;
+
+/// Token and cost limits for one run; `None` leaves a dimension unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RunBudget {
+    pub max_tokens: Option<u64>,
+    pub max_cost_usd: Option<f64>,
+}
+
+impl RunBudget {
+    /// Reads `max_run_tokens` and `max_run_cost_usd`.
+    pub fn from_settings(settings: &NexusSettings) -> Self {
+        Self {
+            max_tokens: settings.max_run_tokens,
+            max_cost_usd: settings.max_run_cost_usd,
+        }
+    }
+
+    pub fn is_unlimited(&self) -> bool {
+        self.max_tokens.is_none() && self.max_cost_usd.is_none()
+    }
+
+    /// Fails if `prompt_tokens` in and `completion_tokens` out of `model`
+    /// are past a limit.
+    pub fn check(
+        &self,
+        model: &str,
+        prompt_tokens: u64,
+        completion_tokens: u64,
+    ) -> Result<(), NexusError> {
+        let used = prompt_tokens + completion_tokens;
+        if let Some(max_tokens) = self.max_tokens.filter(|max| used > *max) {
+            return Err(NexusError::BudgetExceeded {
+                limit: format!("{max_tokens} tokens"),
+                spent: format!("{used} tokens"),
+            });
+        }
+        let Some(max_cost_usd) = self.max_cost_usd else {
+            return Ok(());
+        };
+        match pricing::cost_usd(model, prompt_tokens, completion_tokens) {
+            Some(cost) if cost > max_cost_usd => Err(NexusError::BudgetExceeded {
+                limit: format!("${max_cost_usd:.4}"),
+                spent: format!("${cost:.4}"),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Usage of the call in flight, reported or estimated, on top of what
+/// earlier calls of the run used.
+#[derive(Debug, Clone)]
+pub(crate) struct BudgetMeter {
+    budget: RunBudget,
+    model: String,
+    spent: UsageInfo,
+    prompt_estimate: u64,
+    completion_estimate: u64,
+    reported: Option<UsageInfo>,
+}
+
+impl BudgetMeter {
+    pub(crate) fn new(
+        budget: RunBudget,
+        model: &str,
+        spent: UsageInfo,
+        prompt_estimate: u64,
+    ) -> Self {
+        Self {
+            budget,
+            model: model.to_string(),
+            spent,
+            prompt_estimate,
+            completion_estimate: 0,
+            reported: None,
+        }
+    }
+
+    /// Counts what `chunk` carried: reply text, reasoning, and tool call
+    /// arguments toward the estimate, and reported usage in its place.
+    pub(crate) fn record(&mut self, chunk: &ChatChunk) {
+        if let Some(usage) = &chunk.usage {
+            self.reported = Some(usage.clone());
+        }
+        for choice in &chunk.choices {
+            let delta = &choice.delta;
+            let arguments = delta
+                .tool_calls
+                .iter()
+                .flatten()
+                .filter_map(|call| call.function.as_ref()?.arguments.as_deref());
+            for text in [&delta.content, &delta.reasoning_content]
+                .into_iter()
+                .flatten()
+                .map(String::as_str)
+                .chain(arguments)
+            {
+                self.completion_estimate += tokens::estimate_tokens(text) as u64;
+            }
+        }
+    }
+
+    pub(crate) fn check(&self) -> Result<(), NexusError> {
+        let (prompt, completion) = match &self.reported {
+            Some(usage) => (
+                u64::from(usage.prompt_tokens),
+                u64::from(usage.completion_tokens),
+            ),
+            None => (self.prompt_estimate, self.completion_estimate),
+        };
+        self.budget.check(
+            &self.model,
+            u64::from(self.spent.prompt_tokens) + prompt,
+            u64::from(self.spent.completion_tokens) + completion,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_applies_each_limit() {
+        let budget = RunBudget {
+            max_tokens: Some(1_000),
+            max_cost_usd: Some(0.01),
+        };
+
+        assert!(budget.check("gpt-4o", 600, 400).is_ok());
+        let err = budget.check("gpt-4o", 600, 401).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "run budget exceeded: 1001 tokens spent, limit 1000 tokens"
+        );
+
+        let cost_only = RunBudget {
+            max_tokens: None,
+            max_cost_usd: Some(0.01),
+        };
+        assert!(cost_only.check("gpt-4o", 2_000, 500).is_ok());
+        assert!(cost_only.check("gpt-4o", 2_000, 501).is_err());
+        assert!(cost_only.check("llama3", 1_000_000, 1_000_000).is_ok());
+    }
+
+    #[test]
+    fn test_meter_prefers_reported_usage() {
+        let budget = RunBudget {
+            max_tokens: Some(100),
+            max_cost_usd: None,
+        };
+        let mut meter = BudgetMeter::new(budget, "gpt-4o", UsageInfo::default(), 90);
+        let chunk = |delta: &str| -> ChatChunk {
+            serde_json::from_str(&format!(
+                r#"{{"id":"c","object":"chat.completion.chunk","created":1,"model":"m","choices":[{{"index":0,"delta":{delta},"finish_reason":null}}]}}"#
+            ))
+            .unwrap()
+        };
+
+        meter.record(&chunk(r#"{"content":"12345678"}"#));
+        assert!(meter.check().is_ok());
+        meter.record(&chunk(
+            r#"{"reasoning_content":"xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx"}"#,
+        ));
+        assert!(meter.check().is_err());
+
+        let mut usage = chunk("{}");
+        usage.usage = Some(UsageInfo {
+            prompt_tokens: 50,
+            completion_tokens: 20,
+            total_tokens: 70,
+        });
+        meter.record(&usage);
+        assert!(meter.check().is_ok());
+    }
+}
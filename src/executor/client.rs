@@ -1,14 +1,21 @@
+use super::models::{ModelList, is_model_not_found};
+use super::network::NetworkConfig;
+use super::scheduler::RateLimitScheduler;
 use crate::error::NexusError;
+use crate::redact::Redactor;
 use bytes::Bytes;
 use futures::{Stream, StreamExt};
 use rand::Rng;
-use reqwest::header::{CONTENT_TYPE, HeaderMap, RETRY_AFTER};
+use reqwest::header::{
+    AUTHORIZATION, CONTENT_TYPE, HeaderMap, HeaderName, HeaderValue, RETRY_AFTER,
+};
 use reqwest::{Client, StatusCode};
 use secrecy::{ExposeSecret, SecretString};
 use serde::{Deserialize, Serialize};
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, VecDeque};
 use std::pin::Pin;
 use std::time::Duration;
+use tokio::sync::OnceCell;
 use tokio::time::sleep;
 use tokio_retry2::RetryError;
 use tokio_retry2::strategy::ExponentialBackoff;
@@ -16,13 +23,17 @@ use tokio_retry2::strategy::ExponentialBackoff;
 const DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
 const DEFAULT_MAX_RETRIES: usize = 3;
 const CHAT_COMPLETIONS_PATH: &str = "chat/completions";
+const MODELS_PATH: &str = "models";
 
 const RETRY_BASE_MILLIS: u64 = 100;
 const RETRY_MAX_SECS: u64 = 30;
 const RETRY_FACTOR: u64 = 2;
 const JITTER_DIVISOR: u128 = 2;
+/// Longest `Retry-After` honored by default; longer requests wait this long.
+const DEFAULT_MAX_RETRY_AFTER_SECS: u64 = 120;
 
 const REQUEST_TIMEOUT_SECS: u64 = 60;
+const STREAM_IDLE_TIMEOUT_SECS: u64 = 20;
 
 const SSE_DELIMITER: &[u8] = b"\n\n";
 const SSE_DATA_PREFIX: &str = "data:";
@@ -32,7 +43,15 @@ pub struct CodexClient {
     client: Client,
     api_key: SecretString,
     base_url: String,
+    extra_headers: HeaderMap,
     max_retries: usize,
+    max_retry_after: Duration,
+    idle_timeout: Duration,
+    redactor: Redactor,
+    scheduler: RateLimitScheduler,
+    validate_model: bool,
+    /// Fetched once per client; `None` if the provider could not list models.
+    models: OnceCell<Option<ModelList>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -46,6 +65,47 @@ pub struct ChatCompletionRequest {
     pub temperature: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stream_options: Option<StreamOptions>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<ResponseFormat>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<Tool>>,
+    /// `"auto"`, `"none"`, `"required"`, or an object naming one function.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<serde_json::Value>,
+}
+
+/// Shape the provider constrains the reply to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponseFormat {
+    Text,
+    JsonObject,
+    JsonSchema { json_schema: JsonSchemaFormat },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonSchemaFormat {
+    pub name: String,
+    pub schema: serde_json::Value,
+    /// Strict mode rejects schemas with optional properties, so the action
+    /// schema is sent non-strict.
+    pub strict: bool,
+}
+
+/// A function the model may call instead of answering in text.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tool {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub function: FunctionDefinition,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionDefinition {
+    pub name: String,
+    pub description: String,
+    /// JSON Schema of the arguments object.
+    pub parameters: serde_json::Value,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -75,9 +135,35 @@ pub struct ChunkChoice {
 pub struct Delta {
     pub content: Option<String>,
     pub role: Option<String>,
+    /// Reasoning streamed ahead of the answer by models that expose it:
+    /// `reasoning_content` (DeepSeek, vLLM) or `reasoning` (OpenRouter,
+    /// Ollama).
+    #[serde(default, alias = "reasoning", skip_serializing_if = "Option::is_none")]
+    pub reasoning_content: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<ToolCallDelta>>,
 }
 
+/// A fragment of a streamed tool call; fragments sharing `index` belong to
+/// the same call and their `arguments` concatenate.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCallDelta {
+    pub index: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function: Option<FunctionCallDelta>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionCallDelta {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct UsageInfo {
     pub prompt_tokens: u32,
     pub completion_tokens: u32,
@@ -91,19 +177,50 @@ pub struct StreamOptions {
 
 impl CodexClient {
     pub fn new(api_key: SecretString) -> Self {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
-            .build()
+        let client = build_http_client(&NetworkConfig::default())
             .expect("failed to build HTTP client - TLS backend unavailable");
 
+        let redactor = Redactor::new().with_secret(api_key.expose_secret());
         Self {
             client,
             api_key,
+            redactor,
             base_url: DEFAULT_BASE_URL.to_string(),
+            extra_headers: HeaderMap::new(),
             max_retries: DEFAULT_MAX_RETRIES,
+            max_retry_after: Duration::from_secs(DEFAULT_MAX_RETRY_AFTER_SECS),
+            idle_timeout: Duration::from_secs(STREAM_IDLE_TIMEOUT_SECS),
+            scheduler: RateLimitScheduler::new(),
+            validate_model: true,
+            models: OnceCell::new(),
         }
     }
 
+    /// Enables or disables checking the requested model against the
+    /// provider's model list before the first call.
+    pub fn with_model_validation(mut self, enabled: bool) -> Self {
+        self.validate_model = enabled;
+        self
+    }
+
+    /// Shares rate-limit pauses (and the concurrency cap, if any) with every
+    /// other client using `scheduler`.
+    pub fn with_scheduler(mut self, scheduler: RateLimitScheduler) -> Self {
+        self.scheduler = scheduler;
+        self
+    }
+
+    /// Replaces the redactor applied to API error bodies. The API key is always masked.
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = redactor.with_secret(self.api_key.expose_secret());
+        self
+    }
+
+    /// Redactor masking the API key and configured patterns.
+    pub fn redactor(&self) -> &Redactor {
+        &self.redactor
+    }
+
     pub fn with_base_url(mut self, url: impl Into<String>) -> Self {
         let trimmed = url.into().trim_end_matches('/').to_string();
         if trimmed.is_empty() {
@@ -119,54 +236,116 @@ impl CodexClient {
         self
     }
 
+    /// Sends `headers` (name to value) with every request, e.g. an
+    /// organization ID or a gateway's auth header. The headers Nexus sets
+    /// itself cannot be replaced.
+    pub fn with_extra_headers(
+        mut self,
+        headers: &BTreeMap<String, String>,
+    ) -> Result<Self, NexusError> {
+        self.extra_headers = parse_extra_headers(headers)?;
+        Ok(self)
+    }
+
+    /// Sends requests through `network`'s proxy, trusting its certificates.
+    pub fn with_network(mut self, network: &NetworkConfig) -> Result<Self, NexusError> {
+        self.client = build_http_client(network)?;
+        Ok(self)
+    }
+
+    /// Caps how long a rate-limited request waits for the server's
+    /// `Retry-After` before trying again.
+    pub fn with_max_retry_after(mut self, max: Duration) -> Self {
+        self.max_retry_after = max;
+        self
+    }
+
+    /// Sets the maximum time to wait between stream chunks before aborting.
+    ///
+    /// A zero duration disables stall detection.
+    pub fn with_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = timeout;
+        self
+    }
+
     pub async fn chat_completion_stream(
         &self,
         mut request: ChatCompletionRequest,
     ) -> Result<impl Stream<Item = Result<ChatChunk, NexusError>>, NexusError> {
         request.stream = true;
+        self.check_model(&request.model).await?;
         let response = self.send_with_retry(&request).await?;
         let bytes_stream = response.bytes_stream();
 
-        let state = StreamState::new(bytes_stream);
-        let stream = futures::stream::unfold(state, |mut state| async move {
-            loop {
-                if let Some(chunk) = state.pending.pop_front() {
-                    return Some((Ok(chunk), state));
-                }
-
-                if state.done {
-                    return None;
-                }
+        let state = StreamState::new(bytes_stream, self.idle_timeout);
+        Ok(chunk_stream(state))
+    }
 
-                match state.stream.next().await {
-                    Some(Ok(bytes)) => match state.consume_bytes(bytes) {
-                        Ok(done) => {
-                            state.done = done;
-                        }
-                        Err(err) => {
-                            state.done = true;
-                            return Some((Err(err), state));
-                        }
-                    },
-                    Some(Err(err)) => {
-                        state.done = true;
-                        return Some((Err(map_stream_error(err)), state));
-                    }
-                    None => {
-                        if !state.buffer.is_empty() {
-                            let err = NexusError::StreamInterrupted {
-                                message: "stream closed with incomplete event".to_string(),
-                            };
-                            state.done = true;
-                            return Some((Err(err), state));
-                        }
-                        return None;
+    /// The provider's model list, fetched on first use.
+    ///
+    /// Returns `None` if the provider does not support listing models or the
+    /// request failed; validation is then skipped rather than blocking runs.
+    pub async fn available_models(&self) -> Option<&ModelList> {
+        self.models
+            .get_or_init(|| async {
+                match self.fetch_models().await {
+                    Ok(models) => Some(models),
+                    Err(err) => {
+                        log::debug!("skipping model validation: {err}");
+                        None
                     }
                 }
-            }
-        });
+            })
+            .await
+            .as_ref()
+    }
 
-        Ok(stream)
+    /// Fails with `ModelNotAvailable` if the provider lists models and
+    /// `model` is not among them.
+    async fn check_model(&self, model: &str) -> Result<(), NexusError> {
+        if !self.validate_model {
+            return Ok(());
+        }
+        match self.available_models().await {
+            Some(models) if !models.contains(model) => Err(NexusError::ModelNotAvailable {
+                model: model.to_string(),
+                suggestions: models.suggestions(model),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    async fn fetch_models(&self) -> Result<ModelList, NexusError> {
+        let url = format!("{}/{}", self.base_url.trim_end_matches('/'), MODELS_PATH);
+        let _slot = self.scheduler.acquire().await;
+        let response = self
+            .client
+            .get(url)
+            .bearer_auth(self.api_key.expose_secret())
+            .headers(self.extra_headers.clone())
+            .send()
+            .await
+            .map_err(|err| match map_request_error(err) {
+                RetryError::Permanent(err) | RetryError::Transient { err, .. } => err,
+            })?;
+        let status = response.status();
+        let body = response.text().await.map_err(|err| NexusError::ApiError {
+            message: "failed to read model list".to_string(),
+            status_code: Some(status.as_u16()),
+            source: Some(Box::new(err)),
+        })?;
+        if !status.is_success() {
+            return Err(NexusError::ApiError {
+                message: format!("listing models failed with status {status}"),
+                status_code: Some(status.as_u16()),
+                source: None,
+            });
+        }
+        ModelList::from_response(&body).map_err(|err| NexusError::ApiError {
+            message: format!("unexpected model list response: {err}"),
+            status_code: Some(status.as_u16()),
+            source: None,
+        })
     }
 
     async fn send_with_retry(
@@ -177,26 +356,39 @@ impl CodexClient {
         let mut retries_remaining = self.max_retries;
 
         loop {
-            match self.send_request(request).await {
+            let slot = self.scheduler.acquire().await;
+            let result = self.send_request(request).await;
+            drop(slot);
+            match result {
                 Ok(response) => return Ok(response),
                 Err(RetryError::Permanent(err)) => return Err(err),
                 Err(RetryError::Transient { err, .. }) => {
+                    let delay = match (&err, backoff.next()) {
+                        (
+                            NexusError::RateLimited {
+                                retry_after: Some(retry_after),
+                            },
+                            backoff_delay,
+                        ) => Some(retry_after_delay(
+                            *retry_after,
+                            backoff_delay,
+                            self.max_retry_after,
+                        )),
+                        (_, backoff_delay) => backoff_delay,
+                    };
                     if retries_remaining == 0 {
                         return Err(err);
                     }
                     retries_remaining -= 1;
-
-                    let delay = match err {
-                        NexusError::RateLimited {
-                            retry_after: Some(retry_after),
-                        } => Duration::from_secs(retry_after),
-                        _ => match backoff.next() {
-                            Some(duration) => duration,
-                            None => return Err(err),
-                        },
+                    let Some(delay) = delay else {
+                        return Err(err);
                     };
 
-                    if !delay.is_zero() {
+                    if matches!(err, NexusError::RateLimited { .. }) {
+                        // The limit is shared: hold back every call, not just
+                        // this one. The pause is waited out in `acquire`.
+                        self.scheduler.pause(delay);
+                    } else if !delay.is_zero() {
                         sleep(delay).await;
                     }
                 }
@@ -218,6 +410,7 @@ impl CodexClient {
             .post(url)
             .header(CONTENT_TYPE, "application/json")
             .bearer_auth(self.api_key.expose_secret())
+            .headers(self.extra_headers.clone())
             .json(request)
             .send()
             .await
@@ -244,10 +437,25 @@ impl CodexClient {
             classify_status_error(status, api_error)
         })?;
 
+        if matches!(status, StatusCode::NOT_FOUND | StatusCode::BAD_REQUEST)
+            && is_model_not_found(&body)
+        {
+            let suggestions = self
+                .models
+                .get()
+                .and_then(Option::as_ref)
+                .map(|models| models.suggestions(&request.model))
+                .unwrap_or_default();
+            return Err(RetryError::permanent(NexusError::ModelNotAvailable {
+                model: request.model.clone(),
+                suggestions,
+            }));
+        }
+
         let message = if body.is_empty() {
             format!("request failed with status {}", status)
         } else {
-            body
+            self.redactor.redact(&body)
         };
         let api_error = NexusError::ApiError {
             message,
@@ -263,30 +471,134 @@ struct StreamState {
     buffer: Vec<u8>,
     pending: VecDeque<ChatChunk>,
     done: bool,
+    idle_timeout: Duration,
 }
 
 impl StreamState {
-    fn new(stream: impl Stream<Item = Result<Bytes, reqwest::Error>> + Send + 'static) -> Self {
+    fn new(
+        stream: impl Stream<Item = Result<Bytes, reqwest::Error>> + Send + 'static,
+        idle_timeout: Duration,
+    ) -> Self {
         Self {
             stream: Box::pin(stream),
             buffer: Vec::new(),
             pending: VecDeque::new(),
             done: false,
+            idle_timeout,
         }
     }
 
+    /// Waits for the next byte chunk, failing if the stream stalls past the idle timeout.
+    async fn next_bytes(&mut self) -> Result<Option<Result<Bytes, reqwest::Error>>, NexusError> {
+        if self.idle_timeout.is_zero() {
+            return Ok(self.stream.next().await);
+        }
+
+        tokio::time::timeout(self.idle_timeout, self.stream.next())
+            .await
+            .map_err(|_| NexusError::StreamInterrupted {
+                message: format!(
+                    "stream stalled: no data received for {}ms",
+                    self.idle_timeout.as_millis()
+                ),
+            })
+    }
+
     fn consume_bytes(&mut self, bytes: Bytes) -> Result<bool, NexusError> {
         self.buffer.extend_from_slice(&bytes);
         parse_sse_events(&mut self.buffer, &mut self.pending)
     }
 }
 
+/// Converts raw SSE bytes into a stream of parsed chat chunks.
+fn chunk_stream(state: StreamState) -> impl Stream<Item = Result<ChatChunk, NexusError>> {
+    futures::stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(chunk) = state.pending.pop_front() {
+                return Some((Ok(chunk), state));
+            }
+
+            if state.done {
+                return None;
+            }
+
+            let next = match state.next_bytes().await {
+                Ok(next) => next,
+                Err(err) => {
+                    state.done = true;
+                    return Some((Err(err), state));
+                }
+            };
+
+            match next {
+                Some(Ok(bytes)) => match state.consume_bytes(bytes) {
+                    Ok(done) => {
+                        state.done = done;
+                    }
+                    Err(err) => {
+                        state.done = true;
+                        return Some((Err(err), state));
+                    }
+                },
+                Some(Err(err)) => {
+                    state.done = true;
+                    return Some((Err(map_stream_error(err)), state));
+                }
+                None => {
+                    if !state.buffer.is_empty() {
+                        let err = NexusError::StreamInterrupted {
+                            message: "stream closed with incomplete event".to_string(),
+                        };
+                        state.done = true;
+                        return Some((Err(err), state));
+                    }
+                    return None;
+                }
+            }
+        }
+    })
+}
+
 enum StreamEvent {
     Chunk(ChatChunk),
     Done,
     Empty,
 }
 
+fn parse_extra_headers(headers: &BTreeMap<String, String>) -> Result<HeaderMap, NexusError> {
+    let invalid = |message: String| NexusError::ConfigError {
+        message,
+        path: None,
+        source: None,
+    };
+    let mut parsed = HeaderMap::new();
+    for (name, value) in headers {
+        let header = HeaderName::from_bytes(name.trim().as_bytes())
+            .map_err(|_| invalid(format!("invalid header name `{name}`")))?;
+        if header == AUTHORIZATION || header == CONTENT_TYPE {
+            return Err(invalid(format!("header `{header}` is set by Nexus")));
+        }
+        let mut value = HeaderValue::from_str(value.trim())
+            .map_err(|_| invalid(format!("invalid value for header `{name}`")))?;
+        // Gateway credentials must not show up in debug output.
+        value.set_sensitive(true);
+        parsed.insert(header, value);
+    }
+    Ok(parsed)
+}
+
+fn build_http_client(network: &NetworkConfig) -> Result<Client, NexusError> {
+    let builder = Client::builder().timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS));
+    network
+        .apply(builder)?
+        .build()
+        .map_err(|err| NexusError::ConfigError {
+            message: "failed to build HTTP client".to_string(),
+            path: None,
+            source: Some(Box::new(err)),
+        })
+}
+
 fn build_retry_strategy(max_retries: usize) -> impl Iterator<Item = Duration> {
     ExponentialBackoff::from_millis(RETRY_BASE_MILLIS)
         .factor(RETRY_FACTOR)
@@ -341,6 +653,13 @@ fn is_retryable_status(status: StatusCode) -> bool {
         || status.is_server_error()
 }
 
+/// Waits at least as long as the server asked, up to `max`, and never less
+/// than the regular backoff.
+fn retry_after_delay(retry_after_secs: u64, backoff: Option<Duration>, max: Duration) -> Duration {
+    let requested = Duration::from_secs(retry_after_secs).min(max);
+    backoff.map_or(requested, |backoff| backoff.max(requested))
+}
+
 fn parse_retry_after(headers: &HeaderMap) -> Option<u64> {
     headers
         .get(RETRY_AFTER)
@@ -365,10 +684,7 @@ fn parse_sse_events(
     pending: &mut VecDeque<ChatChunk>,
 ) -> Result<bool, NexusError> {
     let mut done = false;
-    loop {
-        let Some(delimiter_index) = find_delimiter(buffer) else {
-            break;
-        };
+    while let Some(delimiter_index) = find_delimiter(buffer) {
         let event_bytes: Vec<u8> = buffer.drain(..delimiter_index).collect();
         buffer.drain(..SSE_DELIMITER.len());
 
@@ -486,4 +802,95 @@ mod tests {
         assert_eq!(client.base_url, CUSTOM_BASE_URL);
         assert_eq!(client.max_retries, CUSTOM_MAX_RETRIES);
     }
+
+    #[test]
+    fn test_retry_after_delay_honors_server_up_to_cap() {
+        let cap = Duration::from_secs(10);
+        let backoff = Some(Duration::from_millis(200));
+
+        assert_eq!(retry_after_delay(3, backoff, cap), Duration::from_secs(3));
+        assert_eq!(
+            retry_after_delay(0, backoff, cap),
+            Duration::from_millis(200)
+        );
+        assert_eq!(retry_after_delay(600, backoff, cap), cap);
+        assert_eq!(retry_after_delay(3, None, cap), Duration::from_secs(3));
+    }
+
+    #[test]
+    fn test_extra_headers_are_validated() {
+        let headers = |pairs: &[(&str, &str)]| -> BTreeMap<String, String> {
+            pairs
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect()
+        };
+
+        let parsed =
+            parse_extra_headers(&headers(&[("OpenAI-Organization", " org-123 ")])).unwrap();
+        assert_eq!(parsed["openai-organization"], "org-123");
+        assert!(parsed["openai-organization"].is_sensitive());
+
+        for bad in [
+            headers(&[("Authorization", "Bearer other")]),
+            headers(&[("bad header", "x")]),
+            headers(&[("X-Gateway", "line\nbreak")]),
+        ] {
+            assert!(matches!(
+                parse_extra_headers(&bad),
+                Err(NexusError::ConfigError { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_with_idle_timeout_sets_value() {
+        // Arrange
+        let api_key = SecretString::from(TEST_API_KEY);
+
+        // Act
+        let client = CodexClient::new(api_key).with_idle_timeout(Duration::from_secs(5));
+
+        // Assert
+        assert_eq!(client.idle_timeout, Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_chunk_stream_aborts_on_stall() {
+        // Arrange
+        let stalled = futures::stream::pending::<Result<Bytes, reqwest::Error>>();
+        let state = StreamState::new(stalled, Duration::from_millis(20));
+
+        // Act
+        let mut stream = Box::pin(chunk_stream(state));
+        let first = stream.next().await;
+        let second = stream.next().await;
+
+        // Assert
+        match first {
+            Some(Err(NexusError::StreamInterrupted { message })) => {
+                assert!(message.contains("stalled"), "unexpected message: {message}");
+                assert!(message.contains("20ms"), "unexpected message: {message}");
+            }
+            other => panic!("expected stall error, got {other:?}"),
+        }
+        assert!(second.is_none(), "stream should end after a stall");
+    }
+
+    #[tokio::test]
+    async fn test_chunk_stream_parses_events_within_timeout() {
+        // Arrange
+        let body = Bytes::from_static(
+            b"data: {\"id\":\"1\",\"object\":\"chat.completion.chunk\",\"created\":1,\"model\":\"m\",\"choices\":[]}\n\ndata: [DONE]\n\n",
+        );
+        let bytes = futures::stream::iter(vec![Ok::<_, reqwest::Error>(body)]);
+        let state = StreamState::new(bytes, Duration::from_secs(1));
+
+        // Act
+        let chunks: Vec<_> = chunk_stream(state).collect().await;
+
+        // Assert
+        assert_eq!(chunks.len(), 1);
+        assert!(chunks[0].is_ok());
+    }
 }
@@ -2,20 +2,34 @@ use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
 pub mod adapter;
+pub mod auth;
+pub mod budget;
 pub mod client;
+pub mod models;
+pub mod network;
 pub mod parser;
+pub mod pricing;
 pub mod prompt;
+pub mod provider;
+pub mod scheduler;
 pub mod streaming;
+pub mod tokens;
+pub mod tools;
 
 pub use adapter::CodexAdapter;
+pub use auth::{AuthChain, AuthProvider};
+pub use budget::RunBudget;
 pub use client::{ChatChunk, ChatCompletionRequest, ChatMessage, UsageInfo};
-pub use parser::ResponseParser;
-pub use prompt::PromptBuilder;
+pub use network::NetworkConfig;
+pub use parser::{ParserLimits, ResponseParser};
+pub use prompt::{CONVENTIONS_PATH, OmittedFile, PROMPTS_DIR, PromptBuilder, PromptExample};
+pub use provider::{Provider, ProviderRegistry};
+pub use scheduler::RateLimitScheduler;
 pub use streaming::StreamHandler;
 
 use crate::error::NexusError;
 pub use crate::types::PatchFormat;
-use crate::types::ProposedAction;
+use crate::types::{AgentRole, ProposedAction};
 
 #[async_trait]
 pub trait Executor: Send + Sync {
@@ -51,6 +65,52 @@ pub struct ExecuteOptions {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub temperature: Option<f32>,
     pub preferred_format: PatchFormat,
+    #[serde(default)]
+    pub output: OutputFormat,
+    /// Task template from `.nexus/prompts/tasks/` rendered instead of the
+    /// user template.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+}
+
+/// A pass of an agent over a run after its first executor pass: a plan
+/// step or an accepted handoff. Artifacts and action IDs of the phase carry
+/// its label so they stay apart from those of other phases.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunPhase {
+    pub role: AgentRole,
+    pub label: String,
+}
+
+impl RunPhase {
+    /// The executor working on step `index` (from 1) of the run's plan.
+    pub fn plan_step(index: usize) -> Self {
+        Self {
+            role: AgentRole::Executor,
+            label: format!("s{index}"),
+        }
+    }
+
+    /// `role` taking over through the run's `index`-th (from 1) handoff.
+    pub fn handoff(index: usize, role: AgentRole) -> Self {
+        Self {
+            role,
+            label: format!("h{index}"),
+        }
+    }
+}
+
+/// How the model is asked to shape its reply.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputFormat {
+    /// Free text; diffs, search/replace blocks, or JSON are found by
+    /// [`ResponseParser`].
+    #[default]
+    Text,
+    /// A JSON object of proposed actions, constrained by the provider
+    /// through `response_format: json_schema`.
+    JsonSchema,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,113 @@
+//! The provider's model list, used to validate the configured model before
+//! the first real call of a run.
+
+use serde::Deserialize;
+
+/// Suggestions offered for an unknown model.
+const MAX_SUGGESTIONS: usize = 3;
+/// Minimum normalized similarity for a model to be suggested.
+const MIN_SIMILARITY: f64 = 0.5;
+
+/// Model IDs the provider reports as available.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelList {
+    ids: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct ModelsResponse {
+    data: Vec<ModelEntry>,
+}
+
+#[derive(Deserialize)]
+struct ModelEntry {
+    id: String,
+}
+
+impl ModelList {
+    pub fn new(ids: Vec<String>) -> Self {
+        Self { ids }
+    }
+
+    /// Parses an OpenAI-style `GET /models` response body.
+    pub fn from_response(body: &str) -> Result<Self, serde_json::Error> {
+        let response: ModelsResponse = serde_json::from_str(body)?;
+        Ok(Self::new(
+            response.data.into_iter().map(|entry| entry.id).collect(),
+        ))
+    }
+
+    pub fn contains(&self, model: &str) -> bool {
+        self.ids.iter().any(|id| id == model)
+    }
+
+    /// Available models closest to `model`, best first.
+    pub fn suggestions(&self, model: &str) -> Vec<String> {
+        let wanted = model.to_ascii_lowercase();
+        let mut scored: Vec<(f64, &String)> = self
+            .ids
+            .iter()
+            .map(|id| {
+                let candidate = id.to_ascii_lowercase();
+                let mut score = strsim::normalized_levenshtein(&wanted, &candidate);
+                if candidate.starts_with(&wanted) || wanted.starts_with(&candidate) {
+                    score = score.max(MIN_SIMILARITY);
+                }
+                (score, id)
+            })
+            .filter(|(score, _)| *score >= MIN_SIMILARITY)
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+        scored
+            .into_iter()
+            .take(MAX_SUGGESTIONS)
+            .map(|(_, id)| id.clone())
+            .collect()
+    }
+}
+
+/// True if an error response body says the requested model does not exist.
+pub fn is_model_not_found(body: &str) -> bool {
+    let body = body.to_ascii_lowercase();
+    body.contains("model_not_found") || (body.contains("model") && body.contains("does not exist"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list() -> ModelList {
+        ModelList::from_response(
+            r#"{"object":"list","data":[
+                {"id":"gpt-4o","object":"model"},
+                {"id":"gpt-4o-mini","object":"model"},
+                {"id":"gpt-5.2-codex","object":"model"},
+                {"id":"text-embedding-3-small","object":"model"}
+            ]}"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_contains() {
+        assert!(list().contains("gpt-4o"));
+        assert!(!list().contains("gpt-4"));
+    }
+
+    #[test]
+    fn test_suggestions_rank_close_models() {
+        assert_eq!(list().suggestions("gpt-5.2-codx"), ["gpt-5.2-codex"]);
+        assert_eq!(list().suggestions("gpt-4o-mni")[0], "gpt-4o-mini");
+        assert!(list().suggestions("claude").is_empty());
+    }
+
+    #[test]
+    fn test_is_model_not_found() {
+        assert!(is_model_not_found(
+            r#"{"error":{"message":"The model `gpt-9` does not exist or you do not have access to it.","code":"model_not_found"}}"#
+        ));
+        assert!(!is_model_not_found(
+            r#"{"error":{"message":"Invalid URL"}}"#
+        ));
+    }
+}
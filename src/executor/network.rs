@@ -0,0 +1,162 @@
+//! Proxy and trusted certificates for connections to the provider.
+//!
+//! Networks that only reach the internet through an intercepting proxy need
+//! both: the proxy URL, and the proxy's root certificate so its TLS is
+//! trusted. Each comes from its environment variable if set, else from
+//! settings. Without either, reqwest's defaults apply, which already honor
+//! `HTTPS_PROXY` and the system trust store.
+
+use std::path::{Path, PathBuf};
+
+use reqwest::{Certificate, ClientBuilder, Proxy};
+
+use crate::error::NexusError;
+use crate::types::NexusSettings;
+
+/// Overrides the `proxy` setting.
+pub const PROXY_ENV: &str = "NEXUS_PROXY";
+/// Overrides the `ca_cert` setting.
+pub const CA_CERT_ENV: &str = "NEXUS_CA_CERT";
+
+/// How to reach the provider.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NetworkConfig {
+    /// Proxy for every request, e.g. `http://proxy.corp:3128`.
+    pub proxy: Option<String>,
+    /// PEM file of extra root certificates to trust.
+    pub ca_cert: Option<PathBuf>,
+}
+
+impl NetworkConfig {
+    /// Reads `proxy` and `ca_cert`, each overridden by its environment
+    /// variable.
+    pub fn from_settings(settings: &NexusSettings) -> Self {
+        Self::resolve(settings, |var| std::env::var(var).ok())
+    }
+
+    fn resolve(settings: &NexusSettings, env: impl Fn(&str) -> Option<String>) -> Self {
+        let env = |var: &str| env(var).filter(|value| !value.trim().is_empty());
+        Self {
+            proxy: env(PROXY_ENV).or_else(|| settings.proxy.clone()),
+            ca_cert: env(CA_CERT_ENV)
+                .map(PathBuf::from)
+                .or_else(|| settings.ca_cert.clone()),
+        }
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.proxy.is_none() && self.ca_cert.is_none()
+    }
+
+    /// Adds the proxy and certificates to `builder`.
+    pub(crate) fn apply(&self, mut builder: ClientBuilder) -> Result<ClientBuilder, NexusError> {
+        if let Some(url) = &self.proxy {
+            let proxy = Proxy::all(url).map_err(|err| NexusError::ConfigError {
+                message: format!("invalid proxy URL `{url}`"),
+                path: None,
+                source: Some(Box::new(err)),
+            })?;
+            builder = builder.proxy(proxy);
+        }
+        if let Some(path) = &self.ca_cert {
+            for certificate in read_certificates(path)? {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+        Ok(builder)
+    }
+}
+
+fn read_certificates(path: &Path) -> Result<Vec<Certificate>, NexusError> {
+    let pem = std::fs::read(path).map_err(|err| NexusError::IoError {
+        operation: "read CA certificate".to_string(),
+        path: path.to_path_buf(),
+        source: err,
+    })?;
+    let invalid = |message: String, source: Option<reqwest::Error>| NexusError::ConfigError {
+        message,
+        path: Some(path.to_path_buf()),
+        source: source.map(|err| Box::new(err) as _),
+    };
+    let certificates = Certificate::from_pem_bundle(&pem).map_err(|err| {
+        invalid(
+            format!("invalid CA certificate {}", path.display()),
+            Some(err),
+        )
+    })?;
+    if certificates.is_empty() {
+        return Err(invalid(
+            format!("no PEM certificates in {}", path.display()),
+            None,
+        ));
+    }
+    Ok(certificates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_CA: &str = "tests/fixtures/certs/test_ca.pem";
+
+    #[test]
+    fn test_environment_overrides_settings() {
+        let settings = NexusSettings {
+            proxy: Some("http://settings-proxy:3128".to_string()),
+            ca_cert: Some(PathBuf::from("settings.pem")),
+            ..NexusSettings::default()
+        };
+
+        let from_settings = NetworkConfig::resolve(&settings, |_| None);
+        assert_eq!(
+            from_settings.proxy.as_deref(),
+            Some("http://settings-proxy:3128")
+        );
+
+        let from_env = NetworkConfig::resolve(&settings, |var| match var {
+            PROXY_ENV => Some("http://env-proxy:8080".to_string()),
+            CA_CERT_ENV => Some(" ".to_string()),
+            _ => None,
+        });
+        assert_eq!(from_env.proxy.as_deref(), Some("http://env-proxy:8080"));
+        assert_eq!(from_env.ca_cert, Some(PathBuf::from("settings.pem")));
+        assert!(NetworkConfig::resolve(&NexusSettings::default(), |_| None).is_default());
+    }
+
+    #[test]
+    fn test_apply_loads_certificates_and_rejects_bad_input() {
+        let config = NetworkConfig {
+            proxy: Some("http://127.0.0.1:3128".to_string()),
+            ca_cert: Some(PathBuf::from(TEST_CA)),
+        };
+        assert!(config.apply(reqwest::Client::builder()).is_ok());
+
+        let missing = NetworkConfig {
+            ca_cert: Some(PathBuf::from("tests/fixtures/certs/missing.pem")),
+            ..NetworkConfig::default()
+        };
+        assert!(matches!(
+            missing.apply(reqwest::Client::builder()),
+            Err(NexusError::IoError { .. })
+        ));
+
+        let dir = tempfile::tempdir().unwrap();
+        let empty = dir.path().join("empty.pem");
+        std::fs::write(&empty, "not a certificate\n").unwrap();
+        let no_certs = NetworkConfig {
+            ca_cert: Some(empty),
+            ..NetworkConfig::default()
+        };
+        let err = no_certs.apply(reqwest::Client::builder()).err().unwrap();
+        assert!(err.to_string().contains("no PEM certificates"));
+
+        let bad_proxy = NetworkConfig {
+            proxy: Some("::not a url::".to_string()),
+            ..NetworkConfig::default()
+        };
+        assert!(matches!(
+            bad_proxy.apply(reqwest::Client::builder()),
+            Err(NexusError::ConfigError { .. })
+        ));
+    }
+}
@@ -3,11 +3,14 @@ use std::collections::HashSet;
 use std::sync::OnceLock;
 
 use regex::Regex;
+use serde::Deserialize;
 
+use crate::binary::binary_diff_target;
 use crate::error::NexusError;
+use crate::paths::normalize_separators;
 use crate::types::{
-    ActionDetails, ActionKindTag, MatchMode, PatchDetails, PatchFormat, ProposedAction,
-    SearchReplaceBlock,
+    ActionDetails, ActionId, ActionKindTag, MatchMode, PatchDetails, PatchFormat, ProposedAction,
+    RunId, SearchReplaceBlock,
 };
 
 const DEFAULT_RISK: u8 = 1;
@@ -16,12 +19,44 @@ const SINGLE_FILE_COUNT: usize = 1;
 const SUMMARY_DIFF_LINE_THRESHOLD: usize = 2;
 const JSON_KIND_KEY: &str = "\"kind\"";
 const JSON_DETAILS_KEY: &str = "\"details\"";
+const RATIONALE_PREFIX: &str = "why:";
+
+const DEFAULT_MAX_ACTIONS: usize = 200;
+const DEFAULT_MAX_DIFF_BYTES: usize = 1024 * 1024;
+const DEFAULT_MAX_INPUT_BYTES: usize = 4 * 1024 * 1024;
+const DEFAULT_MAX_BRACKET_DEPTH: usize = 64;
+
+/// Bounds applied to model output so hostile or broken responses cannot
+/// exhaust memory or CPU while parsing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParserLimits {
+    /// Maximum number of actions a single response may propose.
+    pub max_actions: usize,
+    /// Maximum size of one diff, in bytes.
+    pub max_diff_bytes: usize,
+    /// Maximum response size handed to the regex scanners, in bytes.
+    pub max_input_bytes: usize,
+    /// Maximum `[` nesting considered when scanning for inline JSON arrays.
+    pub max_bracket_depth: usize,
+}
+
+impl Default for ParserLimits {
+    fn default() -> Self {
+        Self {
+            max_actions: DEFAULT_MAX_ACTIONS,
+            max_diff_bytes: DEFAULT_MAX_DIFF_BYTES,
+            max_input_bytes: DEFAULT_MAX_INPUT_BYTES,
+            max_bracket_depth: DEFAULT_MAX_BRACKET_DEPTH,
+        }
+    }
+}
 
 pub struct ResponseParser {
     diff_fenced: OnceLock<Regex>,
     diff_raw: OnceLock<Regex>,
     search_replace: OnceLock<Regex>,
     json_fenced: OnceLock<Regex>,
+    limits: ParserLimits,
 }
 
 impl Default for ResponseParser {
@@ -37,11 +72,18 @@ impl ResponseParser {
             diff_raw: OnceLock::new(),
             search_replace: OnceLock::new(),
             json_fenced: OnceLock::new(),
+            limits: ParserLimits::default(),
         }
     }
 
+    pub fn with_limits(mut self, limits: ParserLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
     pub fn parse(&self, response: &str, run_id: &str) -> Result<Vec<ProposedAction>, NexusError> {
-        self.validate_run_id(run_id)?;
+        RunId::new(run_id)?;
+        self.check_input_size(response)?;
 
         let mut actions = self.parse_unified_diffs(response, run_id)?;
         if !actions.is_empty() {
@@ -61,10 +103,16 @@ impl ResponseParser {
         response: &str,
         run_id: &str,
     ) -> Result<Vec<ProposedAction>, NexusError> {
-        self.validate_run_id(run_id)?;
+        let run_id = RunId::new(run_id)?;
+        self.check_input_size(response)?;
         let normalized = normalize_line_endings(response);
         let diffs = self.collect_unified_diffs(&normalized);
-        Ok(self.build_patch_actions_from_diffs(diffs, run_id))
+        self.check_action_count(diffs.len())?;
+        for (diff, _) in &diffs {
+            self.check_diff_size(diff)?;
+            reject_binary_diff(diff)?;
+        }
+        Ok(self.build_patch_actions_from_diffs(diffs, &run_id))
     }
 
     pub fn parse_search_replace(
@@ -72,19 +120,59 @@ impl ResponseParser {
         response: &str,
         run_id: &str,
     ) -> Result<Vec<ProposedAction>, NexusError> {
-        self.validate_run_id(run_id)?;
+        let run_id = RunId::new(run_id)?;
+        self.check_input_size(response)?;
         let normalized = normalize_line_endings(response);
         let blocks = self.collect_search_replace_blocks(&normalized);
-        Ok(self.build_search_replace_actions(blocks, run_id))
+        self.check_action_count(blocks.len())?;
+        Ok(self.build_search_replace_actions(blocks, &run_id))
     }
 
     pub fn parse_json_actions(&self, response: &str) -> Result<Vec<ProposedAction>, NexusError> {
+        self.check_input_size(response)?;
         let normalized = normalize_line_endings(response);
-        if let Some(actions) = self.parse_fenced_json_actions(&normalized)? {
-            return Ok(actions);
-        }
+        let actions = match self.parse_fenced_json_actions(&normalized)? {
+            Some(actions) => actions,
+            None => self.parse_inline_json_actions(&normalized)?,
+        };
+        self.check_json_actions(actions)
+    }
 
-        self.parse_inline_json_actions(&normalized)
+    /// Parses `{"actions": [...]}`, the arguments of a `propose_actions`
+    /// call or a reply under the `json_schema` response format.
+    ///
+    /// # Errors
+    ///
+    /// Returns `NexusError::JsonError` if the input does not match the
+    /// schema, and the usual limit errors for oversized output.
+    pub fn parse_structured_actions(
+        &self,
+        arguments: &str,
+    ) -> Result<Vec<ProposedAction>, NexusError> {
+        self.check_input_size(arguments)?;
+        let parsed: StructuredActions =
+            serde_json::from_str(arguments).map_err(|source| NexusError::JsonError {
+                context: "Failed to parse structured actions".to_string(),
+                source,
+            })?;
+        self.check_json_actions(parsed.actions)
+    }
+
+    fn check_json_actions(
+        &self,
+        actions: Vec<ProposedAction>,
+    ) -> Result<Vec<ProposedAction>, NexusError> {
+        self.check_action_count(actions.len())?;
+        for action in &actions {
+            if let ActionDetails::Patch(PatchDetails {
+                diff: Some(diff), ..
+            }) = &action.details
+            {
+                self.check_diff_size(diff)?;
+            }
+            reject_binary_action(action)?;
+        }
+        Ok(actions)
     }
 
     pub fn extract_files_from_diff(&self, diff: &str) -> Vec<String> {
@@ -115,28 +203,20 @@ impl ResponseParser {
         format!("Apply patch to {} and {} other files", files[0], remaining)
     }
 
-    pub fn generate_action_id(&self, run_id: &str, index: usize) -> String {
-        format!("{run_id}-action-{index}")
+    pub fn generate_action_id(&self, run_id: &RunId, index: usize) -> ActionId {
+        ActionId::for_run(run_id, index)
     }
 
-    /// Validates a run_id for use in action IDs and event correlation.
-    ///
-    /// Validates that run_id is non-empty, contains no path separators or traversal
-    /// sequences, and does not exceed 255 characters. These checks match the validation
-    /// in the event_log module per ADR-014.
-    fn validate_run_id(&self, run_id: &str) -> Result<(), NexusError> {
-        if run_id.trim().is_empty() {
-            return Err(NexusError::InvalidRunId(run_id.to_string()));
-        }
-        // Path separator and traversal checks (matches event_log validation)
-        if run_id.contains('/') || run_id.contains('\\') || run_id.contains("..") {
-            return Err(NexusError::InvalidRunId(run_id.to_string()));
-        }
-        // Length check (255 chars max, matches filesystem constraints)
-        if run_id.len() > 255 {
-            return Err(NexusError::InvalidRunId(run_id.to_string()));
-        }
-        Ok(())
+    fn check_input_size(&self, response: &str) -> Result<(), NexusError> {
+        check_limit("response size", response.len(), self.limits.max_input_bytes)
+    }
+
+    fn check_action_count(&self, count: usize) -> Result<(), NexusError> {
+        check_limit("action count", count, self.limits.max_actions)
+    }
+
+    fn check_diff_size(&self, diff: &str) -> Result<(), NexusError> {
+        check_limit("diff size", diff.len(), self.limits.max_diff_bytes)
     }
 
     fn diff_fenced_regex(&self) -> &Regex {
@@ -166,13 +246,18 @@ impl ResponseParser {
         })
     }
 
-    fn collect_unified_diffs(&self, response: &str) -> Vec<String> {
+    /// Collects diffs paired with the `Why:` rationale written before each one.
+    fn collect_unified_diffs(&self, response: &str) -> Vec<(String, Option<String>)> {
         let mut diffs = Vec::new();
+        let mut previous_end = 0;
         for capture in self.diff_fenced_regex().captures_iter(response) {
+            let whole = capture.get(0).expect("capture group 0 always matches");
+            let why = rationale_before(&response[previous_end..whole.start()]);
+            previous_end = whole.end();
             if let Some(diff) = capture.name("diff") {
                 let trimmed = diff.as_str().trim();
                 if !trimmed.is_empty() {
-                    diffs.push(trimmed.to_string());
+                    diffs.push((trimmed.to_string(), why));
                 }
             }
         }
@@ -183,7 +268,7 @@ impl ResponseParser {
         diffs
     }
 
-    fn collect_raw_diff_blocks(&self, response: &str) -> Vec<String> {
+    fn collect_raw_diff_blocks(&self, response: &str) -> Vec<(String, Option<String>)> {
         let mut diffs = Vec::new();
         let mut starts: Vec<usize> = self
             .diff_raw_regex()
@@ -195,12 +280,15 @@ impl ResponseParser {
             return diffs;
         }
 
+        let mut why = rationale_before(&response[..starts[0]]);
         starts.push(response.len());
         for window in starts.windows(2) {
-            let diff = response[window[0]..window[1]].trim();
+            let (diff, next_why) = split_trailing_rationale(&response[window[0]..window[1]]);
+            let diff = diff.trim();
             if !diff.is_empty() {
-                diffs.push(diff.to_string());
+                diffs.push((diff.to_string(), why));
             }
+            why = next_why;
         }
 
         diffs
@@ -208,27 +296,37 @@ impl ResponseParser {
 
     fn build_patch_actions_from_diffs(
         &self,
-        diffs: Vec<String>,
-        run_id: &str,
+        diffs: Vec<(String, Option<String>)>,
+        run_id: &RunId,
     ) -> Vec<ProposedAction> {
         diffs
             .into_iter()
             .enumerate()
-            .map(|(index, diff)| {
+            .map(|(index, (diff, why))| {
                 let files = self.extract_files_from_diff(&diff);
                 let summary = self.generate_summary_from_diff(&diff, &files);
                 let details = patch_details_from_diff(diff, files.clone());
-                self.build_patch_action(run_id, index + ACTION_INDEX_BASE, summary, details)
+                let mut action =
+                    self.build_patch_action(run_id, index + ACTION_INDEX_BASE, summary, details);
+                action.why = why;
+                action
             })
             .collect()
     }
 
-    fn collect_search_replace_blocks(&self, response: &str) -> Vec<SearchReplaceBlock> {
+    fn collect_search_replace_blocks(
+        &self,
+        response: &str,
+    ) -> Vec<(SearchReplaceBlock, Option<String>)> {
         let mut blocks = Vec::new();
+        let mut previous_end = 0;
         for capture in self.search_replace_regex().captures_iter(response) {
+            let whole = capture.get(0).expect("capture group 0 always matches");
+            let why = rationale_before(&response[previous_end..whole.start()]);
+            previous_end = whole.end();
             let file = capture
                 .name("path")
-                .map(|value| value.as_str().trim().to_string())
+                .map(|value| normalize_separators(value.as_str().trim()))
                 .unwrap_or_default();
             let search = capture
                 .name("search")
@@ -239,12 +337,13 @@ impl ResponseParser {
                 .map(|value| value.as_str().to_string())
                 .unwrap_or_default();
 
-            blocks.push(SearchReplaceBlock {
+            let block = SearchReplaceBlock {
                 file,
                 search,
                 replace,
                 match_mode: MatchMode::Exact,
-            });
+            };
+            blocks.push((block, why));
         }
 
         blocks
@@ -252,29 +351,32 @@ impl ResponseParser {
 
     fn build_search_replace_actions(
         &self,
-        blocks: Vec<SearchReplaceBlock>,
-        run_id: &str,
+        blocks: Vec<(SearchReplaceBlock, Option<String>)>,
+        run_id: &RunId,
     ) -> Vec<ProposedAction> {
         blocks
             .into_iter()
             .enumerate()
-            .map(|(index, block)| {
+            .map(|(index, (block, why))| {
                 let summary = summary_from_search_replace(&block.file);
                 let details = patch_details_from_search_replace(block.clone());
-                self.build_patch_action(run_id, index + ACTION_INDEX_BASE, summary, details)
+                let mut action =
+                    self.build_patch_action(run_id, index + ACTION_INDEX_BASE, summary, details);
+                action.why = why;
+                action
             })
             .collect()
     }
 
     fn build_patch_action(
         &self,
-        run_id: &str,
+        run_id: &RunId,
         index: usize,
         summary: String,
         details: PatchDetails,
     ) -> ProposedAction {
         ProposedAction {
-            id: self.generate_action_id(run_id, index),
+            id: self.generate_action_id(run_id, index).into(),
             summary,
             why: None,
             risk: DEFAULT_RISK,
@@ -331,7 +433,7 @@ impl ResponseParser {
     ///
     /// Returns `NexusError::JsonError` if a candidate array is found but parsing fails.
     fn parse_inline_json_actions(&self, response: &str) -> Result<Vec<ProposedAction>, NexusError> {
-        for candidate in extract_json_arrays(response) {
+        for candidate in extract_json_arrays(response, self.limits.max_bracket_depth) {
             if !looks_like_action_array(&candidate) {
                 continue;
             }
@@ -342,6 +444,46 @@ impl ResponseParser {
     }
 }
 
+/// Returns the last `Why:` line in `text`, the rationale for the block that follows.
+fn rationale_before(text: &str) -> Option<String> {
+    text.lines().rev().find_map(parse_rationale_line)
+}
+
+/// Splits a trailing `Why:` line (belonging to the next block) off a raw diff.
+fn split_trailing_rationale(block: &str) -> (&str, Option<String>) {
+    let trimmed = block.trim_end();
+    let (head, last_line) = match trimmed.rfind('\n') {
+        Some(index) => (&trimmed[..index], &trimmed[index + 1..]),
+        None => return (block, None),
+    };
+    match parse_rationale_line(last_line) {
+        Some(why) => (head, Some(why)),
+        None => (block, None),
+    }
+}
+
+fn parse_rationale_line(line: &str) -> Option<String> {
+    let line = line.trim().trim_start_matches(['*', '_', '-', ' ']);
+    let prefix = line.get(..RATIONALE_PREFIX.len())?;
+    if !prefix.eq_ignore_ascii_case(RATIONALE_PREFIX) {
+        return None;
+    }
+    let why = line[RATIONALE_PREFIX.len()..]
+        .trim_start_matches(['*', '_'])
+        .trim();
+    (!why.is_empty()).then(|| why.to_string())
+}
+
+fn check_limit(what: &str, value: usize, max: usize) -> Result<(), NexusError> {
+    if value > max {
+        return Err(NexusError::ResponseParseFailed {
+            context: format!("{what} {value} exceeds limit of {max}"),
+            raw_response: None,
+        });
+    }
+    Ok(())
+}
+
 fn normalize_line_endings(input: &str) -> Cow<'_, str> {
     if input.contains("\r\n") {
         Cow::Owned(input.replace("\r\n", "\n"))
@@ -350,6 +492,39 @@ fn normalize_line_endings(input: &str) -> Cow<'_, str> {
     }
 }
 
+/// Rejects diffs that carry binary patches, which cannot be applied as text.
+fn reject_binary_diff(diff: &str) -> Result<(), NexusError> {
+    match binary_diff_target(diff) {
+        Some(path) => Err(NexusError::BinaryFile {
+            path,
+            reason: "patch targets a binary file".to_string(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Rejects JSON actions whose diff or whole-file content is binary.
+fn reject_binary_action(action: &ProposedAction) -> Result<(), NexusError> {
+    let ActionDetails::Patch(details) = &action.details else {
+        return Ok(());
+    };
+
+    if let Some(diff) = &details.diff {
+        reject_binary_diff(diff)?;
+    }
+    if let Some(contents) = &details.whole_file_content {
+        for (path, content) in contents {
+            if content.contains('\0') {
+                return Err(NexusError::BinaryFile {
+                    path: path.clone(),
+                    reason: "whole-file content contains NUL bytes".to_string(),
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
 fn extract_path_from_diff_line(line: &str) -> Option<String> {
     if !(line.starts_with("--- ") || line.starts_with("+++ ")) {
         return None;
@@ -361,6 +536,7 @@ fn extract_path_from_diff_line(line: &str) -> Option<String> {
         return None;
     }
 
+    let token = normalize_separators(token);
     let normalized = token.trim_start_matches("a/").trim_start_matches("b/");
     if normalized.is_empty() {
         return None;
@@ -410,6 +586,11 @@ fn summary_from_search_replace(file: &str) -> String {
     format!("Apply search/replace to {}", file)
 }
 
+#[derive(Deserialize)]
+struct StructuredActions {
+    actions: Vec<ProposedAction>,
+}
+
 fn parse_actions_from_json(json: &str) -> Result<Vec<ProposedAction>, NexusError> {
     serde_json::from_str::<Vec<ProposedAction>>(json).map_err(|source| NexusError::JsonError {
         context: "Failed to parse JSON actions".to_string(),
@@ -421,10 +602,13 @@ fn looks_like_action_array(candidate: &str) -> bool {
     candidate.contains(JSON_KIND_KEY) && candidate.contains(JSON_DETAILS_KEY)
 }
 
-fn extract_json_arrays(text: &str) -> Vec<String> {
+/// Collects balanced top-level `[...]` spans. Arrays nested deeper than
+/// `max_depth` are skipped rather than buffered.
+fn extract_json_arrays(text: &str, max_depth: usize) -> Vec<String> {
     let mut arrays = Vec::new();
     let mut start: Option<usize> = None;
     let mut depth = 0usize;
+    let mut too_deep = false;
     let mut in_string = false;
     let mut escape = false;
 
@@ -449,8 +633,12 @@ fn extract_json_arrays(text: &str) -> Vec<String> {
             '[' => {
                 if depth == 0 {
                     start = Some(index);
+                    too_deep = false;
                 }
                 depth += 1;
+                if depth > max_depth {
+                    too_deep = true;
+                }
             }
             ']' => {
                 if depth == 0 {
@@ -459,7 +647,9 @@ fn extract_json_arrays(text: &str) -> Vec<String> {
                 depth -= 1;
                 if depth == 0 {
                     if let Some(start_index) = start.take() {
-                        arrays.push(text[start_index..=index].to_string());
+                        if !too_deep {
+                            arrays.push(text[start_index..=index].to_string());
+                        }
                     }
                 }
             }
@@ -593,6 +783,45 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_rejects_binary_diff() {
+        let parser = ResponseParser::new();
+        let response = "```diff\ndiff --git a/logo.png b/logo.png\n--- a/logo.png\n+++ b/logo.png\nGIT binary patch\nliteral 4\n```\n";
+
+        let result = parser.parse(response, RUN_ID);
+
+        assert!(matches!(
+            result,
+            Err(NexusError::BinaryFile { ref path, .. }) if path == "logo.png"
+        ));
+    }
+
+    #[test]
+    fn test_parse_json_rejects_binary_whole_file() {
+        let parser = ResponseParser::new();
+        let response = "```json\n[{\"id\":\"a1\",\"summary\":\"s\",\"kind\":\"patch\",\"details\":{\"format\":\"whole_file\",\"whole_file_content\":{\"data.bin\":\"ab\\u0000cd\"}}}]\n```";
+
+        let result = parser.parse_json_actions(response);
+
+        assert!(matches!(result, Err(NexusError::BinaryFile { .. })));
+    }
+
+    #[test]
+    fn test_parse_structured_actions() {
+        let parser = ResponseParser::new();
+        let arguments = "{\"actions\":[{\"id\":\"a1\",\"summary\":\"Rename\",\"kind\":\"patch\",\"details\":{\"format\":\"unified\",\"diff\":\"--- a/x\\n+++ b/x\\n\"}}]}";
+
+        let actions = parser.parse_structured_actions(arguments).expect("parse");
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].id, "a1");
+        assert_eq!(actions[0].kind, ActionKindTag::Patch);
+
+        assert!(matches!(
+            parser.parse_structured_actions("[]"),
+            Err(NexusError::JsonError { .. })
+        ));
+    }
+
     #[test]
     fn test_parse_empty_response() {
         // Arrange
@@ -647,6 +876,19 @@ mod tests {
         assert_eq!(files, vec!["src/new.rs".to_string()]);
     }
 
+    #[test]
+    fn test_extract_files_normalizes_windows_separators() {
+        // Arrange
+        let parser = ResponseParser::new();
+        let diff = "--- a/src\\nested\\mod.rs\n+++ b/src\\nested\\mod.rs\n";
+
+        // Act
+        let files = parser.extract_files_from_diff(diff);
+
+        // Assert
+        assert_eq!(files, vec!["src/nested/mod.rs".to_string()]);
+    }
+
     #[test]
     fn test_generate_summary_single_file() {
         // Arrange
@@ -694,4 +936,82 @@ mod tests {
         // Assert
         assert_eq!(summary, "Apply patch");
     }
+
+    #[test]
+    fn test_parse_rejects_too_many_actions() {
+        let parser = ResponseParser::new().with_limits(ParserLimits {
+            max_actions: 1,
+            ..ParserLimits::default()
+        });
+        let response = "--- a/a.rs\n+++ b/a.rs\n@@ -1 +1 @@\n-a\n+b\n\
+                        --- a/b.rs\n+++ b/b.rs\n@@ -1 +1 @@\n-a\n+b\n";
+
+        let result = parser.parse(response, RUN_ID);
+        assert!(matches!(
+            result,
+            Err(NexusError::ResponseParseFailed { ref context, .. }) if context.contains("action count")
+        ));
+    }
+
+    #[test]
+    fn test_parse_rejects_oversized_diff_and_input() {
+        let parser = ResponseParser::new().with_limits(ParserLimits {
+            max_diff_bytes: 16,
+            max_input_bytes: 64,
+            ..ParserLimits::default()
+        });
+        let diff = "--- a/a.rs\n+++ b/a.rs\n@@ -1 +1 @@\n-a\n+b\n";
+        assert!(parser.parse(diff, RUN_ID).is_err());
+        assert!(parser.parse(&"x".repeat(65), RUN_ID).is_err());
+    }
+
+    #[test]
+    fn test_extract_json_arrays_skips_deep_nesting() {
+        let deep = format!("{}{}", "[".repeat(100), "]".repeat(100));
+        assert!(extract_json_arrays(&deep, DEFAULT_MAX_BRACKET_DEPTH).is_empty());
+        let text = format!("{deep} [1, [2]]");
+        assert_eq!(
+            extract_json_arrays(&text, DEFAULT_MAX_BRACKET_DEPTH),
+            vec!["[1, [2]]".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_parse_rationale_for_fenced_diffs() {
+        let parser = ResponseParser::new();
+        let response = "Why: the helper name is misleading.\n```diff\n--- a/a.rs\n+++ b/a.rs\n@@ -1 +1 @@\n-a\n+b\n```\n\
+                        No rationale here.\n```diff\n--- a/b.rs\n+++ b/b.rs\n@@ -1 +1 @@\n-a\n+b\n```\n";
+
+        let actions = parser.parse(response, RUN_ID).unwrap();
+        assert_eq!(
+            actions[0].why.as_deref(),
+            Some("the helper name is misleading.")
+        );
+        assert!(actions[1].why.is_none());
+    }
+
+    #[test]
+    fn test_parse_rationale_for_raw_diffs() {
+        let parser = ResponseParser::new();
+        let response = "**Why:** first reason\n--- a/a.rs\n+++ b/a.rs\n@@ -1 +1 @@\n-a\n+b\n\
+                        Why: second reason\n--- a/b.rs\n+++ b/b.rs\n@@ -1 +1 @@\n-a\n+b\n";
+
+        let actions = parser.parse(response, RUN_ID).unwrap();
+        assert_eq!(actions[0].why.as_deref(), Some("first reason"));
+        assert_eq!(actions[1].why.as_deref(), Some("second reason"));
+        let ActionDetails::Patch(details) = &actions[0].details else {
+            panic!("expected patch");
+        };
+        assert!(!details.diff.as_deref().unwrap().contains("second reason"));
+    }
+
+    #[test]
+    fn test_parse_rationale_for_search_replace() {
+        let parser = ResponseParser::new();
+        let response =
+            "Why: fix typo\n<<<<<<< SEARCH src/lib.rs\nteh\n=======\nthe\n>>>>>>> REPLACE\n";
+
+        let actions = parser.parse(response, RUN_ID).unwrap();
+        assert_eq!(actions[0].why.as_deref(), Some("fix typo"));
+    }
 }
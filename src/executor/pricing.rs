@@ -0,0 +1,89 @@
+//! Per-model token prices for estimating what a run cost.
+//!
+//! Prices are list prices in USD per million tokens. A model matches the
+//! longest entry its name starts with, so dated snapshots such as
+//! `gpt-4o-2024-08-06` share their family's price. Models not listed, such
+//! as local ones, have no cost.
+
+use super::UsageInfo;
+
+const TOKENS_PER_PRICE_UNIT: f64 = 1_000_000.0;
+
+/// Price of one model, in USD per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModelPrice {
+    pub input_per_mtok: f64,
+    pub output_per_mtok: f64,
+}
+
+const fn price(input_per_mtok: f64, output_per_mtok: f64) -> ModelPrice {
+    ModelPrice {
+        input_per_mtok,
+        output_per_mtok,
+    }
+}
+
+/// Model name prefixes and their prices.
+const PRICES: &[(&str, ModelPrice)] = &[
+    ("gpt-5.2-codex", price(1.75, 14.0)),
+    ("gpt-5.2", price(1.75, 14.0)),
+    ("gpt-5-codex", price(1.25, 10.0)),
+    ("gpt-5-mini", price(0.25, 2.0)),
+    ("gpt-5-nano", price(0.05, 0.4)),
+    ("gpt-5", price(1.25, 10.0)),
+    ("gpt-4.1-mini", price(0.4, 1.6)),
+    ("gpt-4.1-nano", price(0.1, 0.4)),
+    ("gpt-4.1", price(2.0, 8.0)),
+    ("gpt-4o-mini", price(0.15, 0.6)),
+    ("gpt-4o", price(2.5, 10.0)),
+    ("o4-mini", price(1.1, 4.4)),
+    ("o3", price(2.0, 8.0)),
+    ("claude-opus-4", price(15.0, 75.0)),
+    ("claude-sonnet-4", price(3.0, 15.0)),
+    ("claude-haiku-4", price(1.0, 5.0)),
+];
+
+/// Price of `model`, if it is listed.
+pub fn price_for(model: &str) -> Option<ModelPrice> {
+    PRICES
+        .iter()
+        .filter(|(prefix, _)| model.starts_with(prefix))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, price)| *price)
+}
+
+/// Estimated cost in USD of `prompt_tokens` in and `completion_tokens` out
+/// of `model`.
+pub fn cost_usd(model: &str, prompt_tokens: u64, completion_tokens: u64) -> Option<f64> {
+    let price = price_for(model)?;
+    Some(
+        (prompt_tokens as f64 * price.input_per_mtok
+            + completion_tokens as f64 * price.output_per_mtok)
+            / TOKENS_PER_PRICE_UNIT,
+    )
+}
+
+/// Estimated cost in USD of one call's `usage`.
+pub fn usage_cost_usd(model: &str, usage: &UsageInfo) -> Option<f64> {
+    cost_usd(
+        model,
+        u64::from(usage.prompt_tokens),
+        u64::from(usage.completion_tokens),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_longest_prefix_wins() {
+        assert_eq!(price_for("gpt-4o-mini-2024-07-18"), Some(price(0.15, 0.6)));
+        assert_eq!(price_for("gpt-4o-2024-08-06"), Some(price(2.5, 10.0)));
+        assert_eq!(price_for("qwen2.5-coder"), None);
+
+        let cost = cost_usd("gpt-4o", 1_000_000, 100_000).unwrap();
+        assert!((cost - 3.5).abs() < 1e-9);
+        assert_eq!(cost_usd("llama3", 10, 10), None);
+    }
+}
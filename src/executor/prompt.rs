@@ -1,8 +1,12 @@
+use minijinja::{Environment, context};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::Path;
 
 use super::FileContext;
-use crate::types::PatchFormat;
+use super::tokens::{estimate_tokens, truncate_to_tokens};
+use crate::error::NexusError;
+use crate::types::{NexusSettings, PatchFormat};
 
 const DEFAULT_SYSTEM_PROMPT: &str = r#"You are an expert code refactoring assistant. Your task is to generate precise code changes based on the user's request.
 
@@ -12,10 +16,13 @@ IMPORTANT RULES:
 3. Preserve existing code style and formatting
 4. Make minimal, focused changes
 5. Do not add unnecessary modifications
+6. Before each diff or search/replace block, write one line starting with
+   "Why:" explaining in one sentence why that change is needed
 
 OUTPUT FORMAT (choose one):
 
 Option A - Unified Diff:
+Why: <one-sentence rationale>
 ```diff
 --- a/path/to/file.rs
 +++ b/path/to/file.rs
@@ -27,6 +34,7 @@ Option A - Unified Diff:
 ```
 
 Option B - Search/Replace:
+Why: <one-sentence rationale>
 File: path/to/file.rs
 <<<<<<< SEARCH
 exact code to find
@@ -37,9 +45,60 @@ replacement code
 Always include enough context for unique matching.
 "#;
 
+const DEFAULT_USER_TEMPLATE: &str = "## Files
+
+{% for file in files %}### {{ file.path }}
+```{{ file.language }}
+{{ file.content }}```
+
+{% endfor %}{% if conventions %}## Conventions
+{{ conventions }}
+
+{% endif %}## Task
+{{ task }}
+
+## Preferred Format
+{{ format }}
+";
+
+/// Default location of template overrides, relative to the project root.
+pub const PROMPTS_DIR: &str = ".nexus/prompts";
+/// Project conventions injected into every prompt, relative to the project root.
+pub const CONVENTIONS_PATH: &str = ".nexus/conventions.md";
+/// Token cap applied to the conventions file by `PromptBuilder::for_project`.
+pub const DEFAULT_CONVENTIONS_MAX_TOKENS: usize = 2_000;
+const SYSTEM_TEMPLATE_FILE: &str = "system.j2";
+const SYSTEM_MARKDOWN_FILE: &str = "system.md";
+const USER_TEMPLATE_FILE: &str = "user.j2";
+/// Per-task templates, relative to the prompts directory.
+const TASK_TEMPLATES_DIR: &str = "tasks";
+const JINJA_EXTENSION: &str = "j2";
+const MARKDOWN_EXTENSION: &str = "md";
+/// Placeholders of `.md` templates and the minijinja each stands for;
+/// `{files}` is the files section of the default user template.
+const PLACEHOLDERS: &[(&str, &str)] = &[
+    ("{task}", "{{ task }}"),
+    (
+        "{files}",
+        "{% for file in files %}### {{ file.path }}\n```{{ file.language }}\n{{ file.content }}```\n\n{% endfor %}",
+    ),
+    ("{format}", "{{ format }}"),
+    ("{conventions}", "{{ conventions }}"),
+];
+const EXAMPLES_DIR: &str = "examples";
+const EXAMPLE_TASK_EXTENSION: &str = "task.md";
+const EXAMPLE_RESPONSE_EXTENSION: &str = "diff";
+/// Tokens the template spends on each file besides its content: the path
+/// heading and the code fence.
+const FILE_OVERHEAD_TOKENS: usize = 8;
+/// A file is only truncated if at least this much of it still fits;
+/// otherwise it is dropped.
+const MIN_TRUNCATED_TOKENS: usize = 64;
+const TRUNCATION_MARKER: &str = "[truncated to fit the context budget]\n";
 const DEFAULT_LANGUAGE_HINT: &str = "text";
 const ROLE_SYSTEM: &str = "system";
 const ROLE_USER: &str = "user";
+const ROLE_ASSISTANT: &str = "assistant";
 const FORMAT_UNIFIED: &str = "unified_diff";
 const FORMAT_SEARCH_REPLACE: &str = "search_replace";
 const FORMAT_WHOLE_FILE: &str = "whole_file";
@@ -50,8 +109,46 @@ pub struct ChatMessage {
     pub content: String,
 }
 
+/// A task with the response the model is expected to give, sent as prior turns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptExample {
+    pub task: String,
+    pub response: String,
+}
+
+impl PromptExample {
+    /// Loads `examples/<name>.task.md` and `examples/<name>.diff` from `prompts_dir`.
+    pub fn load(prompts_dir: &Path, name: &str) -> Result<Self, NexusError> {
+        let examples_dir = prompts_dir.join(EXAMPLES_DIR);
+        let read = |extension: &str| {
+            let path = examples_dir.join(format!("{name}.{extension}"));
+            read_optional(&path)?.ok_or_else(|| NexusError::ConfigError {
+                message: format!("prompt example '{name}' is missing {}", path.display()),
+                path: Some(path),
+                source: None,
+            })
+        };
+        Ok(Self {
+            task: read(EXAMPLE_TASK_EXTENSION)?.trim().to_string(),
+            response: read(EXAMPLE_RESPONSE_EXTENSION)?,
+        })
+    }
+}
+
+/// Renders the system and user messages from minijinja templates.
+///
+/// Templates see `task`, `files` (each with `path`, `language`, `content`),
+/// `format` (`unified_diff`, `search_replace`, `whole_file`), and
+/// `conventions` (empty unless configured). Templates written as `.md` are
+/// plain text instead, with `{task}`, `{files}`, `{format}`, and
+/// `{conventions}` replaced and every other brace kept as written.
 pub struct PromptBuilder {
     system_prompt: String,
+    user_template: String,
+    /// Templates a task can ask for in place of the user template, by name.
+    task_templates: BTreeMap<String, String>,
+    conventions: Option<String>,
+    examples: Vec<PromptExample>,
 }
 
 impl Default for PromptBuilder {
@@ -64,71 +161,348 @@ impl PromptBuilder {
     pub fn new() -> Self {
         Self {
             system_prompt: DEFAULT_SYSTEM_PROMPT.to_string(),
+            user_template: DEFAULT_USER_TEMPLATE.to_string(),
+            task_templates: BTreeMap::new(),
+            conventions: None,
+            examples: Vec::new(),
+        }
+    }
+
+    /// Loads overrides from a prompts directory such as `.nexus/prompts/`.
+    ///
+    /// `system.j2` (or `system.md`) and `user.j2` replace the built-in
+    /// templates, and each `tasks/<name>.j2` or `tasks/<name>.md` becomes a
+    /// task template named `<name>`. Where both exist the `.j2` file wins.
+    /// Missing files keep the defaults; templates with syntax errors are
+    /// rejected here.
+    pub fn from_dir(dir: &Path) -> Result<Self, NexusError> {
+        let mut builder = Self::new();
+        if let Some(template) = read_optional(&dir.join(SYSTEM_TEMPLATE_FILE))? {
+            builder.system_prompt = template;
+        } else if let Some(template) = read_optional(&dir.join(SYSTEM_MARKDOWN_FILE))? {
+            builder.system_prompt = markdown_template(&template);
         }
+        if let Some(template) = read_optional(&dir.join(USER_TEMPLATE_FILE))? {
+            builder.user_template = template;
+        }
+        builder.task_templates = read_task_templates(&dir.join(TASK_TEMPLATES_DIR))?;
+        builder
+            .environment()
+            .map_err(|err| NexusError::ConfigError {
+                message: format!("invalid prompt template: {err}"),
+                path: Some(dir.to_path_buf()),
+                source: None,
+            })?;
+        Ok(builder)
+    }
+
+    /// Loads templates from `prompts_dir` plus the examples named in settings.
+    pub fn from_settings(prompts_dir: &Path, settings: &NexusSettings) -> Result<Self, NexusError> {
+        let examples = settings
+            .prompt_examples
+            .iter()
+            .map(|name| PromptExample::load(prompts_dir, name))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::from_dir(prompts_dir)?.with_examples(examples))
+    }
+
+    /// Loads everything a project can customize: templates and examples from
+    /// `.nexus/prompts/` and conventions from `.nexus/conventions.md`.
+    pub fn for_project(root: &Path, settings: &NexusSettings) -> Result<Self, NexusError> {
+        Self::from_settings(&root.join(PROMPTS_DIR), settings)?
+            .with_conventions_file(&root.join(CONVENTIONS_PATH), DEFAULT_CONVENTIONS_MAX_TOKENS)
+    }
+
+    /// Reads conventions from `path` if it exists, truncated to `max_tokens`.
+    pub fn with_conventions_file(self, path: &Path, max_tokens: usize) -> Result<Self, NexusError> {
+        let Some(conventions) = read_optional(path)? else {
+            return Ok(self);
+        };
+        let conventions = match truncate_to_tokens(&conventions, max_tokens) {
+            Some(prefix) => {
+                log::warn!("{} exceeds {max_tokens} tokens; truncating", path.display());
+                format!("{prefix}\n[conventions truncated]")
+            }
+            None => conventions,
+        };
+        Ok(self.with_conventions(conventions))
     }
 
+    /// Adds few-shot examples sent between the system and user messages.
+    pub fn with_examples(mut self, examples: Vec<PromptExample>) -> Self {
+        self.examples = examples;
+        self
+    }
+
+    /// Replaces the system template.
     pub fn with_system_prompt(mut self, prompt: impl Into<String>) -> Self {
         self.system_prompt = prompt.into();
         self
     }
 
+    /// Replaces the user message template.
+    pub fn with_user_template(mut self, template: impl Into<String>) -> Self {
+        self.user_template = template.into();
+        self
+    }
+
+    /// Sets project conventions exposed to templates as `conventions`.
+    pub fn with_conventions(mut self, conventions: impl Into<String>) -> Self {
+        self.conventions = Some(conventions.into());
+        self
+    }
+
     pub fn build_messages(
         &self,
         task: &str,
         files: &[FileContext],
         preferred_format: PatchFormat,
-    ) -> Vec<ChatMessage> {
-        let user_message = build_user_message(task, files, preferred_format);
-
-        vec![
-            ChatMessage {
-                role: ROLE_SYSTEM.to_string(),
-                content: self.system_prompt.clone(),
-            },
-            ChatMessage {
+    ) -> Result<Vec<ChatMessage>, NexusError> {
+        self.build_messages_with_template(None, task, files, preferred_format)
+    }
+
+    /// Like [`Self::build_messages`], rendering the user message from the
+    /// task template `template` if one is named.
+    pub fn build_messages_with_template(
+        &self,
+        template: Option<&str>,
+        task: &str,
+        files: &[FileContext],
+        preferred_format: PatchFormat,
+    ) -> Result<Vec<ChatMessage>, NexusError> {
+        let user_template = match template {
+            Some(name) if self.task_templates.contains_key(name) => task_template_name(name),
+            Some(name) => {
+                return Err(NexusError::ConfigError {
+                    message: format!(
+                        "no prompt template named '{name}' in {PROMPTS_DIR}/{TASK_TEMPLATES_DIR}/"
+                    ),
+                    path: None,
+                    source: None,
+                });
+            }
+            None => USER_TEMPLATE_FILE.to_string(),
+        };
+        let env = self.environment().map_err(template_error)?;
+        let files: Vec<_> = files
+            .iter()
+            .map(|file| {
+                let mut content = file.content.clone();
+                if !content.ends_with('\n') {
+                    content.push('\n');
+                }
+                context! {
+                    path => file.path,
+                    language => language_hint(file),
+                    content => content,
+                }
+            })
+            .collect();
+        let ctx = context! {
+            task => task,
+            files => files,
+            format => format_label(preferred_format),
+            conventions => self.conventions.as_deref().unwrap_or_default().trim(),
+        };
+        let render = |name: &str| {
+            env.get_template(name)
+                .and_then(|template| template.render(&ctx))
+                .map_err(template_error)
+        };
+
+        let mut messages = vec![ChatMessage {
+            role: ROLE_SYSTEM.to_string(),
+            content: render(SYSTEM_TEMPLATE_FILE)?,
+        }];
+        for example in &self.examples {
+            messages.push(ChatMessage {
                 role: ROLE_USER.to_string(),
-                content: user_message,
-            },
-        ]
+                content: example.task.clone(),
+            });
+            messages.push(ChatMessage {
+                role: ROLE_ASSISTANT.to_string(),
+                content: example.response.clone(),
+            });
+        }
+        messages.push(ChatMessage {
+            role: ROLE_USER.to_string(),
+            content: render(&user_template)?,
+        });
+        Ok(messages)
+    }
+
+    fn environment(&self) -> Result<Environment<'_>, minijinja::Error> {
+        let mut env = Environment::new();
+        env.set_keep_trailing_newline(true);
+        env.add_template(SYSTEM_TEMPLATE_FILE, &self.system_prompt)?;
+        env.add_template(USER_TEMPLATE_FILE, &self.user_template)?;
+        for (name, template) in &self.task_templates {
+            env.add_template_owned(task_template_name(name), template.as_str())?;
+        }
+        Ok(env)
+    }
+}
+
+fn task_template_name(name: &str) -> String {
+    format!("{TASK_TEMPLATES_DIR}/{name}")
+}
+
+/// Reads every task template in `dir`, which need not exist.
+fn read_task_templates(dir: &Path) -> Result<BTreeMap<String, String>, NexusError> {
+    let io_error = |err| NexusError::IoError {
+        operation: "read prompt templates".to_string(),
+        path: dir.to_path_buf(),
+        source: err,
+    };
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(err) => return Err(io_error(err)),
+    };
+    let mut paths = entries
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(io_error)?;
+    paths.sort();
+
+    let mut templates = BTreeMap::new();
+    for path in paths {
+        let (Some(name), Some(extension)) = (
+            path.file_stem().and_then(|stem| stem.to_str()),
+            path.extension().and_then(|extension| extension.to_str()),
+        ) else {
+            continue;
+        };
+        let Some(template) = read_optional(&path)? else {
+            continue;
+        };
+        match extension {
+            JINJA_EXTENSION => {
+                templates.insert(name.to_string(), template);
+            }
+            MARKDOWN_EXTENSION => {
+                templates
+                    .entry(name.to_string())
+                    .or_insert_with(|| markdown_template(&template));
+            }
+            _ => {}
+        }
+    }
+    Ok(templates)
+}
+
+/// Turns a `.md` template into minijinja: each placeholder becomes what it
+/// stands for, and the text around them is kept verbatim.
+fn markdown_template(text: &str) -> String {
+    let mut template = String::with_capacity(text.len());
+    let mut rest = text;
+    loop {
+        let next = PLACEHOLDERS
+            .iter()
+            .filter_map(|&(placeholder, jinja)| Some((rest.find(placeholder)?, placeholder, jinja)))
+            .min_by_key(|&(at, _, _)| at);
+        let Some((at, placeholder, jinja)) = next else {
+            push_verbatim(&mut template, rest);
+            return template;
+        };
+        push_verbatim(&mut template, &rest[..at]);
+        template.push_str(jinja);
+        rest = &rest[at + placeholder.len()..];
+    }
+}
+
+fn push_verbatim(template: &mut String, text: &str) {
+    if !text.is_empty() {
+        template.push_str("{% raw %}");
+        template.push_str(text);
+        template.push_str("{% endraw %}");
     }
 }
 
-fn build_user_message(task: &str, files: &[FileContext], preferred_format: PatchFormat) -> String {
-    let mut message = String::new();
-    push_files_section(&mut message, files);
-    push_task_section(&mut message, task);
-    push_format_section(&mut message, preferred_format);
-    message
+/// A file cut short or left out by [`fit_to_budget`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OmittedFile {
+    pub path: String,
+    /// Estimated tokens of the whole file.
+    pub tokens: usize,
+    /// Estimated tokens of the prefix that was kept; zero if dropped.
+    pub kept_tokens: usize,
 }
 
-fn push_files_section(message: &mut String, files: &[FileContext]) {
-    message.push_str("## Files\n\n");
+/// Estimated prompt tokens `file` costs, including its heading.
+pub fn estimate_file_tokens(file: &FileContext) -> usize {
+    estimate_tokens(&file.path) + estimate_tokens(&file.content) + FILE_OVERHEAD_TOKENS
+}
+
+/// Keeps `files` within `budget` tokens.
+///
+/// Files are taken in the order given. Each one that fits is kept whole; the
+/// first that does not is cut at a line break to the remaining budget, or
+/// dropped if less than [`MIN_TRUNCATED_TOKENS`] would remain of it. Later
+/// files are kept only if they still fit whole. The same input always gives
+/// the same result.
+pub fn fit_to_budget(files: &[FileContext], budget: usize) -> (Vec<FileContext>, Vec<OmittedFile>) {
+    let mut remaining = budget;
+    let mut kept = Vec::with_capacity(files.len());
+    let mut omitted = Vec::new();
     for file in files {
-        let language_hint = language_hint(file);
-        message.push_str("### ");
-        message.push_str(&file.path);
-        message.push('\n');
-        message.push_str("```");
-        message.push_str(&language_hint);
-        message.push('\n');
-        message.push_str(&file.content);
-        if !file.content.ends_with('\n') {
-            message.push('\n');
+        let tokens = estimate_file_tokens(file);
+        if tokens <= remaining {
+            remaining -= tokens;
+            kept.push(file.clone());
+            continue;
+        }
+
+        let overhead = tokens - estimate_tokens(&file.content) + estimate_tokens(TRUNCATION_MARKER);
+        let room = remaining.saturating_sub(overhead);
+        let prefix = truncate_to_tokens(&file.content, room).unwrap_or(&file.content);
+        let kept_tokens = estimate_tokens(prefix);
+        if room < MIN_TRUNCATED_TOKENS || kept_tokens == 0 {
+            omitted.push(OmittedFile {
+                path: file.path.clone(),
+                tokens,
+                kept_tokens: 0,
+            });
+            continue;
         }
-        message.push_str("```\n\n");
+
+        remaining = remaining.saturating_sub(overhead + kept_tokens);
+        let mut content = prefix.to_string();
+        if !content.ends_with('\n') {
+            content.push('\n');
+        }
+        content.push_str(TRUNCATION_MARKER);
+        kept.push(FileContext {
+            content,
+            ..file.clone()
+        });
+        omitted.push(OmittedFile {
+            path: file.path.clone(),
+            tokens,
+            kept_tokens,
+        });
     }
+    (kept, omitted)
 }
 
-fn push_task_section(message: &mut String, task: &str) {
-    message.push_str("## Task\n");
-    message.push_str(task);
-    message.push_str("\n\n");
+fn template_error(err: minijinja::Error) -> NexusError {
+    NexusError::ConfigError {
+        message: format!("failed to render prompt template: {err}"),
+        path: None,
+        source: None,
+    }
 }
 
-fn push_format_section(message: &mut String, preferred_format: PatchFormat) {
-    message.push_str("## Preferred Format\n");
-    message.push_str(format_label(preferred_format));
-    message.push('\n');
+fn read_optional(path: &Path) -> Result<Option<String>, NexusError> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(NexusError::IoError {
+            operation: "read prompt template".to_string(),
+            path: path.to_path_buf(),
+            source: err,
+        }),
+    }
 }
 
 fn format_label(preferred_format: PatchFormat) -> &'static str {
@@ -150,7 +524,8 @@ fn language_hint(file: &FileContext) -> String {
     language_from_path(&file.path)
 }
 
-fn language_from_path(path: &str) -> String {
+/// Language hint for `path`, from its extension.
+pub(crate) fn language_from_path(path: &str) -> String {
     let extension = Path::new(path)
         .extension()
         .and_then(|ext| ext.to_str())
@@ -213,8 +588,9 @@ mod tests {
             language: None,
         }];
 
-        let messages =
-            PromptBuilder::new().build_messages("Refactor main", &files, PatchFormat::Unified);
+        let messages = PromptBuilder::new()
+            .build_messages("Refactor main", &files, PatchFormat::Unified)
+            .unwrap();
 
         assert_eq!(messages.len(), 2);
         assert_eq!(messages[0].role, ROLE_SYSTEM);
@@ -238,11 +614,9 @@ mod tests {
             language: Some("toml".to_string()),
         }];
 
-        let messages = PromptBuilder::new().build_messages(
-            "Update manifest",
-            &files,
-            PatchFormat::SearchReplace,
-        );
+        let messages = PromptBuilder::new()
+            .build_messages("Update manifest", &files, PatchFormat::SearchReplace)
+            .unwrap();
 
         assert!(
             messages[1]
@@ -264,8 +638,9 @@ mod tests {
             language: None,
         }];
 
-        let messages =
-            PromptBuilder::new().build_messages("Rewrite readme", &files, PatchFormat::WholeFile);
+        let messages = PromptBuilder::new()
+            .build_messages("Rewrite readme", &files, PatchFormat::WholeFile)
+            .unwrap();
 
         assert!(
             messages[1]
@@ -274,4 +649,246 @@ mod tests {
         );
         assert!(messages[1].content.contains("```markdown\n# Nexus\n```"));
     }
+
+    #[test]
+    fn build_messages_matches_legacy_layout() {
+        let files = vec![FileContext {
+            path: "a.txt".to_string(),
+            content: "no newline".to_string(),
+            language: None,
+        }];
+
+        let messages = PromptBuilder::new()
+            .build_messages("Do it", &files, PatchFormat::Unified)
+            .unwrap();
+
+        assert_eq!(messages[0].content, DEFAULT_SYSTEM_PROMPT);
+        assert_eq!(
+            messages[1].content,
+            "## Files\n\n### a.txt\n```txt\nno newline\n```\n\n## Task\nDo it\n\n## Preferred Format\nunified_diff\n"
+        );
+    }
+
+    #[test]
+    fn for_project_loads_templates_and_conventions() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let prompts = dir.path().join(PROMPTS_DIR);
+        std::fs::create_dir_all(&prompts).unwrap();
+        std::fs::write(
+            prompts.join(USER_TEMPLATE_FILE),
+            "{{ task }} as {{ format }} ({{ files | length }} files)\n{{ conventions }}",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join(CONVENTIONS_PATH), "Use snake_case.\n").unwrap();
+
+        let builder = PromptBuilder::for_project(dir.path(), &NexusSettings::default()).unwrap();
+        let messages = builder
+            .build_messages("Rename", &[], PatchFormat::SearchReplace)
+            .unwrap();
+
+        assert_eq!(messages[0].content, DEFAULT_SYSTEM_PROMPT);
+        assert_eq!(
+            messages[1].content,
+            "Rename as search_replace (0 files)\nUse snake_case."
+        );
+    }
+
+    #[test]
+    fn markdown_and_task_templates_interpolate_placeholders() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let tasks = dir.path().join(TASK_TEMPLATES_DIR);
+        std::fs::create_dir_all(&tasks).unwrap();
+        std::fs::write(
+            dir.path().join(SYSTEM_MARKDOWN_FILE),
+            "Team rules for {format}; keep {{ braces }} and {% this %}.\n",
+        )
+        .unwrap();
+        std::fs::write(
+            tasks.join("tests.md"),
+            "Add tests: {task}\n\n{files}{unknown}",
+        )
+        .unwrap();
+        std::fs::write(tasks.join("docs.md"), "ignored").unwrap();
+        std::fs::write(tasks.join("docs.j2"), "Docs for {{ task }}").unwrap();
+        let files = vec![FileContext {
+            path: "src/lib.rs".to_string(),
+            content: "pub fn a() {}".to_string(),
+            language: None,
+        }];
+
+        let builder = PromptBuilder::from_dir(dir.path()).unwrap();
+        let messages = builder
+            .build_messages_with_template(Some("tests"), "cover a", &files, PatchFormat::Unified)
+            .unwrap();
+        assert_eq!(
+            messages[0].content,
+            "Team rules for unified_diff; keep {{ braces }} and {% this %}.\n"
+        );
+        assert_eq!(
+            messages[1].content,
+            "Add tests: cover a\n\n### src/lib.rs\n```rust\npub fn a() {}\n```\n\n{unknown}"
+        );
+
+        let docs = builder
+            .build_messages_with_template(Some("docs"), "a", &[], PatchFormat::Unified)
+            .unwrap();
+        assert_eq!(docs[1].content, "Docs for a");
+        let err = builder
+            .build_messages_with_template(Some("absent"), "a", &[], PatchFormat::Unified)
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("no prompt template named 'absent'"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn default_template_includes_conventions_section() {
+        let messages = PromptBuilder::new()
+            .with_conventions("Prefer iterators.")
+            .build_messages("Refactor", &[], PatchFormat::Unified)
+            .unwrap();
+
+        assert!(
+            messages[1]
+                .content
+                .contains("## Conventions\nPrefer iterators.\n\n## Task")
+        );
+    }
+
+    #[test]
+    fn from_dir_rejects_invalid_template() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join(SYSTEM_TEMPLATE_FILE), "{% if %}").unwrap();
+
+        assert!(matches!(
+            PromptBuilder::from_dir(dir.path()),
+            Err(NexusError::ConfigError { .. })
+        ));
+    }
+
+    #[test]
+    fn from_settings_injects_examples_as_prior_turns() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let examples = dir.path().join(EXAMPLES_DIR);
+        std::fs::create_dir(&examples).unwrap();
+        std::fs::write(examples.join("rename.task.md"), "Rename foo to bar\n").unwrap();
+        std::fs::write(examples.join("rename.diff"), "--- a/x.rs\n+++ b/x.rs\n").unwrap();
+        let settings = NexusSettings {
+            prompt_examples: vec!["rename".to_string()],
+            ..NexusSettings::default()
+        };
+
+        let messages = PromptBuilder::from_settings(dir.path(), &settings)
+            .unwrap()
+            .build_messages("Real task", &[], PatchFormat::Unified)
+            .unwrap();
+
+        let roles: Vec<&str> = messages.iter().map(|m| m.role.as_str()).collect();
+        assert_eq!(roles, [ROLE_SYSTEM, ROLE_USER, ROLE_ASSISTANT, ROLE_USER]);
+        assert_eq!(messages[1].content, "Rename foo to bar");
+        assert_eq!(messages[2].content, "--- a/x.rs\n+++ b/x.rs\n");
+        assert!(messages[3].content.contains("Real task"));
+    }
+
+    #[test]
+    fn from_settings_reports_missing_example() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let settings = NexusSettings {
+            prompt_examples: vec!["absent".to_string()],
+            ..NexusSettings::default()
+        };
+
+        assert!(matches!(
+            PromptBuilder::from_settings(dir.path(), &settings),
+            Err(NexusError::ConfigError { .. })
+        ));
+    }
+
+    #[test]
+    fn with_conventions_file_caps_tokens() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("conventions.md");
+        std::fs::write(&path, "rule one\nrule two\nrule three\n").unwrap();
+
+        let messages = PromptBuilder::new()
+            .with_conventions_file(&path, 4)
+            .unwrap()
+            .build_messages("Task", &[], PatchFormat::Unified)
+            .unwrap();
+
+        assert!(
+            messages[1]
+                .content
+                .contains("## Conventions\nrule one\n\n[conventions truncated]\n\n## Task")
+        );
+    }
+
+    #[test]
+    fn with_conventions_file_ignores_missing_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let builder = PromptBuilder::new()
+            .with_conventions_file(&dir.path().join("absent.md"), 100)
+            .unwrap();
+        assert!(builder.conventions.is_none());
+    }
+
+    fn file(path: &str, content: String) -> FileContext {
+        FileContext {
+            path: path.to_string(),
+            content,
+            language: None,
+        }
+    }
+
+    #[test]
+    fn fit_to_budget_truncates_then_drops() {
+        let files = vec![
+            file("a.rs", "x".repeat(40)),
+            file("big.rs", "0123456789abcdef\n".repeat(200)),
+            file("c.rs", "y".repeat(40)),
+        ];
+
+        let (kept, omitted) = fit_to_budget(&files, 200);
+        let (again, omitted_again) = fit_to_budget(&files, 200);
+        assert_eq!(omitted_again, omitted);
+        assert_eq!(again[1].content, kept[1].content);
+
+        let paths: Vec<&str> = kept.iter().map(|file| file.path.as_str()).collect();
+        assert_eq!(paths, ["a.rs", "big.rs"]);
+        assert_eq!(kept[0].content, files[0].content);
+        assert!(
+            kept[1]
+                .content
+                .ends_with("0123456789abcdef\n[truncated to fit the context budget]\n")
+        );
+        let used: usize = kept.iter().map(estimate_file_tokens).sum();
+        assert!(used <= 200, "used {used} tokens");
+
+        assert_eq!(omitted.len(), 2);
+        assert_eq!(omitted[0].path, "big.rs");
+        assert_eq!(omitted[0].tokens, estimate_file_tokens(&files[1]));
+        assert!(omitted[0].kept_tokens > 0 && omitted[0].kept_tokens < omitted[0].tokens);
+        assert_eq!(
+            omitted[1],
+            OmittedFile {
+                path: "c.rs".to_string(),
+                tokens: 19,
+                kept_tokens: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn fit_to_budget_keeps_files_that_fit() {
+        let files = vec![file("a.rs", "x".repeat(40)), file("b.rs", "y".repeat(40))];
+        let (kept, omitted) = fit_to_budget(&files, 38);
+        assert_eq!(kept.len(), 2);
+        assert!(omitted.is_empty());
+
+        let (kept, omitted) = fit_to_budget(&files, 37);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(omitted[0].kept_tokens, 0);
+    }
 }
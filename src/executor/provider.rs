@@ -0,0 +1,378 @@
+//! Model backends and how one is chosen.
+//!
+//! Every built-in backend serves an OpenAI-compatible chat completions API,
+//! so each [`Provider`] only decides where requests go, which key they
+//! carry, and which model they ask for; [`CodexAdapter`] does the rest. The
+//! provider is picked by name: `--provider`, then `provider` in
+//! settings, then [`DEFAULT_PROVIDER`].
+
+use std::env;
+use std::sync::Arc;
+
+use secrecy::SecretString;
+
+use super::CodexAdapter;
+use super::adapter::DEFAULT_MODEL;
+use super::auth::AuthChain;
+use crate::error::NexusError;
+use crate::suggest::closest_match;
+use crate::types::NexusSettings;
+
+/// Provider used when neither the command line nor settings name one.
+pub const DEFAULT_PROVIDER: &str = "openai";
+
+const AZURE_ENDPOINT_ENV: &str = "AZURE_OPENAI_ENDPOINT";
+const AZURE_API_KEY_ENV: &str = "AZURE_OPENAI_API_KEY";
+/// Path of Azure OpenAI's OpenAI-compatible API under the resource endpoint.
+const AZURE_API_PATH: &str = "openai/v1";
+/// Sent to backends that need no key; the HTTP client always sends one.
+const PLACEHOLDER_API_KEY: &str = "unused";
+
+/// A model backend Nexus can send tasks to.
+pub trait Provider: Send + Sync {
+    /// Name used in settings and on the command line.
+    fn name(&self) -> &str;
+
+    /// Model requested unless the caller picks another.
+    fn default_model(&self) -> &str;
+
+    /// Base URL of the chat completions API.
+    fn base_url(&self) -> Result<String, NexusError>;
+
+    /// Key sent with each request, from the backend's own environment
+    /// variable.
+    fn api_key(&self) -> Result<SecretString, NexusError>;
+
+    /// Builds an adapter talking to this backend.
+    fn adapter(&self) -> Result<CodexAdapter, NexusError> {
+        self.adapter_with_auth(&AuthChain::default())
+    }
+
+    /// Like [`Provider::adapter`], but takes the key from `auth` when
+    /// [`Provider::api_key`] finds none.
+    fn adapter_with_auth(&self, auth: &AuthChain) -> Result<CodexAdapter, NexusError> {
+        Ok(CodexAdapter::new(self.resolve_api_key(auth)?)
+            .with_base_url(self.base_url()?)
+            .with_model(self.default_model())
+            .with_provider_name(self.name()))
+    }
+
+    /// Key requests carry: [`Provider::api_key`], else the one `auth` finds.
+    fn resolve_api_key(&self, auth: &AuthChain) -> Result<SecretString, NexusError> {
+        match self.api_key() {
+            Ok(key) => Ok(key),
+            Err(err) if auth.is_empty() => Err(err),
+            Err(err) => auth.api_key(self.name())?.ok_or_else(|| {
+                let reason = match err {
+                    NexusError::ConfigError { message, .. } => message,
+                    other => other.to_string(),
+                };
+                NexusError::ConfigError {
+                    message: format!("{reason}, and {} had no key", auth.describe()),
+                    path: None,
+                    source: None,
+                }
+            }),
+        }
+    }
+}
+
+/// A backend at a fixed URL speaking the OpenAI API, e.g. OpenAI itself,
+/// Anthropic's compatibility endpoint, or a local Ollama server.
+#[derive(Debug, Clone)]
+pub struct OpenAiCompatible {
+    name: String,
+    base_url: String,
+    base_url_env: Option<String>,
+    api_key_env: Option<String>,
+    default_model: String,
+}
+
+impl OpenAiCompatible {
+    /// A backend needing no key, serving `default_model` at `base_url`.
+    pub fn new(
+        name: impl Into<String>,
+        base_url: impl Into<String>,
+        default_model: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            base_url: base_url.into(),
+            base_url_env: None,
+            api_key_env: None,
+            default_model: default_model.into(),
+        }
+    }
+
+    /// Reads the key from environment variable `var`; unset is an error.
+    pub fn with_api_key_env(mut self, var: impl Into<String>) -> Self {
+        self.api_key_env = Some(var.into());
+        self
+    }
+
+    /// Lets environment variable `var` override the base URL.
+    pub fn with_base_url_env(mut self, var: impl Into<String>) -> Self {
+        self.base_url_env = Some(var.into());
+        self
+    }
+
+    pub fn openai() -> Self {
+        Self::new("openai", "https://api.openai.com/v1", DEFAULT_MODEL)
+            .with_api_key_env("OPENAI_API_KEY")
+            .with_base_url_env("OPENAI_BASE_URL")
+    }
+
+    pub fn anthropic() -> Self {
+        Self::new(
+            "anthropic",
+            "https://api.anthropic.com/v1",
+            "claude-sonnet-4-5",
+        )
+        .with_api_key_env("ANTHROPIC_API_KEY")
+        .with_base_url_env("ANTHROPIC_BASE_URL")
+    }
+
+    pub fn ollama() -> Self {
+        Self::new("ollama", "http://localhost:11434/v1", "qwen2.5-coder")
+            .with_base_url_env("OLLAMA_BASE_URL")
+    }
+}
+
+impl Provider for OpenAiCompatible {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn default_model(&self) -> &str {
+        &self.default_model
+    }
+
+    fn base_url(&self) -> Result<String, NexusError> {
+        Ok(self
+            .base_url_env
+            .as_deref()
+            .and_then(non_empty_env)
+            .unwrap_or_else(|| self.base_url.clone()))
+    }
+
+    fn api_key(&self) -> Result<SecretString, NexusError> {
+        match &self.api_key_env {
+            Some(var) => require_env(var).map(SecretString::from),
+            None => Ok(SecretString::from(PLACEHOLDER_API_KEY)),
+        }
+    }
+}
+
+/// Azure OpenAI, addressed through the resource endpoint in
+/// `AZURE_OPENAI_ENDPOINT`. The model names a deployment.
+#[derive(Debug, Clone, Default)]
+pub struct Azure;
+
+impl Provider for Azure {
+    fn name(&self) -> &str {
+        "azure"
+    }
+
+    fn default_model(&self) -> &str {
+        DEFAULT_MODEL
+    }
+
+    fn base_url(&self) -> Result<String, NexusError> {
+        let endpoint = require_env(AZURE_ENDPOINT_ENV)?;
+        Ok(format!(
+            "{}/{AZURE_API_PATH}",
+            endpoint.trim_end_matches('/')
+        ))
+    }
+
+    fn api_key(&self) -> Result<SecretString, NexusError> {
+        require_env(AZURE_API_KEY_ENV).map(SecretString::from)
+    }
+}
+
+/// Providers selectable by name.
+#[derive(Clone)]
+pub struct ProviderRegistry {
+    providers: Vec<Arc<dyn Provider>>,
+}
+
+impl Default for ProviderRegistry {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl ProviderRegistry {
+    /// OpenAI, Anthropic, Ollama, and Azure.
+    pub fn builtin() -> Self {
+        Self {
+            providers: vec![
+                Arc::new(OpenAiCompatible::openai()),
+                Arc::new(OpenAiCompatible::anthropic()),
+                Arc::new(OpenAiCompatible::ollama()),
+                Arc::new(Azure),
+            ],
+        }
+    }
+
+    /// Adds `provider`, replacing any registered under the same name.
+    pub fn with_provider(mut self, provider: impl Provider + 'static) -> Self {
+        self.providers
+            .retain(|existing| existing.name() != provider.name());
+        self.providers.push(Arc::new(provider));
+        self
+    }
+
+    /// Registered names, in registration order.
+    pub fn names(&self) -> Vec<&str> {
+        self.providers
+            .iter()
+            .map(|provider| provider.name())
+            .collect()
+    }
+
+    /// Looks up the provider called `name`.
+    pub fn get(&self, name: &str) -> Result<&dyn Provider, NexusError> {
+        if let Some(provider) = self
+            .providers
+            .iter()
+            .find(|provider| provider.name() == name)
+        {
+            return Ok(provider.as_ref());
+        }
+
+        let names = self.names();
+        let hint = match closest_match(name, &names) {
+            Some(candidate) => format!(" (did you mean `{candidate}`?)"),
+            None => String::new(),
+        };
+        Err(NexusError::ConfigError {
+            message: format!(
+                "unknown provider `{name}`{hint}; available: {}",
+                names.join(", ")
+            ),
+            path: None,
+            source: None,
+        })
+    }
+
+    /// The provider named by `flag`, else by `settings.provider`, else
+    /// [`DEFAULT_PROVIDER`].
+    pub fn select(
+        &self,
+        flag: Option<&str>,
+        settings: &NexusSettings,
+    ) -> Result<&dyn Provider, NexusError> {
+        let name = flag
+            .or(settings.provider.as_deref())
+            .unwrap_or(DEFAULT_PROVIDER);
+        self.get(name.trim())
+    }
+}
+
+fn non_empty_env(var: &str) -> Option<String> {
+    env::var(var).ok().filter(|value| !value.trim().is_empty())
+}
+
+fn require_env(var: &str) -> Result<String, NexusError> {
+    non_empty_env(var).ok_or_else(|| NexusError::ConfigError {
+        message: format!("{var} environment variable not set"),
+        path: None,
+        source: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(name: &str) -> OpenAiCompatible {
+        OpenAiCompatible::new(name, "http://127.0.0.1:9/v1", "local-model")
+    }
+
+    #[test]
+    fn test_select_prefers_flag_then_settings() {
+        let registry = ProviderRegistry::builtin();
+        let settings = NexusSettings {
+            provider: Some("ollama".to_string()),
+            ..NexusSettings::default()
+        };
+
+        assert_eq!(
+            registry
+                .select(None, &NexusSettings::default())
+                .unwrap()
+                .name(),
+            DEFAULT_PROVIDER
+        );
+        assert_eq!(registry.select(None, &settings).unwrap().name(), "ollama");
+        assert_eq!(
+            registry.select(Some("azure"), &settings).unwrap().name(),
+            "azure"
+        );
+        assert_eq!(registry.names(), ["openai", "anthropic", "ollama", "azure"]);
+    }
+
+    #[test]
+    fn test_unknown_provider_suggests_name() {
+        let err = ProviderRegistry::builtin()
+            .get("anthropik")
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(err.contains("unknown provider `anthropik` (did you mean `anthropic`?)"));
+        assert!(err.contains("available: openai, anthropic, ollama, azure"));
+    }
+
+    #[test]
+    fn test_registered_provider_replaces_builtin() {
+        let registry = ProviderRegistry::builtin()
+            .with_provider(local("ollama"))
+            .with_provider(local("vllm"));
+
+        assert_eq!(
+            registry.names(),
+            ["openai", "anthropic", "azure", "ollama", "vllm"]
+        );
+        let ollama = registry.get("ollama").unwrap();
+        assert_eq!(ollama.base_url().unwrap(), "http://127.0.0.1:9/v1");
+        assert_eq!(ollama.default_model(), "local-model");
+        assert!(ollama.adapter().is_ok());
+    }
+
+    #[test]
+    fn test_missing_api_key_names_variable() {
+        let provider = local("keyed").with_api_key_env("NEXUS_TEST_UNSET_PROVIDER_KEY");
+        let err = provider.api_key().err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "configuration error: NEXUS_TEST_UNSET_PROVIDER_KEY environment variable not set"
+        );
+    }
+
+    #[test]
+    fn test_missing_api_key_falls_back_to_auth_chain() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("key");
+        std::fs::write(&path, "sk-test\n").unwrap();
+        let provider = local("keyed").with_api_key_env("NEXUS_TEST_UNSET_PROVIDER_KEY");
+
+        let auth = AuthChain::default().with_provider(super::super::auth::KeyFile::new(
+            &path.display().to_string(),
+        ));
+        assert!(provider.adapter_with_auth(&auth).is_ok());
+
+        std::fs::write(&path, "\n").unwrap();
+        let auth = AuthChain::default().with_provider(super::super::auth::KeyFile::new(
+            &path.display().to_string(),
+        ));
+        let err = provider.adapter_with_auth(&auth).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "configuration error: NEXUS_TEST_UNSET_PROVIDER_KEY environment variable not set, and api_key_file ({}) had no key",
+                path.display()
+            )
+        );
+    }
+}
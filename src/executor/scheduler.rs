@@ -0,0 +1,127 @@
+//! Shared pacing of provider calls under one rate limit.
+//!
+//! Calls made in parallel (planner, reviewer, chunked files) hit the same
+//! provider quota. When any of them is rate limited, the whole pool pauses
+//! until the provider's `Retry-After` has passed, instead of each call
+//! retrying on its own schedule and prolonging the breach. An optional cap
+//! limits how many requests are in flight at once.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::{Instant, sleep_until};
+
+/// Coordinates provider calls. Clones share state.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimitScheduler {
+    paused_until: Arc<Mutex<Option<Instant>>>,
+    slots: Option<Arc<Semaphore>>,
+}
+
+/// Held while a request is being sent; releases its slot when dropped.
+#[derive(Debug)]
+pub struct RequestSlot {
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl RateLimitScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allows at most `max` requests in flight at once (minimum 1).
+    pub fn with_max_concurrent(mut self, max: usize) -> Self {
+        self.slots = Some(Arc::new(Semaphore::new(max.max(1))));
+        self
+    }
+
+    /// Pauses every call sharing this scheduler for `delay`.
+    ///
+    /// An earlier, longer pause is never shortened.
+    pub fn pause(&self, delay: Duration) {
+        let until = Instant::now() + delay;
+        let mut paused_until = self.lock();
+        if paused_until.is_none_or(|current| current < until) {
+            log::warn!("rate limited; pausing provider calls for {delay:?}");
+            *paused_until = Some(until);
+        }
+    }
+
+    /// Time left in the current pause, if any.
+    pub fn remaining_pause(&self) -> Option<Duration> {
+        let until = (*self.lock())?;
+        let now = Instant::now();
+        (until > now).then(|| until - now)
+    }
+
+    /// Waits out any pause and for a free slot, then returns the slot to
+    /// hold while sending.
+    pub async fn acquire(&self) -> RequestSlot {
+        let permit = match &self.slots {
+            Some(slots) => Some(
+                Arc::clone(slots)
+                    .acquire_owned()
+                    .await
+                    .expect("scheduler semaphore is never closed"),
+            ),
+            None => None,
+        };
+        // A pause can start while this call sleeps, so check again after.
+        loop {
+            let paused_until = *self.lock();
+            match paused_until {
+                Some(until) if until > Instant::now() => sleep_until(until).await,
+                _ => break,
+            }
+        }
+        RequestSlot { _permit: permit }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<Instant>> {
+        self.paused_until
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAUSE: Duration = Duration::from_millis(80);
+
+    #[tokio::test]
+    async fn test_pause_holds_every_caller() {
+        let scheduler = RateLimitScheduler::new();
+        let start = Instant::now();
+        scheduler.pause(PAUSE);
+
+        let other = scheduler.clone();
+        let (a, b) = tokio::join!(other.acquire(), scheduler.acquire());
+        drop((a, b));
+
+        assert!(start.elapsed() >= PAUSE);
+        assert!(scheduler.remaining_pause().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_shorter_pause_does_not_shorten() {
+        let scheduler = RateLimitScheduler::new();
+        scheduler.pause(PAUSE);
+        scheduler.pause(Duration::from_millis(1));
+        assert!(scheduler.remaining_pause().unwrap() > Duration::from_millis(40));
+    }
+
+    #[tokio::test]
+    async fn test_max_concurrent_limits_slots() {
+        let scheduler = RateLimitScheduler::new().with_max_concurrent(1);
+        let first = scheduler.acquire().await;
+        let blocked = tokio::time::timeout(Duration::from_millis(20), scheduler.acquire()).await;
+        assert!(blocked.is_err());
+
+        drop(first);
+        let second = tokio::time::timeout(Duration::from_millis(20), scheduler.acquire()).await;
+        assert!(second.is_ok());
+    }
+}
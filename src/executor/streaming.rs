@@ -1,4 +1,5 @@
 use futures::{Stream, StreamExt};
+use serde::Serialize;
 
 use crate::error::NexusError;
 
@@ -7,6 +8,30 @@ use super::client::{ChatChunk, UsageInfo};
 
 const PRIMARY_CHOICE_INDEX: usize = 0;
 const FINISH_REASON_STOP: &str = "stop";
+const FINISH_REASON_TOOL_CALLS: &str = "tool_calls";
+
+/// Default cap on accumulated response text (4 MiB).
+pub const DEFAULT_MAX_RESPONSE_BYTES: usize = 4 * 1024 * 1024;
+
+/// A function call assembled from streamed fragments.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ToolCall {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub name: String,
+    /// JSON arguments as sent by the model; not validated.
+    pub arguments: String,
+}
+
+/// Everything a streamed response carried.
+#[derive(Debug, Default)]
+pub struct StreamedReply {
+    pub content: String,
+    /// Reasoning the model streamed before answering, if it exposes any.
+    pub reasoning: String,
+    pub tool_calls: Vec<ToolCall>,
+    pub usage: Option<UsageInfo>,
+}
 
 pub struct StreamHandler;
 
@@ -19,33 +44,124 @@ impl StreamHandler {
     }
 
     pub async fn with_callback<S, F>(
+        stream: S,
+        callback: F,
+    ) -> Result<(String, Option<UsageInfo>), NexusError>
+    where
+        S: Stream<Item = Result<ChatChunk, NexusError>> + Unpin,
+        F: FnMut(StreamChunk),
+    {
+        Self::with_limit(stream, DEFAULT_MAX_RESPONSE_BYTES, callback).await
+    }
+
+    /// Accumulates the stream, aborting once the response exceeds `max_bytes`.
+    ///
+    /// On overflow the stream is dropped (closing the connection) and
+    /// `NexusError::ResponseTooLarge` carries the text received so far.
+    pub async fn with_limit<S, F>(
+        stream: S,
+        max_bytes: usize,
+        callback: F,
+    ) -> Result<(String, Option<UsageInfo>), NexusError>
+    where
+        S: Stream<Item = Result<ChatChunk, NexusError>> + Unpin,
+        F: FnMut(StreamChunk),
+    {
+        let reply = Self::collect(stream, max_bytes, callback).await?;
+        Ok((reply.content, reply.usage))
+    }
+
+    /// Like [`Self::with_limit`], also assembling tool calls and reasoning.
+    /// Both count toward `max_bytes` along with the text; reasoning is
+    /// passed to `callback` as [`StreamChunk::Thinking`].
+    pub async fn collect<S, F>(
         mut stream: S,
+        max_bytes: usize,
         mut callback: F,
-    ) -> Result<(String, Option<UsageInfo>), NexusError>
+    ) -> Result<StreamedReply, NexusError>
     where
         S: Stream<Item = Result<ChatChunk, NexusError>> + Unpin,
         F: FnMut(StreamChunk),
     {
-        let mut content = String::new();
-        let mut usage = None;
+        let mut reply = StreamedReply::default();
+        // Calls by stream index, in order of first appearance.
+        let mut calls: Vec<(u32, ToolCall)> = Vec::new();
+        let mut received = 0usize;
 
         while let Some(result) = stream.next().await {
             let chunk = result?;
-            update_usage(&mut usage, &chunk);
+            update_usage(&mut reply.usage, &chunk);
 
             if let Some(choice) = chunk.choices.get(PRIMARY_CHOICE_INDEX) {
+                if let Some(text) = choice.delta.reasoning_content.as_ref() {
+                    reply.reasoning.push_str(text);
+                    received += text.len();
+                    if received > max_bytes {
+                        return Err(NexusError::ResponseTooLarge {
+                            limit_bytes: max_bytes,
+                            partial: reply.reasoning,
+                        });
+                    }
+                    callback(StreamChunk::Thinking(text.clone()));
+                }
+
                 if let Some(text) = choice.delta.content.as_ref() {
-                    content.push_str(text);
+                    reply.content.push_str(text);
+                    received += text.len();
+                    if received > max_bytes {
+                        return Err(NexusError::ResponseTooLarge {
+                            limit_bytes: max_bytes,
+                            partial: reply.content,
+                        });
+                    }
                     callback(StreamChunk::Text(text.clone()));
                 }
 
-                if is_finish_stop(&choice.finish_reason) {
+                for delta in choice.delta.tool_calls.iter().flatten() {
+                    let position = match calls.iter().position(|(index, _)| *index == delta.index) {
+                        Some(position) => position,
+                        None => {
+                            calls.push((
+                                delta.index,
+                                ToolCall {
+                                    id: None,
+                                    name: String::new(),
+                                    arguments: String::new(),
+                                },
+                            ));
+                            calls.len() - 1
+                        }
+                    };
+                    let call = &mut calls[position].1;
+                    if let Some(id) = &delta.id {
+                        call.id = Some(id.clone());
+                    }
+                    let Some(function) = &delta.function else {
+                        continue;
+                    };
+                    if let Some(name) = &function.name {
+                        call.name.push_str(name);
+                    }
+                    if let Some(arguments) = &function.arguments {
+                        call.arguments.push_str(arguments);
+                        received += arguments.len();
+                        if received > max_bytes {
+                            return Err(NexusError::ResponseTooLarge {
+                                limit_bytes: max_bytes,
+                                partial: call.arguments.clone(),
+                            });
+                        }
+                    }
+                }
+
+                if is_finished(&choice.finish_reason) {
                     callback(StreamChunk::Done);
                 }
             }
         }
 
-        Ok((content, usage))
+        reply.tool_calls = calls.into_iter().map(|(_, call)| call).collect();
+        Ok(reply)
     }
 }
 
@@ -55,16 +171,21 @@ fn update_usage(usage: &mut Option<UsageInfo>, chunk: &ChatChunk) {
     }
 }
 
-fn is_finish_stop(reason: &Option<String>) -> bool {
-    matches!(reason.as_deref(), Some(FINISH_REASON_STOP))
+fn is_finished(reason: &Option<String>) -> bool {
+    matches!(
+        reason.as_deref(),
+        Some(FINISH_REASON_STOP | FINISH_REASON_TOOL_CALLS)
+    )
 }
 
 #[cfg(test)]
 mod tests {
-    use super::StreamHandler;
+    use super::{StreamHandler, ToolCall};
     use crate::error::NexusError;
     use crate::executor::StreamChunk;
-    use crate::executor::client::{ChatChunk, ChunkChoice, Delta};
+    use crate::executor::client::{
+        ChatChunk, ChunkChoice, Delta, FunctionCallDelta, ToolCallDelta,
+    };
     use futures::stream;
     use std::sync::{Arc, Mutex};
 
@@ -85,6 +206,8 @@ mod tests {
                 delta: Delta {
                     content,
                     role: None,
+                    reasoning_content: None,
+                    tool_calls: None,
                 },
                 finish_reason,
             }],
@@ -138,6 +261,120 @@ mod tests {
         assert!(usage.is_none());
     }
 
+    #[tokio::test]
+    async fn test_with_limit_aborts_oversized_response() {
+        // Arrange
+        let stream = stream::iter(vec![
+            Ok(mock_chunk(Some("12345".to_string()), None)),
+            Ok(mock_chunk(Some("67890".to_string()), None)),
+            Ok(mock_chunk(Some("never read".to_string()), None)),
+        ]);
+
+        // Act
+        let result = StreamHandler::with_limit(stream, 8, |_| {}).await;
+
+        // Assert
+        match result {
+            Err(NexusError::ResponseTooLarge {
+                limit_bytes,
+                partial,
+            }) => {
+                assert_eq!(limit_bytes, 8);
+                assert_eq!(partial, "1234567890");
+            }
+            other => panic!("expected ResponseTooLarge, got {other:?}"),
+        }
+    }
+
+    fn tool_call_chunk(
+        index: u32,
+        name: Option<&str>,
+        arguments: &str,
+        finish_reason: Option<&str>,
+    ) -> ChatChunk {
+        let mut chunk = mock_chunk(None, finish_reason.map(str::to_string));
+        chunk.choices[0].delta.tool_calls = Some(vec![ToolCallDelta {
+            index,
+            id: name.map(|_| format!("call_{index}")),
+            function: Some(FunctionCallDelta {
+                name: name.map(str::to_string),
+                arguments: Some(arguments.to_string()),
+            }),
+        }]);
+        chunk
+    }
+
+    #[tokio::test]
+    async fn test_collect_assembles_tool_calls() {
+        // Arrange
+        let stream = stream::iter(vec![
+            Ok(tool_call_chunk(
+                0,
+                Some("propose_actions"),
+                "{\"actions\":",
+                None,
+            )),
+            Ok(tool_call_chunk(1, Some("other"), "{}", None)),
+            Ok(tool_call_chunk(0, None, "[]}", Some("tool_calls"))),
+        ]);
+        let mut done = false;
+
+        // Act
+        let reply = StreamHandler::collect(stream, 1024, |chunk| {
+            done |= matches!(chunk, StreamChunk::Done);
+        })
+        .await
+        .expect("collect should succeed");
+
+        // Assert
+        assert!(done);
+        assert!(reply.content.is_empty());
+        assert_eq!(
+            reply.tool_calls,
+            vec![
+                ToolCall {
+                    id: Some("call_0".to_string()),
+                    name: "propose_actions".to_string(),
+                    arguments: "{\"actions\":[]}".to_string(),
+                },
+                ToolCall {
+                    id: Some("call_1".to_string()),
+                    name: "other".to_string(),
+                    arguments: "{}".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_collect_emits_reasoning_as_thinking() {
+        // Arrange
+        let chunks: Vec<ChatChunk> = [
+            r#"{"id":"r","object":"chat.completion.chunk","created":1,"model":"m","choices":[{"index":0,"delta":{"reasoning_content":"Check "},"finish_reason":null}]}"#,
+            r#"{"id":"r","object":"chat.completion.chunk","created":1,"model":"m","choices":[{"index":0,"delta":{"reasoning":"callers."},"finish_reason":null}]}"#,
+            r#"{"id":"r","object":"chat.completion.chunk","created":1,"model":"m","choices":[{"index":0,"delta":{"content":"Done"},"finish_reason":"stop"}]}"#,
+        ]
+        .iter()
+        .map(|json| serde_json::from_str(json).expect("chunk should parse"))
+        .collect();
+        let mut thinking = Vec::new();
+
+        // Act
+        let reply =
+            StreamHandler::collect(stream::iter(chunks.into_iter().map(Ok)), 1024, |chunk| {
+                if let StreamChunk::Thinking(text) = chunk {
+                    thinking.push(text);
+                }
+            })
+            .await
+            .expect("collect should succeed");
+
+        // Assert
+        assert_eq!(thinking, ["Check ", "callers."]);
+        assert_eq!(reply.reasoning, "Check callers.");
+        assert_eq!(reply.content, "Done");
+    }
+
     #[derive(Debug, PartialEq)]
     enum ObservedChunk {
         Text(String),
@@ -0,0 +1,82 @@
+//! Rough token estimates for budgeting prompt content before sending it.
+//!
+//! Uses the common ~4 characters per token heuristic; close enough to keep
+//! prompts under a context window without shipping a tokenizer.
+
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Context window assumed for models missing from `context_window`.
+pub const DEFAULT_CONTEXT_WINDOW: usize = 128_000;
+
+/// Known context windows by model-name prefix; the first match wins.
+const CONTEXT_WINDOWS: &[(&str, usize)] = &[
+    ("gpt-5", 400_000),
+    ("gpt-4.1", 1_047_576),
+    ("gpt-4o", 128_000),
+    ("o1", 200_000),
+    ("o3", 200_000),
+    ("o4", 200_000),
+];
+
+/// Returns the context window, in tokens, of `model`.
+pub fn context_window(model: &str) -> usize {
+    CONTEXT_WINDOWS
+        .iter()
+        .find(|(prefix, _)| model.starts_with(prefix))
+        .map_or(DEFAULT_CONTEXT_WINDOW, |(_, window)| *window)
+}
+
+/// Estimates the number of tokens `text` occupies.
+///
+/// # Examples
+///
+/// ```
+/// use nexus::executor::tokens::estimate_tokens;
+///
+/// assert_eq!(estimate_tokens(""), 0);
+/// assert_eq!(estimate_tokens("abcdefgh"), 2);
+/// ```
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Returns the longest prefix of `text` within `max_tokens`, cut at a line
+/// break when one exists, or `None` if `text` already fits.
+pub fn truncate_to_tokens(text: &str, max_tokens: usize) -> Option<&str> {
+    let max_chars = max_tokens.saturating_mul(CHARS_PER_TOKEN);
+    let (cut, _) = text.char_indices().nth(max_chars)?;
+    let prefix = &text[..cut];
+    match prefix.rfind('\n') {
+        Some(newline) => Some(&prefix[..=newline]),
+        None => Some(prefix),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_window_by_prefix() {
+        assert_eq!(context_window("gpt-5.2-codex"), 400_000);
+        assert_eq!(context_window("gpt-4o-mini"), 128_000);
+        assert_eq!(context_window("local-model"), DEFAULT_CONTEXT_WINDOW);
+    }
+
+    #[test]
+    fn test_truncate_fits() {
+        assert_eq!(truncate_to_tokens("short", 10), None);
+    }
+
+    #[test]
+    fn test_truncate_prefers_line_break() {
+        let text = "line one\nline two\nline three\n";
+        assert_eq!(truncate_to_tokens(text, 4), Some("line one\n"));
+    }
+
+    #[test]
+    fn test_truncate_respects_char_boundaries() {
+        let text = "ééééééééé";
+        assert_eq!(truncate_to_tokens(text, 1), Some("éééé"));
+    }
+}
@@ -0,0 +1,97 @@
+//! Structured output: the `propose_actions` tool and the `json_schema`
+//! response format.
+//!
+//! Instead of writing diffs as text, the model replies with
+//! `{"actions": [...]}`, either as the arguments of a `propose_actions`
+//! call or as the whole message, each entry a `ProposedAction` as described
+//! by `.nexus/schemas/proposed_action.schema.json`. Models that answer in
+//! free text anyway are parsed as before.
+
+use serde_json::{Value, json};
+
+use super::client::{FunctionDefinition, JsonSchemaFormat, ResponseFormat, Tool};
+
+pub const PROPOSE_ACTIONS_TOOL: &str = "propose_actions";
+const ACTIONS_SCHEMA_NAME: &str = "proposed_actions";
+const PROPOSE_ACTIONS_DESCRIPTION: &str =
+    "Propose the code changes and commands that complete the task.";
+const PROPOSED_ACTION_SCHEMA: &str =
+    include_str!("../../.nexus/schemas/proposed_action.schema.json");
+
+/// The `propose_actions` tool definition.
+pub fn propose_actions_tool() -> Tool {
+    Tool {
+        kind: "function".to_string(),
+        function: FunctionDefinition {
+            name: PROPOSE_ACTIONS_TOOL.to_string(),
+            description: PROPOSE_ACTIONS_DESCRIPTION.to_string(),
+            parameters: actions_schema(),
+        },
+    }
+}
+
+/// `tool_choice` forcing a call to `propose_actions`.
+pub fn propose_actions_choice() -> Value {
+    json!({"type": "function", "function": {"name": PROPOSE_ACTIONS_TOOL}})
+}
+
+/// `response_format` constraining the whole reply to the actions object.
+pub fn actions_response_format() -> ResponseFormat {
+    ResponseFormat::JsonSchema {
+        json_schema: JsonSchemaFormat {
+            name: ACTIONS_SCHEMA_NAME.to_string(),
+            schema: actions_schema(),
+            strict: false,
+        },
+    }
+}
+
+/// An object whose `actions` array holds proposed actions. The action
+/// schema's `$defs` move to the root so its `$ref`s still resolve.
+fn actions_schema() -> Value {
+    let mut action: Value =
+        serde_json::from_str(PROPOSED_ACTION_SCHEMA).expect("bundled action schema is valid JSON");
+    let object = action
+        .as_object_mut()
+        .expect("bundled action schema is an object");
+    let defs = object.remove("$defs").unwrap_or_else(|| json!({}));
+    object.remove("$schema");
+    object.remove("$id");
+
+    json!({
+        "type": "object",
+        "additionalProperties": false,
+        "required": ["actions"],
+        "properties": {
+            "actions": {"type": "array", "items": action}
+        },
+        "$defs": defs
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_schema_wraps_action_schema() {
+        let tool = propose_actions_tool();
+        assert_eq!(tool.function.name, PROPOSE_ACTIONS_TOOL);
+
+        let parameters = &tool.function.parameters;
+        let items = &parameters["properties"]["actions"]["items"];
+        assert_eq!(items["title"], "ProposedAction");
+        assert!(items.get("$schema").is_none());
+        assert!(items.get("$defs").is_none());
+        assert!(parameters["$defs"]["repo_relative_path"].is_object());
+    }
+
+    #[test]
+    fn test_response_format_serializes_json_schema() {
+        let format = serde_json::to_value(actions_response_format()).unwrap();
+        assert_eq!(format["type"], "json_schema");
+        assert_eq!(format["json_schema"]["name"], ACTIONS_SCHEMA_NAME);
+        assert_eq!(format["json_schema"]["strict"], false);
+        assert_eq!(format["json_schema"]["schema"]["required"][0], "actions");
+    }
+}
@@ -0,0 +1,253 @@
+//! Shareable copies of event logs for bug reports.
+//!
+//! Event order, types, timestamps, numbers, and payload shape are kept so
+//! sequencing and parsing bugs still reproduce. What gets replaced or dropped
+//! is controlled by an [`AnonymizePolicy`].
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde_json::{Map, Value};
+
+use crate::error::NexusError;
+use crate::event_log::{EventLogPath, EventLogReader};
+use crate::types::{AnonymizePolicy, RunEvent};
+
+/// Payload keys whose values are file paths.
+const PATH_KEYS: &[&str] = &["path", "paths", "file", "files", "files_modified"];
+/// Payload keys naming the model or provider.
+const MODEL_KEYS: &[&str] = &["model", "provider"];
+/// Payload keys with structural values, kept even when payloads are stripped.
+const STRUCTURAL_KEYS: &[&str] = &[
+    "action_id",
+    "kind",
+    "status",
+    "scope",
+    "decision",
+    "resolution",
+    "format",
+    "retry_of",
+    "refine_of",
+];
+
+/// Rewrites events under a policy, keeping path placeholders stable across
+/// every event it sees.
+pub struct Anonymizer<'a> {
+    policy: &'a AnonymizePolicy,
+    paths: HashMap<String, String>,
+}
+
+impl<'a> Anonymizer<'a> {
+    pub fn new(policy: &'a AnonymizePolicy) -> Self {
+        Self {
+            policy,
+            paths: HashMap::new(),
+        }
+    }
+
+    /// Returns the anonymized copy of `event`.
+    pub fn event(&mut self, event: &RunEvent) -> RunEvent {
+        let mut event = event.clone();
+        if self.policy.actor {
+            event.actor = None;
+        } else if let (true, Some(actor)) = (self.policy.model, event.actor.as_mut()) {
+            actor.model = None;
+            actor.provider = None;
+        }
+        if let Some(payload) = event.payload.take() {
+            event.payload = Some(self.value(None, payload));
+        }
+        if let (true, Some(payload_ref)) = (self.policy.payloads, event.payload_ref.as_mut()) {
+            payload_ref.sha256 = None;
+        }
+        event
+    }
+
+    fn value(&mut self, key: Option<&str>, value: Value) -> Value {
+        match value {
+            Value::Object(object) => {
+                let mut anonymized = Map::new();
+                for (key, value) in object {
+                    if self.policy.model && MODEL_KEYS.contains(&key.as_str()) {
+                        continue;
+                    }
+                    let value = self.value(Some(&key), value);
+                    anonymized.insert(key, value);
+                }
+                Value::Object(anonymized)
+            }
+            Value::Array(items) => Value::Array(
+                items
+                    .into_iter()
+                    .map(|item| self.value(key, item))
+                    .collect(),
+            ),
+            Value::String(text) => Value::String(self.string(key, text)),
+            other => other,
+        }
+    }
+
+    fn string(&mut self, key: Option<&str>, text: String) -> String {
+        let Some(key) = key else {
+            return self.free_text(text);
+        };
+        if STRUCTURAL_KEYS.contains(&key) || self.policy.keep_keys.iter().any(|k| k == key) {
+            text
+        } else if PATH_KEYS.contains(&key) {
+            if self.policy.paths {
+                self.path(&text)
+            } else {
+                text
+            }
+        } else {
+            self.free_text(text)
+        }
+    }
+
+    /// Replaces free text with its length, or just masks known paths in it.
+    fn free_text(&self, text: String) -> String {
+        if self.policy.payloads {
+            return format!("<{} chars>", text.chars().count());
+        }
+        if !self.policy.paths {
+            return text;
+        }
+        let mut known: Vec<_> = self.paths.iter().collect();
+        known.sort_by_key(|(path, _)| std::cmp::Reverse(path.len()));
+        known.into_iter().fold(text, |text, (path, placeholder)| {
+            text.replace(path.as_str(), placeholder)
+        })
+    }
+
+    /// `file_<n>` plus the original extension, so format detection still works.
+    fn path(&mut self, path: &str) -> String {
+        let next = self.paths.len() + 1;
+        self.paths
+            .entry(path.to_string())
+            .or_insert_with(
+                || match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+                    Some(ext) => format!("file_{next}.{ext}"),
+                    None => format!("file_{next}"),
+                },
+            )
+            .clone()
+    }
+}
+
+/// Returns the JSONL event log of `run_id`, anonymized when `policy` is set.
+pub fn export_log_for_run(
+    project_root: &Path,
+    run_id: &str,
+    policy: Option<&AnonymizePolicy>,
+) -> Result<String, NexusError> {
+    let log_path = EventLogPath::new(project_root).for_run(run_id)?;
+    let Some(policy) = policy else {
+        return std::fs::read_to_string(&log_path).map_err(|err| NexusError::IoError {
+            operation: "read event log".to_string(),
+            path: log_path.clone(),
+            source: err,
+        });
+    };
+
+    let events = EventLogReader::open(&log_path)?.load_all()?;
+    let mut anonymizer = Anonymizer::new(policy);
+    let mut out = String::new();
+    for event in &events {
+        out.push_str(&serde_json::to_string(&anonymizer.event(event))?);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Actor, PayloadRef};
+    use serde_json::json;
+
+    fn started() -> RunEvent {
+        RunEvent::new("run_1", "executor.started")
+            .with_actor(Actor {
+                agent: None,
+                provider: Some("openai".to_string()),
+                model: Some("gpt-test".to_string()),
+            })
+            .with_payload(json!({
+                "task": "rename foo",
+                "file_count": 2,
+                "files": ["src/secret/a.rs", "README"],
+                "model": "gpt-test"
+            }))
+    }
+
+    #[test]
+    fn test_default_policy_strips_content() {
+        let policy = AnonymizePolicy::default();
+        let mut anonymizer = Anonymizer::new(&policy);
+
+        let event = anonymizer.event(&started());
+        assert!(event.actor.is_none());
+        assert_eq!(
+            event.payload,
+            Some(json!({
+                "task": "<10 chars>",
+                "file_count": 2,
+                "files": ["file_1.rs", "file_2"]
+            }))
+        );
+    }
+
+    #[test]
+    fn test_path_placeholders_are_stable() {
+        let policy = AnonymizePolicy::default();
+        let mut anonymizer = Anonymizer::new(&policy);
+        anonymizer.event(&started());
+
+        let completed = RunEvent::new("run_1", "tool.completed").with_payload(json!({
+            "action_id": "act_1",
+            "success": true,
+            "files_modified": ["README", "src/secret/a.rs"]
+        }));
+        assert_eq!(
+            anonymizer.event(&completed).payload,
+            Some(json!({
+                "action_id": "act_1",
+                "success": true,
+                "files_modified": ["file_2", "file_1.rs"]
+            }))
+        );
+    }
+
+    #[test]
+    fn test_policy_can_keep_details() {
+        let policy = AnonymizePolicy {
+            payloads: false,
+            actor: false,
+            model: false,
+            keep_keys: vec!["task".to_string()],
+            ..AnonymizePolicy::default()
+        };
+        let mut anonymizer = Anonymizer::new(&policy);
+        anonymizer.event(&started());
+
+        let failed = RunEvent::new("run_1", "tool.failed")
+            .with_payload(json!({"error": "cannot open src/secret/a.rs"}))
+            .with_payload_ref(PayloadRef {
+                uri: "run_1/artifacts/response.txt".to_string(),
+                mime: None,
+                sha256: Some("abc".to_string()),
+                size_bytes: Some(3),
+                label: None,
+            });
+        let event = anonymizer.event(&failed);
+        assert_eq!(
+            event.payload,
+            Some(json!({"error": "cannot open file_1.rs"}))
+        );
+        assert_eq!(event.payload_ref.unwrap().sha256.as_deref(), Some("abc"));
+
+        let event = anonymizer.event(&started());
+        assert_eq!(event.actor.unwrap().model.as_deref(), Some("gpt-test"));
+        assert_eq!(event.payload.unwrap()["task"], "rename foo");
+    }
+}
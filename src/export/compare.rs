@@ -0,0 +1,331 @@
+//! Side-by-side comparison of two runs, e.g. of the same task on two models.
+//!
+//! The report lists which files each run proposed to change, shows a
+//! diff-of-diffs for files both runs touched, and compares usage.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::path::Path;
+
+use similar::TextDiff;
+
+use super::summary::{find_str, payload_u64, usage_value};
+use crate::error::NexusError;
+use crate::event_log::{EventLogReader, load_run_events};
+use crate::paths::normalize_separators;
+use crate::policy::touched_paths;
+use crate::preview::load_action_artifact;
+use crate::types::{ActionDetails, ProposedAction, RunEvent};
+
+/// What one run proposed and what it cost.
+#[derive(Debug, Clone, Default)]
+pub struct RunProfile {
+    pub run_id: String,
+    pub task: Option<String>,
+    pub model: Option<String>,
+    pub actions: Vec<ProposedAction>,
+    pub duration_ms: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cost_usd: f64,
+}
+
+impl RunProfile {
+    /// Loads `run_id` from `.nexus/runs/` under `project_root`.
+    pub fn load_for_run(project_root: &Path, run_id: &str) -> Result<Self, NexusError> {
+        let (events, runs_dir) = load_run_events(project_root, run_id)?;
+        Self::load_with_artifacts(events, &runs_dir)
+    }
+
+    /// Loads the run recorded at `log_path`, including every proposed action.
+    pub fn load(log_path: &Path) -> Result<Self, NexusError> {
+        let events = EventLogReader::open(log_path)?.load_all()?;
+        let runs_dir = log_path.parent().unwrap_or_else(|| Path::new("."));
+        Self::load_with_artifacts(events, runs_dir)
+    }
+
+    fn load_with_artifacts(events: Vec<RunEvent>, runs_dir: &Path) -> Result<Self, NexusError> {
+        let mut actions = Vec::new();
+        for event in events.iter().filter(|e| e.event_type == "action.proposed") {
+            if let Some(payload_ref) = &event.payload_ref {
+                actions.push(load_action_artifact(runs_dir, payload_ref)?);
+            }
+        }
+        Ok(Self::from_events(&events, actions))
+    }
+
+    fn from_events(events: &[RunEvent], actions: Vec<ProposedAction>) -> Self {
+        let sum = |key: &str| -> u64 {
+            events
+                .iter()
+                .filter_map(|event| usage_value(event, key)?.as_u64())
+                .sum()
+        };
+        Self {
+            run_id: events
+                .first()
+                .map(|event| event.run_id.clone())
+                .unwrap_or_default(),
+            task: find_str(events, &["run.started", "executor.started"], "task")
+                .map(str::to_string),
+            model: find_str(events, &["executor.started"], "model").map(str::to_string),
+            actions,
+            duration_ms: events
+                .iter()
+                .filter(|event| event.event_type == "executor.completed")
+                .filter_map(|event| payload_u64(event, "duration_ms"))
+                .sum(),
+            prompt_tokens: sum("prompt_tokens"),
+            completion_tokens: sum("completion_tokens"),
+            cost_usd: events
+                .iter()
+                .filter_map(|event| usage_value(event, "cost_usd")?.as_f64())
+                .sum(),
+        }
+    }
+
+    /// Proposed change text per file, with diff headers stripped so only
+    /// the content of the change is compared.
+    fn changes_by_file(&self) -> BTreeMap<String, String> {
+        let mut changes: BTreeMap<String, String> = BTreeMap::new();
+        for action in &self.actions {
+            for (path, text) in action_changes(action) {
+                changes.entry(path).or_default().push_str(&text);
+            }
+        }
+        changes
+    }
+}
+
+/// Renders a markdown comparison of two runs.
+pub fn render_comparison(a: &RunProfile, b: &RunProfile) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# Comparing `{}` and `{}`\n", a.run_id, b.run_id);
+    match (&a.task, &b.task) {
+        (Some(task_a), Some(task_b)) if task_a == task_b => {
+            let _ = writeln!(out, "**Task:** {task_a}\n");
+        }
+        _ => {
+            let _ = writeln!(
+                out,
+                "**Note:** the runs have different tasks.\n\n- `{}`: {}\n- `{}`: {}\n",
+                a.run_id,
+                a.task.as_deref().unwrap_or("?"),
+                b.run_id,
+                b.task.as_deref().unwrap_or("?")
+            );
+        }
+    }
+
+    let changes_a = a.changes_by_file();
+    let changes_b = b.changes_by_file();
+    let files: BTreeSet<&String> = changes_a.keys().chain(changes_b.keys()).collect();
+
+    let _ = writeln!(out, "## Files\n");
+    if files.is_empty() {
+        let _ = writeln!(out, "Neither run proposed file changes.\n");
+    } else {
+        let _ = writeln!(out, "| File | `{}` | `{}` |", a.run_id, b.run_id);
+        let _ = writeln!(out, "|------|-----|-----|");
+        for file in &files {
+            let mark = |changes: &BTreeMap<String, String>| {
+                if changes.contains_key(*file) {
+                    "changed"
+                } else {
+                    "—"
+                }
+            };
+            let _ = writeln!(
+                out,
+                "| {file} | {} | {} |",
+                mark(&changes_a),
+                mark(&changes_b)
+            );
+        }
+        out.push('\n');
+    }
+
+    let overlapping: Vec<&String> = files
+        .iter()
+        .copied()
+        .filter(|file| changes_a.contains_key(*file) && changes_b.contains_key(*file))
+        .collect();
+    if !overlapping.is_empty() {
+        let _ = writeln!(out, "## Overlapping changes\n");
+        for file in overlapping {
+            let (text_a, text_b) = (&changes_a[file], &changes_b[file]);
+            let _ = writeln!(out, "### {file}\n");
+            if text_a == text_b {
+                let _ = writeln!(out, "Both runs proposed the same change.\n");
+                continue;
+            }
+            let diff = TextDiff::from_lines(text_a, text_b)
+                .unified_diff()
+                .header(&a.run_id, &b.run_id)
+                .to_string();
+            let _ = writeln!(out, "```diff\n{diff}```\n");
+        }
+    }
+
+    write_usage(&mut out, a, b);
+    out
+}
+
+fn write_usage(out: &mut String, a: &RunProfile, b: &RunProfile) {
+    let _ = writeln!(out, "## Usage\n");
+    let _ = writeln!(out, "| | `{}` | `{}` |", a.run_id, b.run_id);
+    let _ = writeln!(out, "|---|---|---|");
+    let model = |run: &RunProfile| run.model.clone().unwrap_or_else(|| "?".into());
+    let seconds = |run: &RunProfile| format!("{:.1}s", run.duration_ms as f64 / 1000.0);
+    let cost = |run: &RunProfile| format!("${:.4}", run.cost_usd);
+    let rows = [
+        ("Model", model(a), model(b)),
+        (
+            "Actions",
+            a.actions.len().to_string(),
+            b.actions.len().to_string(),
+        ),
+        ("Executor time", seconds(a), seconds(b)),
+        (
+            "Prompt tokens",
+            a.prompt_tokens.to_string(),
+            b.prompt_tokens.to_string(),
+        ),
+        (
+            "Completion tokens",
+            a.completion_tokens.to_string(),
+            b.completion_tokens.to_string(),
+        ),
+        ("Estimated cost", cost(a), cost(b)),
+    ];
+    for (label, value_a, value_b) in rows {
+        let _ = writeln!(out, "| {label} | {value_a} | {value_b} |");
+    }
+}
+
+/// Splits an action into per-file change text.
+fn action_changes(action: &ProposedAction) -> Vec<(String, String)> {
+    let ActionDetails::Patch(details) = &action.details else {
+        return touched_paths(action)
+            .into_iter()
+            .map(|path| {
+                let text = serde_json::to_string_pretty(&action.details).unwrap_or_default();
+                (path, text + "\n")
+            })
+            .collect();
+    };
+
+    let mut changes = Vec::new();
+    if let Some(diff) = &details.diff {
+        changes.extend(diff_hunks_by_file(diff));
+    }
+    for block in details.search_replace_blocks.iter().flatten() {
+        changes.push((
+            normalize_separators(&block.file),
+            format!(
+                "<<<<<<< SEARCH\n{}\n=======\n{}\n>>>>>>> REPLACE\n",
+                block.search.trim_end_matches('\n'),
+                block.replace.trim_end_matches('\n')
+            ),
+        ));
+    }
+    let mut whole: Vec<_> = details.whole_file_content.iter().flatten().collect();
+    whole.sort();
+    for (path, content) in whole {
+        changes.push((normalize_separators(path), content.clone()));
+    }
+    changes
+}
+
+/// Hunk lines of a unified diff grouped by target file, without headers.
+fn diff_hunks_by_file(diff: &str) -> Vec<(String, String)> {
+    let mut changes: Vec<(String, String)> = Vec::new();
+    let mut in_hunk = false;
+    for line in diff.lines() {
+        if let Some(path) = line.strip_prefix("+++ ") {
+            let path = path.split_whitespace().next().unwrap_or_default();
+            let path = normalize_separators(path.trim_start_matches("b/"));
+            changes.push((path, String::new()));
+            in_hunk = false;
+        } else if line.starts_with("--- ") || line.starts_with("diff --git ") {
+            in_hunk = false;
+        } else if line.starts_with("@@") {
+            in_hunk = true;
+        }
+        if in_hunk {
+            if let Some((_, text)) = changes.last_mut() {
+                text.push_str(line);
+                text.push('\n');
+            }
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patch(id: &str, diff: &str) -> ProposedAction {
+        ProposedAction::test_diff(id, diff)
+    }
+
+    fn profile(run_id: &str, model: &str, actions: Vec<ProposedAction>) -> RunProfile {
+        RunProfile {
+            run_id: run_id.to_string(),
+            task: Some("rename a".to_string()),
+            model: Some(model.to_string()),
+            actions,
+            prompt_tokens: 100,
+            ..RunProfile::default()
+        }
+    }
+
+    #[test]
+    fn test_diff_hunks_by_file() {
+        let diff = "--- a/x.rs\n+++ b/x.rs\n@@ -1 +1 @@\n-a\n+b\n--- a/y.rs\n+++ b/y.rs\n@@ -2 +2 @@\n-c\n+d\n";
+        let hunks = diff_hunks_by_file(diff);
+        assert_eq!(
+            hunks,
+            [
+                ("x.rs".to_string(), "@@ -1 +1 @@\n-a\n+b\n".to_string()),
+                ("y.rs".to_string(), "@@ -2 +2 @@\n-c\n+d\n".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_comparison() {
+        let a = profile(
+            "run_a",
+            "gpt-a",
+            vec![patch(
+                "a1",
+                "--- a/x.rs\n+++ b/x.rs\n@@ -1 +1 @@\n-a\n+b\n--- a/only_a.rs\n+++ b/only_a.rs\n@@ -1 +1 @@\n-q\n+r\n",
+            )],
+        );
+        let b = profile(
+            "run_b",
+            "gpt-b",
+            vec![patch("b1", "--- a/x.rs\n+++ b/x.rs\n@@ -1 +1 @@\n-a\n+c\n")],
+        );
+
+        let report = render_comparison(&a, &b);
+        assert!(report.contains("**Task:** rename a"));
+        assert!(report.contains("| only_a.rs | changed | — |"));
+        assert!(report.contains("| x.rs | changed | changed |"));
+        assert!(report.contains("### x.rs"));
+        assert!(report.contains("--- run_a\n+++ run_b\n"));
+        assert!(report.contains("-+b\n++c\n"));
+        assert!(report.contains("| Model | gpt-a | gpt-b |"));
+        assert!(report.contains("| Prompt tokens | 100 | 100 |"));
+    }
+
+    #[test]
+    fn test_identical_changes_are_reported() {
+        let diff = "--- a/x.rs\n+++ b/x.rs\n@@ -1 +1 @@\n-a\n+b\n";
+        let a = profile("run_a", "m", vec![patch("a1", diff)]);
+        let b = profile("run_b", "m", vec![patch("b1", diff)]);
+        assert!(render_comparison(&a, &b).contains("Both runs proposed the same change."));
+    }
+}
@@ -0,0 +1,192 @@
+//! Token usage and estimated cost of a run, summed across its log.
+//!
+//! Each `executor.completed` event with usage counts as one call, billed to
+//! the model of the `executor.started` event before it. A call's recorded
+//! `cost_usd` is used as is; calls logged without one are priced from
+//! [`crate::executor::pricing`], and calls on unpriced models are counted
+//! but add nothing to the total.
+
+use std::fmt::Write as _;
+use std::path::Path;
+
+use serde::Serialize;
+
+use super::summary::{payload_str, usage_value};
+use crate::error::NexusError;
+use crate::event_log::{EventLogReader, load_run_events};
+use crate::executor::pricing;
+use crate::types::RunEvent;
+
+/// Usage of one executor call.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CallCost {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+}
+
+/// Usage and cost of every executor call in a run.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RunCost {
+    pub run_id: String,
+    pub calls: Vec<CallCost>,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cost_usd: f64,
+    /// Calls whose cost is unknown and left out of `cost_usd`.
+    pub unpriced_calls: usize,
+}
+
+impl RunCost {
+    /// Loads `run_id` from `.nexus/runs/` under `project_root`.
+    pub fn load_for_run(project_root: &Path, run_id: &str) -> Result<Self, NexusError> {
+        let (events, _) = load_run_events(project_root, run_id)?;
+        Ok(Self::from_events(&events))
+    }
+
+    /// Loads the run recorded at `log_path`.
+    pub fn load(log_path: &Path) -> Result<Self, NexusError> {
+        let events = EventLogReader::open(log_path)?.load_all()?;
+        Ok(Self::from_events(&events))
+    }
+
+    pub fn from_events(events: &[RunEvent]) -> Self {
+        let mut model = None;
+        let mut calls = Vec::new();
+        for event in events {
+            match event.event_type.as_str() {
+                "executor.started" => model = payload_str(event, "model"),
+                "executor.completed" => {
+                    let tokens = |key: &str| usage_value(event, key).and_then(|v| v.as_u64());
+                    let (Some(prompt_tokens), Some(completion_tokens)) =
+                        (tokens("prompt_tokens"), tokens("completion_tokens"))
+                    else {
+                        continue;
+                    };
+                    let cost_usd = usage_value(event, "cost_usd")
+                        .and_then(|value| value.as_f64())
+                        .or_else(|| pricing::cost_usd(model?, prompt_tokens, completion_tokens));
+                    calls.push(CallCost {
+                        model: model.map(str::to_string),
+                        prompt_tokens,
+                        completion_tokens,
+                        cost_usd,
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        Self {
+            run_id: events
+                .first()
+                .map(|event| event.run_id.clone())
+                .unwrap_or_default(),
+            prompt_tokens: calls.iter().map(|call| call.prompt_tokens).sum(),
+            completion_tokens: calls.iter().map(|call| call.completion_tokens).sum(),
+            cost_usd: calls.iter().filter_map(|call| call.cost_usd).sum(),
+            unpriced_calls: calls.iter().filter(|call| call.cost_usd.is_none()).count(),
+            calls,
+        }
+    }
+
+    /// Renders one line per call and a total.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "Run {}\n", self.run_id);
+        if self.calls.is_empty() {
+            let _ = writeln!(out, "No token usage recorded");
+            return out;
+        }
+        let _ = writeln!(
+            out,
+            "{:<24} {:>12} {:>12} {:>10}",
+            "model", "prompt", "completion", "cost"
+        );
+        for call in &self.calls {
+            let _ = writeln!(
+                out,
+                "{:<24} {:>12} {:>12} {:>10}",
+                call.model.as_deref().unwrap_or("?"),
+                call.prompt_tokens,
+                call.completion_tokens,
+                call.cost_usd
+                    .map_or_else(|| "-".to_string(), |cost| format!("${cost:.4}"))
+            );
+        }
+        let _ = writeln!(
+            out,
+            "{:<24} {:>12} {:>12} {:>10}",
+            "total",
+            self.prompt_tokens,
+            self.completion_tokens,
+            format!("${:.4}", self.cost_usd)
+        );
+        if self.unpriced_calls > 0 {
+            let _ = writeln!(
+                out,
+                "\n{} call(s) on unpriced models are not included in the cost",
+                self.unpriced_calls
+            );
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_log::helpers;
+    use crate::executor::UsageInfo;
+
+    fn usage(prompt_tokens: u32, completion_tokens: u32) -> UsageInfo {
+        UsageInfo {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+        }
+    }
+
+    #[test]
+    fn test_cost_sums_calls_across_log() {
+        let events = vec![
+            helpers::run_started("run_1", "rename a"),
+            helpers::executor_started("run_1", "rename a", &[], "gpt-4o"),
+            helpers::with_usage(
+                helpers::executor_completed("run_1", 1, 100),
+                &usage(1_000_000, 100_000),
+                None,
+            ),
+            helpers::executor_started("run_1", "rename a", &[], "llama3"),
+            helpers::with_usage(
+                helpers::executor_completed("run_1", 1, 100),
+                &usage(500, 50),
+                None,
+            ),
+            helpers::executor_started("run_1", "rename a", &[], "gpt-4o"),
+            helpers::with_usage(
+                helpers::executor_completed("run_1", 0, 100),
+                &usage(10, 10),
+                Some(0.5),
+            ),
+            helpers::executor_completed("run_1", 0, 100),
+        ];
+
+        let cost = RunCost::from_events(&events);
+
+        assert_eq!(cost.calls.len(), 3);
+        assert_eq!(cost.prompt_tokens, 1_000_510);
+        assert_eq!(cost.completion_tokens, 100_060);
+        assert!((cost.cost_usd - 4.0).abs() < 1e-9);
+        assert_eq!(cost.unpriced_calls, 1);
+        assert_eq!(cost.calls[1].model.as_deref(), Some("llama3"));
+
+        let text = cost.render();
+        assert!(text.contains("total"));
+        assert!(text.contains("$4.0000"));
+        assert!(text.contains("1 call(s) on unpriced models"));
+    }
+}
@@ -0,0 +1,300 @@
+//! Standalone HTML run report.
+//!
+//! The report is a single file with inline CSS and SVG, so it can be shared
+//! with reviewers who do not have Nexus installed. It contains collapsible
+//! per-action diffs, the event timeline, and executor usage charts.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use super::summary::{
+    DiffStats, action_status, find_str, outcome, payload_str, payload_u64, usage_value,
+};
+use crate::error::NexusError;
+use crate::event_log::{EventLogPath, EventLogReader};
+use crate::preview::{load_action_artifact, render_diff};
+use crate::types::{ActionDetails, RunEvent};
+
+const REPORT_EXTENSION: &str = "report.html";
+const CHART_BAR_WIDTH: usize = 36;
+const CHART_BAR_GAP: usize = 12;
+const CHART_HEIGHT: usize = 120;
+
+const STYLE: &str = "\
+body{font-family:system-ui,sans-serif;margin:2rem auto;max-width:72rem;color:#1f2328}\
+h1{font-size:1.4rem}h2{font-size:1.1rem;margin-top:2rem;border-bottom:1px solid #d0d7de}\
+details{border:1px solid #d0d7de;border-radius:6px;margin:.5rem 0;padding:.25rem .75rem}\
+summary{cursor:pointer;font-weight:600}\
+pre{font:12px/1.45 ui-monospace,monospace;overflow-x:auto;margin:.5rem 0}\
+.add{background:#e6ffec;display:block}.del{background:#ffebe9;display:block}\
+.hunk{color:#0969da;display:block}.file{font-weight:700;display:block}\
+.badge{font-size:.75rem;border-radius:1rem;padding:0 .5rem;background:#eaeef2;margin-left:.5rem}\
+table{border-collapse:collapse;width:100%;font-size:.85rem}\
+td,th{border-bottom:1px solid #eaeef2;padding:.25rem .5rem;text-align:left;vertical-align:top}\
+code{font-size:.85em}";
+
+/// Writes `<run_id>.report.html` next to the run's log and returns its path.
+pub fn write_html_report_for_run(project_root: &Path, run_id: &str) -> Result<PathBuf, NexusError> {
+    let log_path = EventLogPath::new(project_root).for_run(run_id)?;
+    write_html_report(&log_path, project_root)
+}
+
+/// Writes the HTML report for the log at `log_path` (`<stem>.report.html`).
+///
+/// `project_root` is used to render search/replace and whole-file patches
+/// against the current files.
+pub fn write_html_report(log_path: &Path, project_root: &Path) -> Result<PathBuf, NexusError> {
+    let html = render_html_report(log_path, project_root)?;
+    let stem = log_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let path = log_path.with_file_name(format!("{stem}.{REPORT_EXTENSION}"));
+    std::fs::write(&path, html).map_err(|err| NexusError::IoError {
+        operation: "write run report".to_string(),
+        path: path.clone(),
+        source: err,
+    })?;
+    Ok(path)
+}
+
+/// Renders the HTML report for the run recorded at `log_path`.
+pub fn render_html_report(log_path: &Path, project_root: &Path) -> Result<String, NexusError> {
+    let events = EventLogReader::open(log_path)?.load_all()?;
+    let runs_dir = log_path.parent().unwrap_or_else(|| Path::new("."));
+    let run_id = events
+        .first()
+        .map(|event| event.run_id.as_str())
+        .unwrap_or_default();
+
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>Nexus run {id}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n\
+         <h1>Nexus run <code>{id}</code></h1>\n",
+        id = escape(run_id)
+    );
+    if let Some(task) = find_str(&events, &["run.started", "executor.started"], "task") {
+        let _ = writeln!(out, "<p><strong>Task:</strong> {}</p>", escape(task));
+    }
+    let _ = writeln!(
+        out,
+        "<p><strong>Outcome:</strong> {}</p>",
+        escape(&outcome(&events))
+    );
+
+    write_actions(&mut out, &events, runs_dir, project_root);
+    write_timeline(&mut out, &events);
+    write_usage_chart(&mut out, &events);
+
+    out.push_str("</body>\n</html>\n");
+    Ok(out)
+}
+
+fn write_actions(out: &mut String, events: &[RunEvent], runs_dir: &Path, project_root: &Path) {
+    out.push_str("<h2>Actions</h2>\n");
+    let proposed: Vec<&RunEvent> = events
+        .iter()
+        .filter(|event| event.event_type == "action.proposed")
+        .collect();
+    if proposed.is_empty() {
+        out.push_str("<p>No actions were proposed.</p>\n");
+        return;
+    }
+
+    for event in proposed {
+        let action_id = payload_str(event, "action_id").unwrap_or("?");
+        let summary = payload_str(event, "summary").unwrap_or("");
+        let action = event
+            .payload_ref
+            .as_ref()
+            .and_then(|payload_ref| load_action_artifact(runs_dir, payload_ref).ok());
+
+        let stats = match action.as_ref().map(|action| &action.details) {
+            Some(ActionDetails::Patch(details)) => {
+                let stats = DiffStats::from_patch(details);
+                format!("+{} −{}", stats.added, stats.removed)
+            }
+            _ => String::new(),
+        };
+        let _ = write!(
+            out,
+            "<details>\n<summary><code>{}</code> {}<span class=\"badge\">{}</span>\
+             <span class=\"badge\">{}</span></summary>\n",
+            escape(action_id),
+            escape(summary),
+            action_status(events, action_id),
+            stats
+        );
+        match action {
+            Some(action) => {
+                if let Some(why) = &action.why {
+                    let _ = writeln!(out, "<p>{}</p>", escape(why));
+                }
+                let diff = render_diff(std::slice::from_ref(&action), project_root, false);
+                let _ = writeln!(out, "<pre>{}</pre>", highlight_diff(&diff));
+            }
+            None => out.push_str("<p><em>Action details were not stored.</em></p>\n"),
+        }
+        out.push_str("</details>\n");
+    }
+}
+
+fn write_timeline(out: &mut String, events: &[RunEvent]) {
+    out.push_str(
+        "<h2>Timeline</h2>\n<table>\n<tr><th>Time</th><th>Event</th><th>Details</th></tr>\n",
+    );
+    for event in events {
+        let details = event
+            .payload
+            .as_ref()
+            .map(|payload| payload.to_string())
+            .unwrap_or_default();
+        let _ = writeln!(
+            out,
+            "<tr><td>{}</td><td><code>{}</code></td><td><code>{}</code></td></tr>",
+            event.time.format("%H:%M:%S%.3f"),
+            escape(event.event_type.as_str()),
+            escape(&details)
+        );
+    }
+    out.push_str("</table>\n");
+}
+
+/// Renders executor durations (and token counts when recorded) as SVG bars.
+fn write_usage_chart(out: &mut String, events: &[RunEvent]) {
+    let calls: Vec<(u64, u64)> = events
+        .iter()
+        .filter(|event| event.event_type == "executor.completed")
+        .map(|event| {
+            let duration = payload_u64(event, "duration_ms").unwrap_or(0);
+            let tokens = usage_value(event, "total_tokens")
+                .and_then(|value| value.as_u64())
+                .unwrap_or(0);
+            (duration, tokens)
+        })
+        .collect();
+
+    out.push_str("<h2>Usage</h2>\n");
+    if let Some(model) = find_str(events, &["executor.started"], "model") {
+        let _ = writeln!(out, "<p>Model: <code>{}</code></p>", escape(model));
+    }
+    if calls.is_empty() {
+        out.push_str("<p>No completed executor calls.</p>\n");
+        return;
+    }
+
+    let durations: Vec<u64> = calls.iter().map(|(duration, _)| *duration).collect();
+    write_bar_chart(out, "Executor time (ms)", &durations);
+    let tokens: Vec<u64> = calls.iter().map(|(_, tokens)| *tokens).collect();
+    if tokens.iter().any(|count| *count > 0) {
+        write_bar_chart(out, "Tokens", &tokens);
+    }
+}
+
+fn write_bar_chart(out: &mut String, title: &str, values: &[u64]) {
+    let max = values.iter().copied().max().unwrap_or(0).max(1);
+    let width = values.len() * (CHART_BAR_WIDTH + CHART_BAR_GAP) + CHART_BAR_GAP;
+    let _ = writeln!(
+        out,
+        "<figure><figcaption>{}</figcaption>\n<svg width=\"{width}\" height=\"{}\" role=\"img\">",
+        escape(title),
+        CHART_HEIGHT + 20
+    );
+    for (index, value) in values.iter().enumerate() {
+        let height = (*value as f64 / max as f64 * CHART_HEIGHT as f64).round() as usize;
+        let x = CHART_BAR_GAP + index * (CHART_BAR_WIDTH + CHART_BAR_GAP);
+        let _ = writeln!(
+            out,
+            "<rect x=\"{x}\" y=\"{}\" width=\"{CHART_BAR_WIDTH}\" height=\"{height}\" fill=\"#0969da\">\
+             <title>{value}</title></rect>\
+             <text x=\"{}\" y=\"{}\" font-size=\"10\" text-anchor=\"middle\">{value}</text>",
+            CHART_HEIGHT - height,
+            x + CHART_BAR_WIDTH / 2,
+            CHART_HEIGHT + 14
+        );
+    }
+    out.push_str("</svg></figure>\n");
+}
+
+/// Wraps diff lines in spans classed by line kind.
+fn highlight_diff(diff: &str) -> String {
+    let mut out = String::with_capacity(diff.len() * 2);
+    for line in diff.lines() {
+        let class = if line.starts_with("+++") || line.starts_with("---") {
+            "file"
+        } else if line.starts_with('+') {
+            "add"
+        } else if line.starts_with('-') {
+            "del"
+        } else if line.starts_with("@@") {
+            "hunk"
+        } else {
+            ""
+        };
+        if class.is_empty() {
+            let _ = writeln!(out, "{}", escape(line));
+        } else {
+            let _ = write!(out, "<span class=\"{class}\">{}</span>", escape(line));
+        }
+    }
+    out
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_log::{EventLogWriter, helpers};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_highlight_diff_escapes_and_classes_lines() {
+        let html = highlight_diff("--- a/x\n+++ b/x\n@@ -1 +1 @@\n-<a>\n+<b>\n ctx\n");
+        assert!(html.contains("<span class=\"del\">-&lt;a&gt;</span>"));
+        assert!(html.contains("<span class=\"add\">+&lt;b&gt;</span>"));
+        assert!(html.contains("<span class=\"hunk\">@@ -1 +1 @@</span>"));
+        assert!(html.contains(" ctx\n"));
+    }
+
+    #[test]
+    fn test_write_html_report() {
+        let dir = TempDir::new().unwrap();
+        let log_path = dir.path().join("run_1.jsonl");
+        let mut writer = EventLogWriter::open(&log_path).unwrap();
+        for event in [
+            helpers::run_started("run_1", "rename <thing>"),
+            helpers::action_proposed("run_1", "run_1-action-1", "patch", "Rename", None),
+            helpers::executor_completed("run_1", 1, 1200),
+        ] {
+            writer.append(&event).unwrap();
+        }
+        writer.sync().unwrap();
+        drop(writer);
+
+        let path = write_html_report(&log_path, dir.path()).unwrap();
+        assert_eq!(path, dir.path().join("run_1.report.html"));
+
+        let html = std::fs::read_to_string(path).unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("rename &lt;thing&gt;"));
+        assert!(html.contains("<details>"));
+        assert!(html.contains("<code>action.proposed</code>"));
+        assert!(html.contains("<svg"));
+    }
+}
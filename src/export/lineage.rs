@@ -0,0 +1,160 @@
+//! Tree of related runs: the run that spawned a run through a handoff or a
+//! follow-up task, and the runs it spawned in turn.
+//!
+//! Links come from the `parent_run_id` recorded on each run's `run.started`
+//! event, read through the run index.
+
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::error::NexusError;
+use crate::event_log::EventLogIndex;
+
+/// A run and the runs it spawned.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RunTree {
+    pub run_id: String,
+    /// Final status, or `None` while the run is in progress.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<RunTree>,
+}
+
+impl RunTree {
+    /// Loads the tree `run_id` belongs to from `.nexus/runs/` under
+    /// `project_root`.
+    pub fn load_for_run(project_root: &Path, run_id: &str) -> Result<Self, NexusError> {
+        Ok(Self::from_index(
+            &EventLogIndex::load_for_project(project_root)?,
+            run_id,
+        ))
+    }
+
+    /// The tree `run_id` belongs to, rooted at its oldest ancestor in
+    /// `index`.
+    pub fn from_index(index: &EventLogIndex, run_id: &str) -> Self {
+        let mut root = run_id;
+        let mut seen = HashSet::from([run_id]);
+        while let Some(parent) = index
+            .get(root)
+            .and_then(|entry| entry.parent_run_id.as_deref())
+        {
+            // A parent missing from the index still names the root.
+            if !seen.insert(parent) {
+                break;
+            }
+            root = parent;
+        }
+        Self::subtree(index, root, &mut HashSet::new())
+    }
+
+    fn subtree<'a>(index: &'a EventLogIndex, run_id: &'a str, seen: &mut HashSet<&'a str>) -> Self {
+        seen.insert(run_id);
+        let mut children = Vec::new();
+        for child in index.children(run_id) {
+            if !seen.contains(child.run_id.as_str()) {
+                children.push(Self::subtree(index, &child.run_id, seen));
+            }
+        }
+        Self {
+            run_id: run_id.to_string(),
+            status: index.get(run_id).and_then(|entry| entry.status.clone()),
+            children,
+        }
+    }
+
+    /// Number of runs in the tree.
+    pub fn run_count(&self) -> usize {
+        1 + self.children.iter().map(Self::run_count).sum::<usize>()
+    }
+
+    /// Renders the tree as indented plain text, marking `current`.
+    pub fn render(&self, current: &str) -> String {
+        let mut out = String::new();
+        self.render_into(&mut out, "", "", current);
+        out
+    }
+
+    fn render_into(&self, out: &mut String, lead: &str, indent: &str, current: &str) {
+        let status = self.status.as_deref().unwrap_or("running");
+        let marker = if self.run_id == current {
+            "  <- this run"
+        } else {
+            ""
+        };
+        let _ = writeln!(out, "{lead}{} ({status}){marker}", self.run_id);
+        for (index, child) in self.children.iter().enumerate() {
+            let last = index + 1 == self.children.len();
+            let (branch, rest) = if last {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+            child.render_into(
+                out,
+                &format!("{indent}{branch}"),
+                &format!("{indent}{rest}"),
+                current,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_log::{EventLogWriter, helpers};
+    use crate::types::RunStatus;
+    use tempfile::TempDir;
+
+    fn write_run(runs_dir: &Path, run_id: &str, parent: Option<&str>) {
+        let mut writer = EventLogWriter::open(&runs_dir.join(format!("{run_id}.jsonl"))).unwrap();
+        let mut started = helpers::run_started(run_id, "task");
+        if let Some(parent) = parent {
+            started = started.with_parent_run(parent);
+        }
+        writer.append(&started).unwrap();
+        writer
+            .append(&helpers::run_completed(run_id, RunStatus::Success, 0))
+            .unwrap();
+        writer.sync().unwrap();
+    }
+
+    #[test]
+    fn test_tree_is_rooted_at_oldest_ancestor() {
+        let dir = TempDir::new().unwrap();
+        write_run(dir.path(), "run_a", None);
+        write_run(dir.path(), "run_b", Some("run_a"));
+        write_run(dir.path(), "run_c", Some("run_b"));
+        write_run(dir.path(), "run_d", Some("run_a"));
+        write_run(dir.path(), "run_e", None);
+        let index = EventLogIndex::load(dir.path()).unwrap();
+
+        let tree = RunTree::from_index(&index, "run_c");
+        assert_eq!(tree.run_id, "run_a");
+        assert_eq!(tree.run_count(), 4);
+        assert_eq!(
+            tree.render("run_c"),
+            "run_a (success)\n\
+             ├── run_b (success)\n\
+             │   └── run_c (success)  <- this run\n\
+             └── run_d (success)\n"
+        );
+        assert_eq!(RunTree::from_index(&index, "run_e").run_count(), 1);
+    }
+
+    #[test]
+    fn test_cyclic_links_terminate() {
+        let dir = TempDir::new().unwrap();
+        write_run(dir.path(), "run_a", Some("run_b"));
+        write_run(dir.path(), "run_b", Some("run_a"));
+        let index = EventLogIndex::load(dir.path()).unwrap();
+
+        let tree = RunTree::from_index(&index, "run_a");
+        assert_eq!(tree.run_count(), 2);
+    }
+}
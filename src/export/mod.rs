@@ -0,0 +1,21 @@
+//! Exporters turning run data into formats consumed by other tools.
+
+pub mod anonymize;
+pub mod compare;
+pub mod cost;
+pub mod html;
+pub mod lineage;
+pub mod sarif;
+pub mod summary;
+pub mod tables;
+pub mod timeline;
+
+pub use anonymize::{Anonymizer, export_log_for_run};
+pub use compare::{RunProfile, render_comparison};
+pub use cost::RunCost;
+pub use html::{write_html_report, write_html_report_for_run};
+pub use lineage::RunTree;
+pub use sarif::to_sarif;
+pub use summary::{DiffStats, write_summary, write_summary_for_run};
+pub use tables::{EventTables, export_tables};
+pub use timeline::{Timeline, TimelineEntry};
@@ -0,0 +1,279 @@
+//! SARIF 2.1.0 export of proposed actions.
+//!
+//! Each file touched by a patch becomes one result carrying the action
+//! summary, the affected region, and a `fix` describing the replacement, so
+//! code scanning UIs can show Nexus proposals inline.
+
+use std::path::Path;
+
+use serde_json::{Value, json};
+
+use crate::apply::matcher::{MatchOptions, find_match};
+use crate::types::{ActionDetails, PatchDetails, ProposedAction, SearchReplaceBlock};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+const RULE_ID: &str = "nexus/proposed-change";
+const TOOL_NAME: &str = "nexus";
+
+/// Converts `actions` to a SARIF log. `root` is used to locate search/replace
+/// and whole-file targets so their regions can be reported.
+pub fn to_sarif(actions: &[ProposedAction], root: &Path) -> Value {
+    let results: Vec<Value> = actions
+        .iter()
+        .flat_map(|action| action_results(action, root))
+        .collect();
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": SARIF_VERSION,
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": TOOL_NAME,
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                    "rules": [{
+                        "id": RULE_ID,
+                        "shortDescription": {"text": "Change proposed by Nexus"}
+                    }]
+                }
+            },
+            "results": results
+        }]
+    })
+}
+
+/// A contiguous replacement in one file, in 1-based line numbers.
+struct Replacement {
+    file: String,
+    start_line: usize,
+    /// Number of original lines replaced; zero for a pure insertion.
+    line_count: usize,
+    inserted: String,
+}
+
+fn action_results(action: &ProposedAction, root: &Path) -> Vec<Value> {
+    let ActionDetails::Patch(details) = &action.details else {
+        return Vec::new();
+    };
+
+    let mut message = action.summary.clone();
+    if let Some(why) = &action.why {
+        message.push_str(" — ");
+        message.push_str(why);
+    }
+
+    let mut by_file: Vec<(String, Vec<Replacement>)> = Vec::new();
+    for replacement in patch_replacements(details, root) {
+        match by_file
+            .iter_mut()
+            .find(|(file, _)| *file == replacement.file)
+        {
+            Some((_, replacements)) => replacements.push(replacement),
+            None => by_file.push((replacement.file.clone(), vec![replacement])),
+        }
+    }
+
+    by_file
+        .into_iter()
+        .map(|(file, replacements)| {
+            let first = &replacements[0];
+            json!({
+                "ruleId": RULE_ID,
+                "level": "note",
+                "message": {"text": message},
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": {"uri": file},
+                        "region": region(first)
+                    }
+                }],
+                "partialFingerprints": {"nexusActionId": action.id},
+                "fixes": [{
+                    "description": {"text": action.summary},
+                    "artifactChanges": [{
+                        "artifactLocation": {"uri": file},
+                        "replacements": replacements.iter().map(|r| json!({
+                            "deletedRegion": region(r),
+                            "insertedContent": {"text": r.inserted}
+                        })).collect::<Vec<_>>()
+                    }]
+                }]
+            })
+        })
+        .collect()
+}
+
+fn region(replacement: &Replacement) -> Value {
+    if replacement.line_count == 0 {
+        // Zero-length region: insertion before `start_line`.
+        json!({"startLine": replacement.start_line, "startColumn": 1, "endColumn": 1})
+    } else {
+        json!({
+            "startLine": replacement.start_line,
+            "endLine": replacement.start_line + replacement.line_count - 1
+        })
+    }
+}
+
+fn patch_replacements(details: &PatchDetails, root: &Path) -> Vec<Replacement> {
+    let mut replacements = Vec::new();
+    if let Some(diff) = &details.diff {
+        replacements.extend(diff_replacements(diff));
+    }
+    for block in details.search_replace_blocks.iter().flatten() {
+        replacements.extend(search_replace_replacement(block, root));
+    }
+    if let Some(contents) = &details.whole_file_content {
+        let mut paths: Vec<_> = contents.keys().collect();
+        paths.sort();
+        for path in paths {
+            let existing_lines = std::fs::read_to_string(root.join(path))
+                .map(|content| content.lines().count())
+                .unwrap_or(0);
+            replacements.push(Replacement {
+                file: path.clone(),
+                start_line: 1,
+                line_count: existing_lines,
+                inserted: contents[path].clone(),
+            });
+        }
+    }
+    replacements
+}
+
+fn search_replace_replacement(block: &SearchReplaceBlock, root: &Path) -> Option<Replacement> {
+    let content = std::fs::read_to_string(root.join(&block.file)).ok()?;
+    let found = find_match(&content, &block.search, MatchOptions::default())?;
+    let start_line = content[..found.range.start].matches('\n').count() + 1;
+    let line_count = content[found.range].lines().count().max(1);
+    Some(Replacement {
+        file: block.file.clone(),
+        start_line,
+        line_count,
+        inserted: block.replace.clone(),
+    })
+}
+
+/// Extracts one replacement per hunk from a unified diff.
+fn diff_replacements(diff: &str) -> Vec<Replacement> {
+    let mut replacements = Vec::new();
+    let mut file: Option<String> = None;
+    let mut current: Option<Replacement> = None;
+
+    for line in diff.lines() {
+        if let Some(path) = line.strip_prefix("+++ ") {
+            replacements.extend(current.take());
+            let path = path.split_whitespace().next().unwrap_or_default();
+            file = (path != "/dev/null")
+                .then(|| crate::paths::normalize_separators(path.trim_start_matches("b/")));
+        } else if line.starts_with("--- ") {
+            replacements.extend(current.take());
+        } else if let Some(header) = line.strip_prefix("@@ ") {
+            replacements.extend(current.take());
+            if let (Some(file), Some((start, count))) = (&file, parse_old_range(header)) {
+                current = Some(Replacement {
+                    file: file.clone(),
+                    start_line: if count == 0 { start + 1 } else { start },
+                    line_count: count,
+                    inserted: String::new(),
+                });
+            }
+        } else if let Some(replacement) = current.as_mut() {
+            if let Some(text) = line.strip_prefix('+').or_else(|| line.strip_prefix(' ')) {
+                replacement.inserted.push_str(text);
+                replacement.inserted.push('\n');
+            } else if line.is_empty() {
+                replacement.inserted.push('\n');
+            }
+        }
+    }
+    replacements.extend(current);
+    replacements
+}
+
+/// Parses the old-file range `-start[,count]` of a hunk header.
+fn parse_old_range(header: &str) -> Option<(usize, usize)> {
+    let old = header.split_whitespace().next()?.strip_prefix('-')?;
+    let (start, count) = match old.split_once(',') {
+        Some((start, count)) => (start.parse().ok()?, count.parse().ok()?),
+        None => (old.parse().ok()?, 1),
+    };
+    Some((start, count))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{MatchMode, PatchFormat};
+    use tempfile::TempDir;
+
+    fn patch_action(details: PatchDetails) -> ProposedAction {
+        ProposedAction::test("run_1-action-1", ActionDetails::Patch(details))
+            .with_summary("Rename helper")
+            .with_why("clearer name")
+    }
+
+    #[test]
+    fn test_unified_diff_to_sarif() {
+        let diff = "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -3,2 +3,2 @@\n fn keep() {}\n-fn old() {}\n+fn new() {}\n";
+        let action = patch_action(PatchDetails {
+            diff: Some(diff.to_string()),
+            ..Default::default()
+        });
+
+        let sarif = to_sarif(&[action], Path::new("."));
+
+        assert_eq!(sarif["version"], "2.1.0");
+        let result = &sarif["runs"][0]["results"][0];
+        assert_eq!(result["ruleId"], RULE_ID);
+        assert_eq!(result["message"]["text"], "Rename helper — clearer name");
+        let location = &result["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "src/lib.rs");
+        assert_eq!(location["region"], json!({"startLine": 3, "endLine": 4}));
+        let replacement = &result["fixes"][0]["artifactChanges"][0]["replacements"][0];
+        assert_eq!(
+            replacement["insertedContent"]["text"],
+            "fn keep() {}\nfn new() {}\n"
+        );
+    }
+
+    #[test]
+    fn test_search_replace_region_from_file() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("lib.rs"), "a\nb\nc\n").unwrap();
+        let action = patch_action(PatchDetails {
+            format: PatchFormat::SearchReplace,
+            search_replace_blocks: Some(vec![SearchReplaceBlock {
+                file: "lib.rs".to_string(),
+                search: "b\n".to_string(),
+                replace: "B\n".to_string(),
+                match_mode: MatchMode::Exact,
+            }]),
+            ..Default::default()
+        });
+
+        let sarif = to_sarif(&[action], dir.path());
+
+        let region = &sarif["runs"][0]["results"][0]["locations"][0]["physicalLocation"]["region"];
+        assert_eq!(*region, json!({"startLine": 2, "endLine": 2}));
+    }
+
+    #[test]
+    fn test_new_file_insertion_region() {
+        let diff = "--- /dev/null\n+++ b/new.rs\n@@ -0,0 +1 @@\n+fn added() {}\n";
+        let action = patch_action(PatchDetails {
+            diff: Some(diff.to_string()),
+            ..Default::default()
+        });
+
+        let sarif = to_sarif(&[action], Path::new("."));
+
+        let region = &sarif["runs"][0]["results"][0]["locations"][0]["physicalLocation"]["region"];
+        assert_eq!(
+            *region,
+            json!({"startLine": 1, "startColumn": 1, "endColumn": 1})
+        );
+    }
+}
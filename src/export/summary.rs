@@ -0,0 +1,399 @@
+//! Markdown run summaries generated from the event log.
+//!
+//! The summary is derived entirely from `<run_id>.jsonl` and its artifacts,
+//! so it can be regenerated for past runs.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::error::NexusError;
+use crate::event_log::{EventLogPath, EventLogReader};
+use crate::preview::load_action_artifact;
+use crate::types::{ActionDetails, PatchDetails, RunEvent};
+
+const SUMMARY_EXTENSION: &str = "summary.md";
+
+/// Line counts added and removed by an action.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiffStats {
+    pub added: usize,
+    pub removed: usize,
+}
+
+impl DiffStats {
+    /// Counts changed lines in a patch without consulting the working tree.
+    pub fn from_patch(details: &PatchDetails) -> Self {
+        let mut stats = Self::default();
+        if let Some(diff) = &details.diff {
+            for line in diff.lines() {
+                if line.starts_with("+++") || line.starts_with("---") {
+                    continue;
+                }
+                if line.starts_with('+') {
+                    stats.added += 1;
+                } else if line.starts_with('-') {
+                    stats.removed += 1;
+                }
+            }
+        }
+        for block in details.search_replace_blocks.iter().flatten() {
+            stats.removed += block.search.lines().count();
+            stats.added += block.replace.lines().count();
+        }
+        for content in details.whole_file_content.iter().flat_map(|c| c.values()) {
+            stats.added += content.lines().count();
+        }
+        stats
+    }
+}
+
+/// Writes `<run_id>.summary.md` next to the run's log and returns its path.
+pub fn write_summary_for_run(project_root: &Path, run_id: &str) -> Result<PathBuf, NexusError> {
+    let log_path = EventLogPath::new(project_root).for_run(run_id)?;
+    write_summary(&log_path)
+}
+
+/// Writes the summary for the log at `log_path` (`<stem>.summary.md`).
+///
+/// The writer for the log must be closed first; the reader takes a shared lock.
+pub fn write_summary(log_path: &Path) -> Result<PathBuf, NexusError> {
+    let markdown = render_summary(log_path)?;
+    let stem = log_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let path = log_path.with_file_name(format!("{stem}.{SUMMARY_EXTENSION}"));
+    std::fs::write(&path, markdown).map_err(|err| NexusError::IoError {
+        operation: "write run summary".to_string(),
+        path: path.clone(),
+        source: err,
+    })?;
+    Ok(path)
+}
+
+/// Renders a markdown summary of the run recorded at `log_path`.
+pub fn render_summary(log_path: &Path) -> Result<String, NexusError> {
+    let events = EventLogReader::open(log_path)?.load_all()?;
+    let runs_dir = log_path.parent().unwrap_or_else(|| Path::new("."));
+    let run_id = events
+        .first()
+        .map(|event| event.run_id.clone())
+        .unwrap_or_default();
+
+    let mut out = String::new();
+    let _ = writeln!(out, "# Nexus run `{run_id}`\n");
+
+    if let Some(task) = find_str(&events, &["run.started", "executor.started"], "task") {
+        let _ = writeln!(out, "**Task:** {task}\n");
+    }
+    let _ = writeln!(out, "**Outcome:** {}\n", outcome(&events));
+
+    write_actions(&mut out, &events, runs_dir);
+    write_approvals(&mut out, &events);
+    write_verification(&mut out, &events);
+    write_usage(&mut out, &events);
+
+    Ok(out)
+}
+
+fn write_actions(out: &mut String, events: &[RunEvent], runs_dir: &Path) {
+    let proposed: Vec<&RunEvent> = events
+        .iter()
+        .filter(|event| event.event_type == "action.proposed")
+        .collect();
+
+    let _ = writeln!(out, "## Actions\n");
+    if proposed.is_empty() {
+        let _ = writeln!(out, "No actions were proposed.\n");
+        return;
+    }
+
+    let _ = writeln!(out, "| Action | Kind | Summary | +/- | Status |");
+    let _ = writeln!(out, "|--------|------|---------|-----|--------|");
+    for event in proposed {
+        let action_id = payload_str(event, "action_id").unwrap_or("?");
+        let kind = payload_str(event, "kind").unwrap_or("?");
+        let summary = payload_str(event, "summary").unwrap_or("");
+        let stats = event
+            .payload_ref
+            .as_ref()
+            .and_then(|payload_ref| load_action_artifact(runs_dir, payload_ref).ok())
+            .and_then(|action| match action.details {
+                ActionDetails::Patch(details) => Some(DiffStats::from_patch(&details)),
+                _ => None,
+            })
+            .map(|stats| format!("+{} -{}", stats.added, stats.removed))
+            .unwrap_or_else(|| "—".to_string());
+        let status = action_status(events, action_id);
+        let _ = writeln!(
+            out,
+            "| `{action_id}` | {kind} | {} | {stats} | {status} |",
+            escape_cell(summary)
+        );
+    }
+    out.push('\n');
+}
+
+fn write_approvals(out: &mut String, events: &[RunEvent]) {
+    let decisions: Vec<String> = events
+        .iter()
+        .filter_map(|event| match event.event_type.as_str() {
+            "permission.granted" => Some(format!(
+                "- {} granted ({}{})",
+                decided(event),
+                payload_str(event, "scope").unwrap_or("once"),
+                if notified(event) { ", notify" } else { "" }
+            )),
+            "permission.denied" => Some(format!(
+                "- {} denied: {}",
+                decided(event),
+                payload_str(event, "reason").unwrap_or("")
+            )),
+            _ => None,
+        })
+        .collect();
+
+    if decisions.is_empty() {
+        return;
+    }
+    let _ = writeln!(out, "## Approvals\n");
+    for line in decisions {
+        let _ = writeln!(out, "{line}");
+    }
+    out.push('\n');
+}
+
+fn write_verification(out: &mut String, events: &[RunEvent]) {
+    let checks: Vec<&RunEvent> = events
+        .iter()
+        .filter(|event| event.event_type.as_str().starts_with("verification."))
+        .collect();
+    if checks.is_empty() {
+        return;
+    }
+
+    let _ = writeln!(out, "## Verification\n");
+    for event in checks {
+        let name = payload_str(event, "name")
+            .or_else(|| payload_str(event, "command"))
+            .unwrap_or(event.event_type.as_str());
+        let passed = event
+            .payload
+            .as_ref()
+            .and_then(|payload| payload.get("success"))
+            .and_then(Value::as_bool);
+        let mark = match passed {
+            Some(true) => "passed",
+            Some(false) => "failed",
+            None => "recorded",
+        };
+        let _ = writeln!(out, "- {name}: {mark}");
+    }
+    out.push('\n');
+}
+
+fn write_usage(out: &mut String, events: &[RunEvent]) {
+    let model = find_str(events, &["executor.started"], "model");
+    let duration_ms = events
+        .iter()
+        .filter(|event| event.event_type == "executor.completed")
+        .filter_map(|event| payload_u64(event, "duration_ms"))
+        .sum::<u64>();
+    let prompt_tokens = sum_usage(events, "prompt_tokens");
+    let completion_tokens = sum_usage(events, "completion_tokens");
+    let cost: f64 = events
+        .iter()
+        .filter_map(|event| usage_value(event, "cost_usd")?.as_f64())
+        .sum();
+
+    let _ = writeln!(out, "## Usage\n");
+    if let Some(model) = model {
+        let _ = writeln!(out, "- Model: {model}");
+    }
+    let _ = writeln!(out, "- Executor time: {:.1}s", duration_ms as f64 / 1000.0);
+    if prompt_tokens + completion_tokens > 0 {
+        let _ = writeln!(
+            out,
+            "- Tokens: {prompt_tokens} prompt / {completion_tokens} completion"
+        );
+    }
+    if cost > 0.0 {
+        let _ = writeln!(out, "- Estimated cost: ${cost:.4}");
+    }
+}
+
+pub(super) fn outcome(events: &[RunEvent]) -> String {
+    for event in events.iter().rev() {
+        match event.event_type.as_str() {
+            "run.completed" | "run.cancelled" | "run.budget_exceeded" => {
+                return payload_str(event, "status")
+                    .unwrap_or("unknown")
+                    .to_string();
+            }
+            "executor.failed" => {
+                return format!("failed ({})", payload_str(event, "error").unwrap_or(""));
+            }
+            _ => {}
+        }
+    }
+    "in progress".to_string()
+}
+
+fn notified(event: &RunEvent) -> bool {
+    event
+        .payload
+        .as_ref()
+        .and_then(|payload| payload.get("notify"))
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+}
+
+/// What a permission event decided on: one action, or a group of them.
+fn decided(event: &RunEvent) -> String {
+    let ids = event.action_ids();
+    match payload_str(event, "group_id") {
+        Some(group) => {
+            let ids: Vec<String> = ids.iter().map(|id| format!("`{id}`")).collect();
+            format!("group `{group}` ({})", ids.join(", "))
+        }
+        None => format!("`{}`", ids.first().copied().unwrap_or("?")),
+    }
+}
+
+pub(super) fn action_status(events: &[RunEvent], action_id: &str) -> &'static str {
+    let mut status = "proposed";
+    for event in events {
+        if !event.action_ids().contains(&action_id) {
+            continue;
+        }
+        status = match event.event_type.as_str() {
+            "permission.granted" => "approved",
+            "permission.denied" => "denied",
+            "tool.executed" => "applied",
+            "tool.failed" => "failed",
+            _ => status,
+        };
+    }
+    status
+}
+
+pub(super) fn payload_str<'a>(event: &'a RunEvent, key: &str) -> Option<&'a str> {
+    event.payload.as_ref()?.get(key)?.as_str()
+}
+
+pub(super) fn payload_u64(event: &RunEvent, key: &str) -> Option<u64> {
+    event.payload.as_ref()?.get(key)?.as_u64()
+}
+
+/// Looks up a usage figure either at the payload root or under `usage`.
+pub(super) fn usage_value<'a>(event: &'a RunEvent, key: &str) -> Option<&'a Value> {
+    let payload = event.payload.as_ref()?;
+    payload
+        .get("usage")
+        .and_then(|usage| usage.get(key))
+        .or_else(|| payload.get(key))
+}
+
+fn sum_usage(events: &[RunEvent], key: &str) -> u64 {
+    events
+        .iter()
+        .filter_map(|event| usage_value(event, key)?.as_u64())
+        .sum()
+}
+
+pub(super) fn find_str<'a>(events: &'a [RunEvent], types: &[&str], key: &str) -> Option<&'a str> {
+    events
+        .iter()
+        .filter(|event| types.contains(&event.event_type.as_str()))
+        .find_map(|event| payload_str(event, key))
+}
+
+fn escape_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_log::{EventLogWriter, PayloadStore, helpers};
+    use crate::types::{ProposedAction, RunStatus};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_diff_stats_from_unified_diff() {
+        let details = PatchDetails {
+            diff: Some("--- a/x\n+++ b/x\n@@ -1,2 +1,2 @@\n-a\n+b\n+c\n d\n".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            DiffStats::from_patch(&details),
+            DiffStats {
+                added: 2,
+                removed: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_write_summary_from_event_log() {
+        let dir = TempDir::new().unwrap();
+        let log_path = dir.path().join("run_1.jsonl");
+        let action =
+            ProposedAction::test_diff("run_1-action-1", "--- a/x\n+++ b/x\n@@ -1 +1 @@\n-a\n+b\n")
+                .with_summary("Rename a | b");
+        let payload_ref = PayloadStore::for_log(&log_path, "run_1")
+            .unwrap()
+            .write(
+                "action_001.json",
+                &serde_json::to_vec(&action).unwrap(),
+                "application/json",
+                "proposed action",
+            )
+            .unwrap();
+
+        let mut writer = EventLogWriter::open(&log_path).unwrap();
+        for event in [
+            helpers::run_started("run_1", "rename a"),
+            helpers::executor_started("run_1", "rename a", &["a.rs".to_string()], "gpt-test"),
+            helpers::action_proposed("run_1", &action.id, "patch", &action.summary, None)
+                .with_payload_ref(payload_ref),
+            helpers::executor_completed("run_1", 1, 1500),
+            helpers::permission_granted("run_1", &action.id, "once"),
+            helpers::tool_executed("run_1", &action.id, vec!["x".to_string()]),
+            helpers::run_completed("run_1", RunStatus::Success, 1),
+        ] {
+            writer.append(&event).unwrap();
+        }
+        writer.sync().unwrap();
+        drop(writer);
+
+        let path = write_summary(&log_path).unwrap();
+        assert_eq!(path, dir.path().join("run_1.summary.md"));
+
+        let summary = std::fs::read_to_string(path).unwrap();
+        assert!(summary.contains("# Nexus run `run_1`"));
+        assert!(summary.contains("**Task:** rename a"));
+        assert!(summary.contains("**Outcome:** success"));
+        assert!(
+            summary.contains("| `run_1-action-1` | patch | Rename a \\| b | +1 -1 | applied |")
+        );
+        assert!(summary.contains("- `run_1-action-1` granted (once)"));
+        assert!(summary.contains("- Model: gpt-test"));
+        assert!(summary.contains("- Executor time: 1.5s"));
+    }
+
+    #[test]
+    fn test_group_decision_covers_every_member() {
+        let ids = ["act_1".to_string(), "act_2".to_string()];
+        let events = [helpers::permission_granted_group(
+            "run_1", "setup", &ids, "once",
+        )];
+        let mut out = String::new();
+        write_approvals(&mut out, &events);
+        assert!(out.contains("- group `setup` (`act_1`, `act_2`) granted (once)\n"));
+        assert_eq!(action_status(&events, "act_2"), "approved");
+        assert_eq!(action_status(&events, "act_3"), "proposed");
+    }
+}
@@ -0,0 +1,383 @@
+//! Run logs flattened into tables for audit queries.
+//!
+//! Three tables are produced: `events` (one row per event, payload kept as
+//! JSON text), `actions` (one row per proposed action with its final status),
+//! and `usage` (one row per executor call, priced as `nexus runs cost` does).
+//! They can be written as CSV files or into a SQLite database; exporting a
+//! run into a database that already has it replaces its rows.
+
+use std::path::{Path, PathBuf};
+
+use rusqlite::{Connection, params};
+use serde::Serialize;
+
+use super::anonymize::Anonymizer;
+use super::cost::RunCost;
+use super::summary::{action_status, payload_str};
+use crate::error::NexusError;
+use crate::event_log::{EventLogIndex, load_run_events};
+use crate::types::{AnonymizePolicy, RunEvent};
+
+/// File names of the CSV tables, in the order they are written.
+pub const CSV_FILE_NAMES: [&str; 3] = ["events.csv", "actions.csv", "usage.csv"];
+
+const SQLITE_SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS events (
+        run_id TEXT NOT NULL,
+        seq INTEGER NOT NULL,
+        time TEXT NOT NULL,
+        type TEXT NOT NULL,
+        action_id TEXT,
+        model TEXT,
+        payload TEXT,
+        PRIMARY KEY (run_id, seq)
+    );
+    CREATE TABLE IF NOT EXISTS actions (
+        run_id TEXT NOT NULL,
+        action_id TEXT NOT NULL,
+        kind TEXT,
+        summary TEXT,
+        status TEXT NOT NULL,
+        proposed_at TEXT NOT NULL,
+        PRIMARY KEY (run_id, action_id)
+    );
+    CREATE TABLE IF NOT EXISTS usage (
+        run_id TEXT NOT NULL,
+        call INTEGER NOT NULL,
+        model TEXT,
+        prompt_tokens INTEGER NOT NULL,
+        completion_tokens INTEGER NOT NULL,
+        cost_usd REAL,
+        PRIMARY KEY (run_id, call)
+    );
+";
+
+/// One event; `seq` is its position in the run, from 1.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EventRow {
+    pub run_id: String,
+    pub seq: u64,
+    pub time: String,
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub action_id: Option<String>,
+    pub model: Option<String>,
+    /// The payload as JSON text.
+    pub payload: Option<String>,
+}
+
+/// One proposed action and what became of it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ActionRow {
+    pub run_id: String,
+    pub action_id: String,
+    pub kind: Option<String>,
+    pub summary: Option<String>,
+    pub status: String,
+    pub proposed_at: String,
+}
+
+/// One executor call; `call` is its position in the run, from 1.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UsageRow {
+    pub run_id: String,
+    pub call: u64,
+    pub model: Option<String>,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cost_usd: Option<f64>,
+}
+
+/// The rows of every run added so far.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EventTables {
+    pub events: Vec<EventRow>,
+    pub actions: Vec<ActionRow>,
+    pub usage: Vec<UsageRow>,
+}
+
+impl EventTables {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the rows of one run's `events`.
+    pub fn add_run(&mut self, events: &[RunEvent]) {
+        for (seq, event) in (1..).zip(events) {
+            self.events.push(EventRow {
+                run_id: event.run_id.clone(),
+                seq,
+                time: event.time.to_rfc3339(),
+                event_type: event.event_type.to_string(),
+                action_id: payload_str(event, "action_id").map(str::to_string),
+                model: event.actor.as_ref().and_then(|actor| actor.model.clone()),
+                payload: event.payload.as_ref().map(|payload| payload.to_string()),
+            });
+        }
+
+        for event in events.iter().filter(|e| e.event_type == "action.proposed") {
+            let Some(action_id) = payload_str(event, "action_id") else {
+                continue;
+            };
+            self.actions.push(ActionRow {
+                run_id: event.run_id.clone(),
+                action_id: action_id.to_string(),
+                kind: payload_str(event, "kind").map(str::to_string),
+                summary: payload_str(event, "summary").map(str::to_string),
+                status: action_status(events, action_id).to_string(),
+                proposed_at: event.time.to_rfc3339(),
+            });
+        }
+
+        let cost = RunCost::from_events(events);
+        for (call, usage) in (1..).zip(cost.calls) {
+            self.usage.push(UsageRow {
+                run_id: cost.run_id.clone(),
+                call,
+                model: usage.model,
+                prompt_tokens: usage.prompt_tokens,
+                completion_tokens: usage.completion_tokens,
+                cost_usd: usage.cost_usd,
+            });
+        }
+    }
+
+    /// Writes `events.csv`, `actions.csv`, and `usage.csv` into `dir`,
+    /// creating it if needed, and returns their paths.
+    pub fn write_csv(&self, dir: &Path) -> Result<Vec<PathBuf>, NexusError> {
+        std::fs::create_dir_all(dir).map_err(|err| NexusError::IoError {
+            operation: "create export directory".to_string(),
+            path: dir.to_path_buf(),
+            source: err,
+        })?;
+        let [events, actions, usage] = CSV_FILE_NAMES.map(|name| dir.join(name));
+        write_csv_table(&events, &self.events)?;
+        write_csv_table(&actions, &self.actions)?;
+        write_csv_table(&usage, &self.usage)?;
+        Ok(vec![events, actions, usage])
+    }
+
+    /// Writes the tables into the SQLite database at `path`, creating it and
+    /// its tables if needed. Rows of runs already in the database are
+    /// replaced.
+    pub fn write_sqlite(&self, path: &Path) -> Result<(), NexusError> {
+        let sqlite_error = |message: &str, err: rusqlite::Error| NexusError::ExportFailed {
+            format: "SQLite".to_string(),
+            message: format!("{message} in {}", path.display()),
+            source: Some(Box::new(err)),
+        };
+        let mut db =
+            Connection::open(path).map_err(|err| sqlite_error("could not open database", err))?;
+        db.execute_batch(SQLITE_SCHEMA)
+            .map_err(|err| sqlite_error("could not create tables", err))?;
+
+        let tx = db
+            .transaction()
+            .map_err(|err| sqlite_error("could not start transaction", err))?;
+        self.insert_rows(&tx)
+            .map_err(|err| sqlite_error("could not insert rows", err))?;
+        tx.commit()
+            .map_err(|err| sqlite_error("could not commit rows", err))
+    }
+
+    fn insert_rows(&self, db: &Connection) -> rusqlite::Result<()> {
+        let mut run_ids: Vec<&str> = self.events.iter().map(|row| row.run_id.as_str()).collect();
+        run_ids.dedup();
+        for table in ["events", "actions", "usage"] {
+            let mut delete = db.prepare(&format!("DELETE FROM {table} WHERE run_id = ?1"))?;
+            for run_id in &run_ids {
+                delete.execute([run_id])?;
+            }
+        }
+
+        let mut insert = db.prepare(
+            "INSERT INTO events (run_id, seq, time, type, action_id, model, payload)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        )?;
+        for row in &self.events {
+            insert.execute(params![
+                row.run_id,
+                row.seq,
+                row.time,
+                row.event_type,
+                row.action_id,
+                row.model,
+                row.payload
+            ])?;
+        }
+
+        let mut insert = db.prepare(
+            "INSERT INTO actions (run_id, action_id, kind, summary, status, proposed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+        for row in &self.actions {
+            insert.execute(params![
+                row.run_id,
+                row.action_id,
+                row.kind,
+                row.summary,
+                row.status,
+                row.proposed_at
+            ])?;
+        }
+
+        let mut insert = db.prepare(
+            "INSERT INTO usage (run_id, call, model, prompt_tokens, completion_tokens, cost_usd)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+        for row in &self.usage {
+            insert.execute(params![
+                row.run_id,
+                row.call,
+                row.model,
+                row.prompt_tokens,
+                row.completion_tokens,
+                row.cost_usd
+            ])?;
+        }
+        Ok(())
+    }
+}
+
+/// Builds the tables of `run_id` under `project_root`, or of every run
+/// when `run_id` is `None`, anonymized when `policy` is set.
+pub fn export_tables(
+    project_root: &Path,
+    run_id: Option<&str>,
+    policy: Option<&AnonymizePolicy>,
+) -> Result<EventTables, NexusError> {
+    let runs = match run_id {
+        Some(run_id) => vec![load_run_events(project_root, run_id)?.0],
+        None => EventLogIndex::load_for_project(project_root)?
+            .entries()
+            .iter()
+            .map(|entry| entry.load_events())
+            .collect::<Result<_, _>>()?,
+    };
+    let mut anonymizer = policy.map(Anonymizer::new);
+    let mut tables = EventTables::new();
+    for events in runs {
+        match anonymizer.as_mut() {
+            Some(anonymizer) => {
+                let events: Vec<_> = events.iter().map(|e| anonymizer.event(e)).collect();
+                tables.add_run(&events);
+            }
+            None => tables.add_run(&events),
+        }
+    }
+    Ok(tables)
+}
+
+fn write_csv_table<T: Serialize>(path: &Path, rows: &[T]) -> Result<(), NexusError> {
+    let csv_error = |err: csv::Error| NexusError::ExportFailed {
+        format: "CSV".to_string(),
+        message: format!("could not write {}", path.display()),
+        source: Some(Box::new(err)),
+    };
+    let mut writer = csv::Writer::from_path(path).map_err(csv_error)?;
+    for row in rows {
+        writer.serialize(row).map_err(csv_error)?;
+    }
+    writer.flush().map_err(|err| NexusError::IoError {
+        operation: "write CSV export".to_string(),
+        path: path.to_path_buf(),
+        source: err,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_log::helpers;
+    use crate::executor::UsageInfo;
+    use crate::types::RunStatus;
+
+    fn run(run_id: &str) -> Vec<RunEvent> {
+        vec![
+            helpers::run_started(run_id, "rename a"),
+            helpers::executor_started(run_id, "rename a", &[], "gpt-4o"),
+            helpers::with_usage(
+                helpers::executor_completed(run_id, 1, 100),
+                &UsageInfo {
+                    prompt_tokens: 1_000,
+                    completion_tokens: 100,
+                    total_tokens: 1_100,
+                },
+                None,
+            ),
+            helpers::action_proposed(run_id, "act_1", "patch", "rename a, b", None),
+            helpers::permission_granted(run_id, "act_1", "once"),
+            helpers::run_completed(run_id, RunStatus::Success, 1),
+        ]
+    }
+
+    #[test]
+    fn test_add_run_fills_each_table() {
+        let mut tables = EventTables::new();
+        tables.add_run(&run("run_1"));
+
+        assert_eq!(tables.events.len(), 6);
+        assert_eq!(tables.events[3].action_id.as_deref(), Some("act_1"));
+        assert_eq!(tables.actions.len(), 1);
+        assert_eq!(tables.actions[0].status, "approved");
+        assert_eq!(tables.usage.len(), 1);
+        assert_eq!(tables.usage[0].model.as_deref(), Some("gpt-4o"));
+        assert!(tables.usage[0].cost_usd.is_some());
+    }
+
+    #[test]
+    fn test_csv_quotes_fields() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut tables = EventTables::new();
+        tables.add_run(&run("run_1"));
+
+        let paths = tables.write_csv(dir.path()).unwrap();
+        assert_eq!(paths.len(), 3);
+        let actions = std::fs::read_to_string(dir.path().join("actions.csv")).unwrap();
+        assert!(actions.starts_with("run_id,action_id,kind,summary,status,proposed_at\n"));
+        assert!(actions.contains("run_1,act_1,patch,\"rename a, b\",approved,"));
+    }
+
+    #[test]
+    fn test_export_tables_covers_every_indexed_run() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let runs_dir = dir.path().join(".nexus").join("runs");
+        for run_id in ["run_1", "run_2"] {
+            let mut writer =
+                crate::event_log::EventLogWriter::open(&runs_dir.join(format!("{run_id}.jsonl")))
+                    .unwrap();
+            for event in run(run_id) {
+                writer.append(&event).unwrap();
+            }
+        }
+
+        let all = export_tables(dir.path(), None, None).unwrap();
+        assert_eq!(all.actions.len(), 2);
+        let one = export_tables(dir.path(), Some("run_2"), None).unwrap();
+        assert_eq!(one.events.len(), 6);
+        assert!(one.events.iter().all(|row| row.run_id == "run_2"));
+    }
+
+    #[test]
+    fn test_sqlite_export_replaces_runs_already_exported() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db_path = dir.path().join("runs.db");
+        let mut tables = EventTables::new();
+        tables.add_run(&run("run_1"));
+        tables.add_run(&run("run_2"));
+
+        tables.write_sqlite(&db_path).unwrap();
+        let mut again = EventTables::new();
+        again.add_run(&run("run_1"));
+        again.write_sqlite(&db_path).unwrap();
+
+        let db = Connection::open(&db_path).unwrap();
+        let count = |sql: &str| -> i64 { db.query_row(sql, [], |row| row.get(0)).unwrap() };
+        assert_eq!(count("SELECT COUNT(*) FROM events"), 12);
+        assert_eq!(
+            count("SELECT COUNT(*) FROM actions WHERE status = 'approved'"),
+            2
+        );
+        assert_eq!(count("SELECT SUM(prompt_tokens) FROM usage"), 2_000);
+    }
+}
@@ -0,0 +1,365 @@
+//! Replay of a run as a timeline: started → proposals → approvals → applies.
+//!
+//! The timeline is rebuilt from `<run_id>.jsonl` alone, one entry per
+//! event. `executor.streaming` progress events are left out; they carry no
+//! decisions and a long response logs hundreds of them.
+
+use std::fmt::Write as _;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+
+use super::summary::{find_str, outcome, payload_str, payload_u64};
+use crate::error::NexusError;
+use crate::event_log::{EventLogReader, load_run_events};
+use crate::types::{RunEvent, RunEventKind};
+
+/// Stage of a run an event belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    Started,
+    Executor,
+    Proposal,
+    Approval,
+    Apply,
+    Verification,
+    Completed,
+    Other,
+}
+
+impl Phase {
+    fn of(event_type: &str) -> Self {
+        match event_type {
+            "run.started" => Self::Started,
+            "run.completed" | "run.cancelled" | "run.budget_exceeded" => Self::Completed,
+            "action.proposed" | "action.validated" | "action.reviewed" => Self::Proposal,
+            "context.truncated" => Self::Executor,
+            _ if event_type.starts_with("handoff.") => Self::Executor,
+            _ if event_type.starts_with("plan.") => Self::Executor,
+            _ if event_type.starts_with("executor.") => Self::Executor,
+            _ if event_type.starts_with("permission.") => Self::Approval,
+            "run.reverted" => Self::Apply,
+            _ if event_type.starts_with("tool.")
+                || event_type.starts_with("conflict.")
+                || event_type.starts_with("git.") =>
+            {
+                Self::Apply
+            }
+            _ if event_type.starts_with("verification.") => Self::Verification,
+            _ => Self::Other,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Started => "started",
+            Self::Executor => "executor",
+            Self::Proposal => "proposal",
+            Self::Approval => "approval",
+            Self::Apply => "apply",
+            Self::Verification => "verification",
+            Self::Completed => "completed",
+            Self::Other => "other",
+        }
+    }
+}
+
+/// One event of a run, reduced to what a reader needs.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TimelineEntry {
+    pub time: DateTime<Utc>,
+    pub phase: Phase,
+    pub event_type: RunEventKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action_id: Option<String>,
+    pub detail: String,
+}
+
+/// A run's events in log order.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Timeline {
+    pub run_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task: Option<String>,
+    /// Run that spawned this one, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_run_id: Option<String>,
+    pub outcome: String,
+    pub entries: Vec<TimelineEntry>,
+}
+
+impl Timeline {
+    /// Loads `run_id` from `.nexus/runs/` under `project_root`.
+    pub fn load_for_run(project_root: &Path, run_id: &str) -> Result<Self, NexusError> {
+        let (events, _) = load_run_events(project_root, run_id)?;
+        Ok(Self::from_events(&events))
+    }
+
+    /// Loads the run recorded at `log_path`.
+    pub fn load(log_path: &Path) -> Result<Self, NexusError> {
+        let events = EventLogReader::open(log_path)?.load_all()?;
+        Ok(Self::from_events(&events))
+    }
+
+    pub fn from_events(events: &[RunEvent]) -> Self {
+        Self {
+            run_id: events
+                .first()
+                .map(|event| event.run_id.clone())
+                .unwrap_or_default(),
+            task: find_str(events, &["run.started", "executor.started"], "task")
+                .map(str::to_string),
+            parent_run_id: events.iter().find_map(|event| event.parent_run_id.clone()),
+            outcome: outcome(events),
+            entries: events
+                .iter()
+                .filter(|event| event.event_type != "executor.streaming")
+                .map(TimelineEntry::from_event)
+                .collect(),
+        }
+    }
+
+    /// Renders the timeline as aligned plain text, one line per entry.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "Run {}", self.run_id);
+        if let Some(task) = &self.task {
+            let _ = writeln!(out, "Task: {task}");
+        }
+        if let Some(parent) = &self.parent_run_id {
+            let _ = writeln!(out, "Parent: {parent}");
+        }
+        let _ = writeln!(out, "Outcome: {}\n", self.outcome);
+        for entry in &self.entries {
+            let _ = writeln!(out, "{}", entry.render());
+        }
+        out
+    }
+}
+
+impl TimelineEntry {
+    pub fn from_event(event: &RunEvent) -> Self {
+        Self {
+            time: event.time,
+            phase: Phase::of(event.event_type.as_str()),
+            event_type: event.event_type.clone(),
+            action_id: Some(event.action_ids().join(",")).filter(|ids| !ids.is_empty()),
+            detail: detail(event),
+        }
+    }
+
+    /// Renders the entry as one line of a timeline.
+    pub fn render(&self) -> String {
+        let mut line = format!(
+            "{}  {:<12} {:<22}",
+            self.time.format("%Y-%m-%d %H:%M:%S%.3f"),
+            self.phase.as_str(),
+            self.event_type
+        );
+        if let Some(action_id) = &self.action_id {
+            line.push_str(&format!(" {action_id}"));
+        }
+        if !self.detail.is_empty() {
+            line.push_str(&format!(" {}", self.detail));
+        }
+        line.trim_end().to_string()
+    }
+}
+
+/// Short description of what an event records.
+fn detail(event: &RunEvent) -> String {
+    let text = |key: &str| payload_str(event, key).unwrap_or("").to_string();
+    match event.event_type.as_str() {
+        "run.started" => match (
+            payload_str(event, "retry_of"),
+            payload_str(event, "refine_of"),
+        ) {
+            (Some(retry_of), _) => format!("{} (retry of {retry_of})", text("task")),
+            (None, Some(refine_of)) => format!("{} (refining {refine_of})", text("task")),
+            (None, None) => text("task"),
+        },
+        "run.completed" => text("status"),
+        "run.cancelled" => format!("cancelled: {}", text("reason")),
+        "run.budget_exceeded" => format!("spent {}, limit {}", text("spent"), text("limit")),
+        "executor.started" => format!(
+            "model {}, {} file(s)",
+            text("model"),
+            payload_u64(event, "file_count").unwrap_or(0)
+        ),
+        "executor.completed" => format!(
+            "{} action(s) in {}ms",
+            payload_u64(event, "action_count").unwrap_or(0),
+            payload_u64(event, "duration_ms").unwrap_or(0)
+        ),
+        "context.truncated" => format!(
+            "{} file(s) cut to fit {} tokens",
+            event
+                .payload
+                .as_ref()
+                .and_then(|payload| payload.get("files"))
+                .and_then(Value::as_array)
+                .map_or(0, Vec::len),
+            payload_u64(event, "budget").unwrap_or(0)
+        ),
+        "executor.failed" | "tool.failed" => text("error"),
+        "handoff.requested" => format!("{} -> {}: {}", text("from"), text("to"), text("reason")),
+        "handoff.accepted" => format!("{} took over", text("to")),
+        "plan.created" => format!("{} step(s)", payload_u64(event, "steps").unwrap_or(0)),
+        "plan.step.completed" => format!(
+            "step {}: {} ({} action(s))",
+            payload_u64(event, "step").unwrap_or(0),
+            text("title"),
+            payload_u64(event, "action_count").unwrap_or(0)
+        ),
+        "plan.patched" => format!(
+            "{} now has {} step(s)",
+            text("plan_id"),
+            payload_u64(event, "steps").unwrap_or(0)
+        ),
+        "action.proposed" => format!("{}: {}", text("kind"), text("summary")),
+        "action.reviewed" => format!(
+            "{:.0}% confident, {} concern(s)",
+            event
+                .payload
+                .as_ref()
+                .and_then(|payload| payload.get("confidence"))
+                .and_then(Value::as_f64)
+                .unwrap_or(0.0)
+                * 100.0,
+            event
+                .payload
+                .as_ref()
+                .and_then(|payload| payload.get("concerns"))
+                .and_then(Value::as_array)
+                .map_or(0, Vec::len)
+        ),
+        "action.validated" => match payload_str(event, "failing_hunk") {
+            Some(failing_hunk) => format!("does not apply: {failing_hunk}"),
+            None => "applies cleanly".to_string(),
+        },
+        "permission.granted" => format!("granted ({})", text("scope")),
+        "permission.denied" => format!("denied: {}", text("reason")),
+        "tool.executed" => match payload_u64(event, "exit_code") {
+            Some(code) => format!(
+                "exit {code} in {}ms",
+                payload_u64(event, "duration_ms").unwrap_or(0)
+            ),
+            None => match file_list(event, "files_modified") {
+                files if files.is_empty() => "applied".to_string(),
+                files => format!("modified {files}"),
+            },
+        },
+        "conflict.resolved" => text("resolution"),
+        "run.reverted" => format!("restored {}", file_list(event, "files_restored")),
+        "git.branch_created" => match payload_str(event, "base") {
+            Some(base) => format!("{} from {base}", text("branch")),
+            None => text("branch"),
+        },
+        "git.committed" => format!("commit {:.12}", text("commit")),
+        _ if event.event_type.as_str().starts_with("verification.") => {
+            let passed = event
+                .payload
+                .as_ref()
+                .and_then(|payload| payload.get("success"))
+                .and_then(Value::as_bool);
+            let name = payload_str(event, "name")
+                .or_else(|| payload_str(event, "command"))
+                .unwrap_or("");
+            match passed {
+                Some(true) => format!("{name} passed"),
+                Some(false) => format!("{name} failed"),
+                None => name.to_string(),
+            }
+        }
+        _ => String::new(),
+    }
+}
+
+/// The paths listed under `key`, comma-separated.
+fn file_list(event: &RunEvent, key: &str) -> String {
+    let files: Vec<&str> = event
+        .payload
+        .as_ref()
+        .and_then(|payload| payload.get(key))
+        .and_then(Value::as_array)
+        .map(|files| files.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    files.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_log::{EventLogWriter, helpers};
+    use crate::types::RunStatus;
+    use tempfile::TempDir;
+
+    fn write_log(dir: &TempDir) -> std::path::PathBuf {
+        let log_path = dir.path().join("run_1.jsonl");
+        let mut writer = EventLogWriter::open(&log_path).unwrap();
+        for event in [
+            helpers::run_started("run_1", "rename a"),
+            helpers::executor_started("run_1", "rename a", &["a.rs".to_string()], "gpt-test"),
+            helpers::executor_streaming("run_1", 10, 10),
+            helpers::action_proposed("run_1", "act_1", "patch", "Rename a", None),
+            helpers::action_proposed("run_1", "act_2", "command", "Run tests", None),
+            helpers::executor_completed("run_1", 2, 1500),
+            helpers::permission_granted("run_1", "act_1", "once"),
+            helpers::permission_denied("run_1", "act_2", "user declined"),
+            helpers::tool_executed("run_1", "act_1", vec!["a.rs".to_string()]),
+            helpers::run_completed("run_1", RunStatus::Success, 1),
+        ] {
+            writer.append(&event).unwrap();
+        }
+        writer.sync().unwrap();
+        log_path
+    }
+
+    #[test]
+    fn test_timeline_follows_run_phases() {
+        let dir = TempDir::new().unwrap();
+        let timeline = Timeline::load(&write_log(&dir)).unwrap();
+
+        assert_eq!(timeline.run_id, "run_1");
+        assert_eq!(timeline.task.as_deref(), Some("rename a"));
+        assert_eq!(timeline.outcome, "success");
+        let phases: Vec<Phase> = timeline.entries.iter().map(|entry| entry.phase).collect();
+        assert_eq!(
+            phases,
+            [
+                Phase::Started,
+                Phase::Executor,
+                Phase::Proposal,
+                Phase::Proposal,
+                Phase::Executor,
+                Phase::Approval,
+                Phase::Approval,
+                Phase::Apply,
+                Phase::Completed,
+            ]
+        );
+        assert_eq!(timeline.entries[7].detail, "modified a.rs");
+        assert_eq!(timeline.entries[6].action_id.as_deref(), Some("act_2"));
+    }
+
+    #[test]
+    fn test_render_and_json() {
+        let dir = TempDir::new().unwrap();
+        let timeline = Timeline::load(&write_log(&dir)).unwrap();
+
+        let text = timeline.render();
+        assert!(text.starts_with("Run run_1\nTask: rename a\nOutcome: success\n\n"));
+        assert!(text.contains("proposal     action.proposed        act_1 patch: Rename a\n"));
+        assert!(text.contains("approval     permission.denied      act_2 denied: user declined\n"));
+        assert!(text.contains("executor     executor.completed     2 action(s) in 1500ms\n"));
+        assert!(!text.contains("executor.streaming"));
+
+        let json = serde_json::to_value(&timeline).unwrap();
+        assert_eq!(json["entries"][2]["phase"], "proposal");
+        assert_eq!(json["entries"][2]["action_id"], "act_1");
+        assert!(json["entries"][0].get("action_id").is_none());
+    }
+}
@@ -0,0 +1,432 @@
+//! Applying each run's changes on its own git branch.
+//!
+//! Before a run's actions are applied, the work tree is switched to
+//! `nexus/<run_id>`, created from the current HEAD the first time, so the
+//! run can be reviewed, merged, or dropped as a branch. Optionally each
+//! applied action is committed on its own, with the run and action IDs as
+//! trailers of the message, so it can be reviewed or reverted alone.
+//! Outside a git work tree nothing happens. The `git` executable is used rather than a
+//! library, so the user's configuration and hooks apply.
+
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use crate::error::NexusError;
+use crate::types::GitSettings;
+
+/// Prefix of the branches runs are applied on.
+pub const BRANCH_PREFIX: &str = "nexus/";
+
+/// Trailer naming the run in the message of an action's commit.
+pub const RUN_ID_TRAILER: &str = "Nexus-Run-Id";
+
+/// Trailer naming the action in the message of an action's commit.
+pub const ACTION_ID_TRAILER: &str = "Nexus-Action-Id";
+
+/// Pathspec leaving out the `.nexus/` directories Nexus keeps its own
+/// state in; their changes never make the work tree dirty.
+const EXCLUDE_NEXUS_DIRS: &str = ":(exclude,glob)**/.nexus/**";
+
+/// Name of the branch `run_id` is applied on.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(nexus::git::run_branch("run_1"), "nexus/run_1");
+/// ```
+pub fn run_branch(run_id: &str) -> String {
+    format!("{BRANCH_PREFIX}{run_id}")
+}
+
+/// Message of the commit of one applied action: the first line of its
+/// summary as the subject, with the run and action IDs as trailers.
+///
+/// # Examples
+///
+/// ```
+/// let message = nexus::git::action_commit_message("run_1", "run_1-action-1", "Rename foo");
+/// assert_eq!(
+///     message,
+///     "Rename foo\n\nNexus-Run-Id: run_1\nNexus-Action-Id: run_1-action-1\n"
+/// );
+/// ```
+pub fn action_commit_message(run_id: &str, action_id: &str, summary: &str) -> String {
+    let subject = match summary.lines().next().map(str::trim) {
+        Some(subject) if !subject.is_empty() => subject.to_string(),
+        _ => format!("Apply {action_id}"),
+    };
+    format!("{subject}\n\n{RUN_ID_TRAILER}: {run_id}\n{ACTION_ID_TRAILER}: {action_id}\n")
+}
+
+/// How the work tree got onto a run's branch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RunBranch {
+    /// The branch was created from `base`, the branch checked out before
+    /// (`None` on a detached HEAD).
+    Created {
+        branch: String,
+        base: Option<String>,
+    },
+    /// The branch existed, e.g. from an earlier partial apply, and was
+    /// checked out.
+    Switched { branch: String },
+    /// The branch was already checked out.
+    Current { branch: String },
+}
+
+impl RunBranch {
+    pub fn branch(&self) -> &str {
+        match self {
+            Self::Created { branch, .. } | Self::Switched { branch } | Self::Current { branch } => {
+                branch
+            }
+        }
+    }
+}
+
+/// A git work tree.
+#[derive(Debug, Clone)]
+pub struct GitRepo {
+    root: PathBuf,
+}
+
+impl GitRepo {
+    /// The work tree containing `dir`, or `None` if `dir` is not in one or
+    /// git is not installed.
+    pub fn discover(dir: &Path) -> Option<Self> {
+        let output = Command::new("git")
+            .args(["rev-parse", "--show-toplevel"])
+            .current_dir(dir)
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let root = String::from_utf8(output.stdout).ok()?;
+        Some(Self {
+            root: PathBuf::from(root.trim_end()),
+        })
+    }
+
+    /// Top-level directory of the work tree.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Tracked files with uncommitted changes, staged or not. Untracked
+    /// files and Nexus's own `.nexus/` directory are left out.
+    pub fn dirty_files(&self) -> Result<Vec<String>, NexusError> {
+        let output = self.git(&[
+            "status",
+            "--porcelain",
+            "--untracked-files=no",
+            "--",
+            ".",
+            EXCLUDE_NEXUS_DIRS,
+        ])?;
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.get(3..))
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Branch checked out, or `None` on a detached HEAD.
+    pub fn current_branch(&self) -> Result<Option<String>, NexusError> {
+        let output = self.run(&["symbolic-ref", "--quiet", "--short", "HEAD"])?;
+        if !output.status.success() {
+            return Ok(None);
+        }
+        Ok(Some(
+            String::from_utf8_lossy(&output.stdout)
+                .trim_end()
+                .to_string(),
+        ))
+    }
+
+    pub fn branch_exists(&self, branch: &str) -> Result<bool, NexusError> {
+        let reference = format!("refs/heads/{branch}");
+        Ok(self
+            .run(&["show-ref", "--verify", "--quiet", &reference])?
+            .status
+            .success())
+    }
+
+    /// Checks out `branch`, first creating it at HEAD if it does not exist.
+    /// Uncommitted changes are carried over.
+    pub fn checkout_branch(&self, branch: &str) -> Result<(), NexusError> {
+        if self.branch_exists(branch)? {
+            self.git(&["checkout", "--quiet", branch])?;
+        } else {
+            self.git(&["checkout", "--quiet", "-b", branch])?;
+        }
+        Ok(())
+    }
+
+    /// Switches to the branch of `run_id`, creating it if needed.
+    ///
+    /// # Errors
+    /// - `NexusError::WorkingTreeDirty` if `require_clean` is set and the
+    ///   switch would carry uncommitted changes onto the run's branch
+    /// - `NexusError::GitFailed` if git refuses the checkout
+    pub fn prepare_run_branch(
+        &self,
+        run_id: &str,
+        require_clean: bool,
+    ) -> Result<RunBranch, NexusError> {
+        let branch = run_branch(run_id);
+        let base = self.current_branch()?;
+        if base.as_deref() == Some(branch.as_str()) {
+            return Ok(RunBranch::Current { branch });
+        }
+        self.ensure_clean(require_clean)?;
+        let existed = self.branch_exists(&branch)?;
+        self.checkout_branch(&branch)?;
+        Ok(if existed {
+            RunBranch::Switched { branch }
+        } else {
+            RunBranch::Created { branch, base }
+        })
+    }
+
+    /// Prepares the work tree for applying `run_id` as `settings` ask: checks
+    /// it is clean if required and switches to the run's branch; `None` if
+    /// branching is off.
+    pub fn prepare_for_run(
+        &self,
+        run_id: &str,
+        settings: &GitSettings,
+    ) -> Result<Option<RunBranch>, NexusError> {
+        if !settings.branch_per_run {
+            self.ensure_clean(settings.require_clean)?;
+            return Ok(None);
+        }
+        self.prepare_run_branch(run_id, settings.require_clean)
+            .map(Some)
+    }
+
+    /// Commits the current contents of `paths`, and nothing else, with
+    /// `message`; returns the new commit's hash, or `None` if `paths` hold
+    /// no changes.
+    ///
+    /// New files are added and deleted ones removed. Other staged changes
+    /// stay staged.
+    pub fn commit_paths(
+        &self,
+        paths: &[PathBuf],
+        message: &str,
+    ) -> Result<Option<String>, NexusError> {
+        if paths.is_empty() {
+            return Ok(None);
+        }
+        self.git(&with_paths(&["add", "--all"], paths))?;
+        let staged = self.run(&with_paths(&["diff", "--cached", "--quiet"], paths))?;
+        if staged.status.success() {
+            return Ok(None);
+        }
+        self.git(&with_paths(&["commit", "--quiet", "-m", message], paths))?;
+        let head = self.git(&["rev-parse", "HEAD"])?;
+        Ok(Some(
+            String::from_utf8_lossy(&head.stdout).trim_end().to_string(),
+        ))
+    }
+
+    /// Fails with `NexusError::WorkingTreeDirty` if `required` and tracked
+    /// files have uncommitted changes.
+    pub fn ensure_clean(&self, required: bool) -> Result<(), NexusError> {
+        if !required {
+            return Ok(());
+        }
+        let files = self.dirty_files()?;
+        if files.is_empty() {
+            Ok(())
+        } else {
+            Err(NexusError::WorkingTreeDirty { files })
+        }
+    }
+
+    /// Runs `git args` in the work tree, failing if git does.
+    fn git<S: AsRef<OsStr>>(&self, args: &[S]) -> Result<Output, NexusError> {
+        let output = self.run(args)?;
+        if output.status.success() {
+            Ok(output)
+        } else {
+            Err(NexusError::GitFailed {
+                command: args
+                    .first()
+                    .map(|arg| arg.as_ref().to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            })
+        }
+    }
+
+    fn run<S: AsRef<OsStr>>(&self, args: &[S]) -> Result<Output, NexusError> {
+        Command::new("git")
+            .args(args)
+            .current_dir(&self.root)
+            .output()
+            .map_err(|err| NexusError::IoError {
+                operation: format!(
+                    "run git {}",
+                    args.first()
+                        .map(|arg| arg.as_ref().to_string_lossy())
+                        .unwrap_or_default()
+                ),
+                path: self.root.clone(),
+                source: err,
+            })
+    }
+}
+
+/// `args` followed by `--` and `paths`.
+fn with_paths<'a>(args: &[&'a str], paths: &'a [PathBuf]) -> Vec<&'a OsStr> {
+    args.iter()
+        .map(|arg| OsStr::new(*arg))
+        .chain(std::iter::once(OsStr::new("--")))
+        .chain(paths.iter().map(|path| path.as_os_str()))
+        .collect()
+}
+
+/// Switches the work tree containing `root` to the branch of `run_id` as
+/// `settings` ask; `None` if branching is off or `root` is not in a git
+/// work tree.
+pub fn prepare_run_branch(
+    root: &Path,
+    run_id: &str,
+    settings: &GitSettings,
+) -> Result<Option<RunBranch>, NexusError> {
+    match GitRepo::discover(root) {
+        Some(repo) => repo.prepare_for_run(run_id, settings),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {args:?} failed");
+    }
+
+    fn repo() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        git(dir.path(), &["init", "--quiet", "--initial-branch=main"]);
+        git(dir.path(), &["config", "user.name", "Nexus Test"]);
+        git(dir.path(), &["config", "user.email", "nexus@example.com"]);
+        std::fs::write(dir.path().join("a.txt"), "one\n").unwrap();
+        git(dir.path(), &["add", "a.txt"]);
+        git(dir.path(), &["commit", "--quiet", "-m", "initial"]);
+        dir
+    }
+
+    #[test]
+    fn test_run_branch_is_created_then_reused() {
+        let dir = repo();
+        let settings = GitSettings::default();
+
+        let created = prepare_run_branch(dir.path(), "run_1", &settings).unwrap();
+        assert_eq!(
+            created,
+            Some(RunBranch::Created {
+                branch: "nexus/run_1".to_string(),
+                base: Some("main".to_string()),
+            })
+        );
+        let repo = GitRepo::discover(dir.path()).unwrap();
+        assert_eq!(
+            repo.current_branch().unwrap().as_deref(),
+            Some("nexus/run_1")
+        );
+
+        let again = prepare_run_branch(dir.path(), "run_1", &settings).unwrap();
+        assert!(matches!(again, Some(RunBranch::Current { .. })));
+
+        git(dir.path(), &["checkout", "--quiet", "main"]);
+        let back = prepare_run_branch(dir.path(), "run_1", &settings).unwrap();
+        assert!(matches!(back, Some(RunBranch::Switched { .. })));
+    }
+
+    #[test]
+    fn test_require_clean_rejects_uncommitted_changes() {
+        let dir = repo();
+        let runs_dir = dir.path().join(".nexus").join("runs");
+        std::fs::create_dir_all(&runs_dir).unwrap();
+        std::fs::write(runs_dir.join("run_0.jsonl"), "{}\n").unwrap();
+        git(dir.path(), &["add", "--force", ".nexus"]);
+        git(dir.path(), &["commit", "--quiet", "-m", "runs"]);
+        std::fs::write(runs_dir.join("run_0.jsonl"), "{}\n{}\n").unwrap();
+        std::fs::write(dir.path().join("untracked.txt"), "new\n").unwrap();
+        let settings = GitSettings {
+            require_clean: true,
+            ..GitSettings::default()
+        };
+        assert!(
+            GitRepo::discover(dir.path())
+                .unwrap()
+                .dirty_files()
+                .unwrap()
+                .is_empty()
+        );
+
+        std::fs::write(dir.path().join("a.txt"), "two\n").unwrap();
+        let err = prepare_run_branch(dir.path(), "run_1", &settings).unwrap_err();
+        assert!(matches!(err, NexusError::WorkingTreeDirty { ref files } if files == &["a.txt"]));
+
+        let disabled = GitSettings {
+            branch_per_run: false,
+            ..GitSettings::default()
+        };
+        assert_eq!(
+            prepare_run_branch(dir.path(), "run_1", &disabled).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_commit_paths_commits_only_those_paths() {
+        let dir = repo();
+        let repo = GitRepo::discover(dir.path()).unwrap();
+        std::fs::write(dir.path().join("a.txt"), "two\n").unwrap();
+        std::fs::write(dir.path().join("b.txt"), "new\n").unwrap();
+        std::fs::write(dir.path().join("c.txt"), "other\n").unwrap();
+        let message = action_commit_message("run_1", "run_1-action-1", "Update a and b");
+
+        let paths = [dir.path().join("a.txt"), dir.path().join("b.txt")];
+        let commit = repo.commit_paths(&paths, &message).unwrap().unwrap();
+        let show = Command::new("git")
+            .args(["show", "--name-only", "--format=%B", &commit])
+            .current_dir(dir.path())
+            .output()
+            .unwrap();
+        let show = String::from_utf8(show.stdout).unwrap();
+        assert!(show.starts_with(&message));
+        assert!(show.contains("a.txt\nb.txt"));
+        assert!(!show.contains("c.txt"));
+
+        assert_eq!(repo.commit_paths(&paths, &message).unwrap(), None);
+    }
+
+    #[test]
+    fn test_outside_a_work_tree_nothing_happens() {
+        let dir = TempDir::new().unwrap();
+        if GitRepo::discover(dir.path()).is_some() {
+            // The temp directory is inside some other work tree.
+            return;
+        }
+        let settings = GitSettings::default();
+        assert_eq!(
+            prepare_run_branch(dir.path(), "run_1", &settings).unwrap(),
+            None
+        );
+    }
+}
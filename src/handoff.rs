@@ -0,0 +1,122 @@
+//! Handoffs: one agent passing a run on to another.
+//!
+//! A `handoff` action ends the proposing agent's phase. The run logs
+//! `handoff.requested` for it and, if an executor is registered for the
+//! target role and the permission gate (when there is one) allows the
+//! action, `handoff.accepted`; the target agent then works on
+//! [`handoff_task`] within the same run and log, as a
+//! [`crate::executor::RunPhase`]. A run accepts at most [`MAX_HANDOFFS`].
+//! Handoffs that were not accepted stay pending and are skipped on apply.
+
+use crate::types::{ActionDetails, AgentRole, HandoffDetails, ProposedAction};
+
+/// Accepted handoffs per run, so agents handing back and forth stop.
+pub const MAX_HANDOFFS: usize = 4;
+
+/// System prompt of the reviewer taking over from the executor.
+pub const REVIEWER_SYSTEM_PROMPT: &str = r#"You are an expert code reviewer. Another agent has proposed code changes for the task below and handed the run to you for review.
+
+IMPORTANT RULES:
+1. Propose follow-up changes only for real defects: bugs, missing cases, broken builds or tests
+2. Do not restate or reformat the changes already proposed
+3. Use the exact file paths provided
+4. Before each diff or search/replace block, write one line starting with
+   "Why:" explaining in one sentence why that change is needed
+5. If the changes need no follow-up, reply with no diff
+
+Output changes as unified diffs:
+Why: <one-sentence rationale>
+```diff
+--- a/path/to/file.rs
++++ b/path/to/file.rs
+@@ -10,5 +10,6 @@
+ existing context
+-old line to remove
++new line to add
+ more context
+```
+"#;
+
+/// The handoff actions among `actions`, with their details.
+pub fn handoffs(
+    actions: &[ProposedAction],
+) -> impl Iterator<Item = (&ProposedAction, &HandoffDetails)> {
+    actions.iter().filter_map(|action| match &action.details {
+        ActionDetails::Handoff(details) => Some((action, details)),
+        _ => None,
+    })
+}
+
+/// Task given to the agent taking over a run working on `task`, after
+/// the changes rendered in `proposed` (see
+/// [`crate::preview::render_dry_run`]) were proposed.
+pub fn handoff_task(task: &str, details: &HandoffDetails, proposed: &str) -> String {
+    let mut out = format!(
+        "{} handed this run over to you: {}\n\nOriginal task:\n{task}\n",
+        role_name(&details.from),
+        details.reason
+    );
+    if !proposed.is_empty() {
+        out.push_str("\nProposed changes:\n");
+        out.push_str(proposed);
+    }
+    out
+}
+
+/// Name of `role` as it appears in logs and settings.
+pub fn role_name(role: &AgentRole) -> &'static str {
+    match role {
+        AgentRole::Router => "router",
+        AgentRole::Researcher => "researcher",
+        AgentRole::Planner => "planner",
+        AgentRole::Executor => "executor",
+        AgentRole::Reviewer => "reviewer",
+        AgentRole::Tool => "tool",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::PatchDetails;
+
+    fn action(id: &str, details: ActionDetails) -> ProposedAction {
+        ProposedAction::test(id, details)
+    }
+
+    fn review() -> HandoffDetails {
+        HandoffDetails {
+            from: AgentRole::Executor,
+            to: AgentRole::Reviewer,
+            reason: "check the error handling".to_string(),
+            workflow_patch_ref: None,
+        }
+    }
+
+    #[test]
+    fn test_handoffs_are_picked_out() {
+        let actions = [
+            action("act_1", ActionDetails::Patch(PatchDetails::default())),
+            action("act_2", ActionDetails::Handoff(review())),
+        ];
+        let found: Vec<_> = handoffs(&actions)
+            .map(|(action, details)| (action.id.as_str(), details.to.clone()))
+            .collect();
+        assert_eq!(found, [("act_2", AgentRole::Reviewer)]);
+    }
+
+    #[test]
+    fn test_handoff_task_keeps_the_original_task() {
+        let task = handoff_task("rename foo to bar", &review(), "");
+        assert_eq!(
+            task,
+            "executor handed this run over to you: check the error handling\n\nOriginal task:\nrename foo to bar\n"
+        );
+        let task = handoff_task("rename foo to bar", &review(), "act_1 diff\n");
+        assert!(task.ends_with("rename foo to bar\n\nProposed changes:\nact_1 diff\n"));
+        assert_eq!(
+            role_name(&AgentRole::Reviewer),
+            serde_json::to_value(AgentRole::Reviewer).unwrap()
+        );
+    }
+}
@@ -0,0 +1,179 @@
+//! `nexus init`: scaffolding a project's `.nexus/` directory.
+//!
+//! Writes `.nexus/settings.json` from [`NexusSettings::default()`] so users
+//! start from a valid file instead of hand-writing the schema, and creates
+//! `.nexus/runs/` for event logs. Running it again keeps an existing
+//! settings file unless asked to overwrite it.
+
+use std::path::{Path, PathBuf};
+
+use crate::error::NexusError;
+use crate::event_log::EventLogPath;
+use crate::types::NexusSettings;
+
+/// Entry added to `.gitignore` so run logs and artifacts stay local.
+pub const GITIGNORE_ENTRY: &str = ".nexus/runs/";
+
+/// What `nexus init` should do besides the defaults.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct InitOptions {
+    /// Replace an existing settings file with the defaults.
+    pub force: bool,
+    /// Add [`GITIGNORE_ENTRY`] to the project's `.gitignore`.
+    pub gitignore: bool,
+}
+
+/// What `nexus init` changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InitReport {
+    pub settings_path: PathBuf,
+    /// False if an existing settings file was kept.
+    pub wrote_settings: bool,
+    pub runs_dir: PathBuf,
+    /// True if the `.gitignore` entry was added; false if it was already
+    /// there or not requested.
+    pub updated_gitignore: bool,
+}
+
+/// Scaffolds `.nexus/` under `root`.
+pub fn init_project(root: &Path, options: InitOptions) -> Result<InitReport, NexusError> {
+    let nexus_dir = root.join(".nexus");
+    let settings_path = nexus_dir.join("settings.json");
+    let runs_dir = nexus_dir.join("runs");
+
+    EventLogPath::new(root)
+        .ensure_dir()
+        .map_err(|err| NexusError::IoError {
+            operation: "create runs directory".to_string(),
+            path: runs_dir.clone(),
+            source: err,
+        })?;
+
+    let wrote_settings = options.force || !settings_path.exists();
+    if wrote_settings {
+        let json = serde_json::to_string_pretty(&NexusSettings::default()).map_err(|source| {
+            NexusError::JsonError {
+                context: "failed to serialize default settings".to_string(),
+                source,
+            }
+        })?;
+        std::fs::write(&settings_path, json + "\n").map_err(|err| NexusError::IoError {
+            operation: "write settings".to_string(),
+            path: settings_path.clone(),
+            source: err,
+        })?;
+    }
+
+    let updated_gitignore = options.gitignore && add_gitignore_entry(&root.join(".gitignore"))?;
+
+    Ok(InitReport {
+        settings_path,
+        wrote_settings,
+        runs_dir,
+        updated_gitignore,
+    })
+}
+
+/// Appends [`GITIGNORE_ENTRY`] unless the file already ignores the runs
+/// directory. Returns whether the file changed.
+fn add_gitignore_entry(path: &Path) -> Result<bool, NexusError> {
+    let existing = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(err) => {
+            return Err(NexusError::IoError {
+                operation: "read .gitignore".to_string(),
+                path: path.to_path_buf(),
+                source: err,
+            });
+        }
+    };
+    let ignored = existing.lines().map(str::trim).any(|line| {
+        matches!(
+            line.trim_start_matches('/').trim_end_matches('/'),
+            ".nexus/runs" | ".nexus"
+        )
+    });
+    if ignored {
+        return Ok(false);
+    }
+
+    let mut updated = existing;
+    if !updated.is_empty() && !updated.ends_with('\n') {
+        updated.push('\n');
+    }
+    updated.push_str(GITIGNORE_ENTRY);
+    updated.push('\n');
+    std::fs::write(path, updated).map_err(|err| NexusError::IoError {
+        operation: "update .gitignore".to_string(),
+        path: path.to_path_buf(),
+        source: err,
+    })?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::NexusConfig;
+
+    #[test]
+    fn test_init_writes_valid_settings_and_runs_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let report = init_project(dir.path(), InitOptions::default()).unwrap();
+
+        assert!(report.wrote_settings);
+        assert!(report.runs_dir.is_dir());
+        assert!(!report.updated_gitignore);
+        assert!(!dir.path().join(".gitignore").exists());
+        let config =
+            NexusConfig::load_with_config_path_strict(&report.settings_path, true).unwrap();
+        assert_eq!(
+            config.settings.deny_paths,
+            NexusSettings::default().deny_paths
+        );
+
+        std::fs::write(&report.settings_path, "{}\n").unwrap();
+        let again = init_project(dir.path(), InitOptions::default()).unwrap();
+        assert!(!again.wrote_settings);
+        assert_eq!(
+            std::fs::read_to_string(&report.settings_path).unwrap(),
+            "{}\n"
+        );
+
+        let forced = init_project(
+            dir.path(),
+            InitOptions {
+                force: true,
+                ..InitOptions::default()
+            },
+        )
+        .unwrap();
+        assert!(forced.wrote_settings);
+        assert_ne!(
+            std::fs::read_to_string(&report.settings_path).unwrap(),
+            "{}\n"
+        );
+    }
+
+    #[test]
+    fn test_gitignore_entry_is_added_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let gitignore = dir.path().join(".gitignore");
+        std::fs::write(&gitignore, "target/").unwrap();
+        let options = InitOptions {
+            gitignore: true,
+            ..InitOptions::default()
+        };
+
+        assert!(init_project(dir.path(), options).unwrap().updated_gitignore);
+        assert!(!init_project(dir.path(), options).unwrap().updated_gitignore);
+        assert_eq!(
+            std::fs::read_to_string(&gitignore).unwrap(),
+            "target/\n.nexus/runs/\n"
+        );
+
+        std::fs::write(&gitignore, "/.nexus/\n").unwrap();
+        assert!(!init_project(dir.path(), options).unwrap().updated_gitignore);
+    }
+}
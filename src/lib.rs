@@ -1,12 +1,39 @@
+pub mod apply;
+pub mod autopilot;
+pub mod batch;
+pub mod binary;
+pub mod cancel;
+pub mod ci;
 pub mod cli;
+pub mod context;
+pub mod cost;
+pub mod daemon;
 pub mod error;
 pub mod event_log;
 pub mod executor;
+pub mod export;
+pub mod git;
+pub mod handoff;
+pub mod init;
+pub mod paths;
+pub mod plan;
+pub mod policy;
+pub mod preview;
+pub mod redact;
+pub mod render;
+pub mod resume;
+pub mod retry;
+pub mod review;
+pub mod session;
 pub mod settings;
+pub mod suggest;
+pub mod tui;
 pub mod types;
 
 pub use cli::Cli;
 pub use error::{NexusError, NexusResult, exit_code_from_anyhow, exit_codes};
-pub use executor::{CodexAdapter, ExecuteOptions, Executor, FileContext, StreamChunk};
+pub use executor::{
+    CodexAdapter, ExecuteOptions, Executor, FileContext, OutputFormat, StreamChunk,
+};
 pub use settings::NexusConfig;
 pub use types::*;
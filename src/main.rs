@@ -1,56 +1,1193 @@
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use clap::Parser;
+use std::io::{IsTerminal, Write as _};
+use std::path::Path;
 use std::process::ExitCode;
+use std::sync::LazyLock;
+use std::time::Duration;
 
-use nexus::cli::Cli;
-use nexus::error::exit_code_from_anyhow;
+use nexus::apply::ApplyReport;
+use nexus::autopilot::{AutopilotReport, RunAutopilot};
+use nexus::batch::{
+    BatchFile, BatchOutcome, BatchRunner, BatchTask, FollowUp, write_batch_summary,
+};
+use nexus::binary::BinaryGuard;
+use nexus::cancel::{CancelToken, install_ctrl_c_handler};
+use nexus::ci::GithubReporter;
+use nexus::cli::{
+    ApplyArgs, AutopilotArgs, BatchArgs, CiMode, Cli, Command, ConfigArgs, ConfigCommand,
+    DaemonArgs, DiffArgs, ExportArgs, ExportFormat, InitArgs, LogArgs, LogCommand, LogFormat,
+    RefineArgs, ReportFormat, RestoreArgs, ResumeArgs, RetryArgs, RunsArgs, RunsCommand,
+    SummaryArgs, UndoArgs,
+};
+use nexus::daemon::Daemon;
+use nexus::error::NexusError;
+use nexus::error::{exit_code_from_anyhow, exit_codes};
+use nexus::event_log::{EventLogFollower, EventLogIndex, ends_run};
+use nexus::executor::{
+    NetworkConfig, PromptBuilder, ProviderRegistry, RateLimitScheduler, RunBudget,
+};
+use nexus::export::TimelineEntry;
+use nexus::handoff::REVIEWER_SYSTEM_PROMPT;
+use nexus::plan::PLANNER_SYSTEM_PROMPT;
+use nexus::policy::PermissionGate;
+use nexus::policy::scan::PatchScanner;
+use nexus::redact::Redactor;
+use nexus::render::{StreamRenderer, render_stream};
+use nexus::resume::RunResume;
+use nexus::retry::{RetryOverrides, RetrySource};
+use nexus::session::Session;
 use nexus::settings::NexusConfig;
+use nexus::tui::{StatusBar, TuiPrompt};
+use nexus::types::{AgentRole, NexusSettings, PermissionMode, ProposedAction, RunEventKind};
+use nexus::{CodexAdapter, ExecuteOptions, Executor, OutputFormat, PatchFormat, RunStatus};
+use secrecy::ExposeSecret;
+use serde_json::json;
+
+/// How often `nexus log tail --follow` checks the log for new events.
+const TAIL_POLL_INTERVAL: Duration = Duration::from_millis(250);
 
 /// Program entry point that runs the application and converts its result into a process exit code.
 ///
 /// On success, this returns exit code 0. On error, the error is printed to stderr using debug
-/// formatting and a Nexus-specific mapping determines the non-zero exit code returned.
+/// formatting, with secrets redacted, and a Nexus-specific mapping determines the non-zero
+/// exit code returned. With `--output-format json` the error is printed to stdout as JSON
+/// instead, with that exit code as its `code`.
 fn main() -> ExitCode {
-    match run() {
-        Ok(()) => ExitCode::from(0),
-        Err(err) => {
-            eprintln!("Error: {err:?}");
-            ExitCode::from(exit_code_from_anyhow(&err))
-        }
-    }
-}
-
-/// Starts the application: loads environment and CLI options, initializes logging, loads the Nexus configuration, and either prints a dry-run summary or proceeds to execution.
-fn run() -> Result<()> {
     // Load .env if present before parsing CLI options.
     dotenvy::dotenv().ok();
 
     // Parse CLI arguments.
     let cli = Cli::parse();
 
+    match run(&cli) {
+        Ok(code) => ExitCode::from(code),
+        Err(err) => {
+            let redactor = error_redactor(&cli);
+            let code = exit_code_from_anyhow(&err);
+            if cli.json_output() {
+                let message = redactor.redact(&format!("{err:#}"));
+                println!("{}", json!({"error": {"message": message, "code": code}}));
+            } else {
+                eprintln!("Error: {}", redactor.redact(&format!("{err:?}")));
+            }
+            ExitCode::from(code)
+        }
+    }
+}
+
+/// Starts the application: initializes logging, loads the Nexus configuration, and either prints a dry-run summary or proceeds to execution.
+fn run(cli: &Cli) -> Result<u8> {
     // Initialize logging.
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(cli.log_level()))
         .init();
 
-    log::info!("Task: {}", cli.task);
+    match &cli.command {
+        Some(Command::Init(args)) => return run_init(args).map(|()| exit_codes::OK),
+        Some(Command::Diff(args)) => return run_diff(cli, args).map(|()| exit_codes::OK),
+        Some(Command::Apply(args)) => return run_apply(cli, args),
+        Some(Command::Resume(args)) => return run_resume(cli, args),
+        Some(Command::Autopilot(args)) => return run_autopilot(cli, args),
+        Some(Command::Export(args)) => return run_export(args).map(|()| exit_codes::OK),
+        Some(Command::Summary(args)) => return run_summary(args).map(|()| exit_codes::OK),
+        Some(Command::Batch(args)) => return run_batch(cli, args),
+        Some(Command::Daemon(args)) => return run_daemon(cli, args).map(|()| exit_codes::OK),
+        Some(Command::Retry(args)) => return run_retry(cli, args),
+        Some(Command::Refine(args)) => return run_refine(cli, args),
+        Some(Command::Undo(args)) => return run_undo(cli, args).map(|()| exit_codes::OK),
+        Some(Command::Restore(args)) => return run_restore(args).map(|()| exit_codes::OK),
+        Some(Command::Runs(args)) => return run_runs(cli, args).map(|()| exit_codes::OK),
+        Some(Command::Log(args)) => return run_log(cli, args).map(|()| exit_codes::OK),
+        Some(Command::Config(args)) => return run_config(cli, args).map(|()| exit_codes::OK),
+        None => {}
+    }
+
+    // Clap guarantees a task when no subcommand is given.
+    let task = cli.task.as_deref().unwrap_or_default();
+    log::info!("Task: {}", task);
 
     // Load configuration using explicit CLI path (error if missing).
-    let config =
-        NexusConfig::load_with_config_path(&cli.config).context("failed to load configuration")?;
+    let config = load_config(cli)?;
 
     log::debug!("Config path: {:?}", config.settings_path);
     log::debug!("Permission mode: {:?}", config.settings.permission_mode);
 
     if cli.dry_run {
-        println!("[DRY RUN] Would execute: {}", cli.task);
-        println!("Settings loaded: {}", config.has_settings_file());
-        println!("API key available: {}", config.has_api_key());
-        return Ok(());
+        return run_dry_run(cli, &config, task);
     }
 
     // TODO: Phase 2+ - Implement actual execution.
-    println!("Executing: {}", cli.task);
+    if cli.json_output() {
+        print_json(&json!({"task": task, "executed": false}))?;
+        return Ok(exit_codes::OK);
+    }
+    println!("Executing: {}", task);
     println!("(Implementation pending - Phase 2+)");
 
+    Ok(exit_codes::OK)
+}
+
+/// Scaffolds `.nexus/` in the working directory and prints next steps.
+fn run_init(args: &InitArgs) -> Result<()> {
+    let root = std::env::current_dir().context("failed to resolve working directory")?;
+    let report = nexus::init::init_project(
+        &root,
+        nexus::init::InitOptions {
+            force: args.force,
+            gitignore: args.gitignore,
+        },
+    )?;
+
+    if report.wrote_settings {
+        println!("Wrote {}", report.settings_path.display());
+    } else {
+        println!(
+            "Kept existing {} (use --force to overwrite)",
+            report.settings_path.display()
+        );
+    }
+    println!("Created {}", report.runs_dir.display());
+    if report.updated_gitignore {
+        println!("Added {} to .gitignore", nexus::init::GITIGNORE_ENTRY);
+    }
+    println!("\nNext steps:");
+    println!(
+        "  1. Review deny_paths, allow_paths_write, and approval_rules in .nexus/settings.json"
+    );
+    println!("  2. Set OPENAI_API_KEY in your environment or a .env file");
+    println!("  3. Preview a task: nexus --dry-run \"describe the change\"");
+    Ok(())
+}
+
+/// Asks the executor for actions and prints them as colorized diffs with
+/// line counts, without logging the run or touching any file. On a terminal
+/// the model's reply is shown as it streams. Exits like a run that
+/// proposed the same actions.
+fn run_dry_run(cli: &Cli, config: &NexusConfig, task: &str) -> Result<u8> {
+    let patterns = &cli.files;
+    let root = std::env::current_dir().context("failed to resolve working directory")?;
+    let guard = BinaryGuard::from_settings(&config.settings)?;
+    let files = nexus::context::collect_files(&root, patterns, &guard)?;
+    if !patterns.is_empty() && files.is_empty() {
+        bail!("no files match --files {}", patterns.join(" "));
+    }
+    let cancel = CancelToken::new();
+    let adapter = build_adapter(cli, config, &cancel)?;
+    let options = ExecuteOptions {
+        dry_run: false,
+        max_tokens: None,
+        temperature: None,
+        preferred_format: PatchFormat::default(),
+        output: OutputFormat::default(),
+        template: cli.template.clone(),
+    };
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("failed to start async runtime")?;
+    let no_color = std::env::var_os("NO_COLOR").is_some();
+    let live = std::io::stderr().is_terminal() && !cli.json_output();
+    let actions = runtime.block_on(async {
+        install_ctrl_c_handler(cancel);
+        if !live {
+            return adapter.execute(task, files, options).await;
+        }
+        let renderer = StreamRenderer::new(std::io::stderr(), !no_color);
+        render_stream(renderer, |on_chunk| {
+            adapter.execute_streaming(task, files, options, on_chunk)
+        })
+        .await
+    })?;
+
+    let status = if actions.is_empty() {
+        RunStatus::CompletedNoChanges
+    } else {
+        RunStatus::ProposedPendingApply
+    };
+    if cli.json_output() {
+        print_json(&json!({"task": task, "dry_run": true, "actions": actions}))?;
+        return Ok(status.exit_code());
+    }
+    eprintln!("[DRY RUN] {task}");
+    if actions.is_empty() {
+        eprintln!("No changes proposed");
+    } else {
+        let color = !no_color && std::io::stdout().is_terminal();
+        print!("{}", nexus::preview::render_dry_run(&actions, &root, color));
+    }
+    Ok(status.exit_code())
+}
+
+/// Prints the pending actions of a run as one combined diff.
+fn run_diff(cli: &Cli, args: &DiffArgs) -> Result<()> {
+    let root = std::env::current_dir().context("failed to resolve working directory")?;
+    let actions = nexus::preview::pending_actions_for_run(&root, &args.run_id)
+        .with_context(|| format!("failed to load actions for {}", args.run_id))?;
+
+    if actions.is_empty() && !cli.json_output() {
+        eprintln!("No pending actions for {}", args.run_id);
+        return Ok(());
+    }
+    if let Some(stage_dir) = &args.stage_dir {
+        return stage_actions(&root, stage_dir, &actions);
+    }
+    if cli.json_output() {
+        return print_json(&json!({"run_id": args.run_id, "actions": actions}));
+    }
+
+    let color =
+        !args.no_color && std::env::var_os("NO_COLOR").is_none() && std::io::stdout().is_terminal();
+    print!("{}", nexus::preview::render_diff(&actions, &root, color));
+    Ok(())
+}
+
+/// Applies `actions` to copies of the files they touch under `stage_dir`.
+fn stage_actions(root: &Path, stage_dir: &Path, actions: &[ProposedAction]) -> Result<()> {
+    std::fs::create_dir_all(stage_dir)
+        .with_context(|| format!("failed to create {}", stage_dir.display()))?;
+    if stage_dir.canonicalize()? == root.canonicalize()? {
+        bail!("the staging directory must differ from the working tree");
+    }
+
+    let mut applier = nexus::apply::Applier::new(root).with_stage_dir(stage_dir);
+    let mut staged = Vec::new();
+    for action in actions {
+        if !nexus::apply::Applier::supports(action) {
+            eprintln!("Skipped {}: not applicable yet", action.id);
+            continue;
+        }
+        let changes = applier
+            .apply(action)
+            .with_context(|| format!("failed to stage {}", action.id))?;
+        for path in changes.written {
+            if !staged.contains(&path) {
+                staged.push(path);
+            }
+        }
+        for path in changes.deleted {
+            staged.retain(|staged| *staged != path);
+            println!("deleted {path}");
+        }
+    }
+    for path in &staged {
+        println!("{}", stage_dir.join(path).display());
+    }
+    eprintln!("Staged {} file(s) in {}", staged.len(), stage_dir.display());
+    Ok(())
+}
+
+/// Applies the pending actions of a run in place, resolving conflicts
+/// interactively when attached to a terminal.
+fn run_apply(cli: &Cli, args: &ApplyArgs) -> Result<u8> {
+    let root = std::env::current_dir().context("failed to resolve working directory")?;
+    let config = load_config(cli)?;
+    let mut prompt = nexus::apply::LinePrompt::stdio();
+    let mut run_apply =
+        nexus::apply::RunApply::from_settings(&root, &args.run_id, &config.settings)?
+            .with_redactor(build_redactor(cli, &config)?);
+    if !args.no_interactive && std::io::stdin().is_terminal() {
+        run_apply = run_apply.with_prompt(&mut prompt);
+    }
+    let report = run_apply
+        .run()
+        .with_context(|| format!("failed to apply {}", args.run_id))?;
+
+    if cli.json_output() {
+        let mut document = report.to_json();
+        document["run_id"] = json!(args.run_id);
+        print_json(&document)?;
+    } else {
+        print_apply_report(&report);
+    }
+    if let Some(instruction) = report.reask_instruction() {
+        return reask(cli, &args.run_id, &report, instruction);
+    }
+    if report.rolled_back.is_some() {
+        return Ok(exit_codes::ROLLED_BACK);
+    }
+    if report.verification_failed() {
+        return Ok(exit_codes::GENERAL_ERROR);
+    }
+    if !report.failed.is_empty() {
+        return Ok(exit_codes::PARTIALLY_APPLIED);
+    }
+    Ok(exit_codes::OK)
+}
+
+/// Decides and applies the actions an interrupted run had proposed, then
+/// records how the run ended.
+fn run_resume(cli: &Cli, args: &ResumeArgs) -> Result<u8> {
+    let root = std::env::current_dir().context("failed to resolve working directory")?;
+    let config = load_config(cli)?;
+    let interactive = !args.no_interactive && std::io::stdin().is_terminal();
+    if args.tui && !(interactive && std::io::stdout().is_terminal()) {
+        bail!("--tui needs a terminal");
+    }
+    let mut prompt = nexus::apply::LinePrompt::stdio();
+    let mut tui = TuiPrompt::new(&root, status_bar(&root, &args.run_id));
+
+    let redactor = build_redactor(cli, &config)?;
+    let mut resume = RunResume::new(&root, &args.run_id)
+        .with_permission_gate(PermissionGate::from_settings(&config.settings)?)
+        .with_redactor(redactor.clone())
+        .with_sync_interval(config.settings.event_sync_interval());
+    if args.tui {
+        resume = resume.with_prompt(&mut tui);
+    } else if interactive {
+        resume = resume.with_prompt(&mut prompt);
+    }
+    let decided = resume
+        .run()
+        .with_context(|| format!("failed to resume {}", args.run_id))?;
+    eprintln!(
+        "Resuming {}: {} pending action(s)",
+        args.run_id, decided.pending
+    );
+    for (action_id, reason) in &decided.denied {
+        eprintln!("Denied {action_id}: {reason}");
+    }
+
+    let mut run_apply =
+        nexus::apply::RunApply::from_settings(&root, &args.run_id, &config.settings)?
+            .with_redactor(redactor.clone());
+    for (action_id, reason) in &decided.awaiting {
+        run_apply = run_apply.with_held(action_id, reason);
+    }
+    if interactive {
+        run_apply = run_apply.with_prompt(&mut prompt);
+    }
+    let report = run_apply
+        .run()
+        .with_context(|| format!("failed to apply {}", args.run_id))?;
+    if !cli.json_output() {
+        print_apply_report(&report);
+    }
+
+    let status = nexus::resume::finish_run(
+        &root,
+        &args.run_id,
+        &redactor,
+        config.settings.event_sync_interval(),
+    )?;
+    eprintln!("Run {} is {}", args.run_id, status.as_str());
+    if cli.json_output() {
+        print_json(&json!({
+            "run_id": args.run_id,
+            "decided": decided.to_json(),
+            "apply": report.to_json(),
+            "status": status.as_str(),
+        }))?;
+    }
+    if let Some(instruction) = report.reask_instruction() {
+        return reask(cli, &args.run_id, &report, instruction);
+    }
+    Ok(status.exit_code())
+}
+
+/// What the `--tui` status bar says about `run_id`: its model and token
+/// usage, left out if the run cannot be read.
+fn status_bar(root: &Path, run_id: &str) -> StatusBar {
+    let mut status = StatusBar {
+        run_id: run_id.to_string(),
+        ..StatusBar::default()
+    };
+    match nexus::export::RunProfile::load_for_run(root, run_id) {
+        Ok(profile) => {
+            status.model = profile.model;
+            status.prompt_tokens = profile.prompt_tokens;
+            status.completion_tokens = profile.completion_tokens;
+        }
+        Err(err) => log::debug!("no run profile for {run_id}: {err}"),
+    }
+    status
+}
+
+/// Applies the approved actions of a run batch by batch under the
+/// autopilot settings, asking before each next batch when attached to a
+/// terminal.
+fn run_autopilot(cli: &Cli, args: &AutopilotArgs) -> Result<u8> {
+    let root = std::env::current_dir().context("failed to resolve working directory")?;
+    let config = load_config(cli)?;
+    let mut prompt = nexus::apply::LinePrompt::stdio();
+    let mut autopilot = RunAutopilot::new(&root, &args.run_id, config.settings.clone())
+        .with_redactor(build_redactor(cli, &config)?);
+    if !args.no_interactive && std::io::stdin().is_terminal() {
+        autopilot = autopilot.with_prompt(&mut prompt);
+    }
+    let report = autopilot
+        .run()
+        .with_context(|| format!("failed to run autopilot for {}", args.run_id))?;
+
+    if cli.json_output() {
+        let mut document = report.to_json();
+        document["run_id"] = json!(args.run_id);
+        print_json(&document)?;
+    } else {
+        print_autopilot_report(&report);
+    }
+
+    let any_batch =
+        |check: fn(&ApplyReport) -> bool| report.batches.iter().any(|(_, applied)| check(applied));
+    if any_batch(|applied| applied.rolled_back.is_some()) {
+        return Ok(exit_codes::ROLLED_BACK);
+    }
+    if any_batch(|applied| !applied.failed.is_empty() || applied.verification_failed()) {
+        return Ok(exit_codes::PARTIALLY_APPLIED);
+    }
+    if report.stopped.is_some() || !report.decided.awaiting.is_empty() {
+        return Ok(exit_codes::PENDING_APPLY);
+    }
+    Ok(exit_codes::OK)
+}
+
+fn print_autopilot_report(report: &AutopilotReport) {
+    for (action_id, reason) in &report.decided.denied {
+        eprintln!("Denied {action_id}: {reason}");
+    }
+    for (batch, applied) in &report.batches {
+        eprintln!(
+            "Batch of {} action(s), {} CU",
+            batch.action_ids.len(),
+            batch.cu
+        );
+        print_apply_report(applied);
+    }
+    for (action_id, reason) in &report.decided.awaiting {
+        eprintln!("Pending {action_id}: {reason}");
+    }
+    if let Some(reason) = &report.stopped {
+        eprintln!("Autopilot stopped: {reason}");
+    }
+}
+
+fn print_apply_report(report: &ApplyReport) {
+    match &report.branch {
+        Some(nexus::git::RunBranch::Created { branch, .. }) => {
+            eprintln!("Applying on new branch {branch}");
+        }
+        Some(branch) => eprintln!("Applying on branch {}", branch.branch()),
+        None => {}
+    }
+    for (action_id, changes) in &report.applied {
+        for path in &changes.written {
+            println!("{action_id} wrote {path}");
+        }
+        for path in &changes.deleted {
+            println!("{action_id} deleted {path}");
+        }
+        for path in &changes.conflicted {
+            eprintln!("{action_id} left conflict markers in {path}; resolve them by hand");
+        }
+        for (path, note) in &changes.normalized {
+            eprintln!("{action_id} {path}: {note}");
+        }
+    }
+    for (action_id, output) in &report.commands {
+        print!("{}", output.stdout);
+        eprint!("{}", output.stderr);
+        if output.success() {
+            println!("{action_id} ran successfully");
+        }
+    }
+    for (action_id, commit) in &report.commits {
+        println!("{action_id} committed as {commit:.12}");
+    }
+    for (action_id, reason) in &report.failed {
+        eprintln!("Failed {action_id}: {reason}");
+    }
+    for (action_id, reason) in &report.skipped {
+        eprintln!("Skipped {action_id}: {reason}");
+    }
+    for (argv, output) in &report.verification {
+        let command = argv.join(" ");
+        if output.success() {
+            println!("Verified with {command}");
+        } else {
+            print!("{}", output.stdout);
+            eprint!("{}", output.stderr);
+            eprintln!("Verification failed: {command}");
+        }
+    }
+    if let Some(undo) = &report.rolled_back {
+        for (action_id, files) in &undo.reverted {
+            eprintln!("Rolled back {action_id}: {}", files.join(", "));
+        }
+        for (action_id, reason) in &undo.skipped {
+            eprintln!("Could not roll back {action_id}: {reason}");
+        }
+    }
+}
+
+/// Retries `run_id` with an instruction to redo the re-asked actions.
+fn reask(cli: &Cli, run_id: &str, report: &ApplyReport, instruction: String) -> Result<u8> {
+    eprintln!("Asking the model to redo {} action(s)", report.reask.len());
+    let retry = RetryArgs {
+        run_id: run_id.to_string(),
+        temperature: None,
+        instruction: Some(instruction),
+    };
+    run_retry(cli, &retry)
+}
+
+/// Writes the proposed actions of a run in the requested export format.
+fn run_export(args: &ExportArgs) -> Result<()> {
+    let root = std::env::current_dir().context("failed to resolve working directory")?;
+    let actions = nexus::preview::pending_actions_for_run(&root, &args.run_id)
+        .with_context(|| format!("failed to load actions for {}", args.run_id))?;
+
+    let document = match args.format {
+        ExportFormat::Sarif => nexus::export::to_sarif(&actions, &root),
+    };
+    let rendered = serde_json::to_string_pretty(&document)?;
+
+    match &args.output {
+        Some(path) => std::fs::write(path, rendered + "\n")
+            .with_context(|| format!("failed to write {}", path.display()))?,
+        None => println!("{rendered}"),
+    }
+    Ok(())
+}
+
+/// Regenerates the markdown summary or HTML report of a run.
+fn run_summary(args: &SummaryArgs) -> Result<()> {
+    let root = std::env::current_dir().context("failed to resolve working directory")?;
+    let path = match args.report {
+        ReportFormat::Markdown => nexus::export::write_summary_for_run(&root, &args.run_id),
+        ReportFormat::Html => nexus::export::write_html_report_for_run(&root, &args.run_id),
+    }
+    .with_context(|| format!("failed to summarize {}", args.run_id))?;
+    println!("{}", path.display());
+    Ok(())
+}
+
+fn run_runs(cli: &Cli, args: &RunsArgs) -> Result<()> {
+    let root = std::env::current_dir().context("failed to resolve working directory")?;
+    match &args.command {
+        RunsCommand::List { rebuild, json } => {
+            let runs_dir = root.join(".nexus").join("runs");
+            let index = if *rebuild {
+                EventLogIndex::rebuild(&runs_dir)
+            } else {
+                EventLogIndex::load(&runs_dir)
+            }
+            .context("failed to load the run index")?;
+            if cli.json_output() {
+                print_json(index.entries())?;
+            } else if *json {
+                println!("{}", serde_json::to_string_pretty(index.entries())?);
+            } else if index.is_empty() {
+                println!("No runs recorded");
+            } else {
+                for entry in index.entries() {
+                    println!(
+                        "{:<32} {}  {}",
+                        entry.run_id,
+                        entry.started_at.format("%Y-%m-%d %H:%M:%S"),
+                        entry.status.as_deref().unwrap_or("in progress")
+                    );
+                }
+            }
+        }
+        RunsCommand::Diff { run_a, run_b } => {
+            let a = nexus::export::RunProfile::load_for_run(&root, run_a)
+                .with_context(|| format!("failed to load {run_a}"))?;
+            let b = nexus::export::RunProfile::load_for_run(&root, run_b)
+                .with_context(|| format!("failed to load {run_b}"))?;
+            print!("{}", nexus::export::render_comparison(&a, &b));
+        }
+        RunsCommand::Show { run_id, json } => {
+            let timeline = nexus::export::Timeline::load_for_run(&root, run_id)
+                .with_context(|| format!("failed to load {run_id}"))?;
+            if cli.json_output() {
+                print_json(&timeline)?;
+            } else if *json {
+                println!("{}", serde_json::to_string_pretty(&timeline)?);
+            } else {
+                print!("{}", timeline.render());
+                let tree = nexus::export::RunTree::load_for_run(&root, run_id)
+                    .with_context(|| format!("failed to load runs related to {run_id}"))?;
+                if tree.run_count() > 1 {
+                    print!("\nRelated runs:\n{}", tree.render(run_id));
+                }
+            }
+        }
+        RunsCommand::Cost { run_id, json } => {
+            let cost = nexus::export::RunCost::load_for_run(&root, run_id)
+                .with_context(|| format!("failed to load {run_id}"))?;
+            if cli.json_output() {
+                print_json(&cost)?;
+            } else if *json {
+                println!("{}", serde_json::to_string_pretty(&cost)?);
+            } else {
+                print!("{}", cost.render());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Loads the user's and the project's settings (`--config`), with
+/// `--provider` and `--model` on top.
+fn load_config(cli: &Cli) -> Result<NexusConfig> {
+    let config = NexusConfig::load_with_config_path_strict(&cli.config, cli.strict_config)
+        .context("failed to load configuration")?;
+    Ok(config.with_cli_overrides(cli.provider.as_deref(), cli.model.as_deref()))
+}
+
+/// Handles `nexus config` subcommands.
+fn run_config(cli: &Cli, args: &ConfigArgs) -> Result<()> {
+    match &args.command {
+        ConfigCommand::Show { json } => {
+            let config = load_config(cli)?;
+            let serde_json::Value::Object(values) = serde_json::to_value(&config.settings)? else {
+                bail!("settings did not serialize to an object");
+            };
+            let path = |path: &Option<std::path::PathBuf>| {
+                path.as_ref().map(|path| path.display().to_string())
+            };
+            if *json || cli.json_output() {
+                let settings: serde_json::Map<_, _> = values
+                    .into_iter()
+                    .map(|(key, value)| {
+                        let sources = config.sources.get(&key);
+                        let entry = serde_json::json!({"value": value, "sources": sources
+                            .iter()
+                            .map(|source| source.as_str())
+                            .collect::<Vec<_>>()});
+                        (key, entry)
+                    })
+                    .collect();
+                let report = serde_json::json!({
+                    "user_settings": path(&config.user_settings_path),
+                    "project_settings": path(&config.settings_path),
+                    "settings": settings,
+                });
+                if cli.json_output() {
+                    return print_json(&report);
+                }
+                println!("{}", serde_json::to_string_pretty(&report)?);
+                return Ok(());
+            }
+            let none = || "none".to_string();
+            println!(
+                "user settings: {}",
+                path(&config.user_settings_path).unwrap_or_else(none)
+            );
+            println!(
+                "project settings: {}",
+                path(&config.settings_path).unwrap_or_else(none)
+            );
+            for (key, value) in &values {
+                println!("{key} = {value} ({})", config.sources.describe(key));
+            }
+        }
+        ConfigCommand::Migrate { dry_run } => {
+            let path = cli.config.display();
+            let report = nexus::settings::migrate_file(&cli.config, *dry_run)
+                .with_context(|| format!("failed to migrate {path}"))?;
+            if cli.json_output() {
+                let migration = report.as_ref().map(|report| {
+                    json!({"from": report.from, "to": report.to, "changes": report.changes})
+                });
+                return print_json(&json!({
+                    "path": cli.config,
+                    "dry_run": dry_run,
+                    "migration": migration,
+                }));
+            }
+            let Some(report) = report else {
+                println!("{path} is already at schema {}", nexus::SCHEMA_VERSION);
+                return Ok(());
+            };
+            let verb = if *dry_run {
+                "Would migrate"
+            } else {
+                "Migrated"
+            };
+            println!("{verb} {path} from schema {} to {}", report.from, report.to);
+            for change in &report.changes {
+                println!("  - {change}");
+            }
+        }
+    }
+    Ok(())
+}
+
+fn run_log(cli: &Cli, args: &LogArgs) -> Result<()> {
+    let root = std::env::current_dir().context("failed to resolve working directory")?;
+    match &args.command {
+        LogCommand::Export {
+            run_id,
+            all,
+            format,
+            anonymize,
+            output,
+        } => {
+            let policy = if *anonymize {
+                let config = load_config(cli)?;
+                Some(config.settings.anonymize.unwrap_or_default())
+            } else {
+                None
+            };
+            let run_id = run_id.as_deref().filter(|_| !*all);
+            let target = run_id.unwrap_or("all runs");
+            if *format == LogFormat::Jsonl {
+                let run_ids = match run_id {
+                    Some(run_id) => vec![run_id.to_string()],
+                    None => EventLogIndex::load_for_project(&root)
+                        .context("failed to load the run index")?
+                        .entries()
+                        .iter()
+                        .map(|entry| entry.run_id.clone())
+                        .collect(),
+                };
+                let mut jsonl = String::new();
+                for run_id in &run_ids {
+                    jsonl.push_str(
+                        &nexus::export::export_log_for_run(&root, run_id, policy.as_ref())
+                            .with_context(|| format!("failed to export {run_id}"))?,
+                    );
+                }
+                match output {
+                    Some(path) => std::fs::write(path, jsonl)
+                        .with_context(|| format!("failed to write {}", path.display()))?,
+                    None => print!("{jsonl}"),
+                }
+                return Ok(());
+            }
+
+            let Some(output) = output else {
+                bail!("--output is required to export as SQLite or CSV");
+            };
+            let tables = nexus::export::export_tables(&root, run_id, policy.as_ref())
+                .with_context(|| format!("failed to export {target}"))?;
+            if *format == LogFormat::Sqlite {
+                tables.write_sqlite(output)?;
+            } else {
+                tables.write_csv(output)?;
+            }
+            eprintln!(
+                "Exported {} event(s) from {target} to {}",
+                tables.events.len(),
+                output.display()
+            );
+        }
+        LogCommand::Verify { run_id } => {
+            let report = nexus::event_log::verify_run(&root, run_id)
+                .with_context(|| format!("failed to verify {run_id}"))?;
+            if let Some(broken) = report.broken {
+                return Err(NexusError::EventLogTampered {
+                    line: broken.line,
+                    reason: broken.reason,
+                }
+                .into());
+            }
+            println!("{run_id}: {} event(s) verified", report.verified);
+            if report.unchained > 0 {
+                println!(
+                    "{} earlier event(s) were written without hashes and cannot be verified",
+                    report.unchained
+                );
+            }
+        }
+        LogCommand::Tail {
+            run_id,
+            follow,
+            lines,
+        } => {
+            let mut follower = EventLogFollower::open_for_run(&root, run_id)
+                .with_context(|| format!("failed to open the log of {run_id}"))?;
+            let past: Vec<_> = follower
+                .poll()?
+                .into_iter()
+                .filter(|event| event.event_type != RunEventKind::ExecutorStreaming)
+                .collect();
+            let mut ended = past.iter().any(ends_run);
+            for event in &past[past.len().saturating_sub(*lines)..] {
+                println!("{}", TimelineEntry::from_event(event).render());
+            }
+            while *follow && !ended {
+                std::io::stdout().flush()?;
+                std::thread::sleep(TAIL_POLL_INTERVAL);
+                for event in follower.poll()? {
+                    if event.event_type == RunEventKind::ExecutorStreaming {
+                        continue;
+                    }
+                    println!("{}", TimelineEntry::from_event(&event).render());
+                    ended |= event.run_id == *run_id && ends_run(&event);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Runs every task of a batch file and writes a consolidated summary.
+///
+/// Fails with `NexusError::BatchFailed` if any task failed; otherwise the
+/// exit code follows the run status contract, e.g. runs still pending apply
+/// exit with [`exit_codes::PENDING_APPLY`]. Under `--ci` the result is
+/// reported to the CI system, and failed runs exit with their status too.
+fn run_batch(cli: &Cli, args: &BatchArgs) -> Result<u8> {
+    let root = std::env::current_dir().context("failed to resolve working directory")?;
+    let config = load_config(cli)?;
+    let batch = BatchFile::load(&args.file)?;
+
+    let cancel = CancelToken::new();
+    let adapter = build_adapter(cli, &config, &cancel)?;
+    let planner = build_role_adapter(cli, &config, &cancel, PLANNER_SYSTEM_PROMPT)?;
+    let reviewer = build_role_adapter(cli, &config, &cancel, REVIEWER_SYSTEM_PROMPT)?;
+    let runner = BatchRunner::new(&adapter, &root)
+        .with_role_executor(AgentRole::Planner, &planner)
+        .with_role_executor(AgentRole::Reviewer, &reviewer)
+        .with_action_review(config.settings.review_actions)
+        .with_jobs(usize::from(args.jobs))
+        .with_binary_guard(BinaryGuard::from_settings(&config.settings)?)
+        .with_run_id_scheme(config.settings.run_id_scheme)
+        .with_sync_interval(config.settings.event_sync_interval())
+        .with_redactor(adapter.redactor().clone());
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("failed to start async runtime")?;
+    let outcomes = runtime.block_on(async {
+        install_ctrl_c_handler(cancel);
+        runner.run(&batch).await
+    })?;
+
+    let summary = write_batch_summary(&root, &outcomes)?;
+    if cli.json_output() {
+        let runs: Vec<_> = outcomes.iter().map(BatchOutcome::to_json).collect();
+        print_json(&json!({"runs": runs, "summary": summary}))?;
+    } else {
+        for outcome in &outcomes {
+            println!(
+                "{} {} {}",
+                outcome.run_id,
+                outcome.status.as_str(),
+                outcome.error.as_deref().unwrap_or(&outcome.task)
+            );
+        }
+        println!("{}", summary.display());
+    }
+    if let Some(mode) = cli.ci {
+        return report_ci(mode, &outcomes);
+    }
+
+    let failed = outcomes
+        .iter()
+        .filter(|outcome| outcome.is_failure())
+        .count();
+    if failed > 0 {
+        return Err(NexusError::BatchFailed {
+            failed,
+            total: outcomes.len(),
+        }
+        .into());
+    }
+    Ok(nexus::ci::exit_code(&outcomes))
+}
+
+/// Runs queued task files under the autopilot permission policy.
+///
+/// Proposed actions are decided by the permission gate in autopilot mode;
+/// anything that would need a prompt is denied since nobody is watching.
+fn run_daemon(cli: &Cli, args: &DaemonArgs) -> Result<()> {
+    let root = std::env::current_dir().context("failed to resolve working directory")?;
+    let config = load_config(cli)?;
+
+    let mut policy = config.settings.clone();
+    policy.permission_mode = PermissionMode::Autopilot;
+    let cancel = CancelToken::new();
+    let adapter = build_adapter(cli, &config, &cancel)?;
+    let planner = build_role_adapter(cli, &config, &cancel, PLANNER_SYSTEM_PROMPT)?;
+    let reviewer = build_role_adapter(cli, &config, &cancel, REVIEWER_SYSTEM_PROMPT)?;
+    let redactor = adapter.redactor().clone();
+    let runner = BatchRunner::new(&adapter, &root)
+        .with_role_executor(AgentRole::Planner, &planner)
+        .with_role_executor(AgentRole::Reviewer, &reviewer)
+        .with_action_review(config.settings.review_actions)
+        .with_binary_guard(BinaryGuard::from_settings(&config.settings)?)
+        .with_run_id_scheme(config.settings.run_id_scheme)
+        .with_sync_interval(config.settings.event_sync_interval())
+        .with_redactor(redactor.clone())
+        .with_permission_gate(PermissionGate::from_settings(&policy)?);
+    let daemon = Daemon::new(runner, root.join(&args.queue))
+        .with_poll_interval(std::time::Duration::from_secs(args.poll_interval))
+        .with_settings(config.settings.clone())
+        .with_redactor(redactor);
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("failed to start async runtime")?;
+    runtime.block_on(async {
+        install_ctrl_c_handler(cancel.clone());
+        if !args.once {
+            return daemon.run(&cancel).await;
+        }
+        loop {
+            match daemon.run_once().await {
+                Ok(Some(outcome)) if cli.json_output() => println!("{}", outcome.to_json()),
+                Ok(Some(outcome)) => println!("{} {}", outcome.run_id, outcome.status.as_str()),
+                Ok(None) => return Ok(()),
+                Err(err) => return Err(err),
+            }
+        }
+    })?;
+    Ok(())
+}
+
+/// Re-runs a previous run's task, linking the new run through `retry_of`.
+fn run_retry(cli: &Cli, args: &RetryArgs) -> Result<u8> {
+    let root = std::env::current_dir().context("failed to resolve working directory")?;
+    let config = load_config(cli)?;
+    let source = RetrySource::load(&root, &args.run_id)
+        .with_context(|| format!("failed to load {}", args.run_id))?;
+    let overrides = RetryOverrides {
+        model: cli.model.clone(),
+        temperature: args.temperature,
+        instruction: args.instruction.clone(),
+    };
+
+    let cancel = CancelToken::new();
+    let mut adapter = build_adapter(cli, &config, &cancel)?;
+    if let Some(model) = source.model(&overrides) {
+        adapter = adapter.with_model(model);
+    }
+    let task = source.to_task(&overrides);
+    let follow_up = FollowUp::Retry(&source.run_id);
+    let outcome = run_follow_up(&root, cli, &config, &adapter, cancel, &task, follow_up)?;
+
+    if cli.json_output() {
+        let mut document = outcome.to_json();
+        document["retry_of"] = json!(source.run_id);
+        print_json(&document)?;
+    } else {
+        println!("{} {}", outcome.run_id, outcome.status.as_str());
+    }
+    if let Some(mode) = cli.ci {
+        return report_ci(mode, std::slice::from_ref(&outcome));
+    }
+    if let (RunStatus::Failed, Some(error)) = (outcome.status, &outcome.error) {
+        anyhow::bail!("retry of {} failed: {error}", source.run_id);
+    }
+    Ok(outcome.status.exit_code())
+}
+
+/// Continues a run's conversation with `args.instruction`, proposing a
+/// revised set of actions as a new run linked through `refine_of`.
+fn run_refine(cli: &Cli, args: &RefineArgs) -> Result<u8> {
+    let root = std::env::current_dir().context("failed to resolve working directory")?;
+    let config = load_config(cli)?;
+    let session = Session::load(&root, &args.run_id)
+        .with_context(|| format!("failed to load the conversation of {}", args.run_id))?;
+    let task = session.to_task(&args.instruction)?;
+
+    let cancel = CancelToken::new();
+    let mut adapter = build_adapter(cli, &config, &cancel)?;
+    if let Some(model) = cli.model.as_deref().or(session.source.model.as_deref()) {
+        adapter = adapter.with_model(model);
+    }
+    let adapter = adapter.with_history(session.messages.clone());
+    if !cli.json_output() {
+        eprintln!(
+            "Refining {} action(s) proposed by {}",
+            session.actions.len(),
+            session.run_id
+        );
+    }
+    let follow_up = FollowUp::Refine(&session.run_id);
+    let outcome = run_follow_up(&root, cli, &config, &adapter, cancel, &task, follow_up)?;
+
+    if cli.json_output() {
+        let mut document = outcome.to_json();
+        document["refine_of"] = json!(session.run_id);
+        print_json(&document)?;
+    } else {
+        println!("{} {}", outcome.run_id, outcome.status.as_str());
+    }
+    if let Some(mode) = cli.ci {
+        return report_ci(mode, std::slice::from_ref(&outcome));
+    }
+    if let (RunStatus::Failed, Some(error)) = (outcome.status, &outcome.error) {
+        anyhow::bail!("refining {} failed: {error}", session.run_id);
+    }
+    Ok(outcome.status.exit_code())
+}
+
+/// Runs `task` with `adapter` as a new run that follows up on an earlier
+/// one, with the planner and reviewer every run gets.
+fn run_follow_up(
+    root: &Path,
+    cli: &Cli,
+    config: &NexusConfig,
+    adapter: &CodexAdapter,
+    cancel: CancelToken,
+    task: &BatchTask,
+    follow_up: FollowUp<'_>,
+) -> Result<BatchOutcome> {
+    let planner = build_role_adapter(cli, config, &cancel, PLANNER_SYSTEM_PROMPT)?;
+    let reviewer = build_role_adapter(cli, config, &cancel, REVIEWER_SYSTEM_PROMPT)?;
+    let runner = BatchRunner::new(adapter, root)
+        .with_role_executor(AgentRole::Planner, &planner)
+        .with_role_executor(AgentRole::Reviewer, &reviewer)
+        .with_action_review(config.settings.review_actions)
+        .with_binary_guard(BinaryGuard::from_settings(&config.settings)?)
+        .with_run_id_scheme(config.settings.run_id_scheme)
+        .with_sync_interval(config.settings.event_sync_interval())
+        .with_redactor(adapter.redactor().clone());
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("failed to start async runtime")?;
+    let outcome = runtime.block_on(async {
+        install_ctrl_c_handler(cancel);
+        runner.run_follow_up(task, follow_up).await
+    })?;
+    Ok(outcome)
+}
+
+fn run_undo(cli: &Cli, args: &UndoArgs) -> Result<()> {
+    let root = std::env::current_dir().context("failed to resolve working directory")?;
+    let config = load_config(cli)?;
+    let report = nexus::apply::RunUndo::new(&root, &args.run_id)
+        .with_force(args.force)
+        .with_redactor(build_redactor(cli, &config)?)
+        .with_sync_interval(config.settings.event_sync_interval())
+        .run()
+        .with_context(|| format!("failed to undo {}", args.run_id))?;
+    if cli.json_output() {
+        let mut document = report.to_json();
+        document["run_id"] = json!(args.run_id);
+        return print_json(&document);
+    }
+    for (id, files) in &report.reverted {
+        eprintln!("Reverted {id}: {}", files.join(", "));
+    }
+    for (id, reason) in &report.skipped {
+        eprintln!("Skipped {id}: {reason}");
+    }
+    if report.reverted.is_empty() && report.skipped.is_empty() {
+        eprintln!("Nothing to undo for {}", args.run_id);
+    }
+    Ok(())
+}
+
+fn run_restore(args: &RestoreArgs) -> Result<()> {
+    let root = std::env::current_dir().context("failed to resolve working directory")?;
+    let restored = nexus::apply::RunBackups::new(&root, &args.run_id)
+        .and_then(|backups| backups.restore(args.path.as_deref()))
+        .with_context(|| format!("failed to restore {}", args.run_id))?;
+    if restored.is_empty() {
+        eprintln!("No backups for {}", args.run_id);
+    }
+    for path in &restored {
+        eprintln!("Restored {path}");
+    }
     Ok(())
 }
+
+/// Paces the calls of every adapter the command builds, so the executor,
+/// planner and reviewer all pause when the provider rate limits one of them.
+static SCHEDULER: LazyLock<RateLimitScheduler> = LazyLock::new(RateLimitScheduler::new);
+
+/// Builds an adapter for the provider picked by `--provider` or settings,
+/// asking for the model from `--model` or settings, configured the way
+/// every command shares, with the project's prompt templates.
+fn build_adapter(cli: &Cli, config: &NexusConfig, cancel: &CancelToken) -> Result<CodexAdapter> {
+    let root = std::env::current_dir().context("failed to resolve working directory")?;
+    let prompts = PromptBuilder::for_project(&root, &config.settings)
+        .context("failed to load prompt templates")?;
+    let registry = ProviderRegistry::builtin();
+    let provider = registry.select(cli.provider.as_deref(), &config.settings)?;
+    let network = NetworkConfig::from_settings(&config.settings);
+    let mut adapter = provider
+        .adapter_with_auth(&config.auth)?
+        .with_network(&network)?
+        .with_extra_headers(&config.settings.request_headers())?;
+    if let Some(model) = cli.model.as_deref().or(config.settings.model.as_deref()) {
+        adapter = adapter.with_model(model);
+    }
+    if let Some(secs) = config.settings.max_retry_after_secs {
+        adapter = adapter.with_max_retry_after(std::time::Duration::from_secs(secs));
+    }
+    Ok(adapter
+        .with_scheduler(SCHEDULER.clone())
+        .with_cancel_token(cancel.clone())
+        .with_run_id_scheme(config.settings.run_id_scheme)
+        .with_context_budget(config.settings.context_token_budget)
+        .with_show_thinking(!config.settings.hide_thinking)
+        .with_rationale_followup(config.settings.rationale_followup)
+        .with_tool_calling(config.settings.tool_calling)
+        .with_budget(RunBudget::from_settings(&config.settings))
+        .with_redactor(Redactor::from_settings(&config.settings)?)
+        .with_patch_scanner(PatchScanner::from_settings(&config.settings))
+        .with_prompt_builder(prompts))
+}
+
+/// Builds the redactor for the event logs of commands that call no model:
+/// the `redact_patterns` of settings plus the selected provider's key from
+/// its environment variable. Commands with an adapter use its
+/// [`CodexAdapter::redactor`], which masks the key it resolved.
+fn build_redactor(cli: &Cli, config: &NexusConfig) -> Result<Redactor> {
+    let redactor = Redactor::from_settings(&config.settings)?;
+    Ok(with_env_api_key(redactor, cli, &config.settings))
+}
+
+/// Builds the redactor for error messages like [`build_redactor`], with the
+/// default settings when the configuration itself fails. The credential
+/// chain is not consulted, so a failing `api_key_command` is not rerun.
+fn error_redactor(cli: &Cli) -> Redactor {
+    let settings = load_config(cli)
+        .map(|config| config.settings)
+        .unwrap_or_default();
+    let redactor = Redactor::from_settings(&settings).unwrap_or_else(|_| Redactor::new());
+    with_env_api_key(redactor, cli, &settings)
+}
+
+/// Adds the selected provider's key to `redactor`, when its environment
+/// variable holds one.
+fn with_env_api_key(redactor: Redactor, cli: &Cli, settings: &NexusSettings) -> Redactor {
+    let key = ProviderRegistry::builtin()
+        .select(cli.provider.as_deref(), settings)
+        .and_then(|provider| provider.api_key());
+    match key {
+        Ok(key) => redactor.with_secret(key.expose_secret()),
+        Err(_) => redactor,
+    }
+}
+
+/// Builds the adapter of an agent role other than the executor, e.g. the
+/// planner or the reviewer runs are handed over to, answering with
+/// `system_prompt`. Their replies are plans and reviews rather than
+/// actions, so they never answer through the `propose_actions` tool.
+fn build_role_adapter(
+    cli: &Cli,
+    config: &NexusConfig,
+    cancel: &CancelToken,
+    system_prompt: &str,
+) -> Result<CodexAdapter> {
+    Ok(build_adapter(cli, config, cancel)?
+        .with_tool_calling(false)
+        .with_prompt_builder(PromptBuilder::new().with_system_prompt(system_prompt)))
+}
+
+/// Prints `value` as one line of JSON, for `--output-format json`.
+fn print_json<T: serde::Serialize + ?Sized>(value: &T) -> Result<()> {
+    println!("{}", serde_json::to_string(value)?);
+    Ok(())
+}
+
+/// Reports finished runs to the CI system and returns the step's exit code.
+fn report_ci(mode: CiMode, outcomes: &[BatchOutcome]) -> Result<u8> {
+    match mode {
+        CiMode::Github => {
+            let code = GithubReporter::from_env().report(outcomes, &mut std::io::stdout())?;
+            Ok(code)
+        }
+    }
+}
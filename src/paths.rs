@@ -0,0 +1,86 @@
+//! Platform-neutral handling of repository-relative paths.
+//!
+//! Paths coming from model output, settings, and the filesystem are
+//! normalized to forward slashes before comparison so policy matching and
+//! diff targets behave the same on Windows and Unix.
+
+use std::path::Path;
+
+/// Converts Windows-style separators to `/` and collapses `./` prefixes.
+///
+/// # Examples
+///
+/// ```
+/// use nexus::paths::normalize_separators;
+///
+/// assert_eq!(normalize_separators("src\\lib.rs"), "src/lib.rs");
+/// assert_eq!(normalize_separators("./src/lib.rs"), "src/lib.rs");
+/// ```
+pub fn normalize_separators(path: &str) -> String {
+    let normalized = path.replace('\\', "/");
+    let mut trimmed = normalized.as_str();
+    while let Some(rest) = trimmed.strip_prefix("./") {
+        trimmed = rest;
+    }
+    trimmed.to_string()
+}
+
+/// Renders a filesystem path relative to `root` using forward slashes.
+///
+/// Returns `None` if `path` is not inside `root`.
+pub fn to_repo_relative(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    let parts: Vec<_> = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    Some(parts.join("/"))
+}
+
+/// Returns true for absolute paths on either platform (`/x`, `C:\x`, `C:/x`, `\\server`).
+pub fn is_absolute_any_platform(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    path.starts_with('/')
+        || path.starts_with('\\')
+        || (bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_separators() {
+        assert_eq!(
+            normalize_separators("src\\nested\\mod.rs"),
+            "src/nested/mod.rs"
+        );
+        assert_eq!(normalize_separators(".\\src\\lib.rs"), "src/lib.rs");
+        assert_eq!(normalize_separators("src/lib.rs"), "src/lib.rs");
+    }
+
+    #[test]
+    fn test_to_repo_relative() {
+        let root = Path::new("project");
+        let path = root.join("src").join("lib.rs");
+        assert_eq!(to_repo_relative(root, &path).as_deref(), Some("src/lib.rs"));
+        assert!(to_repo_relative(root, Path::new("other/lib.rs")).is_none());
+    }
+
+    #[test]
+    fn test_is_absolute_any_platform() {
+        assert!(is_absolute_any_platform("/etc/passwd"));
+        assert!(is_absolute_any_platform("C:\\Windows"));
+        assert!(is_absolute_any_platform("d:/data"));
+        assert!(is_absolute_any_platform("\\\\server\\share"));
+        assert!(!is_absolute_any_platform("src/lib.rs"));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_to_repo_relative_uses_forward_slashes_on_windows() {
+        let root = Path::new("C:\\project");
+        let path = Path::new("C:\\project\\src\\lib.rs");
+        assert_eq!(to_repo_relative(root, path).as_deref(), Some("src/lib.rs"));
+    }
+}
@@ -0,0 +1,257 @@
+//! Step plans: a planner agent breaking a task down before execution.
+//!
+//! When a run plans, a Planner-role adapter answers the task with
+//! [`PLANNER_SYSTEM_PROMPT`] and its reply is read into a [`Plan`] of
+//! ordered steps. The plan is stored as `.nexus/runs/<run_id>/plan.json`
+//! and logged as `plan.created`; the executor then works on one step at a
+//! time ([`Plan::step_task`]), each finished step logged as
+//! `plan.step.completed`.
+//!
+//! Stored plans are kept by ID in a [`PlanStore`]; a run's plan has the
+//! run's ID. `plan_patch` actions replace or edit them; see [`patch`].
+
+pub mod patch;
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::NexusError;
+use crate::event_log::payload::sha256_hex;
+use crate::types::{PayloadRef, RunId};
+
+/// File the plan of a run is stored in, under `.nexus/runs/<run_id>/`.
+pub const PLAN_FILE: &str = "plan.json";
+
+/// Most steps a plan may have; a planner's later steps are dropped.
+pub const MAX_PLAN_STEPS: usize = 12;
+
+/// System prompt of the planner breaking a task down.
+pub const PLANNER_SYSTEM_PROMPT: &str = r#"You are an expert software planner. Break the user's task down into a short list of ordered steps that another agent will carry out one at a time.
+
+IMPORTANT RULES:
+1. Each step must be a self-contained change that can be made and reviewed on its own
+2. Order the steps so each one only depends on earlier ones
+3. Use as few steps as the task needs; a small task may need just one
+4. Use the exact file paths provided
+5. Do not write the code changes themselves
+
+OUTPUT FORMAT:
+Reply with one JSON object and nothing else:
+{"steps": [{"title": "<what the step does>", "detail": "<files, functions and notes for the step>"}]}
+"#;
+
+/// Ordered steps for a task.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Plan {
+    pub task: String,
+    pub steps: Vec<PlanStep>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlanStep {
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PlanReply {
+    steps: Vec<PlanStep>,
+}
+
+impl Plan {
+    /// Reads the plan for `task` from the planner's `reply`.
+    ///
+    /// # Errors
+    /// - `NexusError::ResponseParseFailed` if the reply holds no JSON object
+    ///   or the plan has no steps
+    /// - `NexusError::JsonError` if the object is not a plan
+    pub fn parse(task: &str, reply: &str) -> Result<Self, NexusError> {
+        let parse_failed = |context: &str| NexusError::ResponseParseFailed {
+            context: context.to_string(),
+            raw_response: Some(reply.to_string()),
+        };
+        let json = match (reply.find('{'), reply.rfind('}')) {
+            (Some(start), Some(end)) if start < end => &reply[start..=end],
+            _ => return Err(parse_failed("plan response contains no JSON object")),
+        };
+        let PlanReply { mut steps } = serde_json::from_str(json)?;
+        steps.retain(|step| !step.title.trim().is_empty());
+        if steps.is_empty() {
+            return Err(parse_failed("plan has no steps"));
+        }
+        if steps.len() > MAX_PLAN_STEPS {
+            log::warn!(
+                "plan has {} steps; keeping the first {MAX_PLAN_STEPS}",
+                steps.len()
+            );
+            steps.truncate(MAX_PLAN_STEPS);
+        }
+        Ok(Self {
+            task: task.to_string(),
+            steps,
+        })
+    }
+
+    /// Task given to the executor for step `index` (from 1), after the
+    /// changes rendered in `proposed` (see
+    /// [`crate::preview::render_dry_run`]) were proposed for earlier steps.
+    pub fn step_task(&self, index: usize, proposed: &str) -> String {
+        let mut out = format!("{}\n\nPlan:\n", self.task);
+        for (number, step) in self.steps.iter().enumerate() {
+            let _ = writeln!(out, "{}. {}", number + 1, step.title);
+        }
+        if !proposed.is_empty() {
+            out.push_str("\nChanges proposed for earlier steps:\n");
+            out.push_str(proposed);
+        }
+        let step = &self.steps[index - 1];
+        let _ = write!(out, "\nCarry out step {index} only: {}", step.title);
+        if let Some(detail) = &step.detail {
+            let _ = write!(out, "\n{detail}");
+        }
+        out.push('\n');
+        out
+    }
+}
+
+/// Path of plan `plan_id` relative to the project root.
+///
+/// # Errors
+/// - `NexusError::InvalidRunId` if `plan_id` is not a valid run ID
+pub fn plan_path(plan_id: &str) -> Result<String, NexusError> {
+    let plan_id = RunId::new(plan_id)?;
+    Ok(format!(".nexus/runs/{plan_id}/{PLAN_FILE}"))
+}
+
+/// Stored plans by ID, each in `.nexus/runs/<plan_id>/plan.json`.
+#[derive(Debug, Clone)]
+pub struct PlanStore {
+    runs_dir: PathBuf,
+}
+
+impl PlanStore {
+    /// Plans of the project at `root`.
+    pub fn new(root: &Path) -> Self {
+        Self {
+            runs_dir: root.join(".nexus").join("runs"),
+        }
+    }
+
+    /// Plans kept next to the run log at `log_path`.
+    pub fn for_log(log_path: &Path) -> Self {
+        Self {
+            runs_dir: log_path
+                .parent()
+                .unwrap_or_else(|| Path::new("."))
+                .to_path_buf(),
+        }
+    }
+
+    /// File plan `plan_id` is stored in.
+    ///
+    /// # Errors
+    /// - `NexusError::InvalidRunId` if `plan_id` is not a valid run ID
+    pub fn path(&self, plan_id: &str) -> Result<PathBuf, NexusError> {
+        let plan_id = RunId::new(plan_id)?;
+        Ok(self.runs_dir.join(plan_id.as_str()).join(PLAN_FILE))
+    }
+
+    /// Plan `plan_id`, or `None` if none is stored.
+    pub fn load(&self, plan_id: &str) -> Result<Option<Plan>, NexusError> {
+        let path = self.path(plan_id)?;
+        match std::fs::read(&path) {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(NexusError::IoError {
+                operation: "read plan".to_string(),
+                path,
+                source: err,
+            }),
+        }
+    }
+
+    /// Stores `plan` as plan `plan_id` and returns a reference to it.
+    pub fn save(&self, plan_id: &str, plan: &Plan) -> Result<PayloadRef, NexusError> {
+        let path = self.path(plan_id)?;
+        let bytes = serde_json::to_vec_pretty(plan)?;
+        let io_error = |path: &Path, err| NexusError::IoError {
+            operation: "write plan".to_string(),
+            path: path.to_path_buf(),
+            source: err,
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|err| io_error(dir, err))?;
+        }
+        std::fs::write(&path, &bytes).map_err(|err| io_error(&path, err))?;
+        Ok(PayloadRef {
+            uri: format!("{}/{PLAN_FILE}", RunId::new(plan_id)?),
+            mime: Some("application/json".to_string()),
+            sha256: Some(sha256_hex(&bytes)),
+            size_bytes: Some(bytes.len() as u64),
+            label: Some("plan".to_string()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_log::PayloadStore;
+
+    const REPLY: &str = r#"Here is the plan:
+{"steps": [
+  {"title": "Add the helper", "detail": "in src/util.rs"},
+  {"title": "Call it from main"},
+  {"title": "  "}
+]}"#;
+
+    #[test]
+    fn test_parse_plan_reply() {
+        let plan = Plan::parse("tidy up", REPLY).unwrap();
+        assert_eq!(plan.task, "tidy up");
+        assert_eq!(plan.steps.len(), 2);
+        assert_eq!(plan.steps[0].detail.as_deref(), Some("in src/util.rs"));
+        assert_eq!(plan.steps[1].detail, None);
+
+        assert!(matches!(
+            Plan::parse("tidy up", "no plan here"),
+            Err(NexusError::ResponseParseFailed { .. })
+        ));
+        assert!(matches!(
+            Plan::parse("tidy up", r#"{"steps": []}"#),
+            Err(NexusError::ResponseParseFailed { .. })
+        ));
+    }
+
+    #[test]
+    fn test_step_task_names_the_step() {
+        let plan = Plan::parse("tidy up", REPLY).unwrap();
+        assert_eq!(
+            plan.step_task(1, ""),
+            "tidy up\n\nPlan:\n1. Add the helper\n2. Call it from main\n\nCarry out step 1 only: Add the helper\nin src/util.rs\n"
+        );
+        let task = plan.step_task(2, "act_1 diff\n");
+        assert!(task.contains("\nChanges proposed for earlier steps:\nact_1 diff\n"));
+        assert!(task.ends_with("Carry out step 2 only: Call it from main\n"));
+    }
+
+    #[test]
+    fn test_store_plan_next_to_the_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let log_path = dir.path().join("run_1.jsonl");
+        let plan = Plan::parse("tidy up", REPLY).unwrap();
+        let store = PlanStore::for_log(&log_path);
+
+        let payload_ref = store.save("run_1", &plan).unwrap();
+        assert_eq!(payload_ref.uri, "run_1/plan.json");
+        let bytes = PayloadStore::read(dir.path(), &payload_ref).unwrap();
+        assert_eq!(serde_json::from_slice::<Plan>(&bytes).unwrap(), plan);
+        assert_eq!(store.load("run_1").unwrap(), Some(plan));
+        assert_eq!(store.load("run_2").unwrap(), None);
+        assert!(store.load("../run_1").is_err());
+        assert_eq!(plan_path("run_1").unwrap(), ".nexus/runs/run_1/plan.json");
+    }
+}
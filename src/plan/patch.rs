@@ -0,0 +1,310 @@
+//! Plan patches: replacing a stored plan or editing it with JSON Patch.
+//!
+//! A `plan_patch` action names a stored plan by ID and a JSON document by
+//! `patch_ref`. With `patch_mode: replace` the document is the new plan;
+//! with `json_patch` it is a list of RFC 6902 operations (`add`, `remove`,
+//! `replace`, `move`, `copy`, `test`) applied to the plan's JSON. The
+//! operations apply all or nothing, and the result must still be a plan of
+//! 1 to [`MAX_PLAN_STEPS`] steps. The [`Applier`](crate::apply::Applier)
+//! writes the patched plan, and applying the run logs `plan.patched`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::types::PatchMode;
+
+use super::{MAX_PLAN_STEPS, Plan};
+
+/// Prefix of a `patch_ref` pointing into `.nexus/runs/`.
+pub const RUN_ARTIFACT_PREFIX: &str = "artifact://runs/";
+
+/// One RFC 6902 operation; paths are JSON Pointers (RFC 6901).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+    Move { from: String, path: String },
+    Copy { from: String, path: String },
+    Test { path: String, value: Value },
+}
+
+/// Path of the document `patch_ref` names, relative to the project root:
+/// `artifact://runs/<path>` is `.nexus/runs/<path>`, anything else is
+/// taken as relative to the root already.
+pub fn patch_ref_path(patch_ref: &str) -> String {
+    match patch_ref.strip_prefix(RUN_ARTIFACT_PREFIX) {
+        Some(path) => format!(".nexus/runs/{path}"),
+        None => patch_ref.to_string(),
+    }
+}
+
+/// The plan stored as `plan` once the `patch` document is applied in
+/// `mode`.
+///
+/// # Errors
+/// Why the patch does not apply, or what is wrong with the plan it leads
+/// to.
+pub fn patch_plan(plan: &str, patch: &str, mode: &PatchMode) -> Result<Plan, String> {
+    let patched: Plan = match mode {
+        PatchMode::Replace => serde_json::from_str(patch)
+            .map_err(|err| format!("replacement is not a plan: {err}"))?,
+        PatchMode::JsonPatch => {
+            let operations: Vec<PatchOperation> = serde_json::from_str(patch)
+                .map_err(|err| format!("not a JSON Patch document: {err}"))?;
+            let mut document: Value = serde_json::from_str(plan)
+                .map_err(|err| format!("stored plan is not JSON: {err}"))?;
+            apply_json_patch(&mut document, &operations)?;
+            serde_json::from_value(document)
+                .map_err(|err| format!("patched plan is not a plan: {err}"))?
+        }
+    };
+    if patched.steps.is_empty() {
+        return Err("plan has no steps".to_string());
+    }
+    if patched.steps.len() > MAX_PLAN_STEPS {
+        return Err(format!("plan has more than {MAX_PLAN_STEPS} steps"));
+    }
+    if let Some(index) = patched
+        .steps
+        .iter()
+        .position(|step| step.title.trim().is_empty())
+    {
+        return Err(format!("step {} has no title", index + 1));
+    }
+    Ok(patched)
+}
+
+/// Applies `operations` to `document` in order. If one fails, `document`
+/// is left as it was.
+///
+/// # Errors
+/// The index of the failing operation and why it failed.
+pub fn apply_json_patch(document: &mut Value, operations: &[PatchOperation]) -> Result<(), String> {
+    let mut patched = document.clone();
+    for (index, operation) in operations.iter().enumerate() {
+        apply_operation(&mut patched, operation)
+            .map_err(|reason| format!("operation {index}: {reason}"))?;
+    }
+    *document = patched;
+    Ok(())
+}
+
+fn apply_operation(document: &mut Value, operation: &PatchOperation) -> Result<(), String> {
+    match operation {
+        PatchOperation::Add { path, value } => add(document, path, value.clone()),
+        PatchOperation::Remove { path } => remove(document, path).map(drop),
+        PatchOperation::Replace { path, value } => {
+            *target(document, path)? = value.clone();
+            Ok(())
+        }
+        PatchOperation::Move { from, path } => {
+            if path.starts_with(&format!("{from}/")) {
+                return Err(format!("cannot move \"{from}\" into itself"));
+            }
+            if path == from {
+                return target(document, from).map(drop);
+            }
+            let value = remove(document, from)?;
+            add(document, path, value)
+        }
+        PatchOperation::Copy { from, path } => {
+            let value = target(document, from)?.clone();
+            add(document, path, value)
+        }
+        PatchOperation::Test { path, value } => {
+            if *target(document, path)? == *value {
+                Ok(())
+            } else {
+                Err(format!("value at \"{path}\" differs"))
+            }
+        }
+    }
+}
+
+fn add(document: &mut Value, pointer: &str, value: Value) -> Result<(), String> {
+    let mut tokens = tokens(pointer)?;
+    let Some(last) = tokens.pop() else {
+        *document = value;
+        return Ok(());
+    };
+    match walk(document, &tokens, pointer)? {
+        Value::Object(map) => {
+            map.insert(last, value);
+            Ok(())
+        }
+        Value::Array(items) => {
+            let index = if last == "-" {
+                items.len()
+            } else {
+                array_index(&last)
+                    .filter(|index| *index <= items.len())
+                    .ok_or_else(|| missing(pointer))?
+            };
+            items.insert(index, value);
+            Ok(())
+        }
+        _ => Err(missing(pointer)),
+    }
+}
+
+fn remove(document: &mut Value, pointer: &str) -> Result<Value, String> {
+    let mut tokens = tokens(pointer)?;
+    let Some(last) = tokens.pop() else {
+        return Err("cannot remove the whole document".to_string());
+    };
+    match walk(document, &tokens, pointer)? {
+        Value::Object(map) => map.remove(&last).ok_or_else(|| missing(pointer)),
+        Value::Array(items) => match array_index(&last).filter(|index| *index < items.len()) {
+            Some(index) => Ok(items.remove(index)),
+            None => Err(missing(pointer)),
+        },
+        _ => Err(missing(pointer)),
+    }
+}
+
+/// The value `pointer` refers to, which must exist.
+fn target<'a>(document: &'a mut Value, pointer: &str) -> Result<&'a mut Value, String> {
+    walk(document, &tokens(pointer)?, pointer)
+}
+
+fn walk<'a>(
+    document: &'a mut Value,
+    tokens: &[String],
+    pointer: &str,
+) -> Result<&'a mut Value, String> {
+    tokens.iter().try_fold(document, |value, token| {
+        match value {
+            Value::Object(map) => map.get_mut(token),
+            Value::Array(items) => array_index(token).and_then(|index| items.get_mut(index)),
+            _ => None,
+        }
+        .ok_or_else(|| missing(pointer))
+    })
+}
+
+/// Reference tokens of the JSON Pointer `pointer`, unescaped.
+fn tokens(pointer: &str) -> Result<Vec<String>, String> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(rest) = pointer.strip_prefix('/') else {
+        return Err(format!("\"{pointer}\" is not a JSON Pointer"));
+    };
+    Ok(rest
+        .split('/')
+        .map(|token| token.replace("~1", "/").replace("~0", "~"))
+        .collect())
+}
+
+/// An array index token: digits without leading zeros.
+fn array_index(token: &str) -> Option<usize> {
+    let digits = !token.is_empty() && token.bytes().all(|byte| byte.is_ascii_digit());
+    if !digits || (token.len() > 1 && token.starts_with('0')) {
+        return None;
+    }
+    token.parse().ok()
+}
+
+fn missing(pointer: &str) -> String {
+    format!("no value at \"{pointer}\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn patched(document: Value, operations: Value) -> Result<Value, String> {
+        let operations: Vec<PatchOperation> = serde_json::from_value(operations).unwrap();
+        let mut document = document;
+        apply_json_patch(&mut document, &operations).map(|()| document)
+    }
+
+    #[test]
+    fn test_json_patch_operations() {
+        let document = json!({"foo": ["bar", "baz"], "a/b": {"~c": 1}});
+        let result = patched(
+            document,
+            json!([
+                {"op": "add", "path": "/foo/1", "value": "qux"},
+                {"op": "add", "path": "/foo/-", "value": "end"},
+                {"op": "remove", "path": "/foo/0"},
+                {"op": "replace", "path": "/a~1b/~0c", "value": 2},
+                {"op": "copy", "from": "/a~1b", "path": "/copied"},
+                {"op": "move", "from": "/foo/2", "path": "/last"},
+                {"op": "test", "path": "/foo", "value": ["qux", "baz"]}
+            ]),
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            json!({
+                "foo": ["qux", "baz"],
+                "a/b": {"~c": 2},
+                "copied": {"~c": 2},
+                "last": "end"
+            })
+        );
+    }
+
+    #[test]
+    fn test_json_patch_is_all_or_nothing() {
+        let operations: Vec<PatchOperation> = serde_json::from_value(json!([
+            {"op": "add", "path": "/b", "value": 2},
+            {"op": "test", "path": "/a", "value": 2}
+        ]))
+        .unwrap();
+        let mut document = json!({"a": 1});
+        let err = apply_json_patch(&mut document, &operations).unwrap_err();
+        assert_eq!(err, "operation 1: value at \"/a\" differs");
+        assert_eq!(document, json!({"a": 1}));
+
+        for (operations, reason) in [
+            (
+                json!([{"op": "remove", "path": "/x"}]),
+                "no value at \"/x\"",
+            ),
+            (
+                json!([{"op": "add", "path": "/a/01", "value": 0}]),
+                "no value at \"/a/01\"",
+            ),
+            (
+                json!([{"op": "move", "from": "/a", "path": "/a/b"}]),
+                "cannot move \"/a\" into itself",
+            ),
+            (
+                json!([{"op": "replace", "path": "a", "value": 0}]),
+                "\"a\" is not a JSON Pointer",
+            ),
+        ] {
+            let err = patched(json!({"a": []}), operations).unwrap_err();
+            assert_eq!(err, format!("operation 0: {reason}"));
+        }
+    }
+
+    #[test]
+    fn test_patch_plan_in_both_modes() {
+        let plan = r#"{"task": "tidy up", "steps": [{"title": "Add the helper"}]}"#;
+        let patch = r#"[{"op": "add", "path": "/steps/-", "value": {"title": "Call it"}}]"#;
+        let patched = patch_plan(plan, patch, &PatchMode::JsonPatch).unwrap();
+        assert_eq!(patched.steps.len(), 2);
+        assert_eq!(patched.steps[1].title, "Call it");
+
+        let replacement = r#"{"task": "tidy up", "steps": [{"title": "Do it all"}]}"#;
+        let replaced = patch_plan(plan, replacement, &PatchMode::Replace).unwrap();
+        assert_eq!(replaced.steps[0].title, "Do it all");
+
+        let emptied = patch_plan(
+            plan,
+            r#"[{"op": "remove", "path": "/steps/0"}]"#,
+            &PatchMode::JsonPatch,
+        );
+        assert_eq!(emptied.unwrap_err(), "plan has no steps");
+        assert_eq!(
+            patch_ref_path("artifact://runs/run_1/artifacts/patch.json"),
+            ".nexus/runs/run_1/artifacts/patch.json"
+        );
+    }
+}
@@ -0,0 +1,167 @@
+//! Command policy: which command actions may run.
+//!
+//! `allow_commands`, `ask_commands`, and `deny_commands` hold argv
+//! prefixes. The longest prefix matching a command's argv decides, so
+//! `["git", "push"]` in `deny_commands` overrides `["git"]` in
+//! `allow_commands`. When prefixes of the same length in different lists
+//! match, deny wins over ask and ask over allow.
+
+use crate::types::{CommandDetails, Decision, NexusSettings};
+
+use super::{PolicyDecision, precedence};
+
+/// Argv-prefix rules for command actions.
+#[derive(Debug, Clone, Default)]
+pub struct CommandPolicy {
+    rules: Vec<CommandRule>,
+}
+
+#[derive(Debug, Clone)]
+struct CommandRule {
+    prefix: Vec<String>,
+    decision: Decision,
+    /// Settings field and index the rule came from, for reasons.
+    source: String,
+}
+
+impl CommandPolicy {
+    pub fn from_settings(settings: &NexusSettings) -> Self {
+        let mut rules = Vec::new();
+        for (field, decision, prefixes) in [
+            ("allow_commands", Decision::Allow, &settings.allow_commands),
+            ("ask_commands", Decision::Ask, &settings.ask_commands),
+            ("deny_commands", Decision::Deny, &settings.deny_commands),
+        ] {
+            for (index, prefix) in prefixes.iter().enumerate() {
+                if prefix.is_empty() {
+                    continue;
+                }
+                rules.push(CommandRule {
+                    prefix: prefix.clone(),
+                    decision,
+                    source: format!("{field}[{index}]"),
+                });
+            }
+        }
+        Self { rules }
+    }
+
+    /// Classifies `argv` by the longest matching prefix, or `None` if no
+    /// prefix matches.
+    pub fn classify(&self, argv: &[String]) -> Option<PolicyDecision> {
+        let best = self
+            .rules
+            .iter()
+            .filter(|rule| matches_prefix(argv, &rule.prefix))
+            .max_by_key(|rule| (rule.prefix.len(), precedence(rule.decision)))?;
+        Some(PolicyDecision::new(
+            best.decision,
+            format!("`{}` matches {}", best.prefix.join(" "), best.source),
+        ))
+    }
+
+    /// Classifies a command action's argv.
+    pub fn check(&self, details: &CommandDetails) -> Option<PolicyDecision> {
+        self.classify(&details.argv)
+    }
+}
+
+/// True if `argv` starts with `prefix`. The program may also be named by
+/// path, so `/bin/rm` matches a prefix of `rm`.
+fn matches_prefix(argv: &[String], prefix: &[String]) -> bool {
+    let (Some((program, args)), Some((wanted, wanted_args))) =
+        (argv.split_first(), prefix.split_first())
+    else {
+        return false;
+    };
+    let program_matches = program == wanted
+        || (!wanted.contains(['/', '\\']) && program_name(program) == wanted.as_str());
+    program_matches && args.starts_with(wanted_args)
+}
+
+fn program_name(program: &str) -> &str {
+    let name = program.rsplit(['/', '\\']).next().unwrap_or(program);
+    name.strip_suffix(".exe").unwrap_or(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn argv(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    fn policy(allow: &[&[&str]], ask: &[&[&str]], deny: &[&[&str]]) -> CommandPolicy {
+        let lists = |prefixes: &[&[&str]]| prefixes.iter().map(|prefix| argv(prefix)).collect();
+        CommandPolicy::from_settings(&NexusSettings {
+            allow_commands: lists(allow),
+            ask_commands: lists(ask),
+            deny_commands: lists(deny),
+            ..NexusSettings::default()
+        })
+    }
+
+    fn decision(policy: &CommandPolicy, args: &[&str]) -> Option<Decision> {
+        policy
+            .classify(&argv(args))
+            .map(|decision| decision.decision)
+    }
+
+    #[test]
+    fn test_longest_prefix_wins() {
+        let policy = policy(
+            &[&["git"], &["git", "push", "--dry-run"]],
+            &[&["git", "commit"]],
+            &[&["git", "push"]],
+        );
+
+        assert_eq!(decision(&policy, &["git", "status"]), Some(Decision::Allow));
+        assert_eq!(
+            decision(&policy, &["git", "commit", "-m", "x"]),
+            Some(Decision::Ask)
+        );
+        assert_eq!(
+            decision(&policy, &["git", "push", "origin"]),
+            Some(Decision::Deny)
+        );
+        assert_eq!(
+            decision(&policy, &["git", "push", "--dry-run", "origin"]),
+            Some(Decision::Allow)
+        );
+
+        let reason = policy.classify(&argv(&["git", "push"])).unwrap().reason;
+        assert_eq!(reason, "`git push` matches deny_commands[0]");
+    }
+
+    #[test]
+    fn test_equal_length_overlap_takes_strictest() {
+        let cargo = policy(&[&["cargo", "test"]], &[&["cargo", "test"]], &[]);
+        assert_eq!(decision(&cargo, &["cargo", "test"]), Some(Decision::Ask));
+
+        let rm = policy(&[&["rm"]], &[&["rm"]], &[&["rm"]]);
+        assert_eq!(
+            decision(&rm, &["rm", "-rf", "target"]),
+            Some(Decision::Deny)
+        );
+    }
+
+    #[test]
+    fn test_prefix_must_match_whole_arguments() {
+        let policy = policy(&[&["cargo", "test"]], &[], &[]);
+        assert_eq!(decision(&policy, &["cargo", "testify"]), None);
+        assert_eq!(decision(&policy, &["cargo"]), None);
+        assert_eq!(decision(&policy, &[]), None);
+    }
+
+    #[test]
+    fn test_program_matches_by_name() {
+        let policy = policy(&[], &[], &[&["rm"], &["sudo"]]);
+        assert_eq!(decision(&policy, &["/bin/rm", "x"]), Some(Decision::Deny));
+        assert_eq!(
+            decision(&policy, &["C:\\tools\\sudo.exe"]),
+            Some(Decision::Deny)
+        );
+        assert_eq!(decision(&policy, &["rmdir", "x"]), None);
+    }
+}
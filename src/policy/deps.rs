@@ -0,0 +1,151 @@
+//! Elevated handling for edits to dependency manifests and lockfiles.
+//!
+//! Such edits are tagged `deps` and raised to at least [`DEPS_RISK`]. The
+//! permission gate only auto-allows them after the configured
+//! `deps_audit_command` (e.g. `cargo deny check`) passes.
+
+use std::path::Path;
+use std::process::Command;
+
+use crate::error::NexusError;
+use crate::types::ProposedAction;
+
+use super::touched_paths;
+
+/// Policy tag added to actions that edit dependency manifests.
+pub const DEPS_TAG: &str = "deps";
+/// Minimum risk of an action that edits dependency manifests.
+pub const DEPS_RISK: u8 = 2;
+
+/// File names of package manifests and lockfiles across ecosystems.
+const MANIFEST_NAMES: &[&str] = &[
+    "Cargo.toml",
+    "Cargo.lock",
+    "package.json",
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "go.mod",
+    "go.sum",
+    "pyproject.toml",
+    "requirements.txt",
+    "Pipfile",
+    "Pipfile.lock",
+    "poetry.lock",
+    "uv.lock",
+    "Gemfile",
+    "Gemfile.lock",
+    "composer.json",
+    "composer.lock",
+    "pom.xml",
+    "build.gradle",
+    "build.gradle.kts",
+];
+
+/// True if `path` names a dependency manifest or lockfile.
+pub fn is_dependency_manifest(path: &str) -> bool {
+    let name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    MANIFEST_NAMES.contains(&name)
+}
+
+/// Tags `action` and raises its risk if it touches a dependency manifest.
+///
+/// Returns the manifests touched.
+pub fn flag_dependency_edits(action: &mut ProposedAction) -> Vec<String> {
+    let manifests: Vec<String> = touched_paths(action)
+        .into_iter()
+        .filter(|path| is_dependency_manifest(path))
+        .collect();
+    if !manifests.is_empty() {
+        if !action.policy_tags.iter().any(|tag| tag == DEPS_TAG) {
+            action.policy_tags.push(DEPS_TAG.to_string());
+        }
+        action.risk = action.risk.max(DEPS_RISK);
+    }
+    manifests
+}
+
+/// Result of running the dependency audit command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditOutcome {
+    pub success: bool,
+    pub exit_code: Option<i32>,
+}
+
+/// Runs `argv` in `root` without a shell.
+pub fn run_audit(argv: &[String], root: &Path) -> Result<AuditOutcome, NexusError> {
+    let (program, args) = argv.split_first().ok_or_else(|| NexusError::ConfigError {
+        message: "deps_audit_command is empty".to_string(),
+        path: None,
+        source: None,
+    })?;
+    let output = Command::new(program)
+        .args(args)
+        .current_dir(root)
+        .output()
+        .map_err(|err| NexusError::IoError {
+            operation: format!("run deps audit `{}`", argv.join(" ")),
+            path: root.to_path_buf(),
+            source: err,
+        })?;
+    if !output.status.success() {
+        log::warn!(
+            "deps audit `{}` failed:\n{}",
+            argv.join(" "),
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(AuditOutcome {
+        success: output.status.success(),
+        exit_code: output.status.code(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patch(files: &[&str]) -> ProposedAction {
+        ProposedAction::test_patch("act_1", files)
+    }
+
+    #[test]
+    fn test_is_dependency_manifest() {
+        assert!(is_dependency_manifest("Cargo.toml"));
+        assert!(is_dependency_manifest("web/package.json"));
+        assert!(is_dependency_manifest("svc\\go.mod"));
+        assert!(!is_dependency_manifest("src/package.rs"));
+        assert!(!is_dependency_manifest("docs/Cargo.toml.md"));
+    }
+
+    #[test]
+    fn test_flag_dependency_edits() {
+        let mut action = patch(&["src/lib.rs", "crates/a/Cargo.toml"]);
+        assert_eq!(flag_dependency_edits(&mut action), ["crates/a/Cargo.toml"]);
+        assert_eq!(action.risk, DEPS_RISK);
+        assert_eq!(action.policy_tags, [DEPS_TAG]);
+
+        let mut action = patch(&["src/lib.rs"]);
+        assert!(flag_dependency_edits(&mut action).is_empty());
+        assert_eq!(action.risk, 1);
+        assert!(action.policy_tags.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_audit() {
+        let dir = tempfile::tempdir().unwrap();
+        let passed = run_audit(&["true".to_string()], dir.path()).unwrap();
+        assert!(passed.success);
+        let failed = run_audit(&["false".to_string()], dir.path()).unwrap();
+        assert_eq!(
+            failed,
+            AuditOutcome {
+                success: false,
+                exit_code: Some(1)
+            }
+        );
+        assert!(run_audit(&[], dir.path()).is_err());
+    }
+}
@@ -0,0 +1,552 @@
+//! Permission Gate: decides whether a proposed action may run.
+//!
+//! Evaluation happens before any interactive prompt. The [`PathPolicy`] and,
+//! for commands, the [`CommandPolicy`] are checked first, then every matching `approval_rules` entry
+//! and every `policy_tag_rules` entry for one of the action's tags contributes a
+//! decision with the precedence from `.nexus/policy.md`: deny wins, ask
+//! overrides allow. A `notify` tag rule allows, and marks the decision so
+//! the user is told. Actions no rule matches fall back to ask, unless the
+//! gate is in autopilot mode and the `autopilot` settings approve them; see
+//! [`crate::autopilot::auto_approval`]. Actions the
+//! patch scanner flagged are never allowed without asking, and dependency
+//! manifest edits are only allowed once the configured audit passes.
+
+pub mod commands;
+pub mod deps;
+pub mod paths;
+pub mod scan;
+
+pub use commands::CommandPolicy;
+pub use paths::PathPolicy;
+
+use std::collections::BTreeMap;
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+
+use crate::autopilot::auto_approval;
+use crate::error::NexusError;
+use crate::paths::normalize_separators;
+use crate::types::{
+    ActionDetails, ApprovalRule, AutopilotConfig, Decision, NexusSettings, PermissionMode,
+    ProposedAction, TagDecision,
+};
+
+/// A decision together with the reason it was reached.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyDecision {
+    pub decision: Decision,
+    pub reason: String,
+    /// The decision is `Ask` only because `deps_audit_command` has not
+    /// passed yet; callers that run the audit may allow on success.
+    pub requires_audit: bool,
+    /// The action is allowed by a `notify` tag rule; the user should be
+    /// told it was.
+    pub notify: bool,
+}
+
+impl PolicyDecision {
+    pub(crate) fn new(decision: Decision, reason: impl Into<String>) -> Self {
+        Self {
+            decision,
+            reason: reason.into(),
+            requires_audit: false,
+            notify: false,
+        }
+    }
+}
+
+/// Evaluates proposed actions against the configured policy.
+#[derive(Debug, Clone)]
+pub struct PermissionGate {
+    mode: PermissionMode,
+    paths: PathPolicy,
+    commands: CommandPolicy,
+    rules: Vec<ApprovalRule>,
+    tag_rules: BTreeMap<String, TagDecision>,
+    deps_audit_command: Vec<String>,
+    /// Auto-approvals; only set in autopilot mode.
+    autopilot: Option<AutopilotConfig>,
+}
+
+impl PermissionGate {
+    pub fn from_settings(settings: &NexusSettings) -> Result<Self, NexusError> {
+        Ok(Self {
+            mode: settings.permission_mode.clone(),
+            paths: PathPolicy::from_settings(settings)?,
+            commands: CommandPolicy::from_settings(settings),
+            rules: settings.approval_rules.clone(),
+            tag_rules: settings.policy_tag_rules.clone(),
+            deps_audit_command: settings.deps_audit_command.clone(),
+            autopilot: (settings.permission_mode == PermissionMode::Autopilot)
+                .then(|| settings.autopilot.clone().unwrap_or_default()),
+        })
+    }
+
+    /// Command that must pass before dependency manifest edits are allowed.
+    pub fn deps_audit_command(&self) -> &[String] {
+        &self.deps_audit_command
+    }
+
+    /// Decides what to do with `action` before the user is asked.
+    pub fn evaluate(&self, action: &ProposedAction) -> PolicyDecision {
+        let paths = touched_paths(action);
+        let path_check = self.paths.check_paths(&paths);
+        if path_check.decision == Decision::Deny {
+            return path_check;
+        }
+        let command_check = match &action.details {
+            ActionDetails::Command(details) => self.commands.check(details),
+            _ => None,
+        };
+        match command_check {
+            Some(check) if check.decision == Decision::Deny => return check,
+            _ => {}
+        }
+
+        let mut result: Option<(Decision, String)> = None;
+        let mut weigh = |decision: Decision, reason: String| {
+            let wins = match &result {
+                None => true,
+                Some((current, _)) => precedence(decision) > precedence(*current),
+            };
+            if wins {
+                result = Some((decision, reason));
+            }
+        };
+        for (index, rule) in self.rules.iter().enumerate() {
+            if self.rule_matches(rule, action, &paths) {
+                weigh(rule.decision, format!("approval_rules[{index}]"));
+            }
+        }
+        let mut notify = false;
+        for tag in &action.policy_tags {
+            if let Some(tag_decision) = self.tag_rules.get(tag) {
+                notify |= *tag_decision == TagDecision::Notify;
+                weigh(tag_decision.decision(), format!("policy_tag_rules.{tag}"));
+            }
+        }
+
+        let flagged = action
+            .policy_tags
+            .iter()
+            .find(|tag| [scan::SECRET_TAG, scan::LICENSE_TAG].contains(&tag.as_str()));
+        let edits_deps = action.policy_tags.iter().any(|tag| tag == deps::DEPS_TAG)
+            || paths.iter().any(|path| deps::is_dependency_manifest(path));
+        // A matching command prefix decides unless a rule is stricter.
+        if flagged.is_none() && matches!(result, None | Some((Decision::Allow, _))) {
+            if let Some(check) = command_check {
+                return check;
+            }
+        }
+        match (result, flagged) {
+            (Some((Decision::Allow, _)), Some(tag)) => {
+                PolicyDecision::new(Decision::Ask, format!("flagged by {tag} scan"))
+            }
+            (Some((Decision::Allow, _)), None) if path_check.decision == Decision::Ask => {
+                path_check
+            }
+            (Some((Decision::Allow, _)), None) if edits_deps => {
+                if self.deps_audit_command.is_empty() {
+                    PolicyDecision::new(
+                        Decision::Ask,
+                        "dependency manifest edit and no deps_audit_command configured",
+                    )
+                } else {
+                    PolicyDecision {
+                        requires_audit: true,
+                        notify,
+                        ..PolicyDecision::new(
+                            Decision::Ask,
+                            "dependency manifest edit awaiting deps audit",
+                        )
+                    }
+                }
+            }
+            (Some((decision, reason)), _) => PolicyDecision {
+                notify: notify && decision == Decision::Allow,
+                ..PolicyDecision::new(decision, reason)
+            },
+            (None, None) if path_check.decision == Decision::Allow && !edits_deps => {
+                match self
+                    .autopilot
+                    .as_ref()
+                    .and_then(|config| auto_approval(config, action))
+                {
+                    Some(setting) => {
+                        PolicyDecision::new(Decision::Allow, format!("autopilot.{setting}"))
+                    }
+                    None => PolicyDecision::new(Decision::Ask, "no approval rule matched"),
+                }
+            }
+            (None, _) => PolicyDecision::new(Decision::Ask, "no approval rule matched"),
+        }
+    }
+
+    fn rule_matches(&self, rule: &ApprovalRule, action: &ProposedAction, paths: &[String]) -> bool {
+        if !rule.modes.is_empty() && !rule.modes.contains(&self.mode) {
+            return false;
+        }
+        if !rule.kinds.is_empty() && !rule.kinds.contains(&action.kind) {
+            return false;
+        }
+        if rule.min_risk.is_some_and(|min| action.risk < min) {
+            return false;
+        }
+        if rule.max_risk.is_some_and(|max| action.risk > max) {
+            return false;
+        }
+        if !rule
+            .policy_tags
+            .iter()
+            .all(|tag| action.policy_tags.contains(tag))
+        {
+            return false;
+        }
+        if rule.within_allow_paths_write
+            && (paths.is_empty() || !paths.iter().all(|p| self.paths.is_writable(p)))
+        {
+            return false;
+        }
+        true
+    }
+}
+
+fn precedence(decision: Decision) -> u8 {
+    match decision {
+        Decision::Allow => 0,
+        Decision::Ask => 1,
+        Decision::Deny => 2,
+    }
+}
+
+fn build_globset(field: &str, patterns: &[String]) -> Result<GlobSet, NexusError> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern).map_err(|err| NexusError::ConfigError {
+            message: format!("invalid {field} pattern '{pattern}': {err}"),
+            path: None,
+            source: None,
+        })?;
+        builder.add(glob);
+    }
+    builder.build().map_err(|err| NexusError::ConfigError {
+        message: format!("invalid {field}: {err}"),
+        path: None,
+        source: None,
+    })
+}
+
+/// Repository-relative paths an action reads or writes.
+pub fn touched_paths(action: &ProposedAction) -> Vec<String> {
+    let mut paths: Vec<String> = match &action.details {
+        ActionDetails::Patch(details) => {
+            let mut paths = details.files.clone();
+            if let Some(diff) = &details.diff {
+                paths.extend(diff_targets(diff));
+            }
+            for block in details.search_replace_blocks.iter().flatten() {
+                paths.push(block.file.clone());
+            }
+            if let Some(contents) = &details.whole_file_content {
+                paths.extend(contents.keys().cloned());
+            }
+            paths
+        }
+        ActionDetails::AgendaPatch(details) => vec![details.target_path.clone()],
+        ActionDetails::FileCreate(details) => vec![details.path.clone()],
+        ActionDetails::FileRename(details) => {
+            vec![details.old_path.clone(), details.new_path.clone()]
+        }
+        ActionDetails::FileDelete(details) => vec![details.path.clone()],
+        ActionDetails::Handoff(_) | ActionDetails::Command(_) | ActionDetails::PlanPatch(_) => {
+            Vec::new()
+        }
+    };
+
+    for path in &mut paths {
+        *path = normalize_separators(path);
+    }
+    paths.retain(|path| !path.is_empty());
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+fn diff_targets(diff: &str) -> Vec<String> {
+    diff.lines()
+        .filter_map(|line| {
+            line.strip_prefix("--- ")
+                .or_else(|| line.strip_prefix("+++ "))
+        })
+        .filter_map(|rest| rest.split_whitespace().next())
+        .filter(|path| *path != "/dev/null")
+        .map(|path| {
+            let path = normalize_separators(path);
+            path.strip_prefix("a/")
+                .or_else(|| path.strip_prefix("b/"))
+                .unwrap_or(&path)
+                .to_string()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ActionKindTag, CommandDetails};
+
+    fn patch(risk: u8, files: &[&str]) -> ProposedAction {
+        ProposedAction::test_patch("act_1", files).with_risk(risk)
+    }
+
+    fn rule(decision: Decision) -> ApprovalRule {
+        ApprovalRule {
+            kinds: Vec::new(),
+            min_risk: None,
+            max_risk: None,
+            policy_tags: Vec::new(),
+            within_allow_paths_write: false,
+            modes: Vec::new(),
+            decision,
+        }
+    }
+
+    fn accept_edits_settings() -> NexusSettings {
+        NexusSettings {
+            permission_mode: PermissionMode::AcceptEdits,
+            allow_paths_write: vec!["src/**".to_string()],
+            approval_rules: vec![ApprovalRule {
+                kinds: vec![ActionKindTag::Patch],
+                max_risk: Some(1),
+                within_allow_paths_write: true,
+                modes: vec![PermissionMode::AcceptEdits],
+                ..rule(Decision::Allow)
+            }],
+            ..NexusSettings::default()
+        }
+    }
+
+    #[test]
+    fn test_low_risk_patch_in_allowed_paths_is_auto_approved() {
+        let gate = PermissionGate::from_settings(&accept_edits_settings()).unwrap();
+
+        let decision = gate.evaluate(&patch(1, &["src/lib.rs"]));
+        assert_eq!(decision.decision, Decision::Allow);
+        assert_eq!(decision.reason, "approval_rules[0]");
+
+        assert_eq!(
+            gate.evaluate(&patch(2, &["src/lib.rs"])).decision,
+            Decision::Ask
+        );
+        assert_eq!(
+            gate.evaluate(&patch(1, &["build.rs"])).decision,
+            Decision::Ask
+        );
+    }
+
+    #[test]
+    fn test_rule_inactive_in_other_modes() {
+        let settings = NexusSettings {
+            permission_mode: PermissionMode::Default,
+            ..accept_edits_settings()
+        };
+        let gate = PermissionGate::from_settings(&settings).unwrap();
+        assert_eq!(
+            gate.evaluate(&patch(0, &["src/lib.rs"])).decision,
+            Decision::Ask
+        );
+    }
+
+    #[test]
+    fn test_deny_paths_and_precedence() {
+        let mut settings = accept_edits_settings();
+        settings.approval_rules.push(ApprovalRule {
+            policy_tags: vec!["migration".to_string()],
+            ..rule(Decision::Deny)
+        });
+        let gate = PermissionGate::from_settings(&settings).unwrap();
+
+        let denied = gate.evaluate(&patch(0, &[".env.local"]));
+        assert_eq!(denied.decision, Decision::Deny);
+        assert!(denied.reason.contains("deny_paths"));
+
+        let mut tagged = patch(0, &["src/db.rs"]);
+        tagged.policy_tags.push("migration".to_string());
+        assert_eq!(gate.evaluate(&tagged).decision, Decision::Deny);
+    }
+
+    #[test]
+    fn test_policy_tag_rules_join_the_precedence() {
+        let settings = NexusSettings {
+            policy_tag_rules: BTreeMap::from([
+                ("file_ops".to_string(), TagDecision::Ask),
+                ("network".to_string(), TagDecision::Deny),
+                ("docs".to_string(), TagDecision::Notify),
+            ]),
+            ..accept_edits_settings()
+        };
+        let gate = PermissionGate::from_settings(&settings).unwrap();
+        let tagged = |tags: &[&str]| {
+            let mut action = patch(1, &["src/lib.rs"]);
+            action.policy_tags = tags.iter().map(|tag| tag.to_string()).collect();
+            action
+        };
+
+        let asked = gate.evaluate(&tagged(&["file_ops"]));
+        assert_eq!(asked.decision, Decision::Ask);
+        assert_eq!(asked.reason, "policy_tag_rules.file_ops");
+        let denied = gate.evaluate(&tagged(&["file_ops", "network"]));
+        assert_eq!(denied.decision, Decision::Deny);
+        assert_eq!(denied.reason, "policy_tag_rules.network");
+
+        let notified = gate.evaluate(&tagged(&["docs"]));
+        assert_eq!(notified.decision, Decision::Allow);
+        assert!(notified.notify);
+        assert!(!gate.evaluate(&tagged(&[])).notify);
+        let outvoted = gate.evaluate(&tagged(&["docs", "file_ops"]));
+        assert_eq!(outvoted.decision, Decision::Ask);
+        assert!(!outvoted.notify);
+    }
+
+    #[test]
+    fn test_scan_flagged_action_is_never_auto_allowed() {
+        let gate = PermissionGate::from_settings(&accept_edits_settings()).unwrap();
+        let mut flagged = patch(1, &["src/lib.rs"]);
+        flagged.policy_tags.push(scan::SECRET_TAG.to_string());
+
+        let decision = gate.evaluate(&flagged);
+        assert_eq!(decision.decision, Decision::Ask);
+        assert_eq!(decision.reason, "flagged by secret scan");
+
+        let mut settings = accept_edits_settings();
+        settings.approval_rules.push(ApprovalRule {
+            policy_tags: vec![scan::SECRET_TAG.to_string()],
+            ..rule(Decision::Deny)
+        });
+        let gate = PermissionGate::from_settings(&settings).unwrap();
+        assert_eq!(gate.evaluate(&flagged).decision, Decision::Deny);
+    }
+
+    #[test]
+    fn test_dependency_edits_need_audit() {
+        let gate = PermissionGate::from_settings(&accept_edits_settings()).unwrap();
+        let manifest = patch(1, &["src/Cargo.toml"]);
+        let decision = gate.evaluate(&manifest);
+        assert_eq!(decision.decision, Decision::Ask);
+        assert!(!decision.requires_audit);
+
+        let settings = NexusSettings {
+            deps_audit_command: vec!["cargo".to_string(), "deny".to_string()],
+            ..accept_edits_settings()
+        };
+        let gate = PermissionGate::from_settings(&settings).unwrap();
+        let decision = gate.evaluate(&manifest);
+        assert_eq!(decision.decision, Decision::Ask);
+        assert!(decision.requires_audit);
+
+        assert!(!gate.evaluate(&patch(2, &["src/Cargo.toml"])).requires_audit);
+    }
+
+    #[test]
+    fn test_command_prefixes_decide_commands() {
+        let command = |args: &[&str]| {
+            ProposedAction::test(
+                "act_1",
+                ActionDetails::Command(CommandDetails {
+                    argv: args.iter().map(|arg| arg.to_string()).collect(),
+                    cwd: None,
+                    timeout_s: 60,
+                    env_allow: Vec::new(),
+                    requires_network: false,
+                    purpose: None,
+                }),
+            )
+        };
+        let settings = NexusSettings {
+            allow_commands: vec![vec!["cargo".to_string(), "test".to_string()]],
+            ask_commands: vec![vec!["git".to_string(), "push".to_string()]],
+            approval_rules: vec![ApprovalRule {
+                kinds: vec![ActionKindTag::Command],
+                ..rule(Decision::Allow)
+            }],
+            ..NexusSettings::default()
+        };
+        let gate = PermissionGate::from_settings(&settings).unwrap();
+
+        let allowed = gate.evaluate(&command(&["cargo", "test"]));
+        assert_eq!(allowed.decision, Decision::Allow);
+        assert_eq!(allowed.reason, "`cargo test` matches allow_commands[0]");
+        assert_eq!(
+            gate.evaluate(&command(&["git", "push"])).decision,
+            Decision::Ask
+        );
+        assert_eq!(
+            gate.evaluate(&command(&["rm", "-rf", "."])).decision,
+            Decision::Deny
+        );
+        // No prefix matches, so the approval rule decides.
+        assert_eq!(
+            gate.evaluate(&command(&["make"])).reason,
+            "approval_rules[0]"
+        );
+    }
+
+    #[test]
+    fn test_autopilot_approves_configured_kinds() {
+        let settings = NexusSettings {
+            permission_mode: PermissionMode::Autopilot,
+            allow_paths_write: vec!["src/**".to_string()],
+            autopilot: Some(AutopilotConfig {
+                auto_approve_patches: true,
+                ..AutopilotConfig::default()
+            }),
+            ..NexusSettings::default()
+        };
+        let gate = PermissionGate::from_settings(&settings).unwrap();
+
+        let decision = gate.evaluate(&patch(1, &["src/lib.rs"]));
+        assert_eq!(decision.decision, Decision::Allow);
+        assert_eq!(decision.reason, "autopilot.auto_approve_patches");
+        // Writes outside allow_paths_write and deps edits still ask.
+        assert_eq!(
+            gate.evaluate(&patch(1, &["build.rs"])).decision,
+            Decision::Ask
+        );
+        assert_eq!(
+            gate.evaluate(&patch(1, &["src/Cargo.toml"])).decision,
+            Decision::Ask
+        );
+
+        let interactive = NexusSettings {
+            permission_mode: PermissionMode::Default,
+            ..settings
+        };
+        let gate = PermissionGate::from_settings(&interactive).unwrap();
+        assert_eq!(
+            gate.evaluate(&patch(1, &["src/lib.rs"])).decision,
+            Decision::Ask
+        );
+    }
+
+    #[test]
+    fn test_touched_paths_from_diff_and_command() {
+        let mut action = patch(1, &[]);
+        if let ActionDetails::Patch(details) = &mut action.details {
+            details.diff = Some("--- a/src\\a.rs\n+++ b/src/a.rs\n".to_string());
+        }
+        assert_eq!(touched_paths(&action), vec!["src/a.rs".to_string()]);
+
+        let command = ProposedAction::test(
+            "act_1",
+            ActionDetails::Command(CommandDetails {
+                argv: vec!["cargo".to_string(), "test".to_string()],
+                cwd: None,
+                timeout_s: 60,
+                env_allow: Vec::new(),
+                requires_network: false,
+                purpose: None,
+            }),
+        );
+        assert!(touched_paths(&command).is_empty());
+    }
+}
@@ -0,0 +1,117 @@
+//! Path policy: which files an action may touch.
+//!
+//! Every path a [`ProposedAction`] reads or writes is matched against
+//! `deny_paths` and `allow_paths_write`. A denied path rejects the action
+//! outright; when `allow_paths_write` is set, a path outside it means the
+//! action needs explicit approval before it is applied.
+
+use globset::GlobSet;
+
+use crate::error::NexusError;
+use crate::types::{Decision, NexusSettings, ProposedAction};
+
+use super::{PolicyDecision, build_globset, touched_paths};
+
+/// Glob rules for the files actions may touch.
+#[derive(Debug, Clone)]
+pub struct PathPolicy {
+    deny_paths: GlobSet,
+    allow_paths_write: GlobSet,
+    /// `allow_paths_write` is non-empty, so writes are restricted to it.
+    restrict_writes: bool,
+}
+
+impl PathPolicy {
+    pub fn from_settings(settings: &NexusSettings) -> Result<Self, NexusError> {
+        Ok(Self {
+            deny_paths: build_globset("deny_paths", &settings.deny_paths)?,
+            allow_paths_write: build_globset("allow_paths_write", &settings.allow_paths_write)?,
+            restrict_writes: !settings.allow_paths_write.is_empty(),
+        })
+    }
+
+    /// True if `path` matches `deny_paths`.
+    pub fn is_denied(&self, path: &str) -> bool {
+        self.deny_paths.is_match(path)
+    }
+
+    /// True if `path` matches `allow_paths_write`.
+    pub fn is_writable(&self, path: &str) -> bool {
+        self.allow_paths_write.is_match(path)
+    }
+
+    /// Checks every path `action` touches.
+    ///
+    /// Returns `Deny` for a path matching `deny_paths`, `Ask` for a path
+    /// outside a non-empty `allow_paths_write`, and `Allow` otherwise.
+    pub fn check(&self, action: &ProposedAction) -> PolicyDecision {
+        self.check_paths(&touched_paths(action))
+    }
+
+    pub(crate) fn check_paths(&self, paths: &[String]) -> PolicyDecision {
+        if let Some(path) = paths.iter().find(|path| self.is_denied(path)) {
+            return PolicyDecision::new(Decision::Deny, format!("{path} matches deny_paths"));
+        }
+        if self.restrict_writes {
+            if let Some(path) = paths.iter().find(|path| !self.is_writable(path)) {
+                return PolicyDecision::new(
+                    Decision::Ask,
+                    format!("{path} is outside allow_paths_write"),
+                );
+            }
+        }
+        PolicyDecision::new(Decision::Allow, "paths allowed")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ActionDetails, FileRenameDetails};
+
+    fn rename(old_path: &str, new_path: &str) -> ProposedAction {
+        ProposedAction::test(
+            "act_1",
+            ActionDetails::FileRename(FileRenameDetails {
+                old_path: old_path.to_string(),
+                new_path: new_path.to_string(),
+                overwrite: false,
+            }),
+        )
+    }
+
+    #[test]
+    fn test_every_touched_path_is_checked() {
+        let policy = PathPolicy::from_settings(&NexusSettings {
+            allow_paths_write: vec!["src/**".to_string()],
+            ..NexusSettings::default()
+        })
+        .unwrap();
+
+        assert_eq!(
+            policy.check(&rename("src/a.rs", "src/b.rs")).decision,
+            Decision::Allow
+        );
+
+        let outside = policy.check(&rename("src/a.rs", "b.rs"));
+        assert_eq!(outside.decision, Decision::Ask);
+        assert_eq!(outside.reason, "b.rs is outside allow_paths_write");
+
+        let denied = policy.check(&rename("src/a.rs", "src/.ssh/id_rsa"));
+        assert_eq!(denied.decision, Decision::Deny);
+        assert_eq!(denied.reason, "src/.ssh/id_rsa matches deny_paths");
+    }
+
+    #[test]
+    fn test_empty_allow_paths_write_allows_everything_not_denied() {
+        let policy = PathPolicy::from_settings(&NexusSettings::default()).unwrap();
+        assert_eq!(
+            policy.check(&rename("a.rs", "docs/b.rs")).decision,
+            Decision::Allow
+        );
+        assert_eq!(
+            policy.check(&rename(".env", "env.txt")).decision,
+            Decision::Deny
+        );
+    }
+}
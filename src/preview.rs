@@ -0,0 +1,536 @@
+//! Combined diff preview of actions a run has proposed but not applied.
+//!
+//! Proposed actions are stored as run artifacts referenced from their
+//! `action.proposed` events. Actions with a matching `tool.executed` event
+//! are considered applied, and handoffs with a `handoff.accepted` event
+//! done; both are left out of the preview.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use similar::TextDiff;
+
+use crate::apply::matcher::MatchOptions;
+use crate::apply::search_replace;
+use crate::apply::unified::agenda_diff;
+use crate::cost::action_cu;
+use crate::error::NexusError;
+use crate::event_log::{EventLogPath, EventLogReader, PayloadStore};
+use crate::types::{ActionDetails, PatchDetails, PayloadRef, ProposedAction, SearchReplaceBlock};
+
+const CONTEXT_LINES: usize = 3;
+const DEV_NULL: &str = "/dev/null";
+
+const ANSI_RESET: &str = "\x1b[0m";
+const ANSI_BOLD: &str = "\x1b[1m";
+const ANSI_RED: &str = "\x1b[31m";
+const ANSI_GREEN: &str = "\x1b[32m";
+const ANSI_CYAN: &str = "\x1b[36m";
+
+/// Loads the proposed, not yet applied actions of `run_id` under `project_root`.
+pub fn pending_actions_for_run(
+    project_root: &Path,
+    run_id: &str,
+) -> Result<Vec<ProposedAction>, NexusError> {
+    let log_path = EventLogPath::new(project_root).for_run(run_id)?;
+    pending_actions(&log_path)
+}
+
+/// Loads the proposed, not yet applied actions recorded in the log at `log_path`.
+pub fn pending_actions(log_path: &Path) -> Result<Vec<ProposedAction>, NexusError> {
+    let events = EventLogReader::open(log_path)?.load_all()?;
+    let runs_dir = log_path.parent().unwrap_or_else(|| Path::new("."));
+
+    let applied: HashSet<&str> = events
+        .iter()
+        .filter(|event| {
+            event.event_type == "tool.executed" || event.event_type == "handoff.accepted"
+        })
+        .filter_map(|event| event.payload.as_ref()?.get("action_id")?.as_str())
+        .collect();
+
+    let mut actions = Vec::new();
+    for event in events.iter().filter(|e| e.event_type == "action.proposed") {
+        let action_id = event
+            .payload
+            .as_ref()
+            .and_then(|payload| payload.get("action_id"))
+            .and_then(|id| id.as_str());
+        if action_id.is_some_and(|id| applied.contains(id)) {
+            continue;
+        }
+
+        let Some(payload_ref) = &event.payload_ref else {
+            log::warn!(
+                "action {} has no stored details; skipping",
+                action_id.unwrap_or("<unknown>")
+            );
+            continue;
+        };
+        actions.push(load_action_artifact(runs_dir, payload_ref)?);
+    }
+
+    Ok(actions)
+}
+
+/// Reads a proposed action stored as a run artifact.
+pub(crate) fn load_action_artifact(
+    runs_dir: &Path,
+    payload_ref: &PayloadRef,
+) -> Result<ProposedAction, NexusError> {
+    let path = PayloadStore::resolve(runs_dir, payload_ref);
+    let bytes = std::fs::read(&path).map_err(|err| NexusError::IoError {
+        operation: "read action artifact".to_string(),
+        path: path.clone(),
+        source: err,
+    })?;
+    serde_json::from_slice(&bytes).map_err(|source| NexusError::JsonError {
+        context: format!("failed to parse action artifact {}", path.display()),
+        source,
+    })
+}
+
+/// Renders `actions` as a single unified diff against the files under `root`.
+///
+/// Search/replace and whole-file patches are converted to unified hunks by
+/// diffing the current file against its patched contents. Agenda patches
+/// are shown as their diff.
+pub fn render_diff(actions: &[ProposedAction], root: &Path, color: bool) -> String {
+    let mut output = String::new();
+    for action in actions {
+        if let Some(diff) = action_diff(action, root) {
+            output.push_str(&diff);
+        }
+    }
+
+    if color { colorize(&output) } else { output }
+}
+
+/// Lines added and removed by a unified diff.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiffStat {
+    pub added: usize,
+    pub removed: usize,
+}
+
+/// Counts the added and removed lines of `diff`, skipping file headers.
+pub fn diff_stat(diff: &str) -> DiffStat {
+    let mut stat = DiffStat::default();
+    let mut lines = diff.lines().peekable();
+    while let Some(line) = lines.next() {
+        let is_header =
+            line.starts_with("--- ") && lines.peek().is_some_and(|next| next.starts_with("+++ "));
+        if is_header {
+            lines.next();
+        } else if line.starts_with('+') {
+            stat.added += 1;
+        } else if line.starts_with('-') {
+            stat.removed += 1;
+        }
+    }
+    stat
+}
+
+/// Lines added and removed per file by a unified diff, in the order the
+/// files appear. Lines before the first file header are not counted.
+pub fn diff_file_stats(diff: &str) -> Vec<(String, DiffStat)> {
+    let mut files: Vec<(String, DiffStat)> = Vec::new();
+    let mut lines = diff.lines().peekable();
+    while let Some(line) = lines.next() {
+        let new = lines.peek().and_then(|next| next.strip_prefix("+++ "));
+        if let (Some(old), Some(new)) = (line.strip_prefix("--- "), new) {
+            lines.next();
+            files.push((header_path(old, new), DiffStat::default()));
+            continue;
+        }
+        let Some((_, stat)) = files.last_mut() else {
+            continue;
+        };
+        if line.starts_with('+') {
+            stat.added += 1;
+        } else if line.starts_with('-') {
+            stat.removed += 1;
+        }
+    }
+    files
+}
+
+/// The file a `--- old` / `+++ new` header pair is about: the new path,
+/// or the old one for a deletion.
+fn header_path(old: &str, new: &str) -> String {
+    let path = |header: &str| {
+        let header = header.split('\t').next().unwrap_or(header).trim();
+        let path = header
+            .strip_prefix("a/")
+            .or_else(|| header.strip_prefix("b/"))
+            .unwrap_or(header);
+        path.to_string()
+    };
+    if new.trim() == DEV_NULL {
+        path(old)
+    } else {
+        path(new)
+    }
+}
+
+/// Renders each action's diff under a header with its added and removed
+/// line counts and complexity units, then a diffstat line per file
+/// (`+12 -4 src/lib.rs`) and the totals. Nothing is written to disk.
+pub fn render_dry_run(actions: &[ProposedAction], root: &Path, color: bool) -> String {
+    let mut output = String::new();
+    let mut files: Vec<(String, DiffStat)> = Vec::new();
+    let mut total = DiffStat::default();
+    let mut total_cu = 0u32;
+    for action in actions {
+        let cu = action_cu(action);
+        total_cu = total_cu.saturating_add(cu);
+        let Some(diff) = action_diff(action, root) else {
+            output.push_str(&header(
+                &format!("{} {} (no diff, {cu} CU)", action.id, action.summary),
+                color,
+            ));
+            continue;
+        };
+        let stat = diff_stat(&diff);
+        total.added += stat.added;
+        total.removed += stat.removed;
+        for (path, file_stat) in diff_file_stats(&diff) {
+            match files.iter_mut().find(|(seen, _)| *seen == path) {
+                Some((_, seen)) => {
+                    seen.added += file_stat.added;
+                    seen.removed += file_stat.removed;
+                }
+                None => files.push((path, file_stat)),
+            }
+        }
+        output.push_str(&header(
+            &format!(
+                "{} {} (+{} -{}, {cu} CU)",
+                action.id, action.summary, stat.added, stat.removed
+            ),
+            color,
+        ));
+        output.push_str(&if color { colorize(&diff) } else { diff });
+    }
+    output.push_str(&render_diffstat(&files, color));
+    output.push_str(&format!(
+        "{} action(s), +{} -{}, {total_cu} CU\n",
+        actions.len(),
+        total.added,
+        total.removed
+    ));
+    output
+}
+
+/// One `+added -removed path` line per file, with the counts aligned.
+fn render_diffstat(files: &[(String, DiffStat)], color: bool) -> String {
+    let counts: Vec<(String, String)> = files
+        .iter()
+        .map(|(_, stat)| (format!("+{}", stat.added), format!("-{}", stat.removed)))
+        .collect();
+    let added_width = counts.iter().map(|(added, _)| added.len()).max();
+    let removed_width = counts.iter().map(|(_, removed)| removed.len()).max();
+    let mut output = String::new();
+    for ((path, _), (added, removed)) in files.iter().zip(counts) {
+        let added = format!("{added:>0$}", added_width.unwrap_or_default());
+        let removed = format!("{removed:<0$}", removed_width.unwrap_or_default());
+        if color {
+            output.push_str(&format!(
+                "{ANSI_GREEN}{added}{ANSI_RESET} {ANSI_RED}{removed}{ANSI_RESET} {path}\n"
+            ));
+        } else {
+            output.push_str(&format!("{added} {removed} {path}\n"));
+        }
+    }
+    output
+}
+
+fn header(text: &str, color: bool) -> String {
+    if color {
+        format!("{ANSI_BOLD}{text}{ANSI_RESET}\n")
+    } else {
+        format!("{text}\n")
+    }
+}
+
+/// Unified diff of a patch or agenda patch action.
+fn action_diff(action: &ProposedAction, root: &Path) -> Option<String> {
+    match &action.details {
+        ActionDetails::Patch(details) => Some(patch_diff(details, root)),
+        ActionDetails::AgendaPatch(details) => Some(agenda_diff(details)),
+        _ => None,
+    }
+}
+
+fn patch_diff(details: &PatchDetails, root: &Path) -> String {
+    let mut output = String::new();
+
+    if let Some(diff) = &details.diff {
+        output.push_str(diff);
+        if !diff.ends_with('\n') {
+            output.push('\n');
+        }
+    }
+
+    for block in details.search_replace_blocks.iter().flatten() {
+        output.push_str(&search_replace_diff(block, root));
+    }
+
+    if let Some(contents) = &details.whole_file_content {
+        let mut paths: Vec<_> = contents.keys().collect();
+        paths.sort();
+        for path in paths {
+            let old = read_current(root, path);
+            output.push_str(&file_diff(path, old.as_deref(), &contents[path]));
+        }
+    }
+
+    output
+}
+
+fn search_replace_diff(block: &SearchReplaceBlock, root: &Path) -> String {
+    if let Some(current) = read_current(root, &block.file) {
+        let block = &search_replace::with_line_ending(&current, block);
+        // `locate` matches by the block's `match_mode`, as the applier does.
+        if let Some(found) = search_replace::locate(&current, block, MatchOptions::default()) {
+            let mut updated = current.clone();
+            updated.replace_range(found.range, &block.replace);
+            return file_diff(&block.file, Some(&current), &updated);
+        }
+    }
+
+    // The target cannot be located; show the block itself as the change.
+    file_diff(&block.file, Some(&block.search), &block.replace)
+}
+
+fn file_diff(path: &str, old: Option<&str>, new: &str) -> String {
+    let old_header = match old {
+        Some(_) => format!("a/{path}"),
+        None => DEV_NULL.to_string(),
+    };
+    let new_header = format!("b/{path}");
+    let diff = TextDiff::from_lines(old.unwrap_or(""), new);
+    diff.unified_diff()
+        .context_radius(CONTEXT_LINES)
+        .header(&old_header, &new_header)
+        .missing_newline_hint(false)
+        .to_string()
+}
+
+fn read_current(root: &Path, path: &str) -> Option<String> {
+    std::fs::read_to_string(root.join(path)).ok()
+}
+
+fn colorize(diff: &str) -> String {
+    let mut output = String::with_capacity(diff.len());
+    for line in diff.split_inclusive('\n') {
+        let style = if line.starts_with("+++") || line.starts_with("---") {
+            Some(ANSI_BOLD)
+        } else if line.starts_with('+') {
+            Some(ANSI_GREEN)
+        } else if line.starts_with('-') {
+            Some(ANSI_RED)
+        } else if line.starts_with("@@") {
+            Some(ANSI_CYAN)
+        } else {
+            None
+        };
+
+        match style {
+            Some(style) => {
+                let content = line.trim_end_matches('\n');
+                output.push_str(style);
+                output.push_str(content);
+                output.push_str(ANSI_RESET);
+                if line.ends_with('\n') {
+                    output.push('\n');
+                }
+            }
+            None => output.push_str(line),
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_log::{EventLogWriter, helpers};
+    use crate::types::{MatchMode, PatchFormat};
+    use tempfile::TempDir;
+
+    fn patch_action(id: &str, details: PatchDetails) -> ProposedAction {
+        ProposedAction::test(id, ActionDetails::Patch(details))
+    }
+
+    #[test]
+    fn test_render_search_replace_against_current_file() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("lib.rs"), "fn a() {}\nfn b() {}\n").unwrap();
+        let action = patch_action(
+            "act_1",
+            PatchDetails {
+                format: PatchFormat::SearchReplace,
+                search_replace_blocks: Some(vec![SearchReplaceBlock {
+                    file: "lib.rs".to_string(),
+                    search: "fn b() {}".to_string(),
+                    replace: "fn c() {}".to_string(),
+                    match_mode: MatchMode::Exact,
+                }]),
+                ..Default::default()
+            },
+        );
+
+        let diff = render_diff(&[action], dir.path(), false);
+
+        assert!(diff.contains("--- a/lib.rs\n+++ b/lib.rs\n"));
+        assert!(diff.contains("-fn b() {}\n+fn c() {}\n"));
+        assert!(diff.contains(" fn a() {}\n"));
+    }
+
+    #[test]
+    fn test_render_search_replace_honors_match_mode() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("lib.rs"), "fn a() {}\nfn  b()  {}\n").unwrap();
+        let action = patch_action(
+            "act_1",
+            PatchDetails {
+                format: PatchFormat::SearchReplace,
+                search_replace_blocks: Some(vec![SearchReplaceBlock {
+                    file: "lib.rs".to_string(),
+                    search: "fn b() {}".to_string(),
+                    replace: "fn c() {}".to_string(),
+                    match_mode: MatchMode::WhitespaceInsensitive,
+                }]),
+                ..Default::default()
+            },
+        );
+
+        let diff = render_diff(&[action], dir.path(), false);
+
+        assert!(diff.contains("-fn  b()  {}\n+fn c() {}\n"), "{diff}");
+        assert!(diff.contains(" fn a() {}\n"), "{diff}");
+    }
+
+    #[test]
+    fn test_diff_stat_skips_headers() {
+        let stat = diff_stat("--- a/x\n+++ b/x\n@@ -1,2 +1,2 @@\n keep\n--- old\n+++new\n+more\n");
+        assert_eq!(
+            stat,
+            DiffStat {
+                added: 2,
+                removed: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_diff_file_stats_and_diffstat() {
+        let diff = "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1,2 +1,2 @@\n-a\n-b\n+c\n\
+                    --- a/old.rs\n+++ /dev/null\n@@ -1 +0,0 @@\n-gone\n\
+                    --- /dev/null\n+++ b/new.rs\n@@ -0,0 +1,12 @@\n";
+        let mut diff = diff.to_string();
+        diff.push_str(&"+x\n".repeat(12));
+        let files = diff_file_stats(&diff);
+        let stat = |added, removed| DiffStat { added, removed };
+        assert_eq!(
+            files,
+            [
+                ("src/lib.rs".to_string(), stat(1, 2)),
+                ("old.rs".to_string(), stat(0, 1)),
+                ("new.rs".to_string(), stat(12, 0)),
+            ]
+        );
+        assert_eq!(
+            render_diffstat(&files, false),
+            " +1 -2 src/lib.rs\n +0 -1 old.rs\n+12 -0 new.rs\n"
+        );
+    }
+
+    #[test]
+    fn test_render_dry_run_counts_lines_per_action() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.rs"), "one\ntwo\n").unwrap();
+        let mut action = patch_action(
+            "act_1",
+            PatchDetails {
+                format: PatchFormat::WholeFile,
+                whole_file_content: Some(
+                    [("a.rs".to_string(), "one\n2\nthree\n".to_string())].into(),
+                ),
+                ..Default::default()
+            },
+        );
+        action.summary = "Use digits".to_string();
+
+        let plain = render_dry_run(std::slice::from_ref(&action), dir.path(), false);
+        assert!(
+            plain.starts_with("act_1 Use digits (+2 -1, 11 CU)\n--- a/a.rs\n"),
+            "{plain}"
+        );
+        assert!(
+            plain.ends_with("+2 -1 a.rs\n1 action(s), +2 -1, 11 CU\n"),
+            "{plain}"
+        );
+
+        let colored = render_dry_run(&[action], dir.path(), true);
+        assert!(colored.starts_with("\x1b[1mact_1 Use digits (+2 -1, 11 CU)\x1b[0m\n"));
+        assert!(colored.contains("\x1b[31m-two\x1b[0m\n"));
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.rs")).unwrap(),
+            "one\ntwo\n"
+        );
+    }
+
+    #[test]
+    fn test_render_new_whole_file_and_color() {
+        let dir = TempDir::new().unwrap();
+        let action = patch_action(
+            "act_1",
+            PatchDetails {
+                format: PatchFormat::WholeFile,
+                whole_file_content: Some([("new.rs".to_string(), "x\n".to_string())].into()),
+                ..Default::default()
+            },
+        );
+
+        let plain = render_diff(std::slice::from_ref(&action), dir.path(), false);
+        assert!(plain.starts_with("--- /dev/null\n+++ b/new.rs\n"));
+
+        let colored = render_diff(&[action], dir.path(), true);
+        assert!(colored.contains("\x1b[32m+x\x1b[0m\n"));
+    }
+
+    #[test]
+    fn test_pending_actions_skips_applied() {
+        let dir = TempDir::new().unwrap();
+        let log_path = dir.path().join("run_1.jsonl");
+        let store = PayloadStore::for_log(&log_path, "run_1").unwrap();
+        let mut writer = EventLogWriter::open(&log_path).unwrap();
+        for id in ["act_1", "act_2"] {
+            let action = patch_action(
+                id,
+                PatchDetails {
+                    diff: Some(format!("--- a/{id}\n+++ b/{id}\n")),
+                    ..Default::default()
+                },
+            );
+            let bytes = serde_json::to_vec(&action).unwrap();
+            let payload_ref = store
+                .write(&format!("{id}.json"), &bytes, "application/json", "action")
+                .unwrap();
+            let event = helpers::action_proposed("run_1", id, "patch", "test", None)
+                .with_payload_ref(payload_ref);
+            writer.append(&event).unwrap();
+        }
+        writer
+            .append(&helpers::tool_executed("run_1", "act_1", vec![]))
+            .unwrap();
+        writer.sync().unwrap();
+        drop(writer);
+
+        let pending = pending_actions(&log_path).unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, "act_2");
+    }
+}
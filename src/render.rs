@@ -0,0 +1,358 @@
+//! Live terminal view of a model reply while it streams.
+//!
+//! A spinner runs until the first chunk arrives; after that the bottom
+//! line shows progress: characters received, elapsed time, and the file
+//! being emitted when the reply names one (a `+++ b/<path>` diff header, a
+//! `<<<<<<< SEARCH <path>` block, or a JSON `"file"`/`"path"` value). Reply
+//! text is printed above it a line at a time, and reasoning (`Thinking`
+//! chunks) is shown dimmed, or marked `[thinking]` without color, so it is
+//! not mistaken for the answer. [`StreamRenderer::finish`] clears the
+//! progress line and ends the transcript so the action summary printed next
+//! starts on a fresh line.
+
+use std::future::Future;
+use std::io::Write;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::executor::StreamChunk;
+
+const SPINNER_FRAMES: &[char] = &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+const SPINNER_INTERVAL: Duration = Duration::from_millis(100);
+const CLEAR_LINE: &str = "\r\x1b[2K";
+
+const ANSI_RESET: &str = "\x1b[0m";
+const ANSI_DIM: &str = "\x1b[2m";
+const THINKING_LABEL: &str = "[thinking] ";
+
+/// Where a file name starts in a streamed reply.
+const FILE_MARKERS: &[&str] = &[
+    "+++ b/",
+    "<<<<<<< SEARCH ",
+    "\"file\":\"",
+    "\"file\": \"",
+    "\"path\":\"",
+    "\"path\": \"",
+];
+/// Bytes of earlier text kept to find file names split across chunks.
+const FILE_SCAN_TAIL: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Waiting,
+    Thinking,
+    Text,
+    Finished,
+}
+
+/// Writes streamed chunks to a terminal.
+///
+/// Write errors are ignored: the view is a courtesy, and losing it must not
+/// fail the run.
+#[derive(Debug)]
+pub struct StreamRenderer<W: Write> {
+    out: W,
+    color: bool,
+    state: State,
+    frame: usize,
+    /// Whether the spinner or progress line is on screen.
+    status_shown: bool,
+    /// Text of the current line, printed once it ends.
+    partial: String,
+    /// Whether this stretch of thinking got its `[thinking]` label.
+    labelled: bool,
+    started: Instant,
+    received: usize,
+    file: Option<String>,
+    /// End of the text so far, searched for file names with the next chunk.
+    tail: String,
+}
+
+impl<W: Write> StreamRenderer<W> {
+    pub fn new(out: W, color: bool) -> Self {
+        Self {
+            out,
+            color,
+            state: State::Waiting,
+            frame: 0,
+            status_shown: false,
+            partial: String::new(),
+            labelled: false,
+            started: Instant::now(),
+            received: 0,
+            file: None,
+            tail: String::new(),
+        }
+    }
+
+    /// Advances the spinner and redraws the progress line.
+    pub fn tick(&mut self) {
+        self.draw_status(self.started.elapsed());
+    }
+
+    pub fn chunk(&mut self, chunk: &StreamChunk) {
+        match chunk {
+            StreamChunk::Text(text) => {
+                self.enter(State::Text);
+                self.receive(text);
+            }
+            StreamChunk::Thinking(text) => {
+                self.enter(State::Thinking);
+                self.receive(text);
+            }
+            StreamChunk::ActionStart { summary, .. } => {
+                self.enter(State::Text);
+                self.end_line();
+                self.write_text(&format!("» {summary}\n"));
+            }
+            StreamChunk::Error(message) => {
+                self.enter(State::Text);
+                self.end_line();
+                self.write_text(&format!("error: {message}\n"));
+            }
+            StreamChunk::ActionComplete(_) => {}
+            StreamChunk::Done => self.finish(),
+        }
+        let _ = self.out.flush();
+    }
+
+    /// Clears the progress line and ends the transcript on its own line.
+    /// Safe to call more than once.
+    pub fn finish(&mut self) {
+        if self.state == State::Finished {
+            return;
+        }
+        self.end_line();
+        self.clear_status();
+        self.state = State::Finished;
+        let _ = self.out.flush();
+    }
+
+    fn draw_status(&mut self, elapsed: Duration) {
+        if self.state == State::Finished {
+            return;
+        }
+        let frame = SPINNER_FRAMES[self.frame % SPINNER_FRAMES.len()];
+        self.frame += 1;
+        let status = if self.state == State::Waiting {
+            "Waiting for the model...".to_string()
+        } else {
+            let mut status = format!(
+                "Receiving {} chars, {:.1}s",
+                self.received,
+                elapsed.as_secs_f64()
+            );
+            if let Some(file) = &self.file {
+                status.push_str(&format!(", {file}"));
+            }
+            status
+        };
+        let _ = write!(self.out, "{CLEAR_LINE}{frame} {status}");
+        let _ = self.out.flush();
+        self.status_shown = true;
+    }
+
+    fn clear_status(&mut self) {
+        if self.status_shown {
+            let _ = write!(self.out, "{CLEAR_LINE}");
+            self.status_shown = false;
+        }
+    }
+
+    /// Switches to `state`, ending the line the previous one left open.
+    fn enter(&mut self, state: State) {
+        if self.state == state || self.state == State::Finished {
+            return;
+        }
+        self.end_line();
+        self.labelled = false;
+        self.state = state;
+    }
+
+    fn receive(&mut self, text: &str) {
+        self.received += text.chars().count();
+        let window = std::mem::take(&mut self.tail) + text;
+        if let Some(file) = emitted_file(&window) {
+            self.file = Some(file.to_string());
+        }
+        let mut keep = window.len().saturating_sub(FILE_SCAN_TAIL);
+        while !window.is_char_boundary(keep) {
+            keep += 1;
+        }
+        self.tail = window[keep..].to_string();
+        self.write_text(text);
+    }
+
+    fn end_line(&mut self) {
+        if !self.partial.is_empty() {
+            self.write_text("\n");
+        }
+    }
+
+    /// Prints the lines `text` completes; the rest waits for its newline.
+    fn write_text(&mut self, text: &str) {
+        self.partial.push_str(text);
+        let Some(end) = self.partial.rfind('\n') else {
+            return;
+        };
+        let lines: String = self.partial.drain(..=end).collect();
+        self.clear_status();
+        for line in lines.lines() {
+            self.write_line(line);
+        }
+    }
+
+    fn write_line(&mut self, line: &str) {
+        if self.state != State::Thinking {
+            let _ = writeln!(self.out, "{line}");
+        } else if self.color {
+            let _ = writeln!(self.out, "{ANSI_DIM}{line}{ANSI_RESET}");
+        } else if !self.labelled {
+            let _ = writeln!(self.out, "{THINKING_LABEL}{line}");
+            self.labelled = true;
+        } else {
+            let _ = writeln!(self.out, "{line}");
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+/// The file named by the last complete file marker in `text`, if any.
+fn emitted_file(text: &str) -> Option<&str> {
+    FILE_MARKERS
+        .iter()
+        .filter_map(|marker| {
+            text.rmatch_indices(marker).find_map(|(start, marker)| {
+                let rest = &text[start + marker.len()..];
+                let end =
+                    rest.find(|c: char| c.is_whitespace() || matches!(c, '"' | '\\' | '`'))?;
+                (end > 0).then(|| (start, &rest[..end]))
+            })
+        })
+        .max_by_key(|(start, _)| *start)
+        .map(|(_, file)| file)
+}
+
+/// Runs `execute` with a chunk callback feeding `renderer`, animating the
+/// spinner until the reply starts, and finishes the view when it returns.
+pub async fn render_stream<W, F, Fut, T>(renderer: StreamRenderer<W>, execute: F) -> T
+where
+    W: Write + Send + 'static,
+    F: FnOnce(Box<dyn Fn(StreamChunk) + Send>) -> Fut,
+    Fut: Future<Output = T>,
+{
+    let renderer = Arc::new(Mutex::new(renderer));
+    let sink = Arc::clone(&renderer);
+    let on_chunk = Box::new(move |chunk: StreamChunk| {
+        sink.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .chunk(&chunk);
+    });
+
+    let run = execute(on_chunk);
+    tokio::pin!(run);
+    let mut spinner = tokio::time::interval(SPINNER_INTERVAL);
+    let result = loop {
+        tokio::select! {
+            result = &mut run => break result,
+            _ = spinner.tick() => renderer
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .tick(),
+        }
+    };
+    renderer
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .finish();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rendered(color: bool, chunks: &[StreamChunk]) -> String {
+        let mut renderer = StreamRenderer::new(Vec::new(), color);
+        renderer.tick();
+        for chunk in chunks {
+            renderer.chunk(chunk);
+        }
+        renderer.finish();
+        String::from_utf8(renderer.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn test_text_streams_after_spinner() {
+        let output = rendered(
+            false,
+            &[
+                StreamChunk::Text("Renaming ".to_string()),
+                StreamChunk::Text("`a`.".to_string()),
+                StreamChunk::Done,
+            ],
+        );
+        assert_eq!(
+            output,
+            format!("{CLEAR_LINE}⠋ Waiting for the model...{CLEAR_LINE}Renaming `a`.\n")
+        );
+    }
+
+    #[test]
+    fn test_thinking_is_set_apart_from_text() {
+        let chunks = [
+            StreamChunk::Thinking("check callers".to_string()),
+            StreamChunk::Text("Done.\n".to_string()),
+        ];
+
+        let plain = rendered(false, &chunks);
+        assert!(plain.ends_with(&format!("{CLEAR_LINE}[thinking] check callers\nDone.\n")));
+
+        let colored = rendered(true, &chunks);
+        assert!(colored.ends_with(&format!(
+            "{CLEAR_LINE}{ANSI_DIM}check callers{ANSI_RESET}\nDone.\n"
+        )));
+    }
+
+    #[test]
+    fn test_progress_line_counts_chars_and_names_the_file() {
+        let mut renderer = StreamRenderer::new(Vec::new(), false);
+        renderer.chunk(&StreamChunk::Text(
+            "--- a/src/lib.rs\n+++ b/src/".to_string(),
+        ));
+        renderer.draw_status(Duration::from_millis(250));
+        renderer.chunk(&StreamChunk::Text("lib.rs\n@@ -1 +1 @@\n-a".to_string()));
+        renderer.draw_status(Duration::from_millis(1500));
+        renderer.finish();
+
+        let output = String::from_utf8(renderer.into_inner()).unwrap();
+        assert_eq!(
+            output,
+            format!(
+                "--- a/src/lib.rs\n{CLEAR_LINE}⠋ Receiving 27 chars, 0.2s\
+                 {CLEAR_LINE}+++ b/src/lib.rs\n@@ -1 +1 @@\n\
+                 {CLEAR_LINE}⠙ Receiving 48 chars, 1.5s, src/lib.rs{CLEAR_LINE}-a\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_emitted_file_markers() {
+        assert_eq!(
+            emitted_file("{\"diff\":\"--- a/x.rs\\n+++ b/x.rs\\n@@"),
+            Some("x.rs")
+        );
+        assert_eq!(
+            emitted_file("<<<<<<< SEARCH src/a.rs\nold"),
+            Some("src/a.rs")
+        );
+        assert_eq!(
+            emitted_file("{\"file\": \"a.rs\", \"search\": \"+++ b/b"),
+            Some("a.rs")
+        );
+        assert_eq!(emitted_file("nothing here"), None);
+    }
+}
@@ -0,0 +1,821 @@
+//! Resuming a run that stopped after proposing actions.
+//!
+//! If Nexus exits between the executor's response and the apply step, the
+//! proposals survive in the log as `action.proposed` events with their
+//! stored artifacts. Resuming rehydrates the actions that were neither
+//! applied nor decided, checks that their patches still apply, records a
+//! decision for each (from the permission gate, or from the user when the
+//! gate asks; actions of one approval group are put to the user together
+//! and decided by a single permission event), and leaves the apply itself
+//! to [`RunApply`](crate::apply::RunApply). [`finish_run`] then records the
+//! terminal event the interrupted run never wrote.
+
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use serde_json::{Value, json};
+
+use crate::apply::run::reasons_json;
+use crate::apply::undo::applied_actions;
+use crate::apply::{Applier, LinePrompt, PatchCheck};
+use crate::batch::{allowed_event, audited_decision};
+use crate::cost::action_cu;
+use crate::error::NexusError;
+use crate::event_log::{EventLogPath, EventLogReader, EventLogWriter, RunTrace, helpers};
+use crate::policy::deps::AuditOutcome;
+use crate::policy::{PermissionGate, PolicyDecision};
+use crate::preview::pending_actions;
+use crate::redact::Redactor;
+use crate::review::{ActionReview, reviews};
+use crate::types::{ApprovalGroup, Decision, ProposedAction, RunStatus};
+
+/// Scope recorded on permissions the user granted at the resume prompt.
+const PROMPT_SCOPE: &str = "once";
+
+/// Asks the user about actions the policy leaves to them.
+pub trait ApprovalPrompt {
+    /// Shown every action about to be put to the user, in order, before
+    /// any of them is asked about. A prompt presenting them side by side
+    /// can collect all the answers here.
+    fn preview(&mut self, _actions: &[GroupMember<'_>]) -> Result<(), NexusError> {
+        Ok(())
+    }
+
+    /// Whether `action` may be applied; `None` if no answer was given.
+    /// `check` tells whether its patch applies to the current files, and
+    /// `review` is what the reviewer made of it, if it was reviewed.
+    fn approve(
+        &mut self,
+        action: &ProposedAction,
+        reason: &str,
+        check: Option<&PatchCheck>,
+        review: Option<&ActionReview>,
+    ) -> Result<Option<bool>, NexusError>;
+
+    /// Whether all `members` of `group` may be applied, decided at once;
+    /// `None` if no answer was given.
+    fn approve_group(
+        &mut self,
+        group: &ApprovalGroup,
+        members: &[GroupMember<'_>],
+    ) -> Result<Option<bool>, NexusError>;
+}
+
+/// An action put to the user, e.g. as one of an approval group.
+#[derive(Debug, Clone, Copy)]
+pub struct GroupMember<'a> {
+    pub action: &'a ProposedAction,
+    /// Why the permission gate asks about it.
+    pub reason: &'a str,
+    pub check: Option<&'a PatchCheck>,
+    pub review: Option<&'a ActionReview>,
+}
+
+impl<R: BufRead, W: Write> ApprovalPrompt for LinePrompt<R, W> {
+    fn approve(
+        &mut self,
+        action: &ProposedAction,
+        reason: &str,
+        check: Option<&PatchCheck>,
+        review: Option<&ActionReview>,
+    ) -> Result<Option<bool>, NexusError> {
+        self.say(&format!(
+            "\n{}: {} [{} CU] ({reason})",
+            action.id,
+            action.summary,
+            action_cu(action)
+        ))
+        .map_err(prompt_error)?;
+        describe(self, "  ", check, review)?;
+        confirm(self, "Apply it? [y/n] ")
+    }
+
+    fn approve_group(
+        &mut self,
+        group: &ApprovalGroup,
+        members: &[GroupMember<'_>],
+    ) -> Result<Option<bool>, NexusError> {
+        let total_cu = members
+            .iter()
+            .map(|member| action_cu(member.action))
+            .fold(0u32, u32::saturating_add);
+        self.say(&format!(
+            "\n{} ({} actions) [{total_cu} CU]",
+            group.label,
+            members.len()
+        ))
+        .map_err(prompt_error)?;
+        for member in members {
+            self.say(&format!(
+                "  {}: {} [{} CU] ({})",
+                member.action.id,
+                member.action.summary,
+                action_cu(member.action),
+                member.reason
+            ))
+            .map_err(prompt_error)?;
+            describe(self, "    ", member.check, member.review)?;
+        }
+        confirm(self, "Apply all of them? [y/n] ")
+    }
+}
+
+fn prompt_error(err: std::io::Error) -> NexusError {
+    NexusError::IoError {
+        operation: "prompt for approval".to_string(),
+        path: PathBuf::from("<terminal>"),
+        source: err,
+    }
+}
+
+/// Says, indented by `indent`, whether a patch no longer applies and what
+/// the reviewer made of it.
+fn describe<R: BufRead, W: Write>(
+    prompt: &mut LinePrompt<R, W>,
+    indent: &str,
+    check: Option<&PatchCheck>,
+    review: Option<&ActionReview>,
+) -> Result<(), NexusError> {
+    if let Some(failing_hunk) = check.and_then(|check| check.failing_hunk.as_deref()) {
+        prompt
+            .say(&format!("{indent}does not apply cleanly: {failing_hunk}"))
+            .map_err(prompt_error)?;
+    }
+    if let Some(review) = review {
+        for line in review.render().lines() {
+            prompt
+                .say(&format!("{indent}{line}"))
+                .map_err(prompt_error)?;
+        }
+    }
+    Ok(())
+}
+
+/// Asks `question` until it is answered yes or no.
+fn confirm<R: BufRead, W: Write>(
+    prompt: &mut LinePrompt<R, W>,
+    question: &str,
+) -> Result<Option<bool>, NexusError> {
+    loop {
+        let Some(line) = prompt.ask(question).map_err(prompt_error)? else {
+            return Ok(None);
+        };
+        match line.trim().to_ascii_lowercase().as_str() {
+            "y" | "yes" => return Ok(Some(true)),
+            "n" | "no" => return Ok(Some(false)),
+            other => prompt
+                .say(&format!("Unknown choice `{other}`"))
+                .map_err(prompt_error)?,
+        }
+    }
+}
+
+/// What resuming decided before the apply.
+#[derive(Debug, Default)]
+pub struct ResumeReport {
+    /// Actions proposed but not applied when the run stopped.
+    pub pending: usize,
+    pub granted: Vec<String>,
+    /// Actions denied now, with the reason.
+    pub denied: Vec<(String, String)>,
+    /// Actions still awaiting approval, with the reason. Hold them back
+    /// from the apply with [`RunApply::with_held`](crate::apply::RunApply::with_held).
+    pub awaiting: Vec<(String, String)>,
+}
+
+impl ResumeReport {
+    /// The decisions as JSON, for `--output-format json`.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "pending": self.pending,
+            "granted": self.granted,
+            "denied": reasons_json(&self.denied),
+            "awaiting": reasons_json(&self.awaiting),
+        })
+    }
+}
+
+/// Records decisions for the undecided pending actions of one run.
+pub struct RunResume<'a> {
+    root: PathBuf,
+    run_id: String,
+    gate: Option<PermissionGate>,
+    prompt: Option<&'a mut dyn ApprovalPrompt>,
+    redactor: Redactor,
+    sync_interval: Option<u64>,
+}
+
+impl<'a> RunResume<'a> {
+    pub fn new(root: impl Into<PathBuf>, run_id: impl Into<String>) -> Self {
+        Self {
+            root: root.into(),
+            run_id: run_id.into(),
+            gate: None,
+            prompt: None,
+            redactor: Redactor::new(),
+            sync_interval: None,
+        }
+    }
+
+    /// Decides actions with `gate`; without one, every action needs the
+    /// user's approval.
+    pub fn with_permission_gate(mut self, gate: PermissionGate) -> Self {
+        self.gate = Some(gate);
+        self
+    }
+
+    /// Asks `prompt` about actions the gate cannot decide. Without a
+    /// prompt they stay pending.
+    pub fn with_prompt(mut self, prompt: &'a mut dyn ApprovalPrompt) -> Self {
+        self.prompt = Some(prompt);
+        self
+    }
+
+    /// Masks secrets in the events this writes.
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    /// Syncs the log to disk every `events` events; see
+    /// [`EventLogWriter::with_sync_interval`].
+    pub fn with_sync_interval(mut self, events: Option<u64>) -> Self {
+        self.sync_interval = events;
+        self
+    }
+
+    /// Rehydrates the run's pending actions and records a decision for
+    /// each one the log has none for.
+    ///
+    /// Fails with `ValidationError` if the run stopped before proposing
+    /// anything; such a run has to be started again with `nexus retry`.
+    pub fn run(mut self) -> Result<ResumeReport, NexusError> {
+        let log_path = EventLogPath::new(&self.root).for_run(&self.run_id)?;
+        let events = EventLogReader::open(&log_path)?.load_all()?;
+        if !events
+            .iter()
+            .any(|event| event.event_type == "action.proposed")
+        {
+            return Err(NexusError::ValidationError {
+                message: format!(
+                    "run {} stopped before proposing any actions; start it again with `nexus retry {}`",
+                    self.run_id, self.run_id
+                ),
+                field: None,
+            });
+        }
+        let decided: HashSet<&str> = events
+            .iter()
+            .filter(|event| event.event_type.as_str().starts_with("permission."))
+            .flat_map(|event| event.action_ids())
+            .collect();
+        let pending = pending_actions(&log_path)?;
+        let reviews = reviews(&events);
+
+        let mut report = ResumeReport {
+            pending: pending.len(),
+            ..ResumeReport::default()
+        };
+        let checks = Applier::new(&self.root).check(&pending);
+        let trace = RunTrace::for_log(&log_path)?.unwrap_or_else(RunTrace::from_env);
+        let mut writer = EventLogWriter::open(&log_path)?
+            .with_redactor(self.redactor.clone())
+            .with_sync_interval(self.sync_interval)
+            .with_trace(trace.span());
+        let mut audit: Option<AuditOutcome> = None;
+        let mut undecided = Vec::new();
+        for (action, check) in pending
+            .iter()
+            .zip(&checks)
+            .filter(|(action, _)| !decided.contains(action.id.as_str()))
+        {
+            if let Some(check) = check {
+                writer.append(&helpers::action_validated(
+                    &self.run_id,
+                    &action.id,
+                    check.applies_cleanly,
+                    check.failing_hunk.as_deref(),
+                ))?;
+            }
+            let decision = match &self.gate {
+                Some(gate) => audited_decision(
+                    gate,
+                    &self.root,
+                    &self.run_id,
+                    action,
+                    &mut audit,
+                    &mut writer,
+                )?,
+                None => PolicyDecision::new(Decision::Ask, "no permission policy configured"),
+            };
+            undecided.push((action, check.as_ref(), decision));
+        }
+
+        if let Some(prompt) = self.prompt.as_deref_mut() {
+            let asked: Vec<GroupMember<'_>> = undecided
+                .iter()
+                .filter(|(_, _, decision)| decision.decision == Decision::Ask)
+                .map(|(action, check, decision)| GroupMember {
+                    action,
+                    reason: &decision.reason,
+                    check: *check,
+                    review: reviews.get(&action.id),
+                })
+                .collect();
+            if !asked.is_empty() {
+                prompt.preview(&asked)?;
+            }
+        }
+
+        let mut prompted_groups = HashSet::new();
+        for (action, check, decision) in &undecided {
+            let event = match decision.decision {
+                Decision::Allow => {
+                    report.granted.push(action.id.clone());
+                    allowed_event(&self.run_id, action, decision)
+                }
+                Decision::Deny => {
+                    report
+                        .denied
+                        .push((action.id.clone(), decision.reason.clone()));
+                    helpers::permission_denied(&self.run_id, &action.id, &decision.reason)
+                }
+                Decision::Ask => {
+                    if let Some(group) = &action.approval_group {
+                        let members = group_members(&undecided, &group.id, &reviews);
+                        if members.len() > 1 {
+                            if prompted_groups.insert(group.id.as_str()) {
+                                self.decide_group(group, &members, &mut writer, &mut report)?;
+                            }
+                            continue;
+                        }
+                    }
+                    let answer = match self.prompt.as_deref_mut() {
+                        Some(prompt) => prompt.approve(
+                            action,
+                            &decision.reason,
+                            *check,
+                            reviews.get(&action.id),
+                        )?,
+                        None => None,
+                    };
+                    match answer {
+                        Some(true) => {
+                            report.granted.push(action.id.clone());
+                            helpers::permission_granted(&self.run_id, &action.id, PROMPT_SCOPE)
+                        }
+                        Some(false) => {
+                            let reason = "declined by user";
+                            report.denied.push((action.id.clone(), reason.to_string()));
+                            helpers::permission_denied(&self.run_id, &action.id, reason)
+                        }
+                        None => {
+                            report.awaiting.push((
+                                action.id.clone(),
+                                format!("awaiting approval: {}", decision.reason),
+                            ));
+                            continue;
+                        }
+                    }
+                }
+            };
+            writer.append(&event)?;
+        }
+        writer.sync()?;
+        Ok(report)
+    }
+
+    /// Puts the `members` of `group` to the user as one decision, recorded
+    /// as a single permission event naming all of them.
+    fn decide_group(
+        &mut self,
+        group: &ApprovalGroup,
+        members: &[GroupMember<'_>],
+        writer: &mut EventLogWriter,
+        report: &mut ResumeReport,
+    ) -> Result<(), NexusError> {
+        let answer = match self.prompt.as_deref_mut() {
+            Some(prompt) => prompt.approve_group(group, members)?,
+            None => None,
+        };
+        let ids: Vec<String> = members
+            .iter()
+            .map(|member| member.action.id.clone())
+            .collect();
+        let event = match answer {
+            Some(true) => {
+                report.granted.extend(ids.iter().cloned());
+                helpers::permission_granted_group(&self.run_id, &group.id, &ids, PROMPT_SCOPE)
+            }
+            Some(false) => {
+                let reason = "declined by user";
+                report
+                    .denied
+                    .extend(ids.iter().map(|id| (id.clone(), reason.to_string())));
+                helpers::permission_denied_group(&self.run_id, &group.id, &ids, reason)
+            }
+            None => {
+                report.awaiting.extend(members.iter().map(|member| {
+                    (
+                        member.action.id.clone(),
+                        format!("awaiting approval: {}", member.reason),
+                    )
+                }));
+                return Ok(());
+            }
+        };
+        writer.append(&event)
+    }
+}
+
+/// The actions of approval group `group_id` among `undecided` that the
+/// user has to decide on.
+fn group_members<'a>(
+    undecided: &'a [(&'a ProposedAction, Option<&'a PatchCheck>, PolicyDecision)],
+    group_id: &str,
+    reviews: &'a HashMap<String, ActionReview>,
+) -> Vec<GroupMember<'a>> {
+    undecided
+        .iter()
+        .filter(|(action, _, decision)| {
+            decision.decision == Decision::Ask
+                && action
+                    .approval_group
+                    .as_ref()
+                    .is_some_and(|group| group.id == group_id)
+        })
+        .map(|(action, check, decision)| GroupMember {
+            action,
+            reason: &decision.reason,
+            check: *check,
+            review: reviews.get(&action.id),
+        })
+        .collect()
+}
+
+/// Records `run.completed` for a resumed run and returns its status.
+///
+/// The run succeeded if nothing is left pending, and is partially applied
+/// if some but not all of its actions were applied. A run whose applied
+/// actions were all undone, e.g. after a failed verify check, is rolled back.
+/// The log is synced every `sync_interval` events, as
+/// [`EventLogWriter::with_sync_interval`] does.
+pub fn finish_run(
+    root: &Path,
+    run_id: &str,
+    redactor: &Redactor,
+    sync_interval: Option<u64>,
+) -> Result<RunStatus, NexusError> {
+    let log_path = EventLogPath::new(root).for_run(run_id)?;
+    let events = EventLogReader::open(&log_path)?.load_all()?;
+    let applied = events
+        .iter()
+        .filter(|event| event.event_type == "tool.executed")
+        .count();
+    let status = if applied > 0 && applied_actions(&events).is_empty() {
+        RunStatus::RolledBack
+    } else if pending_actions(&log_path)?.is_empty() {
+        RunStatus::Success
+    } else if applied > 0 {
+        RunStatus::PartiallyApplied
+    } else {
+        RunStatus::ProposedPendingApply
+    };
+
+    let trace = RunTrace::for_log(&log_path)?.unwrap_or_else(RunTrace::from_env);
+    let mut writer = EventLogWriter::open(&log_path)?
+        .with_redactor(redactor.clone())
+        .with_sync_interval(sync_interval)
+        .with_trace(trace.span());
+    writer.append(&helpers::run_completed(
+        run_id,
+        status,
+        u32::try_from(applied).unwrap_or(u32::MAX),
+    ))?;
+    writer.sync()?;
+    Ok(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apply::RunApply;
+    use crate::event_log::PayloadStore;
+    use crate::types::{ApprovalRule, NexusSettings, RunEvent};
+
+    /// Writes an interrupted run: three proposals and nothing after them.
+    fn interrupted_run(root: &Path) {
+        interrupted_run_grouped(root, None);
+    }
+
+    /// Like [`interrupted_run`], with the first two actions in approval
+    /// group `group`, if given.
+    fn interrupted_run_grouped(root: &Path, group: Option<&str>) {
+        for file in ["a.txt", "b.txt", ".env"] {
+            std::fs::write(root.join(file), "one\n").unwrap();
+        }
+        let log_path = EventLogPath::new(root).for_run("run_1").unwrap();
+        let mut writer = EventLogWriter::open(&log_path).unwrap();
+        writer
+            .append(&helpers::run_started("run_1", "use digits"))
+            .unwrap();
+        for (id, file, risk) in [
+            ("act_1", "a.txt", 1),
+            ("act_2", "b.txt", 3),
+            ("act_3", ".env", 1),
+        ] {
+            let diff = format!("--- a/{file}\n+++ b/{file}\n@@ -1 +1 @@\n-one\n+1\n");
+            let action = ProposedAction::test_diff(id, &diff)
+                .with_summary(format!("Change {file}"))
+                .with_risk(risk)
+                .with_group(group.filter(|_| id != "act_3").map(|group| ApprovalGroup {
+                    id: group.to_string(),
+                    label: "Setup".to_string(),
+                    size: 2,
+                    index: if id == "act_1" { 0 } else { 1 },
+                }));
+            let payload_ref = PayloadStore::for_log(&log_path, "run_1")
+                .unwrap()
+                .write(
+                    &format!("{id}.json"),
+                    &serde_json::to_vec(&action).unwrap(),
+                    "application/json",
+                    "proposed action",
+                )
+                .unwrap();
+            writer
+                .append(
+                    &helpers::action_proposed("run_1", id, "patch", &action.summary, None)
+                        .with_payload_ref(payload_ref),
+                )
+                .unwrap();
+        }
+        writer.sync().unwrap();
+    }
+
+    fn gate() -> PermissionGate {
+        PermissionGate::from_settings(&NexusSettings {
+            approval_rules: vec![ApprovalRule {
+                kinds: Vec::new(),
+                min_risk: None,
+                max_risk: Some(1),
+                policy_tags: Vec::new(),
+                within_allow_paths_write: false,
+                modes: Vec::new(),
+                decision: Decision::Allow,
+            }],
+            ..NexusSettings::default()
+        })
+        .unwrap()
+    }
+
+    fn events(root: &Path) -> Vec<RunEvent> {
+        let log_path = EventLogPath::new(root).for_run("run_1").unwrap();
+        EventLogReader::open(&log_path).unwrap().load_all().unwrap()
+    }
+
+    #[test]
+    fn test_resume_decides_and_applies_pending_actions() {
+        let dir = tempfile::tempdir().unwrap();
+        interrupted_run(dir.path());
+
+        let mut prompt = LinePrompt::new(std::io::Cursor::new("maybe\ny\n"), Vec::new());
+        let report = RunResume::new(dir.path(), "run_1")
+            .with_permission_gate(gate())
+            .with_prompt(&mut prompt)
+            .run()
+            .unwrap();
+        assert_eq!(report.pending, 3);
+        assert_eq!(report.granted, ["act_1", "act_2"]);
+        assert_eq!(report.denied[0].0, "act_3");
+        assert!(report.awaiting.is_empty());
+
+        let applied = RunApply::new(dir.path(), "run_1").run().unwrap();
+        assert_eq!(applied.applied.len(), 2);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("b.txt")).unwrap(),
+            "1\n"
+        );
+        assert_eq!(
+            finish_run(dir.path(), "run_1", &Redactor::new(), None).unwrap(),
+            RunStatus::PartiallyApplied
+        );
+        assert_eq!(
+            events(dir.path()).last().unwrap().event_type,
+            "run.completed"
+        );
+        // Resume, apply and finish events share one correlation ID.
+        let correlation: HashSet<_> = events(dir.path())[4..]
+            .iter()
+            .map(|event| event.trace.as_ref().unwrap().correlation_id.clone())
+            .collect();
+        assert_eq!(correlation.len(), 1);
+
+        // Everything is decided now; resuming again records nothing new.
+        let before = events(dir.path()).len();
+        let report = RunResume::new(dir.path(), "run_1")
+            .with_permission_gate(gate())
+            .run()
+            .unwrap();
+        assert_eq!(report.pending, 1);
+        assert!(report.granted.is_empty() && report.denied.is_empty());
+        assert_eq!(events(dir.path()).len(), before);
+    }
+
+    /// Sees every question up front and approves what it was shown.
+    #[derive(Default)]
+    struct UpFront {
+        previewed: Vec<String>,
+    }
+
+    impl ApprovalPrompt for UpFront {
+        fn preview(&mut self, actions: &[GroupMember<'_>]) -> Result<(), NexusError> {
+            self.previewed = actions
+                .iter()
+                .map(|member| member.action.id.clone())
+                .collect();
+            Ok(())
+        }
+
+        fn approve(
+            &mut self,
+            action: &ProposedAction,
+            _reason: &str,
+            _check: Option<&PatchCheck>,
+            _review: Option<&ActionReview>,
+        ) -> Result<Option<bool>, NexusError> {
+            Ok(Some(self.previewed.contains(&action.id)))
+        }
+
+        fn approve_group(
+            &mut self,
+            _group: &ApprovalGroup,
+            _members: &[GroupMember<'_>],
+        ) -> Result<Option<bool>, NexusError> {
+            Ok(None)
+        }
+    }
+
+    #[test]
+    fn test_prompt_previews_only_what_it_is_asked() {
+        let dir = tempfile::tempdir().unwrap();
+        interrupted_run(dir.path());
+
+        let mut prompt = UpFront::default();
+        let report = RunResume::new(dir.path(), "run_1")
+            .with_permission_gate(gate())
+            .with_prompt(&mut prompt)
+            .run()
+            .unwrap();
+        assert_eq!(prompt.previewed, ["act_2"]);
+        assert_eq!(report.granted, ["act_1", "act_2"]);
+    }
+
+    #[test]
+    fn test_unanswered_actions_stay_pending() {
+        let dir = tempfile::tempdir().unwrap();
+        interrupted_run(dir.path());
+
+        let report = RunResume::new(dir.path(), "run_1")
+            .with_permission_gate(gate())
+            .run()
+            .unwrap();
+        assert_eq!(
+            report.awaiting,
+            [(
+                "act_2".to_string(),
+                "awaiting approval: no approval rule matched".to_string()
+            )]
+        );
+
+        let mut apply = RunApply::new(dir.path(), "run_1");
+        for (id, reason) in &report.awaiting {
+            apply = apply.with_held(id, reason);
+        }
+        let applied = apply.run().unwrap();
+        assert_eq!(applied.applied.len(), 1);
+        assert!(applied.skipped.contains(&(
+            "act_2".to_string(),
+            "awaiting approval: no approval rule matched".to_string()
+        )));
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("b.txt")).unwrap(),
+            "one\n"
+        );
+    }
+
+    #[test]
+    fn test_prompt_warns_about_patches_that_no_longer_apply() {
+        let dir = tempfile::tempdir().unwrap();
+        interrupted_run(dir.path());
+        std::fs::write(dir.path().join("b.txt"), "two\n").unwrap();
+
+        let mut output = Vec::new();
+        let mut prompt = LinePrompt::new(std::io::Cursor::new("n\n"), &mut output);
+        RunResume::new(dir.path(), "run_1")
+            .with_permission_gate(gate())
+            .with_prompt(&mut prompt)
+            .run()
+            .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(
+            output.contains("does not apply cleanly: b.txt: hunk 1 does not match the file"),
+            "{output}"
+        );
+
+        let validated: Vec<_> = events(dir.path())
+            .into_iter()
+            .filter(|event| event.event_type == "action.validated")
+            .map(|event| event.payload.unwrap())
+            .collect();
+        assert_eq!(validated.len(), 3);
+        assert_eq!(validated[0]["applies_cleanly"], true);
+        assert_eq!(validated[1]["action_id"], "act_2");
+        assert_eq!(validated[1]["applies_cleanly"], false);
+    }
+
+    #[test]
+    fn test_prompt_shows_the_review() {
+        let dir = tempfile::tempdir().unwrap();
+        interrupted_run(dir.path());
+        let log_path = EventLogPath::new(dir.path()).for_run("run_1").unwrap();
+        let review = ActionReview {
+            confidence: 0.4,
+            concerns: vec!["b.txt still spells out two".to_string()],
+        };
+        let mut writer = EventLogWriter::open(&log_path).unwrap();
+        writer
+            .append(&helpers::action_reviewed("run_1", "act_2", &review))
+            .unwrap();
+        drop(writer);
+
+        let mut output = Vec::new();
+        let mut prompt = LinePrompt::new(std::io::Cursor::new("n\n"), &mut output);
+        RunResume::new(dir.path(), "run_1")
+            .with_permission_gate(gate())
+            .with_prompt(&mut prompt)
+            .run()
+            .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(
+            output.contains(
+                "act_2: Change b.txt [21 CU] (no approval rule matched)\n  review: 40% confident\n  - b.txt still spells out two\n"
+            ),
+            "{output}"
+        );
+    }
+
+    #[test]
+    fn test_approval_group_is_decided_at_once() {
+        let dir = tempfile::tempdir().unwrap();
+        interrupted_run_grouped(dir.path(), Some("setup"));
+
+        let mut output = Vec::new();
+        let mut prompt = LinePrompt::new(std::io::Cursor::new("y\nn\n"), &mut output);
+        let report = RunResume::new(dir.path(), "run_1")
+            .with_prompt(&mut prompt)
+            .run()
+            .unwrap();
+        assert_eq!(report.granted, ["act_1", "act_2"]);
+        assert_eq!(report.denied[0].0, "act_3");
+        let output = String::from_utf8(output).unwrap();
+        assert!(
+            output.contains(
+                "Setup (2 actions) [32 CU]\n  act_1: Change a.txt [11 CU] (no permission policy configured)\n"
+            ),
+            "{output}"
+        );
+        assert_eq!(output.matches("Apply all of them?").count(), 1);
+
+        let granted: Vec<_> = events(dir.path())
+            .into_iter()
+            .filter(|event| event.event_type == "permission.granted")
+            .collect();
+        assert_eq!(granted.len(), 1);
+        assert_eq!(granted[0].action_ids(), ["act_1", "act_2"]);
+        assert_eq!(granted[0].payload.as_ref().unwrap()["group_id"], "setup");
+
+        let applied = RunApply::new(dir.path(), "run_1").run().unwrap();
+        assert_eq!(applied.applied.len(), 2);
+        assert_eq!(
+            applied.skipped,
+            [("act_3".to_string(), "permission denied".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_run_without_proposals_cannot_resume() {
+        let dir = tempfile::tempdir().unwrap();
+        let log_path = EventLogPath::new(dir.path()).for_run("run_1").unwrap();
+        let mut writer = EventLogWriter::open(&log_path).unwrap();
+        writer
+            .append(&helpers::run_started("run_1", "use digits"))
+            .unwrap();
+        writer.sync().unwrap();
+        drop(writer);
+
+        let err = RunResume::new(dir.path(), "run_1").run().unwrap_err();
+        assert!(err.to_string().contains("nexus retry run_1"), "{err}");
+    }
+}
@@ -0,0 +1,179 @@
+//! Re-running a previous run's task with modified parameters.
+//!
+//! The task, context files, and model are read back from the original run's
+//! log. The new run records the original run ID as `retry_of` in its
+//! `run.started` event so experiment lineage stays visible, and keeps the
+//! original's `parent_run_id`.
+
+use std::path::Path;
+
+use crate::batch::{BatchTask, BatchTaskOptions};
+use crate::error::NexusError;
+use crate::event_log::{EventLogPath, EventLogReader};
+use crate::types::{RunEvent, RunEventKind, RunId};
+
+/// Parameters changed for the new run; `None` keeps the original.
+#[derive(Debug, Clone, Default)]
+pub struct RetryOverrides {
+    pub model: Option<String>,
+    pub temperature: Option<f32>,
+    /// Appended to the original task as an extra paragraph.
+    pub instruction: Option<String>,
+}
+
+/// What a run asked for, as recorded in its event log.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetrySource {
+    pub run_id: String,
+    pub task: String,
+    pub files: Vec<String>,
+    pub model: Option<String>,
+    /// Run the original was spawned by; the retry keeps the link.
+    pub parent_run_id: Option<RunId>,
+}
+
+impl RetrySource {
+    /// Loads the original run `run_id` from `.nexus/runs/` under `root`.
+    pub fn load(root: &Path, run_id: &str) -> Result<Self, NexusError> {
+        let log_path = EventLogPath::new(root).for_run(run_id)?;
+        let events = EventLogReader::open(&log_path)?.load_all()?;
+        Self::from_events(run_id, &events)
+    }
+
+    /// Extracts the task, files, and model from a run's events.
+    pub fn from_events(run_id: &str, events: &[RunEvent]) -> Result<Self, NexusError> {
+        let started = events
+            .iter()
+            .find(|event| event.event_type == "executor.started");
+        let task = events
+            .iter()
+            .filter(|event| {
+                matches!(
+                    event.event_type.as_str(),
+                    "run.started" | "executor.started"
+                )
+            })
+            .find_map(|event| payload(event)?.get("task")?.as_str())
+            .ok_or_else(|| NexusError::ValidationError {
+                message: format!("run {run_id} has no recorded task to retry"),
+                field: Some("task".to_string()),
+            })?;
+        let files = started
+            .and_then(|event| payload(event)?.get("files")?.as_array())
+            .map(|files| {
+                files
+                    .iter()
+                    .filter_map(|file| file.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let model = started
+            .and_then(|event| payload(event)?.get("model")?.as_str())
+            .map(str::to_string);
+        let parent_run_id = events
+            .iter()
+            .find(|event| event.event_type == RunEventKind::RunStarted)
+            .and_then(|event| event.parent_run_id.as_deref())
+            .map(RunId::new)
+            .transpose()?;
+
+        Ok(Self {
+            run_id: run_id.to_string(),
+            task: task.to_string(),
+            files,
+            model,
+            parent_run_id,
+        })
+    }
+
+    /// Builds the task for the new run with `overrides` applied.
+    ///
+    /// Recorded file paths are escaped so they match literally.
+    pub fn to_task(&self, overrides: &RetryOverrides) -> BatchTask {
+        let task = match overrides.instruction.as_deref().map(str::trim) {
+            Some(instruction) if !instruction.is_empty() => {
+                format!("{}\n\n{instruction}", self.task)
+            }
+            _ => self.task.clone(),
+        };
+        BatchTask {
+            task,
+            files: self
+                .files
+                .iter()
+                .map(|path| globset::escape(path))
+                .collect(),
+            options: BatchTaskOptions {
+                temperature: overrides.temperature,
+                ..BatchTaskOptions::default()
+            },
+            parent_run_id: self.parent_run_id.clone(),
+        }
+    }
+
+    /// Model for the new run: the override, else the original model.
+    pub fn model<'a>(&'a self, overrides: &'a RetryOverrides) -> Option<&'a str> {
+        overrides.model.as_deref().or(self.model.as_deref())
+    }
+}
+
+fn payload(event: &RunEvent) -> Option<&serde_json::Value> {
+    event.payload.as_ref()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_log::helpers;
+
+    fn events() -> Vec<RunEvent> {
+        vec![
+            helpers::run_started("run_1", "rename a"),
+            helpers::executor_started("run_1", "rename a", &["src/[id].rs".to_string()], "gpt-old"),
+        ]
+    }
+
+    #[test]
+    fn test_from_events_reads_task_files_and_model() {
+        let source = RetrySource::from_events("run_1", &events()).unwrap();
+        assert_eq!(source.task, "rename a");
+        assert_eq!(source.files, ["src/[id].rs"]);
+        assert_eq!(source.model.as_deref(), Some("gpt-old"));
+    }
+
+    #[test]
+    fn test_from_events_requires_task() {
+        let events = vec![helpers::executor_completed("run_1", 0, 5)];
+        assert!(RetrySource::from_events("run_1", &events).is_err());
+    }
+
+    #[test]
+    fn test_to_task_applies_overrides() {
+        let source = RetrySource::from_events("run_1", &events()).unwrap();
+        let overrides = RetryOverrides {
+            model: Some("gpt-new".to_string()),
+            temperature: Some(0.2),
+            instruction: Some("Keep the old name as a deprecated alias.".to_string()),
+        };
+
+        let task = source.to_task(&overrides);
+        assert_eq!(
+            task.task,
+            "rename a\n\nKeep the old name as a deprecated alias."
+        );
+        assert_eq!(task.files, ["src/[[]id[]].rs"]);
+        assert_eq!(task.options.temperature, Some(0.2));
+        assert_eq!(source.model(&overrides), Some("gpt-new"));
+        assert_eq!(source.model(&RetryOverrides::default()), Some("gpt-old"));
+        assert_eq!(task.parent_run_id, None);
+    }
+
+    #[test]
+    fn test_retry_keeps_parent_run() {
+        let mut events = events();
+        events[0] = events[0].clone().with_parent_run("run_0");
+        let source = RetrySource::from_events("run_1", &events).unwrap();
+        let task = source.to_task(&RetryOverrides::default());
+        assert_eq!(task.parent_run_id.as_deref(), Some("run_0"));
+    }
+}
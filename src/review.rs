@@ -0,0 +1,145 @@
+//! Reviewer pass over proposed actions before they are approved.
+//!
+//! With `review_actions` on, each proposed action is sent with the run's
+//! task to the Reviewer-role adapter ([`REVIEW_SYSTEM_PROMPT`]), which
+//! answers with concerns and a confidence score. The [`ActionReview`] is
+//! logged as `action.reviewed` and shown at the approval prompt. A review
+//! only informs the decision; it never approves or denies anything itself.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::NexusError;
+use crate::types::{ProposedAction, RunEvent, RunEventKind};
+
+/// Tokens a review may use.
+pub const REVIEW_MAX_TOKENS: u32 = 600;
+
+/// System prompt of the reviewer judging one proposed action.
+pub const REVIEW_SYSTEM_PROMPT: &str = r#"You are an expert code reviewer. You are given a task and one change another agent proposed for it. Judge whether the change does what the task asks, and correctly.
+
+Reply with one JSON object and nothing else:
+{"confidence": <0.0 to 1.0, how sure you are the change is correct and complete>, "concerns": ["<one concrete problem per entry>"]}
+
+List only real problems: bugs, missing cases, changes outside the task. Use an empty list if there are none."#;
+
+/// What the reviewer made of one action.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActionReview {
+    /// How sure the reviewer is that the action is right, from 0 to 1.
+    pub confidence: f64,
+    #[serde(default)]
+    pub concerns: Vec<String>,
+}
+
+impl ActionReview {
+    /// Reads the review from the reviewer's `reply`.
+    ///
+    /// # Errors
+    /// - `NexusError::ResponseParseFailed` if the reply holds no JSON object
+    /// - `NexusError::JsonError` if the object is not a review
+    pub fn parse(reply: &str) -> Result<Self, NexusError> {
+        let json = match (reply.find('{'), reply.rfind('}')) {
+            (Some(start), Some(end)) if start < end => &reply[start..=end],
+            _ => {
+                return Err(NexusError::ResponseParseFailed {
+                    context: "review response contains no JSON object".to_string(),
+                    raw_response: Some(reply.to_string()),
+                });
+            }
+        };
+        let mut review: Self = serde_json::from_str(json)?;
+        review.confidence = if review.confidence.is_finite() {
+            review.confidence.clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        review.concerns = review
+            .concerns
+            .into_iter()
+            .map(|concern| concern.trim().to_string())
+            .filter(|concern| !concern.is_empty())
+            .collect();
+        Ok(review)
+    }
+
+    /// One line per part of the review, as shown at the approval prompt.
+    pub fn render(&self) -> String {
+        let mut out = format!("review: {:.0}% confident", self.confidence * 100.0);
+        if self.concerns.is_empty() {
+            out.push_str(", no concerns");
+        }
+        for concern in &self.concerns {
+            let _ = write!(out, "\n- {concern}");
+        }
+        out
+    }
+}
+
+/// What the reviewer is sent about `action`: the task, the action, and
+/// its changes rendered as in [`crate::preview::render_dry_run`].
+pub fn review_request_body(task: &str, action: &ProposedAction, changes: &str) -> String {
+    let mut out = format!("## Task\n{task}\n\n## Proposed change\n{}", action.summary);
+    if let Some(why) = &action.why {
+        let _ = write!(out, "\nWhy: {why}");
+    }
+    let _ = write!(out, "\n\n{changes}");
+    out
+}
+
+/// The latest review of each action in a run's `events`, by action ID.
+pub fn reviews(events: &[RunEvent]) -> HashMap<String, ActionReview> {
+    events
+        .iter()
+        .filter(|event| event.event_type == RunEventKind::ActionReviewed)
+        .filter_map(|event| {
+            let payload = event.payload.as_ref()?;
+            let id = payload.get("action_id")?.as_str()?.to_string();
+            let review = serde_json::from_value(payload.clone()).ok()?;
+            Some((id, review))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_log::helpers::action_reviewed;
+
+    #[test]
+    fn test_parse_review_reply() {
+        let review = ActionReview::parse(
+            "Looks mostly fine.\n{\"confidence\": 1.4, \"concerns\": [\" misses the None case \", \"\"]}",
+        )
+        .unwrap();
+        assert_eq!(review.confidence, 1.0);
+        assert_eq!(review.concerns, ["misses the None case"]);
+        assert_eq!(
+            review.render(),
+            "review: 100% confident\n- misses the None case"
+        );
+
+        assert!(matches!(
+            ActionReview::parse("no idea"),
+            Err(NexusError::ResponseParseFailed { .. })
+        ));
+    }
+
+    #[test]
+    fn test_reviews_are_read_back_from_events() {
+        let review = ActionReview {
+            confidence: 0.5,
+            concerns: Vec::new(),
+        };
+        let events = vec![action_reviewed("run_1", "act_1", &review)];
+
+        let reviews = reviews(&events);
+        assert_eq!(reviews.get("act_1"), Some(&review));
+        assert_eq!(
+            reviews["act_1"].render(),
+            "review: 50% confident, no concerns"
+        );
+    }
+}
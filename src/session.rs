@@ -0,0 +1,239 @@
+//! Multi-turn refinement of a run's proposals.
+//!
+//! A [`Session`] is the conversation behind a run: the messages its
+//! executor pass sent, the model's reply, and the actions parsed from it.
+//! Nothing extra is stored for it; it is read back from the run's
+//! `transcript.json` and action artifacts. `nexus refine` continues the
+//! conversation with a new user turn holding the instruction and the
+//! current contents of the run's files, as a new run linked through
+//! `refine_of` in its `run.started` event. That run's own transcript then
+//! holds the whole conversation, so refining it again adds another turn.
+
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::batch::BatchTask;
+use crate::error::NexusError;
+use crate::event_log::{EventLogPath, EventLogReader, PayloadStore};
+use crate::executor::ChatMessage;
+use crate::executor::tools::PROPOSE_ACTIONS_TOOL;
+use crate::preview::load_action_artifact;
+use crate::retry::{RetryOverrides, RetrySource};
+use crate::types::{ProposedAction, RunEvent, RunEventKind};
+
+/// Added to each refinement so the model answers with a whole new set of
+/// actions rather than only the ones the instruction touches.
+const REVISE_NOTE: &str = "Revise the actions you proposed above accordingly. Reply with the \
+complete revised set of actions; it replaces the previous one.";
+
+/// The conversation of one run.
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub run_id: String,
+    /// Messages sent for the run's last executor pass, ending with the
+    /// model's reply.
+    pub messages: Vec<ChatMessage>,
+    /// Actions the run proposed.
+    pub actions: Vec<ProposedAction>,
+    /// Task, files, and model the run was started with.
+    pub source: RetrySource,
+}
+
+/// The parts of a stored provider exchange a session needs.
+#[derive(Deserialize)]
+struct StoredExchange {
+    request: StoredRequest,
+    #[serde(default)]
+    response: String,
+    #[serde(default)]
+    tool_calls: Vec<StoredToolCall>,
+}
+
+#[derive(Deserialize)]
+struct StoredRequest {
+    messages: Vec<ChatMessage>,
+}
+
+#[derive(Deserialize)]
+struct StoredToolCall {
+    name: String,
+    arguments: String,
+}
+
+impl Session {
+    /// Loads the conversation of `run_id` from `.nexus/runs/` under `root`.
+    pub fn load(root: &Path, run_id: &str) -> Result<Self, NexusError> {
+        let log_path = EventLogPath::new(root).for_run(run_id)?;
+        let events = EventLogReader::open(&log_path)?.load_all()?;
+        let runs_dir = log_path.parent().unwrap_or_else(|| Path::new("."));
+        Self::from_events(runs_dir, run_id, &events)
+    }
+
+    /// Reads the conversation from the artifacts `events` reference, which
+    /// live under `runs_dir`.
+    pub fn from_events(
+        runs_dir: &Path,
+        run_id: &str,
+        events: &[RunEvent],
+    ) -> Result<Self, NexusError> {
+        let source = RetrySource::from_events(run_id, events)?;
+        let no_conversation = || NexusError::ValidationError {
+            message: format!("run {run_id} has no recorded conversation to refine"),
+            field: Some("transcript".to_string()),
+        };
+        let transcript = events
+            .iter()
+            .rev()
+            .filter(|event| event.event_type == RunEventKind::ExecutorCompleted)
+            .find_map(|event| event.payload_ref.as_ref())
+            .ok_or_else(no_conversation)?;
+        let bytes = PayloadStore::read(runs_dir, transcript)?;
+        let exchanges: Vec<StoredExchange> =
+            serde_json::from_slice(&bytes).map_err(|source| NexusError::JsonError {
+                context: format!("failed to parse the transcript of {run_id}"),
+                source,
+            })?;
+        // Later exchanges are follow-ups, e.g. asking for missing rationale.
+        let exchange = exchanges.into_iter().next().ok_or_else(no_conversation)?;
+
+        let mut messages = exchange.request.messages;
+        messages.push(ChatMessage {
+            role: "assistant".to_string(),
+            content: reply(exchange.response, &exchange.tool_calls),
+        });
+
+        let mut actions = Vec::new();
+        for event in events
+            .iter()
+            .filter(|event| event.event_type == RunEventKind::ActionProposed)
+        {
+            if let Some(payload_ref) = &event.payload_ref {
+                actions.push(load_action_artifact(runs_dir, payload_ref)?);
+            }
+        }
+
+        Ok(Self {
+            run_id: run_id.to_string(),
+            messages,
+            actions,
+            source,
+        })
+    }
+
+    /// Builds the task for the next turn: `instruction`, over the files the
+    /// run was given.
+    pub fn to_task(&self, instruction: &str) -> Result<BatchTask, NexusError> {
+        let instruction = instruction.trim();
+        if instruction.is_empty() {
+            return Err(NexusError::ValidationError {
+                message: "refine instruction is empty".to_string(),
+                field: Some("instruction".to_string()),
+            });
+        }
+        let mut task = self.source.to_task(&RetryOverrides::default());
+        task.task = format!("{instruction}\n\n{REVISE_NOTE}");
+        Ok(task)
+    }
+}
+
+/// What the model answered: its text, or the arguments of its
+/// `propose_actions` call when it answered with the tool instead.
+fn reply(response: String, tool_calls: &[StoredToolCall]) -> String {
+    if !response.trim().is_empty() {
+        return response;
+    }
+    tool_calls
+        .iter()
+        .find(|call| call.name == PROPOSE_ACTIONS_TOOL)
+        .map(|call| call.arguments.clone())
+        .unwrap_or(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_log::helpers;
+    use crate::types::{ActionDetails, PatchDetails};
+    use serde_json::json;
+
+    fn action() -> ProposedAction {
+        ProposedAction::test("act_1", ActionDetails::Patch(PatchDetails::default()))
+            .with_summary("rename a")
+    }
+
+    /// Events of a run whose only exchange answered with `exchange`.
+    fn events(runs_dir: &Path, exchange: serde_json::Value) -> Vec<RunEvent> {
+        let store = PayloadStore::new(runs_dir, "run_1").unwrap();
+        let write = |name: &str, bytes: Vec<u8>| {
+            store
+                .write(name, &bytes, "application/json", "test")
+                .unwrap()
+        };
+        let transcript = write(
+            "transcript.json",
+            json!([exchange]).to_string().into_bytes(),
+        );
+        let stored_action = write("action_001.json", serde_json::to_vec(&action()).unwrap());
+        vec![
+            helpers::run_started("run_1", "rename a"),
+            helpers::executor_started("run_1", "rename a", &["src/[id].rs".to_string()], "gpt-old"),
+            helpers::action_proposed("run_1", "act_1", "patch", "rename a", None)
+                .with_payload_ref(stored_action),
+            helpers::executor_completed("run_1", 1, 5).with_payload_ref(transcript),
+        ]
+    }
+
+    fn request() -> serde_json::Value {
+        json!({
+            "model": "gpt-old",
+            "stream": true,
+            "messages": [
+                {"role": "system", "content": "You propose changes."},
+                {"role": "user", "content": "## Task\nrename a"}
+            ]
+        })
+    }
+
+    #[test]
+    fn test_session_ends_with_the_reply_and_keeps_the_actions() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let events = events(
+            dir.path(),
+            json!({"request": request(), "response": "--- a/src/lib.rs"}),
+        );
+
+        let session = Session::from_events(dir.path(), "run_1", &events).unwrap();
+        let roles: Vec<_> = session.messages.iter().map(|m| m.role.as_str()).collect();
+        assert_eq!(roles, ["system", "user", "assistant"]);
+        assert_eq!(session.messages[2].content, "--- a/src/lib.rs");
+        assert_eq!(session.actions.len(), 1);
+        assert_eq!(session.actions[0].id, "act_1");
+
+        let task = session.to_task("  also update the tests\n").unwrap();
+        assert_eq!(task.task, format!("also update the tests\n\n{REVISE_NOTE}"));
+        assert_eq!(task.files, ["src/[[]id[]].rs"]);
+        assert!(session.to_task(" ").is_err());
+    }
+
+    #[test]
+    fn test_tool_call_reply_and_missing_transcript() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let events = events(
+            dir.path(),
+            json!({
+                "request": request(),
+                "response": "",
+                "tool_calls": [{"name": PROPOSE_ACTIONS_TOOL, "arguments": "{\"actions\":[]}"}]
+            }),
+        );
+        let session = Session::from_events(dir.path(), "run_1", &events).unwrap();
+        assert_eq!(session.messages[2].content, "{\"actions\":[]}");
+
+        let err = Session::from_events(dir.path(), "run_1", &events[..2]).unwrap_err();
+        assert!(
+            err.to_string().contains("no recorded conversation"),
+            "{err}"
+        );
+    }
+}
@@ -0,0 +1,374 @@
+//! Layered settings: defaults, then the user's global file, then the
+//! project's, then `NEXUS_*` environment variables, then CLI flags.
+//!
+//! Each file is read on its own (migrated, checked for unknown keys) and
+//! merged over the layers below it key by key, following
+//! [`merge_strategy`]; the environment is merged the same way, see
+//! [`env_layer`]. [`SettingSources`] records which layers set each
+//! top-level key, so `nexus config show` can tell where a value came from.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::path::PathBuf;
+
+use serde_json::{Map, Value, json};
+
+use crate::error::NexusError;
+use crate::types::{NexusSettings, SETTINGS_KEYS};
+
+use super::SECTION_KEYS;
+
+/// Prefix of the environment variables that override settings.
+pub const ENV_PREFIX: &str = "NEXUS_";
+
+/// Where a settings value came from, lowest layer first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SettingSource {
+    /// [`NexusSettings::default()`](crate::types::NexusSettings::default).
+    Default,
+    /// The user's global settings file, see [`user_settings_path`].
+    User,
+    /// The project's settings file (`--config`).
+    Project,
+    /// A `NEXUS_*` environment variable, see [`env_layer`].
+    Env,
+    /// A CLI flag, or the environment variable standing in for it.
+    Cli,
+}
+
+impl SettingSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::User => "user",
+            Self::Project => "project",
+            Self::Env => "env",
+            Self::Cli => "cli",
+        }
+    }
+}
+
+impl fmt::Display for SettingSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Layers that set each top-level settings key, lowest first. Keys no
+/// layer set have their default.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SettingSources(BTreeMap<String, Vec<SettingSource>>);
+
+impl SettingSources {
+    /// Layers that set `key`; `[Default]` if none did.
+    pub fn get(&self, key: &str) -> &[SettingSource] {
+        match self.0.get(key) {
+            Some(sources) => sources,
+            None => &[SettingSource::Default],
+        }
+    }
+
+    /// `key`'s layers joined with `+`, e.g. `user+project`.
+    pub fn describe(&self, key: &str) -> String {
+        self.get(key)
+            .iter()
+            .map(|source| source.as_str())
+            .collect::<Vec<_>>()
+            .join("+")
+    }
+
+    /// Records that `source` set `key`. A layer that replaces the value
+    /// hides the layers below it.
+    pub fn record(&mut self, key: &str, source: SettingSource) {
+        let sources = self.0.entry(key.to_string()).or_default();
+        if merge_strategy(key) == MergeStrategy::Replace {
+            sources.clear();
+        }
+        if !sources.contains(&source) {
+            sources.push(source);
+        }
+    }
+}
+
+/// How a layer's value for a key combines with the layers below it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// The higher layer's value wins.
+    Replace,
+    /// Lists are concatenated, lower layer first, dropping duplicates.
+    Append,
+    /// Objects are merged key by key, recursively; the higher layer wins
+    /// for keys both set.
+    Merge,
+}
+
+/// The [`MergeStrategy`] of top-level settings key `key`.
+///
+/// Lists that only make Nexus stricter append, so a project cannot drop
+/// denials the user set globally: `deny_paths`, `deny_commands`,
+/// `ask_commands`, `disallowed_licenses`, `redact_patterns`, and
+/// `approval_rules`. Objects merge: `policy_tag_rules`, `extra_headers`,
+/// `autopilot`, `anonymize`, `git`, and `verify`. Everything else,
+/// including the lists that grant access (`allow_paths_write`,
+/// `allow_commands`, `binary_allow_paths`), is replaced.
+pub fn merge_strategy(key: &str) -> MergeStrategy {
+    match key {
+        "deny_paths"
+        | "deny_commands"
+        | "ask_commands"
+        | "disallowed_licenses"
+        | "redact_patterns"
+        | "approval_rules" => MergeStrategy::Append,
+        "policy_tag_rules" | "extra_headers" | "autopilot" | "anonymize" | "git" | "verify" => {
+            MergeStrategy::Merge
+        }
+        _ => MergeStrategy::Replace,
+    }
+}
+
+/// The user's global settings file: `$XDG_CONFIG_HOME/nexus/settings.json`,
+/// else `~/.config/nexus/settings.json`. `None` if neither variable is set.
+pub fn user_settings_path() -> Option<PathBuf> {
+    let config_home = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            env::var_os("HOME")
+                .filter(|dir| !dir.is_empty())
+                .map(|home| PathBuf::from(home).join(".config"))
+        })?;
+    Some(config_home.join("nexus").join("settings.json"))
+}
+
+/// Merges the `layer` document from `source` over `merged`.
+pub fn merge_layer(
+    merged: &mut Map<String, Value>,
+    layer: Map<String, Value>,
+    source: SettingSource,
+    sources: &mut SettingSources,
+) {
+    for (key, value) in layer {
+        // The version only describes the file it was read from.
+        if key != "schema_version" {
+            sources.record(&key, source);
+        }
+        let value = match (merge_strategy(&key), merged.remove(&key), value) {
+            (MergeStrategy::Append, Some(Value::Array(mut lower)), Value::Array(higher)) => {
+                for item in higher {
+                    if !lower.contains(&item) {
+                        lower.push(item);
+                    }
+                }
+                Value::Array(lower)
+            }
+            (MergeStrategy::Merge, Some(Value::Object(mut lower)), Value::Object(higher)) => {
+                merge_objects(&mut lower, higher);
+                Value::Object(lower)
+            }
+            (_, _, value) => value,
+        };
+        merged.insert(key, value);
+    }
+}
+
+/// Name of the environment variable overriding `key`, or `key` of the
+/// `section` object: `NEXUS_` and the key in upper case, e.g.
+/// `NEXUS_PERMISSION_MODE`. Keys of objects other than `autopilot` are
+/// prefixed with the object's name (`NEXUS_GIT_REQUIRE_CLEAN`); autopilot
+/// keys already say what they belong to (`NEXUS_MAX_BATCH_CU`).
+pub fn env_var_name(section: Option<&str>, key: &str) -> String {
+    match section {
+        None | Some("autopilot") => format!("{ENV_PREFIX}{}", key.to_uppercase()),
+        Some(section) => format!(
+            "{ENV_PREFIX}{}_{}",
+            section.to_uppercase(),
+            key.to_uppercase()
+        ),
+    }
+}
+
+/// The settings document `vars` set, for every key but `schema_version`
+/// (see [`env_var_name`]). A value is read as JSON if that fits the key,
+/// else as a string, else as a comma-separated list of strings, so
+/// `NEXUS_MAX_BATCH_CU=20`, `NEXUS_MODEL=gpt-4o`, and
+/// `NEXUS_DENY_PATHS=secrets/**,build/**` all work. Variables that name
+/// no key are ignored.
+///
+/// # Errors
+/// `NexusError::ConfigError` naming the first variable whose value fits
+/// its key in none of these ways.
+pub fn env_layer(
+    vars: impl IntoIterator<Item = (String, String)>,
+) -> Result<Map<String, Value>, NexusError> {
+    let vars: BTreeMap<String, String> = vars
+        .into_iter()
+        .filter(|(name, _)| name.starts_with(ENV_PREFIX))
+        .collect();
+
+    let mut layer = Map::new();
+    for key in SETTINGS_KEYS.iter().filter(|key| **key != "schema_version") {
+        let name = env_var_name(None, key);
+        if let Some(raw) = vars.get(&name) {
+            let value = env_value(&name, raw, |value| json!({ *key: value }))?;
+            layer.insert(key.to_string(), value);
+        }
+    }
+    for (section, keys) in SECTION_KEYS {
+        for key in *keys {
+            let name = env_var_name(Some(section), key);
+            let Some(raw) = vars.get(&name) else {
+                continue;
+            };
+            let value = env_value(&name, raw, |value| json!({ *section: { *key: value } }))?;
+            let fields = layer
+                .entry(section.to_string())
+                .or_insert_with(|| Value::Object(Map::new()));
+            if !fields.is_object() {
+                *fields = Value::Object(Map::new());
+            }
+            if let Value::Object(fields) = fields {
+                fields.insert(key.to_string(), value);
+            }
+        }
+    }
+    Ok(layer)
+}
+
+/// The first reading of `raw` that deserializes once placed in a settings
+/// document by `document`.
+fn env_value(
+    name: &str,
+    raw: &str,
+    document: impl Fn(Value) -> Value,
+) -> Result<Value, NexusError> {
+    let list = raw
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| Value::String(item.to_string()))
+        .collect();
+    let candidates = serde_json::from_str(raw)
+        .ok()
+        .into_iter()
+        .chain([Value::String(raw.to_string()), Value::Array(list)]);
+
+    let mut first_error = None;
+    for candidate in candidates {
+        match serde_json::from_value::<NexusSettings>(document(candidate.clone())) {
+            Ok(_) => return Ok(candidate),
+            Err(err) => {
+                first_error.get_or_insert(err);
+            }
+        }
+    }
+    Err(NexusError::ConfigError {
+        message: format!("invalid value in {name}"),
+        path: None,
+        source: first_error.map(|err| Box::new(err) as _),
+    })
+}
+
+fn merge_objects(lower: &mut Map<String, Value>, higher: Map<String, Value>) {
+    for (key, value) in higher {
+        match (lower.get_mut(&key), value) {
+            (Some(Value::Object(lower)), Value::Object(higher)) => merge_objects(lower, higher),
+            (_, value) => {
+                lower.insert(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn object(value: Value) -> Map<String, Value> {
+        match value {
+            Value::Object(map) => map,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_layers_merge_per_key_strategy() {
+        let mut merged = Map::new();
+        let mut sources = SettingSources::default();
+        merge_layer(
+            &mut merged,
+            object(json!({
+                "model": "gpt-4o",
+                "deny_paths": ["secrets/**", ".env*"],
+                "allow_commands": [["cargo", "test"]],
+                "autopilot": {"max_batch_cu": 10, "auto_approve_tests": true},
+                "policy_tag_rules": {"network": "deny"}
+            })),
+            SettingSource::User,
+            &mut sources,
+        );
+        merge_layer(
+            &mut merged,
+            object(json!({
+                "model": "llama3",
+                "deny_paths": [".env*", "build/**"],
+                "allow_commands": [["npm", "test"]],
+                "autopilot": {"max_batch_cu": 20},
+                "policy_tag_rules": {"docs": "allow"}
+            })),
+            SettingSource::Project,
+            &mut sources,
+        );
+
+        assert_eq!(
+            Value::Object(merged),
+            json!({
+                "model": "llama3",
+                "deny_paths": ["secrets/**", ".env*", "build/**"],
+                "allow_commands": [["npm", "test"]],
+                "autopilot": {"max_batch_cu": 20, "auto_approve_tests": true},
+                "policy_tag_rules": {"network": "deny", "docs": "allow"}
+            })
+        );
+        assert_eq!(sources.describe("model"), "project");
+        assert_eq!(sources.describe("allow_commands"), "project");
+        assert_eq!(sources.describe("deny_paths"), "user+project");
+        assert_eq!(sources.describe("autopilot"), "user+project");
+        assert_eq!(sources.describe("verify"), "default");
+    }
+
+    #[test]
+    fn test_env_layer_reads_values_by_key_type() {
+        let vars = [
+            ("NEXUS_PERMISSION_MODE", "autopilot"),
+            ("NEXUS_MODEL", "4"),
+            ("NEXUS_MAX_BATCH_CU", "20"),
+            ("NEXUS_GIT_REQUIRE_CLEAN", "true"),
+            ("NEXUS_DENY_PATHS", "secrets/**, build/**"),
+            ("NEXUS_DENY_COMMANDS", r#"[["curl"]]"#),
+            ("NEXUS_CONFIG", "other.json"),
+            ("HOME", "/home/me"),
+        ]
+        .map(|(name, value)| (name.to_string(), value.to_string()));
+
+        let layer = env_layer(vars).unwrap();
+        assert_eq!(
+            Value::Object(layer),
+            json!({
+                "permission_mode": "autopilot",
+                "model": "4",
+                "autopilot": {"max_batch_cu": 20},
+                "git": {"require_clean": true},
+                "deny_paths": ["secrets/**", "build/**"],
+                "deny_commands": [["curl"]]
+            })
+        );
+
+        let err = env_layer([("NEXUS_MAX_BATCH_CU".to_string(), "lots".to_string())]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "configuration error: invalid value in NEXUS_MAX_BATCH_CU"
+        );
+    }
+}
@@ -0,0 +1,213 @@
+//! Upgrading settings documents written for an older `schema_version`.
+//!
+//! Each [`Migration`] takes a document from one schema version to the
+//! next; [`migrate`] runs them in order until the document is at
+//! [`SCHEMA_VERSION`]. Migrations work on the raw JSON before it is
+//! deserialized, so they can move or rename fields the current
+//! [`NexusSettings`](crate::types::NexusSettings) no longer has. Loading
+//! settings migrates them in memory and logs what changed;
+//! `nexus config migrate` writes the result back to the file. Versions
+//! newer than [`SCHEMA_VERSION`], or unknown ones, are left alone and fail
+//! validation.
+
+use serde_json::{Map, Value};
+
+use crate::types::SCHEMA_VERSION;
+
+/// Version of documents that do not set `schema_version`, which predate
+/// versioned settings.
+pub const UNVERSIONED_SCHEMA_VERSION: &str = "1.0";
+
+/// One step from schema version `from` to `to`.
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    pub from: &'static str,
+    pub to: &'static str,
+    /// Rewrites the document's fields, returning one line per change made.
+    /// `schema_version` is set by [`migrate`].
+    pub apply: fn(&mut Map<String, Value>) -> Vec<String>,
+}
+
+/// Every migration, oldest first.
+pub const MIGRATIONS: &[Migration] = &[Migration {
+    from: "1.0",
+    to: "1.1",
+    apply: tag_only_rules_to_policy_tag_rules,
+}];
+
+/// What [`migrate`] did to a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationReport {
+    pub from: String,
+    pub to: String,
+    /// Changes beyond the version bump, in the order they were made.
+    pub changes: Vec<String>,
+}
+
+/// Upgrades `document` to [`SCHEMA_VERSION`]. Returns `None` if it is
+/// already there, is not an object, or has a version no migration starts
+/// from.
+pub fn migrate(document: &mut Value) -> Option<MigrationReport> {
+    let fields = document.as_object_mut()?;
+    let from = match fields.get("schema_version") {
+        None => UNVERSIONED_SCHEMA_VERSION.to_string(),
+        Some(Value::String(version)) => version.clone(),
+        Some(_) => return None,
+    };
+
+    let mut version = from.clone();
+    let mut changes = Vec::new();
+    while version != SCHEMA_VERSION {
+        let migration = MIGRATIONS
+            .iter()
+            .find(|migration| migration.from == version)?;
+        changes.extend((migration.apply)(fields));
+        version = migration.to.to_string();
+    }
+    if version == from {
+        return None;
+    }
+    fields.insert("schema_version".to_string(), Value::String(version.clone()));
+    Some(MigrationReport {
+        from,
+        to: version,
+        changes,
+    })
+}
+
+/// 1.0 to 1.1: an `approval_rules` entry that only matches one policy tag
+/// becomes a `policy_tag_rules` entry. Both are weighed together with the
+/// strictest decision winning, so the move changes no outcome.
+fn tag_only_rules_to_policy_tag_rules(fields: &mut Map<String, Value>) -> Vec<String> {
+    let Some(Value::Array(rules)) = fields.get("approval_rules") else {
+        return Vec::new();
+    };
+
+    let mut moved = Vec::new();
+    let mut kept = Vec::new();
+    for (index, rule) in rules.iter().enumerate() {
+        match tag_only_rule(rule) {
+            Some((tag, decision)) => moved.push((index, tag, decision)),
+            None => kept.push(rule.clone()),
+        }
+    }
+    if moved.is_empty() {
+        return Vec::new();
+    }
+
+    let mut tag_rules = match fields.remove("policy_tag_rules") {
+        Some(Value::Object(tag_rules)) => tag_rules,
+        _ => Map::new(),
+    };
+    let mut changes = Vec::new();
+    for (index, tag, decision) in moved {
+        let stricter = tag_rules
+            .get(&tag)
+            .and_then(Value::as_str)
+            .and_then(strictness)
+            .is_none_or(|current| strictness(&decision) > Some(current));
+        if stricter {
+            tag_rules.insert(tag.clone(), Value::String(decision.clone()));
+        }
+        changes.push(format!(
+            "moved approval_rules[{index}] to policy_tag_rules.{tag} ({decision})"
+        ));
+    }
+
+    fields.insert("policy_tag_rules".to_string(), Value::Object(tag_rules));
+    if kept.is_empty() {
+        fields.remove("approval_rules");
+    } else {
+        fields.insert("approval_rules".to_string(), Value::Array(kept));
+    }
+    changes
+}
+
+/// The tag and decision of a rule whose only condition is a single policy
+/// tag.
+fn tag_only_rule(rule: &Value) -> Option<(String, String)> {
+    let rule = rule.as_object()?;
+    let decision = rule.get("decision")?.as_str()?;
+    strictness(decision)?;
+    let [Value::String(tag)] = rule.get("policy_tags")?.as_array()?.as_slice() else {
+        return None;
+    };
+    let unconditional = rule.iter().all(|(key, value)| match key.as_str() {
+        "policy_tags" | "decision" => true,
+        "kinds" | "modes" => value.as_array().is_some_and(Vec::is_empty),
+        "min_risk" | "max_risk" => value.is_null(),
+        "within_allow_paths_write" => *value == Value::Bool(false),
+        _ => false,
+    });
+    unconditional.then(|| (tag.clone(), decision.to_string()))
+}
+
+/// Orders decisions by how much they hold an action back.
+fn strictness(decision: &str) -> Option<u8> {
+    match decision {
+        "allow" => Some(0),
+        "notify" => Some(1),
+        "ask" => Some(2),
+        "deny" => Some(3),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_tag_only_rules_move_to_policy_tag_rules() {
+        let mut document = json!({
+            "schema_version": "1.0",
+            "permission_mode": "default",
+            "approval_rules": [
+                {"policy_tags": ["network"], "decision": "ask"},
+                {"kinds": ["patch"], "max_risk": 2, "decision": "allow"},
+                {"policy_tags": ["secrets"], "kinds": [], "decision": "deny"},
+                {"policy_tags": ["network"], "modes": ["autopilot"], "decision": "allow"}
+            ],
+            "policy_tag_rules": {"network": "deny", "docs": "allow"}
+        });
+
+        let report = migrate(&mut document).unwrap();
+        assert_eq!(report.from, "1.0");
+        assert_eq!(report.to, SCHEMA_VERSION);
+        assert_eq!(
+            report.changes,
+            [
+                "moved approval_rules[0] to policy_tag_rules.network (ask)",
+                "moved approval_rules[2] to policy_tag_rules.secrets (deny)",
+            ]
+        );
+        assert_eq!(
+            document,
+            json!({
+                "schema_version": SCHEMA_VERSION,
+                "permission_mode": "default",
+                "approval_rules": [
+                    {"kinds": ["patch"], "max_risk": 2, "decision": "allow"},
+                    {"policy_tags": ["network"], "modes": ["autopilot"], "decision": "allow"}
+                ],
+                "policy_tag_rules": {"network": "deny", "docs": "allow", "secrets": "deny"}
+            })
+        );
+
+        assert_eq!(migrate(&mut document), None);
+    }
+
+    #[test]
+    fn test_unversioned_and_unknown_versions() {
+        let mut unversioned = json!({"permission_mode": "default"});
+        let report = migrate(&mut unversioned).unwrap();
+        assert_eq!(report.from, UNVERSIONED_SCHEMA_VERSION);
+        assert!(report.changes.is_empty());
+        assert_eq!(unversioned["schema_version"], SCHEMA_VERSION);
+
+        let mut newer = json!({"schema_version": "9.0", "approval_rules": []});
+        assert_eq!(migrate(&mut newer), None);
+        assert_eq!(newer["schema_version"], "9.0");
+    }
+}
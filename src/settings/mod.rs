@@ -0,0 +1,629 @@
+pub mod layers;
+pub mod migrate;
+
+use crate::error::NexusError;
+use crate::executor::AuthChain;
+use crate::suggest::closest_match;
+use crate::types::{
+    ANONYMIZE_KEYS, AUTOPILOT_KEYS, GIT_KEYS, NexusSettings, SETTINGS_KEYS, VERIFY_KEYS,
+};
+use layers::{SettingSource, SettingSources};
+use log::debug;
+use migrate::MigrationReport;
+use secrecy::SecretString;
+use serde_json::{Map, Value};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Keys of each settings object.
+const SECTION_KEYS: &[(&str, &[&str])] = &[
+    ("autopilot", AUTOPILOT_KEYS),
+    ("anonymize", ANONYMIZE_KEYS),
+    ("git", GIT_KEYS),
+    ("verify", VERIFY_KEYS),
+];
+
+/// Runtime configuration (settings + secrets from environment).
+#[derive(Debug)]
+pub struct NexusConfig {
+    pub settings: NexusSettings,
+    /// The project's settings file, if one was loaded.
+    pub settings_path: Option<PathBuf>,
+    /// The user's global settings file, if one was loaded.
+    pub user_settings_path: Option<PathBuf>,
+    /// Which layers set each settings key.
+    pub sources: SettingSources,
+    /// Where API keys come from when a provider's variable is unset.
+    pub auth: AuthChain,
+    api_key: Option<SecretString>,
+}
+
+impl NexusConfig {
+    /// Load the application's configuration from disk and environment.
+    pub fn load() -> Result<Self, NexusError> {
+        let loaded = load_settings()?;
+        Ok(Self::from_loaded(loaded))
+    }
+
+    /// Load configuration honoring an explicit config path.
+    ///
+    /// If the path exists, it is loaded directly. If it does not exist,
+    /// defaults are used and a warning is logged.
+    pub fn load_with_config_path(config_path: &Path) -> Result<Self, NexusError> {
+        Self::load_with_config_path_strict(config_path, false)
+    }
+
+    /// Like [`NexusConfig::load_with_config_path`], but `force_strict` rejects
+    /// unknown keys even when the file does not set `strict: true`.
+    pub fn load_with_config_path_strict(
+        config_path: &Path,
+        force_strict: bool,
+    ) -> Result<Self, NexusError> {
+        let loaded = load_settings_with_preference(config_path, force_strict)?;
+        Ok(Self::from_loaded(loaded))
+    }
+
+    fn from_loaded(loaded: LoadedSettings) -> Self {
+        let api_key = load_api_key();
+
+        if api_key.is_none() {
+            debug!("OPENAI_API_KEY not set; LLM operations will fail");
+        }
+
+        NexusConfig {
+            auth: AuthChain::from_settings(&loaded.settings),
+            settings: loaded.settings,
+            settings_path: loaded.project_path,
+            user_settings_path: loaded.user_path,
+            sources: loaded.sources,
+            api_key,
+        }
+    }
+
+    /// Puts `--provider` and `--model` (or their environment variables)
+    /// over the loaded settings.
+    pub fn with_cli_overrides(mut self, provider: Option<&str>, model: Option<&str>) -> Self {
+        if let Some(provider) = provider {
+            self.settings.provider = Some(provider.to_string());
+            self.sources.record("provider", SettingSource::Cli);
+        }
+        if let Some(model) = model {
+            self.settings.model = Some(model.to_string());
+            self.sources.record("model", SettingSource::Cli);
+        }
+        self
+    }
+
+    /// Check if API key is available.
+    pub fn has_api_key(&self) -> bool {
+        self.api_key.is_some()
+    }
+
+    /// Return a reference to the configured API key.
+    pub fn require_api_key(&self) -> Result<&SecretString, NexusError> {
+        self.api_key.as_ref().ok_or(NexusError::MissingApiKey)
+    }
+
+    /// Indicates whether the active configuration was loaded from a settings file.
+    pub fn has_settings_file(&self) -> bool {
+        self.settings_path.is_some()
+    }
+}
+
+/// Locate a settings.json file at ".nexus/settings.json" inside the current working directory.
+fn discover_settings_path() -> Option<PathBuf> {
+    let cwd = env::current_dir().ok()?;
+    let settings_path = cwd.join(".nexus").join("settings.json");
+
+    if settings_path.exists() {
+        Some(settings_path)
+    } else {
+        None
+    }
+}
+
+/// Settings merged from every layer found, and where they came from.
+struct LoadedSettings {
+    settings: NexusSettings,
+    sources: SettingSources,
+    user_path: Option<PathBuf>,
+    project_path: Option<PathBuf>,
+}
+
+/// Locate the user's global settings file, if it exists.
+fn discover_user_settings_path() -> Option<PathBuf> {
+    layers::user_settings_path().filter(|path| path.exists())
+}
+
+/// Load Nexus settings from the user's global file and a settings file in
+/// the current working directory, whichever exist.
+fn load_settings() -> Result<LoadedSettings, NexusError> {
+    load_layered(
+        discover_user_settings_path(),
+        discover_settings_path(),
+        false,
+    )
+}
+
+/// Load settings preferring an explicit path; use defaults if missing.
+///
+/// If the explicit path exists, it is loaded over the user's global file.
+/// If it does not exist, only the global file (or the defaults) is used,
+/// with a warning (matching CLI validator documentation).
+fn load_settings_with_preference(
+    config_path: &Path,
+    force_strict: bool,
+) -> Result<LoadedSettings, NexusError> {
+    let project_path = if config_path.exists() {
+        debug!("Loading settings from explicit path {:?}", config_path);
+        Some(config_path.to_path_buf())
+    } else {
+        // Config file not found - use defaults as documented in CLI validator
+        log::warn!(
+            "Config file {:?} not found, using default settings",
+            config_path
+        );
+        None
+    };
+    load_layered(discover_user_settings_path(), project_path, force_strict)
+}
+
+fn load_layered(
+    user_path: Option<PathBuf>,
+    project_path: Option<PathBuf>,
+    force_strict: bool,
+) -> Result<LoadedSettings, NexusError> {
+    let mut layers = Vec::new();
+    if let Some(path) = &user_path {
+        debug!("Loading user settings from {:?}", path);
+        layers.push((SettingSource::User, path.as_path()));
+    }
+    if let Some(path) = &project_path {
+        layers.push((SettingSource::Project, path.as_path()));
+    }
+    let env = layers::env_layer(env::vars())?;
+    let (settings, sources) = load_layers(&layers, env, force_strict)?;
+    Ok(LoadedSettings {
+        settings,
+        sources,
+        user_path,
+        project_path,
+    })
+}
+
+/// Upgrades the settings file at `path` to the current schema version and,
+/// unless `dry_run`, writes it back as pretty-printed JSON. Returns `None`
+/// if the file needs no migration.
+///
+/// # Errors
+/// - `NexusError::ConfigLoad`/`ConfigParse` if the file cannot be read
+/// - `NexusError::ConfigValidation` if the migrated settings are invalid
+/// - `NexusError::IoError` if the file cannot be written
+pub fn migrate_file(path: &Path, dry_run: bool) -> Result<Option<MigrationReport>, NexusError> {
+    let (_, mut document) = read_document(path)?;
+    let Some(report) = migrate::migrate(&mut document) else {
+        return Ok(None);
+    };
+
+    let settings: NexusSettings =
+        serde_json::from_value(document.clone()).map_err(|err| NexusError::ConfigParse {
+            path: path.to_path_buf(),
+            message: format!("migrated settings do not parse: {err}"),
+        })?;
+    settings
+        .validate()
+        .map_err(|err| NexusError::ConfigValidation {
+            path: path.to_path_buf(),
+            source: err,
+        })?;
+
+    if !dry_run {
+        let json =
+            serde_json::to_string_pretty(&document).map_err(|source| NexusError::JsonError {
+                context: "failed to serialize migrated settings".to_string(),
+                source,
+            })?;
+        fs::write(path, json + "\n").map_err(|err| NexusError::IoError {
+            operation: "write settings".to_string(),
+            path: path.to_path_buf(),
+            source: err,
+        })?;
+    }
+    Ok(Some(report))
+}
+
+/// Reads the settings file at `path` as text and as JSON.
+fn read_document(path: &Path) -> Result<(String, Value), NexusError> {
+    let content = fs::read_to_string(path).map_err(|err| NexusError::ConfigLoad {
+        path: path.to_path_buf(),
+        source: err,
+    })?;
+
+    if content.trim().is_empty() {
+        return Err(NexusError::ConfigParse {
+            path: path.to_path_buf(),
+            message: "settings file is empty".to_string(),
+        });
+    }
+
+    let document = serde_json::from_str(&content).map_err(|err| parse_error(path, &err))?;
+    Ok((content, document))
+}
+
+fn parse_error(path: &Path, err: &serde_json::Error) -> NexusError {
+    NexusError::ConfigParse {
+        path: path.to_path_buf(),
+        message: format!(
+            "JSON parse error at line {}, column {}: {}",
+            err.line(),
+            err.column(),
+            err
+        ),
+    }
+}
+
+/// Load and validate settings from a specific file.
+#[cfg(test)]
+fn load_from_file(path: &Path, force_strict: bool) -> Result<NexusSettings, NexusError> {
+    load_layers(&[(SettingSource::Project, path)], Map::new(), force_strict)
+        .map(|(settings, _)| settings)
+}
+
+/// Load settings files lowest layer first, merge them and the `env` layer
+/// over them (see [`layers`]), and validate the result.
+fn load_layers(
+    layers: &[(SettingSource, &Path)],
+    env: Map<String, Value>,
+    force_strict: bool,
+) -> Result<(NexusSettings, SettingSources), NexusError> {
+    let mut merged = Map::new();
+    let mut sources = SettingSources::default();
+    for (source, path) in layers {
+        let layer = read_layer(path, force_strict)?;
+        layers::merge_layer(&mut merged, layer, *source, &mut sources);
+    }
+    layers::merge_layer(&mut merged, env, SettingSource::Env, &mut sources);
+
+    let path = layers.last().map(|(_, path)| path.to_path_buf());
+    let mut settings: NexusSettings =
+        serde_json::from_value(Value::Object(merged)).map_err(|err| NexusError::ConfigError {
+            message: format!("invalid merged settings: {err}"),
+            path: path.clone(),
+            source: None,
+        })?;
+
+    merge_with_defaults(&mut settings);
+
+    settings.validate().map_err(|err| match path {
+        Some(path) => NexusError::ConfigValidation { path, source: err },
+        None => NexusError::ConfigError {
+            message: err.to_string(),
+            path: None,
+            source: Some(Box::new(err)),
+        },
+    })?;
+
+    Ok((settings, sources))
+}
+
+/// Read one settings file as a document ready to merge.
+///
+/// Documents written for an older schema version are migrated in memory
+/// first (see [`migrate`]), logging what changed. Unknown keys are logged
+/// and ignored unless strict mode is enabled by the file's `strict` field
+/// or by `force_strict`, in which case the first one is reported with a
+/// did-you-mean suggestion.
+fn read_layer(path: &Path, force_strict: bool) -> Result<Map<String, Value>, NexusError> {
+    let (content, mut document) = read_document(path)?;
+
+    let mut unknown_keys = Vec::new();
+    let record = |key: serde_ignored::Path<'_>| unknown_keys.push(settings_key_path(&key));
+    let settings: NexusSettings = match migrate::migrate(&mut document) {
+        Some(report) => {
+            log_migration(path, &report);
+            serde_ignored::deserialize(document.clone(), record).map_err(|err| {
+                NexusError::ConfigParse {
+                    path: path.to_path_buf(),
+                    message: format!("invalid settings after migration: {err}"),
+                }
+            })?
+        }
+        None => {
+            let mut deserializer = serde_json::Deserializer::from_str(&content);
+            serde_ignored::deserialize(&mut deserializer, record)
+                .map_err(|err| parse_error(path, &err))?
+        }
+    };
+
+    if let Some(key) = unknown_keys.first() {
+        if settings.strict || force_strict {
+            return Err(NexusError::ConfigParse {
+                path: path.to_path_buf(),
+                message: unknown_key_message(key),
+            });
+        }
+        for key in &unknown_keys {
+            log::warn!("ignoring {} in {:?}", unknown_key_message(key), path);
+        }
+    }
+
+    match document {
+        Value::Object(fields) => Ok(fields),
+        _ => Err(NexusError::ConfigParse {
+            path: path.to_path_buf(),
+            message: "settings must be a JSON object".to_string(),
+        }),
+    }
+}
+
+/// Logs a migration done while loading `path`. Changes beyond the version
+/// bump are warned about, since the file keeps its old form until
+/// `nexus config migrate` rewrites it.
+fn log_migration(path: &Path, report: &MigrationReport) {
+    if report.changes.is_empty() {
+        log::info!(
+            "reading {:?} (schema {}) as schema {}",
+            path,
+            report.from,
+            report.to
+        );
+    } else {
+        log::warn!(
+            "migrated {:?} from schema {} to {}: {}; run `nexus config migrate` to update the file",
+            path,
+            report.from,
+            report.to,
+            report.changes.join("; ")
+        );
+    }
+}
+
+/// Renders an ignored-key path as dotted keys, dropping the `?` segments
+/// `serde_ignored` inserts for `Option` fields.
+fn settings_key_path(path: &serde_ignored::Path<'_>) -> String {
+    path.to_string()
+        .split('.')
+        .filter(|segment| *segment != "?")
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Describes an unknown settings key, suggesting the closest known key.
+fn unknown_key_message(key: &str) -> String {
+    let (parent, name) = match key.rsplit_once('.') {
+        Some((parent, name)) => (Some(parent), name),
+        None => (None, key),
+    };
+    let candidates = match parent {
+        Some(parent) => SECTION_KEYS
+            .iter()
+            .find(|(section, _)| *section == parent)
+            .map_or(&[][..], |(_, keys)| keys),
+        None => SETTINGS_KEYS,
+    };
+
+    match closest_match(name, candidates) {
+        Some(candidate) => {
+            let full = match parent {
+                Some(parent) => format!("{parent}.{candidate}"),
+                None => candidate.to_string(),
+            };
+            format!("unknown key `{key}` (did you mean `{full}`?)")
+        }
+        None => format!("unknown key `{key}`"),
+    }
+}
+
+/// Apply default values for optional fields that are currently empty in `settings`.
+fn merge_with_defaults(settings: &mut NexusSettings) {
+    let defaults = NexusSettings::default();
+
+    if settings.deny_paths.is_empty() {
+        settings.deny_paths = defaults.deny_paths;
+    }
+
+    if settings.deny_commands.is_empty() {
+        settings.deny_commands = defaults.deny_commands;
+    }
+
+    if settings.disallowed_licenses.is_empty() {
+        settings.disallowed_licenses = defaults.disallowed_licenses;
+    }
+}
+
+/// Load the OpenAI API key from the `OPENAI_API_KEY` environment variable.
+fn load_api_key() -> Option<SecretString> {
+    env::var("OPENAI_API_KEY")
+        .ok()
+        .filter(|value| !value.is_empty())
+        .map(|value| SecretString::new(value.into_boxed_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn test_api_key_from_env() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        unsafe {
+            env::set_var("OPENAI_API_KEY", "sk-test-key");
+        }
+        let key = load_api_key();
+        assert!(key.is_some());
+        unsafe {
+            env::remove_var("OPENAI_API_KEY");
+        }
+    }
+
+    #[test]
+    fn test_empty_api_key_ignored() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        unsafe {
+            env::set_var("OPENAI_API_KEY", "");
+        }
+        let key = load_api_key();
+        assert!(key.is_none());
+        unsafe {
+            env::remove_var("OPENAI_API_KEY");
+        }
+    }
+
+    fn write_settings(dir: &tempfile::TempDir, content: &str) -> PathBuf {
+        let path = dir.path().join("settings.json");
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_unknown_keys_ignored_by_default() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = write_settings(&dir, r#"{"deny_path": ["secrets/**"]}"#);
+
+        let settings = load_from_file(&path, false).unwrap();
+        assert!(!settings.deny_paths.contains(&"secrets/**".to_string()));
+    }
+
+    #[test]
+    fn test_strict_setting_rejects_unknown_key_with_suggestion() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = write_settings(&dir, r#"{"strict": true, "deny_path": ["secrets/**"]}"#);
+
+        let err = load_from_file(&path, false).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("unknown key `deny_path` (did you mean `deny_paths`?)"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_force_strict_rejects_nested_unknown_key() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = write_settings(&dir, r#"{"autopilot": {"max_batch_step": 3}}"#);
+
+        let err = load_from_file(&path, true).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("did you mean `autopilot.max_batch_steps`?"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_older_settings_are_migrated() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let original = r#"{
+            "schema_version": "1.0",
+            "approval_rules": [{"policy_tags": ["network"], "decision": "deny"}]
+        }"#;
+        let path = write_settings(&dir, original);
+
+        let settings = load_from_file(&path, true).unwrap();
+        assert_eq!(settings.schema_version, crate::types::SCHEMA_VERSION);
+        assert!(settings.approval_rules.is_empty());
+        assert_eq!(
+            settings.policy_tag_rules.get("network"),
+            Some(&crate::types::TagDecision::Deny)
+        );
+
+        let report = migrate_file(&path, true).unwrap().unwrap();
+        assert_eq!(
+            report.changes,
+            ["moved approval_rules[0] to policy_tag_rules.network (deny)"]
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), original);
+
+        migrate_file(&path, false).unwrap();
+        let migrated: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(
+            migrated,
+            serde_json::json!({
+                "schema_version": crate::types::SCHEMA_VERSION,
+                "policy_tag_rules": {"network": "deny"}
+            })
+        );
+        assert_eq!(migrate_file(&path, false).unwrap(), None);
+    }
+
+    #[test]
+    fn test_newer_schema_version_is_rejected() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = write_settings(&dir, r#"{"schema_version": "9.0"}"#);
+
+        let err = load_from_file(&path, false).unwrap_err();
+        assert!(
+            err.to_string().contains(&format!(
+                "expected '{}', got '9.0'",
+                crate::types::SCHEMA_VERSION
+            )),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_project_settings_layer_over_user_settings() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let user = dir.path().join("user.json");
+        fs::write(
+            &user,
+            r#"{"model": "gpt-4o", "deny_paths": ["secrets/**"], "hide_thinking": true}"#,
+        )
+        .unwrap();
+        let project = write_settings(&dir, r#"{"model": "llama3", "deny_paths": ["build/**"]}"#);
+
+        let loaded = load_layered(Some(user), Some(project), true).unwrap();
+        assert_eq!(loaded.settings.model.as_deref(), Some("llama3"));
+        assert_eq!(loaded.settings.deny_paths, ["secrets/**", "build/**"]);
+        assert!(loaded.settings.hide_thinking);
+        assert_eq!(loaded.sources.describe("model"), "project");
+        assert_eq!(loaded.sources.describe("deny_paths"), "user+project");
+        assert_eq!(loaded.sources.describe("hide_thinking"), "user");
+
+        let config = NexusConfig::from_loaded(loaded).with_cli_overrides(None, Some("gpt-x"));
+        assert_eq!(config.settings.model.as_deref(), Some("gpt-x"));
+        assert_eq!(config.sources.describe("model"), "cli");
+        assert_eq!(config.sources.describe("provider"), "default");
+    }
+
+    #[test]
+    fn test_env_overrides_settings_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let project = write_settings(
+            &dir,
+            r#"{"permission_mode": "default", "autopilot": {"max_batch_steps": 3}}"#,
+        );
+        let env = layers::env_layer([
+            ("NEXUS_PERMISSION_MODE".to_string(), "autopilot".to_string()),
+            ("NEXUS_MAX_BATCH_CU".to_string(), "20".to_string()),
+        ])
+        .unwrap();
+
+        let (settings, sources) =
+            load_layers(&[(SettingSource::Project, &project)], env, false).unwrap();
+        assert_eq!(
+            settings.permission_mode,
+            crate::types::PermissionMode::Autopilot
+        );
+        let autopilot = settings.autopilot.unwrap();
+        assert_eq!((autopilot.max_batch_cu, autopilot.max_batch_steps), (20, 3));
+        assert_eq!(sources.describe("permission_mode"), "env");
+        assert_eq!(sources.describe("autopilot"), "project+env");
+
+        let env = layers::env_layer([("NEXUS_CONTEXT_TOKEN_BUDGET".to_string(), "0".to_string())])
+            .unwrap();
+        assert!(matches!(
+            load_layers(&[], env, false),
+            Err(NexusError::ConfigError { path: None, .. })
+        ));
+    }
+
+    #[test]
+    fn test_unknown_key_without_close_match() {
+        assert_eq!(unknown_key_message("zzz"), "unknown key `zzz`");
+    }
+}
@@ -0,0 +1,28 @@
+//! Did-you-mean suggestions for mistyped names, e.g. settings keys and
+//! provider names.
+
+/// Minimum Jaro-Winkler similarity for a did-you-mean suggestion.
+const SUGGESTION_THRESHOLD: f64 = 0.8;
+
+/// The candidate most similar to `name`, if any is similar enough to
+/// suggest.
+///
+/// # Examples
+///
+/// ```
+/// use nexus::suggest::closest_match;
+///
+/// assert_eq!(closest_match("opnai", &["openai", "ollama"]), Some("openai"));
+/// assert_eq!(closest_match("gemini", &["openai", "ollama"]), None);
+/// ```
+pub fn closest_match<'a, S: AsRef<str>>(name: &str, candidates: &'a [S]) -> Option<&'a str> {
+    candidates
+        .iter()
+        .map(|candidate| {
+            let candidate = candidate.as_ref();
+            (candidate, strsim::jaro_winkler(name, candidate))
+        })
+        .filter(|(_, score)| *score >= SUGGESTION_THRESHOLD)
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(candidate, _)| candidate)
+}
@@ -0,0 +1,470 @@
+//! Full-screen review of the actions a resumed run puts to the user.
+//!
+//! `nexus resume --tui` hands [`TuiPrompt`] every action the permission
+//! gate asks about before any of them is decided. The actions are listed on
+//! the left and the selected one's diff is shown on the right; the status
+//! bar names the run, its model, and the tokens it used. Each action is
+//! approved, rejected, or skipped (left pending), and the answers are then
+//! given to [`RunResume`](crate::resume::RunResume) one by one. Actions of
+//! an approval group are decided together, so marking one marks them all.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::cost::action_cu;
+use crate::error::NexusError;
+use crate::preview::render_diff;
+use crate::resume::{ApprovalPrompt, GroupMember};
+use crate::types::{ApprovalGroup, ProposedAction};
+
+/// Lines PageUp and PageDown scroll the diff by.
+const PAGE_LINES: u16 = 10;
+
+const KEY_HELP: &str =
+    "a approve  r reject  s skip  ↑↓ select  PgUp/PgDn scroll  Enter apply  q quit";
+
+/// What the user made of one action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Approve,
+    Reject,
+    /// Left pending, as if no answer was given.
+    Skip,
+}
+
+impl Verdict {
+    fn answer(self) -> Option<bool> {
+        match self {
+            Self::Approve => Some(true),
+            Self::Reject => Some(false),
+            Self::Skip => None,
+        }
+    }
+}
+
+/// The run described in the status bar.
+#[derive(Debug, Clone, Default)]
+pub struct StatusBar {
+    pub run_id: String,
+    pub model: Option<String>,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+}
+
+impl StatusBar {
+    fn render(&self) -> String {
+        format!(
+            "{} | {} | {} in / {} out tokens",
+            self.run_id,
+            self.model.as_deref().unwrap_or("unknown model"),
+            self.prompt_tokens,
+            self.completion_tokens
+        )
+    }
+}
+
+/// One action under review.
+#[derive(Debug, Clone)]
+struct ReviewItem {
+    id: String,
+    summary: String,
+    group: Option<String>,
+    /// Why it is asked about, its size, and what is known about it.
+    details: String,
+    diff: String,
+}
+
+impl ReviewItem {
+    fn new(root: &std::path::Path, member: &GroupMember<'_>) -> Self {
+        let action = member.action;
+        let mut details = format!("{} [{} CU]\n", action.summary, action_cu(action));
+        if let Some(why) = &action.why {
+            let _ = writeln!(details, "why: {why}");
+        }
+        let _ = writeln!(details, "asked because: {}", member.reason);
+        if let Some(group) = &action.approval_group {
+            let _ = writeln!(details, "group: {} (decided together)", group.label);
+        }
+        if let Some(failing_hunk) = member.check.and_then(|check| check.failing_hunk.as_deref()) {
+            let _ = writeln!(details, "does not apply cleanly: {failing_hunk}");
+        }
+        if let Some(review) = member.review {
+            let _ = writeln!(details, "{}", review.render());
+        }
+
+        let mut diff = render_diff(std::slice::from_ref(action), root, false);
+        if diff.is_empty() {
+            diff = serde_json::to_string_pretty(&action.details).unwrap_or_default();
+        }
+        Self {
+            id: action.id.clone(),
+            summary: action.summary.clone(),
+            group: action.approval_group.as_ref().map(|group| group.id.clone()),
+            details,
+            diff,
+        }
+    }
+}
+
+/// What a key press asks of the review.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Continue,
+    /// Apply the verdicts given.
+    Done,
+    /// Leave without deciding anything.
+    Quit,
+}
+
+/// The review screen: the actions, their verdicts, and what is selected.
+#[derive(Debug)]
+struct Review {
+    items: Vec<ReviewItem>,
+    verdicts: Vec<Option<Verdict>>,
+    selected: usize,
+    scroll: u16,
+    status: StatusBar,
+}
+
+impl Review {
+    fn new(root: &std::path::Path, actions: &[GroupMember<'_>], status: StatusBar) -> Self {
+        let items: Vec<ReviewItem> = actions
+            .iter()
+            .map(|member| ReviewItem::new(root, member))
+            .collect();
+        Self {
+            verdicts: vec![None; items.len()],
+            items,
+            selected: 0,
+            scroll: 0,
+            status,
+        }
+    }
+
+    fn handle(&mut self, key: KeyCode) -> Step {
+        match key {
+            KeyCode::Up | KeyCode::Char('k') => self.select(self.selected.saturating_sub(1)),
+            KeyCode::Down | KeyCode::Char('j') => self.select(self.selected + 1),
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(PAGE_LINES),
+            KeyCode::PageDown => self.scroll = self.scroll.saturating_add(PAGE_LINES),
+            KeyCode::Char('a' | 'y') => self.mark(Verdict::Approve),
+            KeyCode::Char('r' | 'n') => self.mark(Verdict::Reject),
+            KeyCode::Char('s') => self.mark(Verdict::Skip),
+            KeyCode::Enter => return Step::Done,
+            KeyCode::Char('q') | KeyCode::Esc => return Step::Quit,
+            _ => {}
+        }
+        Step::Continue
+    }
+
+    fn select(&mut self, index: usize) {
+        if index < self.items.len() && index != self.selected {
+            self.selected = index;
+            self.scroll = 0;
+        }
+    }
+
+    /// Gives the selected action, and the rest of its group, `verdict`,
+    /// then moves on to the next action.
+    fn mark(&mut self, verdict: Verdict) {
+        let Some(selected) = self.items.get(self.selected) else {
+            return;
+        };
+        let group = selected.group.clone();
+        for (index, item) in self.items.iter().enumerate() {
+            if index == self.selected || (group.is_some() && item.group == group) {
+                self.verdicts[index] = Some(verdict);
+            }
+        }
+        self.select(self.selected + 1);
+    }
+
+    /// The verdict on each action, by ID; actions without one are skipped.
+    fn verdicts(&self) -> HashMap<String, Verdict> {
+        self.items
+            .iter()
+            .zip(&self.verdicts)
+            .map(|(item, verdict)| (item.id.clone(), verdict.unwrap_or(Verdict::Skip)))
+            .collect()
+    }
+
+    fn draw(&self, frame: &mut Frame<'_>) {
+        let [main, status] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+        let [list_area, diff_area] =
+            Layout::horizontal([Constraint::Percentage(35), Constraint::Percentage(65)])
+                .areas(main);
+
+        let items: Vec<ListItem<'_>> = self
+            .items
+            .iter()
+            .zip(&self.verdicts)
+            .map(|(item, verdict)| {
+                let (marker, color) = match verdict {
+                    Some(Verdict::Approve) => ("[✓]", Color::Green),
+                    Some(Verdict::Reject) => ("[✗]", Color::Red),
+                    Some(Verdict::Skip) => ("[-]", Color::DarkGray),
+                    None => ("[ ]", Color::Reset),
+                };
+                ListItem::new(format!("{marker} {} {}", item.id, item.summary))
+                    .style(Style::default().fg(color))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(" Actions ({}) ", self.items.len())),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        let mut state = ListState::default().with_selected(Some(self.selected));
+        frame.render_stateful_widget(list, list_area, &mut state);
+
+        if let Some(item) = self.items.get(self.selected) {
+            let mut lines: Vec<Line<'_>> = item.details.lines().map(Line::raw).collect();
+            lines.push(Line::default());
+            lines.extend(item.diff.lines().map(diff_line));
+            let diff = Paragraph::new(lines)
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title(format!(" {} ", item.id)),
+                )
+                .scroll((self.scroll, 0));
+            frame.render_widget(diff, diff_area);
+        }
+
+        let decided = |wanted: Verdict| {
+            self.verdicts
+                .iter()
+                .filter(|verdict| **verdict == Some(wanted))
+                .count()
+        };
+        let bar = format!(
+            " {} | {} approved, {} rejected | {KEY_HELP}",
+            self.status.render(),
+            decided(Verdict::Approve),
+            decided(Verdict::Reject)
+        );
+        frame.render_widget(
+            Paragraph::new(bar).style(Style::default().add_modifier(Modifier::REVERSED)),
+            status,
+        );
+    }
+}
+
+/// A diff line colored like `nexus diff`.
+fn diff_line(line: &str) -> Line<'_> {
+    let style = if line.starts_with("+++") || line.starts_with("---") {
+        Style::default().add_modifier(Modifier::BOLD)
+    } else if line.starts_with('+') {
+        Style::default().fg(Color::Green)
+    } else if line.starts_with('-') {
+        Style::default().fg(Color::Red)
+    } else if line.starts_with("@@") {
+        Style::default().fg(Color::Cyan)
+    } else {
+        Style::default()
+    };
+    Line::styled(line, style)
+}
+
+/// Answers approval questions from a full-screen review of all of them.
+#[derive(Debug)]
+pub struct TuiPrompt {
+    root: PathBuf,
+    status: StatusBar,
+    verdicts: HashMap<String, Verdict>,
+}
+
+impl TuiPrompt {
+    /// Shows diffs against the files under `root`, and `status` at the
+    /// bottom.
+    pub fn new(root: impl Into<PathBuf>, status: StatusBar) -> Self {
+        Self {
+            root: root.into(),
+            status,
+            verdicts: HashMap::new(),
+        }
+    }
+
+    fn answer(&self, action: &ProposedAction) -> Option<bool> {
+        self.verdicts
+            .get(&action.id)
+            .and_then(|verdict| verdict.answer())
+    }
+}
+
+impl ApprovalPrompt for TuiPrompt {
+    fn preview(&mut self, actions: &[GroupMember<'_>]) -> Result<(), NexusError> {
+        let mut review = Review::new(&self.root, actions, self.status.clone());
+        let mut terminal = ratatui::try_init().map_err(terminal_error)?;
+        let step = review_loop(&mut terminal, &mut review);
+        ratatui::try_restore().map_err(terminal_error)?;
+        self.verdicts = match step.map_err(terminal_error)? {
+            Step::Done => review.verdicts(),
+            Step::Continue | Step::Quit => HashMap::new(),
+        };
+        Ok(())
+    }
+
+    fn approve(
+        &mut self,
+        action: &ProposedAction,
+        _reason: &str,
+        _check: Option<&crate::apply::PatchCheck>,
+        _review: Option<&crate::review::ActionReview>,
+    ) -> Result<Option<bool>, NexusError> {
+        Ok(self.answer(action))
+    }
+
+    fn approve_group(
+        &mut self,
+        _group: &ApprovalGroup,
+        members: &[GroupMember<'_>],
+    ) -> Result<Option<bool>, NexusError> {
+        let answers: Vec<Option<bool>> = members
+            .iter()
+            .map(|member| self.answer(member.action))
+            .collect();
+        Ok(if answers.contains(&Some(false)) {
+            Some(false)
+        } else if answers.iter().all(|answer| *answer == Some(true)) {
+            Some(true)
+        } else {
+            None
+        })
+    }
+}
+
+/// Draws `review` and feeds it key presses until it is done or left.
+fn review_loop(terminal: &mut DefaultTerminal, review: &mut Review) -> std::io::Result<Step> {
+    loop {
+        terminal.draw(|frame| review.draw(frame))?;
+        if let Event::Key(key) = event::read()? {
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match review.handle(key.code) {
+                Step::Continue => {}
+                step => return Ok(step),
+            }
+        }
+    }
+}
+
+fn terminal_error(err: std::io::Error) -> NexusError {
+    NexusError::IoError {
+        operation: "run the review screen".to_string(),
+        path: PathBuf::from("<terminal>"),
+        source: err,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    fn action(id: &str, group: Option<&str>) -> ProposedAction {
+        let diff = format!("--- a/{id}.rs\n+++ b/{id}.rs\n@@ -1 +1 @@\n-a\n+b\n");
+        ProposedAction::test_diff(id, &diff)
+            .with_summary(format!("Change {id}"))
+            .with_group(group.map(|group| ApprovalGroup {
+                id: group.to_string(),
+                label: "Setup".to_string(),
+                size: 2,
+                index: 0,
+            }))
+    }
+
+    fn review(actions: &[ProposedAction]) -> Review {
+        let members: Vec<GroupMember<'_>> = actions
+            .iter()
+            .map(|action| GroupMember {
+                action,
+                reason: "writes outside allow_paths_write",
+                check: None,
+                review: None,
+            })
+            .collect();
+        let status = StatusBar {
+            run_id: "run_1".to_string(),
+            model: Some("gpt-x".to_string()),
+            prompt_tokens: 1200,
+            completion_tokens: 300,
+        };
+        Review::new(std::path::Path::new("."), &members, status)
+    }
+
+    #[test]
+    fn test_keys_mark_actions_and_groups() {
+        let actions = [
+            action("act_1", None),
+            action("act_2", Some("setup")),
+            action("act_3", Some("setup")),
+            action("act_4", None),
+        ];
+        let mut review = review(&actions);
+        assert_eq!(review.handle(KeyCode::Char('a')), Step::Continue);
+        assert_eq!(review.selected, 1);
+        review.handle(KeyCode::Char('r'));
+        review.handle(KeyCode::Down);
+        review.handle(KeyCode::Char('s'));
+        review.handle(KeyCode::Up);
+        review.handle(KeyCode::PageDown);
+        assert_eq!(review.scroll, PAGE_LINES);
+
+        let verdicts = review.verdicts();
+        assert_eq!(verdicts["act_1"], Verdict::Approve);
+        assert_eq!(verdicts["act_2"], Verdict::Reject);
+        assert_eq!(verdicts["act_3"], Verdict::Reject);
+        assert_eq!(verdicts["act_4"], Verdict::Skip);
+        assert_eq!(review.handle(KeyCode::Enter), Step::Done);
+        assert_eq!(review.handle(KeyCode::Char('q')), Step::Quit);
+
+        let mut prompt = TuiPrompt::new(".", StatusBar::default());
+        prompt.verdicts = verdicts;
+        assert_eq!(
+            prompt.approve(&actions[0], "", None, None).unwrap(),
+            Some(true)
+        );
+        assert_eq!(prompt.approve(&actions[3], "", None, None).unwrap(), None);
+    }
+
+    #[test]
+    fn test_draws_list_diff_and_status_bar() {
+        let actions = [action("act_1", None), action("act_2", None)];
+        let mut review = review(&actions);
+        review.handle(KeyCode::Char('a'));
+
+        let mut terminal = Terminal::new(TestBackend::new(160, 20)).unwrap();
+        terminal.draw(|frame| review.draw(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let screen: Vec<String> = (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect()
+            })
+            .collect();
+        let screen = screen.join("\n");
+
+        assert!(screen.contains("[✓] act_1 Change act_1"), "{screen}");
+        assert!(screen.contains("[ ] act_2 Change act_2"), "{screen}");
+        assert!(screen.contains("+++ b/act_2.rs"), "{screen}");
+        assert!(
+            screen.contains("run_1 | gpt-x | 1200 in / 300 out tokens | 1 approved, 0 rejected"),
+            "{screen}"
+        );
+    }
+}
@@ -283,6 +283,79 @@ fn is_default<T: Default + PartialEq>(value: &T) -> bool {
     *value == T::default()
 }
 
+/// Builders for actions in tests across the crate.
+#[cfg(test)]
+impl ProposedAction {
+    /// An approval-required, risk 1 action `id` carrying `details`, with
+    /// `kind` matching them.
+    pub(crate) fn test(id: &str, details: ActionDetails) -> Self {
+        let kind = match &details {
+            ActionDetails::Handoff(_) => ActionKindTag::Handoff,
+            ActionDetails::Command(_) => ActionKindTag::Command,
+            ActionDetails::PlanPatch(_) => ActionKindTag::PlanPatch,
+            ActionDetails::AgendaPatch(_) => ActionKindTag::AgendaPatch,
+            ActionDetails::FileCreate(_) => ActionKindTag::FileCreate,
+            ActionDetails::FileRename(_) => ActionKindTag::FileRename,
+            ActionDetails::FileDelete(_) => ActionKindTag::FileDelete,
+            ActionDetails::Patch(_) => ActionKindTag::Patch,
+        };
+        Self {
+            id: id.to_string(),
+            summary: "change".to_string(),
+            why: None,
+            risk: 1,
+            policy_tags: Vec::new(),
+            requires_approval: true,
+            created_by: None,
+            approval_group: None,
+            kind,
+            details,
+        }
+    }
+
+    /// A patch action `id` over `files`.
+    pub(crate) fn test_patch(id: &str, files: &[&str]) -> Self {
+        Self::test(
+            id,
+            ActionDetails::Patch(PatchDetails {
+                files: files.iter().map(|file| file.to_string()).collect(),
+                ..Default::default()
+            }),
+        )
+    }
+
+    /// A unified-diff patch action `id` applying `diff`.
+    pub(crate) fn test_diff(id: &str, diff: &str) -> Self {
+        Self::test(
+            id,
+            ActionDetails::Patch(PatchDetails {
+                diff: Some(diff.to_string()),
+                ..Default::default()
+            }),
+        )
+    }
+
+    pub(crate) fn with_summary(mut self, summary: impl Into<String>) -> Self {
+        self.summary = summary.into();
+        self
+    }
+
+    pub(crate) fn with_why(mut self, why: &str) -> Self {
+        self.why = Some(why.to_string());
+        self
+    }
+
+    pub(crate) fn with_risk(mut self, risk: u8) -> Self {
+        self.risk = risk;
+        self
+    }
+
+    pub(crate) fn with_group(mut self, group: Option<ApprovalGroup>) -> Self {
+        self.approval_group = group;
+        self
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -39,6 +39,203 @@ pub struct PayloadRef {
     pub label: Option<String>,
 }
 
+/// Type of a [`RunEvent`], written to the log as its `type` string.
+///
+/// Types Nexus does not know, e.g. from a newer version, are kept as
+/// [`Other`](Self::Other) and written back unchanged. Parsing never puts a
+/// known type in `Other`, so matching on variants is reliable; kinds also
+/// compare by their wire string, so `Other("run.started")` equals
+/// `RunStarted`.
+#[derive(Debug, Clone)]
+pub enum RunEventKind {
+    /// `run.started`
+    RunStarted,
+    /// `run.completed`
+    RunCompleted,
+    /// `run.cancelled`
+    RunCancelled,
+    /// `run.budget_exceeded`
+    RunBudgetExceeded,
+    /// `run.reverted`
+    RunReverted,
+    /// `action.proposed`
+    ActionProposed,
+    /// `action.validated`
+    ActionValidated,
+    /// `action.reviewed`
+    ActionReviewed,
+    /// `permission.granted`
+    PermissionGranted,
+    /// `permission.denied`
+    PermissionDenied,
+    /// `tool.executed`
+    ToolExecuted,
+    /// `tool.failed`
+    ToolFailed,
+    /// `conflict.resolved`
+    ConflictResolved,
+    /// `verification.completed`
+    VerificationCompleted,
+    /// `executor.started`
+    ExecutorStarted,
+    /// `context.truncated`
+    ContextTruncated,
+    /// `executor.streaming`
+    ExecutorStreaming,
+    /// `executor.completed`
+    ExecutorCompleted,
+    /// `executor.failed`
+    ExecutorFailed,
+    /// `handoff.requested`
+    HandoffRequested,
+    /// `handoff.accepted`
+    HandoffAccepted,
+    /// `plan.created`
+    PlanCreated,
+    /// `plan.step.completed`
+    PlanStepCompleted,
+    /// `plan.patched`
+    PlanPatched,
+    /// `log.recovered`
+    LogRecovered,
+    /// `git.branch_created`
+    GitBranchCreated,
+    /// `git.committed`
+    GitCommitted,
+    /// Any other type string.
+    Other(String),
+}
+
+impl RunEventKind {
+    /// Returns the wire string, e.g. `"action.proposed"`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::RunStarted => "run.started",
+            Self::RunCompleted => "run.completed",
+            Self::RunCancelled => "run.cancelled",
+            Self::RunBudgetExceeded => "run.budget_exceeded",
+            Self::RunReverted => "run.reverted",
+            Self::ActionProposed => "action.proposed",
+            Self::ActionValidated => "action.validated",
+            Self::ActionReviewed => "action.reviewed",
+            Self::PermissionGranted => "permission.granted",
+            Self::PermissionDenied => "permission.denied",
+            Self::ToolExecuted => "tool.executed",
+            Self::ToolFailed => "tool.failed",
+            Self::ConflictResolved => "conflict.resolved",
+            Self::VerificationCompleted => "verification.completed",
+            Self::ExecutorStarted => "executor.started",
+            Self::ContextTruncated => "context.truncated",
+            Self::ExecutorStreaming => "executor.streaming",
+            Self::ExecutorCompleted => "executor.completed",
+            Self::ExecutorFailed => "executor.failed",
+            Self::HandoffRequested => "handoff.requested",
+            Self::HandoffAccepted => "handoff.accepted",
+            Self::PlanCreated => "plan.created",
+            Self::PlanStepCompleted => "plan.step.completed",
+            Self::PlanPatched => "plan.patched",
+            Self::LogRecovered => "log.recovered",
+            Self::GitBranchCreated => "git.branch_created",
+            Self::GitCommitted => "git.committed",
+            Self::Other(other) => other,
+        }
+    }
+}
+
+impl From<&str> for RunEventKind {
+    fn from(event_type: &str) -> Self {
+        match event_type {
+            "run.started" => Self::RunStarted,
+            "run.completed" => Self::RunCompleted,
+            "run.cancelled" => Self::RunCancelled,
+            "run.budget_exceeded" => Self::RunBudgetExceeded,
+            "run.reverted" => Self::RunReverted,
+            "action.proposed" => Self::ActionProposed,
+            "action.validated" => Self::ActionValidated,
+            "action.reviewed" => Self::ActionReviewed,
+            "permission.granted" => Self::PermissionGranted,
+            "permission.denied" => Self::PermissionDenied,
+            "tool.executed" => Self::ToolExecuted,
+            "tool.failed" => Self::ToolFailed,
+            "conflict.resolved" => Self::ConflictResolved,
+            "verification.completed" => Self::VerificationCompleted,
+            "executor.started" => Self::ExecutorStarted,
+            "context.truncated" => Self::ContextTruncated,
+            "executor.streaming" => Self::ExecutorStreaming,
+            "executor.completed" => Self::ExecutorCompleted,
+            "executor.failed" => Self::ExecutorFailed,
+            "handoff.requested" => Self::HandoffRequested,
+            "handoff.accepted" => Self::HandoffAccepted,
+            "plan.created" => Self::PlanCreated,
+            "plan.step.completed" => Self::PlanStepCompleted,
+            "plan.patched" => Self::PlanPatched,
+            "log.recovered" => Self::LogRecovered,
+            "git.branch_created" => Self::GitBranchCreated,
+            "git.committed" => Self::GitCommitted,
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
+impl From<String> for RunEventKind {
+    fn from(event_type: String) -> Self {
+        match Self::from(event_type.as_str()) {
+            Self::Other(_) => Self::Other(event_type),
+            known => known,
+        }
+    }
+}
+
+impl std::fmt::Display for RunEventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+impl PartialEq for RunEventKind {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for RunEventKind {}
+
+impl std::hash::Hash for RunEventKind {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
+    }
+}
+
+impl PartialEq<str> for RunEventKind {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for RunEventKind {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<RunEventKind> for &str {
+    fn eq(&self, other: &RunEventKind) -> bool {
+        *self == other.as_str()
+    }
+}
+
+impl Serialize for RunEventKind {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for RunEventKind {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::from)
+    }
+}
+
 /// Run event (append-only log entry)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RunEvent {
@@ -47,6 +244,11 @@ pub struct RunEvent {
 
     pub run_id: String,
 
+    /// Run that spawned this one, e.g. through a handoff or a follow-up
+    /// task. Recorded on `run.started`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_run_id: Option<String>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub workflow_id: Option<String>,
 
@@ -55,7 +257,7 @@ pub struct RunEvent {
 
     /// Event type (e.g., "action.proposed", "permission.granted")
     #[serde(rename = "type")]
-    pub event_type: String,
+    pub event_type: RunEventKind,
 
     pub time: DateTime<Utc>,
 
@@ -76,7 +278,7 @@ pub struct RunEvent {
 impl RunEvent {
     /// Constructs a minimal RunEvent for the given run and event type, setting the schema version to "nexus/1" and the timestamp to the current UTC time.
     ///
-    /// The returned event has `workflow_id`, `node_id`, `trace`, `actor`, `payload`, and `payload_ref` set to `None`.
+    /// The returned event has `parent_run_id`, `workflow_id`, `node_id`, `trace`, `actor`, `payload`, and `payload_ref` set to `None`.
     ///
     /// # Examples
     ///
@@ -88,10 +290,11 @@ impl RunEvent {
     /// assert_eq!(ev.run_id, "run-123");
     /// assert_eq!(ev.event_type, "action.proposed");
     /// ```
-    pub fn new(run_id: impl Into<String>, event_type: impl Into<String>) -> Self {
+    pub fn new(run_id: impl Into<String>, event_type: impl Into<RunEventKind>) -> Self {
         Self {
             v: "nexus/1".to_string(),
             run_id: run_id.into(),
+            parent_run_id: None,
             workflow_id: None,
             node_id: None,
             event_type: event_type.into(),
@@ -138,6 +341,62 @@ impl RunEvent {
         self.actor = Some(actor);
         self
     }
+
+    /// Sets the event's trace (correlation and span IDs) and returns the
+    /// updated event.
+    pub fn with_trace(mut self, trace: TraceInfo) -> Self {
+        self.trace = Some(trace);
+        self
+    }
+
+    /// Links the event's run to the run that spawned it and returns the
+    /// updated event.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nexus::RunEvent;
+    ///
+    /// let event = RunEvent::new("run-2", "run.started").with_parent_run("run-1");
+    /// assert_eq!(event.parent_run_id.as_deref(), Some("run-1"));
+    /// ```
+    pub fn with_parent_run(mut self, parent_run_id: impl Into<String>) -> Self {
+        self.parent_run_id = Some(parent_run_id.into());
+        self
+    }
+
+    /// Attaches a reference to an externally stored payload and returns the updated event.
+    pub fn with_payload_ref(mut self, payload_ref: PayloadRef) -> Self {
+        self.payload_ref = Some(payload_ref);
+        self
+    }
+
+    /// IDs of the actions the event is about: its payload's `action_id`,
+    /// or the `action_ids` of a decision on a whole approval group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nexus::RunEvent;
+    /// use serde_json::json;
+    ///
+    /// let event = RunEvent::new("run-1", "permission.granted")
+    ///     .with_payload(json!({"group_id": "setup", "action_ids": ["act_1", "act_2"]}));
+    /// assert_eq!(event.action_ids(), ["act_1", "act_2"]);
+    /// ```
+    pub fn action_ids(&self) -> Vec<&str> {
+        let Some(payload) = &self.payload else {
+            return Vec::new();
+        };
+        if let Some(action_id) = payload.get("action_id").and_then(|id| id.as_str()) {
+            return vec![action_id];
+        }
+        payload
+            .get("action_ids")
+            .and_then(|ids| ids.as_array())
+            .map(|ids| ids.iter().filter_map(|id| id.as_str()).collect())
+            .unwrap_or_default()
+    }
 }
 
 #[cfg(test)]
@@ -152,6 +411,24 @@ mod tests {
         assert_eq!(event.event_type, "action.proposed");
     }
 
+    #[test]
+    fn test_event_kind_round_trips_wire_strings() {
+        let known: RunEventKind = serde_json::from_str("\"tool.executed\"").unwrap();
+        assert!(matches!(known, RunEventKind::ToolExecuted));
+
+        let unknown: RunEventKind = serde_json::from_str("\"plugin.loaded\"").unwrap();
+        assert_eq!(unknown, RunEventKind::Other("plugin.loaded".to_string()));
+        assert_eq!(
+            serde_json::to_string(&unknown).unwrap(),
+            "\"plugin.loaded\""
+        );
+
+        assert_eq!(
+            RunEventKind::Other("run.started".to_string()),
+            RunEventKind::RunStarted
+        );
+    }
+
     #[test]
     fn test_serialize_event() {
         let event = RunEvent::new("run-123", "action.proposed");
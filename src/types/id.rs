@@ -0,0 +1,220 @@
+//! Validated identifiers for runs and actions.
+//!
+//! A run ID names the run's log file and artifact directory, and an action
+//! ID is built from it, so both are checked once, on construction, instead
+//! of wherever they reach a path.
+
+use std::fmt;
+use std::ops::Deref;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::NexusError;
+
+/// Longest run ID: 255-byte file names, less the `.jsonl` extension.
+pub const MAX_RUN_ID_LEN: usize = 249;
+
+/// Longest action ID.
+pub const MAX_ACTION_ID_LEN: usize = 255;
+
+/// Run ID that would name the run index rather than a log.
+const RESERVED_RUN_ID: &str = "index";
+
+/// Why `id` cannot name a file, if it cannot.
+fn path_safety(id: &str) -> Option<&'static str> {
+    if id.trim().is_empty() {
+        Some("is empty")
+    } else if id.contains('/') || id.contains('\\') || id.contains("..") {
+        Some("contains invalid characters")
+    } else {
+        None
+    }
+}
+
+/// ID of a run, safe to use in file names.
+///
+/// # Examples
+///
+/// ```
+/// use nexus::RunId;
+///
+/// let run_id = RunId::new("run_20260108_120000_000").unwrap();
+/// assert_eq!(run_id, "run_20260108_120000_000");
+/// assert!(RunId::new("../etc/passwd").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct RunId(String);
+
+impl RunId {
+    /// Validates `id` as a run ID.
+    ///
+    /// # Errors
+    /// - `NexusError::InvalidRunId` if `id` is empty, contains path
+    ///   separators or `..`, is reserved for the run index, or is longer
+    ///   than [`MAX_RUN_ID_LEN`]
+    pub fn new(id: impl Into<String>) -> Result<Self, NexusError> {
+        let id = id.into();
+        if let Some(reason) = path_safety(&id) {
+            return Err(NexusError::InvalidRunId(format!("run_id {reason}: {id}")));
+        }
+        if id == RESERVED_RUN_ID {
+            return Err(NexusError::InvalidRunId(format!(
+                "run_id is reserved for the run index: {id}"
+            )));
+        }
+        if id.len() > MAX_RUN_ID_LEN {
+            return Err(NexusError::InvalidRunId(format!(
+                "run_id exceeds {MAX_RUN_ID_LEN} characters (filename limit with .jsonl extension)"
+            )));
+        }
+        Ok(Self(id))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// ID of a proposed action, safe to use in file names.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ActionId(String);
+
+impl ActionId {
+    /// Validates `id` as an action ID.
+    ///
+    /// # Errors
+    /// - `NexusError::InvalidActionId` if `id` is empty, contains path
+    ///   separators or `..`, or is longer than [`MAX_ACTION_ID_LEN`]
+    pub fn new(id: impl Into<String>) -> Result<Self, NexusError> {
+        let id = id.into();
+        if let Some(reason) = path_safety(&id) {
+            return Err(NexusError::InvalidActionId(format!(
+                "action_id {reason}: {id}"
+            )));
+        }
+        if id.len() > MAX_ACTION_ID_LEN {
+            return Err(NexusError::InvalidActionId(format!(
+                "action_id exceeds {MAX_ACTION_ID_LEN} characters"
+            )));
+        }
+        Ok(Self(id))
+    }
+
+    /// ID of the `index`th action proposed in `run_id`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nexus::{ActionId, RunId};
+    ///
+    /// let run_id = RunId::new("run_1").unwrap();
+    /// assert_eq!(ActionId::for_run(&run_id, 2), "run_1-action-2");
+    /// ```
+    pub fn for_run(run_id: &RunId, index: usize) -> Self {
+        Self(format!("{run_id}-action-{index}"))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+macro_rules! impl_id_traits {
+    ($id:ident) => {
+        impl Deref for $id {
+            type Target = str;
+
+            fn deref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl AsRef<str> for $id {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $id {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.pad(&self.0)
+            }
+        }
+
+        impl TryFrom<String> for $id {
+            type Error = NexusError;
+
+            fn try_from(id: String) -> Result<Self, NexusError> {
+                Self::new(id)
+            }
+        }
+
+        impl TryFrom<&str> for $id {
+            type Error = NexusError;
+
+            fn try_from(id: &str) -> Result<Self, NexusError> {
+                Self::new(id)
+            }
+        }
+
+        impl From<$id> for String {
+            fn from(id: $id) -> String {
+                id.0
+            }
+        }
+
+        impl PartialEq<str> for $id {
+            fn eq(&self, other: &str) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl PartialEq<&str> for $id {
+            fn eq(&self, other: &&str) -> bool {
+                self.0 == *other
+            }
+        }
+    };
+}
+
+impl_id_traits!(RunId);
+impl_id_traits!(ActionId);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_id_validation() {
+        assert!(RunId::new("run_123").is_ok());
+        for invalid in [
+            "",
+            "  ",
+            "../etc/passwd",
+            "foo/bar",
+            "foo\\bar",
+            "a..b",
+            "index",
+        ] {
+            assert!(
+                matches!(RunId::new(invalid), Err(NexusError::InvalidRunId(_))),
+                "{invalid:?} should be rejected"
+            );
+        }
+        assert!(RunId::new("r".repeat(MAX_RUN_ID_LEN)).is_ok());
+        assert!(RunId::new("r".repeat(MAX_RUN_ID_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn test_ids_deserialize_with_validation() {
+        let run_id: RunId = serde_json::from_str("\"run_1\"").unwrap();
+        assert_eq!(serde_json::to_string(&run_id).unwrap(), "\"run_1\"");
+        assert!(serde_json::from_str::<RunId>("\"../x\"").is_err());
+
+        let action_id: ActionId = serde_json::from_str("\"run_1-action-1\"").unwrap();
+        assert_eq!(action_id, ActionId::for_run(&run_id, 1));
+        assert!(serde_json::from_str::<ActionId>("\"a/b\"").is_err());
+    }
+}
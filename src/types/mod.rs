@@ -1,7 +1,11 @@
 pub mod action;
 pub mod event;
+pub mod id;
+pub mod run;
 pub mod settings;
 
 pub use action::*;
 pub use event::*;
+pub use id::*;
+pub use run::*;
 pub use settings::*;
@@ -0,0 +1,183 @@
+use chrono::Utc;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::error::exit_codes;
+
+const RUN_ID_PREFIX: &str = "run_";
+const RUN_ID_TIME_FORMAT: &str = "%Y%m%d_%H%M%S";
+const RUN_ID_MILLIS_WIDTH: usize = 3;
+const RUN_ID_RANDOM_BYTES: usize = 4;
+
+/// Scheme used to generate new run IDs.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RunIdScheme {
+    /// `run_YYYYMMDD_HHMMSS_mmm`. Runs started in the same millisecond collide.
+    Timestamp,
+    /// `run_YYYYMMDD_HHMMSS_mmm_xxxxxxxx` with a random hex suffix.
+    #[default]
+    TimestampRandom,
+    /// `run_` followed by a time-ordered UUIDv7.
+    Uuidv7,
+}
+
+impl RunIdScheme {
+    /// Generates a new run ID using this scheme.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nexus::RunIdScheme;
+    ///
+    /// let id = RunIdScheme::TimestampRandom.generate();
+    /// assert!(id.starts_with("run_"));
+    /// assert_ne!(id, RunIdScheme::TimestampRandom.generate());
+    /// ```
+    pub fn generate(&self) -> String {
+        match self {
+            RunIdScheme::Timestamp => timestamp_run_id(),
+            RunIdScheme::TimestampRandom => {
+                let suffix: [u8; RUN_ID_RANDOM_BYTES] = rand::rng().random();
+                let hex: String = suffix.iter().map(|byte| format!("{byte:02x}")).collect();
+                format!("{}_{hex}", timestamp_run_id())
+            }
+            RunIdScheme::Uuidv7 => format!("{RUN_ID_PREFIX}{}", uuid::Uuid::now_v7()),
+        }
+    }
+}
+
+fn timestamp_run_id() -> String {
+    let now = Utc::now();
+    let timestamp = now.format(RUN_ID_TIME_FORMAT).to_string();
+    let millis = now.timestamp_subsec_millis();
+    format!(
+        "{RUN_ID_PREFIX}{timestamp}_{millis:0width$}",
+        width = RUN_ID_MILLIS_WIDTH
+    )
+}
+
+/// Terminal state of a run, recorded in the `run.completed` event.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    /// All proposed actions were applied.
+    Success,
+    /// The run failed before or while proposing actions.
+    Failed,
+    /// The executor proposed no actions; there was nothing to do.
+    CompletedNoChanges,
+    /// Actions were proposed but none were applied (dry run or awaiting approval).
+    ProposedPendingApply,
+    /// Some, but not all, approved actions were applied.
+    PartiallyApplied,
+    /// Applied actions were reverted after a failure.
+    RolledBack,
+    /// The run was interrupted (Ctrl-C) before reaching another terminal state.
+    Cancelled,
+}
+
+impl RunStatus {
+    /// Returns the snake_case label used in event payloads.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RunStatus::Success => "success",
+            RunStatus::Failed => "failed",
+            RunStatus::CompletedNoChanges => "completed_no_changes",
+            RunStatus::ProposedPendingApply => "proposed_pending_apply",
+            RunStatus::PartiallyApplied => "partially_applied",
+            RunStatus::RolledBack => "rolled_back",
+            RunStatus::Cancelled => "cancelled",
+        }
+    }
+
+    /// Returns the process exit code for this terminal state.
+    ///
+    /// | Status                   | Exit code |
+    /// |--------------------------|-----------|
+    /// | `success`                | 0         |
+    /// | `failed`                 | 1         |
+    /// | `completed_no_changes`   | 2         |
+    /// | `proposed_pending_apply` | 3         |
+    /// | `partially_applied`      | 4         |
+    /// | `rolled_back`            | 5         |
+    /// | `cancelled`              | 130       |
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nexus::{RunStatus, exit_codes};
+    ///
+    /// assert_eq!(RunStatus::Success.exit_code(), exit_codes::OK);
+    /// assert_eq!(RunStatus::ProposedPendingApply.exit_code(), exit_codes::PENDING_APPLY);
+    /// ```
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            RunStatus::Success => exit_codes::OK,
+            RunStatus::Failed => exit_codes::GENERAL_ERROR,
+            RunStatus::CompletedNoChanges => exit_codes::NO_CHANGES,
+            RunStatus::ProposedPendingApply => exit_codes::PENDING_APPLY,
+            RunStatus::PartiallyApplied => exit_codes::PARTIALLY_APPLIED,
+            RunStatus::RolledBack => exit_codes::ROLLED_BACK,
+            RunStatus::Cancelled => exit_codes::CANCELLED,
+        }
+    }
+}
+
+impl std::fmt::Display for RunStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_id_scheme_formats() {
+        let timestamp = RunIdScheme::Timestamp.generate();
+        assert_eq!(timestamp.len(), "run_20260101_120000_000".len());
+
+        let random = RunIdScheme::TimestampRandom.generate();
+        let suffix = random.rsplit('_').next().unwrap();
+        assert_eq!(suffix.len(), RUN_ID_RANDOM_BYTES * 2);
+        assert!(suffix.chars().all(|ch| ch.is_ascii_hexdigit()));
+
+        let uuid = RunIdScheme::Uuidv7.generate();
+        let parsed = uuid::Uuid::parse_str(uuid.trim_start_matches(RUN_ID_PREFIX)).unwrap();
+        assert_eq!(parsed.get_version_num(), 7);
+    }
+
+    #[test]
+    fn test_run_id_scheme_random_does_not_collide() {
+        let ids: std::collections::HashSet<String> = (0..1000)
+            .map(|_| RunIdScheme::TimestampRandom.generate())
+            .collect();
+        assert_eq!(ids.len(), 1000);
+    }
+
+    #[test]
+    fn test_run_status_serializes_snake_case() {
+        let json = serde_json::to_string(&RunStatus::ProposedPendingApply).unwrap();
+        assert_eq!(json, "\"proposed_pending_apply\"");
+
+        let parsed: RunStatus = serde_json::from_str("\"rolled_back\"").unwrap();
+        assert_eq!(parsed, RunStatus::RolledBack);
+    }
+
+    #[test]
+    fn test_run_status_exit_codes_are_distinct() {
+        let statuses = [
+            RunStatus::Success,
+            RunStatus::Failed,
+            RunStatus::CompletedNoChanges,
+            RunStatus::ProposedPendingApply,
+            RunStatus::PartiallyApplied,
+            RunStatus::RolledBack,
+        ];
+        let codes: std::collections::HashSet<u8> =
+            statuses.iter().map(RunStatus::exit_code).collect();
+        assert_eq!(codes.len(), statuses.len());
+    }
+}
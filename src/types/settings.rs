@@ -1,5 +1,9 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
 use serde::{Deserialize, Serialize};
 
+use super::{ActionKindTag, RunIdScheme};
 use crate::error::SettingsValidationError;
 
 /// Permission mode enumeration.
@@ -12,6 +16,94 @@ pub enum PermissionMode {
     Autopilot,
 }
 
+/// When the event log writer syncs appended events to disk.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Durability {
+    /// Sync after every event; slowest, loses nothing on a crash.
+    PerEvent,
+    /// Sync after every `durability_interval` events.
+    EveryNEvents,
+    /// Sync when a run finishes and when the log is closed.
+    #[default]
+    OnClose,
+}
+
+/// Events between syncs under `Durability::EveryNEvents` when
+/// `durability_interval` is unset.
+pub const DEFAULT_DURABILITY_INTERVAL: u64 = 100;
+
+/// Outcome of a permission decision.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Decision {
+    /// Execute without prompting.
+    Allow,
+    /// Show an approval prompt.
+    Ask,
+    /// Block execution.
+    Deny,
+}
+
+/// Decision a `policy_tag_rules` entry gives actions carrying its tag.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TagDecision {
+    Allow,
+    Ask,
+    Deny,
+    /// Allow, and tell the user the action was allowed.
+    Notify,
+}
+
+impl TagDecision {
+    /// The permission decision it stands for; `Notify` allows.
+    pub fn decision(self) -> Decision {
+        match self {
+            Self::Allow | Self::Notify => Decision::Allow,
+            Self::Ask => Decision::Ask,
+            Self::Deny => Decision::Deny,
+        }
+    }
+}
+
+/// Maps action attributes to a decision. All populated conditions must match.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalRule {
+    /// Action kinds the rule applies to; empty matches any kind.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub kinds: Vec<ActionKindTag>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_risk: Option<u8>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_risk: Option<u8>,
+
+    /// Policy tags the action must all carry.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub policy_tags: Vec<String>,
+
+    /// Require every touched path to match `allow_paths_write`.
+    #[serde(default)]
+    pub within_allow_paths_write: bool,
+
+    /// Permission modes the rule is active in; empty means all modes.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modes: Vec<PermissionMode>,
+
+    pub decision: Decision,
+}
+
+/// Keys accepted in the `autopilot` object, used for strict-mode suggestions.
+pub const AUTOPILOT_KEYS: &[&str] = &[
+    "max_batch_cu",
+    "max_batch_steps",
+    "auto_approve_patches",
+    "auto_approve_tests",
+    "auto_handoffs",
+];
+
 /// Autopilot configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AutopilotConfig {
@@ -74,6 +166,160 @@ fn default_max_batch_steps() -> u32 {
     8
 }
 
+/// Keys accepted in the `anonymize` object, used for strict-mode suggestions.
+pub const ANONYMIZE_KEYS: &[&str] = &["paths", "payloads", "actor", "model", "keep_keys"];
+
+/// What `nexus log export --anonymize` removes from an event log.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AnonymizePolicy {
+    /// Replace file paths with stable placeholders.
+    #[serde(default = "default_true")]
+    pub paths: bool,
+
+    /// Replace free-text payload values and drop artifact fingerprints.
+    #[serde(default = "default_true")]
+    pub payloads: bool,
+
+    /// Drop the `actor` of every event.
+    #[serde(default = "default_true")]
+    pub actor: bool,
+
+    /// Drop model and provider names from payloads.
+    #[serde(default = "default_true")]
+    pub model: bool,
+
+    /// Extra payload keys whose values are kept verbatim.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keep_keys: Vec<String>,
+}
+
+impl Default for AnonymizePolicy {
+    fn default() -> Self {
+        Self {
+            paths: true,
+            payloads: true,
+            actor: true,
+            model: true,
+            keep_keys: Vec::new(),
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Keys accepted in the `git` object, used for strict-mode suggestions.
+pub const GIT_KEYS: &[&str] = &["branch_per_run", "require_clean", "commit_per_action"];
+
+/// How `nexus apply` uses git when the project is a git work tree.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GitSettings {
+    /// Apply each run's changes on its own `nexus/<run_id>` branch.
+    #[serde(default = "default_true")]
+    pub branch_per_run: bool,
+
+    /// Refuse to apply while tracked files have uncommitted changes.
+    #[serde(default)]
+    pub require_clean: bool,
+
+    /// Commit the files of each applied action on their own, with the run
+    /// and action IDs in the message.
+    #[serde(default)]
+    pub commit_per_action: bool,
+}
+
+impl Default for GitSettings {
+    fn default() -> Self {
+        Self {
+            branch_per_run: true,
+            require_clean: false,
+            commit_per_action: false,
+        }
+    }
+}
+
+/// Keys accepted in the `verify` object, used for strict-mode suggestions.
+pub const VERIFY_KEYS: &[&str] = &["commands", "timeout_s", "rollback_on_failure"];
+
+/// Checks `nexus apply` runs once a run's actions are applied.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct VerifySettings {
+    /// Commands run in order from the project root, e.g.
+    /// `[["cargo", "check"]]`; the first that fails stops the rest.
+    #[serde(default)]
+    pub commands: Vec<Vec<String>>,
+
+    /// Seconds each command may run before it is killed.
+    #[serde(default = "default_verify_timeout_s")]
+    pub timeout_s: u32,
+
+    /// Undo the run's applied actions when a check fails.
+    #[serde(default)]
+    pub rollback_on_failure: bool,
+}
+
+impl Default for VerifySettings {
+    fn default() -> Self {
+        Self {
+            commands: Vec::new(),
+            timeout_s: default_verify_timeout_s(),
+            rollback_on_failure: false,
+        }
+    }
+}
+
+fn default_verify_timeout_s() -> u32 {
+    600
+}
+
+/// Header carrying [`NexusSettings::organization`].
+const ORGANIZATION_HEADER: &str = "OpenAI-Organization";
+
+/// Top-level settings keys, used for strict-mode suggestions.
+pub const SETTINGS_KEYS: &[&str] = &[
+    "schema_version",
+    "permission_mode",
+    "deny_paths",
+    "allow_paths_write",
+    "binary_allow_paths",
+    "redact_patterns",
+    "disallowed_licenses",
+    "deps_audit_command",
+    "approval_rules",
+    "policy_tag_rules",
+    "prompt_examples",
+    "provider",
+    "model",
+    "context_token_budget",
+    "max_retry_after_secs",
+    "proxy",
+    "ca_cert",
+    "organization",
+    "api_key_command",
+    "api_key_file",
+    "api_key_keychain",
+    "extra_headers",
+    "hide_thinking",
+    "max_run_tokens",
+    "max_run_cost_usd",
+    "allow_commands",
+    "ask_commands",
+    "deny_commands",
+    "autopilot",
+    "anonymize",
+    "git",
+    "verify",
+    "review_actions",
+    "rationale_followup",
+    "tool_calling",
+    "normalize_unicode",
+    "run_id_scheme",
+    "durability",
+    "durability_interval",
+    "strict",
+];
+
 /// Nexus settings (matches .nexus/schemas/settings.schema.json).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NexusSettings {
@@ -89,6 +335,107 @@ pub struct NexusSettings {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub allow_paths_write: Vec<String>,
 
+    /// Paths exempt from binary detection (e.g. formats with embedded NUL bytes).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub binary_allow_paths: Vec<String>,
+
+    /// Extra regular expressions masked before text reaches logs, stderr, or artifacts.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redact_patterns: Vec<String>,
+
+    /// SPDX license identifiers flagged when a proposed change adds them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disallowed_licenses: Vec<String>,
+
+    /// Command (argv) that must pass before dependency manifest edits are
+    /// auto-approved, e.g. `["cargo", "deny", "check"]`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deps_audit_command: Vec<String>,
+
+    /// Rules evaluated before the interactive approval prompt.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub approval_rules: Vec<ApprovalRule>,
+
+    /// Decision for actions carrying a policy tag, e.g. `{"network": "deny"}`,
+    /// weighed together with `approval_rules`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub policy_tag_rules: BTreeMap<String, TagDecision>,
+
+    /// Few-shot examples under `.nexus/prompts/examples/`, sent as prior turns.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub prompt_examples: Vec<String>,
+
+    /// Model backend, e.g. `openai`, `anthropic`, `ollama`, or `azure`;
+    /// `--provider` overrides it. Unset uses OpenAI.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+
+    /// Model requested from the provider; `--model` overrides it. Unset
+    /// uses the provider's default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+
+    /// Estimated tokens of context file contents sent per request; files
+    /// past it are truncated or dropped. Unset sends every file whole.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_token_budget: Option<usize>,
+
+    /// Longest `Retry-After`, in seconds, waited out before retrying a
+    /// rate-limited request. Unset uses 120.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_retry_after_secs: Option<u64>,
+
+    /// Proxy for provider requests, e.g. `http://proxy.corp:3128`;
+    /// `NEXUS_PROXY` overrides it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+
+    /// PEM file of extra root certificates to trust for provider requests;
+    /// `NEXUS_CA_CERT` overrides it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_cert: Option<PathBuf>,
+
+    /// OpenAI organization billed for requests, sent as
+    /// `OpenAI-Organization`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub organization: Option<String>,
+
+    /// Credential helper (argv) printing the API key on stdout, asked when
+    /// the provider's key variable is unset. It runs with
+    /// `NEXUS_AUTH_PROVIDER` set to the provider's name.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub api_key_command: Vec<String>,
+
+    /// File holding the API key, asked after `api_key_command`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_file: Option<String>,
+
+    /// Keychain service holding API keys, one entry per provider name as
+    /// the account (macOS Keychain, or the secret service via
+    /// `secret-tool`); asked last.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_keychain: Option<String>,
+
+    /// Extra headers (name to value) sent with every provider request, e.g.
+    /// an internal gateway's auth header.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra_headers: BTreeMap<String, String>,
+
+    /// Keep the model's reasoning out of the live view; only its answer is
+    /// streamed.
+    #[serde(default)]
+    pub hide_thinking: bool,
+
+    /// Prompt plus completion tokens a run may use across its calls before
+    /// it is aborted. Unset is unlimited.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_run_tokens: Option<u64>,
+
+    /// Estimated cost in USD a run may reach before it is aborted; only
+    /// enforced for models with a known price. Unset is unlimited.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_run_cost_usd: Option<f64>,
+
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub allow_commands: Vec<Vec<String>>,
 
@@ -100,30 +447,92 @@ pub struct NexusSettings {
 
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub autopilot: Option<AutopilotConfig>,
+
+    /// Policy for anonymized log exports; everything is stripped when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anonymize: Option<AnonymizePolicy>,
+
+    /// Git integration of `nexus apply`; branches per run when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<GitSettings>,
+
+    /// Checks run after `nexus apply` changes files; none when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verify: Option<VerifySettings>,
+
+    /// Have the reviewer agent look over each proposed action before it is
+    /// approved.
+    #[serde(default)]
+    pub review_actions: bool,
+
+    /// Ask the model in a short follow-up request for the `why` of actions
+    /// it proposed without one.
+    #[serde(default)]
+    pub rationale_followup: bool,
+
+    /// Ask for actions through the `propose_actions` tool instead of as
+    /// free text; replies that do not call it are still parsed.
+    #[serde(default)]
+    pub tool_calling: bool,
+
+    /// Retry search text that is not found after Unicode NFC normalization
+    /// and with invisible characters ignored.
+    #[serde(default = "default_true")]
+    pub normalize_unicode: bool,
+
+    #[serde(default)]
+    pub run_id_scheme: RunIdScheme,
+
+    /// When event logs are synced to disk; trades throughput for crash
+    /// safety on large runs.
+    #[serde(default)]
+    pub durability: Durability,
+
+    /// Events between syncs when `durability` is `every_n_events`. Unset
+    /// uses 100.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub durability_interval: Option<u64>,
+
+    /// Reject unknown keys instead of ignoring them.
+    #[serde(default)]
+    pub strict: bool,
 }
 
-/// Returns the default schema version used by Nexus settings ("1.0").
+/// Settings schema version this build reads and writes. Older documents
+/// are upgraded by [`crate::settings::migrate`].
+pub const SCHEMA_VERSION: &str = "1.1";
+
+/// Returns the default schema version used by Nexus settings ([`SCHEMA_VERSION`]).
 fn default_schema_version() -> String {
-    "1.0".to_string()
+    SCHEMA_VERSION.to_string()
 }
 
 impl Default for NexusSettings {
     /// Creates a NexusSettings initialized with the module's canonical defaults.
     ///
     /// Defaults:
-    /// - `schema_version` = "1.0"
+    /// - `schema_version` = [`SCHEMA_VERSION`]
     /// - `permission_mode` = `PermissionMode::Default`
     /// - `deny_paths` includes [".env*", "**/.ssh/**", "**/.aws/**", "**/.npmrc", "**/.pypirc"]
     /// - `deny_commands` includes `["sudo"]` and `["rm"]`
+    /// - `disallowed_licenses` = `["AGPL-3.0", "GPL-2.0", "GPL-3.0"]`
     /// - `autopilot` = `None`
+    /// - `git` = `None` (each run is applied on its own branch)
+    /// - `verify` = `None`
+    /// - `review_actions` = `false`
+    /// - `rationale_followup` = `false`
+    /// - `tool_calling` = `false`
+    /// - `normalize_unicode` = `true`
+    /// - `run_id_scheme` = `RunIdScheme::TimestampRandom`
+    /// - `durability` = `Durability::OnClose`
     ///
     /// # Examples
     ///
     /// ```
-    /// use nexus::{NexusSettings, PermissionMode};
+    /// use nexus::{NexusSettings, PermissionMode, SCHEMA_VERSION};
     ///
     /// let s = NexusSettings::default();
-    /// assert_eq!(s.schema_version, "1.0");
+    /// assert_eq!(s.schema_version, SCHEMA_VERSION);
     /// assert!(s.deny_paths.contains(&".env*".to_string()));
     /// assert_eq!(s.permission_mode, PermissionMode::Default);
     /// assert!(s.autopilot.is_none());
@@ -140,20 +549,102 @@ impl Default for NexusSettings {
                 "**/.pypirc".to_string(),
             ],
             allow_paths_write: Vec::new(),
+            binary_allow_paths: Vec::new(),
+            redact_patterns: Vec::new(),
+            disallowed_licenses: vec![
+                "AGPL-3.0".to_string(),
+                "GPL-2.0".to_string(),
+                "GPL-3.0".to_string(),
+            ],
+            deps_audit_command: Vec::new(),
+            approval_rules: Vec::new(),
+            policy_tag_rules: BTreeMap::new(),
+            prompt_examples: Vec::new(),
+            provider: None,
+            model: None,
+            context_token_budget: None,
+            max_retry_after_secs: None,
+            proxy: None,
+            ca_cert: None,
+            organization: None,
+            api_key_command: Vec::new(),
+            api_key_file: None,
+            api_key_keychain: None,
+            extra_headers: BTreeMap::new(),
+            hide_thinking: false,
+            max_run_tokens: None,
+            max_run_cost_usd: None,
             allow_commands: Vec::new(),
             ask_commands: Vec::new(),
             deny_commands: vec![vec!["sudo".to_string()], vec!["rm".to_string()]],
             autopilot: None,
+            anonymize: None,
+            git: None,
+            verify: None,
+            review_actions: false,
+            rationale_followup: false,
+            tool_calling: false,
+            normalize_unicode: true,
+            run_id_scheme: RunIdScheme::default(),
+            durability: Durability::default(),
+            durability_interval: None,
+            strict: false,
         }
     }
 }
 
 impl NexusSettings {
+    /// `extra_headers`, plus `OpenAI-Organization` when `organization` is
+    /// set and the header is not given explicitly.
+    pub fn request_headers(&self) -> BTreeMap<String, String> {
+        let mut headers = self.extra_headers.clone();
+        if let Some(organization) = &self.organization {
+            let explicit = headers
+                .keys()
+                .any(|name| name.eq_ignore_ascii_case(ORGANIZATION_HEADER));
+            if !explicit {
+                headers.insert(ORGANIZATION_HEADER.to_string(), organization.clone());
+            }
+        }
+        headers
+    }
+
+    /// Events the log writer appends between syncs under `durability`;
+    /// `None` syncs only when a run finishes or the log is closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nexus::{Durability, NexusSettings};
+    ///
+    /// let settings = NexusSettings {
+    ///     durability: Durability::EveryNEvents,
+    ///     durability_interval: Some(50),
+    ///     ..NexusSettings::default()
+    /// };
+    /// assert_eq!(settings.event_sync_interval(), Some(50));
+    /// assert_eq!(NexusSettings::default().event_sync_interval(), None);
+    /// ```
+    pub fn event_sync_interval(&self) -> Option<u64> {
+        match self.durability {
+            Durability::PerEvent => Some(1),
+            Durability::EveryNEvents => Some(
+                self.durability_interval
+                    .unwrap_or(DEFAULT_DURABILITY_INTERVAL),
+            ),
+            Durability::OnClose => None,
+        }
+    }
+
     /// Validate that the settings conform to the expected schema and constraints.
     ///
-    /// This checks that the `schema_version` equals "1.0", validates each pattern in
-    /// `deny_paths` and `allow_paths_write`, and verifies that any present `autopilot`
-    /// configuration has `max_batch_cu` and `max_batch_steps` greater than or equal to 1.
+    /// This checks that the `schema_version` equals [`SCHEMA_VERSION`], validates each pattern in
+    /// `deny_paths`, `allow_paths_write`, and `binary_allow_paths`, checks that each
+    /// `redact_patterns` entry is a valid regular expression, that `prompt_examples`
+    /// are bare names, that any `context_token_budget`, `max_run_tokens`, and
+    /// `durability_interval` are at least 1 and any `max_run_cost_usd` is positive, and verifies that
+    /// any present `autopilot` configuration has `max_batch_cu` and `max_batch_steps`
+    /// greater than or equal to 1.
     ///
     /// # Returns
     ///
@@ -169,7 +660,7 @@ impl NexusSettings {
     /// assert!(settings.validate().is_ok());
     /// ```
     pub fn validate(&self) -> Result<(), SettingsValidationError> {
-        if self.schema_version != "1.0" {
+        if self.schema_version != SCHEMA_VERSION {
             return Err(SettingsValidationError::InvalidSchemaVersion(
                 self.schema_version.clone(),
             ));
@@ -181,6 +672,48 @@ impl NexusSettings {
         for path in &self.allow_paths_write {
             validate_path_pattern(path)?;
         }
+        for path in &self.binary_allow_paths {
+            validate_path_pattern(path)?;
+        }
+        for pattern in &self.redact_patterns {
+            regex::Regex::new(pattern).map_err(|err| {
+                SettingsValidationError::InvalidRedactPattern {
+                    pattern: pattern.clone(),
+                    reason: err.to_string(),
+                }
+            })?;
+        }
+
+        for name in &self.prompt_examples {
+            validate_prompt_example(name)?;
+        }
+
+        for (index, rule) in self.approval_rules.iter().enumerate() {
+            if let (Some(min), Some(max)) = (rule.min_risk, rule.max_risk) {
+                if min > max {
+                    return Err(SettingsValidationError::InvalidApprovalRule {
+                        index,
+                        reason: format!("min_risk {min} is greater than max_risk {max}"),
+                    });
+                }
+            }
+        }
+
+        if self.context_token_budget == Some(0) {
+            return Err(SettingsValidationError::InvalidContextTokenBudget(0));
+        }
+        if self.max_run_tokens == Some(0) {
+            return Err(SettingsValidationError::InvalidMaxRunTokens(0));
+        }
+        if self.durability_interval == Some(0) {
+            return Err(SettingsValidationError::InvalidDurabilityInterval(0));
+        }
+        if let Some(cost) = self
+            .max_run_cost_usd
+            .filter(|cost| cost.is_nan() || *cost <= 0.0)
+        {
+            return Err(SettingsValidationError::InvalidMaxRunCost(cost));
+        }
 
         if let Some(ref autopilot) = self.autopilot {
             if autopilot.max_batch_cu < 1 {
@@ -199,6 +732,21 @@ impl NexusSettings {
     }
 }
 
+/// Validates a `prompt_examples` entry: a bare name inside the examples directory.
+fn validate_prompt_example(name: &str) -> Result<(), SettingsValidationError> {
+    let reason = if name.trim().is_empty() {
+        "name is empty"
+    } else if name.contains(['/', '\\']) || name.contains("..") {
+        "name must not contain path separators or '..'"
+    } else {
+        return Ok(());
+    };
+    Err(SettingsValidationError::InvalidPromptExample {
+        name: name.to_string(),
+        reason: reason.to_string(),
+    })
+}
+
 /// Validates a path glob pattern for Nexus settings.
 ///
 /// Ensures the pattern does not contain path traversal (`..`), is not an absolute
@@ -262,7 +810,7 @@ mod tests {
     #[test]
     fn test_default_settings() {
         let settings = NexusSettings::default();
-        assert_eq!(settings.schema_version, "1.0");
+        assert_eq!(settings.schema_version, SCHEMA_VERSION);
         assert_eq!(settings.permission_mode, PermissionMode::Default);
         assert_eq!(
             settings.deny_paths,
@@ -357,4 +905,201 @@ mod tests {
             if reason.contains("control characters")
         ));
     }
+
+    #[test]
+    fn test_validate_prompt_examples() {
+        let mut settings = NexusSettings {
+            prompt_examples: vec!["rename_fn".to_string()],
+            ..NexusSettings::default()
+        };
+        assert!(settings.validate().is_ok());
+
+        settings.prompt_examples = vec!["../secrets".to_string()];
+        assert!(matches!(
+            settings.validate(),
+            Err(SettingsValidationError::InvalidPromptExample { .. })
+        ));
+    }
+
+    #[test]
+    fn test_validate_context_token_budget() {
+        let mut settings = NexusSettings {
+            context_token_budget: Some(50_000),
+            ..NexusSettings::default()
+        };
+        assert!(settings.validate().is_ok());
+
+        settings.context_token_budget = Some(0);
+        assert!(matches!(
+            settings.validate(),
+            Err(SettingsValidationError::InvalidContextTokenBudget(0))
+        ));
+    }
+
+    #[test]
+    fn test_request_headers_include_organization() {
+        let mut settings = NexusSettings {
+            organization: Some("org-123".to_string()),
+            extra_headers: BTreeMap::from([("X-Gateway".to_string(), "team-a".to_string())]),
+            ..NexusSettings::default()
+        };
+        assert_eq!(
+            settings.request_headers(),
+            BTreeMap::from([
+                ("OpenAI-Organization".to_string(), "org-123".to_string()),
+                ("X-Gateway".to_string(), "team-a".to_string()),
+            ])
+        );
+
+        settings
+            .extra_headers
+            .insert("openai-organization".to_string(), "org-456".to_string());
+        assert_eq!(settings.request_headers().len(), 2);
+        assert_eq!(settings.request_headers()["openai-organization"], "org-456");
+    }
+
+    #[test]
+    fn test_validate_run_budget() {
+        let mut settings = NexusSettings {
+            max_run_tokens: Some(100_000),
+            max_run_cost_usd: Some(1.5),
+            ..NexusSettings::default()
+        };
+        assert!(settings.validate().is_ok());
+
+        settings.max_run_cost_usd = Some(0.0);
+        assert!(matches!(
+            settings.validate(),
+            Err(SettingsValidationError::InvalidMaxRunCost(_))
+        ));
+
+        settings.max_run_tokens = Some(0);
+        assert!(matches!(
+            settings.validate(),
+            Err(SettingsValidationError::InvalidMaxRunTokens(0))
+        ));
+    }
+
+    #[test]
+    fn test_durability_sync_interval() {
+        let mut settings: NexusSettings =
+            serde_json::from_str(r#"{"durability": "per_event"}"#).unwrap();
+        assert_eq!(settings.durability, Durability::PerEvent);
+        assert_eq!(settings.event_sync_interval(), Some(1));
+
+        settings.durability = Durability::EveryNEvents;
+        assert_eq!(
+            settings.event_sync_interval(),
+            Some(DEFAULT_DURABILITY_INTERVAL)
+        );
+        settings.durability_interval = Some(0);
+        assert!(matches!(
+            settings.validate(),
+            Err(SettingsValidationError::InvalidDurabilityInterval(0))
+        ));
+    }
+
+    #[test]
+    fn test_settings_keys_cover_all_fields() {
+        let settings = NexusSettings {
+            allow_paths_write: vec!["src/**".to_string()],
+            binary_allow_paths: vec!["*.bin".to_string()],
+            redact_patterns: vec!["secret".to_string()],
+            approval_rules: vec![ApprovalRule {
+                kinds: Vec::new(),
+                min_risk: None,
+                max_risk: None,
+                policy_tags: Vec::new(),
+                within_allow_paths_write: false,
+                modes: Vec::new(),
+                decision: Decision::Ask,
+            }],
+            policy_tag_rules: BTreeMap::from([("network".to_string(), TagDecision::Deny)]),
+            prompt_examples: vec!["rename".to_string()],
+            provider: Some("ollama".to_string()),
+            model: Some("qwen2.5-coder".to_string()),
+            context_token_budget: Some(50_000),
+            max_retry_after_secs: Some(30),
+            proxy: Some("http://proxy.corp:3128".to_string()),
+            ca_cert: Some(PathBuf::from("corp-ca.pem")),
+            organization: Some("org-123".to_string()),
+            api_key_command: vec!["op".to_string(), "read".to_string()],
+            api_key_file: Some("~/.nexus-key".to_string()),
+            api_key_keychain: Some("nexus".to_string()),
+            extra_headers: BTreeMap::from([("X-Gateway".to_string(), "team-a".to_string())]),
+            max_run_tokens: Some(200_000),
+            max_run_cost_usd: Some(2.5),
+            durability_interval: Some(500),
+            deps_audit_command: vec!["cargo".to_string(), "deny".to_string()],
+            allow_commands: vec![vec!["cargo".to_string()]],
+            ask_commands: vec![vec!["git".to_string()]],
+            autopilot: Some(AutopilotConfig::default()),
+            anonymize: Some(AnonymizePolicy {
+                keep_keys: vec!["task".to_string()],
+                ..AnonymizePolicy::default()
+            }),
+            git: Some(GitSettings::default()),
+            verify: Some(VerifySettings {
+                commands: vec![vec!["cargo".to_string(), "check".to_string()]],
+                ..VerifySettings::default()
+            }),
+            ..NexusSettings::default()
+        };
+        let value = serde_json::to_value(&settings).unwrap();
+
+        let mut keys: Vec<&str> = value
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        let mut expected = SETTINGS_KEYS.to_vec();
+        keys.sort_unstable();
+        expected.sort_unstable();
+        assert_eq!(keys, expected);
+
+        let mut autopilot_keys: Vec<&str> = value["autopilot"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        let mut expected = AUTOPILOT_KEYS.to_vec();
+        autopilot_keys.sort_unstable();
+        expected.sort_unstable();
+        assert_eq!(autopilot_keys, expected);
+
+        let mut anonymize_keys: Vec<&str> = value["anonymize"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        let mut expected = ANONYMIZE_KEYS.to_vec();
+        anonymize_keys.sort_unstable();
+        expected.sort_unstable();
+        assert_eq!(anonymize_keys, expected);
+
+        let mut git_keys: Vec<&str> = value["git"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        let mut expected = GIT_KEYS.to_vec();
+        git_keys.sort_unstable();
+        expected.sort_unstable();
+        assert_eq!(git_keys, expected);
+
+        let mut verify_keys: Vec<&str> = value["verify"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        let mut expected = VERIFY_KEYS.to_vec();
+        verify_keys.sort_unstable();
+        expected.sort_unstable();
+        assert_eq!(verify_keys, expected);
+    }
 }
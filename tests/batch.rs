@@ -0,0 +1,420 @@
+use secrecy::SecretString;
+use tempfile::TempDir;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use nexus::CodexAdapter;
+use nexus::RunStatus;
+use nexus::batch::{BatchFile, BatchRunner, FollowUp};
+use nexus::event_log::EventLogReader;
+use nexus::preview::pending_actions;
+use nexus::redact::Redactor;
+use nexus::session::Session;
+use nexus::types::AgentRole;
+
+const FIXTURE: &str = "tests/fixtures/codex_responses/unified_diff_single.txt";
+
+#[tokio::test]
+async fn test_batch_runs_each_task_with_its_own_log() {
+    // Arrange
+    let server = MockServer::start().await;
+    let body = std::fs::read_to_string(FIXTURE).expect("read fixture");
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(body, "text/event-stream"))
+        .mount(&server)
+        .await;
+    let adapter = CodexAdapter::new(SecretString::from("test-key"))
+        .with_base_url(format!("{}/v1", server.uri()));
+
+    let dir = TempDir::new().expect("create temp dir");
+    std::fs::create_dir_all(dir.path().join("src")).unwrap();
+    std::fs::write(dir.path().join("src/lib.rs"), "pub fn a() {}\n").unwrap();
+    let batch_path = dir.path().join("tasks.yaml");
+    std::fs::write(
+        &batch_path,
+        "tasks:\n  - task: Update lib\n    files: [\"src/*.rs\"]\n  - task: Broken globs\n    files: [\"src/[\"]\n",
+    )
+    .unwrap();
+    let batch = BatchFile::load(&batch_path).expect("load batch");
+
+    // Act
+    let outcomes = BatchRunner::new(&adapter, dir.path())
+        .with_jobs(2)
+        .run(&batch)
+        .await
+        .expect("run batch");
+
+    // Assert
+    assert_eq!(outcomes.len(), 2);
+    assert_ne!(outcomes[0].run_id, outcomes[1].run_id);
+    assert_eq!(outcomes[0].status, RunStatus::ProposedPendingApply);
+    assert_eq!(outcomes[0].action_count, 1);
+    assert_eq!(outcomes[1].status, RunStatus::Failed);
+    assert!(outcomes[1].is_failure());
+
+    for outcome in &outcomes {
+        let events = EventLogReader::open(&outcome.log_path)
+            .expect("open log")
+            .load_all()
+            .expect("load log");
+        assert!(events.iter().all(|event| event.run_id == outcome.run_id));
+        assert_eq!(events.first().unwrap().event_type, "run.started");
+        assert_eq!(events.last().unwrap().event_type, "run.completed");
+        let summary = outcome.log_path.with_extension("summary.md");
+        assert!(summary.is_file(), "{}", summary.display());
+    }
+}
+
+#[tokio::test]
+async fn test_batch_masks_secrets_in_run_events() {
+    let server = MockServer::start().await;
+    let body = std::fs::read_to_string(FIXTURE).expect("read fixture");
+    let adapter = mock_adapter(&server, body).await;
+    let dir = TempDir::new().expect("create temp dir");
+    std::fs::create_dir_all(dir.path().join("src")).unwrap();
+    std::fs::write(dir.path().join("src/lib.rs"), "pub fn a() {}\n").unwrap();
+    let batch_path = dir.path().join("tasks.yaml");
+    std::fs::write(
+        &batch_path,
+        "tasks:\n  - task: Rotate sk-live-0123456789\n    files: [\"src/*.rs\"]\n",
+    )
+    .unwrap();
+    let batch = BatchFile::load(&batch_path).expect("load batch");
+
+    let outcomes = BatchRunner::new(&adapter, dir.path())
+        .with_redactor(Redactor::new().with_secret("sk-live-0123456789"))
+        .run(&batch)
+        .await
+        .expect("run batch");
+
+    let log = std::fs::read_to_string(&outcomes[0].log_path).expect("read log");
+    assert!(log.contains("Rotate [REDACTED]"), "{log}");
+    assert!(!log.contains("sk-live-0123456789"));
+}
+
+#[tokio::test]
+async fn test_refine_continues_the_conversation() {
+    // Arrange
+    let server = MockServer::start().await;
+    let adapter = mock_adapter(
+        &server,
+        std::fs::read_to_string(FIXTURE).expect("read fixture"),
+    )
+    .await;
+    let dir = TempDir::new().expect("create temp dir");
+    std::fs::create_dir_all(dir.path().join("src")).unwrap();
+    std::fs::write(dir.path().join("src/lib.rs"), "pub fn a() {}\n").unwrap();
+    let batch_path = dir.path().join("tasks.yaml");
+    std::fs::write(
+        &batch_path,
+        "tasks:\n  - task: Update lib\n    files: [\"src/*.rs\"]\n",
+    )
+    .unwrap();
+    let batch = BatchFile::load(&batch_path).expect("load batch");
+    let first = BatchRunner::new(&adapter, dir.path())
+        .run(&batch)
+        .await
+        .expect("run batch")
+        .remove(0);
+
+    // Act
+    let session = Session::load(dir.path(), &first.run_id).expect("load session");
+    let task = session
+        .to_task("also update the tests")
+        .expect("build task");
+    let refiner = CodexAdapter::new(SecretString::from("test-key"))
+        .with_base_url(format!("{}/v1", server.uri()))
+        .with_history(session.messages.clone());
+    let refined = BatchRunner::new(&refiner, dir.path())
+        .run_follow_up(&task, FollowUp::Refine(&first.run_id))
+        .await
+        .expect("refine run");
+
+    // Assert
+    assert_eq!(session.actions.len(), 1);
+    assert_eq!(refined.status, RunStatus::ProposedPendingApply);
+
+    let requests = server.received_requests().await.unwrap();
+    let last = requests.last().expect("refine request");
+    let sent: serde_json::Value = serde_json::from_slice(&last.body).unwrap();
+    let messages = sent["messages"].as_array().unwrap();
+    let roles: Vec<&str> = messages
+        .iter()
+        .map(|message| message["role"].as_str().unwrap())
+        .collect();
+    assert_eq!(roles, ["system", "user", "assistant", "user"]);
+    assert!(
+        messages[1]["content"]
+            .as_str()
+            .unwrap()
+            .contains("Update lib")
+    );
+    assert!(
+        messages[2]["content"]
+            .as_str()
+            .unwrap()
+            .contains("--- a/src/lib.rs")
+    );
+    assert!(
+        messages[3]["content"]
+            .as_str()
+            .unwrap()
+            .contains("also update the tests")
+    );
+
+    let events = EventLogReader::open(&refined.log_path)
+        .expect("open log")
+        .load_all()
+        .expect("load log");
+    assert_eq!(
+        events[0].payload.as_ref().unwrap()["refine_of"],
+        first.run_id.as_str()
+    );
+    let next = Session::load(dir.path(), &refined.run_id).expect("load refined session");
+    assert_eq!(next.messages.len(), 5);
+}
+
+/// A streamed completion whose whole reply is `content`.
+fn completion_stream(content: &str) -> String {
+    let chunk = |delta: serde_json::Value, finish: Option<&str>| {
+        let chunk = serde_json::json!({
+            "id": "chatcmpl-123",
+            "object": "chat.completion.chunk",
+            "created": 1700000000,
+            "model": "gpt-5.2-codex",
+            "choices": [{"index": 0, "delta": delta, "finish_reason": finish}]
+        });
+        format!("data: {chunk}\n\n")
+    };
+    let mut body = chunk(
+        serde_json::json!({"role": "assistant", "content": content}),
+        None,
+    );
+    body.push_str(&chunk(serde_json::json!({}), Some("stop")));
+    body.push_str("data: [DONE]\n\n");
+    body
+}
+
+async fn mock_adapter(server: &MockServer, body: String) -> CodexAdapter {
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(body, "text/event-stream"))
+        .mount(server)
+        .await;
+    CodexAdapter::new(SecretString::from("test-key")).with_base_url(format!("{}/v1", server.uri()))
+}
+
+#[tokio::test]
+async fn test_batch_hands_run_over_to_reviewer() {
+    // Arrange
+    let actions = serde_json::json!([
+        {
+            "id": "a1",
+            "summary": "Update lib",
+            "kind": "patch",
+            "details": {"format": "unified", "diff": "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1 +1 @@\n-old\n+new\n"}
+        },
+        {
+            "id": "a2",
+            "summary": "Review the update",
+            "kind": "handoff",
+            "details": {"from": "executor", "to": "reviewer", "reason": "check the new code"}
+        }
+    ]);
+    let executor_server = MockServer::start().await;
+    let executor = mock_adapter(
+        &executor_server,
+        completion_stream(&format!("```json\n{actions}\n```\n")),
+    )
+    .await;
+    let reviewer_server = MockServer::start().await;
+    let reviewer = mock_adapter(
+        &reviewer_server,
+        std::fs::read_to_string(FIXTURE).expect("read fixture"),
+    )
+    .await;
+
+    let dir = TempDir::new().expect("create temp dir");
+    std::fs::create_dir_all(dir.path().join("src")).unwrap();
+    std::fs::write(dir.path().join("src/lib.rs"), "old\n").unwrap();
+    let batch_path = dir.path().join("tasks.yaml");
+    std::fs::write(
+        &batch_path,
+        "tasks:\n  - task: Update lib\n    files: [\"src/*.rs\"]\n",
+    )
+    .unwrap();
+    let batch = BatchFile::load(&batch_path).expect("load batch");
+
+    // Act
+    let outcomes = BatchRunner::new(&executor, dir.path())
+        .with_role_executor(AgentRole::Reviewer, &reviewer)
+        .run(&batch)
+        .await
+        .expect("run batch");
+
+    // Assert
+    let outcome = &outcomes[0];
+    assert_eq!(outcome.status, RunStatus::ProposedPendingApply);
+    assert_eq!(outcome.action_count, 2);
+
+    let reviewed = reviewer_server.received_requests().await.unwrap();
+    let prompt = reviewed
+        .iter()
+        .map(|request| String::from_utf8_lossy(&request.body))
+        .find(|body| !body.is_empty())
+        .expect("reviewer prompt");
+    assert!(prompt.contains("check the new code"));
+
+    let events = EventLogReader::open(&outcome.log_path)
+        .expect("open log")
+        .load_all()
+        .expect("load log");
+    let kinds: Vec<&str> = events
+        .iter()
+        .map(|event| event.event_type.as_str())
+        .collect();
+    let requested = kinds
+        .iter()
+        .position(|kind| *kind == "handoff.requested")
+        .unwrap();
+    assert_eq!(kinds[requested + 1], "handoff.accepted");
+    assert_eq!(
+        kinds
+            .iter()
+            .filter(|kind| **kind == "executor.started")
+            .count(),
+        2
+    );
+    assert_eq!(kinds.last(), Some(&"run.completed"));
+
+    let pending = pending_actions(&outcome.log_path).expect("load pending actions");
+    let ids: Vec<&str> = pending.iter().map(|action| action.id.as_str()).collect();
+    assert_eq!(ids.len(), 2);
+    assert_eq!(ids[0], "a1");
+    assert!(ids[1].ends_with("_h1"), "{ids:?}");
+}
+
+#[tokio::test]
+async fn test_batch_plans_then_executes_each_step() {
+    // Arrange
+    let plan = serde_json::json!({"steps": [
+        {"title": "Update lib", "detail": "src/lib.rs"},
+        {"title": "Update it again"}
+    ]});
+    let planner_server = MockServer::start().await;
+    let planner = mock_adapter(&planner_server, completion_stream(&plan.to_string())).await;
+    let executor_server = MockServer::start().await;
+    let executor = mock_adapter(
+        &executor_server,
+        std::fs::read_to_string(FIXTURE).expect("read fixture"),
+    )
+    .await;
+
+    let dir = TempDir::new().expect("create temp dir");
+    std::fs::create_dir_all(dir.path().join("src")).unwrap();
+    std::fs::write(dir.path().join("src/lib.rs"), "old\n").unwrap();
+    let batch_path = dir.path().join("tasks.yaml");
+    std::fs::write(
+        &batch_path,
+        "tasks:\n  - task: Update lib\n    files: [\"src/*.rs\"]\n    options:\n      plan: true\n",
+    )
+    .unwrap();
+    let batch = BatchFile::load(&batch_path).expect("load batch");
+
+    // Act
+    let outcomes = BatchRunner::new(&executor, dir.path())
+        .with_role_executor(AgentRole::Planner, &planner)
+        .run(&batch)
+        .await
+        .expect("run batch");
+
+    // Assert
+    let outcome = &outcomes[0];
+    assert_eq!(outcome.status, RunStatus::ProposedPendingApply);
+    assert_eq!(outcome.action_count, 2);
+
+    let stored = dir
+        .path()
+        .join(".nexus/runs")
+        .join(&outcome.run_id)
+        .join("plan.json");
+    let stored: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(stored).expect("read plan")).unwrap();
+    assert_eq!(stored["steps"][1]["title"], "Update it again");
+
+    let executed = executor_server.received_requests().await.unwrap();
+    let prompts: Vec<String> = executed
+        .iter()
+        .map(|request| String::from_utf8_lossy(&request.body).into_owned())
+        .filter(|body| !body.is_empty())
+        .collect();
+    assert_eq!(prompts.len(), 2);
+    assert!(prompts[0].contains("Carry out step 1 only: Update lib"));
+    assert!(prompts[1].contains("Carry out step 2 only: Update it again"));
+
+    let events = EventLogReader::open(&outcome.log_path)
+        .expect("open log")
+        .load_all()
+        .expect("load log");
+    let kinds: Vec<&str> = events
+        .iter()
+        .map(|event| event.event_type.as_str())
+        .filter(|kind| kind.starts_with("plan."))
+        .collect();
+    assert_eq!(
+        kinds,
+        ["plan.created", "plan.step.completed", "plan.step.completed"]
+    );
+
+    let pending = pending_actions(&outcome.log_path).expect("load pending actions");
+    assert!(pending[0].id.ends_with("_s1"));
+    assert!(pending[1].id.ends_with("_s2"));
+}
+
+#[tokio::test]
+async fn test_batch_reviews_actions_before_approval() {
+    // Arrange
+    let executor_server = MockServer::start().await;
+    let executor = mock_adapter(
+        &executor_server,
+        std::fs::read_to_string(FIXTURE).expect("read fixture"),
+    )
+    .await;
+    let reviewer_server = MockServer::start().await;
+    let reviewer = mock_adapter(
+        &reviewer_server,
+        completion_stream(r#"{"confidence": 0.7, "concerns": ["no test covers it"]}"#),
+    )
+    .await;
+
+    let dir = TempDir::new().expect("create temp dir");
+    std::fs::create_dir_all(dir.path().join("src")).unwrap();
+    std::fs::write(dir.path().join("src/lib.rs"), "old\n").unwrap();
+    let batch_path = dir.path().join("tasks.yaml");
+    std::fs::write(
+        &batch_path,
+        "tasks:\n  - task: Update lib\n    files: [\"src/*.rs\"]\n",
+    )
+    .unwrap();
+    let batch = BatchFile::load(&batch_path).expect("load batch");
+
+    // Act
+    let outcomes = BatchRunner::new(&executor, dir.path())
+        .with_role_executor(AgentRole::Reviewer, &reviewer)
+        .with_action_review(true)
+        .run(&batch)
+        .await
+        .expect("run batch");
+
+    // Assert
+    let events = EventLogReader::open(&outcomes[0].log_path)
+        .expect("open log")
+        .load_all()
+        .expect("load log");
+    let reviews = nexus::review::reviews(&events);
+    assert_eq!(reviews.len(), 1);
+    let review = reviews.values().next().unwrap();
+    assert_eq!(review.confidence, 0.7);
+    assert_eq!(review.concerns, ["no test covers it"]);
+}
@@ -0,0 +1,120 @@
+use secrecy::SecretString;
+use tempfile::TempDir;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use nexus::batch::BatchRunner;
+use nexus::daemon::Daemon;
+use nexus::event_log::EventLogReader;
+use nexus::policy::PermissionGate;
+use nexus::{ApprovalRule, CodexAdapter, Decision, NexusSettings, PermissionMode, RunStatus};
+
+const FIXTURE: &str = "tests/fixtures/codex_responses/unified_diff_single.txt";
+
+#[tokio::test]
+async fn test_daemon_processes_queue_under_autopilot_gate() {
+    // Arrange
+    let server = MockServer::start().await;
+    let body = std::fs::read_to_string(FIXTURE).expect("read fixture");
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(body, "text/event-stream"))
+        .mount(&server)
+        .await;
+    let adapter = CodexAdapter::new(SecretString::from("test-key"))
+        .with_base_url(format!("{}/v1", server.uri()));
+
+    let settings = NexusSettings {
+        permission_mode: PermissionMode::Autopilot,
+        approval_rules: vec![ApprovalRule {
+            kinds: Vec::new(),
+            min_risk: None,
+            max_risk: None,
+            policy_tags: Vec::new(),
+            within_allow_paths_write: false,
+            modes: vec![PermissionMode::Autopilot],
+            decision: Decision::Allow,
+        }],
+        ..NexusSettings::default()
+    };
+    let gate = PermissionGate::from_settings(&settings).expect("gate");
+
+    let dir = TempDir::new().expect("create temp dir");
+    let git = |args: &[&str]| {
+        let output = std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir.path())
+            .output()
+            .expect("run git");
+        assert!(output.status.success(), "git {args:?} failed");
+        String::from_utf8(output.stdout).unwrap()
+    };
+    git(&["init", "--quiet", "--initial-branch=main"]);
+    std::fs::create_dir_all(dir.path().join("src")).unwrap();
+    std::fs::write(dir.path().join("src/lib.rs"), "old\n").unwrap();
+    git(&["add", "src/lib.rs"]);
+    git(&[
+        "-c",
+        "user.name=Nexus Test",
+        "-c",
+        "user.email=nexus@example.com",
+        "commit",
+        "--quiet",
+        "-m",
+        "initial",
+    ]);
+    let queue = dir.path().join("queue");
+    std::fs::create_dir_all(&queue).unwrap();
+    std::fs::write(queue.join("01-update.yaml"), "task: Update lib\n").unwrap();
+    std::fs::write(queue.join("02-broken.yaml"), "task: [unterminated\n").unwrap();
+    std::fs::write(queue.join("notes.txt"), "ignored").unwrap();
+
+    let runner = BatchRunner::new(&adapter, dir.path()).with_permission_gate(gate);
+    let daemon = Daemon::new(runner, &queue);
+
+    // Act
+    let first = daemon.run_once().await.expect("first task");
+    let second = daemon.run_once().await;
+    let third = daemon.run_once().await.expect("empty queue");
+
+    // Assert
+    let outcome = first.expect("outcome for queued task");
+    assert_eq!(outcome.status, RunStatus::Success);
+    assert_eq!(outcome.approved_count, 1);
+    let branch = format!("nexus/{}", outcome.run_id);
+    assert_eq!(git(&["branch", "--show-current"]).trim(), branch);
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("src/lib.rs")).unwrap(),
+        "new\n"
+    );
+    assert!(queue.join("done/01-update.yaml").exists());
+    let result: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(queue.join("done/01-update.result.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(result["run_id"], outcome.run_id.as_str());
+    assert_eq!(result["status"], "success");
+    assert_eq!(result["branch"], branch.as_str());
+    assert_eq!(result["apply"]["applied"][0]["written"][0], "src/lib.rs");
+
+    let events = EventLogReader::open(&outcome.log_path)
+        .unwrap()
+        .load_all()
+        .unwrap();
+    assert!(
+        events
+            .iter()
+            .any(|event| event.event_type == "permission.granted")
+    );
+    assert!(
+        events
+            .iter()
+            .any(|event| event.event_type == "tool.executed")
+    );
+
+    assert!(second.is_err());
+    assert!(queue.join("failed/02-broken.yaml").exists());
+    assert!(queue.join("failed/02-broken.result.json").exists());
+    assert!(third.is_none());
+    assert!(queue.join("notes.txt").exists());
+}
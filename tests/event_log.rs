@@ -6,7 +6,7 @@ use tempfile::TempDir;
 
 use nexus::NexusError;
 use nexus::event_log::{EventLogReader, EventLogWriter, filter_by_run, filter_by_type, helpers};
-use nexus::types::RunEvent;
+use nexus::types::{RunEvent, RunStatus};
 
 fn temp_log_path() -> (TempDir, PathBuf) {
     let dir = TempDir::new().expect("create temp dir");
@@ -105,7 +105,7 @@ fn test_writer_event_seq_and_order() {
     let events = vec![
         helpers::run_started("run_order", "order test"),
         helpers::action_proposed("run_order", "act_1", "patch", "Update file", None),
-        helpers::run_completed("run_order", "success", 1),
+        helpers::run_completed("run_order", RunStatus::Success, 1),
     ];
 
     let mut writer = EventLogWriter::open(&path).expect("open event log writer");
@@ -204,7 +204,7 @@ fn test_round_trip_with_helpers() {
             "act_01",
             vec!["src/lib.rs".to_string(), "src/main.rs".to_string()],
         ),
-        helpers::run_completed("run_round", "success", 2),
+        helpers::run_completed("run_round", RunStatus::Success, 2),
     ];
 
     let mut writer = EventLogWriter::open(&path).expect("open event log writer");
@@ -1,22 +1,31 @@
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use secrecy::SecretString;
 use tempfile::TempDir;
-use wiremock::matchers::{method, path};
+use wiremock::matchers::{body_string_contains, header, method, path};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
-use nexus::event_log::{EventLogReader, EventLogWriter};
+use nexus::cancel::CancelToken;
+use nexus::error::exit_codes;
+use nexus::event_log::payload::sha256_hex;
+use nexus::event_log::{AsyncEventLogWriter, EventLogReader, PayloadStore};
+use nexus::executor::client::CodexClient;
+use nexus::executor::{
+    ChatCompletionRequest, ChatMessage, NetworkConfig, RateLimitScheduler, RunBudget,
+};
 use nexus::{
-    ActionDetails, ActionKindTag, CodexAdapter, ExecuteOptions, Executor, NexusError, PatchFormat,
-    ProposedAction, StreamChunk,
+    ActionDetails, ActionKindTag, CodexAdapter, ExecuteOptions, Executor, NexusError, OutputFormat,
+    PatchFormat, ProposedAction, StreamChunk,
 };
 
 const API_PATH: &str = "/v1/chat/completions";
 const FIXTURE_DIR: &str = "tests/fixtures/codex_responses";
 const FIXTURE_UNIFIED_DIFF: &str = "unified_diff_single.txt";
 const FIXTURE_SEARCH_REPLACE: &str = "search_replace.txt";
+const FIXTURE_TOOL_CALL: &str = "tool_call_actions.txt";
 const TEST_API_KEY: &str = "test-key";
 const TEST_TASK: &str = "Update lib";
 
@@ -49,6 +58,8 @@ fn execute_options(preferred_format: PatchFormat) -> ExecuteOptions {
         max_tokens: None,
         temperature: None,
         preferred_format,
+        output: OutputFormat::default(),
+        template: None,
     }
 }
 
@@ -123,6 +134,37 @@ async fn test_executor_returns_actions_from_search_replace() {
     assert_patch_format(action, PatchFormat::SearchReplace);
 }
 
+#[tokio::test]
+async fn test_executor_records_base_hashes_of_context_files() {
+    let server = MockServer::start().await;
+    mount_sse_response(&server, load_fixture(FIXTURE_UNIFIED_DIFF)).await;
+    let adapter = adapter_for(&server);
+    let files = vec![
+        nexus::FileContext {
+            path: "src/lib.rs".to_string(),
+            content: "old\n".to_string(),
+            language: None,
+        },
+        nexus::FileContext {
+            path: "src/other.rs".to_string(),
+            content: "untouched\n".to_string(),
+            language: None,
+        },
+    ];
+
+    let actions = adapter
+        .execute(TEST_TASK, files, execute_options(PatchFormat::Unified))
+        .await
+        .expect("execute");
+
+    let ActionDetails::Patch(details) = &actions[0].details else {
+        panic!("expected patch details");
+    };
+    let base = details.base_file_sha256.as_ref().expect("base hashes");
+    assert_eq!(base.len(), 1);
+    assert_eq!(base["src/lib.rs"], sha256_hex(b"old\n"));
+}
+
 #[tokio::test]
 async fn test_executor_handles_rate_limit() {
     // Arrange
@@ -150,6 +192,146 @@ async fn test_executor_handles_rate_limit() {
     }
 }
 
+#[tokio::test]
+async fn test_rate_limit_without_retries_left_does_not_pause() {
+    // Arrange
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(API_PATH))
+        .respond_with(
+            ResponseTemplate::new(STATUS_TOO_MANY_REQUESTS).insert_header("Retry-After", "1"),
+        )
+        .mount(&server)
+        .await;
+    let scheduler = RateLimitScheduler::new();
+    let client = CodexClient::new(SecretString::from(TEST_API_KEY))
+        .with_base_url(format!("{}/v1", server.uri()))
+        .with_model_validation(false)
+        .with_max_retries(0)
+        .with_scheduler(scheduler.clone());
+    let request = ChatCompletionRequest {
+        model: "gpt-5.2-codex".to_string(),
+        messages: vec![ChatMessage {
+            role: "user".to_string(),
+            content: TEST_TASK.to_string(),
+        }],
+        stream: true,
+        max_tokens: None,
+        temperature: None,
+        stream_options: None,
+        response_format: None,
+        tools: None,
+        tool_choice: None,
+    };
+
+    // Act
+    let result = client.chat_completion_stream(request).await;
+
+    // Assert: nothing will retry, so other calls are not held back.
+    assert!(matches!(result, Err(NexusError::RateLimited { .. })));
+    assert!(scheduler.remaining_pause().is_none());
+}
+
+#[tokio::test]
+async fn test_shared_scheduler_pauses_other_adapters() {
+    // Arrange
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(API_PATH))
+        .respond_with(
+            ResponseTemplate::new(STATUS_TOO_MANY_REQUESTS).insert_header("Retry-After", "1"),
+        )
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mount_sse_response(&server, load_fixture(FIXTURE_UNIFIED_DIFF)).await;
+    let scheduler = RateLimitScheduler::new();
+    let first = adapter_for(&server).with_scheduler(scheduler.clone());
+    let second = adapter_for(&server).with_scheduler(scheduler);
+
+    // Act: the second call starts while the first waits out its 429.
+    let (first_result, second_elapsed) = tokio::join!(
+        first.execute(TEST_TASK, Vec::new(), execute_options(PatchFormat::Unified)),
+        async {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            let start = Instant::now();
+            second
+                .execute(TEST_TASK, Vec::new(), execute_options(PatchFormat::Unified))
+                .await
+                .expect("second execute");
+            start.elapsed()
+        }
+    );
+
+    // Assert
+    first_result.expect("first execute");
+    assert!(
+        second_elapsed >= Duration::from_millis(500),
+        "{second_elapsed:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_preflight_rejects_unknown_model_with_suggestions() {
+    // Arrange
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/models"))
+        .respond_with(
+            ResponseTemplate::new(STATUS_OK).set_body_string(
+                r#"{"object":"list","data":[{"id":"gpt-4o"},{"id":"gpt-4o-mini"}]}"#,
+            ),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path(API_PATH))
+        .respond_with(ResponseTemplate::new(STATUS_OK))
+        .expect(0)
+        .mount(&server)
+        .await;
+    let adapter = adapter_for(&server).with_model("gpt-4o-mni");
+
+    // Act
+    let result = adapter
+        .execute(TEST_TASK, Vec::new(), execute_options(PatchFormat::Unified))
+        .await;
+
+    // Assert
+    match result {
+        Err(NexusError::ModelNotAvailable { model, suggestions }) => {
+            assert_eq!(model, "gpt-4o-mni");
+            assert_eq!(suggestions[0], "gpt-4o-mini");
+        }
+        other => panic!("expected ModelNotAvailable, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_model_not_found_response_maps_to_model_not_available() {
+    // Arrange: the provider cannot list models, so the preflight is skipped.
+    let server = MockServer::start().await;
+    mount_status_response(
+        &server,
+        404,
+        r#"{"error":{"message":"The model `gpt-9` does not exist","code":"model_not_found"}}"#,
+    )
+    .await;
+    let adapter = adapter_for(&server).with_model("gpt-9");
+
+    // Act
+    let result = adapter
+        .execute(TEST_TASK, Vec::new(), execute_options(PatchFormat::Unified))
+        .await;
+
+    // Assert
+    assert!(
+        matches!(result, Err(NexusError::ModelNotAvailable { ref model, .. }) if model == "gpt-9"),
+        "{result:?}"
+    );
+}
+
 #[tokio::test]
 async fn test_executor_handles_unauthorized() {
     // Arrange
@@ -242,17 +424,17 @@ async fn test_executor_with_logging_emits_events() {
     let options = execute_options(PatchFormat::Unified);
     let dir = TempDir::new().expect("create temp dir");
     let log_path = dir.path().join("events.jsonl");
-    let mut writer = EventLogWriter::open(&log_path).expect("open event log writer");
+    let writer = AsyncEventLogWriter::open(&log_path).expect("open event log writer");
 
     // Act
     let actions = adapter
-        .execute_with_logging(TEST_TASK, &[], options, &mut writer)
+        .execute_with_logging(TEST_TASK, &[], options, &writer)
         .await
         .expect("execute with logging");
 
     // Assert
     assert_eq!(actions.len(), EXPECTED_ACTION_COUNT);
-    drop(writer);
+    writer.close().await.expect("close event log writer");
 
     let mut reader = EventLogReader::open(&log_path).expect("open event log reader");
     let events = reader.load_all().expect("load event log");
@@ -279,3 +461,463 @@ async fn test_executor_with_logging_emits_events() {
         "expected single run_id for events"
     );
 }
+
+#[tokio::test]
+async fn test_tool_calling_reads_propose_actions_arguments() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(API_PATH))
+        .and(body_string_contains("\"tool_choice\""))
+        .and(body_string_contains("\"name\":\"propose_actions\""))
+        .respond_with(
+            ResponseTemplate::new(STATUS_OK)
+                .set_body_raw(load_fixture(FIXTURE_TOOL_CALL), "text/event-stream"),
+        )
+        .mount(&server)
+        .await;
+    let adapter = adapter_for(&server).with_tool_calling(true);
+
+    let actions = adapter
+        .execute(TEST_TASK, Vec::new(), execute_options(PatchFormat::Unified))
+        .await
+        .expect("execute with tool calling");
+
+    assert_eq!(actions.len(), EXPECTED_ACTION_COUNT);
+    assert_eq!(actions[0].id, "act_tool_1");
+    assert_eq!(actions[0].kind, ActionKindTag::Patch);
+    match &actions[0].details {
+        ActionDetails::Patch(details) => {
+            assert!(details.diff.as_deref().unwrap().contains("+new"));
+        }
+        other => panic!("expected patch details, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_tool_calling_falls_back_to_text() {
+    let server = MockServer::start().await;
+    mount_sse_response(&server, load_fixture(FIXTURE_UNIFIED_DIFF)).await;
+    let adapter = adapter_for(&server).with_tool_calling(true);
+
+    let actions = adapter
+        .execute(TEST_TASK, Vec::new(), execute_options(PatchFormat::Unified))
+        .await
+        .expect("text reply is parsed");
+
+    assert_eq!(actions.len(), EXPECTED_ACTION_COUNT);
+    assert_eq!(actions[0].kind, ActionKindTag::Patch);
+}
+
+#[tokio::test]
+async fn test_json_schema_output_parses_actions_object() {
+    let server = MockServer::start().await;
+    let reply = serde_json::json!({
+        "actions": [{
+            "id": "act_json_1",
+            "summary": "Replace old with new",
+            "kind": "patch",
+            "details": {
+                "format": "unified",
+                "diff": "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1 +1 @@\n-old\n+new\n"
+            }
+        }]
+    });
+    let chunk = serde_json::json!({
+        "id": "chatcmpl-789",
+        "object": "chat.completion.chunk",
+        "created": 1700000000,
+        "model": "gpt-5.2-codex",
+        "choices": [{
+            "index": 0,
+            "delta": {"role": "assistant", "content": reply.to_string()},
+            "finish_reason": "stop"
+        }]
+    });
+    Mock::given(method("POST"))
+        .and(path(API_PATH))
+        .and(body_string_contains(
+            "\"response_format\":{\"type\":\"json_schema\"",
+        ))
+        .respond_with(ResponseTemplate::new(STATUS_OK).set_body_raw(
+            format!("data: {chunk}\n\ndata: [DONE]\n\n"),
+            "text/event-stream",
+        ))
+        .mount(&server)
+        .await;
+    let options = ExecuteOptions {
+        output: OutputFormat::JsonSchema,
+        ..execute_options(PatchFormat::Unified)
+    };
+
+    let actions = adapter_for(&server)
+        .execute(TEST_TASK, Vec::new(), options)
+        .await
+        .expect("execute with json_schema output");
+
+    assert_eq!(actions.len(), EXPECTED_ACTION_COUNT);
+    assert_eq!(actions[0].id, "act_json_1");
+}
+
+#[tokio::test]
+async fn test_context_budget_truncation_is_logged() {
+    let server = MockServer::start().await;
+    let body = load_fixture(FIXTURE_UNIFIED_DIFF);
+    Mock::given(method("POST"))
+        .and(path(API_PATH))
+        .and(body_string_contains(
+            "[truncated to fit the context budget]",
+        ))
+        .respond_with(ResponseTemplate::new(STATUS_OK).set_body_raw(body, "text/event-stream"))
+        .mount(&server)
+        .await;
+    let adapter = adapter_for(&server).with_context_budget(Some(200));
+    let files = vec![
+        nexus::FileContext {
+            path: "src/big.rs".to_string(),
+            content: "// filler line\n".repeat(200),
+            language: None,
+        },
+        nexus::FileContext {
+            path: "src/small.rs".to_string(),
+            content: "fn small() {}\n".to_string(),
+            language: None,
+        },
+    ];
+    let dir = TempDir::new().expect("create temp dir");
+    let log_path = dir.path().join("events.jsonl");
+    let writer = AsyncEventLogWriter::open(&log_path).expect("open event log writer");
+
+    adapter
+        .execute_with_logging(
+            TEST_TASK,
+            &files,
+            execute_options(PatchFormat::Unified),
+            &writer,
+        )
+        .await
+        .expect("truncated request is sent");
+    writer.close().await.expect("close event log writer");
+
+    let events = EventLogReader::open(&log_path)
+        .expect("open event log reader")
+        .load_all()
+        .expect("load event log");
+    let truncated = events
+        .iter()
+        .find(|event| event.event_type == "context.truncated")
+        .expect("expected context.truncated event");
+    let payload = truncated.payload.as_ref().expect("payload");
+    assert_eq!(payload["budget"], 200);
+    assert_eq!(payload["files"][0]["path"], "src/big.rs");
+    assert_eq!(
+        payload["files"][0]["kept_tokens"].as_u64().map(|n| n > 0),
+        Some(true)
+    );
+    assert_eq!(payload["files"][1]["path"], "src/small.rs");
+}
+
+#[tokio::test]
+async fn test_usage_is_recorded_on_executor_completed() {
+    let server = MockServer::start().await;
+    let usage_chunk = "data: {\"id\":\"chatcmpl-123\",\"object\":\"chat.completion.chunk\",\"created\":1700000000,\"model\":\"gpt-5.2-codex\",\"choices\":[],\"usage\":{\"prompt_tokens\":1000,\"completion_tokens\":200,\"total_tokens\":1200}}\n\n";
+    let body = load_fixture(FIXTURE_UNIFIED_DIFF)
+        .replace("data: [DONE]", &format!("{usage_chunk}data: [DONE]"));
+    Mock::given(method("POST"))
+        .and(path(API_PATH))
+        .and(body_string_contains("\"include_usage\":true"))
+        .respond_with(ResponseTemplate::new(STATUS_OK).set_body_raw(body, "text/event-stream"))
+        .mount(&server)
+        .await;
+    let adapter = adapter_for(&server).with_model("gpt-4o");
+    let dir = TempDir::new().expect("create temp dir");
+    let log_path = dir.path().join("events.jsonl");
+    let writer = AsyncEventLogWriter::open(&log_path).expect("open event log writer");
+
+    adapter
+        .execute_with_logging(
+            TEST_TASK,
+            &[],
+            execute_options(PatchFormat::Unified),
+            &writer,
+        )
+        .await
+        .expect("execute with logging");
+    writer.close().await.expect("close event log writer");
+
+    let cost = nexus::export::RunCost::load(&log_path).expect("load run cost");
+    assert_eq!(cost.prompt_tokens, 1000);
+    assert_eq!(cost.completion_tokens, 200);
+    assert_eq!(cost.calls[0].model.as_deref(), Some("gpt-4o"));
+    assert!((cost.cost_usd - 0.0045).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn test_budget_exceeded_aborts_run() {
+    let server = MockServer::start().await;
+    let usage_chunk = "data: {\"id\":\"chatcmpl-123\",\"object\":\"chat.completion.chunk\",\"created\":1700000000,\"model\":\"gpt-5.2-codex\",\"choices\":[],\"usage\":{\"prompt_tokens\":1000,\"completion_tokens\":200,\"total_tokens\":1200}}\n\n";
+    let body = load_fixture(FIXTURE_UNIFIED_DIFF)
+        .replace("data: [DONE]", &format!("{usage_chunk}data: [DONE]"));
+    mount_sse_response(&server, body).await;
+    let adapter = adapter_for(&server).with_budget(RunBudget {
+        max_tokens: Some(1_000),
+        max_cost_usd: None,
+    });
+    let dir = TempDir::new().expect("create temp dir");
+    let log_path = dir.path().join("events.jsonl");
+    let writer = AsyncEventLogWriter::open(&log_path).expect("open event log writer");
+
+    let err = adapter
+        .execute_with_logging(
+            TEST_TASK,
+            &[],
+            execute_options(PatchFormat::Unified),
+            &writer,
+        )
+        .await
+        .expect_err("budget is exceeded");
+    writer.close().await.expect("close event log writer");
+
+    assert!(matches!(err, NexusError::BudgetExceeded { .. }));
+    assert_eq!(u8::from(&err), exit_codes::BUDGET_EXCEEDED);
+    let events = EventLogReader::open(&log_path)
+        .expect("open event log reader")
+        .load_all()
+        .expect("load event log");
+    let exceeded = events.last().expect("events logged");
+    assert_eq!(exceeded.event_type, "run.budget_exceeded");
+    let payload = exceeded.payload.as_ref().expect("payload");
+    assert_eq!(payload["limit"], "1000 tokens");
+    assert_eq!(payload["spent"], "1200 tokens");
+    assert!(
+        events
+            .iter()
+            .all(|event| event.event_type != "action.proposed")
+    );
+}
+
+#[tokio::test]
+async fn test_requests_go_through_configured_proxy() {
+    // The mock server stands in for the proxy; the provider host does not
+    // resolve, so the request only succeeds if it is proxied.
+    let proxy = MockServer::start().await;
+    mount_sse_response(&proxy, load_fixture(FIXTURE_UNIFIED_DIFF)).await;
+    let network = NetworkConfig {
+        proxy: Some(proxy.uri()),
+        ca_cert: Some(PathBuf::from("tests/fixtures/certs/test_ca.pem")),
+    };
+    let adapter = CodexAdapter::new(SecretString::from(TEST_API_KEY))
+        .with_base_url("http://provider.nexus.invalid/v1")
+        .with_network(&network)
+        .expect("valid network config");
+
+    let actions = adapter
+        .execute(TEST_TASK, Vec::new(), execute_options(PatchFormat::Unified))
+        .await
+        .expect("execute through proxy");
+
+    assert_eq!(actions.len(), EXPECTED_ACTION_COUNT);
+}
+
+#[tokio::test]
+async fn test_extra_headers_are_sent() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(API_PATH))
+        .and(header("OpenAI-Organization", "org-123"))
+        .and(header("X-Gateway", "team-a"))
+        .respond_with(
+            ResponseTemplate::new(STATUS_OK)
+                .set_body_raw(load_fixture(FIXTURE_UNIFIED_DIFF), "text/event-stream"),
+        )
+        .mount(&server)
+        .await;
+    let headers = std::collections::BTreeMap::from([
+        ("OpenAI-Organization".to_string(), "org-123".to_string()),
+        ("X-Gateway".to_string(), "team-a".to_string()),
+    ]);
+    let adapter = adapter_for(&server)
+        .with_extra_headers(&headers)
+        .expect("valid headers");
+
+    let actions = adapter
+        .execute(TEST_TASK, Vec::new(), execute_options(PatchFormat::Unified))
+        .await
+        .expect("request carries the extra headers");
+
+    assert_eq!(actions.len(), EXPECTED_ACTION_COUNT);
+}
+
+#[tokio::test]
+async fn test_executor_cancelled_writes_run_cancelled_event() {
+    // Arrange
+    let server = MockServer::start().await;
+    let response = ResponseTemplate::new(STATUS_OK)
+        .set_body_raw(load_fixture(FIXTURE_UNIFIED_DIFF), "text/event-stream")
+        .set_delay(std::time::Duration::from_secs(30));
+    Mock::given(method("POST"))
+        .and(path(API_PATH))
+        .respond_with(response)
+        .mount(&server)
+        .await;
+    let token = CancelToken::new();
+    let adapter = adapter_for(&server).with_cancel_token(token.clone());
+    let options = execute_options(PatchFormat::Unified);
+    let dir = TempDir::new().expect("create temp dir");
+    let log_path = dir.path().join("events.jsonl");
+    let writer = AsyncEventLogWriter::open(&log_path).expect("open event log writer");
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        token.cancel();
+    });
+
+    // Act
+    let result = adapter
+        .execute_with_logging(TEST_TASK, &[], options, &writer)
+        .await;
+
+    // Assert
+    assert!(matches!(result, Err(NexusError::Cancelled)));
+    writer.close().await.expect("close event log writer");
+    let mut reader = EventLogReader::open(&log_path).expect("open event log reader");
+    let events = reader.load_all().expect("load event log");
+    let last = events.last().expect("expected logged events");
+    assert_eq!(last.event_type, "run.cancelled");
+}
+
+fn sse_content(content: &str) -> String {
+    let chunk = serde_json::json!({
+        "id": "chatcmpl-why",
+        "object": "chat.completion.chunk",
+        "created": 1700000000,
+        "model": "gpt-5.2-codex",
+        "choices": [{"index": 0, "delta": {"content": content}, "finish_reason": null}]
+    });
+    format!("data: {chunk}\n\ndata: [DONE]\n\n")
+}
+
+#[tokio::test]
+async fn test_executor_rationale_followup_fills_missing_why() {
+    // Arrange
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path(API_PATH))
+        .and(body_string_contains("mapping each action id"))
+        .respond_with(|request: &wiremock::Request| {
+            let body: serde_json::Value = request.body_json().expect("request json");
+            let listing = body["messages"][1]["content"]
+                .as_str()
+                .expect("user message");
+            let pending: serde_json::Value = serde_json::from_str(listing).expect("listing");
+            let action_id = pending[0]["action_id"].as_str().expect("action id");
+            let answer = serde_json::json!({ action_id: "keeps the API consistent" });
+            ResponseTemplate::new(STATUS_OK)
+                .set_body_raw(sse_content(&answer.to_string()), "text/event-stream")
+        })
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mount_sse_response(&server, load_fixture(FIXTURE_UNIFIED_DIFF)).await;
+    let adapter = adapter_for(&server).with_rationale_followup(true);
+
+    // Act
+    let actions = adapter
+        .execute(TEST_TASK, Vec::new(), execute_options(PatchFormat::Unified))
+        .await
+        .expect("execute");
+
+    // Assert
+    assert_eq!(actions.len(), EXPECTED_ACTION_COUNT);
+    assert_eq!(actions[0].why.as_deref(), Some("keeps the API consistent"));
+}
+
+#[tokio::test]
+async fn test_executor_with_logging_stores_transcript() {
+    // Arrange
+    let server = MockServer::start().await;
+    mount_sse_response(&server, load_fixture(FIXTURE_UNIFIED_DIFF)).await;
+    let adapter = adapter_for(&server);
+    let dir = TempDir::new().expect("create temp dir");
+    let log_path = dir.path().join("events.jsonl");
+    let writer = AsyncEventLogWriter::open(&log_path).expect("open event log writer");
+
+    // Act
+    adapter
+        .execute_with_logging(
+            TEST_TASK,
+            &[],
+            execute_options(PatchFormat::Unified),
+            &writer,
+        )
+        .await
+        .expect("execute with logging");
+    writer.close().await.expect("close event log writer");
+
+    // Assert
+    let mut reader = EventLogReader::open(&log_path).expect("open event log reader");
+    let events = reader.load_all().expect("load event log");
+    let read_ref = |event_type: &str| {
+        let event = events
+            .iter()
+            .find(|event| event.event_type == event_type)
+            .expect("event present");
+        let payload_ref = event.payload_ref.as_ref().expect("payload_ref set");
+        let path = PayloadStore::resolve(dir.path(), payload_ref);
+        let bytes = std::fs::read(path).expect("read artifact");
+        serde_json::from_slice::<serde_json::Value>(&bytes).expect("artifact json")
+    };
+
+    let request = read_ref("executor.started");
+    assert_eq!(request["messages"][1]["role"], "user");
+    assert!(
+        request["messages"][1]["content"]
+            .as_str()
+            .unwrap()
+            .contains(TEST_TASK)
+    );
+
+    let transcript = read_ref("executor.completed");
+    assert_eq!(transcript.as_array().map(Vec::len), Some(1));
+    assert!(
+        transcript[0]["response"]
+            .as_str()
+            .unwrap()
+            .contains("--- a/src/lib.rs")
+    );
+    assert_eq!(transcript[0]["request"], request);
+}
+
+#[tokio::test]
+async fn test_executor_rejects_prompt_exceeding_context_window() {
+    // Arrange
+    let server = MockServer::start().await;
+    mount_sse_response(&server, load_fixture(FIXTURE_UNIFIED_DIFF)).await;
+    let adapter = adapter_for(&server).with_context_window(1_000);
+    let files = vec![
+        nexus::FileContext {
+            path: "src/big.rs".to_string(),
+            content: "x".repeat(8_000),
+            language: None,
+        },
+        nexus::FileContext {
+            path: "src/small.rs".to_string(),
+            content: "fn main() {}\n".to_string(),
+            language: None,
+        },
+    ];
+
+    // Act
+    let result = adapter
+        .execute(TEST_TASK, files, execute_options(PatchFormat::Unified))
+        .await;
+
+    // Assert
+    let err = result.expect_err("prompt should not fit");
+    assert!(matches!(err, NexusError::PromptTooLarge { .. }));
+    let message = err.to_string();
+    assert!(message.contains("src/big.rs (~2000), src/small.rs"));
+    assert!(
+        message.ends_with("Narrow the file set (e.g. with --files) and retry"),
+        "{message}"
+    );
+    assert!(server.received_requests().await.unwrap().is_empty());
+}